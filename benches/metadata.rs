@@ -0,0 +1,158 @@
+//! Benchmarks of the read, write and batch paths.
+//!
+//! Run with `cargo bench`; criterion keeps the previous results under
+//! `target/criterion` and reports the change against them, so running the suite on
+//! a release tag and again on a branch shows regressions between the two. Filter
+//! with `cargo bench -- read/` to run one group.
+//!
+//! Documents are generated in memory, so no fixtures are needed:
+//!
+//! * `large`: 2,000 pages with a content stream each and a short Info dictionary,
+//!   written with a classic cross-reference table as most producers do.
+//! * `large_xref_stream`: the same document with a cross-reference stream, which
+//!   readers cannot index without parsing it.
+//! * `many_keys`: a single page with 500 custom Info entries.
+
+use criterion::{BatchSize, Criterion, black_box, criterion_group, criterion_main};
+use lopdf::xref::XrefType;
+use lopdf::{Document, Object, Stream, dictionary};
+use pdf_metadata::batch::{self, BatchOptions};
+use pdf_metadata::{
+    MetadataEditor, SaveMode, SetOptions, get_metadata, get_metadata_ref, get_pdf_metadata, get_pdf_metadata_values,
+    set_pdf_metadata, set_pdf_metadata_with_options, update_metadata_in_place,
+};
+use std::fs;
+use std::path::PathBuf;
+
+const LARGE_PAGES: usize = 2_000;
+const MANY_KEYS: usize = 500;
+const BATCH_FILES: usize = 32;
+
+/// A document with `pages` pages and `keys` custom Info entries besides Title and Author.
+fn build_pdf(pages: usize, keys: usize, xref: XrefType) -> Vec<u8> {
+    let mut doc = Document::with_version("1.7");
+    let pages_id = doc.new_object_id();
+    let kids: Vec<Object> = (0..pages)
+        .map(|page| {
+            let content = format!("BT /F1 12 Tf 72 720 Td (Page {} of the benchmark document) Tj ET", page + 1);
+            let content_id = doc.add_object(Stream::new(dictionary! {}, content.into_bytes()));
+            doc.add_object(dictionary! {
+                "Type" => "Page",
+                "Parent" => pages_id,
+                "MediaBox" => vec![0.into(), 0.into(), 612.into(), 792.into()],
+                "Contents" => content_id,
+            })
+            .into()
+        })
+        .collect();
+    doc.objects.insert(pages_id, dictionary! { "Type" => "Pages", "Count" => pages as i64, "Kids" => kids }.into());
+    let catalog_id = doc.add_object(dictionary! { "Type" => "Catalog", "Pages" => pages_id });
+    doc.trailer.set("Root", catalog_id);
+
+    let mut info = dictionary! {
+        "Title" => Object::string_literal("Benchmark document"),
+        "Author" => Object::string_literal("pdf_metadata"),
+        "CreationDate" => Object::string_literal("D:20240102030405Z"),
+    };
+    for key in 0..keys {
+        info.set(format!("Custom{}", key), Object::string_literal(format!("Value number {}", key)));
+    }
+    let info_id = doc.add_object(info);
+    doc.trailer.set("Info", info_id);
+
+    doc.reference_table.cross_reference_type = xref;
+    let mut bytes = Vec::new();
+    doc.save_to(&mut bytes).expect("the benchmark document is written");
+    bytes
+}
+
+/// A fresh directory under the system temporary directory.
+fn bench_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join("pdf_metadata_bench").join(name);
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).expect("the benchmark directory is created");
+    dir
+}
+
+fn read(c: &mut Criterion) {
+    let large = build_pdf(LARGE_PAGES, 0, XrefType::CrossReferenceTable);
+    let large_stream = build_pdf(LARGE_PAGES, 0, XrefType::CrossReferenceStream);
+    let many_keys = build_pdf(1, MANY_KEYS, XrefType::CrossReferenceTable);
+    let dir = bench_dir("read");
+    let large_file = dir.join("large.pdf");
+    fs::write(&large_file, &large).expect("the benchmark file is written");
+
+    let mut group = c.benchmark_group("read");
+    group.bench_function("get_pdf_metadata/large", |b| b.iter(|| get_pdf_metadata(black_box(&large))));
+    group.bench_function("get_pdf_metadata/large_xref_stream", |b| {
+        b.iter(|| get_pdf_metadata(black_box(&large_stream)))
+    });
+    group.bench_function("get_pdf_metadata/many_keys", |b| b.iter(|| get_pdf_metadata(black_box(&many_keys))));
+    group.bench_function("get_pdf_metadata_values/many_keys", |b| {
+        b.iter(|| get_pdf_metadata_values(black_box(&many_keys)))
+    });
+    group.bench_function("get_metadata/large_file", |b| b.iter(|| get_metadata(black_box(&large_file))));
+
+    let loaded = Document::load_mem(&many_keys).expect("the benchmark document loads");
+    group.bench_function("get_metadata_ref/many_keys", |b| b.iter(|| get_metadata_ref(black_box(&loaded)).count()));
+    group.finish();
+}
+
+fn write(c: &mut Criterion) {
+    let large = build_pdf(LARGE_PAGES, 0, XrefType::CrossReferenceTable);
+    let many_keys = build_pdf(1, MANY_KEYS, XrefType::CrossReferenceTable);
+    let preserve = SetOptions { save_mode: SaveMode::Preserve, ..SetOptions::default() };
+
+    let mut group = c.benchmark_group("write");
+    group.sample_size(20);
+    group.bench_function("set_pdf_metadata/large", |b| {
+        b.iter(|| set_pdf_metadata(black_box(&large), "Subject", "Benchmark"))
+    });
+    group.bench_function("set_pdf_metadata_with_options/large_preserve", |b| {
+        b.iter(|| set_pdf_metadata_with_options(black_box(&large), "Subject", "Benchmark", &preserve))
+    });
+    group.bench_function("set_pdf_metadata/many_keys", |b| {
+        b.iter(|| set_pdf_metadata(black_box(&many_keys), "Custom250", "Changed"))
+    });
+
+    let dir = bench_dir("write");
+    let file = dir.join("many_keys.pdf");
+    group.bench_function("editor_50_keys/many_keys", |b| {
+        b.iter_batched(
+            || fs::write(&file, &many_keys).expect("the benchmark file is written"),
+            |()| {
+                let editor = MetadataEditor::open(&file).expect("the benchmark file opens");
+                let editor = (0..50).fold(editor, |editor, key| editor.set(&format!("Custom{}", key * 10), "Changed"));
+                editor.save_in_place().expect("the benchmark file is saved")
+            },
+            BatchSize::PerIteration,
+        )
+    });
+    group.finish();
+}
+
+fn batch(c: &mut Criterion) {
+    let small = build_pdf(10, 20, XrefType::CrossReferenceTable);
+    let dir = bench_dir("batch");
+    let files: Vec<PathBuf> = (0..BATCH_FILES)
+        .map(|index| {
+            let path = dir.join(format!("doc_{}.pdf", index));
+            fs::write(&path, &small).expect("the benchmark file is written");
+            path
+        })
+        .collect();
+    let options = BatchOptions::default();
+
+    let mut group = c.benchmark_group("batch");
+    group.sample_size(20);
+    group.bench_function("get_metadata/32_files", |b| {
+        b.iter(|| batch::run(black_box(&files), &options, |path| get_metadata(path)))
+    });
+    group.bench_function("update_in_place/32_files", |b| {
+        b.iter(|| batch::run(black_box(&files), &options, |path| update_metadata_in_place(path, "Subject", "Batch")))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, read, write, batch);
+criterion_main!(benches);
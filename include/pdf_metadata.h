@@ -0,0 +1,108 @@
+#ifndef PDF_METADATA_H
+#define PDF_METADATA_H
+
+/* Generated by cbindgen from src/ffi.rs; do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Outcome of an FFI call.
+typedef enum PdfMetadataStatus {
+  PDF_METADATA_STATUS_OK = 0,
+  // A pointer was null, or a string was not valid UTF-8.
+  PDF_METADATA_STATUS_INVALID_ARGUMENT = 1,
+  // The requested key is not in the Info dictionary.
+  PDF_METADATA_STATUS_NOT_FOUND = 2,
+  // The file could not be read or written.
+  PDF_METADATA_STATUS_IO = 3,
+  // The input is not a valid PDF.
+  PDF_METADATA_STATUS_PARSE = 4,
+  // The document is encrypted and needs a password.
+  PDF_METADATA_STATUS_ENCRYPTED = 5,
+  // The document has no Info dictionary.
+  PDF_METADATA_STATUS_MISSING_INFO_DICT = 6,
+  // Any other error; see `pdf_metadata_last_error`.
+  PDF_METADATA_STATUS_OTHER = 98,
+  // The library panicked. This is a bug.
+  PDF_METADATA_STATUS_PANIC = 99,
+} PdfMetadataStatus;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Returns the message of the last error on the calling thread, or null if the last
+// call succeeded.
+//
+// The string stays valid until the next call into this library on the same thread
+// and must not be freed.
+const char *pdf_metadata_last_error(void);
+
+// Reads the value of `key` from the PDF file at `path`.
+//
+// On success `*out_value` receives a new string; with `NotFound` it is set to null.
+//
+// # Safety
+//
+// `path` and `key` must be valid NUL-terminated strings and `out_value` a valid
+// pointer to writable storage.
+enum PdfMetadataStatus pdf_metadata_get(const char *path, const char *key, char **out_value);
+
+// Sets `key` to `value` in the PDF file at `path`, replacing the file in place.
+// `ModDate` is refreshed.
+//
+// # Safety
+//
+// `path`, `key` and `value` must be valid NUL-terminated strings.
+enum PdfMetadataStatus pdf_metadata_set(const char *path, const char *key, const char *value);
+
+// Like [`pdf_metadata_get`], for a PDF held in memory.
+//
+// # Safety
+//
+// `data` must point to `len` readable bytes, `key` must be a valid NUL-terminated
+// string and `out_value` a valid pointer to writable storage.
+enum PdfMetadataStatus pdf_metadata_get_buffer(const uint8_t *data,
+                                               size_t len,
+                                               const char *key,
+                                               char **out_value);
+
+// Like [`pdf_metadata_set`], for a PDF held in memory: the input is left unchanged
+// and the updated PDF is returned in `*out_data` and `*out_len`.
+//
+// # Safety
+//
+// `data` must point to `len` readable bytes, `key` and `value` must be valid
+// NUL-terminated strings, and `out_data` and `out_len` valid pointers to writable
+// storage.
+enum PdfMetadataStatus pdf_metadata_set_buffer(const uint8_t *data,
+                                               size_t len,
+                                               const char *key,
+                                               const char *value,
+                                               uint8_t **out_data,
+                                               size_t *out_len);
+
+// Frees a string returned by this library. Null is ignored.
+//
+// # Safety
+//
+// `value` must be null or a string returned through an out-parameter of this
+// library that was not freed yet.
+void pdf_metadata_string_free(char *value);
+
+// Frees a buffer returned by [`pdf_metadata_set_buffer`]. Null is ignored.
+//
+// # Safety
+//
+// `data` must be null or a buffer returned by this library, with the length
+// returned alongside it, that was not freed yet.
+void pdf_metadata_buffer_free(uint8_t *data, size_t len);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* PDF_METADATA_H */
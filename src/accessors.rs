@@ -0,0 +1,276 @@
+//! Shortcuts for the standard Info entries.
+//!
+//! Each getter reads one entry with [`get_metadata_as`], so common lookups do not
+//! need to scan the `(key, value)` vector. The setters update the file in place
+//! like [`update_metadata_in_place`], refreshing `ModDate`. [`add_keyword`] and
+//! [`remove_keyword`] edit `Keywords` as a list.
+
+use crate::{
+    Result, SetOptions, format_pdf_date, get_metadata_as, remove_metadata_in_place, update_metadata_in_place,
+    update_metadata_in_place_with_options,
+};
+use chrono::{DateTime, FixedOffset};
+use std::path::Path;
+
+/// Returns the document's `Title`, or `None` if it has none.
+///
+/// # Example
+///
+/// ```no_run
+/// use pdf_metadata::{get_title, set_title};
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let path = "path/to/document.pdf";
+///     if get_title(path)?.is_none() {
+///         set_title(path, "Annual Report")?;
+///     }
+///     Ok(())
+/// }
+/// ```
+pub fn get_title<P: AsRef<Path>>(file_path: P) -> Result<Option<String>> {
+    let file_path = file_path.as_ref();
+    get_metadata_as(file_path, "Title")
+}
+
+/// Returns the document's `Author`, or `None` if it has none.
+pub fn get_author<P: AsRef<Path>>(file_path: P) -> Result<Option<String>> {
+    let file_path = file_path.as_ref();
+    get_metadata_as(file_path, "Author")
+}
+
+/// Returns the document's `Subject`, or `None` if it has none.
+pub fn get_subject<P: AsRef<Path>>(file_path: P) -> Result<Option<String>> {
+    let file_path = file_path.as_ref();
+    get_metadata_as(file_path, "Subject")
+}
+
+/// Returns the document's `Keywords` split on commas and semicolons, trimmed and
+/// without empty items. A document without `Keywords` gives an empty list.
+pub fn get_keywords_list<P: AsRef<Path>>(file_path: P) -> Result<Vec<String>> {
+    let file_path = file_path.as_ref();
+    Ok(get_metadata_as(file_path, "Keywords")?.unwrap_or_default())
+}
+
+/// Returns the distinct keywords of the document.
+///
+/// Like [`get_keywords_list`], but repeated keywords are dropped, comparing them
+/// case-insensitively and keeping the first spelling.
+pub fn get_keywords<P: AsRef<Path>>(file_path: P) -> Result<Vec<String>> {
+    let file_path = file_path.as_ref();
+    let mut keywords = get_keywords_list(file_path)?;
+    dedup_keywords(&mut keywords);
+    Ok(keywords)
+}
+
+/// Adds `keyword` to the document's `Keywords`, in place.
+///
+/// The list is rewritten with `", "` separators and without duplicates. A keyword
+/// containing commas or semicolons is added as several keywords.
+///
+/// # Returns
+///
+/// * `Ok(true)` if a keyword was added.
+/// * `Ok(false)` if every keyword was already present (ignoring case); the file is
+///   left untouched.
+/// * `Err(Error)`: If the file cannot be read or written.
+///
+/// # Example
+///
+/// ```no_run
+/// use pdf_metadata::{add_keyword, get_keywords, remove_keyword};
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let path = "path/to/document.pdf";
+///     add_keyword(path, "tax")?;
+///     remove_keyword(path, "draft")?;
+///     println!("{:?}", get_keywords(path)?);
+///     Ok(())
+/// }
+/// ```
+pub fn add_keyword<P: AsRef<Path>>(file_path: P, keyword: &str) -> Result<bool> {
+    let file_path = file_path.as_ref();
+    let mut keywords = get_keywords(file_path)?;
+    let before = keywords.len();
+    keywords.extend(split_keywords(keyword));
+    dedup_keywords(&mut keywords);
+    if keywords.len() == before {
+        return Ok(false);
+    }
+    set_keywords_list(file_path, &keywords)?;
+    Ok(true)
+}
+
+/// Removes `keyword` from the document's `Keywords`, ignoring case, in place.
+///
+/// The remaining list is rewritten with `", "` separators and without duplicates;
+/// `Keywords` is removed when no keyword is left.
+///
+/// # Returns
+///
+/// * `Ok(true)` if the keyword was found and removed.
+/// * `Ok(false)` if it was not present; the file is left untouched.
+/// * `Err(Error)`: If the file cannot be read or written.
+pub fn remove_keyword<P: AsRef<Path>>(file_path: P, keyword: &str) -> Result<bool> {
+    let file_path = file_path.as_ref();
+    let mut keywords = get_keywords(file_path)?;
+    let before = keywords.len();
+    let keyword = keyword.trim().to_lowercase();
+    keywords.retain(|k| k.to_lowercase() != keyword);
+    if keywords.len() == before {
+        return Ok(false);
+    }
+    if keywords.is_empty() {
+        remove_metadata_in_place(file_path, "Keywords")?;
+    } else {
+        set_keywords_list(file_path, &keywords)?;
+    }
+    Ok(true)
+}
+
+/// Splits a keywords string as [`get_keywords_list`] does.
+fn split_keywords(keywords: &str) -> impl Iterator<Item = String> + '_ {
+    keywords.split([',', ';']).map(str::trim).filter(|k| !k.is_empty()).map(str::to_string)
+}
+
+/// Drops keywords equal to an earlier one, ignoring case.
+fn dedup_keywords(keywords: &mut Vec<String>) {
+    let mut seen = std::collections::HashSet::new();
+    keywords.retain(|k| seen.insert(k.to_lowercase()));
+}
+
+/// Returns the document's `Creator`, or `None` if it has none.
+pub fn get_creator<P: AsRef<Path>>(file_path: P) -> Result<Option<String>> {
+    let file_path = file_path.as_ref();
+    get_metadata_as(file_path, "Creator")
+}
+
+/// Returns the document's `Producer`, or `None` if it has none.
+pub fn get_producer<P: AsRef<Path>>(file_path: P) -> Result<Option<String>> {
+    let file_path = file_path.as_ref();
+    get_metadata_as(file_path, "Producer")
+}
+
+/// Returns the document's `CreationDate`, or `None` if it has none.
+///
+/// Fails with `Error::Conversion` if the entry is not a valid date.
+pub fn get_creation_date<P: AsRef<Path>>(file_path: P) -> Result<Option<DateTime<FixedOffset>>> {
+    let file_path = file_path.as_ref();
+    get_metadata_as(file_path, "CreationDate")
+}
+
+/// Returns the document's `ModDate`, or `None` if it has none.
+///
+/// Fails with `Error::Conversion` if the entry is not a valid date.
+pub fn get_mod_date<P: AsRef<Path>>(file_path: P) -> Result<Option<DateTime<FixedOffset>>> {
+    let file_path = file_path.as_ref();
+    get_metadata_as(file_path, "ModDate")
+}
+
+/// Sets the document's `Title` in place.
+pub fn set_title<P: AsRef<Path>>(file_path: P, title: &str) -> Result<()> {
+    let file_path = file_path.as_ref();
+    update_metadata_in_place(file_path, "Title", title)
+}
+
+/// Sets the document's `Author` in place.
+pub fn set_author<P: AsRef<Path>>(file_path: P, author: &str) -> Result<()> {
+    let file_path = file_path.as_ref();
+    update_metadata_in_place(file_path, "Author", author)
+}
+
+/// Sets the document's `Subject` in place.
+pub fn set_subject<P: AsRef<Path>>(file_path: P, subject: &str) -> Result<()> {
+    let file_path = file_path.as_ref();
+    update_metadata_in_place(file_path, "Subject", subject)
+}
+
+/// Sets the document's `Keywords` in place, joining `keywords` with `", "`.
+pub fn set_keywords_list<P: AsRef<Path>, S: AsRef<str>>(file_path: P, keywords: &[S]) -> Result<()> {
+    let file_path = file_path.as_ref();
+    let joined = keywords.iter().map(AsRef::as_ref).collect::<Vec<_>>().join(", ");
+    update_metadata_in_place(file_path, "Keywords", &joined)
+}
+
+/// Sets the document's `Creator` in place.
+pub fn set_creator<P: AsRef<Path>>(file_path: P, creator: &str) -> Result<()> {
+    let file_path = file_path.as_ref();
+    update_metadata_in_place(file_path, "Creator", creator)
+}
+
+/// Sets the document's `Producer` in place.
+pub fn set_producer<P: AsRef<Path>>(file_path: P, producer: &str) -> Result<()> {
+    let file_path = file_path.as_ref();
+    update_metadata_in_place(file_path, "Producer", producer)
+}
+
+/// Sets the document's `CreationDate` in place, formatted as a PDF date.
+pub fn set_creation_date<P: AsRef<Path>>(file_path: P, date: &DateTime<FixedOffset>) -> Result<()> {
+    let file_path = file_path.as_ref();
+    update_metadata_in_place(file_path, "CreationDate", &format_pdf_date(date))
+}
+
+/// Sets the document's `ModDate` in place to `date` instead of the current time.
+pub fn set_mod_date<P: AsRef<Path>>(file_path: P, date: &DateTime<FixedOffset>) -> Result<()> {
+    let file_path = file_path.as_ref();
+    let options = SetOptions { update_mod_date: false, ..SetOptions::default() };
+    update_metadata_in_place_with_options(file_path, "ModDate", &format_pdf_date(date), &options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_pdf_date;
+    use crate::test_support::{create_minimal_test_pdf, setup_unique_test_dir};
+    use std::fs;
+
+    #[test]
+    fn test_accessors_round_trip() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("accessors_round_trip");
+        let file = test_dir.join("doc.pdf");
+        create_minimal_test_pdf(&file)?;
+        let file_str = file.to_str().unwrap();
+
+        assert_eq!(get_title(file_str)?, None);
+        assert!(get_keywords_list(file_str)?.is_empty());
+
+        set_title(file_str, "Relatório")?;
+        set_author(file_str, "Ana")?;
+        set_keywords_list(file_str, &["tax", "2024"])?;
+        let created = parse_pdf_date("D:20240102030405+01'00'")?;
+        set_creation_date(file_str, &created)?;
+        let modified = parse_pdf_date("D:20240506070809Z")?;
+        set_mod_date(file_str, &modified)?;
+
+        assert_eq!(get_title(file_str)?.as_deref(), Some("Relatório"));
+        assert_eq!(get_author(file_str)?.as_deref(), Some("Ana"));
+        assert_eq!(get_keywords_list(file_str)?, ["tax", "2024"]);
+        assert_eq!(get_creation_date(file_str)?, Some(created));
+        assert_eq!(get_mod_date(file_str)?, Some(modified));
+        assert_eq!(get_producer(file_str)?, None);
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_keywords_are_edited_as_a_list() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("accessors_keywords");
+        let file = test_dir.join("doc.pdf");
+        create_minimal_test_pdf(&file)?;
+        let file_str = file.to_str().unwrap();
+        update_metadata_in_place(file_str, "Keywords", "Tax;  audit,tax ,, 2024")?;
+
+        assert_eq!(get_keywords(file_str)?, ["Tax", "audit", "2024"]);
+        assert!(!add_keyword(file_str, "TAX")?);
+        assert!(add_keyword(file_str, "Relatório; audit")?);
+        assert_eq!(get_metadata_as::<String>(file_str, "Keywords")?.as_deref(), Some("Tax, audit, 2024, Relatório"));
+
+        assert!(remove_keyword(file_str, "relatório")?);
+        assert!(!remove_keyword(file_str, "missing")?);
+        assert!(remove_keyword(file_str, "tax")? && remove_keyword(file_str, "audit")? && remove_keyword(file_str, "2024")?);
+        assert_eq!(get_metadata_as::<String>(file_str, "Keywords")?, None);
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,96 @@
+//! Metadata of the PDFs inside a ZIP archive.
+//!
+//! Only available with the `archives` feature.
+
+use crate::{Error, Result, get_pdf_metadata};
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// The name of a PDF entry in a ZIP archive and its Info entries, as returned by
+/// [`get_metadata_from_zip`].
+pub type ZipEntryMetadata = (String, Vec<(String, String)>);
+
+/// Retrieves the metadata entries of every PDF stored in the ZIP archive at `zip_path`.
+///
+/// Entries are PDFs when their name ends in `.pdf` (in any case); they are read in
+/// archive order, including those in subfolders, without extracting anything to disk.
+/// Other entries are ignored.
+///
+/// # Returns
+///
+/// * `Ok(Vec<ZipEntryMetadata>)`: The name of each PDF entry with the
+///   entries [`get_metadata`](crate::get_metadata) would return for it.
+/// * `Err(Error::Archive)`: If the file is not a readable ZIP archive, or an entry
+///   cannot be decompressed.
+/// * `Err(Error)`: If a contained PDF cannot be parsed or is encrypted.
+///
+/// # Example
+///
+/// ```no_run
+/// use pdf_metadata::get_metadata_from_zip;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     for (entry, metadata) in get_metadata_from_zip("drop/batch-0423.zip")? {
+///         println!("{}: {} entries", entry, metadata.len());
+///     }
+///     Ok(())
+/// }
+/// ```
+pub fn get_metadata_from_zip<P: AsRef<Path>>(zip_path: P) -> Result<Vec<ZipEntryMetadata>> {
+    let mut archive = zip::ZipArchive::new(File::open(zip_path)?).map_err(|e| Error::Archive(e.to_string()))?;
+    let mut documents = Vec::new();
+    for index in 0..archive.len() {
+        let mut entry = archive.by_index(index).map_err(|e| Error::Archive(e.to_string()))?;
+        if !entry.is_file() || !entry.name().to_ascii_lowercase().ends_with(".pdf") {
+            continue;
+        }
+        let name = entry.name().to_string();
+        let mut content = Vec::new();
+        entry.read_to_end(&mut content).map_err(|e| Error::Archive(format!("{}: {}", name, e)))?;
+        documents.push((name, get_pdf_metadata(&content)?));
+    }
+    Ok(documents)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{create_minimal_test_pdf, setup_unique_test_dir};
+    use crate::update_metadata_in_place;
+    use std::fs;
+    use std::io::Write;
+
+    #[test]
+    fn test_get_metadata_from_zip() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("zip_metadata");
+        let pdf = test_dir.join("doc.pdf");
+        create_minimal_test_pdf(&pdf)?;
+        let untitled = fs::read(&pdf)?;
+        update_metadata_in_place(pdf.to_str().unwrap(), "Title", "Relatório")?;
+        let titled = fs::read(&pdf)?;
+
+        let zip_path = test_dir.join("drop.zip");
+        let mut writer = zip::ZipWriter::new(File::create(&zip_path)?);
+        let options = zip::write::SimpleFileOptions::default();
+        writer.start_file("a/report.PDF", options)?;
+        writer.write_all(&titled)?;
+        writer.add_directory("b.pdf/", options)?;
+        writer.start_file("notes.txt", options)?;
+        writer.write_all(b"not a pdf")?;
+        writer.start_file("b.pdf/blank.pdf", options)?;
+        writer.write_all(&untitled)?;
+        writer.finish()?;
+
+        let documents = get_metadata_from_zip(&zip_path)?;
+        let names: Vec<&str> = documents.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["a/report.PDF", "b.pdf/blank.pdf"]);
+        assert!(documents[0].1.contains(&("Title".to_string(), "Relatório".to_string())));
+        assert!(documents[1].1.is_empty());
+
+        assert!(matches!(get_metadata_from_zip(&pdf), Err(Error::Archive(_))));
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,119 @@
+//! Async variants of the file and in-memory functions (`async` feature).
+//!
+//! Parsing and writing PDFs is blocking work, so each function runs the matching
+//! synchronous implementation on tokio's blocking thread pool via
+//! [`tokio::task::spawn_blocking`]. Behavior and errors are identical to the
+//! blocking versions; only the calling convention differs. A tokio runtime must be
+//! running when these functions are awaited.
+
+use crate::{Error, Result};
+use std::path::PathBuf;
+
+/// Runs `operation` on the blocking pool and waits for its result.
+///
+/// A panic inside `operation` is propagated to the awaiting task, as it would be
+/// for a direct call.
+pub(crate) async fn run_blocking<T, F>(operation: F) -> Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T> + Send + 'static,
+{
+    match tokio::task::spawn_blocking(operation).await {
+        Ok(result) => result,
+        Err(join_error) if join_error.is_panic() => std::panic::resume_unwind(join_error.into_panic()),
+        Err(join_error) => Err(Error::Io(std::io::Error::other(join_error))),
+    }
+}
+
+/// Async variant of [`get_metadata`](crate::get_metadata).
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn handler() -> Result<(), pdf_metadata::Error> {
+/// let metadata = pdf_metadata::get_metadata_async("path/to/document.pdf").await?;
+/// # Ok(())
+/// # }
+/// ```
+pub async fn get_metadata_async(file_path: impl Into<PathBuf>) -> Result<Vec<(String, String)>> {
+    let file_path = file_path.into();
+    run_blocking(move || crate::get_metadata(&file_path)).await
+}
+
+/// Async variant of [`set_metadata`](crate::set_metadata).
+pub async fn set_metadata_async(
+    file_path: impl Into<PathBuf>,
+    output_path: impl Into<PathBuf>,
+    metadata_key: impl Into<String>,
+    metadata_value: impl Into<String>,
+) -> Result<()> {
+    let (file_path, output_path) = (file_path.into(), output_path.into());
+    let (key, value) = (metadata_key.into(), metadata_value.into());
+    run_blocking(move || crate::set_metadata(&file_path, &output_path, &key, &value)).await
+}
+
+/// Async variant of [`update_metadata_in_place`](crate::update_metadata_in_place).
+pub async fn update_metadata_in_place_async(
+    file_path: impl Into<PathBuf>,
+    metadata_key: impl Into<String>,
+    metadata_value: impl Into<String>,
+) -> Result<()> {
+    let file_path = file_path.into();
+    let (key, value) = (metadata_key.into(), metadata_value.into());
+    run_blocking(move || crate::update_metadata_in_place(&file_path, &key, &value)).await
+}
+
+/// Async variant of [`get_pdf_metadata`](crate::get_pdf_metadata).
+///
+/// Takes ownership of the bytes so they can be moved to the blocking pool without copying.
+pub async fn get_pdf_metadata_async(pdf_content: Vec<u8>) -> Result<Vec<(String, String)>> {
+    run_blocking(move || crate::get_pdf_metadata(&pdf_content)).await
+}
+
+/// Async variant of [`set_pdf_metadata`](crate::set_pdf_metadata).
+pub async fn set_pdf_metadata_async(
+    pdf_content: Vec<u8>,
+    metadata_key: impl Into<String>,
+    metadata_value: impl Into<String>,
+) -> Result<Vec<u8>> {
+    let (key, value) = (metadata_key.into(), metadata_value.into());
+    run_blocking(move || crate::set_pdf_metadata(&pdf_content, &key, &value)).await
+}
+
+/// Async variant of [`update_pdf_metadata_in_place`](crate::update_pdf_metadata_in_place).
+pub async fn update_pdf_metadata_in_place_async(
+    pdf_content: Vec<u8>,
+    metadata_key: impl Into<String>,
+    metadata_value: impl Into<String>,
+) -> Result<Vec<u8>> {
+    let (key, value) = (metadata_key.into(), metadata_value.into());
+    run_blocking(move || crate::update_pdf_metadata_in_place(&pdf_content, &key, &value)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{create_minimal_test_pdf, setup_unique_test_dir};
+    use std::fs;
+
+    #[tokio::test]
+    async fn test_async_round_trip() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("async_round_trip");
+        let file = test_dir.join("async.pdf");
+        create_minimal_test_pdf(&file)?;
+        let file_str = file.to_str().unwrap().to_string();
+
+        update_metadata_in_place_async(file_str.clone(), "Title", "Async").await?;
+        let metadata = get_metadata_async(file_str.clone()).await?;
+        assert!(metadata.iter().any(|(k, v)| k == "Title" && v == "Async"));
+
+        let bytes = set_pdf_metadata_async(fs::read(&file)?, "Author", "Tokio").await?;
+        let metadata = get_pdf_metadata_async(bytes).await?;
+        assert!(metadata.iter().any(|(k, v)| k == "Author" && v == "Tokio"));
+
+        assert!(matches!(get_metadata_async("missing_async.pdf").await, Err(Error::Io(_))));
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+}
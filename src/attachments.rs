@@ -0,0 +1,458 @@
+//! Embedded files (attachments) listed in the document's `EmbeddedFiles` name tree.
+
+#[cfg(feature = "std-fs")]
+use crate::{
+    Error, Result, decode_string_object, format_pdf_date, incremental, info_string_object, load_document, mark_modified,
+    write_file,
+};
+#[cfg(feature = "std-fs")]
+use chrono::{DateTime, Local};
+use lopdf::{Dictionary, Document, Object};
+#[cfg(feature = "std-fs")]
+use lopdf::{ObjectId, Stream, dictionary};
+#[cfg(feature = "std-fs")]
+use std::collections::HashSet;
+#[cfg(feature = "std-fs")]
+use std::fs;
+#[cfg(feature = "std-fs")]
+use std::path::Path;
+
+/// Upper bound on the depth of the name tree; deeper trees are treated as damaged.
+#[cfg(feature = "std-fs")]
+const MAX_TREE_DEPTH: usize = 32;
+
+/// An embedded file, as returned by [`list_attachments`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AttachmentInfo {
+    /// The key of the attachment in the name tree, unique within the document.
+    pub name: String,
+    /// The file name from the file specification (`/UF`, or `/F` when there is no `/UF`).
+    pub file_name: Option<String>,
+    /// The description shown by viewers (`/Desc`).
+    pub description: Option<String>,
+    /// The MIME type (`/Subtype` of the embedded stream), e.g. `text/xml`.
+    pub mime_type: Option<String>,
+    /// Uncompressed size in bytes: `/Params /Size`, or the decoded stream length
+    /// when the file does not record it.
+    pub size: Option<u64>,
+    /// `/Params /CreationDate` as stored, a PDF date string.
+    pub creation_date: Option<String>,
+    /// `/Params /ModDate` as stored, a PDF date string.
+    pub mod_date: Option<String>,
+    /// `/Params /CheckSum`, the MD5 digest of the file, as lowercase hex.
+    pub checksum: Option<String>,
+    /// The PDF/A-3 relationship to the document (`/AFRelationship`), e.g. `Alternative`
+    /// for the XML of a ZUGFeRD/Factur-X invoice.
+    pub relationship: Option<String>,
+}
+
+/// Lists the files embedded in a PDF through its `EmbeddedFiles` name tree.
+///
+/// Attachments that only exist as file attachment annotations on a page are not
+/// listed. Entries whose file specification cannot be resolved are skipped.
+///
+/// # Returns
+///
+/// * `Ok(Vec<AttachmentInfo>)`: The attachments in name tree order; empty if there are none.
+/// * `Err(Error)`: If the file cannot be read or parsed.
+///
+/// # Example
+///
+/// ```no_run
+/// use pdf_metadata::list_attachments;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     for attachment in list_attachments("invoice.pdf")? {
+///         println!("{} ({} bytes)", attachment.name, attachment.size.unwrap_or_default());
+///     }
+///     Ok(())
+/// }
+/// ```
+#[cfg(feature = "std-fs")]
+pub fn list_attachments<P: AsRef<Path>>(file_path: P) -> Result<Vec<AttachmentInfo>> {
+    let file_path = file_path.as_ref();
+    let doc = load_document(file_path)?;
+    Ok(embedded_files(&doc)
+        .into_iter()
+        .filter_map(|(name, filespec)| attachment_info(&doc, name, filespec))
+        .collect())
+}
+
+/// Settings for [`add_attachment_with_options`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AttachmentOptions {
+    /// The name of the attachment in the document. Defaults to the file name.
+    pub name: Option<String>,
+    /// The description shown by viewers.
+    pub description: Option<String>,
+    /// The MIME type. Defaults to a guess from the extension (`text/xml` for `.xml`, ...).
+    pub mime_type: Option<String>,
+    /// The PDF/A-3 relationship to the document: `Source`, `Data`, `Alternative`,
+    /// `Supplement` or `Unspecified`. When set, the attachment is also listed in the
+    /// catalog's `/AF` array, as ZUGFeRD/Factur-X invoices require (`Alternative`).
+    pub relationship: Option<String>,
+}
+
+/// Embeds `file` in the PDF at `file_path`, writing the result to `output_path`.
+///
+/// The attachment is named after the file; an existing attachment with the same
+/// name is replaced. `ModDate` is refreshed. Use the same path for input and output
+/// to update the file in place.
+///
+/// # Returns
+///
+/// * `Ok(())` if the document was written.
+/// * `Err(Error)`: If either file cannot be read, or the output cannot be written.
+///
+/// # Example
+///
+/// ```no_run
+/// use pdf_metadata::{add_attachment, extract_attachment};
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     add_attachment("invoice.pdf", "invoice.pdf", "data/order.xml", Some("Order data"))?;
+///     extract_attachment("invoice.pdf", "order.xml", "copy/order.xml")?;
+///     Ok(())
+/// }
+/// ```
+#[cfg(feature = "std-fs")]
+pub fn add_attachment<P: AsRef<Path>, Q: AsRef<Path>, R: AsRef<Path>>(
+    file_path: P,
+    output_path: Q,
+    file: R,
+    description: Option<&str>,
+) -> Result<()> {
+    let file_path = file_path.as_ref();
+    let output_path = output_path.as_ref();
+    let options = AttachmentOptions { description: description.map(str::to_string), ..AttachmentOptions::default() };
+    add_attachment_with_options(file_path, output_path, file, &options)
+}
+
+/// Like [`add_attachment`], with `options` setting the name, description, MIME type
+/// and PDF/A-3 relationship of the attachment.
+#[cfg(feature = "std-fs")]
+pub fn add_attachment_with_options<P: AsRef<Path>, Q: AsRef<Path>, R: AsRef<Path>>(
+    file_path: P,
+    output_path: Q,
+    file: R,
+    options: &AttachmentOptions,
+) -> Result<()> {
+    let file_path = file_path.as_ref();
+    let output_path = output_path.as_ref();
+    let file = file.as_ref();
+    let content = fs::read(file)?;
+    let file_name = file.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+    let name = options.name.clone().unwrap_or_else(|| file_name.clone());
+    let mime_type = options.mime_type.as_deref().or_else(|| guess_mime_type(file)).unwrap_or("application/octet-stream");
+
+    let write_options = crate::options::current();
+    write_file(file_path, Some(output_path), &write_options, |doc| {
+        let mut params = dictionary! { "Size" => content.len() as i64 };
+        if let Ok(modified) = fs::metadata(file).and_then(|metadata| metadata.modified()) {
+            params.set("ModDate", Object::string_literal(format_pdf_date(&DateTime::<Local>::from(modified))));
+        }
+        let mut stream = Stream::new(dictionary! { "Type" => "EmbeddedFile", "Subtype" => mime_type, "Params" => params }, content);
+        stream.compress()?;
+        let stream_id = doc.add_object(stream);
+
+        let mut filespec = dictionary! {
+            "Type" => "Filespec",
+            "F" => Object::string_literal(file_name.clone()),
+            "UF" => info_string_object(&file_name),
+            "EF" => dictionary! { "F" => stream_id, "UF" => stream_id },
+        };
+        if let Some(description) = &options.description {
+            filespec.set("Desc", info_string_object(description));
+        }
+        if let Some(relationship) = &options.relationship {
+            filespec.set("AFRelationship", Object::Name(relationship.as_bytes().to_vec()));
+        }
+        let filespec_id = doc.add_object(filespec);
+
+        let mut entries: Vec<(String, Object)> = embedded_files(doc)
+            .into_iter()
+            .filter(|(existing, _)| *existing != name)
+            .map(|(existing, filespec)| (existing, filespec.clone()))
+            .collect();
+        entries.push((name, Object::Reference(filespec_id)));
+        // Name tree keys are kept sorted; the rebuilt tree is a single flat node.
+        entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+        let names = entries.into_iter().flat_map(|(name, filespec)| [info_string_object(&name), filespec]).collect::<Vec<_>>();
+        let tree_id = doc.add_object(dictionary! { "Names" => names });
+        set_embedded_files(doc, tree_id)?;
+        if options.relationship.is_some() {
+            add_associated_file(doc, filespec_id)?;
+        }
+
+        mark_modified(doc, &write_options)
+    })
+}
+
+/// Writes the contents of the attachment called `name` to `dest_path`.
+///
+/// # Returns
+///
+/// * `Ok(u64)`: The number of bytes written.
+/// * `Err(Error::AttachmentNotFound)`: If the document has no attachment with this name.
+/// * `Err(Error)`: If the PDF cannot be read or `dest_path` cannot be written.
+#[cfg(feature = "std-fs")]
+pub fn extract_attachment<P: AsRef<Path>, Q: AsRef<Path>>(file_path: P, name: &str, dest_path: Q) -> Result<u64> {
+    let file_path = file_path.as_ref();
+    let doc = load_document(file_path)?;
+    let stream = embedded_files(&doc)
+        .into_iter()
+        .find(|(existing, _)| existing == name)
+        .and_then(|(_, filespec)| embedded_stream(&doc, resolve_dict(&doc, filespec)?))
+        .ok_or_else(|| Error::AttachmentNotFound(name.to_string()))?;
+    let content = stream.get_plain_content()?;
+    let dest_path = dest_path.as_ref();
+    fs::write(dest_path, &content).map_err(|source| Error::Write { path: dest_path.to_path_buf(), source })?;
+    Ok(content.len() as u64)
+}
+
+/// Points the catalog's `/Names /EmbeddedFiles` at `tree_id`, keeping the other name trees.
+#[cfg(feature = "std-fs")]
+fn set_embedded_files(doc: &mut Document, tree_id: ObjectId) -> Result<()> {
+    let names = doc.catalog()?.get(b"Names").ok().cloned();
+    match names {
+        Some(Object::Reference(names_id)) => {
+            incremental::dictionary_mut(doc, names_id)?.set("EmbeddedFiles", tree_id);
+        }
+        names => {
+            let mut names = names.and_then(|names| names.as_dict().ok().cloned()).unwrap_or_default();
+            names.set("EmbeddedFiles", tree_id);
+            incremental::catalog_mut(doc)?.set("Names", names);
+        }
+    }
+    Ok(())
+}
+
+/// Appends `filespec_id` to the catalog's `/AF` array of associated files.
+#[cfg(feature = "std-fs")]
+fn add_associated_file(doc: &mut Document, filespec_id: ObjectId) -> Result<()> {
+    let af = doc.catalog()?.get(b"AF").ok().cloned();
+    let mut files = match af {
+        Some(Object::Reference(id)) => doc.get_object(id)?.as_array()?.clone(),
+        Some(Object::Array(files)) => files,
+        _ => Vec::new(),
+    };
+    files.push(Object::Reference(filespec_id));
+    incremental::catalog_mut(doc)?.set("AF", files);
+    Ok(())
+}
+
+/// MIME types of the files usually attached to PDFs, by extension.
+#[cfg(feature = "std-fs")]
+fn guess_mime_type(file: &Path) -> Option<&'static str> {
+    let extension = file.extension()?.to_str()?.to_ascii_lowercase();
+    Some(match extension.as_str() {
+        "xml" => "text/xml",
+        "txt" => "text/plain",
+        "csv" => "text/csv",
+        "json" => "application/json",
+        "pdf" => "application/pdf",
+        "zip" => "application/zip",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "docx" => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        "xlsx" => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        _ => return None,
+    })
+}
+
+/// Collects the `(name, file specification)` pairs of the `EmbeddedFiles` name tree.
+#[cfg(feature = "std-fs")]
+pub(crate) fn embedded_files(doc: &Document) -> Vec<(String, &Object)> {
+    name_tree(doc, b"EmbeddedFiles")
+}
+
+/// Collects the `(name, value)` pairs of the catalog's `/Names /<tree>` name tree.
+#[cfg(feature = "std-fs")]
+pub(crate) fn name_tree<'a>(doc: &'a Document, tree: &[u8]) -> Vec<(String, &'a Object)> {
+    let mut entries = Vec::new();
+    let root = doc
+        .catalog()
+        .ok()
+        .and_then(|catalog| resolve_dict(doc, catalog.get(b"Names").ok()?))
+        .and_then(|names| resolve_dict(doc, names.get(tree).ok()?));
+    if let Some(root) = root {
+        collect_names(doc, root, 0, &mut HashSet::new(), &mut entries);
+    }
+    entries
+}
+
+#[cfg(feature = "std-fs")]
+fn collect_names<'a>(
+    doc: &'a Document,
+    node: &'a Dictionary,
+    depth: usize,
+    visited: &mut HashSet<ObjectId>,
+    entries: &mut Vec<(String, &'a Object)>,
+) {
+    if depth > MAX_TREE_DEPTH {
+        return;
+    }
+    if let Ok(names) = node.get(b"Names").and_then(|names| doc.dereference(names)).and_then(|(_, o)| o.as_array()) {
+        for pair in names.chunks_exact(2) {
+            if let Some(name) = decode_string_object(&pair[0]) {
+                entries.push((name, &pair[1]));
+            }
+        }
+    }
+    if let Ok(kids) = node.get(b"Kids").and_then(Object::as_array) {
+        for kid in kids {
+            let Ok(id) = kid.as_reference() else { continue };
+            if visited.insert(id)
+                && let Ok(kid) = doc.get_dictionary(id)
+            {
+                collect_names(doc, kid, depth + 1, visited, entries);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std-fs")]
+fn attachment_info(doc: &Document, name: String, filespec: &Object) -> Option<AttachmentInfo> {
+    let filespec = resolve_dict(doc, filespec)?;
+    let text = |dict: &Dictionary, key: &[u8]| {
+        dict.get(key).ok().and_then(|o| doc.dereference(o).ok()).and_then(|(_, o)| decode_string_object(o))
+    };
+    let mut info = AttachmentInfo {
+        file_name: text(filespec, b"UF").or_else(|| text(filespec, b"F")),
+        description: text(filespec, b"Desc"),
+        relationship: filespec.get(b"AFRelationship").and_then(Object::as_name).ok().map(name_text),
+        name,
+        ..AttachmentInfo::default()
+    };
+
+    if let Some(stream) = embedded_stream(doc, filespec) {
+        info.mime_type = stream.dict.get(b"Subtype").and_then(Object::as_name).ok().map(name_text);
+        let params = stream.dict.get(b"Params").ok().and_then(|params| resolve_dict(doc, params));
+        if let Some(params) = params {
+            info.size = params.get(b"Size").and_then(Object::as_i64).ok().and_then(|size| u64::try_from(size).ok());
+            info.creation_date = text(params, b"CreationDate");
+            info.mod_date = text(params, b"ModDate");
+            info.checksum = params
+                .get(b"CheckSum")
+                .and_then(Object::as_str)
+                .ok()
+                .map(|digest| digest.iter().map(|b| format!("{:02x}", b)).collect());
+        }
+        if info.size.is_none() {
+            info.size = stream.get_plain_content().ok().map(|content| content.len() as u64);
+        }
+    }
+    Some(info)
+}
+
+/// The embedded file stream of a file specification (`/EF /UF`, or `/EF /F`).
+#[cfg(feature = "std-fs")]
+pub(crate) fn embedded_stream<'a>(doc: &'a Document, filespec: &'a Dictionary) -> Option<&'a Stream> {
+    let ef = resolve_dict(doc, filespec.get(b"EF").ok()?)?;
+    let stream = ef.get(b"UF").or_else(|_| ef.get(b"F")).ok()?;
+    doc.dereference(stream).ok()?.1.as_stream().ok()
+}
+
+pub(crate) fn resolve_dict<'a>(doc: &'a Document, object: &'a Object) -> Option<&'a Dictionary> {
+    doc.dereference(object).ok()?.1.as_dict().ok()
+}
+
+/// Renders a name such as `text#2Fxml`, already unescaped by the parser, as text.
+#[cfg(feature = "std-fs")]
+fn name_text(name: &[u8]) -> String {
+    String::from_utf8_lossy(name).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{create_minimal_test_pdf, setup_unique_test_dir};
+    use lopdf::StringFormat;
+    use std::fs;
+
+    #[test]
+    fn test_list_attachments_reads_name_tree() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("attachments_list");
+        let file = test_dir.join("invoice.pdf");
+        create_minimal_test_pdf(&file)?;
+        assert!(list_attachments(file.to_str().unwrap())?.is_empty());
+
+        let mut doc = Document::load(&file)?;
+        let params = dictionary! {
+            "Size" => 5,
+            "ModDate" => Object::string_literal("D:20240102030405Z"),
+            "CheckSum" => Object::String(vec![0xab, 0x01], StringFormat::Hexadecimal),
+        };
+        let mut stream = Stream::new(dictionary! { "Type" => "EmbeddedFile", "Subtype" => "text/xml", "Params" => params }, b"<a/>\n".to_vec());
+        stream.compress()?;
+        let stream_id = doc.add_object(stream);
+        let filespec_id = doc.add_object(dictionary! {
+            "Type" => "Filespec",
+            "F" => Object::string_literal("factur-x.xml"),
+            "Desc" => Object::string_literal("Invoice data"),
+            "AFRelationship" => "Alternative",
+            "EF" => dictionary! { "F" => stream_id },
+        });
+        // A kid node, so the tree is followed through /Kids.
+        let leaf_id = doc.add_object(dictionary! {
+            "Names" => vec![Object::string_literal("factur-x.xml"), Object::Reference(filespec_id)],
+        });
+        let names = dictionary! { "EmbeddedFiles" => dictionary! { "Kids" => vec![Object::Reference(leaf_id)] } };
+        doc.catalog_mut()?.set("Names", names);
+        doc.save(&file)?;
+
+        let attachments = list_attachments(file.to_str().unwrap())?;
+        assert_eq!(
+            attachments,
+            [AttachmentInfo {
+                name: "factur-x.xml".to_string(),
+                file_name: Some("factur-x.xml".to_string()),
+                description: Some("Invoice data".to_string()),
+                mime_type: Some("text/xml".to_string()),
+                size: Some(5),
+                creation_date: None,
+                mod_date: Some("D:20240102030405Z".to_string()),
+                checksum: Some("ab01".to_string()),
+                relationship: Some("Alternative".to_string()),
+            }]
+        );
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_add_and_extract_attachment() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("attachments_add_extract");
+        let file = test_dir.join("invoice.pdf");
+        create_minimal_test_pdf(&file)?;
+        let file_str = file.to_str().unwrap();
+        let xml = test_dir.join("factur-x.xml");
+        fs::write(&xml, "<Invoice>42</Invoice>")?;
+
+        let options = AttachmentOptions { relationship: Some("Alternative".to_string()), ..AttachmentOptions::default() };
+        add_attachment_with_options(file_str, file_str, &xml, &options)?;
+        fs::write(&xml, "<Invoice>43</Invoice>")?;
+        add_attachment(file_str, file_str, &xml, Some("Invoice data"))?;
+        add_attachment(file_str, file_str, test_dir.join("invoice.pdf"), None)?;
+
+        let attachments = list_attachments(file_str)?;
+        assert_eq!(attachments.iter().map(|a| a.name.as_str()).collect::<Vec<_>>(), ["factur-x.xml", "invoice.pdf"]);
+        assert_eq!(attachments[0].mime_type.as_deref(), Some("text/xml"));
+        assert_eq!(attachments[0].description.as_deref(), Some("Invoice data"));
+        assert!(crate::get_metadata(file_str)?.iter().any(|(key, _)| key == "ModDate"));
+
+        let extracted = test_dir.join("extracted.xml");
+        assert_eq!(extract_attachment(file_str, "factur-x.xml", &extracted)?, 21);
+        assert_eq!(fs::read_to_string(&extracted)?, "<Invoice>43</Invoice>");
+        assert!(matches!(extract_attachment(file_str, "missing.xml", &extracted), Err(Error::AttachmentNotFound(_))));
+
+        let doc = Document::load(&file)?;
+        assert_eq!(doc.catalog()?.get(b"AF")?.as_array()?.len(), 1);
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,446 @@
+//! # Info dictionary audit and repair
+//!
+//! Careless writers leave the Info dictionary in states that readers disagree on:
+//! a key written twice (most readers keep the last copy, some the first), keys that
+//! differ only in case, Info dictionaries left behind unreferenced after a rewrite,
+//! or a trailer `/Info` that is a direct dictionary or points at nothing.
+//! [`audit_info`] reports these problems and [`repair_info`] fixes them.
+//!
+//! Duplicated keys disappear when a file is parsed, so they are found by scanning
+//! the raw bytes of the Info object; an Info dictionary stored in an object stream
+//! is not scanned.
+
+use crate::recover::{looks_like_info, object_headers};
+use crate::validate::STANDARD_KEYS;
+use crate::{Result, incremental, load_document_mem, write_loaded};
+use lopdf::{Dictionary, Document, Object, ObjectId};
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// A problem found by [`audit_info`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Issue {
+    /// The key appears `count` times in the Info dictionary.
+    DuplicateKey { key: String, count: usize },
+    /// Keys that differ only in case, e.g. `Author` and `author`, in document order.
+    /// Viewers show only the standard spelling.
+    ConflictingKeys { keys: Vec<String> },
+    /// The trailer holds the Info dictionary directly instead of a reference to it,
+    /// which the PDF specification does not allow.
+    DirectInfo,
+    /// The trailer `/Info` refers to an object that is missing or not an Info dictionary.
+    InvalidInfoReference { id: ObjectId },
+    /// An untyped dictionary with Info keys that nothing refers to, typically the Info
+    /// dictionary of an earlier version of the file.
+    OrphanedInfo { id: ObjectId },
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Issue::DuplicateKey { key, count } => write!(f, "{} appears {} times", key, count),
+            Issue::ConflictingKeys { keys } => write!(f, "keys differ only in case: {}", keys.join(", ")),
+            Issue::DirectInfo => write!(f, "the trailer holds the Info dictionary directly"),
+            Issue::InvalidInfoReference { id } => {
+                write!(f, "the trailer refers to {} {} R, which is not an Info dictionary", id.0, id.1)
+            }
+            Issue::OrphanedInfo { id } => write!(f, "object {} {} R is an unreferenced Info dictionary", id.0, id.1),
+        }
+    }
+}
+
+/// Checks the Info dictionary of a PDF and the trailer entry that refers to it.
+///
+/// # Returns
+///
+/// * `Ok(Vec<Issue>)`: The problems found; empty if there are none.
+/// * `Err(Error)`: If the file cannot be read or parsed.
+///
+/// # Example
+///
+/// ```no_run
+/// use pdf_metadata::audit::{audit_info, repair_info};
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let issues = audit_info("scan.pdf")?;
+///     for issue in &issues {
+///         println!("{}", issue);
+///     }
+///     if !issues.is_empty() {
+///         repair_info("scan.pdf", "scan-fixed.pdf")?;
+///     }
+///     Ok(())
+/// }
+/// ```
+pub fn audit_info<P: AsRef<Path>>(file_path: P) -> Result<Vec<Issue>> {
+    let file_path = file_path.as_ref();
+    let bytes = fs::read(file_path)?;
+    let doc = load_document_mem(&bytes)?;
+    Ok(audit(&doc, &bytes))
+}
+
+/// Fixes the problems [`audit_info`] reports and writes the result to `output_path`,
+/// which may be the input file.
+///
+/// * Duplicated keys keep their last value, as most readers do.
+/// * Keys differing only in case are merged under the standard spelling, or the first
+///   spelling for custom keys, keeping the value stored under that spelling.
+/// * A direct Info dictionary is moved into an object of its own.
+/// * A missing or invalid trailer `/Info` is pointed at the newest orphaned Info
+///   dictionary, if any, and removed otherwise; the other orphans are deleted.
+///
+/// `ModDate` is left alone, since the entries themselves do not change.
+///
+/// # Returns
+///
+/// * `Ok(Vec<Issue>)`: The problems fixed; empty if the file had none, in which case
+///   it is written unchanged.
+/// * `Err(Error)`: If the file cannot be read, parsed or written.
+pub fn repair_info<P: AsRef<Path>, Q: AsRef<Path>>(file_path: P, output_path: Q) -> Result<Vec<Issue>> {
+    let file_path = file_path.as_ref();
+    let output_path = output_path.as_ref();
+    let bytes = fs::read(file_path)?;
+    let doc = load_document_mem(&bytes)?;
+    let issues = audit(&doc, &bytes);
+
+    let options = crate::options::current();
+    write_loaded(doc, file_path, Some(output_path), &options, |doc| {
+        let mut orphans: Vec<ObjectId> = issues
+            .iter()
+            .filter_map(|issue| match issue {
+                Issue::OrphanedInfo { id } => Some(*id),
+                _ => None,
+            })
+            .collect();
+        if issues.contains(&Issue::DirectInfo)
+            && let Some(Object::Dictionary(info)) = doc.trailer.remove(b"Info")
+        {
+            let info_id = doc.add_object(info);
+            doc.trailer.set("Info", info_id);
+        }
+        if issues.iter().any(|issue| matches!(issue, Issue::InvalidInfoReference { .. })) || !doc.trailer.has(b"Info") {
+            doc.trailer.remove(b"Info");
+            if let Some(newest) = orphans.pop() {
+                doc.trailer.set("Info", newest);
+            }
+        }
+        for orphan in orphans {
+            doc.objects.remove(&orphan);
+        }
+        if let Some(info) = info_id(doc).and_then(|id| incremental::dictionary_mut(doc, id).ok()) {
+            for issue in &issues {
+                if let Issue::ConflictingKeys { keys } = issue {
+                    merge_keys(info, keys);
+                }
+            }
+        }
+        Ok(())
+    })?;
+    Ok(issues)
+}
+
+fn audit(doc: &Document, bytes: &[u8]) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    let info_id = info_id(doc);
+    match doc.trailer.get(b"Info") {
+        Ok(Object::Dictionary(_)) => issues.push(Issue::DirectInfo),
+        Ok(Object::Reference(id)) if !doc.get_dictionary(*id).is_ok_and(is_info) => {
+            issues.push(Issue::InvalidInfoReference { id: *id })
+        }
+        _ => {}
+    }
+
+    let info = match doc.trailer.get(b"Info") {
+        Ok(Object::Dictionary(info)) => Some(info),
+        Ok(Object::Reference(id)) => doc.get_dictionary(*id).ok().filter(|info| is_info(info)),
+        _ => None,
+    };
+    if let (Some(id), Some(_)) = (info_id, info) {
+        let raw = object_headers(bytes).into_iter().rfind(|(_, header)| *header == id);
+        let keys = raw.map(|(offset, _)| dictionary_keys(&bytes[offset..])).unwrap_or_default();
+        let mut counts: BTreeMap<&[u8], usize> = BTreeMap::new();
+        for key in &keys {
+            *counts.entry(key.as_slice()).or_default() += 1;
+        }
+        issues.extend(counts.into_iter().filter(|(_, count)| *count > 1).map(|(key, count)| Issue::DuplicateKey {
+            key: String::from_utf8_lossy(key).into_owned(),
+            count,
+        }));
+    }
+    if let Some(info) = info {
+        let mut groups: Vec<Vec<String>> = Vec::new();
+        for (key, _) in info.iter() {
+            let key = String::from_utf8_lossy(key).into_owned();
+            match groups.iter_mut().find(|group| group[0].eq_ignore_ascii_case(&key)) {
+                Some(group) => group.push(key),
+                None => groups.push(vec![key]),
+            }
+        }
+        issues.extend(groups.into_iter().filter(|group| group.len() > 1).map(|keys| Issue::ConflictingKeys { keys }));
+    }
+
+    let referenced = referenced_ids(doc);
+    issues.extend(
+        doc.objects
+            .iter()
+            .filter(|(id, object)| {
+                Some(**id) != info_id && !referenced.contains(id) && object.as_dict().is_ok_and(looks_like_info)
+            })
+            .map(|(id, _)| Issue::OrphanedInfo { id: *id }),
+    );
+    issues
+}
+
+/// The object the trailer `/Info` refers to, if it is a reference.
+fn info_id(doc: &Document) -> Option<ObjectId> {
+    doc.trailer.get(b"Info").and_then(Object::as_reference).ok()
+}
+
+/// Whether `dict` can be an Info dictionary: Info dictionaries have no `/Type`, but
+/// writers are forgiven anything but the types of the document structure.
+fn is_info(dict: &Dictionary) -> bool {
+    !matches!(dict.get(b"Type").and_then(Object::as_name), Ok(b"Catalog" | b"Pages" | b"Page" | b"XRef" | b"ObjStm"))
+}
+
+/// Every object referred to by another object or by the trailer.
+fn referenced_ids(doc: &Document) -> HashSet<ObjectId> {
+    fn collect(object: &Object, ids: &mut HashSet<ObjectId>) {
+        match object {
+            Object::Reference(id) => {
+                ids.insert(*id);
+            }
+            Object::Array(items) => items.iter().for_each(|item| collect(item, ids)),
+            Object::Dictionary(dict) => dict.iter().for_each(|(_, value)| collect(value, ids)),
+            Object::Stream(stream) => stream.dict.iter().for_each(|(_, value)| collect(value, ids)),
+            _ => {}
+        }
+    }
+    let mut ids = HashSet::new();
+    doc.trailer.iter().for_each(|(_, value)| collect(value, &mut ids));
+    doc.objects.values().for_each(|object| collect(object, &mut ids));
+    ids
+}
+
+/// Replaces the entries `keys`, which differ only in case, by one under the standard
+/// spelling or the first one, holding the value stored under that spelling.
+fn merge_keys(info: &mut Dictionary, keys: &[String]) {
+    let target = STANDARD_KEYS
+        .iter()
+        .find(|standard| standard.eq_ignore_ascii_case(&keys[0]))
+        .map_or_else(|| keys[0].clone(), |standard| standard.to_string());
+    let value = info.get(target.as_bytes()).or_else(|_| info.get(keys[0].as_bytes())).cloned();
+    for key in keys {
+        info.remove(key.as_bytes());
+    }
+    if let Ok(value) = value {
+        info.set(target.into_bytes(), value);
+    }
+}
+
+/// The keys of the first dictionary in `bytes`, in order and with repetitions.
+/// Scanning stops at the first thing that cannot be a dictionary entry.
+fn dictionary_keys(bytes: &[u8]) -> Vec<Vec<u8>> {
+    let mut lexer = Lexer { bytes, pos: 0 };
+    while let Some(token) = lexer.next() {
+        if token == Token::DictOpen {
+            break;
+        }
+    }
+    let mut keys = Vec::new();
+    while let Some(Token::Name(key)) = lexer.next() {
+        keys.push(key);
+        match lexer.next() {
+            Some(Token::DictOpen | Token::ArrayOpen) => {
+                let mut depth = 1;
+                while depth > 0 {
+                    match lexer.next() {
+                        Some(Token::DictOpen | Token::ArrayOpen) => depth += 1,
+                        Some(Token::DictClose | Token::ArrayClose) => depth -= 1,
+                        Some(_) => {}
+                        None => return keys,
+                    }
+                }
+            }
+            // A reference, `<number> <generation> R`, is three tokens.
+            Some(Token::Other(number)) if number.iter().all(u8::is_ascii_digit) => {
+                let mut ahead = Lexer { bytes, pos: lexer.pos };
+                if let (Some(Token::Other(generation)), Some(Token::Other(b"R"))) = (ahead.next(), ahead.next())
+                    && generation.iter().all(u8::is_ascii_digit)
+                {
+                    lexer.pos = ahead.pos;
+                }
+            }
+            Some(Token::DictClose | Token::ArrayClose) | None => break,
+            Some(_) => {}
+        }
+    }
+    keys
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Token<'a> {
+    /// A name, with `#` escapes decoded.
+    Name(Vec<u8>),
+    DictOpen,
+    DictClose,
+    ArrayOpen,
+    ArrayClose,
+    /// A literal or hexadecimal string, whose content is not needed.
+    String,
+    /// A number, keyword or anything else.
+    Other(&'a [u8]),
+}
+
+struct Lexer<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Lexer<'a> {
+    fn next(&mut self) -> Option<Token<'a>> {
+        loop {
+            match self.bytes.get(self.pos)? {
+                b if is_whitespace(*b) => self.pos += 1,
+                b'%' => self.pos += self.bytes[self.pos..].iter().take_while(|b| !matches!(b, b'\r' | b'\n')).count(),
+                _ => break,
+            }
+        }
+        let rest = &self.bytes[self.pos..];
+        let (token, len) = match rest[0] {
+            b'<' if rest.get(1) == Some(&b'<') => (Token::DictOpen, 2),
+            b'>' if rest.get(1) == Some(&b'>') => (Token::DictClose, 2),
+            b'[' => (Token::ArrayOpen, 1),
+            b']' => (Token::ArrayClose, 1),
+            b'<' => (Token::String, rest.iter().position(|b| *b == b'>').map_or(rest.len(), |end| end + 1)),
+            b'(' => (Token::String, literal_string_len(rest)),
+            b'/' => {
+                let len = 1 + rest[1..].iter().take_while(|b| is_regular(**b)).count();
+                (Token::Name(decode_name(&rest[1..len])), len)
+            }
+            _ => {
+                let len = rest.iter().take_while(|b| is_regular(**b)).count().max(1);
+                (Token::Other(&rest[..len]), len)
+            }
+        };
+        self.pos += len;
+        Some(token)
+    }
+}
+
+/// The length of the literal string at the start of `bytes`, parentheses included.
+fn literal_string_len(bytes: &[u8]) -> usize {
+    let mut depth = 0;
+    let mut at = 0;
+    while at < bytes.len() {
+        match bytes[at] {
+            b'\\' => at += 1,
+            b'(' => depth += 1,
+            b')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return at + 1;
+                }
+            }
+            _ => {}
+        }
+        at += 1;
+    }
+    bytes.len()
+}
+
+fn decode_name(raw: &[u8]) -> Vec<u8> {
+    let mut name = Vec::with_capacity(raw.len());
+    let mut at = 0;
+    while at < raw.len() {
+        let escaped = (raw[at] == b'#')
+            .then(|| raw.get(at + 1..at + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match escaped {
+            Some(byte) => {
+                name.push(byte);
+                at += 3;
+            }
+            None => {
+                name.push(raw[at]);
+                at += 1;
+            }
+        }
+    }
+    name
+}
+
+fn is_whitespace(byte: u8) -> bool {
+    matches!(byte, b' ' | b'\t' | b'\r' | b'\n' | b'\x0C' | b'\0')
+}
+
+fn is_regular(byte: u8) -> bool {
+    !is_whitespace(byte) && !b"()<>[]{}/%".contains(&byte)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::get_metadata;
+    use crate::test_support::setup_unique_test_dir;
+    use lopdf::dictionary;
+
+    #[test]
+    fn test_audit_and_repair_info() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("audit_info");
+        let file = test_dir.join("doc.pdf");
+
+        let mut doc = Document::with_version("1.7");
+        let pages_id = doc.add_object(dictionary! { "Type" => "Pages", "Count" => 0, "Kids" => vec![] });
+        let catalog_id = doc.add_object(dictionary! { "Type" => "Catalog", "Pages" => pages_id });
+        let info_ref = doc.add_object(dictionary! {
+            "Title" => Object::string_literal("Primeiro"),
+            "Titlf" => Object::string_literal("Segundo"),
+            "Author" => Object::string_literal("Ana"),
+            "author" => Object::string_literal("ana"),
+            "Nested" => dictionary! { "Ref" => pages_id },
+        });
+        let orphan_id = doc.add_object(dictionary! { "Producer" => Object::string_literal("Antigo") });
+        doc.trailer.set("Root", catalog_id);
+        doc.trailer.set("Info", info_ref);
+        let mut bytes = Vec::new();
+        doc.save_to(&mut bytes)?;
+        // Rename Titlf to Title in place, which keeps every offset valid.
+        let at = bytes.windows(6).position(|w| w == b"/Titlf").unwrap();
+        bytes[at + 5] = b'e';
+        fs::write(&file, &bytes)?;
+        let file_str = file.to_str().unwrap();
+
+        let issues = audit_info(file_str)?;
+        assert!(issues.contains(&Issue::DuplicateKey { key: "Title".into(), count: 2 }));
+        assert!(issues.contains(&Issue::ConflictingKeys { keys: vec!["Author".into(), "author".into()] }));
+        assert!(issues.contains(&Issue::OrphanedInfo { id: orphan_id }));
+        assert_eq!(issues.len(), 3, "{issues:?}");
+
+        let output = test_dir.join("fixed.pdf");
+        let output_str = output.to_str().unwrap();
+        assert_eq!(repair_info(file_str, output_str)?, issues);
+        assert_eq!(audit_info(output_str)?, []);
+        let metadata = get_metadata(output_str)?;
+        assert!(metadata.contains(&("Title".to_string(), "Segundo".to_string())));
+        assert!(metadata.contains(&("Author".to_string(), "Ana".to_string())));
+        assert!(!metadata.iter().any(|(key, _)| key == "author" || key == "Producer"));
+
+        // A trailer holding the dictionary directly gets a reference instead.
+        let mut doc = Document::load(&output)?;
+        let id = info_id(&doc).unwrap();
+        let info = doc.objects.remove(&id).unwrap();
+        doc.trailer.set("Info", info);
+        doc.save(&file)?;
+        assert_eq!(audit_info(file_str)?, [Issue::DirectInfo]);
+        repair_info(file_str, file_str)?;
+        assert_eq!(audit_info(file_str)?, []);
+        assert!(get_metadata(file_str)?.contains(&("Title".to_string(), "Segundo".to_string())));
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+}
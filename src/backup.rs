@@ -0,0 +1,146 @@
+//! Timestamped copies of a file taken before an in-place update replaces it.
+//!
+//! With [`SetOptions::backup`](crate::SetOptions::backup) set, the original is
+//! copied to `<file>.bak-<timestamp>` next to it once the new contents are ready
+//! and just before they take its place, so a destructive edit can be undone by
+//! renaming the copy back. Timestamps are in UTC with millisecond precision, as in
+//! `report.pdf.bak-20240102T030405.678Z`, so the names sort by age.
+
+#[cfg(feature = "std-fs")]
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+#[cfg(feature = "std-fs")]
+use std::fs;
+#[cfg(feature = "std-fs")]
+use std::io;
+#[cfg(feature = "std-fs")]
+use std::path::{Path, PathBuf};
+
+/// The format of the timestamp after `.bak-`.
+#[cfg(feature = "std-fs")]
+const TIMESTAMP_FORMAT: &str = "%Y%m%dT%H%M%S%.3fZ";
+
+/// How many backups an in-place update keeps.
+///
+/// ```
+/// use pdf_metadata::{BackupPolicy, SetOptions};
+///
+/// // Keep the three most recent copies of each file.
+/// let options = SetOptions { backup: Some(BackupPolicy { keep: 3 }), ..SetOptions::default() };
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BackupPolicy {
+    /// The number of backups of a file kept after a new one is taken; the oldest
+    /// beyond it are deleted. `0` keeps them all.
+    pub keep: usize,
+}
+
+/// Copies `original` to a new `<file>.bak-<timestamp>` and deletes the backups
+/// beyond `policy.keep`. Returns the path of the copy.
+///
+/// Old backups are removed on a best-effort basis: one that cannot be deleted
+/// stays, and the update goes ahead.
+#[cfg(feature = "std-fs")]
+pub(crate) fn create(original: &Path, policy: &BackupPolicy) -> io::Result<PathBuf> {
+    let backup = free_backup_path(original, Utc::now());
+    fs::copy(original, &backup)?;
+    fs::File::open(&backup)?.sync_all()?;
+
+    if policy.keep > 0 {
+        let mut existing = list(original);
+        let excess = existing.len().saturating_sub(policy.keep);
+        for old in existing.drain(..excess) {
+            let _ = fs::remove_file(old);
+        }
+    }
+    Ok(backup)
+}
+
+/// The backups of `original`, oldest first.
+#[cfg(feature = "std-fs")]
+pub(crate) fn list(original: &Path) -> Vec<PathBuf> {
+    let (Some(dir), Some(prefix)) = (original.parent(), backup_prefix(original)) else {
+        return Vec::new();
+    };
+    let dir = if dir.as_os_str().is_empty() { Path::new(".") } else { dir };
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut backups: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            entry.file_name().to_str().and_then(|name| name.strip_prefix(&prefix)).is_some_and(|timestamp| {
+                NaiveDateTime::parse_from_str(timestamp, TIMESTAMP_FORMAT).is_ok()
+            })
+        })
+        .map(|entry| original.with_file_name(entry.file_name()))
+        .collect();
+    backups.sort();
+    backups
+}
+
+/// `<file name>.bak-`, the start of the names of `original`'s backups.
+#[cfg(feature = "std-fs")]
+fn backup_prefix(original: &Path) -> Option<String> {
+    original.file_name().and_then(|name| name.to_str()).map(|name| format!("{}.bak-", name))
+}
+
+/// The backup path for `now`, moved forward a millisecond at a time past any
+/// backup already taken at that instant.
+#[cfg(feature = "std-fs")]
+fn free_backup_path(original: &Path, mut now: DateTime<Utc>) -> PathBuf {
+    let prefix = backup_prefix(original).unwrap_or_else(|| "pdf.bak-".to_string());
+    loop {
+        let candidate = original.with_file_name(format!("{}{}", prefix, now.format(TIMESTAMP_FORMAT)));
+        if !candidate.exists() {
+            return candidate;
+        }
+        now += Duration::milliseconds(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{create_minimal_test_pdf, setup_unique_test_dir};
+    use crate::{get_pdf_metadata, update_metadata_in_place_with_options, SetOptions};
+
+    #[test]
+    fn test_in_place_update_keeps_recent_backups() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = setup_unique_test_dir("backup_retention");
+        let pdf_path = dir.join("report.pdf");
+        create_minimal_test_pdf(&pdf_path)?;
+        let pdf_str = pdf_path.to_str().unwrap();
+        let options = SetOptions { backup: Some(BackupPolicy { keep: 2 }), ..SetOptions::default() };
+
+        for version in ["v1", "v2", "v3"] {
+            update_metadata_in_place_with_options(pdf_str, "Title", version, &options)?;
+        }
+
+        let backups = list(&pdf_path);
+        assert_eq!(backups.len(), 2);
+        let titles: Vec<_> = backups
+            .iter()
+            .map(|backup| {
+                let metadata = get_pdf_metadata(&fs::read(backup).unwrap()).unwrap();
+                metadata.into_iter().find(|(key, _)| key == "Title").map(|(_, value)| value).unwrap()
+            })
+            .collect();
+        assert_eq!(titles, ["v1", "v2"]);
+
+        // Without a policy nothing is copied.
+        update_metadata_in_place_with_options(pdf_str, "Title", "v4", &SetOptions::default())?;
+        assert_eq!(list(&pdf_path).len(), 2);
+
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_backup_name_format() {
+        let original = Path::new("dir/report.pdf");
+        let now = DateTime::parse_from_rfc3339("2024-01-02T03:04:05.678Z").unwrap().with_timezone(&Utc);
+        assert_eq!(free_backup_path(original, now), Path::new("dir/report.pdf.bak-20240102T030405.678Z"));
+    }
+}
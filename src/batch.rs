@@ -0,0 +1,490 @@
+//! # Batch engine
+//!
+//! Runs an operation over many PDF files using a bounded pool of worker threads.
+//! Results are reported per file and in the same order as the input, so one
+//! failing document never aborts the rest of the batch. Writes that produce a new
+//! file per input use [`run_mapped`], which picks each output path with an
+//! [`OutputMapper`] such as an [`OutputPattern`].
+//!
+//! ```no_run
+//! use pdf_metadata::batch::{self, BatchOptions};
+//!
+//! fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let files = batch::collect_pdfs("path/to/archive", true)?;
+//!     let outcomes = batch::run(&files, &BatchOptions::default(), |path| pdf_metadata::get_metadata(path));
+//!     for outcome in outcomes {
+//!         match outcome.result {
+//!             Ok(metadata) => println!("{}: {} entries", outcome.path.display(), metadata.len()),
+//!             Err(e) => eprintln!("{}: {}", outcome.path.display(), e),
+//!         }
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use crate::progress::{self, ProgressSink};
+use crate::{Error, Result, UsageStats, clock, stats};
+use chrono::NaiveDate;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+/// Settings shared by every batch operation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BatchOptions {
+    /// Maximum number of files processed at the same time. Values below 1 are treated as 1.
+    pub max_concurrency: usize,
+}
+
+impl Default for BatchOptions {
+    /// Uses one worker per available CPU.
+    fn default() -> Self {
+        BatchOptions {
+            max_concurrency: thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+        }
+    }
+}
+
+/// The result of processing a single file in a batch.
+#[derive(Debug)]
+pub struct FileOutcome<T> {
+    /// The file that was processed.
+    pub path: PathBuf,
+    /// The value produced by the operation, or the error it failed with.
+    pub result: Result<T>,
+}
+
+/// Runs `operation` on every path, with at most `options.max_concurrency` files in flight.
+///
+/// # Arguments
+///
+/// * `paths`: The files to process.
+/// * `options`: Concurrency settings.
+/// * `operation`: The work to perform for each file. It is called from worker threads.
+///
+/// # Returns
+///
+/// One `FileOutcome` per input path, in input order.
+pub fn run<T, F>(paths: &[PathBuf], options: &BatchOptions, operation: F) -> Vec<FileOutcome<T>>
+where
+    T: Send,
+    F: Fn(&Path) -> Result<T> + Sync,
+{
+    run_indexed(paths.len(), options, |index| operation(&paths[index]))
+        .into_iter()
+        .zip(paths)
+        .map(|(result, path)| FileOutcome { path: path.clone(), result })
+        .collect()
+}
+
+/// Calls `operation` for every index below `count` on the worker pool, returning
+/// the results in index order.
+fn run_indexed<T, F>(count: usize, options: &BatchOptions, operation: F) -> Vec<Result<T>>
+where
+    T: Send,
+    F: Fn(usize) -> Result<T> + Sync,
+{
+    let workers = options.max_concurrency.max(1).min(count.max(1));
+    let next_index = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<Result<T>>>> = Mutex::new((0..count).map(|_| None).collect());
+
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| {
+                loop {
+                    let index = next_index.fetch_add(1, Ordering::Relaxed);
+                    if index >= count {
+                        break;
+                    }
+                    let result = operation(index);
+                    results.lock().unwrap_or_else(|e| e.into_inner())[index] = Some(result);
+                }
+            });
+        }
+    });
+
+    results
+        .into_inner()
+        .unwrap_or_else(|e| e.into_inner())
+        .into_iter()
+        .map(|result| result.expect("every index is processed exactly once"))
+        .collect()
+}
+
+/// Like [`run`], also returning the [`UsageStats`] of the work done by this batch.
+///
+/// Only the work `operation` does on the batch's worker threads is counted; other
+/// batches and threads running at the same time do not affect the result.
+pub fn run_with_stats<T, F>(paths: &[PathBuf], options: &BatchOptions, operation: F) -> (Vec<FileOutcome<T>>, UsageStats)
+where
+    T: Send,
+    F: Fn(&Path) -> Result<T> + Sync,
+{
+    let counts = Arc::new(stats::Counts::new());
+    let outcomes = run(paths, options, |path| stats::scoped(&counts, || operation(path)));
+    (outcomes, counts.snapshot())
+}
+
+/// Like [`run`], reporting to `sink` the [`Phase`](crate::progress::Phase)s of each
+/// file and every finished file.
+///
+/// `sink` is called from the worker threads; see the [`progress`] module.
+pub fn run_with_progress<T, F>(
+    paths: &[PathBuf],
+    options: &BatchOptions,
+    sink: Arc<dyn ProgressSink>,
+    operation: F,
+) -> Vec<FileOutcome<T>>
+where
+    T: Send,
+    F: Fn(&Path) -> Result<T> + Sync,
+{
+    let finished = AtomicUsize::new(0);
+    run(paths, options, |path| {
+        let result = progress::with_file(sink.clone(), path, || operation(path));
+        let finished = finished.fetch_add(1, Ordering::Relaxed) + 1;
+        sink.file_finished(path, finished, paths.len(), result.is_ok());
+        result
+    })
+}
+
+/// Chooses the output path of each input file in [`run_mapped`].
+///
+/// Implemented for [`OutputPattern`] and for closures `Fn(&Path) -> PathBuf`.
+pub trait OutputMapper: Sync {
+    /// Returns the path the output for `input` is written to.
+    fn output_for(&self, input: &Path) -> PathBuf;
+}
+
+impl<F> OutputMapper for F
+where
+    F: Fn(&Path) -> PathBuf + Sync,
+{
+    fn output_for(&self, input: &Path) -> PathBuf {
+        self(input)
+    }
+}
+
+/// Placeholders accepted by [`OutputPattern`].
+const PLACEHOLDERS: [&str; 8] = ["stem", "name", "ext", "parent", "date", "year", "month", "day"];
+
+/// An output path template such as `{stem}_tagged.pdf` or `tagged/{year}/{month}/{name}`.
+///
+/// | Placeholder | Replaced by |
+/// |-------------|-------------|
+/// | `{stem}` | the input file name without extension |
+/// | `{name}` | the input file name |
+/// | `{ext}` | the input extension, without the dot |
+/// | `{parent}` | the name of the input's directory |
+/// | `{date}`, `{year}`, `{month}`, `{day}` | the date of the run, as `YYYY-MM-DD`, `YYYY`, `MM`, `DD` |
+///
+/// Relative results are resolved against the input's directory.
+///
+/// ```
+/// use pdf_metadata::batch::{OutputMapper, OutputPattern};
+/// use std::path::Path;
+///
+/// let pattern = OutputPattern::new("{stem}_tagged.pdf").unwrap();
+/// assert_eq!(pattern.output_for(Path::new("in/report.pdf")), Path::new("in/report_tagged.pdf"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputPattern {
+    template: String,
+    date: NaiveDate,
+}
+
+impl OutputPattern {
+    /// Parses `template`, dating the run today.
+    ///
+    /// Fails with `Error::UnsupportedFormat` on unknown or unclosed placeholders.
+    pub fn new(template: &str) -> Result<Self> {
+        let mut rest = template;
+        while let Some(open) = rest.find('{') {
+            let close = rest[open..]
+                .find('}')
+                .ok_or_else(|| Error::UnsupportedFormat(format!("unclosed placeholder in output pattern '{}'", template)))?;
+            let placeholder = &rest[open + 1..open + close];
+            if !PLACEHOLDERS.contains(&placeholder) {
+                return Err(Error::UnsupportedFormat(format!("output pattern placeholder '{{{}}}'", placeholder)));
+            }
+            rest = &rest[open + close + 1..];
+        }
+        Ok(OutputPattern { template: template.to_string(), date: clock::now().date_naive() })
+    }
+
+    /// Uses `date` for the date placeholders instead of today.
+    pub fn with_date(mut self, date: NaiveDate) -> Self {
+        self.date = date;
+        self
+    }
+}
+
+impl OutputMapper for OutputPattern {
+    fn output_for(&self, input: &Path) -> PathBuf {
+        let text = |s: Option<&std::ffi::OsStr>| s.map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+        let parent = input.parent().unwrap_or(Path::new(""));
+        let mut output = self.template.clone();
+        for (placeholder, value) in [
+            ("{stem}", text(input.file_stem())),
+            ("{name}", text(input.file_name())),
+            ("{ext}", text(input.extension())),
+            ("{parent}", text(parent.file_name())),
+            ("{date}", self.date.format("%Y-%m-%d").to_string()),
+            ("{year}", self.date.format("%Y").to_string()),
+            ("{month}", self.date.format("%m").to_string()),
+            ("{day}", self.date.format("%d").to_string()),
+        ] {
+            output = output.replace(placeholder, &value);
+        }
+        parent.join(output)
+    }
+}
+
+/// The result of processing a single file in [`run_mapped`].
+#[derive(Debug)]
+pub struct MappedOutcome<T> {
+    /// The file that was processed.
+    pub path: PathBuf,
+    /// Where the output for `path` was (or would have been) written.
+    pub output: PathBuf,
+    /// The value produced by the operation, or the error it failed with.
+    pub result: Result<T>,
+}
+
+/// Runs `operation(input, output)` on every path, with outputs chosen by `mapper`.
+///
+/// Missing output directories are created first. When several inputs map to the same
+/// output, only the first is processed; the others fail with `Error::Write` so no
+/// output is silently overwritten by another input.
+///
+/// # Returns
+///
+/// One `MappedOutcome` per input path, in input order.
+///
+/// # Example
+///
+/// ```no_run
+/// use pdf_metadata::batch::{self, BatchOptions, OutputPattern};
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let files = batch::collect_pdfs("inbox", false)?;
+///     let pattern = OutputPattern::new("../tagged/{year}/{month}/{stem}_tagged.pdf")?;
+///     for outcome in batch::run_mapped(&files, &BatchOptions::default(), &pattern, |input, output| {
+///         pdf_metadata::set_metadata(input, output, "Subject", "Tagged")
+///     }) {
+///         println!("{} -> {}: {:?}", outcome.path.display(), outcome.output.display(), outcome.result.is_ok());
+///     }
+///     Ok(())
+/// }
+/// ```
+pub fn run_mapped<T, M, F>(paths: &[PathBuf], options: &BatchOptions, mapper: &M, operation: F) -> Vec<MappedOutcome<T>>
+where
+    T: Send,
+    M: OutputMapper + ?Sized,
+    F: Fn(&Path, &Path) -> Result<T> + Sync,
+{
+    let outputs: Vec<PathBuf> = paths.iter().map(|path| mapper.output_for(path)).collect();
+    let mut first_input: HashMap<&Path, &Path> = HashMap::new();
+    let conflicts: Vec<Option<&Path>> = paths
+        .iter()
+        .zip(&outputs)
+        .map(|(path, output)| {
+            let first = *first_input.entry(output.as_path()).or_insert(path.as_path());
+            (first != path.as_path()).then_some(first)
+        })
+        .collect();
+    run_indexed(paths.len(), options, |index| {
+        let output = &outputs[index];
+        if let Some(first) = conflicts[index] {
+            return Err(Error::Write {
+                path: output.clone(),
+                source: std::io::Error::new(
+                    std::io::ErrorKind::AlreadyExists,
+                    format!("output is also mapped from '{}'", first.display()),
+                ),
+            });
+        }
+        if let Some(dir) = output.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir).map_err(|source| Error::Write { path: output.clone(), source })?;
+        }
+        operation(&paths[index], output)
+    })
+    .into_iter()
+    .zip(paths.iter().zip(outputs))
+    .map(|(result, (path, output))| MappedOutcome { path: path.clone(), output, result })
+    .collect()
+}
+
+/// Lists the PDF files (by `.pdf` extension, case-insensitive) in `dir`.
+///
+/// # Arguments
+///
+/// * `dir`: The directory to scan.
+/// * `recursive`: Whether to descend into subdirectories.
+///
+/// # Returns
+///
+/// * `Ok(Vec<PathBuf>)`: The PDF paths found, sorted for stable output.
+/// * `Err(Error)`: If `dir` (or a subdirectory) cannot be read.
+pub fn collect_pdfs<P: AsRef<Path>>(dir: P, recursive: bool) -> Result<Vec<PathBuf>> {
+    let mut found = Vec::new();
+    let mut pending = vec![dir.as_ref().to_path_buf()];
+
+    while let Some(current) = pending.pop() {
+        for entry in fs::read_dir(&current)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                if recursive {
+                    pending.push(path);
+                }
+            } else if path
+                .extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|e| e.eq_ignore_ascii_case("pdf"))
+            {
+                found.push(path);
+            }
+        }
+    }
+
+    found.sort();
+    Ok(found)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Error;
+    use crate::test_support::{create_minimal_test_pdf, setup_unique_test_dir};
+
+    #[test]
+    fn test_run_preserves_order_and_isolates_failures() {
+        let paths: Vec<PathBuf> = (0..20).map(|i| PathBuf::from(format!("file_{}.pdf", i))).collect();
+        let options = BatchOptions { max_concurrency: 4 };
+        let outcomes = run(&paths, &options, |path| {
+            if path.to_str() == Some("file_7.pdf") {
+                return Err(Error::MissingInfoDict);
+            }
+            Ok(path.to_string_lossy().len())
+        });
+
+        assert_eq!(outcomes.len(), 20);
+        for (outcome, path) in outcomes.iter().zip(&paths) {
+            assert_eq!(&outcome.path, path);
+        }
+        assert!(matches!(outcomes[7].result, Err(Error::MissingInfoDict)));
+        assert_eq!(outcomes.iter().filter(|o| o.result.is_ok()).count(), 19);
+    }
+
+    #[test]
+    fn test_run_with_stats_counts_only_its_own_work() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("batch_stats");
+        let paths: Vec<PathBuf> = (0..4).map(|i| test_dir.join(format!("doc_{}.pdf", i))).collect();
+        for path in &paths {
+            crate::test_support::create_classic_xref_test_pdf(path)?;
+            crate::update_metadata_in_place(path, "Title", "Batch")?;
+        }
+
+        // Two batches over the same files at the same time keep separate counts.
+        let options = BatchOptions { max_concurrency: 2 };
+        let (first, second) = thread::scope(|scope| {
+            let first = scope.spawn(|| run_with_stats(&paths, &options, |path| crate::get_metadata(path)));
+            let second = scope.spawn(|| run_with_stats(&paths[..2], &options, |path| crate::get_metadata(path)));
+            (first.join().unwrap(), second.join().unwrap())
+        });
+
+        assert!(first.0.iter().chain(&second.0).all(|outcome| outcome.result.is_ok()));
+        // Each file holds an ASCII Title and ModDate.
+        assert_eq!(first.1, UsageStats { documents_scanned: 4, ascii_strings: 8, ..UsageStats::default() });
+        assert_eq!(second.1, UsageStats { documents_scanned: 2, ascii_strings: 4, ..UsageStats::default() });
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_run_with_progress_reports_each_file() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        #[derive(Default)]
+        struct Counter(Mutex<Vec<(PathBuf, usize, bool)>>, AtomicUsize);
+        impl ProgressSink for Counter {
+            fn phase(&self, _file: Option<&Path>, _phase: progress::Phase) {
+                self.1.fetch_add(1, Ordering::Relaxed);
+            }
+            fn file_finished(&self, file: &Path, finished: usize, total: usize, succeeded: bool) {
+                assert_eq!(total, 3);
+                self.0.lock().unwrap().push((file.to_path_buf(), finished, succeeded));
+            }
+        }
+
+        let test_dir = setup_unique_test_dir("batch_progress");
+        let paths = vec![test_dir.join("a.pdf"), test_dir.join("b.pdf"), test_dir.join("missing.pdf")];
+        create_minimal_test_pdf(&paths[0])?;
+        create_minimal_test_pdf(&paths[1])?;
+        let sink = Arc::new(Counter::default());
+        let outcomes = run_with_progress(&paths, &BatchOptions { max_concurrency: 2 }, sink.clone(), |path| {
+            crate::update_metadata_in_place(path.to_str().unwrap(), "Title", "Batch")
+        });
+
+        assert_eq!(outcomes.iter().filter(|o| o.result.is_ok()).count(), 2);
+        let mut finished = sink.0.lock().unwrap().clone();
+        finished.sort_by_key(|(_, n, _)| *n);
+        assert_eq!(finished.iter().map(|(_, n, _)| *n).collect::<Vec<_>>(), [1, 2, 3]);
+        assert!(finished.iter().all(|(file, _, succeeded)| *succeeded == (file != &paths[2])));
+        // Loading, modifying and saving for each of the two readable files.
+        assert_eq!(sink.1.load(Ordering::Relaxed), 6);
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_collect_pdfs_recursive() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("batch_collect_pdfs");
+        fs::create_dir_all(test_dir.join("sub"))?;
+        create_minimal_test_pdf(&test_dir.join("a.pdf"))?;
+        create_minimal_test_pdf(&test_dir.join("sub").join("b.PDF"))?;
+        fs::write(test_dir.join("notes.txt"), "not a pdf")?;
+
+        assert_eq!(collect_pdfs(&test_dir, false)?, vec![test_dir.join("a.pdf")]);
+        assert_eq!(collect_pdfs(&test_dir, true)?.len(), 2);
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_run_mapped_reports_outputs_and_conflicts() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("batch_run_mapped");
+        let inputs = vec![test_dir.join("a.pdf"), test_dir.join("b.pdf")];
+        for input in &inputs {
+            create_minimal_test_pdf(input)?;
+        }
+        let date = NaiveDate::from_ymd_opt(2024, 3, 9).unwrap();
+        let pattern = OutputPattern::new("tagged/{year}/{month}/{stem}_tagged.{ext}")?.with_date(date);
+        let options = BatchOptions { max_concurrency: 2 };
+
+        let outcomes = run_mapped(&inputs, &options, &pattern, |input, output| {
+            crate::set_metadata(input.to_str().unwrap(), output.to_str().unwrap(), "Subject", "Tagged")
+        });
+        assert_eq!(outcomes[1].output, test_dir.join("tagged/2024/03/b_tagged.pdf"));
+        for outcome in &outcomes {
+            assert!(outcome.result.is_ok());
+            assert!(crate::get_metadata(outcome.output.to_str().unwrap())?.iter().any(|(_, v)| v == "Tagged"));
+        }
+
+        let same = |_: &Path| test_dir.join("merged.pdf");
+        let outcomes = run_mapped(&inputs, &options, &same, |_, _| Ok(()));
+        assert!(outcomes[0].result.is_ok());
+        assert!(matches!(&outcomes[1].result, Err(Error::Write { path, .. }) if path == &test_dir.join("merged.pdf")));
+
+        assert!(OutputPattern::new("{stem}_{user}.pdf").is_err());
+        assert!(OutputPattern::new("{stem.pdf").is_err());
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+}
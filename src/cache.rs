@@ -0,0 +1,439 @@
+//! # Read caches
+//!
+//! [`ContentCache`] memoizes the parsed trailer and Info entries of PDF documents,
+//! keyed by the SHA-256 of their bytes. Review workflows tend to request the same
+//! document again and again, often as uploads under different names; with the
+//! cache, repeated requests skip parsing entirely.
+//!
+//! [`MetadataCache`] serves applications that query the same files on disk, such as
+//! a GUI refreshing its view: it remembers the metadata of each path together with
+//! the file's modification time and size (and, on Unix, its inode and status-change
+//! time), and only reads the file again when one of them changed. Both caches are `Send + Sync` and meant to be shared (e.g. in an `Arc`)
+//! between request handlers.
+//!
+//! ```no_run
+//! use pdf_metadata::cache::ContentCache;
+//!
+//! fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let cache = ContentCache::new(1024);
+//!     let bytes = std::fs::read("path/to/document.pdf")?;
+//!     let first = cache.get(&bytes)?; // parsed
+//!     let second = cache.get(&bytes)?; // served from the cache
+//!     assert_eq!(first.metadata, second.metadata);
+//!     Ok(())
+//! }
+//! ```
+
+#[cfg(feature = "std-fs")]
+use crate::fast_info::read_info_values_from_file;
+#[cfg(feature = "std-fs")]
+use crate::value::parse_date_entries;
+use crate::{MetadataValue, Result, load_document_mem, read_info_entries};
+use lopdf::Dictionary;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+#[cfg(feature = "std-fs")]
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::SystemTime;
+
+/// The SHA-256 digest identifying a document's content.
+pub type ContentHash = [u8; 32];
+
+/// The parsed data kept for each cached document.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CachedDocument {
+    /// The document's trailer dictionary.
+    pub trailer: Dictionary,
+    /// The `(key, value)` entries of the Info dictionary, as returned by `get_pdf_metadata`.
+    pub metadata: Vec<(String, String)>,
+}
+
+/// The metadata kept for each file in a [`MetadataCache`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct CachedMetadata {
+    /// The typed Info entries, as returned by `get_metadata_values`.
+    pub values: Vec<(String, MetadataValue)>,
+    /// The modification time of the file when it was read.
+    pub modified: SystemTime,
+    /// The size of the file in bytes when it was read.
+    pub len: u64,
+}
+
+/// The state of a file on disk that cached metadata was read from.
+///
+/// On Unix it includes the inode and the status-change time besides the modification
+/// time and size: a file replaced through a rename gets a new inode, and any write
+/// moves `ctime`, even one that puts the modification time back the way
+/// `SetOptions::preserve_mtime` does.
+#[cfg(feature = "std-fs")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct FileStamp {
+    modified: SystemTime,
+    len: u64,
+    #[cfg(unix)]
+    inode: u64,
+    #[cfg(unix)]
+    changed: (i64, i64),
+}
+
+#[cfg(feature = "std-fs")]
+impl FileStamp {
+    /// The stamp of a file whose metadata is `stat` and modification time `modified`.
+    pub(crate) fn of(stat: &std::fs::Metadata, modified: SystemTime) -> FileStamp {
+        #[cfg(unix)]
+        use std::os::unix::fs::MetadataExt;
+
+        FileStamp {
+            modified,
+            len: stat.len(),
+            #[cfg(unix)]
+            inode: stat.ino(),
+            #[cfg(unix)]
+            changed: (stat.ctime(), stat.ctime_nsec()),
+        }
+    }
+}
+
+/// Hit and miss counters of a [`ContentCache`] or [`MetadataCache`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// Number of documents currently cached.
+    pub entries: usize,
+}
+
+/// A thread-safe LRU cache of parsed documents keyed by content hash.
+#[derive(Debug)]
+pub struct ContentCache {
+    capacity: usize,
+    state: Mutex<LruState<ContentHash, CachedDocument>>,
+}
+
+#[derive(Debug)]
+struct LruState<K, V> {
+    entries: HashMap<K, (Arc<V>, u64)>,
+    /// Last-use tick of every entry; the first key is the least recently used.
+    recency: BTreeMap<u64, K>,
+    tick: u64,
+    hits: u64,
+    misses: u64,
+}
+
+impl<K, V> Default for LruState<K, V> {
+    fn default() -> Self {
+        LruState { entries: HashMap::new(), recency: BTreeMap::new(), tick: 0, hits: 0, misses: 0 }
+    }
+}
+
+impl<K: Clone + Eq + Hash, V> LruState<K, V> {
+    fn touch(&mut self, key: &K) -> Option<Arc<V>> {
+        self.tick += 1;
+        let tick = self.tick;
+        let (value, last_used) = self.entries.get_mut(key)?;
+        self.recency.remove(last_used);
+        self.recency.insert(tick, key.clone());
+        *last_used = tick;
+        Some(Arc::clone(value))
+    }
+
+    /// Stores `value` under `key`, replacing any previous value and evicting the
+    /// least recently used entries beyond `capacity`.
+    fn insert(&mut self, key: K, value: Arc<V>, capacity: usize) {
+        if capacity == 0 {
+            return;
+        }
+        self.remove(&key);
+        while self.entries.len() >= capacity {
+            let Some((_, oldest)) = self.recency.pop_first() else {
+                break;
+            };
+            self.entries.remove(&oldest);
+        }
+        self.tick += 1;
+        let tick = self.tick;
+        self.entries.insert(key.clone(), (value, tick));
+        self.recency.insert(tick, key);
+    }
+
+    fn remove(&mut self, key: &K) {
+        if let Some((_, last_used)) = self.entries.remove(key) {
+            self.recency.remove(&last_used);
+        }
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.recency.clear();
+    }
+
+    fn stats(&self) -> CacheStats {
+        CacheStats { hits: self.hits, misses: self.misses, entries: self.entries.len() }
+    }
+}
+
+impl ContentCache {
+    /// Creates a cache holding at most `capacity` documents. A capacity of 0 disables caching.
+    pub fn new(capacity: usize) -> Self {
+        ContentCache {
+            capacity,
+            state: Mutex::new(LruState::default()),
+        }
+    }
+
+    /// Returns the parsed trailer and metadata of `pdf_content`, parsing it only on a cache miss.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Arc<CachedDocument>)`: The cached or freshly parsed document.
+    /// * `Err(Error)`: If the data cannot be parsed. Failures are not cached.
+    pub fn get(&self, pdf_content: &[u8]) -> Result<Arc<CachedDocument>> {
+        let hash = content_hash(pdf_content);
+        {
+            let mut state = self.lock();
+            if let Some(document) = state.touch(&hash) {
+                state.hits += 1;
+                return Ok(document);
+            }
+            state.misses += 1;
+        }
+
+        // Parse without holding the lock so other requests are not serialized behind it.
+        let doc = load_document_mem(pdf_content)?;
+        let document = Arc::new(CachedDocument {
+            trailer: doc.trailer.clone(),
+            metadata: read_info_entries(&doc),
+        });
+        self.insert(hash, Arc::clone(&document));
+        Ok(document)
+    }
+
+    /// Reads the file at `path` and looks it up like [`ContentCache::get`].
+    #[cfg(feature = "std-fs")]
+    pub fn get_file<P: AsRef<Path>>(&self, path: P) -> Result<Arc<CachedDocument>> {
+        self.get(&std::fs::read(path)?)
+    }
+
+    /// Removes every cached document. Counters are kept.
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    /// Returns the current hit/miss counters and entry count.
+    pub fn stats(&self) -> CacheStats {
+        self.lock().stats()
+    }
+
+    fn insert(&self, hash: ContentHash, document: Arc<CachedDocument>) {
+        let mut state = self.lock();
+        // Another thread may have parsed the same content concurrently.
+        if state.touch(&hash).is_some() {
+            return;
+        }
+        state.insert(hash, document, self.capacity);
+    }
+
+    fn lock(&self) -> MutexGuard<'_, LruState<ContentHash, CachedDocument>> {
+        lock(&self.state)
+    }
+}
+
+/// A thread-safe LRU cache of file metadata keyed by path, invalidated when the
+/// file's modification time or size changes, or on Unix its inode or status-change
+/// time.
+///
+/// Paths are used as given: `doc.pdf` and `./doc.pdf` are cached separately. On
+/// other platforms a rewrite that keeps both the size and the modification time goes
+/// unnoticed: coarse file system timestamps make that possible within the same
+/// second, and writes with `SetOptions::preserve_mtime` do it on purpose. Call
+/// [`MetadataCache::invalidate`] after writing a file yourself.
+///
+/// ```no_run
+/// use pdf_metadata::cache::MetadataCache;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let cache = MetadataCache::new(256);
+///     let first = cache.get("path/to/document.pdf")?; // read from disk
+///     let second = cache.get("path/to/document.pdf")?; // unchanged, served from the cache
+///     assert_eq!(first.values, second.values);
+///     Ok(())
+/// }
+/// ```
+#[cfg(feature = "std-fs")]
+#[derive(Debug)]
+pub struct MetadataCache {
+    capacity: usize,
+    state: Mutex<LruState<PathBuf, (FileStamp, Arc<CachedMetadata>)>>,
+}
+
+#[cfg(feature = "std-fs")]
+impl MetadataCache {
+    /// Creates a cache holding the metadata of at most `capacity` files. A capacity of 0 disables caching.
+    pub fn new(capacity: usize) -> Self {
+        MetadataCache {
+            capacity,
+            state: Mutex::new(LruState::default()),
+        }
+    }
+
+    /// Returns the metadata of the file at `path`, reading it only when it is not
+    /// cached or the file changed since, as told by its [stamp](MetadataCache).
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Arc<CachedMetadata>)`: The cached or freshly read metadata.
+    /// * `Err(Error)`: If the file cannot be read or parsed. Failures are not cached.
+    pub fn get<P: AsRef<Path>>(&self, path: P) -> Result<Arc<CachedMetadata>> {
+        let path = path.as_ref();
+        let stat = std::fs::metadata(path)?;
+        // Without a modification time there is nothing to validate an entry against,
+        // so such files are read on every call and never cached.
+        let Ok(modified) = stat.modified() else {
+            return Ok(Arc::new(read_metadata(path, SystemTime::UNIX_EPOCH, stat.len())?));
+        };
+        let stamp = FileStamp::of(&stat, modified);
+        {
+            let mut state = self.lock();
+            if let Some(cached) = state.touch(&path.to_path_buf())
+                && cached.0 == stamp
+            {
+                state.hits += 1;
+                return Ok(Arc::clone(&cached.1));
+            }
+            state.misses += 1;
+        }
+
+        // The file was stat'ed before it is read, so a change made in between shows
+        // up as a different stamp on the next lookup rather than as stale values.
+        let cached = Arc::new(read_metadata(path, modified, stat.len())?);
+        self.lock().insert(path.to_path_buf(), Arc::new((stamp, Arc::clone(&cached))), self.capacity);
+        Ok(cached)
+    }
+
+    /// Drops the cached metadata of `path`, if any.
+    pub fn invalidate<P: AsRef<Path>>(&self, path: P) {
+        self.lock().remove(&path.as_ref().to_path_buf());
+    }
+
+    /// Removes every cached entry. Counters are kept.
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    /// Returns the current hit/miss counters and entry count.
+    pub fn stats(&self) -> CacheStats {
+        self.lock().stats()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, LruState<PathBuf, (FileStamp, Arc<CachedMetadata>)>> {
+        lock(&self.state)
+    }
+}
+
+#[cfg(feature = "std-fs")]
+pub(crate) fn read_metadata(path: &Path, modified: SystemTime, len: u64) -> Result<CachedMetadata> {
+    let mut values = read_info_values_from_file(path)?;
+    parse_date_entries(&mut values);
+    Ok(CachedMetadata { values, modified, len })
+}
+
+fn lock<T>(state: &Mutex<T>) -> MutexGuard<'_, T> {
+    state.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Computes the cache key of a document's bytes.
+pub fn content_hash(pdf_content: &[u8]) -> ContentHash {
+    Sha256::digest(pdf_content).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{create_minimal_test_pdf, setup_unique_test_dir};
+    use crate::{set_pdf_metadata, update_metadata_in_place};
+    use std::fs;
+    use std::time::Duration;
+
+    #[test]
+    fn test_cache_hits_and_evicts_least_recently_used() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("content_cache");
+        let file = test_dir.join("base.pdf");
+        create_minimal_test_pdf(&file)?;
+        let base = fs::read(&file)?;
+        let a = set_pdf_metadata(&base, "Title", "A")?;
+        let b = set_pdf_metadata(&base, "Title", "B")?;
+        let c = set_pdf_metadata(&base, "Title", "C")?;
+
+        let cache = ContentCache::new(2);
+        let first = cache.get(&a)?;
+        assert!(first.metadata.iter().any(|(k, v)| k == "Title" && v == "A"));
+        assert!(first.trailer.has(b"Root"));
+        assert!(Arc::ptr_eq(&first, &cache.get(&a)?));
+
+        cache.get(&b)?;
+        cache.get(&a)?; // `a` is now more recently used than `b`
+        cache.get(&c)?; // evicts `b`
+        assert_eq!(cache.stats(), CacheStats { hits: 2, misses: 3, entries: 2 });
+        cache.get(&a)?;
+        cache.get(&b)?;
+        assert_eq!(cache.stats(), CacheStats { hits: 3, misses: 4, entries: 2 });
+
+        assert!(cache.get(b"not a pdf").is_err());
+        assert_eq!(cache.stats().entries, 2);
+        cache.clear();
+        assert_eq!(cache.stats().entries, 0);
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_metadata_cache_rereads_changed_files() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("metadata_cache");
+        let file = test_dir.join("doc.pdf");
+        create_minimal_test_pdf(&file)?;
+        update_metadata_in_place(&file, "Title", "First")?;
+        let title = |cached: &CachedMetadata| match cached.values.iter().find(|(key, _)| key == "Title") {
+            Some((_, MetadataValue::String(text))) => Some(text.clone()),
+            _ => None,
+        };
+
+        let cache = MetadataCache::new(2);
+        let first = cache.get(&file)?;
+        assert_eq!(title(&first).as_deref(), Some("First"));
+        assert!(first.values.iter().any(|(key, value)| key == "ModDate" && matches!(value, MetadataValue::Date(_))));
+        assert!(Arc::ptr_eq(&first, &cache.get(&file)?));
+        assert_eq!(cache.stats(), CacheStats { hits: 1, misses: 1, entries: 1 });
+
+        // Same size, new modification time.
+        update_metadata_in_place(&file, "Title", "Other")?;
+        fs::File::options().write(true).open(&file)?.set_modified(first.modified + Duration::from_secs(10))?;
+        assert_eq!(title(&*cache.get(&file)?).as_deref(), Some("Other"));
+        assert_eq!(cache.stats(), CacheStats { hits: 1, misses: 2, entries: 1 });
+
+        // Same size and modification time, as with `preserve_mtime`.
+        #[cfg(unix)]
+        {
+            let before = cache.get(&file)?;
+            let bytes = fs::read(&file)?;
+            let at = bytes.windows(7).position(|window| window == b"(Other)").unwrap();
+            let mut edited = bytes.clone();
+            edited[at..at + 7].copy_from_slice(b"(Third)");
+            fs::write(&file, edited)?;
+            fs::File::options().write(true).open(&file)?.set_modified(before.modified)?;
+            assert_eq!(fs::metadata(&file)?.modified()?, before.modified);
+            assert_eq!(title(&*cache.get(&file)?).as_deref(), Some("Third"));
+        }
+
+        cache.invalidate(&file);
+        assert_eq!(cache.stats().entries, 0);
+        fs::remove_file(&file)?;
+        assert!(cache.get(&file).is_err());
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+}
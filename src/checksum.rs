@@ -0,0 +1,242 @@
+//! Content checksums for tamper evidence.
+//!
+//! [`stamp_checksum`] stores a digest of the document's content in the Info
+//! dictionary, and [`verify_checksum`] recomputes it. The digest covers the parsed
+//! objects rather than the file bytes, so it survives the rewrites that metadata
+//! edits cause: the Info dictionary, the XMP packet and the trailer (with `/ID`) are
+//! left out, as are the cross-reference and object streams a writer may reorganize.
+//! Any change to pages, fonts, images, annotations or other objects is detected.
+
+use crate::{Error, Result, apply_entries_with, load_document, options, read_info_entries, write_file};
+use lopdf::{Dictionary, Document, Object, ObjectId};
+use sha2::{Digest, Sha256, Sha512};
+use std::path::Path;
+
+/// The Info key the checksum is stored under, as `<algorithm>:<hex digest>`.
+pub const CHECKSUM_KEY: &str = "ContentChecksum";
+
+/// The digest [`stamp_checksum`] computes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ChecksumAlgorithm {
+    #[default]
+    Sha256,
+    Sha512,
+}
+
+impl ChecksumAlgorithm {
+    /// The name stored before the digest, e.g. `sha256`.
+    fn name(self) -> &'static str {
+        match self {
+            ChecksumAlgorithm::Sha256 => "sha256",
+            ChecksumAlgorithm::Sha512 => "sha512",
+        }
+    }
+}
+
+impl std::str::FromStr for ChecksumAlgorithm {
+    type Err = Error;
+
+    /// Parses `sha256` or `sha512`.
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "sha256" => Ok(ChecksumAlgorithm::Sha256),
+            "sha512" => Ok(ChecksumAlgorithm::Sha512),
+            other => Err(Error::UnsupportedFormat(format!("checksum algorithm '{}'", other))),
+        }
+    }
+}
+
+/// The result of [`verify_checksum`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ChecksumStatus {
+    /// The content matches the stored checksum.
+    Valid,
+    /// The content was changed after the checksum was stored.
+    Mismatch,
+    /// The document has no stored checksum.
+    Missing,
+}
+
+/// Computes a digest of the document's content and stores it in the Info dictionary
+/// under [`CHECKSUM_KEY`], writing the result to `output_path`.
+///
+/// The digest covers every object but the Info dictionary, the XMP packet and the
+/// trailer, so later metadata edits keep it valid while any change to pages, fonts,
+/// images or annotations is detected. `ModDate` is refreshed. `output_path` may be
+/// the input file.
+///
+/// # Returns
+///
+/// * `Ok(String)`: The stored value, e.g. `sha256:9F86D0...`.
+/// * `Err(Error)`: If the file cannot be read, parsed or written.
+///
+/// # Example
+///
+/// ```no_run
+/// use pdf_metadata::{ChecksumAlgorithm, ChecksumStatus, stamp_checksum, verify_checksum};
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     stamp_checksum("archive/contract.pdf", "archive/contract.pdf", ChecksumAlgorithm::Sha256)?;
+///     assert_eq!(verify_checksum("archive/contract.pdf")?, ChecksumStatus::Valid);
+///     Ok(())
+/// }
+/// ```
+pub fn stamp_checksum<P: AsRef<Path>, Q: AsRef<Path>>(file_path: P, output_path: Q, algorithm: ChecksumAlgorithm) -> Result<String> {
+    let options = options::current();
+    write_file(file_path.as_ref(), Some(output_path.as_ref()), &options, |doc| {
+        let checksum = content_checksum(doc, algorithm);
+        apply_entries_with(doc, &[(CHECKSUM_KEY, checksum.as_str())], &options)?;
+        Ok(checksum)
+    })
+}
+
+/// Recomputes the checksum stored by [`stamp_checksum`] and compares it.
+///
+/// # Returns
+///
+/// * `Ok(ChecksumStatus)`: Whether the content matches, or `Missing` if no checksum
+///   is stored.
+/// * `Err(Error::UnsupportedFormat)`: If the stored value names an unknown algorithm.
+/// * `Err(Error)`: If the file cannot be read or parsed.
+pub fn verify_checksum<P: AsRef<Path>>(file_path: P) -> Result<ChecksumStatus> {
+    let file_path = file_path.as_ref();
+    let doc = load_document(file_path)?;
+    let Some((_, stored)) = read_info_entries(&doc).into_iter().find(|(key, _)| key == CHECKSUM_KEY) else {
+        return Ok(ChecksumStatus::Missing);
+    };
+    let Some((name, _)) = stored.split_once(':') else {
+        return Err(Error::UnsupportedFormat(format!("checksum '{}'", stored)));
+    };
+    let actual = content_checksum(&doc, name.parse()?);
+    Ok(if actual.eq_ignore_ascii_case(stored.trim()) { ChecksumStatus::Valid } else { ChecksumStatus::Mismatch })
+}
+
+/// The checksum of `doc` as stored: the algorithm name, a colon and the hex digest.
+fn content_checksum(doc: &Document, algorithm: ChecksumAlgorithm) -> String {
+    let digest = match algorithm {
+        ChecksumAlgorithm::Sha256 => content_digest::<Sha256>(doc),
+        ChecksumAlgorithm::Sha512 => content_digest::<Sha512>(doc),
+    };
+    let hex: String = digest.iter().map(|byte| format!("{:02X}", byte)).collect();
+    format!("{}:{}", algorithm.name(), hex)
+}
+
+fn content_digest<D: Digest>(doc: &Document) -> Vec<u8> {
+    let info_id = doc.trailer.get(b"Info").and_then(Object::as_reference).ok();
+    let root_id = doc.trailer.get(b"Root").and_then(Object::as_reference).ok();
+    let metadata_id = doc.catalog().and_then(|catalog| catalog.get(b"Metadata")).and_then(Object::as_reference).ok();
+
+    let mut hasher = D::new();
+    for (&id, object) in &doc.objects {
+        if Some(id) == info_id || Some(id) == metadata_id || is_structural(object) {
+            continue;
+        }
+        hash_id(&mut hasher, id);
+        match object {
+            // The catalog gains `/Metadata` when a packet is first written.
+            Object::Dictionary(catalog) if Some(id) == root_id => hash_dict(&mut hasher, catalog, &[b"Metadata"]),
+            object => hash_object(&mut hasher, object),
+        }
+    }
+    hasher.finalize().to_vec()
+}
+
+/// Whether `object` is a cross-reference or object stream, which only a writer's
+/// choice of file structure puts there.
+fn is_structural(object: &Object) -> bool {
+    let Object::Stream(stream) = object else { return false };
+    matches!(stream.dict.get(b"Type").and_then(Object::as_name), Ok(b"XRef" | b"ObjStm"))
+}
+
+fn hash_id(hasher: &mut impl Digest, (number, generation): ObjectId) {
+    hasher.update(number.to_be_bytes());
+    hasher.update(generation.to_be_bytes());
+}
+
+/// Hashes `object` unambiguously: each value is tagged with its type, and strings,
+/// names and containers with their length.
+fn hash_object(hasher: &mut impl Digest, object: &Object) {
+    match object {
+        Object::Null => hash_tagged(hasher, b'n', &[]),
+        Object::Boolean(value) => hash_tagged(hasher, b'b', &[u8::from(*value)]),
+        Object::Integer(value) => hash_tagged(hasher, b'i', &value.to_be_bytes()),
+        Object::Real(value) => hash_tagged(hasher, b'r', &value.to_be_bytes()),
+        Object::Name(name) => hash_tagged(hasher, b'/', name),
+        Object::String(text, _) => hash_tagged(hasher, b's', text),
+        Object::Array(items) => {
+            hash_tagged(hasher, b'[', &(items.len() as u64).to_be_bytes());
+            for item in items {
+                hash_object(hasher, item);
+            }
+        }
+        Object::Dictionary(dict) => hash_dict(hasher, dict, &[]),
+        // `/Length` may be a reference in the source and a number once rewritten.
+        Object::Stream(stream) => {
+            hash_dict(hasher, &stream.dict, &[b"Length"]);
+            hash_tagged(hasher, b'S', &stream.content);
+        }
+        Object::Reference(id) => {
+            hasher.update([b'R']);
+            hash_id(hasher, *id);
+        }
+    }
+}
+
+fn hash_tagged(hasher: &mut impl Digest, tag: u8, bytes: &[u8]) {
+    hasher.update([tag]);
+    hasher.update((bytes.len() as u64).to_be_bytes());
+    hasher.update(bytes);
+}
+
+/// Hashes the entries of `dict` but `skipped`, sorted by key.
+fn hash_dict(hasher: &mut impl Digest, dict: &Dictionary, skipped: &[&[u8]]) {
+    let mut entries: Vec<_> = dict.iter().filter(|(key, _)| !skipped.contains(&key.as_slice())).collect();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+    hasher.update([b'<']);
+    hasher.update((entries.len() as u64).to_be_bytes());
+    for (key, value) in entries {
+        hash_object(hasher, &Object::Name(key.clone()));
+        hash_object(hasher, value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{create_minimal_test_pdf, setup_unique_test_dir};
+    use crate::{update_metadata_in_place, xmp};
+    use std::fs;
+
+    #[test]
+    fn test_stamp_and_verify_checksum() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("checksum");
+        let file = test_dir.join("doc.pdf");
+        create_minimal_test_pdf(&file)?;
+        let file_str = file.to_str().unwrap();
+
+        assert_eq!(verify_checksum(file_str)?, ChecksumStatus::Missing);
+        let stamped = stamp_checksum(file_str, file_str, ChecksumAlgorithm::Sha256)?;
+        assert!(stamped.starts_with("sha256:") && stamped.len() == "sha256:".len() + 64);
+        assert_eq!(verify_checksum(file_str)?, ChecksumStatus::Valid);
+
+        // Metadata edits, including a new XMP packet, keep the checksum valid.
+        update_metadata_in_place(file_str, "Title", "Contrato")?;
+        xmp::set_xmp_custom(file_str, "http://example.com/ns/", "ex", "box", "12")?;
+        assert_eq!(verify_checksum(file_str)?, ChecksumStatus::Valid);
+
+        // Any other change is detected.
+        let mut doc = Document::load(&file)?;
+        doc.add_object(Object::string_literal("inserted"));
+        doc.save(&file)?;
+        assert_eq!(verify_checksum(file_str)?, ChecksumStatus::Mismatch);
+
+        let sha512 = stamp_checksum(file_str, file_str, ChecksumAlgorithm::Sha512)?;
+        assert!(sha512.starts_with("sha512:"));
+        assert_eq!(verify_checksum(file_str)?, ChecksumStatus::Valid);
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+}
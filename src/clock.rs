@@ -0,0 +1,265 @@
+//! The source of the current time.
+//!
+//! Writes stamp `ModDate` (and page `LastModified`) with the current time, and
+//! output patterns and templates expand `{date}` with today's date. They all ask
+//! the active [`Clock`], which is the default clock unless one was installed:
+//! [`set_clock`] replaces it for the whole process, for example to pin dates in a
+//! reproducible build, and [`with_clock`] for a closure on the current thread, so
+//! tests running in parallel can each use their own.
+//!
+//! Without either, the `SOURCE_DATE_EPOCH` environment variable is honoured as
+//! the [reproducible builds](https://reproducible-builds.org/docs/source-date-epoch/)
+//! convention asks: the current time is that many seconds after the Unix epoch, in
+//! UTC, and dates in the Info dictionary later than it are clamped to it when a
+//! document is saved. lopdf writes objects in a fixed order, and the XMP document
+//! and instance IDs this crate adds are then derived from the epoch and the document
+//! instead of drawn at random, so the same input gives byte-identical output.
+//!
+//! The default clock is [`SystemClock`] (`std-fs` feature). The WebAssembly bindings
+//! read JavaScript's `Date` instead, because the standard library has no clock in
+//! browsers; builds with neither report the Unix epoch until a clock is installed.
+//!
+//! ```
+//! use chrono::DateTime;
+//! use pdf_metadata::{FixedClock, with_clock};
+//!
+//! let pinned = DateTime::parse_from_rfc3339("2024-01-02T03:04:05+00:00").unwrap();
+//! with_clock(FixedClock(pinned), || {
+//!     // Writes made here get `ModDate` D:20240102030405+00'00'.
+//! });
+//! ```
+
+use crate::{format_pdf_date, info_dict_mut, parse_pdf_date};
+use chrono::{DateTime, FixedOffset};
+#[cfg(feature = "std-fs")]
+use chrono::Local;
+use lopdf::{Document, Object};
+use std::cell::RefCell;
+use std::sync::{Arc, RwLock};
+
+/// A source of the current time. Closures returning a `DateTime<FixedOffset>`
+/// are clocks too.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<FixedOffset>;
+}
+
+/// The system clock, in the local time zone. The default (`std-fs` feature).
+#[cfg(feature = "std-fs")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SystemClock;
+
+#[cfg(feature = "std-fs")]
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<FixedOffset> {
+        Local::now().fixed_offset()
+    }
+}
+
+/// A clock that always reports the same time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedClock(pub DateTime<FixedOffset>);
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<FixedOffset> {
+        self.0
+    }
+}
+
+impl<F: Fn() -> DateTime<FixedOffset> + Send + Sync> Clock for F {
+    fn now(&self) -> DateTime<FixedOffset> {
+        self()
+    }
+}
+
+static PROCESS_CLOCK: RwLock<Option<Arc<dyn Clock>>> = RwLock::new(None);
+
+thread_local! {
+    static THREAD_CLOCKS: RefCell<Vec<Arc<dyn Clock>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Makes `clock` the source of the current time for the whole process, except
+/// inside [`with_clock`].
+pub fn set_clock<C: Clock + 'static>(clock: C) {
+    *PROCESS_CLOCK.write().unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(clock));
+}
+
+/// Goes back to the system clock after [`set_clock`].
+pub fn reset_clock() {
+    *PROCESS_CLOCK.write().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+/// Runs `f` with `clock` as the source of the current time on this thread.
+///
+/// Work `f` hands to other threads, such as the workers of the
+/// [batch engine](crate::batch), still uses the process clock; only
+/// [`with_timeout`](crate::with_timeout) carries the scope over to its worker.
+pub fn with_clock<C: Clock + 'static, T>(clock: C, f: impl FnOnce() -> T) -> T {
+    with_thread_clock(Some(Arc::new(clock)), f)
+}
+
+/// The clock of the innermost [`with_clock`] on this thread, if any.
+pub(crate) fn thread_clock() -> Option<Arc<dyn Clock>> {
+    THREAD_CLOCKS.with(|clocks| clocks.borrow().last().cloned())
+}
+
+/// Runs `f` with `clock`, as returned by [`thread_clock`], as this thread's clock.
+pub(crate) fn with_thread_clock<T>(clock: Option<Arc<dyn Clock>>, f: impl FnOnce() -> T) -> T {
+    /// Removes the clock again, also when `f` panics.
+    struct Restore;
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            THREAD_CLOCKS.with(|clocks| clocks.borrow_mut().pop());
+        }
+    }
+
+    let Some(clock) = clock else { return f() };
+    THREAD_CLOCKS.with(|clocks| clocks.borrow_mut().push(clock));
+    let _restore = Restore;
+    f()
+}
+
+/// The current time according to the active clock: the thread's, the process's,
+/// `SOURCE_DATE_EPOCH` or the default clock, in that order.
+pub(crate) fn now() -> DateTime<FixedOffset> {
+    if let Some(clock) = thread_clock() {
+        return clock.now();
+    }
+    let process = PROCESS_CLOCK.read().unwrap_or_else(|e| e.into_inner()).clone();
+    match process {
+        Some(clock) => clock.now(),
+        None => source_date_epoch().unwrap_or_else(default_now),
+    }
+}
+
+#[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+fn default_now() -> DateTime<FixedOffset> {
+    crate::wasm::JsClock.now()
+}
+
+#[cfg(all(feature = "std-fs", not(all(feature = "wasm", target_arch = "wasm32"))))]
+fn default_now() -> DateTime<FixedOffset> {
+    SystemClock.now()
+}
+
+#[cfg(not(any(feature = "std-fs", all(feature = "wasm", target_arch = "wasm32"))))]
+fn default_now() -> DateTime<FixedOffset> {
+    DateTime::UNIX_EPOCH.fixed_offset()
+}
+
+/// The time `SOURCE_DATE_EPOCH` is set to, if it is set to a valid timestamp.
+pub(crate) fn source_date_epoch() -> Option<DateTime<FixedOffset>> {
+    parse_source_date_epoch(&std::env::var("SOURCE_DATE_EPOCH").ok()?)
+}
+
+/// Parses the decimal seconds since the Unix epoch the variable holds.
+fn parse_source_date_epoch(value: &str) -> Option<DateTime<FixedOffset>> {
+    let seconds = value.trim().parse().ok()?;
+    Some(DateTime::from_timestamp(seconds, 0)?.fixed_offset())
+}
+
+/// Clamps `CreationDate` and `ModDate` to `SOURCE_DATE_EPOCH`, when it is set, before
+/// `doc` is saved.
+pub(crate) fn clamp_to_source_date(doc: &mut Document) -> crate::Result<()> {
+    match source_date_epoch() {
+        Some(epoch) if doc.trailer.has(b"Info") => clamp_dates(doc, epoch),
+        _ => Ok(()),
+    }
+}
+
+fn clamp_dates(doc: &mut Document, epoch: DateTime<FixedOffset>) -> crate::Result<()> {
+    // Only a date that needs clamping touches the Info dictionary, so an incremental
+    // update does not append it for nothing.
+    let later: Vec<&[u8]> = match doc.trailer.get(b"Info").and_then(Object::as_reference) {
+        Ok(id) => {
+            let info_dict = doc.get_dictionary(id)?;
+            [&b"CreationDate"[..], b"ModDate"]
+                .into_iter()
+                .filter(|key| {
+                    info_dict
+                        .get(key)
+                        .and_then(Object::as_str)
+                        .ok()
+                        .and_then(|date| parse_pdf_date(&String::from_utf8_lossy(date)).ok())
+                        .is_some_and(|date| date > epoch)
+                })
+                .collect()
+        }
+        Err(_) => Vec::new(),
+    };
+    if !later.is_empty() {
+        let info_dict = info_dict_mut(doc)?;
+        for key in later {
+            info_dict.set(key.to_vec(), Object::string_literal(format_pdf_date(&epoch)));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{create_minimal_test_pdf, setup_unique_test_dir, with_source_date_epoch};
+    use crate::{MetadataValue, get_metadata_values, set_metadata, update_metadata_in_place, update_metadata_incremental};
+    use std::fs;
+
+    #[test]
+    fn test_with_clock_pins_mod_date() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("clock_pinned");
+        let file = test_dir.join("doc.pdf");
+        create_minimal_test_pdf(&file)?;
+        let file_str = file.to_str().unwrap();
+        let pinned = DateTime::parse_from_rfc3339("2001-02-03T04:05:06-03:00")?;
+        let later = DateTime::parse_from_rfc3339("2001-02-03T05:00:00-03:00")?;
+
+        with_clock(FixedClock(pinned), || {
+            assert_eq!(now(), pinned);
+            with_clock(move || later, || assert_eq!(now(), later));
+            assert_eq!(now(), pinned);
+            update_metadata_in_place(file_str, "Title", "Pinned")
+        })?;
+        assert_ne!(now(), pinned);
+
+        let values = get_metadata_values(file_str)?;
+        assert!(values.contains(&("ModDate".to_string(), MetadataValue::Date(pinned))));
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_source_date_epoch_gives_identical_output() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let epoch = parse_source_date_epoch("4102444800").unwrap();
+        assert_eq!(format_pdf_date(&epoch), "D:21000101000000+00'00'");
+        assert_eq!(parse_source_date_epoch("soon"), None);
+
+        let test_dir = setup_unique_test_dir("clock_reproducible");
+        let file = test_dir.join("doc.pdf");
+        create_minimal_test_pdf(&file)?;
+        let file_str = file.to_str().unwrap();
+        crate::update_entries_in_place(file_str, &[("CreationDate", "D:21991231000000Z"), ("Title", "Build")])?;
+        let (a, b, c, d) = (test_dir.join("a.pdf"), test_dir.join("b.pdf"), test_dir.join("c.pdf"), test_dir.join("d.pdf"));
+        fs::copy(&file, &c)?;
+        fs::copy(&file, &d)?;
+
+        with_source_date_epoch(4_102_444_800, || -> crate::Result<()> {
+            assert_eq!(now(), epoch);
+            for output in [&a, &b] {
+                set_metadata(file_str, output, "Subject", "CI")?;
+            }
+            for output in [&c, &d] {
+                update_metadata_incremental(output, "Subject", "CI")?;
+            }
+            Ok(())
+        })?;
+        assert_eq!(fs::read(&a)?, fs::read(&b)?);
+        assert_eq!(fs::read(&c)?, fs::read(&d)?);
+        for output in [&a, &c] {
+            let values = get_metadata_values(output)?;
+            assert!(values.contains(&("CreationDate".to_string(), MetadataValue::Date(epoch))), "{}", output.display());
+            assert!(values.contains(&("ModDate".to_string(), MetadataValue::Date(epoch))), "{}", output.display());
+        }
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,269 @@
+//! Encodings of Info string values.
+//!
+//! Every write turns a value into the bytes of a string object with the active write
+//! codec: [`StandardCodec`], which stores ASCII as-is and everything else as UTF-16BE,
+//! unless [`set_write_codec`] replaced it for the whole process or [`with_write_codec`]
+//! for a closure on the current thread. [`Utf16BeCodec`] and [`PdfDocCodec`] write the
+//! two encodings of the PDF specification only, and [`LegacyBase64Codec`] the
+//! `UTF16BE:` + BASE64 form of early versions of this crate, which other tools show
+//! verbatim and which is only useful to feed those versions.
+//!
+//! Reads try the codecs added with [`register_codec`], newest first, and then the
+//! built-in decoding, which already understands everything the built-in codecs write.
+//! Registering is for encodings of an application's own.
+//!
+//! ```
+//! use pdf_metadata::{Utf16BeCodec, normalize_value, with_write_codec};
+//!
+//! with_write_codec(Utf16BeCodec, || {
+//!     // Writes made here store even "Report" as UTF-16BE.
+//!     assert_eq!(normalize_value("Report"), "Report");
+//! });
+//! ```
+
+use crate::{encode_info_string, pdf_doc_encoding, text_string};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use lopdf::StringFormat;
+use std::cell::RefCell;
+use std::sync::{Arc, RwLock};
+
+/// An encoding of string values in the Info dictionary.
+pub trait ValueCodec: Send + Sync {
+    /// Encodes `value` as the bytes of a string object and the notation to write them
+    /// in, or returns `None` if the encoding cannot represent it. Writes then fall back
+    /// to [`StandardCodec`].
+    fn encode(&self, value: &str) -> Option<(Vec<u8>, StringFormat)>;
+
+    /// Decodes the bytes of a string object written in `format`, or returns `None` if
+    /// they are not in this encoding.
+    fn decode(&self, bytes: &[u8], format: StringFormat) -> Option<String>;
+}
+
+/// Printable ASCII, tabs and line breaks as-is, anything else as UTF-16BE with a byte
+/// order mark in hex notation. The default write codec. Decodes like the built-in
+/// decoding.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StandardCodec;
+
+impl ValueCodec for StandardCodec {
+    fn encode(&self, value: &str) -> Option<(Vec<u8>, StringFormat)> {
+        let bytes = encode_info_string(value);
+        let format = if bytes.starts_with(&[0xFE, 0xFF]) { StringFormat::Hexadecimal } else { StringFormat::Literal };
+        Some((bytes, format))
+    }
+
+    fn decode(&self, bytes: &[u8], format: StringFormat) -> Option<String> {
+        Some(text_string::decode_text_string(bytes, format).0)
+    }
+}
+
+/// Every value as UTF-16BE with a byte order mark, in hex notation.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Utf16BeCodec;
+
+impl ValueCodec for Utf16BeCodec {
+    fn encode(&self, value: &str) -> Option<(Vec<u8>, StringFormat)> {
+        Some((utf16be(value), StringFormat::Hexadecimal))
+    }
+
+    fn decode(&self, bytes: &[u8], _format: StringFormat) -> Option<String> {
+        bytes.starts_with(&[0xFE, 0xFF]).then(|| text_string::decode_text_string(bytes, StringFormat::Hexadecimal).0)
+    }
+}
+
+/// Values as PDFDocEncoding, the single-byte encoding of the PDF specification, in
+/// literal notation.
+///
+/// Values with characters PDFDocEncoding lacks cannot be encoded, nor can the rare
+/// ones whose encoding would also be valid UTF-8, which the built-in decoding reads
+/// as UTF-8. Decoding takes any string without a byte order mark as PDFDocEncoding,
+/// so registering this codec turns off the UTF-8 reading of unmarked strings.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PdfDocCodec;
+
+impl ValueCodec for PdfDocCodec {
+    fn encode(&self, value: &str) -> Option<(Vec<u8>, StringFormat)> {
+        let bytes = pdf_doc_encoding::encode(value)?;
+        if !bytes.is_ascii() && std::str::from_utf8(&bytes).is_ok() {
+            return None;
+        }
+        Some((bytes, StringFormat::Literal))
+    }
+
+    fn decode(&self, bytes: &[u8], _format: StringFormat) -> Option<String> {
+        let marked = [&[0xFE, 0xFF][..], &[0xFF, 0xFE], &[0xEF, 0xBB, 0xBF]].iter().any(|bom| bytes.starts_with(bom));
+        (!marked).then(|| pdf_doc_encoding::decode(bytes))
+    }
+}
+
+/// The literal string `UTF16BE:` followed by the BASE64 of the value in UTF-16BE with
+/// a byte order mark, as early versions of this crate wrote Unicode values.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LegacyBase64Codec;
+
+impl ValueCodec for LegacyBase64Codec {
+    fn encode(&self, value: &str) -> Option<(Vec<u8>, StringFormat)> {
+        let mut bytes = text_string::LEGACY_PREFIX.to_vec();
+        bytes.extend_from_slice(BASE64.encode(utf16be(value)).as_bytes());
+        Some((bytes, StringFormat::Literal))
+    }
+
+    fn decode(&self, bytes: &[u8], format: StringFormat) -> Option<String> {
+        let utf16 = text_string::unwrap_legacy(bytes).filter(|_| format == StringFormat::Literal)?;
+        Some(text_string::decode_text_string(&utf16, StringFormat::Hexadecimal).0)
+    }
+}
+
+static PROCESS_WRITE_CODEC: RwLock<Option<Arc<dyn ValueCodec>>> = RwLock::new(None);
+static READ_CODECS: RwLock<Vec<Arc<dyn ValueCodec>>> = RwLock::new(Vec::new());
+
+thread_local! {
+    static THREAD_WRITE_CODECS: RefCell<Vec<Arc<dyn ValueCodec>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Makes `codec` encode the values of every write in the process, except inside
+/// [`with_write_codec`].
+pub fn set_write_codec<C: ValueCodec + 'static>(codec: C) {
+    *PROCESS_WRITE_CODEC.write().unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(codec));
+}
+
+/// Runs `f` with `codec` encoding the values written on this thread.
+///
+/// Work `f` hands to other threads, such as the workers of the
+/// [batch engine](crate::batch), still uses the process codec; only
+/// [`with_timeout`](crate::with_timeout) carries the scope over to its worker.
+pub fn with_write_codec<C: ValueCodec + 'static, T>(codec: C, f: impl FnOnce() -> T) -> T {
+    with_thread_write_codec(Some(Arc::new(codec)), f)
+}
+
+/// The codec of the innermost [`with_write_codec`] on this thread, if any.
+pub(crate) fn thread_write_codec() -> Option<Arc<dyn ValueCodec>> {
+    THREAD_WRITE_CODECS.with(|codecs| codecs.borrow().last().cloned())
+}
+
+/// Runs `f` with `codec`, as returned by [`thread_write_codec`], as this thread's
+/// write codec.
+pub(crate) fn with_thread_write_codec<T>(codec: Option<Arc<dyn ValueCodec>>, f: impl FnOnce() -> T) -> T {
+    /// Removes the codec again, also when `f` panics.
+    struct Restore;
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            THREAD_WRITE_CODECS.with(|codecs| codecs.borrow_mut().pop());
+        }
+    }
+
+    let Some(codec) = codec else { return f() };
+    THREAD_WRITE_CODECS.with(|codecs| codecs.borrow_mut().push(codec));
+    let _restore = Restore;
+    f()
+}
+
+/// Adds `codec` to the decodings every read in the process tries before the built-in
+/// one. Codecs registered later are tried first.
+pub fn register_codec<C: ValueCodec + 'static>(codec: C) {
+    READ_CODECS.write().unwrap_or_else(|e| e.into_inner()).push(Arc::new(codec));
+}
+
+/// Goes back to [`StandardCodec`] for writes and to the built-in decoding alone for
+/// reads, undoing [`set_write_codec`] and [`register_codec`].
+pub fn reset_codecs() {
+    *PROCESS_WRITE_CODEC.write().unwrap_or_else(|e| e.into_inner()) = None;
+    READ_CODECS.write().unwrap_or_else(|e| e.into_inner()).clear();
+}
+
+/// Encodes `value` with the active write codec: the thread's, the process's or
+/// [`StandardCodec`], which also covers values the active codec cannot encode.
+pub(crate) fn encode(value: &str) -> (Vec<u8>, StringFormat) {
+    let active = thread_write_codec()
+        .or_else(|| PROCESS_WRITE_CODEC.read().unwrap_or_else(|e| e.into_inner()).clone());
+    active
+        .and_then(|codec| codec.encode(value))
+        .or_else(|| StandardCodec.encode(value))
+        .expect("the standard codec encodes every value")
+}
+
+/// Decodes with the registered codecs, newest first, if one recognizes the bytes.
+pub(crate) fn decode_registered(bytes: &[u8], format: StringFormat) -> Option<String> {
+    let codecs = READ_CODECS.read().unwrap_or_else(|e| e.into_inner());
+    codecs.iter().rev().find_map(|codec| codec.decode(bytes, format))
+}
+
+fn utf16be(value: &str) -> Vec<u8> {
+    let mut bytes = vec![0xFE, 0xFF];
+    bytes.extend(value.encode_utf16().flat_map(u16::to_be_bytes));
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{create_minimal_test_pdf, setup_unique_test_dir};
+    use crate::{get_pdf_metadata, set_pdf_metadata};
+    use lopdf::{Document, Object};
+    use std::fs;
+
+    /// An application encoding: `ROT13:` and the value with ASCII letters rotated.
+    struct Rot13;
+
+    fn rot13(text: &str) -> String {
+        text.chars()
+            .map(|c| match c {
+                'a'..='z' => (((c as u8 - b'a') + 13) % 26 + b'a') as char,
+                'A'..='Z' => (((c as u8 - b'A') + 13) % 26 + b'A') as char,
+                c => c,
+            })
+            .collect()
+    }
+
+    impl ValueCodec for Rot13 {
+        fn encode(&self, value: &str) -> Option<(Vec<u8>, StringFormat)> {
+            value.is_ascii().then(|| (format!("ROT13:{}", rot13(value)).into_bytes(), StringFormat::Literal))
+        }
+
+        fn decode(&self, bytes: &[u8], _format: StringFormat) -> Option<String> {
+            Some(rot13(std::str::from_utf8(bytes.strip_prefix(b"ROT13:")?).ok()?))
+        }
+    }
+
+    #[test]
+    fn test_write_codecs() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("value_codecs");
+        let file = test_dir.join("doc.pdf");
+        create_minimal_test_pdf(&file)?;
+        let base = fs::read(&file)?;
+        let stored = |pdf: &[u8]| -> std::result::Result<Object, Box<dyn std::error::Error>> {
+            let doc = Document::load_mem(pdf)?;
+            let info = doc.get_dictionary(doc.trailer.get(b"Info")?.as_reference()?)?;
+            Ok(info.get(b"Title")?.clone())
+        };
+        let title = |pdf: &[u8]| get_pdf_metadata(pdf).map(|entries| entries.into_iter().find(|(k, _)| k == "Title").map(|(_, v)| v));
+
+        for value in ["Report", "Relatório – 2024"] {
+            let pdf = with_write_codec(Utf16BeCodec, || set_pdf_metadata(&base, "Title", value))?;
+            assert_eq!(stored(&pdf)?, Object::String(utf16be(value), StringFormat::Hexadecimal));
+            assert_eq!(title(&pdf)?.as_deref(), Some(value));
+
+            let pdf = with_write_codec(LegacyBase64Codec, || set_pdf_metadata(&base, "Title", value))?;
+            assert!(stored(&pdf)?.as_str()?.starts_with(b"UTF16BE:"));
+            assert_eq!(title(&pdf)?.as_deref(), Some(value));
+        }
+
+        let pdf = with_write_codec(PdfDocCodec, || set_pdf_metadata(&base, "Title", "Relatório – 2024"))?;
+        assert_eq!(stored(&pdf)?.as_str()?, b"Relat\xF3rio \x85 2024");
+        assert_eq!(title(&pdf)?.as_deref(), Some("Relatório – 2024"));
+        let pdf = with_write_codec(PdfDocCodec, || set_pdf_metadata(&base, "Title", "日本"))?;
+        assert_eq!(stored(&pdf)?, Object::String(utf16be("日本"), StringFormat::Hexadecimal), "falls back to standard");
+
+        let pdf = with_write_codec(Rot13, || set_pdf_metadata(&base, "Title", "Hello"))?;
+        assert_eq!(stored(&pdf)?.as_str()?, b"ROT13:Uryyb");
+        assert_eq!(title(&pdf)?.as_deref(), Some("ROT13:Uryyb"), "unregistered codecs are not read");
+        register_codec(Rot13);
+        let decoded = title(&pdf)?;
+        READ_CODECS.write().unwrap().pop();
+        assert_eq!(decoded.as_deref(), Some("Hello"));
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,221 @@
+//! Conversion of metadata values to application types.
+//!
+//! Custom Info entries are strings more often than not, even when they hold numbers,
+//! flags or dates. [`get_metadata_as`] reads one entry and converts it with
+//! [`FromMetadataValue`], accepting both the typed PDF object and its usual string
+//! spellings.
+
+use crate::value::MetadataValue;
+use crate::parse_pdf_date;
+#[cfg(feature = "std-fs")]
+use crate::{Error, Result, get_metadata_values};
+use chrono::{DateTime, FixedOffset};
+#[cfg(feature = "std-fs")]
+use std::path::Path;
+
+/// Types an Info value can be converted to by [`get_metadata_as`].
+///
+/// | Type | Accepted values |
+/// |------|-----------------|
+/// | `i64` | integers; reals without a fractional part; decimal strings |
+/// | `f64` | integers; reals; decimal strings |
+/// | `bool` | booleans; `true`/`false`, `yes`/`no`, `1`/`0` as names or strings (case-insensitive) |
+/// | `DateTime<FixedOffset>` | PDF dates (`D:YYYYMMDDHHmmSS...`); RFC 3339 strings |
+/// | `Vec<String>` | strings split on `,` or `;`, trimmed, empty items dropped; a name as a single item; arrays of strings and names |
+///
+/// `String` accepts any string or name. Surrounding whitespace is ignored by every
+/// string conversion.
+pub trait FromMetadataValue: Sized {
+    /// Name of the target type, used in [`Error::Conversion`].
+    const TARGET: &'static str;
+
+    /// Converts `value`, or returns `None` if it has no sensible interpretation as `Self`.
+    fn from_metadata_value(value: &MetadataValue) -> Option<Self>;
+}
+
+impl FromMetadataValue for i64 {
+    const TARGET: &'static str = "integer";
+
+    fn from_metadata_value(value: &MetadataValue) -> Option<Self> {
+        match value {
+            MetadataValue::Integer(i) => Some(*i),
+            MetadataValue::Real(f) if f.fract() == 0.0 && f.is_finite() => Some(*f as i64),
+            MetadataValue::String(s) => s.trim().parse().ok(),
+            _ => None,
+        }
+    }
+}
+
+impl FromMetadataValue for f64 {
+    const TARGET: &'static str = "number";
+
+    fn from_metadata_value(value: &MetadataValue) -> Option<Self> {
+        match value {
+            MetadataValue::Integer(i) => Some(*i as f64),
+            MetadataValue::Real(f) => Some(f64::from(*f)),
+            MetadataValue::String(s) => s.trim().parse().ok().filter(|f: &f64| f.is_finite()),
+            _ => None,
+        }
+    }
+}
+
+impl FromMetadataValue for bool {
+    const TARGET: &'static str = "boolean";
+
+    fn from_metadata_value(value: &MetadataValue) -> Option<Self> {
+        match value {
+            MetadataValue::Boolean(b) => Some(*b),
+            MetadataValue::String(s) | MetadataValue::Name(s) => match s.trim().to_ascii_lowercase().as_str() {
+                "true" | "yes" | "1" => Some(true),
+                "false" | "no" | "0" => Some(false),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+impl FromMetadataValue for DateTime<FixedOffset> {
+    const TARGET: &'static str = "date";
+
+    fn from_metadata_value(value: &MetadataValue) -> Option<Self> {
+        match value {
+            MetadataValue::Date(dt) => Some(*dt),
+            MetadataValue::String(s) => {
+                let s = s.trim();
+                parse_pdf_date(s).ok().or_else(|| DateTime::parse_from_rfc3339(s).ok())
+            }
+            _ => None,
+        }
+    }
+}
+
+impl FromMetadataValue for Vec<String> {
+    const TARGET: &'static str = "list";
+
+    fn from_metadata_value(value: &MetadataValue) -> Option<Self> {
+        match value {
+            MetadataValue::String(s) => Some(
+                s.split([',', ';'])
+                    .map(str::trim)
+                    .filter(|item| !item.is_empty())
+                    .map(str::to_string)
+                    .collect(),
+            ),
+            MetadataValue::Name(name) => Some(vec![name.clone()]),
+            MetadataValue::Array(items) => items
+                .iter()
+                .map(|item| match item {
+                    MetadataValue::String(s) | MetadataValue::Name(s) => Some(s.clone()),
+                    _ => None,
+                })
+                .collect(),
+            _ => None,
+        }
+    }
+}
+
+impl FromMetadataValue for String {
+    const TARGET: &'static str = "string";
+
+    fn from_metadata_value(value: &MetadataValue) -> Option<Self> {
+        match value {
+            MetadataValue::String(s) | MetadataValue::Name(s) => Some(s.clone()),
+            _ => None,
+        }
+    }
+}
+
+/// Reads the entry `key` and converts it to `T`.
+///
+/// # Returns
+///
+/// * `Ok(Some(T))`: The converted value.
+/// * `Ok(None)`: If the document has no entry `key`.
+/// * `Err(Error::Conversion)`: If the entry exists but cannot be converted to `T`.
+/// * `Err(Error)`: If the file cannot be loaded.
+///
+/// # Example
+///
+/// ```no_run
+/// use chrono::{DateTime, FixedOffset};
+/// use pdf_metadata::get_metadata_as;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let path = "path/to/document.pdf";
+///     let revision: Option<i64> = get_metadata_as(path, "Revision")?;
+///     let approved = get_metadata_as::<bool>(path, "Approved")?.unwrap_or(false);
+///     let created: Option<DateTime<FixedOffset>> = get_metadata_as(path, "CreationDate")?;
+///     let reviewers: Vec<String> = get_metadata_as(path, "Reviewers")?.unwrap_or_default();
+///     println!("{:?} {} {:?} {:?}", revision, approved, created, reviewers);
+///     Ok(())
+/// }
+/// ```
+#[cfg(feature = "std-fs")]
+pub fn get_metadata_as<T: FromMetadataValue>(file_path: impl AsRef<Path>, key: &str) -> Result<Option<T>> {
+    let file_path = file_path.as_ref();
+    let Some((_, value)) = get_metadata_values(file_path)?.into_iter().find(|(k, _)| k == key) else {
+        return Ok(None);
+    };
+    convert(key, &value).map(Some)
+}
+
+/// Converts the value of entry `key` to `T`, describing failures with [`Error::Conversion`].
+#[cfg(feature = "std-fs")]
+pub(crate) fn convert<T: FromMetadataValue>(key: &str, value: &MetadataValue) -> Result<T> {
+    T::from_metadata_value(value).ok_or_else(|| Error::Conversion {
+        key: key.to_string(),
+        value: value.to_string(),
+        target: T::TARGET,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{create_minimal_test_pdf, setup_unique_test_dir};
+    use crate::update_entries_in_place;
+    use std::fs;
+
+    #[test]
+    fn test_conversions() {
+        let string = |s: &str| MetadataValue::String(s.to_string());
+        assert_eq!(convert::<i64>("N", &string(" 42 ")).unwrap(), 42);
+        assert_eq!(convert::<i64>("N", &MetadataValue::Real(3.0)).unwrap(), 3);
+        assert!(convert::<i64>("N", &MetadataValue::Real(3.5)).is_err());
+        assert_eq!(convert::<f64>("N", &string("2.5")).unwrap(), 2.5);
+        assert!(convert::<f64>("N", &string("NaN")).is_err());
+        assert!(convert::<bool>("B", &MetadataValue::Name("True".to_string())).unwrap());
+        assert!(!convert::<bool>("B", &string("no")).unwrap());
+        assert_eq!(
+            convert::<Vec<String>>("L", &string("alpha, beta;; gamma ")).unwrap(),
+            vec!["alpha", "beta", "gamma"]
+        );
+        let array = MetadataValue::Array(vec![string("Ana"), MetadataValue::Name("Bo".to_string())]);
+        assert_eq!(convert::<Vec<String>>("L", &array).unwrap(), vec!["Ana", "Bo"]);
+        let rfc = convert::<DateTime<FixedOffset>>("D", &string("2023-10-27T15:30:00+02:00")).unwrap();
+        assert_eq!(rfc, convert::<DateTime<FixedOffset>>("D", &string("D:20231027153000+02'00'")).unwrap());
+
+        let error = convert::<i64>("Pages", &string("many")).unwrap_err();
+        assert!(matches!(&error, Error::Conversion { key, target: "integer", .. } if key == "Pages"));
+        assert_eq!(error.to_string(), "Cannot convert Pages value \"many\" to integer");
+    }
+
+    #[test]
+    fn test_get_metadata_as_reads_entries() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("coerce_entries");
+        let file = test_dir.join("typed.pdf");
+        create_minimal_test_pdf(&file)?;
+        let file_str = file.to_str().unwrap();
+        update_entries_in_place(file_str, &[("Revision", "7"), ("Reviewers", "Ana, Bo")])?;
+
+        assert_eq!(get_metadata_as::<i64>(file_str, "Revision")?, Some(7));
+        assert_eq!(get_metadata_as::<Vec<String>>(file_str, "Reviewers")?, Some(vec!["Ana".into(), "Bo".into()]));
+        assert!(get_metadata_as::<DateTime<FixedOffset>>(file_str, "ModDate")?.is_some());
+        assert_eq!(get_metadata_as::<bool>(file_str, "Missing")?, None);
+        assert!(matches!(get_metadata_as::<f64>(file_str, "Reviewers"), Err(Error::Conversion { .. })));
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,33 @@
+//! # Legacy string API
+//!
+//! The original free functions of this crate, which exchange metadata as
+//! `(String, String)` pairs. They stay available here, and at the crate root, for
+//! as long as the crate is on its current major version, but they are frozen: new
+//! capabilities are added to the typed API only.
+//!
+//! Code can switch imports to `pdf_metadata::compat::*` today and migrate call by
+//! call:
+//!
+//! | Legacy function | Typed replacement |
+//! |-----------------|-------------------|
+//! | [`get_metadata`] | [`get_metadata_values`](crate::get_metadata_values), [`get_metadata_as`](crate::get_metadata_as) |
+//! | [`get_pdf_metadata`] | [`get_pdf_metadata_values`](crate::get_pdf_metadata_values) |
+//! | [`get_metadata_with_renderer`] | [`get_metadata_values`](crate::get_metadata_values), matching on [`MetadataValue::Unsupported`](crate::MetadataValue::Unsupported) |
+//! | [`set_metadata`], [`update_metadata_in_place`] | [`set_metadata_with_options`](crate::set_metadata_with_options), [`update_metadata_in_place_with_options`](crate::update_metadata_in_place_with_options) |
+//! | [`set_pdf_metadata`], [`update_pdf_metadata_in_place`] | [`set_metadata_from_reader_with_options`](crate::set_metadata_from_reader_with_options) |
+//!
+//! ```no_run
+//! use pdf_metadata::compat::{get_metadata, update_metadata_in_place};
+//!
+//! fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     update_metadata_in_place("path/to/document.pdf", "Title", "Annual Report")?;
+//!     for (key, value) in get_metadata("path/to/document.pdf")? {
+//!         println!("{}: {}", key, value);
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+pub use crate::{get_pdf_metadata, set_pdf_metadata, update_pdf_metadata_in_place};
+#[cfg(feature = "std-fs")]
+pub use crate::{get_metadata, get_metadata_with_renderer, set_metadata, update_metadata_in_place};
@@ -0,0 +1,132 @@
+//! PDF date strings.
+//!
+//! PDF dates have the form `D:YYYYMMDDHHmmSSOHH'mm'` (ISO 32000-1, 7.9.4). Every
+//! field after the year is optional, and the offset `O` is `+`, `-` or `Z`.
+
+use crate::{Error, Result};
+use chrono::{DateTime, FixedOffset, NaiveDate, TimeZone};
+
+/// Parses a PDF date string into a timestamp with its UTC offset.
+///
+/// Truncated forms such as `D:2023` or `D:202310` default the missing fields to the
+/// start of the period. A missing offset, or `Z`, is treated as UTC. The `D:` prefix
+/// and the apostrophes around the offset minutes are optional.
+///
+/// # Returns
+///
+/// * `Ok(DateTime<FixedOffset>)`: The parsed timestamp.
+/// * `Err(Error::InvalidDate)`: If `value` is not a valid PDF date.
+///
+/// # Example
+///
+/// ```
+/// use pdf_metadata::parse_pdf_date;
+///
+/// let dt = parse_pdf_date("D:20231027153000+02'00'").unwrap();
+/// assert_eq!(dt.to_rfc3339(), "2023-10-27T15:30:00+02:00");
+/// ```
+pub fn parse_pdf_date(value: &str) -> Result<DateTime<FixedOffset>> {
+    let invalid = || Error::InvalidDate(value.to_string());
+    let text = value.trim();
+    let text = text.strip_prefix("D:").unwrap_or(text);
+
+    let digit_count = text.bytes().take_while(u8::is_ascii_digit).count();
+    if !(4..=14).contains(&digit_count) || !digit_count.is_multiple_of(2) {
+        return Err(invalid());
+    }
+    let (digits, offset) = text.split_at(digit_count);
+    let field = |start: usize, default: u32| -> u32 {
+        digits.get(start..start + 2).and_then(|f| f.parse().ok()).unwrap_or(default)
+    };
+    let year: i32 = digits[..4].parse().map_err(|_| invalid())?;
+
+    let naive = NaiveDate::from_ymd_opt(year, field(4, 1), field(6, 1))
+        .and_then(|date| date.and_hms_opt(field(8, 0), field(10, 0), field(12, 0)))
+        .ok_or_else(invalid)?;
+    let offset = parse_offset(offset).ok_or_else(invalid)?;
+    offset.from_local_datetime(&naive).single().ok_or_else(invalid)
+}
+
+/// Parses the `OHH'mm'` suffix of a PDF date.
+fn parse_offset(suffix: &str) -> Option<FixedOffset> {
+    let suffix = suffix.trim_end_matches('\'');
+    let sign = match suffix.chars().next() {
+        None => return FixedOffset::east_opt(0),
+        Some('Z') if suffix.len() == 1 || suffix[1..].bytes().all(|b| b == b'0' || b == b'\'') => {
+            return FixedOffset::east_opt(0);
+        }
+        Some('+') => 1,
+        Some('-') => -1,
+        Some(_) => return None,
+    };
+    let mut parts = suffix[1..].split('\'');
+    let hours: i32 = parts.next().filter(|h| h.len() == 2)?.parse().ok()?;
+    let minutes: i32 = match parts.next() {
+        Some(m) if !m.is_empty() => m.parse().ok().filter(|_| m.len() == 2)?,
+        _ => 0,
+    };
+    if parts.next().is_some() || hours > 23 || minutes > 59 {
+        return None;
+    }
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+/// Formats a timestamp as a PDF date string, keeping its UTC offset.
+///
+/// # Example
+///
+/// ```
+/// use chrono::DateTime;
+/// use pdf_metadata::format_pdf_date;
+///
+/// let dt = DateTime::parse_from_rfc3339("2023-10-27T15:30:00+02:00").unwrap();
+/// assert_eq!(format_pdf_date(&dt), "D:20231027153000+02'00'");
+/// ```
+pub fn format_pdf_date<Tz: TimeZone>(dt: &DateTime<Tz>) -> String {
+    let dt = dt.fixed_offset();
+    let offset = dt.offset().local_minus_utc();
+    let offset_hours = offset / 3600;
+    let offset_minutes = (offset.abs() % 3600) / 60;
+    let offset_sign = if offset >= 0 { '+' } else { '-' };
+    format!(
+        "D:{}{}{:02}'{:02}'",
+        dt.format("%Y%m%d%H%M%S"),
+        offset_sign,
+        offset_hours.abs(),
+        offset_minutes
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_pdf_date_forms() {
+        let cases = [
+            ("D:20231027153000+02'00'", "2023-10-27T15:30:00+02:00"),
+            ("D:20231027153000-03'30", "2023-10-27T15:30:00-03:30"),
+            ("D:20231027153000Z", "2023-10-27T15:30:00+00:00"),
+            ("D:20231027153000Z00'00'", "2023-10-27T15:30:00+00:00"),
+            ("D:20231027153000", "2023-10-27T15:30:00+00:00"),
+            ("20231027", "2023-10-27T00:00:00+00:00"),
+            ("D:2023", "2023-01-01T00:00:00+00:00"),
+            ("D:202310271530+05", "2023-10-27T15:30:00+05:00"),
+        ];
+        for (input, expected) in cases {
+            let parsed = parse_pdf_date(input).unwrap_or_else(|e| panic!("{}: {}", input, e));
+            assert_eq!(parsed.to_rfc3339(), expected, "input: {}", input);
+        }
+
+        for invalid in ["", "D:", "yesterday", "D:20231327", "D:202310271", "D:20231027153000+2'00'", "D:20231027153000X"] {
+            assert!(matches!(parse_pdf_date(invalid), Err(Error::InvalidDate(_))), "input: {}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_format_pdf_date_round_trip() {
+        let dt = parse_pdf_date("D:19991231235959-08'00'").unwrap();
+        assert_eq!(format_pdf_date(&dt), "D:19991231235959-08'00'");
+        assert_eq!(parse_pdf_date(&format_pdf_date(&dt)).unwrap(), dt);
+    }
+}
@@ -0,0 +1,143 @@
+//! File-level properties reported next to the metadata.
+
+use crate::Result;
+use crate::stats::{self, Counter};
+use lopdf::{Document, Object};
+use std::fs;
+use std::path::Path;
+
+/// Structural facts about a PDF file, as returned by [`get_document_info`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DocumentInfo {
+    /// The PDF version: the catalog's `/Version` when it is newer than the file
+    /// header, the header version otherwise.
+    pub version: String,
+    /// Number of pages in the page tree.
+    pub page_count: usize,
+    /// Whether the file is encrypted, including with an empty user password.
+    pub encrypted: bool,
+    /// Whether the file is linearized ("fast web view"): it has a linearization
+    /// dictionary whose `/L` is the file size. Any later change, rewritten or
+    /// appended, leaves the dictionary stale and the file no longer counts.
+    pub linearized: bool,
+    /// Whether the catalog references an XMP metadata stream.
+    pub has_xmp: bool,
+    /// Whether the trailer references an Info dictionary.
+    pub has_info: bool,
+    /// Size of the file in bytes.
+    pub file_size: u64,
+}
+
+/// Reports the version, page count, encryption, linearization, metadata streams
+/// and size of a PDF file.
+///
+/// Encrypted files are inspected without a password: only their structure is read.
+///
+/// # Returns
+///
+/// * `Ok(DocumentInfo)`: The properties of the file.
+/// * `Err(Error)`: If the file cannot be read or parsed.
+///
+/// # Example
+///
+/// ```no_run
+/// use pdf_metadata::get_document_info;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let info = get_document_info("path/to/document.pdf")?;
+///     println!("PDF {} with {} pages, {} bytes", info.version, info.page_count, info.file_size);
+///     Ok(())
+/// }
+/// ```
+pub fn get_document_info<P: AsRef<Path>>(file_path: P) -> Result<DocumentInfo> {
+    let file_path = file_path.as_ref();
+    let file_size = fs::metadata(file_path)?.len();
+    let doc = Document::load(file_path)?;
+    stats::record(Counter::DocumentParsed);
+
+    let catalog_version = doc
+        .catalog()
+        .ok()
+        .and_then(|catalog| catalog.get(b"Version").ok())
+        .and_then(|version| version.as_name().ok())
+        .map(|version| String::from_utf8_lossy(version).into_owned());
+    let version = match catalog_version {
+        Some(catalog) if version_number(&catalog) > version_number(&doc.version) => catalog,
+        _ => doc.version.clone(),
+    };
+
+    Ok(DocumentInfo {
+        version,
+        page_count: doc.page_iter().count(),
+        encrypted: doc.is_encrypted() || doc.encryption_state.is_some(),
+        linearized: is_linearized(&doc, file_size),
+        has_xmp: doc.catalog().is_ok_and(|catalog| catalog.has(b"Metadata")),
+        has_info: doc.trailer.has(b"Info"),
+        file_size,
+    })
+}
+
+/// Whether `doc`, read from a file of `file_size` bytes, is linearized. The
+/// linearization dictionary records the file size in `/L`, so one left over from
+/// before a change no longer matches it.
+pub(crate) fn is_linearized(doc: &Document, file_size: u64) -> bool {
+    doc.objects.values().any(|object| match object {
+        Object::Dictionary(dict) if dict.has(b"Linearized") => {
+            dict.get(b"L").and_then(Object::as_i64).is_ok_and(|length| length as u64 == file_size)
+        }
+        _ => false,
+    })
+}
+
+/// Orders versions such as `1.7` and `2.0`; unparsable versions sort first.
+pub(crate) fn version_number(version: &str) -> (u32, u32) {
+    let mut parts = version.trim().splitn(2, '.').map(|part| part.parse().unwrap_or(0));
+    (parts.next().unwrap_or(0), parts.next().unwrap_or(0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{create_minimal_test_pdf, setup_unique_test_dir};
+    use crate::{update_metadata_in_place, xmp};
+    use lopdf::{Dictionary, Stream};
+
+    #[test]
+    fn test_get_document_info() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("document_info");
+        let file = test_dir.join("doc.pdf");
+        create_minimal_test_pdf(&file)?;
+        let file_str = file.to_str().unwrap();
+
+        let info = get_document_info(file_str)?;
+        assert_eq!(info.version, "1.7");
+        assert_eq!(info.page_count, 0);
+        assert!(!info.encrypted && !info.linearized && !info.has_xmp && !info.has_info);
+        assert_eq!(info.file_size, fs::metadata(&file)?.len());
+
+        let mut doc = Document::load(&file)?;
+        let pages_id = doc.catalog()?.get(b"Pages")?.as_reference()?;
+        let mut page = Dictionary::new();
+        page.set("Type", Object::Name(b"Page".to_vec()));
+        page.set("Parent", Object::Reference(pages_id));
+        let page_id = doc.add_object(page);
+        let pages = doc.get_object_mut(pages_id)?.as_dict_mut()?;
+        pages.set("Kids", Object::Array(vec![Object::Reference(page_id)]));
+        pages.set("Count", Object::Integer(1));
+        let xmp = doc.add_object(Stream::new(Dictionary::new(), xmp::rewrite_packet(None, &[])?.into_bytes()));
+        let catalog = doc.catalog_mut()?;
+        catalog.set("Metadata", Object::Reference(xmp));
+        catalog.set("Version", Object::Name(b"2.0".to_vec()));
+        doc.save(&file)?;
+        update_metadata_in_place(file_str, "Title", "Report")?;
+
+        let info = get_document_info(file_str)?;
+        assert_eq!(info.version, "2.0");
+        assert_eq!(info.page_count, 1);
+        assert!(info.has_xmp && info.has_info && !info.encrypted);
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,278 @@
+//! The trailer `/ID`: two byte strings identifying a file.
+//!
+//! The first identifier is assigned when a document is created and stays with it;
+//! the second changes with every revision. Document management systems use the
+//! pair to recognize a file and tell its revisions apart.
+
+use crate::{DocumentIdPolicy, Error, Result, clock, read_info_entries};
+#[cfg(feature = "std-fs")]
+use crate::{SetOptions, load_document, options, write_file};
+use lopdf::{Document, Object, StringFormat};
+use sha2::{Digest, Sha256};
+use std::fmt;
+#[cfg(feature = "std-fs")]
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// The identifiers of a file, as stored in the trailer `/ID` array.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DocumentId {
+    /// The permanent identifier, assigned when the document was created.
+    pub original: Vec<u8>,
+    /// The identifier of this revision.
+    pub current: Vec<u8>,
+}
+
+impl DocumentId {
+    /// Parses two identifiers written in hexadecimal, as [`Display`](fmt::Display) prints them.
+    pub fn from_hex(original: &str, current: &str) -> Result<DocumentId> {
+        Ok(DocumentId { original: parse_hex(original)?, current: parse_hex(current)? })
+    }
+}
+
+impl fmt::Display for DocumentId {
+    /// Writes both identifiers in uppercase hexadecimal, separated by a space.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", hex(&self.original), hex(&self.current))
+    }
+}
+
+/// Reads the trailer `/ID` of a PDF.
+///
+/// # Returns
+///
+/// * `Ok(Some(DocumentId))`: The identifiers.
+/// * `Ok(None)`: If the trailer has no `/ID`, or one that is not two strings.
+/// * `Err(Error)`: If the file cannot be read or parsed.
+///
+/// # Example
+///
+/// ```no_run
+/// use pdf_metadata::get_document_id;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     if let Some(id) = get_document_id("contract.pdf")? {
+///         println!("{}", id);
+///     }
+///     Ok(())
+/// }
+/// ```
+#[cfg(feature = "std-fs")]
+pub fn get_document_id<P: AsRef<Path>>(file_path: P) -> Result<Option<DocumentId>> {
+    let file_path = file_path.as_ref();
+    Ok(read_id(&load_document(file_path)?))
+}
+
+/// Writes a copy of the PDF whose trailer `/ID` is `id` to `output_path`.
+///
+/// Use the same path for input and output to update the file in place. The Info
+/// dictionary, `ModDate` included, is left alone.
+///
+/// # Returns
+///
+/// * `Ok(())` if the document was written.
+/// * `Err(Error::UnsupportedFormat)`: If the document is encrypted with RC4 or AES-128
+///   and `id` changes the first identifier, from which the encryption key is derived.
+/// * `Err(Error)`: If the file cannot be read, or the output cannot be written.
+#[cfg(feature = "std-fs")]
+pub fn set_document_id<P: AsRef<Path>, Q: AsRef<Path>>(file_path: P, output_path: Q, id: &DocumentId) -> Result<()> {
+    write_file(file_path.as_ref(), Some(output_path.as_ref()), &keep_id(), |doc| write_id(doc, id))
+}
+
+/// Writes a copy of the PDF with a new pair of identifiers to `output_path`, for a
+/// file that should count as a new document, such as a copy made from a template.
+///
+/// The identifiers are derived from the time of the active [`Clock`](crate::Clock),
+/// the Info entries and a counter, so they are reproducible under `SOURCE_DATE_EPOCH`
+/// in a fresh process.
+///
+/// # Returns
+///
+/// * `Ok(DocumentId)`: The new identifiers; both are the same, as for a new file.
+/// * `Err(Error::UnsupportedFormat)`: If the document is encrypted with RC4 or AES-128.
+/// * `Err(Error)`: If the file cannot be read, or the output cannot be written.
+#[cfg(feature = "std-fs")]
+pub fn regenerate_document_id<P: AsRef<Path>, Q: AsRef<Path>>(file_path: P, output_path: Q) -> Result<DocumentId> {
+    write_file(file_path.as_ref(), Some(output_path.as_ref()), &keep_id(), regenerate)
+}
+
+/// The options for a writer that sets `/ID` itself, which the policy must not override.
+#[cfg(feature = "std-fs")]
+fn keep_id() -> SetOptions {
+    SetOptions { document_id: DocumentIdPolicy::Keep, ..options::current() }
+}
+
+/// Updates the trailer `/ID` of a document about to be written, as `policy` requests.
+pub(crate) fn apply_policy(doc: &mut Document, policy: DocumentIdPolicy) -> Result<()> {
+    match policy {
+        DocumentIdPolicy::Keep => Ok(()),
+        DocumentIdPolicy::UpdateCurrent => {
+            let current = generate_id(doc);
+            let original = read_id(doc).map_or_else(|| current.clone(), |id| id.original);
+            write_id(doc, &DocumentId { original, current })
+        }
+        DocumentIdPolicy::Regenerate => regenerate(doc).map(drop),
+    }
+}
+
+/// Gives a document without a valid `/ID` a new pair, as encryption requires one.
+#[cfg(feature = "std-fs")]
+pub(crate) fn ensure_id(doc: &mut Document) -> Result<()> {
+    if read_id(doc).is_none() {
+        regenerate(doc)?;
+    }
+    Ok(())
+}
+
+fn regenerate(doc: &mut Document) -> Result<DocumentId> {
+    let new_id = generate_id(doc);
+    let id = DocumentId { original: new_id.clone(), current: new_id };
+    write_id(doc, &id)?;
+    Ok(id)
+}
+
+fn read_id(doc: &Document) -> Option<DocumentId> {
+    let ids = doc.trailer.get(b"ID").ok()?.as_array().ok()?;
+    match ids.as_slice() {
+        [Object::String(original, _), Object::String(current, _)] => {
+            Some(DocumentId { original: original.clone(), current: current.clone() })
+        }
+        _ => None,
+    }
+}
+
+fn write_id(doc: &mut Document, id: &DocumentId) -> Result<()> {
+    let original_changes = read_id(doc).is_none_or(|old| old.original != id.original);
+    // Security handlers before AES-256 (version 5) derive their key from the first /ID.
+    let key_uses_id = doc.encryption_state.as_ref().is_some_and(|state| state.version() < 5);
+    if key_uses_id && original_changes {
+        return Err(Error::UnsupportedFormat(
+            "changing the first /ID of an encrypted document, which its encryption key depends on".to_string(),
+        ));
+    }
+    let string = |bytes: &[u8]| Object::String(bytes.to_vec(), StringFormat::Hexadecimal);
+    doc.trailer.set("ID", Object::Array(vec![string(&id.original), string(&id.current)]));
+    Ok(())
+}
+
+/// Distinguishes identifiers generated within the same clock tick.
+static GENERATED: AtomicU64 = AtomicU64::new(0);
+
+/// A 16-byte identifier, as the PDF specification suggests: a digest of the time
+/// and the document's properties.
+fn generate_id(doc: &Document) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.update(clock::now().to_rfc3339());
+    hasher.update(GENERATED.fetch_add(1, Ordering::Relaxed).to_le_bytes());
+    for (key, value) in read_info_entries(doc) {
+        hasher.update(key);
+        hasher.update([0]);
+        hasher.update(value);
+        hasher.update([0]);
+    }
+    hasher.update(doc.objects.len().to_le_bytes());
+    hasher.finalize()[..16].to_vec()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02X}", byte)).collect()
+}
+
+fn parse_hex(text: &str) -> Result<Vec<u8>> {
+    let invalid = || Error::UnsupportedFormat(format!("document ID '{}': expected an even number of hex digits", text));
+    if !text.len().is_multiple_of(2) || !text.is_ascii() {
+        return Err(invalid());
+    }
+    (0..text.len()).step_by(2).map(|i| u8::from_str_radix(&text[i..i + 2], 16).map_err(|_| invalid())).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{create_minimal_test_pdf, setup_unique_test_dir};
+    use crate::xmp::ensure_xmp_identifiers;
+    use crate::{
+        ConflictPolicy, DocumentIdPolicy, MetadataValue, SetOptions, set_metadata_with_options,
+        update_metadata_in_place, update_metadata_in_place_with_options, update_metadata_incremental_with_options,
+        update_metadata_value_in_place, with_write_options,
+    };
+    use std::fs;
+
+    #[test]
+    fn test_document_id_round_trip_and_update() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("document_id");
+        let file = test_dir.join("doc.pdf");
+        create_minimal_test_pdf(&file)?;
+        let file_str = file.to_str().unwrap();
+        assert_eq!(get_document_id(file_str)?, None);
+
+        let id = DocumentId::from_hex("00112233445566778899AABBCCDDEEFF", "00112233445566778899aabbccddeeff")?;
+        assert_eq!(id.to_string(), "00112233445566778899AABBCCDDEEFF 00112233445566778899AABBCCDDEEFF");
+        assert!(DocumentId::from_hex("abc", "00").is_err());
+        set_document_id(file_str, file_str, &id)?;
+        assert_eq!(get_document_id(file_str)?.as_ref(), Some(&id));
+
+        // Plain writes keep /ID; UpdateCurrent replaces only the second identifier.
+        update_metadata_in_place(file_str, "Title", "Draft")?;
+        assert_eq!(get_document_id(file_str)?.as_ref(), Some(&id));
+        let options = SetOptions { document_id: DocumentIdPolicy::UpdateCurrent, ..SetOptions::default() };
+        update_metadata_in_place_with_options(file_str, "Title", "Final", &options)?;
+        let updated = get_document_id(file_str)?.unwrap();
+        assert_eq!(updated.original, id.original);
+        assert_ne!(updated.current, id.current);
+        assert_eq!(updated.current.len(), 16);
+
+        // Incremental revisions carry the policy into the appended trailer.
+        update_metadata_incremental_with_options(file_str, "Subject", "Signed", &options)?;
+        let revised = get_document_id(file_str)?.unwrap();
+        assert_eq!(revised.original, id.original);
+        assert_ne!(revised.current, updated.current);
+        let regenerate: DocumentIdPolicy = "regenerate".parse()?;
+        update_metadata_in_place_with_options(file_str, "Title", "Copy", &SetOptions { document_id: regenerate, ..options })?;
+        let renewed = get_document_id(file_str)?.unwrap();
+        assert_eq!(renewed.original, renewed.current);
+        assert_ne!(renewed.original, id.original);
+        assert!("replace".parse::<DocumentIdPolicy>().is_err());
+
+        let copy = test_dir.join("copy.pdf");
+        let regenerated = regenerate_document_id(file_str, copy.to_str().unwrap())?;
+        assert_eq!(regenerated.original, regenerated.current);
+        assert_ne!(regenerated.original, renewed.original);
+        assert_eq!(get_document_id(copy.to_str().unwrap())?, Some(regenerated));
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_every_writer_applies_the_id_policy() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("document_id_writers");
+        let file = test_dir.join("doc.pdf");
+        create_minimal_test_pdf(&file)?;
+        let id = DocumentId::from_hex("00112233445566778899AABBCCDDEEFF", "00112233445566778899AABBCCDDEEFF")?;
+        set_document_id(&file, &file, &id)?;
+        let update = SetOptions { document_id: DocumentIdPolicy::UpdateCurrent, ..SetOptions::default() };
+
+        // A typed value and an XMP-only change, neither of which takes options.
+        with_write_options(update.clone(), || update_metadata_value_in_place(&file, "Pages", &MetadataValue::Integer(3)))?;
+        let typed = get_document_id(&file)?.ok_or("no /ID")?;
+        assert_eq!(typed.original, id.original);
+        assert_ne!(typed.current, id.current);
+        with_write_options(update.clone(), || ensure_xmp_identifiers(&file, &file))?;
+        let xmp = get_document_id(&file)?.ok_or("no /ID")?;
+        assert_ne!(xmp.current, typed.current);
+
+        // Setting /ID explicitly is not overridden by the policy.
+        with_write_options(update.clone(), || set_document_id(&file, &file, &id))?;
+        assert_eq!(get_document_id(&file)?, Some(id.clone()));
+
+        // A write that skips every entry changes nothing, /ID included.
+        let skip = SetOptions { on_conflict: ConflictPolicy::Skip, ..update };
+        set_metadata_with_options(&file, &file, "Pages", "4", &skip)?;
+        assert_eq!(get_document_id(&file)?, Some(id));
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,314 @@
+//! A fluent editor that collects several changes and writes them in one pass.
+
+use crate::value::MetadataValue;
+use crate::{
+    KeyMatch, LoadLimits, Result, SetOptions, UsageStats, collect_stats, apply_objects_with, docinfo, format_pdf_date, info_dict_mut,
+    info_string_object, keycase, limits, load_document, namespace, options, write_loaded,
+};
+use chrono::{DateTime, TimeZone};
+use lopdf::{Document, Object};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Accumulates edits to the Info dictionary of one PDF and applies them together.
+///
+/// The document is parsed once by [`open`](MetadataEditor::open); the edits are
+/// applied in order when [`save_as`](MetadataEditor::save_as) or
+/// [`save_in_place`](MetadataEditor::save_in_place) writes it, so a later edit of
+/// a key wins over an earlier one. `ModDate` and existing keys are handled as the
+/// [`SetOptions`] given to [`options`](MetadataEditor::options) request.
+///
+/// # Example
+///
+/// ```no_run
+/// use chrono::Utc;
+/// use pdf_metadata::MetadataEditor;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     MetadataEditor::open("draft.pdf")?
+///         .set("Author", "Ana Souza")
+///         .remove("Producer")
+///         .set_date("CreationDate", &Utc::now())
+///         .save_as("final.pdf")?;
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct MetadataEditor {
+    path: PathBuf,
+    doc: Document,
+    linearized: bool,
+    edits: Vec<Edit>,
+    options: SetOptions,
+    stats: UsageStats,
+}
+
+/// What a save by [`MetadataEditor`] did besides applying the edits.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SaveOutcome {
+    /// The file was linearized ("fast web view") and no longer is. Neither a rewrite
+    /// nor an incremental update keeps linearization, so web-delivery pipelines
+    /// should linearize the output again, e.g. with `qpdf --linearize`.
+    pub linearization_lost: bool,
+    /// The usage counters of the whole editing session, from opening the file to
+    /// writing it.
+    pub stats: UsageStats,
+}
+
+#[derive(Debug)]
+enum Edit {
+    Set(String, Object),
+    Remove(String),
+}
+
+impl MetadataEditor {
+    /// Loads the PDF at `file_path` for editing.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(MetadataEditor)`: An editor without edits.
+    /// * `Err(Error)`: If the file cannot be read, is not a PDF or is encrypted.
+    pub fn open<P: AsRef<Path>>(file_path: P) -> Result<MetadataEditor> {
+        let path = file_path.as_ref().to_path_buf();
+        let (doc, stats) = collect_stats(|| load_document(&path));
+        let doc = doc?;
+        let linearized = docinfo::is_linearized(&doc, fs::metadata(&path)?.len());
+        Ok(MetadataEditor { path, doc, linearized, edits: Vec::new(), options: options::current(), stats })
+    }
+
+    /// Loads the PDF at `file_path` for editing like [`open`](MetadataEditor::open),
+    /// failing with `Error::LimitExceeded` if it breaks one of `limits`.
+    pub fn open_with_limits<P: AsRef<Path>>(file_path: P, limits: &LoadLimits) -> Result<MetadataEditor> {
+        let path = file_path.as_ref().to_path_buf();
+        let bytes = limits::read_file_limited(&path, limits)?;
+        let (doc, stats) = collect_stats(|| limits::load_document_limited(&bytes, limits));
+        let doc = doc?;
+        let linearized = docinfo::is_linearized(&doc, bytes.len() as u64);
+        Ok(MetadataEditor { path, doc, linearized, edits: Vec::new(), options: options::current(), stats })
+    }
+
+    /// Sets `key` to the string `value`.
+    pub fn set(mut self, key: &str, value: &str) -> MetadataEditor {
+        self.edits.push(Edit::Set(key.to_string(), info_string_object(value)));
+        self
+    }
+
+    /// Sets `key` to a typed value, e.g. `Trapped` to the name `True`. An
+    /// [`MetadataValue::Unsupported`] value removes the key, as it carries no content.
+    pub fn set_value(mut self, key: &str, value: &MetadataValue) -> MetadataEditor {
+        self.edits.push(match value.to_object() {
+            Some(object) => Edit::Set(key.to_string(), object),
+            None => Edit::Remove(key.to_string()),
+        });
+        self
+    }
+
+    /// Sets `key` to a PDF date string.
+    pub fn set_date<Tz: TimeZone>(mut self, key: &str, date: &DateTime<Tz>) -> MetadataEditor {
+        self.edits.push(Edit::Set(key.to_string(), Object::string_literal(format_pdf_date(date))));
+        self
+    }
+
+    /// Removes `key`. Removing a missing key is not an error.
+    pub fn remove(mut self, key: &str) -> MetadataEditor {
+        self.edits.push(Edit::Remove(key.to_string()));
+        self
+    }
+
+    /// Whether the file was linearized ("fast web view") when it was opened. Saving
+    /// undoes it, as [`SaveOutcome::linearization_lost`] reports.
+    pub fn is_linearized(&self) -> bool {
+        self.linearized
+    }
+
+    /// The usage counters of the work this editor has done so far, i.e. opening the
+    /// document. [`SaveOutcome::stats`] adds the save.
+    pub fn stats(&self) -> UsageStats {
+        self.stats
+    }
+
+    /// Uses `options` when the edits are applied.
+    pub fn options(mut self, options: SetOptions) -> MetadataEditor {
+        self.options = options;
+        self
+    }
+
+    /// Applies the edits and writes the document to `output_path`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(SaveOutcome)` if the document was written.
+    /// * `Err(Error)`: If an edit is refused (`Error::Validation` in strict mode,
+    ///   `Error::KeyExists` with [`ConflictPolicy::Fail`](crate::ConflictPolicy::Fail)),
+    ///   the document is signed under [`SignedDocPolicy::Error`](crate::SignedDocPolicy::Error),
+    ///   or the output cannot be written.
+    pub fn save_as<P: AsRef<Path>>(self, output_path: P) -> Result<SaveOutcome> {
+        let output_path = output_path.as_ref();
+        self.save(|editor, edits| {
+            let doc = std::mem::take(&mut editor.doc);
+            write_loaded(doc, &editor.path, Some(output_path), &editor.options, |doc| apply(doc, edits, &editor.options))
+        })
+    }
+
+    /// Applies the edits and replaces the original file, as `update_metadata_in_place` does.
+    pub fn save_in_place(self) -> Result<SaveOutcome> {
+        self.save(|editor, edits| {
+            let doc = std::mem::take(&mut editor.doc);
+            write_loaded(doc, &editor.path, None, &editor.options, |doc| apply(doc, edits, &editor.options))
+        })
+    }
+
+    /// Runs `write` with the reduced edits, counting its work in the session's stats.
+    fn save(mut self, write: impl FnOnce(&mut MetadataEditor, &Edits) -> Result<()>) -> Result<SaveOutcome> {
+        let edits = reduce(std::mem::take(&mut self.edits));
+        let (written, stats) = collect_stats(|| write(&mut self, &edits));
+        written?;
+        Ok(SaveOutcome { linearization_lost: self.linearized, stats: self.stats + stats })
+    }
+}
+
+/// The edits reduced to the final value of each key: the entries to set and the keys
+/// to remove.
+struct Edits {
+    sets: Vec<(String, Object)>,
+    removals: Vec<String>,
+}
+
+fn reduce(edits: Vec<Edit>) -> Edits {
+    let mut sets: Vec<(String, Object)> = Vec::new();
+    let mut removals: Vec<String> = Vec::new();
+    for edit in edits {
+        match edit {
+            Edit::Set(key, object) => {
+                removals.retain(|removed| *removed != key);
+                sets.retain(|(set, _)| *set != key);
+                sets.push((key, object));
+            }
+            Edit::Remove(key) => {
+                sets.retain(|(set, _)| *set != key);
+                removals.push(key);
+            }
+        }
+    }
+    Edits { sets, removals }
+}
+
+fn apply(doc: &mut Document, edits: &Edits, options: &SetOptions) -> Result<()> {
+    if edits.sets.is_empty() && edits.removals.is_empty() {
+        return Ok(());
+    }
+    let entries: Vec<(&str, Object)> = edits.sets.iter().map(|(key, object)| (key.as_str(), object.clone())).collect();
+    if !edits.removals.is_empty() {
+        let info_dict = info_dict_mut(doc)?;
+        for key in &edits.removals {
+            let key = match &options.key_prefix {
+                Some(prefix) => namespace::prefixed_key(key, prefix),
+                None => key.into(),
+            };
+            if options.key_match == KeyMatch::CaseInsensitive {
+                keycase::remove_variants(info_dict, &key);
+            }
+            info_dict.remove(key.as_bytes());
+        }
+    }
+    apply_objects_with(doc, &entries, options).map(drop)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{create_minimal_test_pdf, setup_unique_test_dir};
+    use crate::{ConflictPolicy, get_metadata, get_metadata_values, update_metadata_in_place};
+    use std::fs;
+
+    #[test]
+    fn test_editor_reports_session_stats() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("editor_stats");
+        let file = test_dir.join("doc.pdf");
+        create_minimal_test_pdf(&file)?;
+
+        let editor = MetadataEditor::open(&file)?;
+        assert_eq!(editor.stats(), UsageStats { documents_parsed: 1, ..UsageStats::default() });
+        let outcome = editor.set("Title", "Counted").save_in_place()?;
+        assert_eq!(
+            outcome.stats,
+            UsageStats { documents_parsed: 1, documents_written: 1, ..UsageStats::default() }
+        );
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_editor_applies_edits_in_order() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("editor_chain");
+        let file = test_dir.join("draft.pdf");
+        create_minimal_test_pdf(&file)?;
+        update_metadata_in_place(file.to_str().unwrap(), "Producer", "Old Tool")?;
+        update_metadata_in_place(file.to_str().unwrap(), "Author", "Ana")?;
+        let output = test_dir.join("final.pdf");
+        let created = DateTime::parse_from_rfc3339("2024-03-01T10:00:00-03:00")?;
+
+        MetadataEditor::open(&file)?
+            .set("Title", "First")
+            .set("Author", "Bruno")
+            .remove("Producer")
+            .set_date("CreationDate", &created)
+            .set_value("Trapped", &MetadataValue::Name("False".to_string()))
+            .set("Title", "Atlas")
+            .remove("Subject")
+            .set("Subject", "Mapas")
+            .options(SetOptions { on_conflict: ConflictPolicy::Skip, ..SetOptions::default() })
+            .save_as(&output)?;
+
+        let values = get_metadata_values(output.to_str().unwrap())?;
+        let value = |key: &str| values.iter().find(|(k, _)| k == key).map(|(_, v)| v.clone());
+        assert_eq!(value("Title"), Some(MetadataValue::String("Atlas".to_string())));
+        assert_eq!(value("Author"), Some(MetadataValue::String("Ana".to_string())), "skipped: Author existed");
+        assert_eq!(value("Subject"), Some(MetadataValue::String("Mapas".to_string())));
+        assert_eq!(value("Producer"), None);
+        assert_eq!(value("CreationDate"), Some(MetadataValue::Date(created)));
+        assert_eq!(value("Trapped"), Some(MetadataValue::Name("False".to_string())));
+
+        MetadataEditor::open(&file)?.remove("Author").save_in_place()?;
+        assert!(!get_metadata(file.to_str().unwrap())?.iter().any(|(key, _)| key == "Author"));
+        // Without edits the document is written unchanged, ModDate included.
+        let before = get_metadata(file.to_str().unwrap())?;
+        MetadataEditor::open(&file)?.save_in_place()?;
+        assert_eq!(get_metadata(file.to_str().unwrap())?, before);
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_editor_reports_lost_linearization() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("editor_linearized");
+        let file = test_dir.join("web.pdf");
+        // Written by hand: lopdf drops linearization dictionaries when saving. The
+        // zero-padded `/L` keeps the file size independent of its value.
+        let objects = ["<</Linearized 1/L 0000000000>>", "<</Type/Catalog/Pages 3 0 R>>", "<</Type/Pages/Count 0/Kids[]>>"];
+        let mut pdf = String::from("%PDF-1.7\n");
+        let mut xref = String::from("xref\n0 4\n0000000000 65535 f \n");
+        for (number, object) in objects.iter().enumerate() {
+            xref.push_str(&format!("{:010} 00000 n \n", pdf.len()));
+            pdf.push_str(&format!("{} 0 obj\n{object}\nendobj\n", number + 1));
+        }
+        let startxref = pdf.len();
+        pdf.push_str(&format!("{xref}trailer\n<</Size 4/Root 2 0 R>>\nstartxref\n{startxref}\n%%EOF\n"));
+        fs::write(&file, pdf.replace("/L 0000000000", &format!("/L {:010}", pdf.len())))?;
+        assert!(docinfo::get_document_info(file.to_str().unwrap())?.linearized);
+
+        let editor = MetadataEditor::open(&file)?;
+        assert!(editor.is_linearized());
+        let outcome = editor.set("Title", "Atlas").save_in_place()?;
+        assert!(outcome.linearization_lost);
+        assert!(!docinfo::get_document_info(file.to_str().unwrap())?.linearized);
+        let outcome = MetadataEditor::open(&file)?.set("Title", "Mapas").save_in_place()?;
+        assert!(!outcome.linearization_lost);
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+}
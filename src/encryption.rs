@@ -0,0 +1,695 @@
+//! Access to password-protected PDFs.
+//!
+//! lopdf can only decrypt documents automatically when their user password is empty.
+//! The functions here accept an explicit password, edit the decrypted Info dictionary,
+//! and re-encrypt the output with the original security handler, so passwords and
+//! permission flags are unchanged. [`set_metadata_and_encrypt`] instead protects the
+//! output with new passwords, and [`set_permissions`] changes the flags.
+
+use crate::docinfo::version_number;
+use crate::{
+    DocumentIdPolicy, Error, Result, SetOptions, apply_entries_with, document_id, load_document, options,
+    read_info_entries, rewrite_loaded,
+};
+use crate::stats::{self, Counter};
+use lopdf::encryption::crypt_filters::{Aes256CryptFilter, CryptFilter};
+use lopdf::{Document, EncryptionState, EncryptionVersion};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Arc;
+
+/// What a reader lets a user do with an encrypted document opened with the user
+/// password. The owner password lifts all restrictions.
+///
+/// The default allows everything.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Permissions {
+    /// Print the document; in low quality only, unless `print_high_quality` is set too.
+    pub print: bool,
+    /// Print the document at full resolution.
+    pub print_high_quality: bool,
+    /// Change the contents of the document.
+    pub modify: bool,
+    /// Copy text and graphics.
+    pub copy: bool,
+    /// Add or change annotations and fill in form fields.
+    pub annotate: bool,
+    /// Fill in existing form fields, even when `annotate` is not set.
+    pub fill_forms: bool,
+    /// Extract text and graphics for assistive technology, such as screen readers.
+    pub copy_for_accessibility: bool,
+    /// Insert, rotate or delete pages and create bookmarks.
+    pub assemble: bool,
+}
+
+impl Permissions {
+    /// Allows viewing only, plus extraction for assistive technology, which PDF 2.0
+    /// requires to stay allowed.
+    pub fn read_only() -> Permissions {
+        Permissions {
+            print: false,
+            print_high_quality: false,
+            modify: false,
+            copy: false,
+            annotate: false,
+            fill_forms: false,
+            copy_for_accessibility: true,
+            assemble: false,
+        }
+    }
+
+    pub(crate) fn to_flags(self) -> lopdf::Permissions {
+        use lopdf::Permissions as Flags;
+        let mut flags = Flags::empty();
+        for (allowed, flag) in [
+            (self.print, Flags::PRINTABLE),
+            (self.print_high_quality, Flags::PRINTABLE_IN_HIGH_QUALITY),
+            (self.modify, Flags::MODIFIABLE),
+            (self.copy, Flags::COPYABLE),
+            (self.annotate, Flags::ANNOTABLE),
+            (self.fill_forms, Flags::FILLABLE),
+            (self.copy_for_accessibility, Flags::COPYABLE_FOR_ACCESSIBILITY),
+            (self.assemble, Flags::ASSEMBLABLE),
+        ] {
+            flags.set(flag, allowed);
+        }
+        flags
+    }
+
+    pub(crate) fn from_flags(flags: lopdf::Permissions) -> Permissions {
+        use lopdf::Permissions as Flags;
+        Permissions {
+            print: flags.contains(Flags::PRINTABLE),
+            print_high_quality: flags.contains(Flags::PRINTABLE_IN_HIGH_QUALITY),
+            modify: flags.contains(Flags::MODIFIABLE),
+            copy: flags.contains(Flags::COPYABLE),
+            annotate: flags.contains(Flags::ANNOTABLE),
+            fill_forms: flags.contains(Flags::FILLABLE),
+            copy_for_accessibility: flags.contains(Flags::COPYABLE_FOR_ACCESSIBILITY),
+            assemble: flags.contains(Flags::ASSEMBLABLE),
+        }
+    }
+}
+
+impl Default for Permissions {
+    fn default() -> Self {
+        Permissions::from_flags(lopdf::Permissions::all())
+    }
+}
+
+/// The cipher used by [`set_metadata_and_encrypt`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EncryptionAlgorithm {
+    /// RC4 with a 128-bit key (PDF 1.4, security handler revision 3). Weak; only for
+    /// readers that predate AES.
+    Rc4,
+    /// AES with a 256-bit key (PDF 2.0, security handler revision 6).
+    #[default]
+    Aes256,
+}
+
+impl std::str::FromStr for EncryptionAlgorithm {
+    type Err = Error;
+
+    /// Parses `rc4` or `aes256`.
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "rc4" => Ok(EncryptionAlgorithm::Rc4),
+            "aes256" => Ok(EncryptionAlgorithm::Aes256),
+            other => Err(Error::UnsupportedFormat(format!("encryption algorithm '{}'", other))),
+        }
+    }
+}
+
+/// Settings for [`set_metadata_and_encrypt`].
+///
+/// ```
+/// use pdf_metadata::{EncryptionOptions, Permissions};
+///
+/// // Readers may open and print the file, but not copy from or edit it.
+/// let options = EncryptionOptions {
+///     owner_password: "publisher-secret".to_string(),
+///     permissions: Permissions { print: true, print_high_quality: true, ..Permissions::read_only() },
+///     ..EncryptionOptions::default()
+/// };
+/// assert!(options.user_password.is_empty());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EncryptionOptions {
+    /// The password needed to open the document. When empty, anyone can open it,
+    /// subject to `permissions`.
+    pub user_password: String,
+    /// The password that lifts the restrictions of `permissions`. When empty, the user
+    /// password is used, as the PDF specification prescribes; with both empty, any
+    /// reader can lift the restrictions.
+    pub owner_password: String,
+    pub permissions: Permissions,
+    pub algorithm: EncryptionAlgorithm,
+}
+
+/// Loads `file_path` and decrypts it with the user `password`.
+///
+/// Documents that are not encrypted, or that lopdf already opened with the empty
+/// user password, are returned as loaded and `password` is ignored.
+fn load_with_password(file_path: &Path, password: &str) -> Result<Document> {
+    let mut doc = Document::load(file_path)?;
+    stats::record(Counter::DocumentParsed);
+    if doc.is_encrypted() {
+        // For revision 2-4 handlers lopdf derives the file key as if the password were the
+        // user password; an owner password would authenticate but decrypt to garbage.
+        let revision = doc
+            .get_encrypted()
+            .and_then(|dict| dict.get(b"R"))
+            .and_then(|r| r.as_i64())
+            .unwrap_or(0);
+        if revision <= 4 && doc.authenticate_user_password(password).is_err() {
+            return Err(Error::IncorrectPassword);
+        }
+        doc.decrypt(password)?;
+    }
+    if doc.is_encrypted() {
+        return Err(Error::Encrypted);
+    }
+    Ok(doc)
+}
+
+/// Retrieves all metadata entries from a password-protected PDF file.
+///
+/// # Arguments
+///
+/// * `file_path`: The path to the PDF file from which to read metadata.
+/// * `password`: The user password of the document.
+///
+/// # Returns
+///
+/// * `Ok(Vec<(String, String)>)`: The decrypted `(key, value)` entries of the Info dictionary.
+/// * `Err(Error::IncorrectPassword)`: If `password` does not open the document.
+/// * `Err(Error)`: If the file cannot be loaded or uses an unsupported security handler.
+///
+/// # Example
+///
+/// ```no_run
+/// use pdf_metadata::get_metadata_with_password;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     for (key, value) in get_metadata_with_password("path/to/protected.pdf", "secret")? {
+///         println!("{}: {}", key, value);
+///     }
+///     Ok(())
+/// }
+/// ```
+pub fn get_metadata_with_password<P: AsRef<Path>>(file_path: P, password: &str) -> Result<Vec<(String, String)>> {
+    let file_path = file_path.as_ref();
+    let doc = load_with_password(file_path, password)?;
+    Ok(read_info_entries(&doc))
+}
+
+/// Sets (adds or updates) a metadata entry in a password-protected PDF and saves it to a new path.
+///
+/// The document is decrypted with `password`, modified like `set_metadata` (including the
+/// `ModDate` update), and re-encrypted on save with the original keys, passwords and
+/// permission flags.
+///
+/// # Arguments
+///
+/// * `file_path`: The path to the original PDF file.
+/// * `output_path`: The path where the modified PDF file will be saved.
+/// * `metadata_key`: The key of the metadata entry to set.
+/// * `metadata_value`: The value for the metadata entry.
+/// * `password`: The user password of the document.
+///
+/// # Returns
+///
+/// * `Ok(())` if the operation was successful.
+/// * `Err(Error::IncorrectPassword)`: If `password` does not open the document.
+/// * `Err(Error)`: If any error occurs during loading, modification, or saving.
+///
+/// # Example
+///
+/// ```no_run
+/// use pdf_metadata::set_metadata_with_password;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     set_metadata_with_password("protected.pdf", "protected_out.pdf", "Author", "Jane Doe", "secret")?;
+///     Ok(())
+/// }
+/// ```
+pub fn set_metadata_with_password<P: AsRef<Path>, Q: AsRef<Path>>(
+    file_path: P,
+    output_path: Q,
+    metadata_key: &str,
+    metadata_value: &str,
+    password: &str,
+) -> Result<()> {
+    let file_path = file_path.as_ref();
+    let output_path = output_path.as_ref();
+    let doc = load_with_password(file_path, password)?;
+    let options = options::current();
+    rewrite_loaded(doc, file_path, Some(output_path), &options, "updating an encrypted PDF", |doc| {
+        apply_entries_with(doc, &[(metadata_key, metadata_value)], &options).map(drop)
+    })
+}
+
+/// Sets `entries` like [`set_metadata`](crate::set_metadata) and encrypts the output
+/// with the passwords and permissions of `options`.
+///
+/// An existing encryption with an empty user password is replaced. The document gets a
+/// trailer `/ID` if it has none, since the encryption key is derived from it, and its
+/// PDF version is raised to the one the algorithm requires (1.4 for RC4, 2.0 for AES-256).
+///
+/// # Returns
+///
+/// * `Ok(())` if the encrypted document was written.
+/// * `Err(Error::Encrypted)`: If the source cannot be opened without a password.
+/// * `Err(Error)`: If a password cannot be used (e.g. RC4 with non-Latin-1 characters),
+///   or the file cannot be read or written.
+///
+/// # Example
+///
+/// ```no_run
+/// use pdf_metadata::{EncryptionOptions, Permissions, set_metadata_and_encrypt};
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let options = EncryptionOptions {
+///         owner_password: "publisher-secret".to_string(),
+///         permissions: Permissions { print: true, ..Permissions::read_only() },
+///         ..EncryptionOptions::default()
+///     };
+///     set_metadata_and_encrypt("book.pdf", "book_protected.pdf", &[("Title", "O Alienista")], &options)?;
+///     Ok(())
+/// }
+/// ```
+pub fn set_metadata_and_encrypt<P: AsRef<Path>, Q: AsRef<Path>, K: AsRef<str>, V: AsRef<str>>(
+    file_path: P,
+    output_path: Q,
+    entries: &[(K, V)],
+    options: &EncryptionOptions,
+) -> Result<()> {
+    let file_path = file_path.as_ref();
+    let output_path = output_path.as_ref();
+    let doc = load_document(file_path)?;
+    let write_options = crate::options::current();
+    rewrite_loaded(doc, file_path, Some(output_path), &keep_id(&write_options), "encrypting a PDF", |doc| {
+        // The original security handler, if any, is replaced rather than restored on save.
+        doc.encryption_state = None;
+        apply_entries_with(doc, entries, &write_options)?;
+        // The new key is derived from the /ID the output will have.
+        document_id::apply_policy(doc, write_options.document_id)?;
+        document_id::ensure_id(doc)?;
+        let minimum_version = match options.algorithm {
+            EncryptionAlgorithm::Rc4 => "1.4",
+            EncryptionAlgorithm::Aes256 => "2.0",
+        };
+        if version_number(&doc.version) < version_number(minimum_version) {
+            doc.version = minimum_version.to_string();
+        }
+        doc.encryption_state = Some(encryption_state(doc, options)?);
+        Ok(())
+    })
+}
+
+/// `options` for a writer that applies their /ID policy itself, before deriving a key.
+fn keep_id(options: &SetOptions) -> SetOptions {
+    SetOptions { document_id: DocumentIdPolicy::Keep, ..options.clone() }
+}
+
+fn encryption_state(doc: &Document, options: &EncryptionOptions) -> Result<EncryptionState> {
+    let permissions = options.permissions.to_flags();
+    let owner_password =
+        if options.owner_password.is_empty() { &options.user_password } else { &options.owner_password };
+    let mut key = [0u8; 32];
+    let version = match options.algorithm {
+        // Revision 3 derives the key from the passwords and the first /ID.
+        EncryptionAlgorithm::Rc4 => EncryptionVersion::V2 {
+            document: doc,
+            owner_password,
+            user_password: &options.user_password,
+            key_length: 128,
+            permissions,
+        },
+        // Revision 6 uses a random key, wrapped with each password.
+        EncryptionAlgorithm::Aes256 => {
+            getrandom::fill(&mut key)
+                .map_err(|error| Error::Io(std::io::Error::other(format!("no random source: {}", error))))?;
+            let filter: Arc<dyn CryptFilter> = Arc::new(Aes256CryptFilter);
+            EncryptionVersion::V5 {
+                encrypt_metadata: true,
+                crypt_filters: BTreeMap::from([(b"StdCF".to_vec(), filter)]),
+                file_encryption_key: &key,
+                stream_filter: b"StdCF".to_vec(),
+                string_filter: b"StdCF".to_vec(),
+                owner_password,
+                user_password: &options.user_password,
+                permissions,
+            }
+        }
+    };
+    Ok(EncryptionState::try_from(version)?)
+}
+
+/// Reads the permission flags of a PDF.
+///
+/// The flags are stored unencrypted, so no password is needed. Documents without
+/// encryption impose no restrictions, so everything is allowed for them.
+///
+/// # Returns
+///
+/// * `Ok(Permissions)`: What readers may do with the document.
+/// * `Err(Error)`: If the file cannot be read or parsed.
+///
+/// # Example
+///
+/// ```no_run
+/// use pdf_metadata::get_permissions;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     if !get_permissions("contract.pdf")?.copy {
+///         println!("copying text is not allowed");
+///     }
+///     Ok(())
+/// }
+/// ```
+pub fn get_permissions<P: AsRef<Path>>(file_path: P) -> Result<Permissions> {
+    let file_path = file_path.as_ref();
+    let doc = Document::load(file_path)?;
+    stats::record(Counter::DocumentParsed);
+    if let Some(state) = &doc.encryption_state {
+        return Ok(Permissions::from_flags(state.permissions()));
+    }
+    let Ok(encrypt) = doc.get_encrypted() else { return Ok(Permissions::default()) };
+    // /P is a signed 32-bit integer whose low bits are the flags.
+    let p = encrypt.get(b"P").and_then(|p| p.as_i64())?;
+    Ok(Permissions::from_flags(lopdf::Permissions::from_bits_truncate(p as u32 as u64)))
+}
+
+/// Writes a copy of an encrypted PDF with new permission flags to `output_path`.
+///
+/// Changing what the user password allows takes the owner password; the user password
+/// is needed too, as the document is re-encrypted for both. The security handler
+/// keeps its algorithm and key length, and the Info dictionary is left alone. Use the
+/// same path for input and output to update the file in place.
+///
+/// # Returns
+///
+/// * `Ok(())` if the document was written.
+/// * `Err(Error::IncorrectPassword)`: If either password is wrong.
+/// * `Err(Error::UnsupportedFormat)`: If the document is not encrypted; use
+///   [`set_metadata_and_encrypt`] to protect it.
+/// * `Err(Error)`: If the file cannot be read, or the output cannot be written.
+///
+/// # Example
+///
+/// ```no_run
+/// use pdf_metadata::{Permissions, set_permissions};
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let permissions = Permissions { copy: false, modify: false, ..Permissions::default() };
+///     set_permissions("book.pdf", "book.pdf", &permissions, "publisher-secret", "")?;
+///     Ok(())
+/// }
+/// ```
+pub fn set_permissions<P: AsRef<Path>, Q: AsRef<Path>>(
+    file_path: P,
+    output_path: Q,
+    permissions: &Permissions,
+    owner_password: &str,
+    user_password: &str,
+) -> Result<()> {
+    let file_path = file_path.as_ref();
+    let output_path = output_path.as_ref();
+    let doc = load_with_password(file_path, user_password)?;
+    let options = options::current();
+    rewrite_loaded(doc, file_path, Some(output_path), &keep_id(&options), "changing the permissions", |doc| {
+        let state = doc
+            .encryption_state
+            .take()
+            .ok_or_else(|| Error::UnsupportedFormat("setting permissions of a PDF that is not encrypted".to_string()))?;
+        if !is_owner_password(doc, &state, owner_password) {
+            return Err(Error::IncorrectPassword);
+        }
+        // The handler is re-created below, its key derived from the /ID the output will have.
+        document_id::apply_policy(doc, options.document_id)?;
+
+        let flags = permissions.to_flags();
+        let version = match state.version() {
+            1 => EncryptionVersion::V1 { document: doc, owner_password, user_password, permissions: flags },
+            2 | 3 => EncryptionVersion::V2 {
+                document: doc,
+                owner_password,
+                user_password,
+                key_length: state.key_length().unwrap_or(40),
+                permissions: flags,
+            },
+            4 => EncryptionVersion::V4 {
+                document: doc,
+                encrypt_metadata: state.encrypt_metadata(),
+                crypt_filters: state.crypt_filters().clone(),
+                stream_filter: state.default_stream_filter().to_vec(),
+                string_filter: state.default_string_filter().to_vec(),
+                owner_password,
+                user_password,
+                permissions: flags,
+            },
+            _ => EncryptionVersion::V5 {
+                encrypt_metadata: state.encrypt_metadata(),
+                crypt_filters: state.crypt_filters().clone(),
+                file_encryption_key: state.file_encryption_key(),
+                stream_filter: state.default_stream_filter().to_vec(),
+                string_filter: state.default_string_filter().to_vec(),
+                owner_password,
+                user_password,
+                permissions: flags,
+            },
+        };
+        let new_state = EncryptionState::try_from(version)?;
+        doc.encryption_state = Some(new_state);
+        Ok(())
+    })
+}
+
+/// Whether `password` is the owner password of the security handler `state`, which
+/// lopdf removed from `doc` when decrypting it.
+fn is_owner_password(doc: &Document, state: &EncryptionState, password: &str) -> bool {
+    let Ok(encrypt) = state.encode() else { return false };
+    let mut probe = Document::new();
+    if let Ok(id) = doc.trailer.get(b"ID") {
+        probe.trailer.set("ID", id.clone());
+    }
+    let encrypt_id = probe.add_object(encrypt);
+    probe.trailer.set("Encrypt", encrypt_id);
+    probe.authenticate_owner_password(password).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{create_minimal_test_pdf, setup_unique_test_dir};
+    use crate::{get_document_id, get_metadata, with_write_options};
+    use lopdf::{Dictionary, Object, Permissions as Flags, StringFormat};
+    use std::fs;
+
+    fn create_encrypted_test_pdf(path: &Path, user_password: &str, permissions: Flags) -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let mut doc = Document::with_version("1.7");
+        let mut pages_dict = Dictionary::new();
+        pages_dict.set("Type", Object::Name(b"Pages".to_vec()));
+        pages_dict.set("Count", Object::Integer(0));
+        pages_dict.set("Kids", Object::Array(vec![]));
+        let pages_id = doc.add_object(pages_dict);
+        let mut catalog_dict = Dictionary::new();
+        catalog_dict.set("Type", Object::Name(b"Catalog".to_vec()));
+        catalog_dict.set("Pages", Object::Reference(pages_id));
+        let catalog_id = doc.add_object(catalog_dict);
+        doc.trailer.set("Root", Object::Reference(catalog_id));
+        let mut info_dict = Dictionary::new();
+        info_dict.set("Title", Object::string_literal("Confidential Report"));
+        let info_id = doc.add_object(info_dict);
+        doc.trailer.set("Info", Object::Reference(info_id));
+        let file_id = Object::String(b"0123456789abcdef".to_vec(), StringFormat::Hexadecimal);
+        doc.trailer.set("ID", Object::Array(vec![file_id.clone(), file_id]));
+
+        let version = EncryptionVersion::V2 {
+            document: &doc,
+            owner_password: "owner-secret",
+            user_password,
+            key_length: 128,
+            permissions,
+        };
+        let state = EncryptionState::try_from(version)?;
+        doc.encrypt(&state)?;
+        doc.save(path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_and_set_metadata_with_password() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("encrypted_password");
+        let input = test_dir.join("protected.pdf");
+        let output = test_dir.join("protected_out.pdf");
+        let permissions = Flags::PRINTABLE | Flags::COPYABLE_FOR_ACCESSIBILITY;
+        create_encrypted_test_pdf(&input, "user-secret", permissions)?;
+
+        assert!(matches!(get_metadata(input.to_str().unwrap()), Err(Error::Encrypted)));
+        for wrong in ["wrong", "owner-secret"] {
+            assert!(matches!(
+                get_metadata_with_password(input.to_str().unwrap(), wrong),
+                Err(Error::IncorrectPassword)
+            ));
+        }
+
+        let metadata = get_metadata_with_password(input.to_str().unwrap(), "user-secret")?;
+        assert!(metadata.iter().any(|(k, v)| k == "Title" && v == "Confidential Report"));
+
+        set_metadata_with_password(input.to_str().unwrap(), output.to_str().unwrap(), "Author", "Jane", "user-secret")?;
+
+        // The output is still protected by the same passwords and permissions.
+        assert!(matches!(get_metadata(output.to_str().unwrap()), Err(Error::Encrypted)));
+        let metadata = get_metadata_with_password(output.to_str().unwrap(), "user-secret")?;
+        assert!(metadata.iter().any(|(k, v)| k == "Author" && v == "Jane"));
+        assert!(metadata.iter().any(|(k, v)| k == "Title" && v == "Confidential Report"));
+
+        let mut reloaded = Document::load(&output)?;
+        reloaded.decrypt("user-secret")?;
+        let state = reloaded.encryption_state.as_ref().expect("decrypted document keeps its state");
+        assert_eq!(state.permissions(), permissions);
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_empty_user_password_encryption_survives_set_metadata() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("encrypted_empty_password");
+        let input = test_dir.join("permissions_only.pdf");
+        let output = test_dir.join("permissions_only_out.pdf");
+        create_encrypted_test_pdf(&input, "", Flags::PRINTABLE)?;
+
+        crate::set_metadata(input.to_str().unwrap(), output.to_str().unwrap(), "Subject", "Kept locked")?;
+
+        let metadata = get_metadata(output.to_str().unwrap())?;
+        assert!(metadata.iter().any(|(k, v)| k == "Subject" && v == "Kept locked"));
+
+        let reloaded = Document::load(&output)?;
+        let state = reloaded.encryption_state.as_ref().expect("Output should still be encrypted");
+        assert_eq!(state.permissions(), Flags::PRINTABLE);
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_metadata_and_encrypt() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("encrypt_output");
+        let input = test_dir.join("book.pdf");
+        create_minimal_test_pdf(&input)?;
+        let permissions = Permissions { print: true, ..Permissions::read_only() };
+
+        for algorithm in [EncryptionAlgorithm::Rc4, EncryptionAlgorithm::Aes256] {
+            let output = test_dir.join(format!("{:?}.pdf", algorithm));
+            let output_str = output.to_str().unwrap();
+            let options = EncryptionOptions {
+                user_password: "leitor".to_string(),
+                owner_password: "editora".to_string(),
+                permissions,
+                algorithm,
+            };
+            set_metadata_and_encrypt(input.to_str().unwrap(), output_str, &[("Title", "O Alienista")], &options)?;
+
+            assert!(matches!(get_metadata(output_str), Err(Error::Encrypted)));
+            let metadata = get_metadata_with_password(output_str, "leitor")?;
+            assert!(metadata.iter().any(|(k, v)| k == "Title" && v == "O Alienista"), "{:?}", algorithm);
+
+            let mut reloaded = Document::load(&output)?;
+            reloaded.decrypt("leitor")?;
+            let state = reloaded.encryption_state.as_ref().expect("decrypted document keeps its state");
+            assert_eq!(Permissions::from_flags(state.permissions()), permissions);
+            assert_eq!(state.revision(), if algorithm == EncryptionAlgorithm::Rc4 { 3 } else { 6 });
+        }
+        assert_eq!(Document::load(test_dir.join("Aes256.pdf"))?.version, "2.0");
+        assert_eq!("AES256".parse::<EncryptionAlgorithm>()?, EncryptionAlgorithm::Aes256);
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_and_set_permissions() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("permissions");
+        let plain = test_dir.join("plain.pdf");
+        create_minimal_test_pdf(&plain)?;
+        let plain_str = plain.to_str().unwrap();
+        assert_eq!(get_permissions(plain_str)?, Permissions::default());
+        assert!(matches!(
+            set_permissions(plain_str, plain_str, &Permissions::read_only(), "", ""),
+            Err(Error::UnsupportedFormat(_))
+        ));
+
+        // RC4 with a user password, readable without it.
+        let rc4 = test_dir.join("rc4.pdf");
+        let rc4_str = rc4.to_str().unwrap();
+        create_encrypted_test_pdf(&rc4, "user-secret", Flags::PRINTABLE | Flags::COPYABLE)?;
+        let permissions = get_permissions(rc4_str)?;
+        assert!(permissions.print && permissions.copy && !permissions.modify);
+
+        let restricted = Permissions { print: true, ..Permissions::read_only() };
+        assert!(matches!(
+            set_permissions(rc4_str, rc4_str, &restricted, "user-secret", "user-secret"),
+            Err(Error::IncorrectPassword)
+        ));
+        set_permissions(rc4_str, rc4_str, &restricted, "owner-secret", "user-secret")?;
+        assert_eq!(get_permissions(rc4_str)?, restricted);
+        let metadata = get_metadata_with_password(rc4_str, "user-secret")?;
+        assert!(metadata.iter().any(|(k, v)| k == "Title" && v == "Confidential Report"));
+
+        // AES-256 with an empty user password.
+        let aes = test_dir.join("aes.pdf");
+        let aes_str = aes.to_str().unwrap();
+        let options = EncryptionOptions { owner_password: "editora".to_string(), ..EncryptionOptions::default() };
+        set_metadata_and_encrypt(plain_str, aes_str, &[("Title", "Atlas")], &options)?;
+        set_permissions(aes_str, aes_str, &restricted, "editora", "")?;
+        assert_eq!(get_permissions(aes_str)?, restricted);
+        assert!(get_metadata(aes_str)?.iter().any(|(k, v)| k == "Title" && v == "Atlas"));
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_regenerated_id_on_encrypted_documents() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("encrypted_regenerate_id");
+        let input = test_dir.join("book.pdf");
+        create_minimal_test_pdf(&input)?;
+        let regenerate = || SetOptions { document_id: DocumentIdPolicy::Regenerate, ..SetOptions::default() };
+
+        // The new key is derived from the regenerated /ID.
+        for algorithm in [EncryptionAlgorithm::Rc4, EncryptionAlgorithm::Aes256] {
+            let output = test_dir.join(format!("{:?}.pdf", algorithm));
+            let options = EncryptionOptions { owner_password: "editora".to_string(), algorithm, ..EncryptionOptions::default() };
+            with_write_options(regenerate(), || set_metadata_and_encrypt(&input, &output, &[("Title", "Atlas")], &options))?;
+            assert!(get_document_id(&output)?.is_some(), "{:?}", algorithm);
+        }
+
+        // AES-256 keys do not depend on the /ID, so the document can get a new one.
+        let aes = test_dir.join("Aes256.pdf");
+        let before = get_document_id(&aes)?;
+        with_write_options(regenerate(), || set_metadata_with_password(&aes, &aes, "Author", "Ana", ""))?;
+        let after = get_document_id(&aes)?;
+        assert_ne!(after, before);
+        assert!(get_metadata(&aes)?.iter().any(|(k, v)| k == "Author" && v == "Ana"));
+        with_write_options(regenerate(), || set_permissions(&aes, &aes, &Permissions::read_only(), "editora", ""))?;
+        assert_ne!(get_document_id(&aes)?, after);
+        assert_eq!(get_permissions(&aes)?, Permissions::read_only());
+
+        // An RC4 handler kept on save needs the /ID it was derived from.
+        let rc4 = test_dir.join("Rc4.pdf");
+        assert!(matches!(
+            with_write_options(regenerate(), || set_metadata_with_password(&rc4, &rc4, "Author", "Ana", "")),
+            Err(Error::UnsupportedFormat(_))
+        ));
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+}
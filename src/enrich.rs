@@ -0,0 +1,351 @@
+//! # Metadata enrichment
+//!
+//! An [`Enricher`] looks at a document's current metadata and proposes new or
+//! updated entries, typically by consulting an external source such as a CRM,
+//! a DOI resolver or a sidecar file. [`enrich_files`] runs a chain of enrichers
+//! over many files through the [batch engine](crate::batch) and writes the
+//! additions back to each PDF in a single update.
+//!
+//! With the `async` feature, enrichers that call network services can implement
+//! [`AsyncEnricher`] instead and run through `enrich_files_async`, which keeps up to
+//! `max_concurrency` documents in flight on the caller's tokio runtime while file
+//! access runs on the blocking pool.
+//!
+//! ```no_run
+//! use pdf_metadata::batch::BatchOptions;
+//! use pdf_metadata::enrich::{enrich_files, Enricher, EnricherError};
+//! use std::path::{Path, PathBuf};
+//!
+//! struct ProjectTagger;
+//!
+//! impl Enricher for ProjectTagger {
+//!     fn name(&self) -> &str {
+//!         "project-tagger"
+//!     }
+//!
+//!     fn enrich(&self, path: &Path, _metadata: &[(String, String)]) -> Result<Vec<(String, String)>, EnricherError> {
+//!         let project = path.parent().and_then(|p| p.file_name()).map(|n| n.to_string_lossy().into_owned());
+//!         Ok(project.map(|p| vec![("Project".to_string(), p)]).unwrap_or_default())
+//!     }
+//! }
+//!
+//! let files = vec![PathBuf::from("projects/apollo/report.pdf")];
+//! let enrichers: Vec<Box<dyn Enricher>> = vec![Box::new(ProjectTagger)];
+//! for outcome in enrich_files(&files, &enrichers, &BatchOptions { max_concurrency: 2 }) {
+//!     println!("{}: {:?}", outcome.path.display(), outcome.result.map(|added| added.len()));
+//! }
+//! ```
+
+use crate::batch::{self, BatchOptions, FileOutcome};
+use crate::{Error, Result, get_metadata, update_entries_in_place};
+use std::path::{Path, PathBuf};
+#[cfg(feature = "async")]
+use std::{future::Future, pin::Pin, task::Poll};
+
+/// Error type enrichers may return; any error from the external source can be boxed into it.
+pub type EnricherError = Box<dyn std::error::Error + Send + Sync>;
+
+/// A source of additional metadata for a document.
+///
+/// Implementations are shared between worker threads, so they must be `Send + Sync`.
+/// Connection pools or caches should use interior mutability.
+pub trait Enricher: Send + Sync {
+    /// A short identifier used in error messages.
+    fn name(&self) -> &str;
+
+    /// Returns the `(key, value)` entries to add or overwrite for the document at `path`.
+    ///
+    /// `metadata` contains the document's current entries, including any additions
+    /// made by enrichers earlier in the chain. Returning an empty vector leaves the
+    /// document untouched.
+    fn enrich(&self, path: &Path, metadata: &[(String, String)]) -> std::result::Result<Vec<(String, String)>, EnricherError>;
+}
+
+/// Runs the enricher chain on one file and writes any additions back in place.
+///
+/// # Returns
+///
+/// * `Ok(Vec<(String, String)>)`: The entries that were written (empty if nothing changed).
+/// * `Err(Error)`: If reading, an enricher, or writing failed. Nothing is written on error.
+pub fn enrich_file<P: AsRef<Path>>(path: P, enrichers: &[Box<dyn Enricher>]) -> Result<Vec<(String, String)>> {
+    let path = path.as_ref();
+    let mut metadata = get_metadata(path)?;
+    let mut additions: Vec<(String, String)> = Vec::new();
+
+    for enricher in enrichers {
+        let proposed = enricher.enrich(path, &metadata).map_err(|source| Error::Enrichment {
+            enricher: enricher.name().to_string(),
+            source,
+        })?;
+        for (key, value) in proposed {
+            upsert(&mut metadata, &key, &value);
+            upsert(&mut additions, &key, &value);
+        }
+    }
+
+    if !additions.is_empty() {
+        update_entries_in_place(path, &additions)?;
+    }
+    Ok(additions)
+}
+
+/// Enriches many files concurrently, honoring `options.max_concurrency`.
+///
+/// # Returns
+///
+/// One outcome per input path, in input order, holding the entries written to that file.
+pub fn enrich_files(
+    paths: &[PathBuf],
+    enrichers: &[Box<dyn Enricher>],
+    options: &BatchOptions,
+) -> Vec<FileOutcome<Vec<(String, String)>>> {
+    batch::run(paths, options, |path| enrich_file(path, enrichers))
+}
+
+/// The future returned by [`AsyncEnricher::enrich`].
+#[cfg(feature = "async")]
+pub type EnrichFuture<'a> = Pin<Box<dyn Future<Output = std::result::Result<Vec<(String, String)>, EnricherError>> + Send + 'a>>;
+
+/// An [`Enricher`] whose lookup is asynchronous, such as an HTTP call (`async` feature).
+///
+/// `enrich` returns a boxed future so that enrichers of different types can share a
+/// chain; implementations usually wrap an `async` block in `Box::pin`.
+#[cfg(feature = "async")]
+pub trait AsyncEnricher: Send + Sync {
+    /// A short identifier used in error messages.
+    fn name(&self) -> &str;
+
+    /// Resolves to the `(key, value)` entries to add or overwrite, with the same
+    /// meaning as [`Enricher::enrich`].
+    fn enrich<'a>(&'a self, path: &'a Path, metadata: &'a [(String, String)]) -> EnrichFuture<'a>;
+}
+
+/// Async counterpart of [`enrich_file`]. Reading and writing the file run on
+/// tokio's blocking pool; the enrichers are awaited in order on the calling task.
+#[cfg(feature = "async")]
+pub async fn enrich_file_async(
+    path: impl Into<PathBuf>,
+    enrichers: &[Box<dyn AsyncEnricher>],
+) -> Result<Vec<(String, String)>> {
+    use crate::asynchronous::run_blocking;
+
+    let path = path.into();
+    let reader = path.clone();
+    let mut metadata = run_blocking(move || get_metadata(&reader)).await?;
+    let mut additions: Vec<(String, String)> = Vec::new();
+
+    for enricher in enrichers {
+        let proposed = enricher.enrich(&path, &metadata).await.map_err(|source| Error::Enrichment {
+            enricher: enricher.name().to_string(),
+            source,
+        })?;
+        for (key, value) in proposed {
+            upsert(&mut metadata, &key, &value);
+            upsert(&mut additions, &key, &value);
+        }
+    }
+
+    if !additions.is_empty() {
+        let entries = additions.clone();
+        run_blocking(move || {
+            let entries: Vec<(&str, &str)> = entries.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+            update_entries_in_place(&path, &entries)
+        })
+        .await?;
+    }
+    Ok(additions)
+}
+
+/// Async counterpart of [`enrich_files`]: enriches up to `options.max_concurrency`
+/// files at a time on the calling task.
+///
+/// # Returns
+///
+/// One outcome per input path, in input order, holding the entries written to that file.
+#[cfg(feature = "async")]
+pub async fn enrich_files_async(
+    paths: &[PathBuf],
+    enrichers: &[Box<dyn AsyncEnricher>],
+    options: &BatchOptions,
+) -> Vec<FileOutcome<Vec<(String, String)>>> {
+    let limit = options.max_concurrency.max(1);
+    let mut results: Vec<Option<_>> = (0..paths.len()).map(|_| None).collect();
+    let mut queued = paths.iter().enumerate();
+    let mut in_flight = Vec::new();
+
+    std::future::poll_fn(|cx| {
+        loop {
+            while in_flight.len() < limit
+                && let Some((index, path)) = queued.next()
+            {
+                in_flight.push((index, Box::pin(enrich_file_async(path.clone(), enrichers))));
+            }
+            let before = in_flight.len();
+            in_flight.retain_mut(|(index, future)| match future.as_mut().poll(cx) {
+                Poll::Ready(result) => {
+                    results[*index] = Some(result);
+                    false
+                }
+                Poll::Pending => true,
+            });
+            if in_flight.is_empty() {
+                return Poll::Ready(());
+            }
+            // Only go around again if a slot was freed for a queued file.
+            if in_flight.len() == before {
+                return Poll::Pending;
+            }
+        }
+    })
+    .await;
+
+    results
+        .into_iter()
+        .zip(paths)
+        .map(|(result, path)| FileOutcome {
+            path: path.clone(),
+            result: result.expect("every file is enriched exactly once"),
+        })
+        .collect()
+}
+
+fn upsert(entries: &mut Vec<(String, String)>, key: &str, value: &str) {
+    match entries.iter_mut().find(|(k, _)| k == key) {
+        Some(entry) => entry.1 = value.to_string(),
+        None => entries.push((key.to_string(), value.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{create_minimal_test_pdf, setup_unique_test_dir};
+    use std::fs;
+
+    struct Static(&'static str, &'static str);
+
+    impl Enricher for Static {
+        fn name(&self) -> &str {
+            "static"
+        }
+
+        fn enrich(&self, _path: &Path, _metadata: &[(String, String)]) -> std::result::Result<Vec<(String, String)>, EnricherError> {
+            Ok(vec![(self.0.to_string(), self.1.to_string())])
+        }
+    }
+
+    /// Derives a value from what earlier enrichers added.
+    struct Echo;
+
+    impl Enricher for Echo {
+        fn name(&self) -> &str {
+            "echo"
+        }
+
+        fn enrich(&self, _path: &Path, metadata: &[(String, String)]) -> std::result::Result<Vec<(String, String)>, EnricherError> {
+            match metadata.iter().find(|(k, _)| k == "Doi") {
+                Some((_, doi)) => Ok(vec![("Source".to_string(), format!("https://doi.org/{}", doi))]),
+                None => Err("no DOI available".into()),
+            }
+        }
+    }
+
+    #[test]
+    fn test_enrichers_chain_and_write_once() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("enrich_chain");
+        let files: Vec<PathBuf> = (0..3).map(|i| test_dir.join(format!("doc_{}.pdf", i))).collect();
+        for file in &files {
+            create_minimal_test_pdf(file)?;
+        }
+
+        let enrichers: Vec<Box<dyn Enricher>> = vec![Box::new(Static("Doi", "10.1000/182")), Box::new(Echo)];
+        let outcomes = enrich_files(&files, &enrichers, &BatchOptions { max_concurrency: 2 });
+
+        for outcome in outcomes {
+            let added = outcome.result?;
+            assert_eq!(added.len(), 2);
+            let metadata = get_metadata(outcome.path.to_str().unwrap())?;
+            let source = metadata.iter().find(|(k, _)| k == "Source").map(|(_, v)| v.as_str());
+            assert_eq!(source, Some("https://doi.org/10.1000/182"));
+        }
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_enricher_error_leaves_file_untouched() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("enrich_error");
+        let file = test_dir.join("doc.pdf");
+        create_minimal_test_pdf(&file)?;
+
+        let enrichers: Vec<Box<dyn Enricher>> = vec![Box::new(Static("Project", "Apollo")), Box::new(Echo)];
+        match enrich_file(&file, &enrichers) {
+            Err(Error::Enrichment { enricher, .. }) => assert_eq!(enricher, "echo"),
+            other => panic!("Expected Error::Enrichment, got {:?}", other),
+        }
+        assert!(get_metadata(file.to_str().unwrap())?.is_empty());
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+
+    /// Looks the DOI up "remotely", tracking how many lookups overlap.
+    #[cfg(feature = "async")]
+    struct Resolver {
+        active: std::sync::atomic::AtomicUsize,
+        peak: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+    }
+
+    #[cfg(feature = "async")]
+    impl AsyncEnricher for Resolver {
+        fn name(&self) -> &str {
+            "resolver"
+        }
+
+        fn enrich<'a>(&'a self, path: &'a Path, _metadata: &'a [(String, String)]) -> EnrichFuture<'a> {
+            use std::sync::atomic::Ordering;
+            Box::pin(async move {
+                let active = self.active.fetch_add(1, Ordering::SeqCst) + 1;
+                self.peak.fetch_max(active, Ordering::SeqCst);
+                for _ in 0..5 {
+                    tokio::task::yield_now().await;
+                }
+                self.active.fetch_sub(1, Ordering::SeqCst);
+                if path.ends_with("unknown.pdf") {
+                    return Err("DOI not found".into());
+                }
+                Ok(vec![("Doi".to_string(), "10.1000/182".to_string())])
+            })
+        }
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn test_async_enrichers_respect_concurrency() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("enrich_async");
+        let mut files: Vec<PathBuf> = (0..4).map(|i| test_dir.join(format!("doc_{}.pdf", i))).collect();
+        files.push(test_dir.join("unknown.pdf"));
+        for file in &files {
+            create_minimal_test_pdf(file)?;
+        }
+
+        let peak = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let resolver = Resolver { active: 0.into(), peak: peak.clone() };
+        let enrichers: Vec<Box<dyn AsyncEnricher>> = vec![Box::new(resolver)];
+        let outcomes = enrich_files_async(&files, &enrichers, &BatchOptions { max_concurrency: 2 }).await;
+        assert!((1..=2).contains(&peak.load(std::sync::atomic::Ordering::SeqCst)));
+
+        let paths: Vec<_> = outcomes.iter().map(|outcome| outcome.path.clone()).collect();
+        assert_eq!(paths, files);
+        for outcome in &outcomes[..4] {
+            assert_eq!(outcome.result.as_ref().map(Vec::len).ok(), Some(1));
+            assert!(get_metadata(&outcome.path)?.iter().any(|(k, v)| k == "Doi" && v == "10.1000/182"));
+        }
+        assert!(matches!(&outcomes[4].result, Err(Error::Enrichment { enricher, .. }) if enricher == "resolver"));
+        assert!(get_metadata(&files[4])?.is_empty());
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+}
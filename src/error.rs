@@ -0,0 +1,152 @@
+//! # Error type
+//!
+//! All fallible functions in this crate return [`Error`], so callers can match on
+//! the failure mode instead of inspecting message strings.
+
+use crate::limits::Limit;
+use crate::query::QueryError;
+use lopdf::encryption::DecryptionError;
+use std::path::PathBuf;
+
+/// Convenience alias used throughout the crate.
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Errors that can occur while reading or writing PDF metadata.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error {
+    /// An I/O error occurred while reading the input or accessing the filesystem.
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    /// The input could not be parsed as a PDF document.
+    #[error("Failed to parse PDF: {0}")]
+    Parse(#[source] lopdf::Error),
+
+    /// The document is encrypted and could not be opened without a password.
+    #[error("PDF is encrypted and requires a password")]
+    Encrypted,
+
+    /// The supplied password matches neither the user nor the owner password.
+    #[error("Incorrect password for encrypted PDF")]
+    IncorrectPassword,
+
+    /// The operation requires an Info dictionary but the document has none.
+    #[error("PDF does not have an Info dictionary")]
+    MissingInfoDict,
+
+    /// A value could not be interpreted as a PDF date (`D:YYYYMMDDHHmmSSOHH'mm'`).
+    #[error("Invalid PDF date: {0}")]
+    InvalidDate(String),
+
+    /// A value used an encoding that could not be decoded (hex, BASE64, UTF-16).
+    #[error("Invalid encoding: {0}")]
+    InvalidEncoding(String),
+
+    /// A metadata value could not be converted to the type requested with
+    /// [`get_metadata_as`](crate::get_metadata_as).
+    #[error("Cannot convert {key} value {value:?} to {target}")]
+    Conversion {
+        key: String,
+        value: String,
+        target: &'static str,
+    },
+
+    /// The input is not in a format this operation supports.
+    #[error("Unsupported format: {0}")]
+    UnsupportedFormat(String),
+
+    /// A job file is malformed or refers to unknown operations.
+    #[error("Invalid job file: {0}")]
+    JobFile(String),
+
+    /// A document failed a validation step.
+    #[error("Validation failed: {0}")]
+    Validation(String),
+
+    /// A ZIP-based container (DOCX, ODT, ...) could not be read.
+    #[error("Invalid archive: {0}")]
+    Archive(String),
+
+    /// An XML document (office properties, XMP packet, ...) is malformed.
+    #[error("Invalid XML: {0}")]
+    Xml(String),
+
+    /// A query expression could not be parsed.
+    #[error(transparent)]
+    Query(#[from] QueryError),
+
+    /// An [`Enricher`](crate::enrich::Enricher) failed while computing additions.
+    #[error("Enricher '{enricher}' failed: {source}")]
+    Enrichment {
+        enricher: String,
+        #[source]
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+
+    /// A `sandbox::Sandbox` worker rejected the document, crashed
+    /// or sent an unreadable response.
+    #[error("Sandboxed parser failed: {0}")]
+    Sandbox(String),
+
+    /// A `watch::MetadataWatcher` could not watch the directory.
+    #[error("Cannot watch directory: {0}")]
+    Watch(String),
+
+    /// The operation did not finish within the time given to
+    /// [`with_timeout`](crate::with_timeout). No file was modified.
+    #[error("Operation timed out after {0:?}")]
+    TimedOut(std::time::Duration),
+
+    /// The document broke one of the [`LoadLimits`](crate::LoadLimits) it was
+    /// loaded with; the limit and its configured maximum.
+    #[error("Load limit exceeded: {0}")]
+    LimitExceeded(Limit),
+
+    /// The document has no embedded file with this name.
+    #[error("Attachment not found: {0}")]
+    AttachmentNotFound(String),
+
+    /// The document has no page with this (1-based) number.
+    #[error("Page {0} not found")]
+    PageNotFound(u32),
+
+    /// The key is already set and the write used `ConflictPolicy::Fail`.
+    #[error("Key already exists: {0}")]
+    KeyExists(String),
+
+    /// The document is digitally signed and the write used
+    /// [`SignedDocPolicy::Error`](crate::SignedDocPolicy::Error); the number of
+    /// signatures a full rewrite would invalidate.
+    #[error("PDF has {0} digital signature(s) that a full rewrite would invalidate")]
+    Signed(usize),
+
+    /// The modified document could not be written to `path`.
+    #[error("Error writing to '{}': {source}", path.display())]
+    Write {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+}
+
+impl From<lopdf::Error> for Error {
+    fn from(err: lopdf::Error) -> Self {
+        match err {
+            lopdf::Error::IO(io_err) => Error::Io(io_err),
+            lopdf::Error::Decryption(DecryptionError::IncorrectPassword) => Error::IncorrectPassword,
+            lopdf::Error::Decryption(_) => Error::Encrypted,
+            other => Error::Parse(other),
+        }
+    }
+}
+
+#[cfg(feature = "watch")]
+impl From<notify::Error> for Error {
+    fn from(err: notify::Error) -> Self {
+        match err.kind {
+            notify::ErrorKind::Io(io_err) => Error::Io(io_err),
+            _ => Error::Watch(err.to_string()),
+        }
+    }
+}
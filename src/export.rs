@@ -0,0 +1,443 @@
+//! # Export formats
+//!
+//! Serializes metadata entries for consumption by other tools: [`render`] writes
+//! them as plain `key: value` lines, JSON, CSV or YAML.
+//!
+//! [`rename_keys`] relabels entries the way Acrobat or exiftool present them, so
+//! output can be diffed against those tools when validating a migration, and
+//! [`sort_canonical`] gives entries an order that does not depend on the file.
+//!
+//! [`export_directory_csv`] tabulates a whole directory, one row per PDF.
+
+#[cfg(feature = "std-fs")]
+use crate::batch::{self, BatchOptions};
+use crate::{Error, Result};
+#[cfg(feature = "std-fs")]
+use crate::get_metadata;
+use std::cmp::Ordering;
+#[cfg(feature = "std-fs")]
+use std::fs;
+#[cfg(feature = "std-fs")]
+use std::path::Path;
+
+/// Standard Info keys with the labels of Acrobat's Document Properties dialog.
+const ACROBAT_LABELS: [(&str, &str); 8] = [
+    ("Title", "Title"),
+    ("Author", "Author"),
+    ("Subject", "Subject"),
+    ("Keywords", "Keywords"),
+    ("CreationDate", "Created"),
+    ("ModDate", "Modified"),
+    ("Creator", "Application"),
+    ("Producer", "PDF Producer"),
+];
+
+/// Info keys whose exiftool tag name differs from the key.
+const EXIFTOOL_TAGS: [(&str, &str); 2] = [("CreationDate", "CreateDate"), ("ModDate", "ModifyDate")];
+
+/// How [`rename_keys`] labels entries.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum KeyNaming {
+    /// The Info dictionary keys themselves.
+    #[default]
+    Pdf,
+    /// The labels of Acrobat's Document Properties dialog (`Created`, `Application`,
+    /// ...). Custom keys keep their names, as in the dialog's Custom tab.
+    Acrobat,
+    /// exiftool's default output labels (`Create Date`, `Modify Date`, `Source File Name`).
+    Exiftool,
+    /// exiftool's tag names, as printed with `exiftool -s` (`CreateDate`, `ModifyDate`).
+    ExiftoolTags,
+}
+
+impl std::str::FromStr for KeyNaming {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "pdf" => Ok(KeyNaming::Pdf),
+            "acrobat" => Ok(KeyNaming::Acrobat),
+            "exiftool" => Ok(KeyNaming::Exiftool),
+            "exiftool-tags" => Ok(KeyNaming::ExiftoolTags),
+            other => Err(Error::UnsupportedFormat(format!("key naming '{}'", other))),
+        }
+    }
+}
+
+/// Output format of [`render`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OutputFormat {
+    /// One `key: value` line per entry, without escaping.
+    #[default]
+    Plain,
+    /// A JSON object, as [`to_json`].
+    Json,
+    /// A `key,value` header and one row per entry, as [`to_csv`].
+    Csv,
+    /// A YAML mapping, as [`to_yaml`].
+    Yaml,
+}
+
+impl std::str::FromStr for OutputFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "plain" | "text" => Ok(OutputFormat::Plain),
+            "json" => Ok(OutputFormat::Json),
+            "csv" => Ok(OutputFormat::Csv),
+            "yaml" | "yml" => Ok(OutputFormat::Yaml),
+            other => Err(Error::UnsupportedFormat(format!("output format '{}'", other))),
+        }
+    }
+}
+
+/// Returns the label `naming` uses for the Info key `key`.
+///
+/// ```
+/// use pdf_metadata::export::{KeyNaming, key_label};
+///
+/// assert_eq!(key_label("Producer", KeyNaming::Acrobat), "PDF Producer");
+/// assert_eq!(key_label("CreationDate", KeyNaming::Exiftool), "Create Date");
+/// assert_eq!(key_label("SourceModified", KeyNaming::Exiftool), "Source Modified");
+/// assert_eq!(key_label("ModDate", KeyNaming::ExiftoolTags), "ModifyDate");
+/// ```
+pub fn key_label(key: &str, naming: KeyNaming) -> String {
+    let lookup = |table: &[(&str, &str)]| table.iter().find(|(k, _)| *k == key).map(|(_, label)| label.to_string());
+    match naming {
+        KeyNaming::Pdf => key.to_string(),
+        KeyNaming::Acrobat => lookup(&ACROBAT_LABELS).unwrap_or_else(|| key.to_string()),
+        KeyNaming::ExiftoolTags => lookup(&EXIFTOOL_TAGS).unwrap_or_else(|| exiftool_tag_name(key)),
+        KeyNaming::Exiftool => exiftool_description(&key_label(key, KeyNaming::ExiftoolTags)),
+    }
+}
+
+/// Relabels the keys of `entries` according to `naming`, keeping values and order.
+pub fn rename_keys(entries: &[(String, String)], naming: KeyNaming) -> Vec<(String, String)> {
+    entries.iter().map(|(key, value)| (key_label(key, naming), value.clone())).collect()
+}
+
+/// The standard Info keys in the order [`sort_canonical`] puts them first.
+pub const CANONICAL_ORDER: [&str; 8] =
+    ["Title", "Author", "Subject", "Keywords", "Creator", "Producer", "CreationDate", "ModDate"];
+
+/// Sorts `entries` into a canonical order: the standard keys first, in the order of
+/// [`CANONICAL_ORDER`], then the other keys alphabetically. Unlike the document's
+/// own order, this does not change when a tool rewrites the Info dictionary, so
+/// output sorted this way can be diffed across files and runs.
+///
+/// ```
+/// use pdf_metadata::export::sort_canonical;
+///
+/// let mut entries = vec![
+///     ("Source".to_string(), "scan"),
+///     ("Producer".to_string(), "lopdf"),
+///     ("Department".to_string(), "Legal"),
+///     ("Title".to_string(), "Atlas"),
+/// ];
+/// sort_canonical(&mut entries);
+/// let keys: Vec<&str> = entries.iter().map(|(key, _)| key.as_str()).collect();
+/// assert_eq!(keys, ["Title", "Producer", "Department", "Source"]);
+/// ```
+pub fn sort_canonical<V>(entries: &mut [(String, V)]) {
+    entries.sort_by(|(a, _), (b, _)| canonical_cmp(a, b));
+}
+
+fn canonical_cmp(a: &str, b: &str) -> Ordering {
+    let rank = |key: &str| CANONICAL_ORDER.iter().position(|standard| *standard == key).unwrap_or(CANONICAL_ORDER.len());
+    rank(a).cmp(&rank(b)).then_with(|| a.cmp(b))
+}
+
+/// exiftool's tag name for an Info key it has no definition for: characters other
+/// than letters, digits, `-` and `_` are dropped and the first letter is capitalized.
+fn exiftool_tag_name(key: &str) -> String {
+    let mut name: String = key.chars().filter(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_')).collect();
+    if let Some(first) = name.get(..1) {
+        name.replace_range(..1, &first.to_ascii_uppercase());
+    }
+    name
+}
+
+/// exiftool's description of a tag name (`Image::ExifTool::MakeDescription`): words
+/// are separated at case and digit boundaries and underscores become spaces.
+fn exiftool_description(tag: &str) -> String {
+    let desc: Vec<char> = tag.replace('_', " ").chars().collect();
+    // Each pass mirrors one substitution of the Perl original, which applies them in order.
+    let desc = insert_spaces(&desc, 2, |w| w[0].is_ascii_lowercase() && (w[1].is_ascii_uppercase() || w[1].is_ascii_digit()));
+    let desc = insert_spaces(&desc, 3, |w| w[0].is_ascii_uppercase() && w[1].is_ascii_uppercase() && w[2].is_ascii_lowercase());
+    let desc = insert_spaces(&desc, 3, |w| w[0].is_ascii_digit() && w[1].is_ascii_uppercase() && !w[2].is_whitespace());
+    desc.into_iter().collect()
+}
+
+/// Inserts a space after the first character of every non-overlapping `len`-character
+/// window matching `matches`, scanning left to right like a global regex substitution.
+fn insert_spaces(chars: &[char], len: usize, matches: impl Fn(&[char]) -> bool) -> Vec<char> {
+    let mut out = Vec::with_capacity(chars.len() + 4);
+    let mut i = 0;
+    while i < chars.len() {
+        match chars.get(i..i + len) {
+            Some(window) if matches(window) => {
+                out.push(window[0]);
+                out.push(' ');
+                out.extend_from_slice(&window[1..]);
+                i += len;
+            }
+            _ => {
+                out.push(chars[i]);
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+/// Serializes `(key, value)` entries as a JSON object, keeping their order.
+///
+/// ```
+/// use pdf_metadata::export::to_json;
+///
+/// let entries = vec![("Title".to_string(), "Q1 \"final\"".to_string())];
+/// assert_eq!(to_json(&entries), r#"{"Title":"Q1 \"final\""}"#);
+/// ```
+pub fn to_json(entries: &[(String, String)]) -> String {
+    let fields: Vec<String> = entries
+        .iter()
+        .map(|(key, value)| format!("{}:{}", json_string(key), json_string(value)))
+        .collect();
+    format!("{{{}}}", fields.join(","))
+}
+
+/// Serializes `(key, value)` entries in `format`, ending with a newline.
+pub fn render(entries: &[(String, String)], format: OutputFormat) -> String {
+    match format {
+        OutputFormat::Plain => entries.iter().map(|(key, value)| format!("{}: {}\n", key, value)).collect(),
+        OutputFormat::Json => format!("{}\n", to_json(entries)),
+        OutputFormat::Csv => to_csv(entries),
+        OutputFormat::Yaml => to_yaml(entries),
+    }
+}
+
+/// Serializes `(key, value)` entries as CSV (RFC 4180) with a `key,value` header.
+///
+/// Fields containing commas, quotes, line breaks or surrounding spaces are quoted.
+///
+/// ```
+/// use pdf_metadata::export::to_csv;
+///
+/// let entries = vec![("Title".to_string(), "Q1, \"final\"".to_string())];
+/// assert_eq!(to_csv(&entries), "key,value\r\nTitle,\"Q1, \"\"final\"\"\"\r\n");
+/// ```
+pub fn to_csv(entries: &[(String, String)]) -> String {
+    let mut out = String::from("key,value\r\n");
+    for (key, value) in entries {
+        out.push_str(&format!("{},{}\r\n", csv_field(key), csv_field(value)));
+    }
+    out
+}
+
+fn csv_field(text: &str) -> String {
+    let needs_quotes = text.contains([',', '"', '\r', '\n']) || text.starts_with(' ') || text.ends_with(' ');
+    if needs_quotes { format!("\"{}\"", text.replace('"', "\"\"")) } else { text.to_string() }
+}
+
+/// Writes a CSV table (RFC 4180) with one row per PDF under `dir`, subdirectories
+/// included, to `out_csv`.
+///
+/// The columns are `path`, one per entry of `fields` and `error`. Missing entries
+/// are left empty; files that cannot be read get empty fields and the error
+/// message. With no `fields`, every key found in the directory becomes a column,
+/// in the order of [`sort_canonical`]. Rows are sorted by path.
+///
+/// # Returns
+///
+/// * `Ok(usize)`: The number of rows written, not counting the header.
+/// * `Err(Error)`: If `dir` cannot be listed or `out_csv` cannot be written.
+///
+/// # Example
+///
+/// ```no_run
+/// use pdf_metadata::export::export_directory_csv;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let rows = export_directory_csv("/srv/archive", "archive.csv", &["Title", "Author", "Producer"])?;
+///     println!("{} documents", rows);
+///     Ok(())
+/// }
+/// ```
+#[cfg(feature = "std-fs")]
+pub fn export_directory_csv<P: AsRef<Path>, Q: AsRef<Path>>(dir: P, out_csv: Q, fields: &[&str]) -> Result<usize> {
+    let files = batch::collect_pdfs(dir, true)?;
+    let outcomes = batch::run(&files, &BatchOptions::default(), |path| get_metadata(path));
+
+    let columns: Vec<String> = if fields.is_empty() {
+        let mut keys: Vec<String> = outcomes
+            .iter()
+            .filter_map(|outcome| outcome.result.as_ref().ok())
+            .flat_map(|metadata| metadata.iter().map(|(key, _)| key.clone()))
+            .collect();
+        keys.sort_by(|a, b| canonical_cmp(a, b));
+        keys.dedup();
+        keys
+    } else {
+        fields.iter().map(|field| field.to_string()).collect()
+    };
+
+    let mut out = String::new();
+    let header: Vec<String> =
+        std::iter::once("path").chain(columns.iter().map(String::as_str)).chain(["error"]).map(csv_field).collect();
+    out.push_str(&format!("{}\r\n", header.join(",")));
+    for outcome in &outcomes {
+        let mut row = vec![csv_field(&outcome.path.to_string_lossy())];
+        match &outcome.result {
+            Ok(metadata) => {
+                let value = |column: &String| metadata.iter().find(|(key, _)| key == column).map(|(_, value)| value.as_str());
+                row.extend(columns.iter().map(|column| csv_field(value(column).unwrap_or_default())));
+                row.push(String::new());
+            }
+            Err(e) => {
+                row.extend(columns.iter().map(|_| String::new()));
+                row.push(csv_field(&e.to_string()));
+            }
+        }
+        out.push_str(&format!("{}\r\n", row.join(",")));
+    }
+    fs::write(out_csv, out)?;
+    Ok(outcomes.len())
+}
+
+/// Serializes `(key, value)` entries as a YAML mapping, keeping their order.
+///
+/// Keys and values are written as double-quoted scalars, so values such as `yes`,
+/// `null` or `2024-01-02` stay strings.
+///
+/// ```
+/// use pdf_metadata::export::to_yaml;
+///
+/// let entries = vec![("Trapped".to_string(), "no".to_string())];
+/// assert_eq!(to_yaml(&entries), "\"Trapped\": \"no\"\n");
+/// ```
+pub fn to_yaml(entries: &[(String, String)]) -> String {
+    if entries.is_empty() {
+        return "{}\n".to_string();
+    }
+    entries.iter().map(|(key, value)| format!("{}: {}\n", yaml_string(key), yaml_string(value))).collect()
+}
+
+/// Quotes `text` as a YAML double-quoted scalar, escaping the characters YAML does
+/// not allow unescaped.
+fn yaml_string(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len() + 2);
+    escaped.push('"');
+    for ch in text.chars() {
+        match ch {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 || ('\u{7F}'..='\u{9F}').contains(&c) || c == '\u{FEFF}' => {
+                escaped.push_str(&format!("\\u{:04X}", c as u32))
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+/// Quotes and escapes `text` as a JSON string literal.
+pub fn json_string(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len() + 2);
+    escaped.push('"');
+    for ch in text.chars() {
+        match ch {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{create_minimal_test_pdf, setup_unique_test_dir};
+    use crate::update_metadata_in_place;
+
+    #[test]
+    fn test_json_string_escapes_control_characters() {
+        assert_eq!(json_string("a\\b\n\u{1}"), r#""a\\b\n\u0001""#);
+        assert_eq!(to_json(&[]), "{}");
+    }
+
+    #[test]
+    fn test_render_escapes_each_format() {
+        let entries = vec![
+            ("Title".to_string(), "Line 1\nLine 2, \"quoted\"".to_string()),
+            ("Custom\u{85}".to_string(), " padded".to_string()),
+        ];
+        assert_eq!(
+            render(&entries, OutputFormat::Csv),
+            "key,value\r\nTitle,\"Line 1\nLine 2, \"\"quoted\"\"\"\r\nCustom\u{85},\" padded\"\r\n"
+        );
+        assert_eq!(
+            render(&entries, OutputFormat::Yaml),
+            "\"Title\": \"Line 1\\nLine 2, \\\"quoted\\\"\"\n\"Custom\\u0085\": \" padded\"\n"
+        );
+        assert_eq!(render(&entries[1..], OutputFormat::Json), "{\"Custom\u{85}\":\" padded\"}\n");
+        assert_eq!(render(&[], OutputFormat::Yaml), "{}\n");
+        assert_eq!("yml".parse::<OutputFormat>().unwrap(), OutputFormat::Yaml);
+    }
+
+    #[test]
+    fn test_key_labels_match_incumbent_tools() {
+        let entries: Vec<(String, String)> = ["Title", "CreationDate", "Creator", "Producer", "GTS_PDFXVersion", "Doc ID", "PTEX.Fullbanner"]
+            .iter()
+            .map(|key| (key.to_string(), String::new()))
+            .collect();
+        let labels = |naming| rename_keys(&entries, naming).into_iter().map(|(key, _)| key).collect::<Vec<_>>();
+
+        assert_eq!(labels(KeyNaming::Pdf), ["Title", "CreationDate", "Creator", "Producer", "GTS_PDFXVersion", "Doc ID", "PTEX.Fullbanner"]);
+        assert_eq!(labels(KeyNaming::Acrobat), ["Title", "Created", "Application", "PDF Producer", "GTS_PDFXVersion", "Doc ID", "PTEX.Fullbanner"]);
+        assert_eq!(labels(KeyNaming::ExiftoolTags), ["Title", "CreateDate", "Creator", "Producer", "GTS_PDFXVersion", "DocID", "PTEXFullbanner"]);
+        assert_eq!(labels(KeyNaming::Exiftool), ["Title", "Create Date", "Creator", "Producer", "GTS PDFX Version", "Doc ID", "PTEX Fullbanner"]);
+        assert_eq!("exiftool-tags".parse::<KeyNaming>().unwrap(), KeyNaming::ExiftoolTags);
+        assert!("word".parse::<KeyNaming>().is_err());
+    }
+
+    #[test]
+    fn test_export_directory_csv() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("export_directory_csv");
+        let dir = test_dir.join("corpus");
+        fs::create_dir_all(dir.join("2023"))?;
+        create_minimal_test_pdf(&dir.join("a.pdf"))?;
+        update_metadata_in_place(dir.join("a.pdf").to_str().unwrap(), "Title", "Relatório, \"final\"")?;
+        create_minimal_test_pdf(&dir.join("2023/b.pdf"))?;
+        update_metadata_in_place(dir.join("2023/b.pdf").to_str().unwrap(), "Author", "Ana")?;
+        fs::write(dir.join("c.pdf"), b"not a pdf")?;
+        let out = test_dir.join("corpus.csv");
+
+        assert_eq!(export_directory_csv(&dir, &out, &["Title", "Author"])?, 3);
+        let csv = fs::read_to_string(&out)?;
+        let rows: Vec<&str> = csv.split("\r\n").collect();
+        assert_eq!(rows[0], "path,Title,Author,error");
+        assert_eq!(rows[1], format!("{},,Ana,", dir.join("2023/b.pdf").display()));
+        assert_eq!(rows[2], format!("{},\"Relatório, \"\"final\"\"\",,", dir.join("a.pdf").display()));
+        assert!(rows[3].starts_with(&format!("{},,,", dir.join("c.pdf").display())) && rows[3].len() > 20);
+        assert_eq!(rows[4], "");
+
+        export_directory_csv(&dir, &out, &[])?;
+        assert!(fs::read_to_string(&out)?.starts_with("path,Title,Author,ModDate,error\r\n"));
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,402 @@
+//! Reading the Info dictionary without parsing the whole document.
+//!
+//! `Document::load` parses every object in the file, page content streams
+//! included, which dominates the cost of reading metadata from large PDFs. The
+//! reader here follows the `startxref` chain of classic cross-reference tables,
+//! seeks straight to the Info object and parses nothing else. Whenever the file
+//! does not fit that model (cross-reference streams, encryption, a damaged table,
+//! Info values stored in other objects) it gives up and the caller falls back to a
+//! full parse, so results never differ from the full reader's. The same scan locates the newest trailer for appending
+//! an incremental update to a seekable stream.
+
+use crate::stats::{self, Counter};
+use crate::value::MetadataValue;
+use crate::{Result, load_document_mem, read_info_values};
+#[cfg(feature = "std-fs")]
+use crate::load_document;
+use lopdf::xref::XrefEntry;
+use lopdf::{Dictionary, Document, Object, ObjectId, Reader};
+use std::collections::HashSet;
+#[cfg(feature = "std-fs")]
+use std::fs::File;
+use std::io::{BufRead, BufReader, Cursor, Read, Seek, SeekFrom};
+#[cfg(feature = "std-fs")]
+use std::path::Path;
+use std::str::FromStr;
+
+/// How many bytes at the end of the file are searched for `startxref`.
+const TAIL_LEN: u64 = 1024;
+/// Size of one cross-reference table entry, including its two-byte end of line.
+const XREF_ENTRY_LEN: u64 = 20;
+/// Upper bound on the bytes read for a trailer or the Info object.
+const MAX_OBJECT_LEN: usize = 16 * 1024 * 1024;
+/// Upper bound on the number of cross-reference sections followed through `/Prev`.
+const MAX_SECTIONS: usize = 4096;
+/// Tokens longer than this are never part of a cross-reference table.
+const MAX_TOKEN_LEN: usize = 32;
+
+/// Reads the typed Info entries of the file at `file_path`, parsing only the
+/// cross-reference chain and the Info object when possible.
+#[cfg(feature = "std-fs")]
+pub(crate) fn read_info_values_from_file(file_path: impl AsRef<Path>) -> Result<Vec<(String, MetadataValue)>> {
+    let file_path = file_path.as_ref();
+    if let Some(values) = scan_info(&mut File::open(file_path)?) {
+        return Ok(values);
+    }
+    Ok(read_info_values(&load_document(file_path)?))
+}
+
+/// Like `read_info_values_from_file`, for a PDF in memory. The fallback parses
+/// `pdf_content` in place, without copying it.
+pub(crate) fn read_info_values_from_mem(pdf_content: &[u8]) -> Result<Vec<(String, MetadataValue)>> {
+    if let Some(values) = scan_info(&mut Cursor::new(pdf_content)) {
+        return Ok(values);
+    }
+    Ok(read_info_values(&load_document_mem(pdf_content)?))
+}
+
+/// Like `read_info_values_from_file`, for any seekable source. The full-parse
+/// fallback reads the whole source into memory.
+pub(crate) fn read_info_values_from_reader<R: Read + Seek>(reader: &mut R) -> Result<Vec<(String, MetadataValue)>> {
+    if let Some(values) = scan_info(reader) {
+        return Ok(values);
+    }
+    let mut pdf_content = Vec::new();
+    reader.seek(SeekFrom::Start(0))?;
+    reader.read_to_end(&mut pdf_content)?;
+    Ok(read_info_values(&load_document_mem(&pdf_content)?))
+}
+
+/// What the fast path learns about the newest revision of a document.
+pub(crate) struct ScannedDocument {
+    /// The newest trailer dictionary.
+    pub trailer: Dictionary,
+    /// The Info object the trailer points at, if any.
+    pub info: Option<(ObjectId, Object)>,
+    /// Offset of the newest cross-reference table.
+    pub xref_start: u64,
+    /// Total length of the source.
+    pub len: u64,
+}
+
+/// Locates the newest trailer and the Info object of `reader` without a full parse.
+///
+/// Returns `None` whenever the full reader is needed instead.
+pub(crate) fn scan_document<R: Read + Seek>(reader: &mut R) -> Option<ScannedDocument> {
+    let mut scanner = Scanner::new(reader)?;
+    let xref_start = scanner.startxref()?;
+    let (subsections, trailer) = scanner.xref_section(xref_start)?;
+    // Encrypted strings and hybrid files with cross-reference streams need the full reader.
+    if trailer.has(b"Encrypt") || trailer.has(b"XRefStm") {
+        return None;
+    }
+
+    let info = match trailer.get(b"Info").and_then(Object::as_reference) {
+        Ok(info_id) => {
+            let offset = scanner.find_offset(info_id, subsections, &trailer)?;
+            Some((info_id, scanner.object_at(offset, info_id)?))
+        }
+        Err(_) => None,
+    };
+    Some(ScannedDocument { trailer, info, xref_start, len: scanner.len })
+}
+
+fn scan_info<R: Read + Seek>(reader: &mut R) -> Option<Vec<(String, MetadataValue)>> {
+    let scanned = scan_document(reader)?;
+    // Only the Info object is loaded, so values stored in other objects need the full reader.
+    if scanned.info.as_ref().is_some_and(|(_, info)| has_references(info)) {
+        return None;
+    }
+    stats::record(Counter::DocumentScanned);
+    let mut document = Document::new();
+    document.objects.extend(scanned.info);
+    document.trailer = scanned.trailer;
+    Some(read_info_values(&document))
+}
+
+fn has_references(object: &Object) -> bool {
+    match object {
+        Object::Reference(_) => true,
+        Object::Array(items) => items.iter().any(has_references),
+        Object::Dictionary(dict) => dict.iter().any(|(_, value)| has_references(value)),
+        _ => false,
+    }
+}
+
+/// A run of consecutive object numbers in a cross-reference table.
+struct Subsection {
+    first: u32,
+    count: u32,
+    /// File offset of the subsection's first entry.
+    entries_at: u64,
+}
+
+impl Subsection {
+    fn contains(&self, number: u32) -> bool {
+        number >= self.first && number - self.first < self.count
+    }
+}
+
+struct Scanner<R: Read + Seek> {
+    reader: BufReader<R>,
+    len: u64,
+}
+
+impl<R: Read + Seek> Scanner<R> {
+    /// Wraps `source`, requiring the `%PDF-` header at offset 0 so that table
+    /// offsets can be used as they are.
+    fn new(mut source: R) -> Option<Self> {
+        let len = source.seek(SeekFrom::End(0)).ok()?;
+        source.seek(SeekFrom::Start(0)).ok()?;
+        let mut reader = BufReader::new(source);
+        let mut header = [0u8; 5];
+        reader.read_exact(&mut header).ok()?;
+        (&header == b"%PDF-").then_some(Scanner { reader, len })
+    }
+
+    /// Returns the offset recorded after the last `startxref` keyword.
+    fn startxref(&mut self) -> Option<u64> {
+        let tail_start = self.len.saturating_sub(TAIL_LEN);
+        self.seek(tail_start)?;
+        let mut tail = Vec::new();
+        self.reader.by_ref().take(TAIL_LEN).read_to_end(&mut tail).ok()?;
+        let keyword = tail.windows(9).rposition(|w| w == b"startxref")?;
+        self.seek(tail_start + keyword as u64 + 9)?;
+        self.number()
+    }
+
+    /// Reads the cross-reference table starting at `offset` and the trailer after it.
+    ///
+    /// Entries are not read: every subsection is skipped with a single seek, relying
+    /// on the fixed entry size, and only the entry that is needed is read later.
+    fn xref_section(&mut self, offset: u64) -> Option<(Vec<Subsection>, Dictionary)> {
+        self.seek(offset)?;
+        if self.token()? != b"xref" {
+            return None;
+        }
+        let mut subsections = Vec::new();
+        loop {
+            self.skip_whitespace()?;
+            let token_at = self.reader.stream_position().ok()?;
+            let token = self.token()?;
+            if token.starts_with(b"trailer") {
+                self.seek(token_at + b"trailer".len() as u64)?;
+                let mut bytes = Vec::new();
+                self.read_until(b"startxref", &mut bytes)?;
+                let trailer = parse_dictionary(&bytes)?;
+                return Some((subsections, trailer));
+            }
+            let first = parse_number(&token)?;
+            let count: u32 = self.number()?;
+            self.skip_whitespace()?;
+            let entries_at = self.reader.stream_position().ok()?;
+            let end = entries_at.checked_add(u64::from(count) * XREF_ENTRY_LEN)?;
+            if end > self.len {
+                return None;
+            }
+            self.seek(end)?;
+            subsections.push(Subsection { first, count, entries_at });
+        }
+    }
+
+    /// Finds the offset of object `id`, following `/Prev` from the newest section back.
+    fn find_offset(&mut self, id: ObjectId, mut subsections: Vec<Subsection>, trailer: &Dictionary) -> Option<u64> {
+        let mut trailer = trailer.clone();
+        let mut seen = HashSet::new();
+        loop {
+            if let Some(subsection) = subsections.iter().find(|s| s.contains(id.0)) {
+                return self.entry_offset(subsection, id);
+            }
+            let prev = u64::try_from(trailer.get(b"Prev").and_then(Object::as_i64).ok()?).ok()?;
+            if !seen.insert(prev) || seen.len() > MAX_SECTIONS {
+                return None;
+            }
+            (subsections, trailer) = self.xref_section(prev)?;
+        }
+    }
+
+    /// Reads the table entry of `id`, which must be an in-use entry of the same generation.
+    fn entry_offset(&mut self, subsection: &Subsection, id: ObjectId) -> Option<u64> {
+        self.seek(subsection.entries_at + u64::from(id.0 - subsection.first) * XREF_ENTRY_LEN)?;
+        let mut entry = [0u8; XREF_ENTRY_LEN as usize];
+        self.reader.read_exact(&mut entry).ok()?;
+        let mut fields = std::str::from_utf8(&entry).ok()?.split_ascii_whitespace();
+        let offset: u64 = fields.next()?.parse().ok()?;
+        let generation: u16 = fields.next()?.parse().ok()?;
+        (generation == id.1 && fields.next()? == "n" && offset < self.len).then_some(offset)
+    }
+
+    /// Parses the indirect object `id` stored at `offset`.
+    fn object_at(&mut self, offset: u64, id: ObjectId) -> Option<Object> {
+        self.seek(offset)?;
+        let mut bytes = Vec::new();
+        loop {
+            let found = self.read_until(b"endobj", &mut bytes)?;
+            bytes.extend_from_slice(b"endobj");
+            if let Some(object) = parse_object(&bytes, id) {
+                return Some(object);
+            }
+            // The keyword may have been part of a string value; keep reading.
+            if !found {
+                return None;
+            }
+        }
+    }
+
+    fn seek(&mut self, offset: u64) -> Option<()> {
+        self.reader.seek(SeekFrom::Start(offset)).ok().map(|_| ())
+    }
+
+    fn skip_whitespace(&mut self) -> Option<()> {
+        loop {
+            let buffer = self.reader.fill_buf().ok()?;
+            let skipped = buffer.iter().take_while(|b| is_whitespace(**b)).count();
+            let done = skipped < buffer.len() || buffer.is_empty();
+            self.reader.consume(skipped);
+            if done {
+                return Some(());
+            }
+        }
+    }
+
+    /// Reads the next whitespace-delimited token.
+    fn token(&mut self) -> Option<Vec<u8>> {
+        self.skip_whitespace()?;
+        let mut token = Vec::new();
+        loop {
+            let buffer = self.reader.fill_buf().ok()?;
+            let taken = buffer.iter().take_while(|b| !is_whitespace(**b)).count();
+            token.extend_from_slice(&buffer[..taken]);
+            let done = taken < buffer.len() || buffer.is_empty();
+            self.reader.consume(taken);
+            if done || token.len() > MAX_TOKEN_LEN {
+                break;
+            }
+        }
+        (!token.is_empty()).then_some(token)
+    }
+
+    fn number<T: FromStr>(&mut self) -> Option<T> {
+        parse_number(&self.token()?)
+    }
+
+    /// Appends to `bytes` everything up to the next occurrence of `marker`, or up to
+    /// the end of the file, and leaves the reader just after the marker.
+    ///
+    /// Returns whether the marker was found.
+    fn read_until(&mut self, marker: &[u8], bytes: &mut Vec<u8>) -> Option<bool> {
+        let start = self.reader.stream_position().ok()?;
+        let appended_at = bytes.len();
+        loop {
+            let buffer = self.reader.fill_buf().ok()?;
+            if buffer.is_empty() {
+                return Some(false);
+            }
+            let search_from = bytes.len().saturating_sub(marker.len() - 1).max(appended_at);
+            let read = buffer.len();
+            bytes.extend_from_slice(buffer);
+            self.reader.consume(read);
+            if let Some(found) = bytes[search_from..].windows(marker.len()).position(|w| w == marker) {
+                let end = search_from + found;
+                bytes.truncate(end);
+                self.seek(start + (end - appended_at + marker.len()) as u64)?;
+                return Some(true);
+            }
+            if bytes.len() > MAX_OBJECT_LEN {
+                return None;
+            }
+        }
+    }
+}
+
+fn is_whitespace(byte: u8) -> bool {
+    matches!(byte, b' ' | b'\t' | b'\r' | b'\n' | b'\x0C' | b'\0')
+}
+
+fn parse_number<T: FromStr>(token: &[u8]) -> Option<T> {
+    std::str::from_utf8(token).ok()?.parse().ok()
+}
+
+/// Parses a bare dictionary, such as a trailer, by wrapping it in an indirect object.
+pub(crate) fn parse_dictionary(bytes: &[u8]) -> Option<Dictionary> {
+    let mut wrapped = b"1 0 obj\n".to_vec();
+    wrapped.extend_from_slice(bytes);
+    wrapped.extend_from_slice(b"\nendobj\n");
+    match parse_object(&wrapped, (1, 0))? {
+        Object::Dictionary(dictionary) => Some(dictionary),
+        _ => None,
+    }
+}
+
+/// Parses the indirect object `id` at the start of `bytes` with lopdf's own parser.
+pub(crate) fn parse_object(bytes: &[u8], id: ObjectId) -> Option<Object> {
+    let mut document = Document::new();
+    document.reference_table.insert(id.0, XrefEntry::Normal { offset: 0, generation: id.1 });
+    let reader = Reader { buffer: bytes, document };
+    reader.get_object(id, &mut HashSet::new()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{create_classic_xref_test_pdf, create_minimal_test_pdf, setup_unique_test_dir};
+    use crate::{update_entries_in_place, update_metadata_incremental};
+    use std::fs;
+
+    fn full_read(path: &std::path::Path) -> Vec<(String, MetadataValue)> {
+        read_info_values(&Document::load(path).unwrap())
+    }
+
+    #[test]
+    fn test_scan_matches_full_parse_across_incremental_updates() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("fast_info_scan");
+        let file = test_dir.join("updated.pdf");
+        create_classic_xref_test_pdf(&file)?;
+        let file_str = file.to_str().unwrap();
+
+        // No Info dictionary yet.
+        assert_eq!(scan_info(&mut File::open(&file)?), Some(Vec::new()));
+
+        update_entries_in_place(file_str, &[("Title", "Original"), ("Custom", "(nested) ) endobj")])?;
+        update_metadata_incremental(file_str, "Title", "Revised")?;
+        update_metadata_incremental(file_str, "Author", "Ana")?;
+
+        let scanned = scan_info(&mut File::open(&file)?).expect("classic table should take the fast path");
+        assert_eq!(scanned, full_read(&file));
+        assert!(scanned.contains(&("Title".to_string(), MetadataValue::String("Revised".to_string()))));
+        assert!(scanned.contains(&("Author".to_string(), MetadataValue::String("Ana".to_string()))));
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_falls_back_when_fast_path_does_not_apply() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("fast_info_fallback");
+
+        // Cross-reference stream, as lopdf writes by default.
+        let modern = test_dir.join("modern.pdf");
+        create_minimal_test_pdf(&modern)?;
+        update_entries_in_place(modern.to_str().unwrap(), &[("Title", "Streamed")])?;
+        assert_eq!(scan_info(&mut File::open(&modern)?), None);
+        let values = read_info_values_from_file(modern.to_str().unwrap())?;
+        assert_eq!(values, full_read(&modern));
+        assert!(!values.is_empty());
+
+        // A `startxref` that does not point at a table; the full reader decides.
+        let damaged = test_dir.join("damaged.pdf");
+        create_classic_xref_test_pdf(&damaged)?;
+        let mut bytes = fs::read(&damaged)?;
+        let keyword = bytes.windows(9).rposition(|w| w == b"startxref").unwrap();
+        bytes.truncate(keyword);
+        bytes.extend_from_slice(b"startxref\n1\n%%EOF\n");
+        fs::write(&damaged, &bytes)?;
+        assert_eq!(scan_info(&mut File::open(&damaged)?), None);
+        assert!(read_info_values_from_file(damaged.to_str().unwrap()).is_err());
+
+        assert!(matches!(read_info_values_from_file("missing_fast_info.pdf"), Err(crate::Error::Io(_))));
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,311 @@
+//! # C interface (`ffi` feature)
+//!
+//! `extern "C"` functions for C, C++, Swift and other languages that can link a
+//! shared library built from this crate with
+//! `cargo rustc --release --lib --features ffi --crate-type cdylib`. The matching declarations are in
+//! `include/pdf_metadata.h`, generated with `cbindgen` (see `cbindgen.toml`).
+//!
+//! Every function returns a [`PdfMetadataStatus`]; on failure
+//! [`pdf_metadata_last_error`] describes the error. Strings are UTF-8 and
+//! NUL-terminated. Strings and buffers returned through out-parameters are owned
+//! by the caller and must be released with [`pdf_metadata_string_free`] and
+//! [`pdf_metadata_buffer_free`]; nothing else needs to be freed.
+//!
+//! ```c
+//! char *title = NULL;
+//! if (pdf_metadata_get("report.pdf", "Title", &title) == PDF_METADATA_STATUS_OK) {
+//!     printf("%s\n", title);
+//!     pdf_metadata_string_free(title);
+//! } else {
+//!     fprintf(stderr, "%s\n", pdf_metadata_last_error());
+//! }
+//! ```
+
+use crate::{Error, Result};
+use std::cell::RefCell;
+use std::ffi::{CStr, CString, c_char};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use std::slice;
+
+/// Outcome of an FFI call.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PdfMetadataStatus {
+    Ok = 0,
+    /// A pointer was null, or a string was not valid UTF-8.
+    InvalidArgument = 1,
+    /// The requested key is not in the Info dictionary.
+    NotFound = 2,
+    /// The file could not be read or written.
+    Io = 3,
+    /// The input is not a valid PDF.
+    Parse = 4,
+    /// The document is encrypted and needs a password.
+    Encrypted = 5,
+    /// The document has no Info dictionary.
+    MissingInfoDict = 6,
+    /// Any other error; see `pdf_metadata_last_error`.
+    Other = 98,
+    /// The library panicked. This is a bug.
+    Panic = 99,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Returns the message of the last error on the calling thread, or null if the last
+/// call succeeded.
+///
+/// The string stays valid until the next call into this library on the same thread
+/// and must not be freed.
+#[unsafe(no_mangle)]
+pub extern "C" fn pdf_metadata_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().as_ref().map_or(ptr::null(), |message| message.as_ptr()))
+}
+
+/// Reads the value of `key` from the PDF file at `path`.
+///
+/// On success `*out_value` receives a new string; with `NotFound` it is set to null.
+///
+/// # Safety
+///
+/// `path` and `key` must be valid NUL-terminated strings and `out_value` a valid
+/// pointer to writable storage.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pdf_metadata_get(
+    path: *const c_char,
+    key: *const c_char,
+    out_value: *mut *mut c_char,
+) -> PdfMetadataStatus {
+    guard(|| {
+        let path = unsafe { arg_str(path) }?;
+        let key = unsafe { arg_str(key) }?;
+        let out_value = unsafe { out_ptr(out_value) }?;
+        *out_value = ptr::null_mut();
+        let entries = crate::get_metadata(path).map_err(Failure::from)?;
+        *out_value = lookup(entries, key)?;
+        Ok(())
+    })
+}
+
+/// Sets `key` to `value` in the PDF file at `path`, replacing the file in place.
+/// `ModDate` is refreshed.
+///
+/// # Safety
+///
+/// `path`, `key` and `value` must be valid NUL-terminated strings.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pdf_metadata_set(
+    path: *const c_char,
+    key: *const c_char,
+    value: *const c_char,
+) -> PdfMetadataStatus {
+    guard(|| {
+        let path = unsafe { arg_str(path) }?;
+        let key = unsafe { arg_str(key) }?;
+        let value = unsafe { arg_str(value) }?;
+        crate::update_metadata_in_place(path, key, value).map_err(Failure::from)
+    })
+}
+
+/// Like [`pdf_metadata_get`], for a PDF held in memory.
+///
+/// # Safety
+///
+/// `data` must point to `len` readable bytes, `key` must be a valid NUL-terminated
+/// string and `out_value` a valid pointer to writable storage.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pdf_metadata_get_buffer(
+    data: *const u8,
+    len: usize,
+    key: *const c_char,
+    out_value: *mut *mut c_char,
+) -> PdfMetadataStatus {
+    guard(|| {
+        let data = unsafe { arg_bytes(data, len) }?;
+        let key = unsafe { arg_str(key) }?;
+        let out_value = unsafe { out_ptr(out_value) }?;
+        *out_value = ptr::null_mut();
+        let entries = crate::get_pdf_metadata(data).map_err(Failure::from)?;
+        *out_value = lookup(entries, key)?;
+        Ok(())
+    })
+}
+
+/// Like [`pdf_metadata_set`], for a PDF held in memory: the input is left unchanged
+/// and the updated PDF is returned in `*out_data` and `*out_len`.
+///
+/// # Safety
+///
+/// `data` must point to `len` readable bytes, `key` and `value` must be valid
+/// NUL-terminated strings, and `out_data` and `out_len` valid pointers to writable
+/// storage.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pdf_metadata_set_buffer(
+    data: *const u8,
+    len: usize,
+    key: *const c_char,
+    value: *const c_char,
+    out_data: *mut *mut u8,
+    out_len: *mut usize,
+) -> PdfMetadataStatus {
+    guard(|| {
+        let data = unsafe { arg_bytes(data, len) }?;
+        let key = unsafe { arg_str(key) }?;
+        let value = unsafe { arg_str(value) }?;
+        let out_data = unsafe { out_ptr(out_data) }?;
+        let out_len = unsafe { out_ptr(out_len) }?;
+        let updated = crate::set_pdf_metadata(data, key, value).map_err(Failure::from)?;
+        *out_len = updated.len();
+        *out_data = Box::into_raw(updated.into_boxed_slice()).cast::<u8>();
+        Ok(())
+    })
+}
+
+/// Frees a string returned by this library. Null is ignored.
+///
+/// # Safety
+///
+/// `value` must be null or a string returned through an out-parameter of this
+/// library that was not freed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pdf_metadata_string_free(value: *mut c_char) {
+    if !value.is_null() {
+        drop(unsafe { CString::from_raw(value) });
+    }
+}
+
+/// Frees a buffer returned by [`pdf_metadata_set_buffer`]. Null is ignored.
+///
+/// # Safety
+///
+/// `data` must be null or a buffer returned by this library, with the length
+/// returned alongside it, that was not freed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pdf_metadata_buffer_free(data: *mut u8, len: usize) {
+    if !data.is_null() {
+        drop(unsafe { Box::from_raw(ptr::slice_from_raw_parts_mut(data, len)) });
+    }
+}
+
+/// An error on its way to the C caller.
+struct Failure {
+    status: PdfMetadataStatus,
+    message: String,
+}
+
+impl Failure {
+    fn new(status: PdfMetadataStatus, message: impl Into<String>) -> Self {
+        Failure { status, message: message.into() }
+    }
+}
+
+impl From<Error> for Failure {
+    fn from(err: Error) -> Self {
+        let status = match err {
+            Error::Io(_) | Error::Write { .. } => PdfMetadataStatus::Io,
+            Error::Parse(_) => PdfMetadataStatus::Parse,
+            Error::Encrypted => PdfMetadataStatus::Encrypted,
+            Error::MissingInfoDict => PdfMetadataStatus::MissingInfoDict,
+            _ => PdfMetadataStatus::Other,
+        };
+        Failure::new(status, err.to_string())
+    }
+}
+
+/// Runs `call`, recording its error (or panic) for `pdf_metadata_last_error`.
+fn guard(call: impl FnOnce() -> Result<(), Failure>) -> PdfMetadataStatus {
+    let outcome = panic::catch_unwind(AssertUnwindSafe(call))
+        .unwrap_or_else(|_| Err(Failure::new(PdfMetadataStatus::Panic, "pdf_metadata panicked")));
+    LAST_ERROR.with(|last| {
+        *last.borrow_mut() = outcome.as_ref().err().map(|failure| {
+            CString::new(failure.message.replace('\0', " ")).unwrap_or_default()
+        });
+    });
+    match outcome {
+        Ok(()) => PdfMetadataStatus::Ok,
+        Err(failure) => failure.status,
+    }
+}
+
+/// Returns a new C string holding the value of `key`.
+fn lookup(entries: Vec<(String, String)>, key: &str) -> Result<*mut c_char, Failure> {
+    let (_, value) = entries
+        .into_iter()
+        .find(|(k, _)| k == key)
+        .ok_or_else(|| Failure::new(PdfMetadataStatus::NotFound, format!("Key not found: {}", key)))?;
+    to_c_string(value)
+}
+
+fn to_c_string(value: String) -> Result<*mut c_char, Failure> {
+    CString::new(value)
+        .map(CString::into_raw)
+        .map_err(|_| Failure::new(PdfMetadataStatus::Other, "value contains a NUL character"))
+}
+
+unsafe fn arg_str<'a>(value: *const c_char) -> Result<&'a str, Failure> {
+    if value.is_null() {
+        return Err(Failure::new(PdfMetadataStatus::InvalidArgument, "null string argument"));
+    }
+    unsafe { CStr::from_ptr(value) }
+        .to_str()
+        .map_err(|_| Failure::new(PdfMetadataStatus::InvalidArgument, "string argument is not valid UTF-8"))
+}
+
+unsafe fn arg_bytes<'a>(data: *const u8, len: usize) -> Result<&'a [u8], Failure> {
+    if data.is_null() {
+        return Err(Failure::new(PdfMetadataStatus::InvalidArgument, "null buffer argument"));
+    }
+    Ok(unsafe { slice::from_raw_parts(data, len) })
+}
+
+unsafe fn out_ptr<'a, T>(out: *mut T) -> Result<&'a mut T, Failure> {
+    unsafe { out.as_mut() }.ok_or_else(|| Failure::new(PdfMetadataStatus::InvalidArgument, "null output pointer"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{create_minimal_test_pdf, setup_unique_test_dir};
+    use std::fs;
+
+    #[test]
+    fn test_ffi_round_trip() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("ffi_round_trip");
+        let file = test_dir.join("doc.pdf");
+        create_minimal_test_pdf(&file)?;
+        let path = CString::new(file.to_str().unwrap())?;
+        let (title, value) = (CString::new("Title")?, CString::new("Relatório")?);
+
+        unsafe {
+            let mut out = ptr::null_mut();
+            assert_eq!(pdf_metadata_get(path.as_ptr(), title.as_ptr(), &mut out), PdfMetadataStatus::NotFound);
+            assert!(out.is_null());
+            assert!(!pdf_metadata_last_error().is_null());
+
+            assert_eq!(pdf_metadata_set(path.as_ptr(), title.as_ptr(), value.as_ptr()), PdfMetadataStatus::Ok);
+            assert!(pdf_metadata_last_error().is_null());
+            assert_eq!(pdf_metadata_get(path.as_ptr(), title.as_ptr(), &mut out), PdfMetadataStatus::Ok);
+            assert_eq!(CStr::from_ptr(out), value.as_c_str());
+            pdf_metadata_string_free(out);
+
+            let bytes = fs::read(&file)?;
+            let (mut data, mut len) = (ptr::null_mut(), 0);
+            let author = CString::new("Author")?;
+            let status = pdf_metadata_set_buffer(bytes.as_ptr(), bytes.len(), author.as_ptr(), value.as_ptr(), &mut data, &mut len);
+            assert_eq!(status, PdfMetadataStatus::Ok);
+            assert_eq!(pdf_metadata_get_buffer(data, len, author.as_ptr(), &mut out), PdfMetadataStatus::Ok);
+            assert_eq!(CStr::from_ptr(out), value.as_c_str());
+            pdf_metadata_string_free(out);
+            pdf_metadata_buffer_free(data, len);
+
+            assert_eq!(pdf_metadata_get_buffer(b"nope".as_ptr(), 4, author.as_ptr(), &mut out), PdfMetadataStatus::Parse);
+            assert_eq!(pdf_metadata_get(ptr::null(), title.as_ptr(), &mut out), PdfMetadataStatus::InvalidArgument);
+        }
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+}
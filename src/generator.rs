@@ -0,0 +1,301 @@
+//! Identifying the software that produced a PDF.
+//!
+//! The `Producer` entry names the program that wrote the PDF bytes, `Creator`
+//! the application the content was authored in, and the `x:xmptk` attribute of
+//! the XMP packet the toolkit that wrote the packet. The strings are free text;
+//! [`identify_generator`] recognizes the common spellings of well-known tools so
+//! that documents can be grouped by generator, for example when triaging a batch
+//! of malformed files.
+
+#[cfg(feature = "std-fs")]
+use crate::xmp::{self, PDF_NS, XMP_NS};
+#[cfg(feature = "std-fs")]
+use crate::{Result, load_document, read_info_entries};
+use std::fmt;
+#[cfg(feature = "std-fs")]
+use std::path::Path;
+
+/// A known PDF generator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Generator {
+    AdobeAcrobat,
+    AdobeDistiller,
+    /// The PDF library behind Acrobat and the Creative Cloud applications.
+    AdobePdfLibrary,
+    AdobeInDesign,
+    AdobeIllustrator,
+    AdobePhotoshop,
+    /// Adobe's XMP toolkit, recognized from the XMP packet only.
+    AdobeXmpCore,
+    MicrosoftWord,
+    MicrosoftExcel,
+    MicrosoftPowerPoint,
+    MicrosoftPrintToPdf,
+    LibreOffice,
+    OpenOffice,
+    Ghostscript,
+    PdfTex,
+    XeTex,
+    LuaTex,
+    /// macOS Quartz PDFContext, used by Preview and "Save as PDF".
+    Quartz,
+    /// Skia/PDF, used by Chrome and other Chromium-based browsers.
+    Skia,
+    WkHtmlToPdf,
+    Prince,
+    IText,
+    PdfSharp,
+    ReportLab,
+    Tcpdf,
+    Fpdf,
+    Mpdf,
+    Cairo,
+    PdfLib,
+    ApacheFop,
+    ExifTool,
+    /// None of the strings names a known generator.
+    Unknown,
+}
+
+/// Lowercase substrings that identify each generator, most specific first.
+const SIGNATURES: [(Generator, &[&str]); 31] = [
+    (Generator::MicrosoftPrintToPdf, &["microsoft: print to pdf"]),
+    (Generator::AdobeDistiller, &["acrobat distiller", "distiller"]),
+    (Generator::AdobePdfLibrary, &["adobe pdf library"]),
+    (Generator::AdobeInDesign, &["indesign"]),
+    (Generator::AdobeIllustrator, &["illustrator"]),
+    (Generator::AdobePhotoshop, &["photoshop"]),
+    (Generator::AdobeAcrobat, &["adobe acrobat", "acrobat pdfmaker", "acrobat web capture"]),
+    (Generator::AdobeXmpCore, &["adobe xmp core", "xmp toolkit"]),
+    (Generator::MicrosoftWord, &["microsoft® word", "microsoft word", "microsoft® office word", "microsoft office word"]),
+    (Generator::MicrosoftExcel, &["microsoft® excel", "microsoft excel", "microsoft® office excel", "microsoft office excel"]),
+    (
+        Generator::MicrosoftPowerPoint,
+        &["microsoft® powerpoint", "microsoft powerpoint", "microsoft® office powerpoint", "microsoft office powerpoint"],
+    ),
+    (Generator::LibreOffice, &["libreoffice"]),
+    (Generator::OpenOffice, &["openoffice"]),
+    (Generator::Ghostscript, &["ghostscript"]),
+    (Generator::PdfTex, &["pdftex"]),
+    (Generator::XeTex, &["xdvipdfmx", "xetex"]),
+    (Generator::LuaTex, &["luatex"]),
+    (Generator::Quartz, &["macos version", "mac os x", "quartz pdfcontext"]),
+    (Generator::Skia, &["skia/pdf"]),
+    (Generator::WkHtmlToPdf, &["wkhtmltopdf"]),
+    (Generator::Prince, &["prince ", "princexml"]),
+    (Generator::IText, &["itextsharp", "itext"]),
+    (Generator::PdfSharp, &["pdfsharp"]),
+    (Generator::ReportLab, &["reportlab"]),
+    (Generator::Tcpdf, &["tcpdf"]),
+    (Generator::Fpdf, &["fpdf"]),
+    (Generator::Mpdf, &["mpdf"]),
+    (Generator::Cairo, &["cairo"]),
+    (Generator::PdfLib, &["pdflib"]),
+    (Generator::ApacheFop, &["apache fop"]),
+    (Generator::ExifTool, &["exiftool"]),
+];
+
+impl Generator {
+    /// The product name, e.g. `Ghostscript` or `Microsoft Word`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Generator::AdobeAcrobat => "Adobe Acrobat",
+            Generator::AdobeDistiller => "Adobe Acrobat Distiller",
+            Generator::AdobePdfLibrary => "Adobe PDF Library",
+            Generator::AdobeInDesign => "Adobe InDesign",
+            Generator::AdobeIllustrator => "Adobe Illustrator",
+            Generator::AdobePhotoshop => "Adobe Photoshop",
+            Generator::AdobeXmpCore => "Adobe XMP Core",
+            Generator::MicrosoftWord => "Microsoft Word",
+            Generator::MicrosoftExcel => "Microsoft Excel",
+            Generator::MicrosoftPowerPoint => "Microsoft PowerPoint",
+            Generator::MicrosoftPrintToPdf => "Microsoft Print to PDF",
+            Generator::LibreOffice => "LibreOffice",
+            Generator::OpenOffice => "OpenOffice",
+            Generator::Ghostscript => "Ghostscript",
+            Generator::PdfTex => "pdfTeX",
+            Generator::XeTex => "XeTeX",
+            Generator::LuaTex => "LuaTeX",
+            Generator::Quartz => "macOS Quartz",
+            Generator::Skia => "Skia/PDF",
+            Generator::WkHtmlToPdf => "wkhtmltopdf",
+            Generator::Prince => "Prince",
+            Generator::IText => "iText",
+            Generator::PdfSharp => "PDFsharp",
+            Generator::ReportLab => "ReportLab",
+            Generator::Tcpdf => "TCPDF",
+            Generator::Fpdf => "FPDF",
+            Generator::Mpdf => "mPDF",
+            Generator::Cairo => "cairo",
+            Generator::PdfLib => "PDFlib",
+            Generator::ApacheFop => "Apache FOP",
+            Generator::ExifTool => "ExifTool",
+            Generator::Unknown => "unknown",
+        }
+    }
+}
+
+impl fmt::Display for Generator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Which string a generator was recognized in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GeneratorSource {
+    Producer,
+    Creator,
+    XmpToolkit,
+}
+
+/// The result of [`detect_generator`] and [`identify_generator`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GeneratorInfo {
+    /// The recognized generator, or [`Generator::Unknown`].
+    pub generator: Generator,
+    /// The version following the product name, e.g. `9.50` in `GPL Ghostscript 9.50`.
+    pub version: Option<String>,
+    /// Where the generator was recognized; `None` when it is unknown.
+    pub source: Option<GeneratorSource>,
+    /// The strings examined, as found in the document.
+    pub producer: Option<String>,
+    pub creator: Option<String>,
+    pub xmp_toolkit: Option<String>,
+}
+
+/// Identifies the generator from `Producer`, `Creator` and XMP toolkit strings.
+///
+/// `Producer` is tried first, as it names the program that wrote the file, then
+/// `Creator`, then the toolkit.
+///
+/// ```
+/// use pdf_metadata::{Generator, GeneratorSource, identify_generator};
+///
+/// let info = identify_generator(Some("GPL Ghostscript 9.50"), Some("Microsoft® Word 2016"), None);
+/// assert_eq!(info.generator, Generator::Ghostscript);
+/// assert_eq!(info.version.as_deref(), Some("9.50"));
+/// assert_eq!(info.source, Some(GeneratorSource::Producer));
+///
+/// let info = identify_generator(Some("a custom tool"), Some("LaTeX with hyperref"), Some("Adobe XMP Core 5.6-c015"));
+/// assert_eq!(info.generator, Generator::AdobeXmpCore);
+/// ```
+pub fn identify_generator(producer: Option<&str>, creator: Option<&str>, xmp_toolkit: Option<&str>) -> GeneratorInfo {
+    let candidates =
+        [(GeneratorSource::Producer, producer), (GeneratorSource::Creator, creator), (GeneratorSource::XmpToolkit, xmp_toolkit)];
+    let (generator, version, source) = candidates
+        .iter()
+        .find_map(|(source, text)| {
+            let (generator, version) = recognize(text.as_deref()?)?;
+            Some((generator, version, Some(*source)))
+        })
+        .unwrap_or((Generator::Unknown, None, None));
+    GeneratorInfo {
+        generator,
+        version,
+        source,
+        producer: producer.map(str::to_string),
+        creator: creator.map(str::to_string),
+        xmp_toolkit: xmp_toolkit.map(str::to_string),
+    }
+}
+
+/// Identifies the software that produced a PDF, from its Info dictionary and XMP
+/// packet; see [`identify_generator`]. When the Info dictionary lacks `Producer`
+/// or `Creator`, the XMP `pdf:Producer` and `xmp:CreatorTool` are used.
+///
+/// # Returns
+///
+/// * `Ok(GeneratorInfo)`: The generator, [`Generator::Unknown`] if not recognized.
+/// * `Err(Error)`: If the file cannot be read or parsed.
+///
+/// # Example
+///
+/// ```no_run
+/// use pdf_metadata::detect_generator;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let info = detect_generator("broken.pdf")?;
+///     println!("{} {}", info.generator, info.version.unwrap_or_default());
+///     Ok(())
+/// }
+/// ```
+#[cfg(feature = "std-fs")]
+pub fn detect_generator<P: AsRef<Path>>(file_path: P) -> Result<GeneratorInfo> {
+    let file_path = file_path.as_ref();
+    let doc = load_document(file_path)?;
+    let entries = read_info_entries(&doc);
+    let info = |key: &str| entries.iter().find(|(k, _)| k == key).map(|(_, value)| value.clone());
+    let packet_xml = xmp::read_packet_xml(&doc)?;
+    let packet = packet_xml.as_deref().and_then(|xml| xmp::XmpPacket::parse(xml).ok());
+    let from_xmp = |namespace: &str, name: &str| packet.as_ref()?.get(namespace, name).map(|value| value.to_text());
+
+    let producer = info("Producer").or_else(|| from_xmp(PDF_NS, "Producer"));
+    let creator = info("Creator").or_else(|| from_xmp(XMP_NS, "CreatorTool"));
+    let toolkit = packet_xml.as_deref().and_then(xmp::xmp_toolkit);
+    Ok(identify_generator(producer.as_deref(), creator.as_deref(), toolkit.as_deref()))
+}
+
+/// The generator named in `text`, with the version that follows its name.
+fn recognize(text: &str) -> Option<(Generator, Option<String>)> {
+    // ASCII lowercasing keeps byte offsets, so positions apply to `text` as well.
+    let lower = text.to_ascii_lowercase();
+    SIGNATURES.iter().find_map(|(generator, needles)| {
+        needles.iter().find_map(|needle| {
+            let end = lower.find(needle)? + needle.len();
+            Some((*generator, version_after(&text[end..])))
+        })
+    })
+}
+
+/// The version-like token at the start of `rest`, after separators: `9.50` in
+/// ` 9.50 (2019-10-15)`, `1.40.21` in `-1.40.21`, `m120` in ` m120`.
+fn version_after(rest: &str) -> Option<String> {
+    let rest = rest.trim_start_matches(|c: char| c.is_whitespace() || matches!(c, '-' | '/' | ':' | '®' | '™'));
+    let token: &str = rest.split(|c: char| c.is_whitespace() || matches!(c, '(' | ')' | ',' | ';')).next()?;
+    let token = token.strip_prefix(['v', 'V']).unwrap_or(token);
+    let starts_with_digit = |s: &str| s.starts_with(|c: char| c.is_ascii_digit());
+    (starts_with_digit(token) || token.strip_prefix('m').is_some_and(starts_with_digit)).then(|| token.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recognizes_common_producers() {
+        let cases = [
+            ("GPL Ghostscript 9.50", Generator::Ghostscript, Some("9.50")),
+            ("Acrobat Distiller 10.1.8 (Windows)", Generator::AdobeDistiller, Some("10.1.8")),
+            ("Adobe PDF Library 15.0", Generator::AdobePdfLibrary, Some("15.0")),
+            ("Microsoft® Word for Microsoft 365", Generator::MicrosoftWord, None),
+            ("Microsoft® Excel® 2016", Generator::MicrosoftExcel, Some("2016")),
+            ("Microsoft: Print To PDF", Generator::MicrosoftPrintToPdf, None),
+            ("LibreOffice 7.3", Generator::LibreOffice, Some("7.3")),
+            ("pdfTeX-1.40.21", Generator::PdfTex, Some("1.40.21")),
+            ("xdvipdfmx (20200315)", Generator::XeTex, None),
+            ("macOS Version 10.15.7 (Build 19H2) Quartz PDFContext", Generator::Quartz, Some("10.15.7")),
+            ("Skia/PDF m120", Generator::Skia, Some("m120")),
+            ("iText® 5.5.13 ©2000-2020 iText Group NV", Generator::IText, Some("5.5.13")),
+            ("Prince 14.2 (www.princexml.com)", Generator::Prince, Some("14.2")),
+            ("cairo 1.16.0 (https://cairographics.org)", Generator::Cairo, Some("1.16.0")),
+            ("Image::ExifTool 12.40", Generator::ExifTool, Some("12.40")),
+            ("Adobe XMP Core 5.6-c015 84.159810, 2016/09/10-02:41:30", Generator::AdobeXmpCore, Some("5.6-c015")),
+        ];
+        for (text, generator, version) in cases {
+            assert_eq!(recognize(text), Some((generator, version.map(str::to_string))), "{}", text);
+        }
+        assert_eq!(recognize("Princeton scanner"), None);
+
+        let info = identify_generator(Some("in-house tool"), Some("Adobe InDesign 16.0 (Windows)"), None);
+        assert_eq!((info.generator, info.source), (Generator::AdobeInDesign, Some(GeneratorSource::Creator)));
+        assert_eq!(identify_generator(None, None, None).generator, Generator::Unknown);
+
+        let packet = "<?xpacket begin=\"\"?><x:xmpmeta xmlns:x=\"adobe:ns:meta/\" x:xmptk=\"Adobe XMP Core 5.6\"><rdf:RDF/></x:xmpmeta>";
+        assert_eq!(xmp::xmp_toolkit(packet).as_deref(), Some("Adobe XMP Core 5.6"));
+    }
+}
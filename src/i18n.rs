@@ -0,0 +1,755 @@
+//! Catálogos de mensagens da ferramenta de linha de comando.
+//!
+//! O idioma vem de `--lang`, da variável `PDF_METADATA_LANG` ou da localidade do
+//! sistema (`LC_ALL`, `LC_MESSAGES`, `LANG`); sem nenhuma delas, o inglês. As
+//! mensagens da biblioteca (erros, avisos de validação) são sempre em inglês.
+//!
+//! Cada mensagem é um modelo cujos `{}` são preenchidos em ordem por [`tr!`]. Os
+//! textos de `--help` também vêm dos catálogos e são aplicados ao comando do clap
+//! por [`localize`].
+
+use clap::{Arg, Command};
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+/// Idiomas das mensagens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lang {
+    En,
+    Pt,
+}
+
+impl std::str::FromStr for Lang {
+    type Err = String;
+
+    /// Aceita `en` ou `pt`, também com região (`pt_BR`, `en-US`).
+    fn from_str(s: &str) -> Result<Self, String> {
+        let s = s.to_ascii_lowercase();
+        if s.starts_with("pt") {
+            Ok(Lang::Pt)
+        } else if s.starts_with("en") {
+            Ok(Lang::En)
+        } else {
+            Err(format!("unsupported language '{}' (en or pt)", s))
+        }
+    }
+}
+
+impl Lang {
+    /// O idioma de `PDF_METADATA_LANG` ou, sem ela, o da localidade do sistema.
+    pub fn from_env() -> Lang {
+        if let Some(lang) = std::env::var("PDF_METADATA_LANG").ok().and_then(|lang| lang.parse().ok()) {
+            return lang;
+        }
+        // Como no POSIX, vale a primeira variável de localidade definida.
+        let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .into_iter()
+            .filter_map(|name| std::env::var(name).ok())
+            .find(|value| !value.is_empty());
+        match locale {
+            Some(locale) if locale.to_ascii_lowercase().starts_with("pt") => Lang::Pt,
+            _ => Lang::En,
+        }
+    }
+
+    /// O idioma passado com `--lang` em `args`, procurado sem analisá-los para que o
+    /// `--help` do clap já saia traduzido.
+    pub fn from_args(args: impl IntoIterator<Item = std::ffi::OsString>) -> Option<Lang> {
+        let mut args = args.into_iter().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.to_str() {
+                Some("--") => break,
+                Some("--lang") => return args.next()?.to_str()?.parse().ok(),
+                Some(arg) if arg.starts_with("--lang=") => return arg["--lang=".len()..].parse().ok(),
+                _ => {}
+            }
+        }
+        None
+    }
+
+    /// O catálogo deste idioma.
+    pub fn messages(self) -> &'static Messages {
+        match self {
+            Lang::En => &EN,
+            Lang::Pt => &PT,
+        }
+    }
+}
+
+static LANG: OnceLock<Lang> = OnceLock::new();
+
+/// Escolhe o idioma das mensagens; só a primeira chamada tem efeito.
+pub fn set_lang(lang: Lang) {
+    let _ = LANG.set(lang);
+}
+
+/// O catálogo do idioma escolhido.
+pub fn messages() -> &'static Messages {
+    LANG.get_or_init(Lang::from_env).messages()
+}
+
+/// Aplica a `command` a descrição, a ajuda dos subcomandos e a dos argumentos do
+/// catálogo de `lang`.
+pub fn localize(command: Command, lang: Lang) -> Command {
+    let catalog = lang.messages();
+    let name = command.get_name().to_owned();
+    command
+        .about(catalog.about)
+        .mut_args(|arg| localize_arg(catalog, arg, &name))
+        .mut_subcommands(|subcommand| {
+            let name = subcommand.get_name().to_owned();
+            subcommand.about(catalog.help(&name)).mut_args(|arg| localize_arg(catalog, arg, &name))
+        })
+}
+
+fn localize_arg(catalog: &'static Messages, arg: Arg, command: &str) -> Arg {
+    let id = arg.get_id().as_str();
+    let text = catalog.help(&format!("{}.{}", command, id)).or_else(|| catalog.help(&format!("*.{}", id)));
+    arg.help(text)
+}
+
+/// Substitui os `{}` de `template` por `args`, em ordem.
+pub fn fill(template: &str, args: &[&dyn Display]) -> String {
+    let mut filled = String::with_capacity(template.len());
+    let mut args = args.iter();
+    let mut rest = template;
+    while let Some(position) = rest.find("{}") {
+        filled.push_str(&rest[..position]);
+        if let Some(arg) = args.next() {
+            filled.push_str(&arg.to_string());
+        }
+        rest = &rest[position + 2..];
+    }
+    filled.push_str(rest);
+    filled
+}
+
+/// A mensagem `$field` do catálogo ativo, com os `{}` preenchidos pelos argumentos.
+macro_rules! tr {
+    ($field:ident) => {
+        $crate::i18n::messages().$field
+    };
+    ($field:ident, $($arg:expr),+ $(,)?) => {
+        $crate::i18n::fill($crate::i18n::messages().$field, &[$(&$arg),+])
+    };
+}
+
+/// As mensagens de um idioma.
+pub struct Messages {
+    pub error: &'static str,
+    pub file_not_found: &'static str,
+    pub key_not_found: &'static str,
+    pub warning: &'static str,
+    pub dry_run_needs_file: &'static str,
+    pub signatures_invalidated: &'static str,
+    pub warning_count: &'static str,
+    pub no_problems: &'static str,
+    pub problems_repaired: &'static str,
+    pub key_removed: &'static str,
+    pub csv_exported: &'static str,
+    pub no_document_id: &'static str,
+    pub not_pdfa: &'static str,
+    pub pdfa_not_compliant: &'static str,
+    pub checksum_valid: &'static str,
+    pub checksum_missing: &'static str,
+    pub checksum_mismatch: &'static str,
+    pub no_thumbnail: &'static str,
+    pub revision: &'static str,
+    pub no_signatures: &'static str,
+    pub signer: &'static str,
+    pub signed_at: &'static str,
+    pub reason: &'static str,
+    pub location: &'static str,
+    pub signature_format: &'static str,
+    pub signature_partial: &'static str,
+    pub permission_print: &'static str,
+    pub permission_print_high_quality: &'static str,
+    pub permission_modify: &'static str,
+    pub permission_copy: &'static str,
+    pub permission_annotate: &'static str,
+    pub permission_fill_forms: &'static str,
+    pub permission_accessibility: &'static str,
+    pub permission_assemble: &'static str,
+    pub yes: &'static str,
+    pub no: &'static str,
+    #[cfg(feature = "jobs")]
+    pub job_ok: &'static str,
+    #[cfg(feature = "jobs")]
+    pub job_failed: &'static str,
+    #[cfg(feature = "jobs")]
+    pub jobs_failed: &'static str,
+    #[cfg(feature = "text-stats")]
+    pub stats_pages: &'static str,
+    #[cfg(feature = "text-stats")]
+    pub stats_words: &'static str,
+    #[cfg(feature = "text-stats")]
+    pub stats_characters: &'static str,
+    #[cfg(feature = "text-stats")]
+    pub stats_characters_with_spaces: &'static str,
+    pub editor_title: &'static str,
+    pub editor_file: &'static str,
+    pub editor_files: &'static str,
+    pub retry: &'static str,
+    pub non_interactive: &'static str,
+    pub goodbye: &'static str,
+    pub menu_list: &'static str,
+    pub menu_create: &'static str,
+    pub menu_edit: &'static str,
+    pub menu_rename: &'static str,
+    pub menu_delete: &'static str,
+    pub menu_undo: &'static str,
+    pub menu_switch: &'static str,
+    pub menu_apply: &'static str,
+    pub menu_quit: &'static str,
+    pub menu_prompt: &'static str,
+    pub switch_prompt: &'static str,
+    pub list_heading: &'static str,
+    pub no_metadata: &'static str,
+    pub total: &'static str,
+    pub create_heading: &'static str,
+    pub key_prompt: &'static str,
+    pub empty_key: &'static str,
+    pub key_exists_use_edit: &'static str,
+    pub value_prompt: &'static str,
+    pub created: &'static str,
+    pub edit_heading: &'static str,
+    pub nothing_to_edit: &'static str,
+    pub select_to_edit: &'static str,
+    pub key_label: &'static str,
+    pub current_value: &'static str,
+    pub new_value_prompt: &'static str,
+    pub value_updated: &'static str,
+    pub rename_heading: &'static str,
+    pub nothing_to_rename: &'static str,
+    pub select_to_rename: &'static str,
+    pub current_key: &'static str,
+    pub new_key_prompt: &'static str,
+    pub same_key: &'static str,
+    pub key_exists: &'static str,
+    pub key_renamed: &'static str,
+    pub delete_heading: &'static str,
+    pub nothing_to_delete: &'static str,
+    pub select_to_delete: &'static str,
+    pub value_label: &'static str,
+    pub confirm_delete: &'static str,
+    pub deleted: &'static str,
+    pub cancelled: &'static str,
+    pub undone: &'static str,
+    pub apply_heading: &'static str,
+    pub select_files: &'static str,
+    pub no_files_selected: &'static str,
+    pub applied: &'static str,
+    pub press_enter: &'static str,
+    /// A descrição do programa exibida por `--help`.
+    pub about: &'static str,
+    /// O texto de `--help` de cada subcomando, pelo nome, e de cada argumento, por
+    /// `subcomando.argumento` (`pdf_metadata.argumento` para os globais). Um
+    /// argumento sem entrada própria usa `*.argumento`.
+    pub help: &'static [(&'static str, &'static str)],
+}
+
+impl Messages {
+    /// O texto de `--help` guardado em `key`.
+    fn help(&self, key: &str) -> Option<&'static str> {
+        self.help.iter().find(|(k, _)| *k == key).map(|(_, text)| *text)
+    }
+}
+
+pub static PT: Messages = Messages {
+    error: "Erro: {}",
+    file_not_found: "Arquivo não encontrado: {}",
+    key_not_found: "Metadado '{}' não encontrado",
+    warning: "aviso: {}",
+    dry_run_needs_file: "--dry-run precisa de um arquivo, não da entrada padrão",
+    signatures_invalidated: "{} assinatura(s) digital(is) serão invalidadas; use --signed incremental para preservá-las",
+    warning_count: "{} aviso(s)",
+    no_problems: "Nenhum problema encontrado.",
+    problems_repaired: "{} problema(s) corrigido(s).",
+    key_removed: "Removida: {}",
+    csv_exported: "{} arquivo(s) exportado(s) para {}",
+    no_document_id: "O arquivo não tem /ID",
+    not_pdfa: "Não declara PDF/A",
+    pdfa_not_compliant: "os metadados não atendem ao PDF/A",
+    checksum_valid: "Checksum válido.",
+    checksum_missing: "o arquivo não tem checksum",
+    checksum_mismatch: "o conteúdo foi alterado depois do checksum",
+    no_thumbnail: "a página {} não tem miniatura",
+    revision: "Revisão {} ({} bytes{})",
+    no_signatures: "O arquivo não tem assinaturas",
+    signer: "Signatário",
+    signed_at: "Data",
+    reason: "Motivo",
+    location: "Local",
+    signature_format: "Formato",
+    signature_partial: "aviso: a assinatura não cobre o arquivo inteiro; ele foi alterado depois de assinado",
+    permission_print: "Imprimir",
+    permission_print_high_quality: "Imprimir em alta qualidade",
+    permission_modify: "Alterar",
+    permission_copy: "Copiar",
+    permission_annotate: "Anotar",
+    permission_fill_forms: "Preencher formulários",
+    permission_accessibility: "Extrair para acessibilidade",
+    permission_assemble: "Montar páginas",
+    yes: "sim",
+    no: "não",
+    #[cfg(feature = "jobs")]
+    job_ok: "ok    {} -> {}",
+    #[cfg(feature = "jobs")]
+    job_failed: "erro  {}: {}",
+    #[cfg(feature = "jobs")]
+    jobs_failed: "{} de {} arquivos falharam",
+    #[cfg(feature = "text-stats")]
+    stats_pages: "Páginas: {}",
+    #[cfg(feature = "text-stats")]
+    stats_words: "Palavras: {}",
+    #[cfg(feature = "text-stats")]
+    stats_characters: "Caracteres: {}",
+    #[cfg(feature = "text-stats")]
+    stats_characters_with_spaces: "Caracteres com espaços: {}",
+    editor_title: "📄 Editor de Metadados PDF",
+    editor_file: "Arquivo: {}",
+    editor_files: "{} arquivos abertos",
+    retry: "Deseja tentar novamente?",
+    non_interactive: "Executando em modo não-interativo. Saindo...",
+    goodbye: "👋 Obrigado por usar o Editor de Metadados PDF!",
+    menu_list: "📋 Listar todos os metadados",
+    menu_create: "➕ Criar novo metadado",
+    menu_edit: "✏️  Editar valor de metadado",
+    menu_rename: "🔄 Alterar chave de metadado",
+    menu_delete: "🗑️  Excluir metadado",
+    menu_undo: "↩️  Desfazer última alteração",
+    menu_switch: "📂 Trocar de arquivo",
+    menu_apply: "📑 Aplicar um metadado a vários arquivos",
+    menu_quit: "🚪 Sair",
+    menu_prompt: "\nSelecione uma opção:",
+    switch_prompt: "Selecione o arquivo",
+    list_heading: "📋 Metadados do PDF:",
+    no_metadata: "ℹ️  Nenhum metadado encontrado.",
+    total: "📊 Total: {} metadados",
+    create_heading: "➕ Criar Novo Metadado",
+    key_prompt: "Chave do metadado",
+    empty_key: "⚠️  A chave não pode estar vazia.",
+    key_exists_use_edit: "⚠️  A chave '{}' já existe. Use a opção de editar.",
+    value_prompt: "Valor do metadado",
+    created: "✅ Metadado '{}' criado com sucesso!",
+    edit_heading: "✏️  Editar Valor de Metadado",
+    nothing_to_edit: "ℹ️  Nenhum metadado encontrado para editar.",
+    select_to_edit: "Selecione o metadado para editar",
+    key_label: "Chave: {}",
+    current_value: "Valor atual: {}",
+    new_value_prompt: "Novo valor",
+    value_updated: "✅ Valor do metadado '{}' atualizado com sucesso!",
+    rename_heading: "🔄 Alterar Chave de Metadado",
+    nothing_to_rename: "ℹ️  Nenhum metadado encontrado para alterar.",
+    select_to_rename: "Selecione o metadado para alterar a chave",
+    current_key: "Chave atual: {}",
+    new_key_prompt: "Nova chave",
+    same_key: "⚠️  A nova chave deve ser diferente da atual.",
+    key_exists: "⚠️  A chave '{}' já existe.",
+    key_renamed: "✅ Chave alterada de '{}' para '{}' com sucesso!",
+    delete_heading: "🗑️  Excluir Metadado",
+    nothing_to_delete: "ℹ️  Nenhum metadado encontrado para excluir.",
+    select_to_delete: "Selecione o metadado para excluir",
+    value_label: "Valor: {}",
+    confirm_delete: "Tem certeza que deseja excluir este metadado?",
+    deleted: "✅ Metadado '{}' excluído com sucesso!",
+    cancelled: "❌ Operação cancelada.",
+    undone: "↩️  Alteração desfeita: {}",
+    apply_heading: "📑 Aplicar a Vários Arquivos",
+    select_files: "Arquivos (Espaço marca ou desmarca, Enter confirma)",
+    no_files_selected: "ℹ️  Nenhum arquivo selecionado.",
+    applied: "📊 '{}' gravado em {} de {} arquivo(s).",
+    press_enter: "⏎ Pressione Enter para continuar...",
+    about: "Editor de metadados PDF.\n\nSem subcomando, abre o menu interativo para os arquivos informados.",
+    help: &[
+        ("pdf_metadata.pdf_paths", "Arquivos PDF (ou padrões como \"docs/*.pdf\") a serem editados no modo interativo"),
+        ("pdf_metadata.quiet", "Não exibe mensagens de erro; só o código de saída indica a falha"),
+        ("pdf_metadata.errors_json", "Exibe os erros como objetos JSON na saída de erro ({\"error\", \"code\", \"message\"})"),
+        ("pdf_metadata.lang", "Idioma das mensagens (en ou pt); o padrão vem de PDF_METADATA_LANG ou da localidade"),
+        ("*.dir", "Pasta a ser percorrida, incluindo subpastas"),
+        ("*.file", "Arquivo PDF"),
+        ("*.format", "Formato de saída (plain, json, csv ou yaml)"),
+        ("*.key", "Chave do metadado"),
+        ("*.no_annotate", "Proíbe anotações e o preenchimento de formulários"),
+        ("*.no_copy", "Proíbe copiar texto e imagens"),
+        ("*.no_modify", "Proíbe alterar o documento"),
+        ("*.no_print", "Proíbe a impressão"),
+        ("*.output", "Arquivo de saída"),
+        ("*.page", "Número da página (a partir de 1)"),
+        ("*.sorted", "Lista as chaves padrão primeiro e as demais em ordem alfabética"),
+        ("*.user_password", "Senha para abrir o documento (padrão: nenhuma)"),
+        ("*.value", "Novo valor"),
+        ("get", "Exibe o valor de um metadado (ou todos, se nenhuma chave for informada)"),
+        ("get.file", "Arquivo PDF (\"-\" lê da entrada padrão)"),
+        ("get.ignore_case", "Ignora maiúsculas e minúsculas na chave"),
+        ("set", "Cria ou atualiza um metadado"),
+        ("set.file", "Arquivo PDF (\"-\" lê da entrada padrão e grava na saída padrão)"),
+        ("set.output", "Grava o resultado neste arquivo em vez de alterar o original (\"-\" para a saída padrão)"),
+        ("set.keep_mod_date", "Não altera o ModDate"),
+        ("set.preserve_mtime", "Mantém a data de modificação do arquivo"),
+        ("set.backup", "Copia o original para arquivo.pdf.bak-<data e hora> antes de alterá-lo"),
+        ("set.keep_backups", "Com --backup, quantas cópias manter (0 mantém todas)"),
+        ("set.dry_run", "Mostra o que mudaria, sem gravar o arquivo"),
+        ("set.strict", "Recusa valores que não passam na validação"),
+        ("set.if_exists", "O que fazer se a chave já existir (fail, skip, overwrite ou append)"),
+        ("set.separator", "Separador usado com --if-exists append"),
+        ("set.id", "O que fazer com os identificadores do arquivo, /ID (keep, update ou regenerate)"),
+        ("set.signed", "O que fazer se o PDF estiver assinado (error, incremental ou rewrite)"),
+        ("set.preserve", "Anexa a alteração como atualização incremental, sem regravar o restante do arquivo"),
+        ("set.xref", "Formato das referências cruzadas ao regravar (input, table, stream ou object-streams)"),
+        ("set.history", "Registra a alteração no histórico XMP (xmpMM:History)"),
+        ("set.prefix", "Prefixo das chaves personalizadas (ex.: ACME grava ACME:ProjectId)"),
+        ("set.ignore_case", "Ignora maiúsculas e minúsculas na chave (author grava Author)"),
+        ("set.merge_duplicates", "Com --ignore-case, remove as chaves que diferem só em maiúsculas e minúsculas"),
+        ("validate", "Verifica os metadados gravados no arquivo"),
+        ("audit", "Procura chaves duplicadas e inconsistências do dicionário Info"),
+        ("audit.repair", "Corrige os problemas encontrados"),
+        ("delete", "Exclui um metadado"),
+        ("delete.ignore_case", "Exclui também as chaves que diferem só em maiúsculas e minúsculas"),
+        ("list", "Lista todos os metadados"),
+        ("list.json", "Atalho para --format json"),
+        ("list.naming", "Nomes das chaves (pdf, acrobat, exiftool ou exiftool-tags)"),
+        ("list.lenient", "Recupera os metadados de arquivos com tabela xref danificada"),
+        ("scrub", "Remove caminhos locais e nomes de usuário de Creator/Producer"),
+        ("flatten", "Grava o PDF como uma única revisão, sem valores antigos de Info e XMP recuperáveis"),
+        ("flatten.output", "Arquivo de saída (pode ser o próprio arquivo)"),
+        ("report", "Gera um relatório de metadados de um arquivo ou pasta"),
+        ("report.path", "Arquivo PDF ou pasta"),
+        ("report.format", "Formato do relatório (markdown ou html)"),
+        ("report.recursive", "Inclui subpastas"),
+        ("find", "Procura PDFs cujos metadados satisfazem uma consulta (ex.: \"Producer ~= 'Ghostscript'\")"),
+        ("find.query", "Consulta (==, !=, ~=, ^=, $=, =~, has(Chave), !, &&, ||)"),
+        ("csv", "Exporta os metadados de todos os PDFs de uma pasta para uma planilha CSV"),
+        ("csv.output", "Arquivo CSV de saída"),
+        ("csv.fields", "Colunas, separadas por vírgula (padrão: todas as chaves encontradas)"),
+        ("id", "Exibe os identificadores do arquivo (/ID)"),
+        ("id.regenerate", "Gera um novo par de identificadores, como para um documento novo"),
+        ("generator", "Identifica o programa que gerou o PDF (Producer, Creator e XMP)"),
+        ("pdfa", "Verifica a identificação PDF/A e a coerência entre Info e XMP"),
+        ("reconcile", "Iguala os valores do dicionário Info e do pacote XMP"),
+        ("reconcile.from", "Lado que prevalece em caso de conflito (info ou xmp)"),
+        ("lang", "Lista ou define o título (ou a descrição) XMP em um idioma"),
+        ("lang.lang", "Idioma, p. ex. pt-BR ou x-default; sem ele, lista os idiomas"),
+        ("lang.description", "Usa a descrição (dc:description) em vez do título"),
+        ("checksum", "Verifica (ou grava, com --stamp) o checksum do conteúdo do PDF"),
+        ("checksum.stamp", "Calcula e grava o checksum no dicionário Info"),
+        ("checksum.algorithm", "Algoritmo usado com --stamp (sha256 ou sha512)"),
+        ("namespace", "Lista, define ou remove as chaves personalizadas de um prefixo (ex.: ACME:)"),
+        ("namespace.prefix", "Prefixo, sem os dois-pontos"),
+        ("namespace.name", "Nome da chave, sem o prefixo"),
+        ("namespace.remove", "Remove a chave indicada"),
+        ("namespace.clear", "Remove todas as chaves do prefixo"),
+        ("xmp-ids", "Garante os identificadores XMP (xmpMM:DocumentID e InstanceID)"),
+        ("attachments", "Lista os arquivos anexados ao PDF"),
+        ("outline", "Mostra os marcadores (sumário) do PDF"),
+        ("page-info", "Mostra os dados de aplicações (PieceInfo) gravados em uma página"),
+        ("thumbnail", "Salva a miniatura (/Thumb) de uma página, em JPEG ou PNM"),
+        ("thumbnail.max_px", "Tamanho máximo, em pixels, do maior lado (0 mantém o original)"),
+        ("page-set", "Grava um dado de aplicação (PieceInfo) em uma página"),
+        ("page-set.application", "Nome da aplicação"),
+        ("page-set.key", "Chave do dado"),
+        ("viewer", "Mostra ou altera o idioma e as preferências de exibição do documento"),
+        ("viewer.lang", "Idioma do documento (ex.: pt-BR)"),
+        ("viewer.page_mode", "Painel exibido ao abrir (UseNone, UseOutlines, UseThumbs, FullScreen, UseOC, UseAttachments)"),
+        ("viewer.page_layout", "Disposição das páginas (SinglePage, OneColumn, TwoColumnLeft, TwoColumnRight, TwoPageLeft, TwoPageRight)"),
+        ("viewer.display_doc_title", "Mostra o título (Title) na barra da janela em vez do nome do arquivo"),
+        ("title", "Define o título no Info, no XMP (dc:title) e em DisplayDocTitle, como exige o PDF/UA"),
+        ("title.title", "Novo título"),
+        ("title.hide_in_window", "Não mostra o título na barra da janela"),
+        ("attach", "Anexa um arquivo ao PDF (substitui um anexo com o mesmo nome)"),
+        ("attach.attachment", "Arquivo a anexar"),
+        ("attach.description", "Descrição do anexo"),
+        ("attach.relationship", "Relação PDF/A-3 com o documento (ex.: Alternative, Data, Source)"),
+        ("extract", "Extrai um anexo do PDF"),
+        ("extract.name", "Nome do anexo"),
+        ("extract.dest", "Arquivo de destino"),
+        ("encrypt", "Protege o PDF com senhas e restrições de uso"),
+        ("encrypt.owner_password", "Senha que libera as restrições"),
+        ("encrypt.algorithm", "Algoritmo (aes256 ou rc4)"),
+        ("revisions", "Mostra os metadados de cada revisão salva por atualizações incrementais"),
+        ("signatures", "Lista as assinaturas digitais do PDF (sem verificá-las)"),
+        ("permissions", "Mostra as permissões de uso do PDF ou, com --owner-password, altera-as"),
+        ("permissions.owner_password", "Senha do proprietário, necessária para alterar as permissões"),
+        ("run", "Executa um arquivo de tarefas (TOML)"),
+        ("run.job", "Arquivo de tarefas"),
+        ("text-stats", "Conta páginas, palavras e caracteres do texto do PDF"),
+        ("text-stats.store", "Grava as contagens no dicionário Info"),
+    ],
+};
+
+pub static EN: Messages = Messages {
+    error: "Error: {}",
+    file_not_found: "File not found: {}",
+    key_not_found: "Metadata entry '{}' not found",
+    warning: "warning: {}",
+    dry_run_needs_file: "--dry-run needs a file, not standard input",
+    signatures_invalidated: "{} digital signature(s) will be invalidated; use --signed incremental to keep them",
+    warning_count: "{} warning(s)",
+    no_problems: "No problems found.",
+    problems_repaired: "{} problem(s) repaired.",
+    key_removed: "Removed: {}",
+    csv_exported: "{} file(s) exported to {}",
+    no_document_id: "The file has no /ID",
+    not_pdfa: "Does not declare PDF/A",
+    pdfa_not_compliant: "the metadata does not meet PDF/A",
+    checksum_valid: "Checksum valid.",
+    checksum_missing: "the file has no checksum",
+    checksum_mismatch: "the content changed after the checksum was stamped",
+    no_thumbnail: "page {} has no thumbnail",
+    revision: "Revision {} ({} bytes{})",
+    no_signatures: "The file has no signatures",
+    signer: "Signer",
+    signed_at: "Date",
+    reason: "Reason",
+    location: "Location",
+    signature_format: "Format",
+    signature_partial: "warning: the signature does not cover the whole file; it was changed after signing",
+    permission_print: "Print",
+    permission_print_high_quality: "Print in high quality",
+    permission_modify: "Modify",
+    permission_copy: "Copy",
+    permission_annotate: "Annotate",
+    permission_fill_forms: "Fill in forms",
+    permission_accessibility: "Extract for accessibility",
+    permission_assemble: "Assemble pages",
+    yes: "yes",
+    no: "no",
+    #[cfg(feature = "jobs")]
+    job_ok: "ok    {} -> {}",
+    #[cfg(feature = "jobs")]
+    job_failed: "error {}: {}",
+    #[cfg(feature = "jobs")]
+    jobs_failed: "{} of {} files failed",
+    #[cfg(feature = "text-stats")]
+    stats_pages: "Pages: {}",
+    #[cfg(feature = "text-stats")]
+    stats_words: "Words: {}",
+    #[cfg(feature = "text-stats")]
+    stats_characters: "Characters: {}",
+    #[cfg(feature = "text-stats")]
+    stats_characters_with_spaces: "Characters with spaces: {}",
+    editor_title: "📄 PDF Metadata Editor",
+    editor_file: "File: {}",
+    editor_files: "{} files open",
+    retry: "Try again?",
+    non_interactive: "Running non-interactively. Exiting...",
+    goodbye: "👋 Thanks for using the PDF Metadata Editor!",
+    menu_list: "📋 List all metadata",
+    menu_create: "➕ Create metadata entry",
+    menu_edit: "✏️  Edit metadata value",
+    menu_rename: "🔄 Rename metadata key",
+    menu_delete: "🗑️  Delete metadata entry",
+    menu_undo: "↩️  Undo last change",
+    menu_switch: "📂 Switch file",
+    menu_apply: "📑 Apply an entry to several files",
+    menu_quit: "🚪 Quit",
+    menu_prompt: "\nSelect an option:",
+    switch_prompt: "Select the file",
+    list_heading: "📋 PDF metadata:",
+    no_metadata: "ℹ️  No metadata found.",
+    total: "📊 Total: {} entries",
+    create_heading: "➕ Create Metadata Entry",
+    key_prompt: "Metadata key",
+    empty_key: "⚠️  The key cannot be empty.",
+    key_exists_use_edit: "⚠️  The key '{}' already exists. Use the edit option.",
+    value_prompt: "Metadata value",
+    created: "✅ Metadata entry '{}' created!",
+    edit_heading: "✏️  Edit Metadata Value",
+    nothing_to_edit: "ℹ️  No metadata found to edit.",
+    select_to_edit: "Select the entry to edit",
+    key_label: "Key: {}",
+    current_value: "Current value: {}",
+    new_value_prompt: "New value",
+    value_updated: "✅ Value of '{}' updated!",
+    rename_heading: "🔄 Rename Metadata Key",
+    nothing_to_rename: "ℹ️  No metadata found to rename.",
+    select_to_rename: "Select the entry to rename",
+    current_key: "Current key: {}",
+    new_key_prompt: "New key",
+    same_key: "⚠️  The new key must differ from the current one.",
+    key_exists: "⚠️  The key '{}' already exists.",
+    key_renamed: "✅ Key renamed from '{}' to '{}'!",
+    delete_heading: "🗑️  Delete Metadata Entry",
+    nothing_to_delete: "ℹ️  No metadata found to delete.",
+    select_to_delete: "Select the entry to delete",
+    value_label: "Value: {}",
+    confirm_delete: "Are you sure you want to delete this entry?",
+    deleted: "✅ Metadata entry '{}' deleted!",
+    cancelled: "❌ Operation cancelled.",
+    undone: "↩️  Change undone: {}",
+    apply_heading: "📑 Apply to Several Files",
+    select_files: "Files (Space toggles, Enter confirms)",
+    no_files_selected: "ℹ️  No files selected.",
+    applied: "📊 '{}' written to {} of {} file(s).",
+    press_enter: "⏎ Press Enter to continue...",
+    about: "PDF metadata editor.\n\nWithout a subcommand, opens the interactive menu for the given files.",
+    help: &[
+        ("pdf_metadata.pdf_paths", "PDF files (or patterns such as \"docs/*.pdf\") to edit in the interactive menu"),
+        ("pdf_metadata.quiet", "Prints no error messages; only the exit code reports the failure"),
+        ("pdf_metadata.errors_json", "Prints errors as JSON objects on standard error ({\"error\", \"code\", \"message\"})"),
+        ("pdf_metadata.lang", "Message language (en or pt); defaults to PDF_METADATA_LANG or the locale"),
+        ("*.dir", "Folder to walk, including subfolders"),
+        ("*.file", "PDF file"),
+        ("*.format", "Output format (plain, json, csv or yaml)"),
+        ("*.key", "Metadata key"),
+        ("*.no_annotate", "Forbids annotations and filling in forms"),
+        ("*.no_copy", "Forbids copying text and images"),
+        ("*.no_modify", "Forbids modifying the document"),
+        ("*.no_print", "Forbids printing"),
+        ("*.output", "Output file"),
+        ("*.page", "Page number (starting at 1)"),
+        ("*.sorted", "Lists the standard keys first and the others in alphabetical order"),
+        ("*.user_password", "Password to open the document (default: none)"),
+        ("*.value", "New value"),
+        ("get", "Shows the value of a metadata entry (or all of them, if no key is given)"),
+        ("get.file", "PDF file (\"-\" reads standard input)"),
+        ("get.ignore_case", "Ignores case in the key"),
+        ("set", "Creates or updates a metadata entry"),
+        ("set.file", "PDF file (\"-\" reads standard input and writes to standard output)"),
+        ("set.output", "Writes the result to this file instead of changing the original (\"-\" for standard output)"),
+        ("set.keep_mod_date", "Leaves ModDate unchanged"),
+        ("set.preserve_mtime", "Keeps the file's modification time"),
+        ("set.backup", "Copies the original to file.pdf.bak-<date and time> before changing it"),
+        ("set.keep_backups", "With --backup, how many copies to keep (0 keeps all)"),
+        ("set.dry_run", "Shows what would change, without writing the file"),
+        ("set.strict", "Rejects values that fail validation"),
+        ("set.if_exists", "What to do if the key already exists (fail, skip, overwrite or append)"),
+        ("set.separator", "Separator used with --if-exists append"),
+        ("set.id", "What to do with the file identifiers, /ID (keep, update or regenerate)"),
+        ("set.signed", "What to do if the PDF is signed (error, incremental or rewrite)"),
+        ("set.preserve", "Appends the change as an incremental update, without rewriting the rest of the file"),
+        ("set.xref", "Cross-reference format when rewriting (input, table, stream or object-streams)"),
+        ("set.history", "Records the change in the XMP history (xmpMM:History)"),
+        ("set.prefix", "Prefix for custom keys (e.g. ACME writes ACME:ProjectId)"),
+        ("set.ignore_case", "Ignores case in the key (author writes Author)"),
+        ("set.merge_duplicates", "With --ignore-case, removes the keys that differ only in case"),
+        ("validate", "Checks the metadata stored in the file"),
+        ("audit", "Looks for duplicate keys and inconsistencies in the Info dictionary"),
+        ("audit.repair", "Fixes the problems found"),
+        ("delete", "Deletes a metadata entry"),
+        ("delete.ignore_case", "Also deletes the keys that differ only in case"),
+        ("list", "Lists all metadata"),
+        ("list.json", "Shorthand for --format json"),
+        ("list.naming", "Key names (pdf, acrobat, exiftool or exiftool-tags)"),
+        ("list.lenient", "Recovers the metadata of files with a damaged xref table"),
+        ("scrub", "Removes local paths and user names from Creator/Producer"),
+        ("flatten", "Writes the PDF as a single revision, with no recoverable old Info and XMP values"),
+        ("flatten.output", "Output file (may be the input file itself)"),
+        ("report", "Generates a metadata report for a file or folder"),
+        ("report.path", "PDF file or folder"),
+        ("report.format", "Report format (markdown or html)"),
+        ("report.recursive", "Includes subfolders"),
+        ("find", "Finds PDFs whose metadata matches a query (e.g. \"Producer ~= 'Ghostscript'\")"),
+        ("find.query", "Query (==, !=, ~=, ^=, $=, =~, has(Key), !, &&, ||)"),
+        ("csv", "Exports the metadata of every PDF in a folder to a CSV spreadsheet"),
+        ("csv.output", "Output CSV file"),
+        ("csv.fields", "Columns, separated by commas (default: every key found)"),
+        ("id", "Shows the file identifiers (/ID)"),
+        ("id.regenerate", "Generates a new pair of identifiers, as for a new document"),
+        ("generator", "Identifies the program that produced the PDF (Producer, Creator and XMP)"),
+        ("pdfa", "Checks the PDF/A identification and the consistency between Info and XMP"),
+        ("reconcile", "Makes the values of the Info dictionary and the XMP packet agree"),
+        ("reconcile.from", "Side that wins on a conflict (info or xmp)"),
+        ("lang", "Lists or sets the XMP title (or description) in a language"),
+        ("lang.lang", "Language, e.g. pt-BR or x-default; without it, lists the languages"),
+        ("lang.description", "Uses the description (dc:description) instead of the title"),
+        ("checksum", "Verifies (or stamps, with --stamp) the checksum of the PDF content"),
+        ("checksum.stamp", "Computes the checksum and stores it in the Info dictionary"),
+        ("checksum.algorithm", "Algorithm used with --stamp (sha256 or sha512)"),
+        ("namespace", "Lists, sets or removes the custom keys of a prefix (e.g. ACME:)"),
+        ("namespace.prefix", "Prefix, without the colon"),
+        ("namespace.name", "Key name, without the prefix"),
+        ("namespace.remove", "Removes the given key"),
+        ("namespace.clear", "Removes every key of the prefix"),
+        ("xmp-ids", "Ensures the XMP identifiers (xmpMM:DocumentID and InstanceID)"),
+        ("attachments", "Lists the files attached to the PDF"),
+        ("outline", "Shows the bookmarks (outline) of the PDF"),
+        ("page-info", "Shows the application data (PieceInfo) stored on a page"),
+        ("thumbnail", "Saves the thumbnail (/Thumb) of a page, as JPEG or PNM"),
+        ("thumbnail.max_px", "Maximum size, in pixels, of the longer side (0 keeps the original)"),
+        ("page-set", "Stores an application data entry (PieceInfo) on a page"),
+        ("page-set.application", "Application name"),
+        ("page-set.key", "Data key"),
+        ("viewer", "Shows or changes the document language and display preferences"),
+        ("viewer.lang", "Document language (e.g. pt-BR)"),
+        ("viewer.page_mode", "Panel shown on opening (UseNone, UseOutlines, UseThumbs, FullScreen, UseOC, UseAttachments)"),
+        ("viewer.page_layout", "Page layout (SinglePage, OneColumn, TwoColumnLeft, TwoColumnRight, TwoPageLeft, TwoPageRight)"),
+        ("viewer.display_doc_title", "Shows the title (Title) in the window title bar instead of the file name"),
+        ("title", "Sets the title in Info, in XMP (dc:title) and DisplayDocTitle, as PDF/UA requires"),
+        ("title.title", "New title"),
+        ("title.hide_in_window", "Does not show the title in the window title bar"),
+        ("attach", "Attaches a file to the PDF (replacing an attachment with the same name)"),
+        ("attach.attachment", "File to attach"),
+        ("attach.description", "Attachment description"),
+        ("attach.relationship", "PDF/A-3 relationship to the document (e.g. Alternative, Data, Source)"),
+        ("extract", "Extracts an attachment from the PDF"),
+        ("extract.name", "Attachment name"),
+        ("extract.dest", "Destination file"),
+        ("encrypt", "Protects the PDF with passwords and usage restrictions"),
+        ("encrypt.owner_password", "Password that lifts the restrictions"),
+        ("encrypt.algorithm", "Algorithm (aes256 or rc4)"),
+        ("revisions", "Shows the metadata of each revision saved by incremental updates"),
+        ("signatures", "Lists the digital signatures of the PDF (without verifying them)"),
+        ("permissions", "Shows the usage permissions of the PDF or, with --owner-password, changes them"),
+        ("permissions.owner_password", "Owner password, needed to change the permissions"),
+        ("run", "Runs a job file (TOML)"),
+        ("run.job", "Job file"),
+        ("text-stats", "Counts the pages, words and characters of the PDF text"),
+        ("text-stats.store", "Stores the counts in the Info dictionary"),
+    ],
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fill_and_lang_parsing() {
+        assert_eq!(fill(PT.key_renamed, &[&"autor", &"Author"]), "✅ Chave alterada de 'autor' para 'Author' com sucesso!");
+        assert_eq!(fill(EN.csv_exported, &[&2, &"out.csv"]), "2 file(s) exported to out.csv");
+        assert_eq!("pt_BR.UTF-8".parse(), Ok(Lang::Pt));
+        assert_eq!("EN".parse(), Ok(Lang::En));
+        assert!("fr".parse::<Lang>().is_err());
+        let args = |line: &str| line.split(' ').map(std::ffi::OsString::from).collect::<Vec<_>>();
+        assert_eq!(Lang::from_args(args("pdf_metadata -h --lang pt")), Some(Lang::Pt));
+        assert_eq!(Lang::from_args(args("pdf_metadata get --lang=en-US a.pdf")), Some(Lang::En));
+        assert_eq!(Lang::from_args(args("pdf_metadata set a.pdf -- --lang pt")), None);
+    }
+
+    #[test]
+    fn test_every_argument_has_help_in_both_catalogs() {
+        use clap::CommandFactory;
+        use std::collections::BTreeSet;
+
+        for lang in [Lang::En, Lang::Pt] {
+            let command = localize(crate::Cli::command(), lang);
+            let root = command.get_name();
+            for subcommand in std::iter::once(&command).chain(command.get_subcommands()) {
+                let name = subcommand.get_name();
+                assert!(name == root || subcommand.get_about().is_some(), "{:?}: no help for {}", lang, name);
+                for arg in subcommand.get_arguments() {
+                    assert!(arg.get_help().is_some(), "{:?}: no help for {}.{}", lang, name, arg.get_id());
+                }
+            }
+        }
+
+        // Both catalogs have the same entries, and each names a subcommand or an argument.
+        let command = crate::Cli::command();
+        let mut known = BTreeSet::new();
+        for subcommand in std::iter::once(&command).chain(command.get_subcommands()) {
+            known.insert(subcommand.get_name().to_owned());
+            for arg in subcommand.get_arguments() {
+                known.insert(format!("{}.{}", subcommand.get_name(), arg.get_id()));
+                known.insert(format!("*.{}", arg.get_id()));
+            }
+        }
+        let keys = |catalog: &Messages| catalog.help.iter().map(|(key, _)| *key).collect::<BTreeSet<_>>();
+        assert_eq!(keys(&EN), keys(&PT));
+        // Subcommands left out of this build by their feature keep their entries.
+        let gated = [("run", cfg!(feature = "jobs")), ("text-stats", cfg!(feature = "text-stats"))];
+        let disabled = |key: &str| gated.iter().any(|&(name, enabled)| !enabled && key.split('.').next() == Some(name));
+        for key in keys(&EN) {
+            assert!(known.contains(key) || disabled(key), "help for unknown {}", key);
+        }
+    }
+}
@@ -0,0 +1,400 @@
+//! Incremental updates.
+//!
+//! A full rewrite renumbers and re-serializes every object, which invalidates the
+//! byte ranges covered by digital signatures. An incremental update instead appends
+//! a new revision (the changed objects, a cross-reference section and a
+//! trailer pointing back at the previous one) and leaves the existing bytes untouched.
+//!
+//! Writers change the loaded document directly. So that the revision can hold just
+//! the changed objects without comparing the whole document against a copy, writers
+//! reach existing objects through [`object_mut`], [`dictionary_mut`] and
+//! [`catalog_mut`], or report them with [`touch`]; objects they add are found by id.
+//! [`modify_document`] relies on the same record to tell whether a write changed
+//! anything.
+
+use crate::{Error, Result, SaveMode, clock, SetOptions, apply_entries_with, load_document_mem, modify_document, signatures};
+#[cfg(feature = "std-fs")]
+use crate::{check_overwrite, is_same_file, options, timeout, write_in_place_with};
+use crate::stats::{self, Counter};
+use lopdf::{Dictionary, Document, IncrementalDocument, Object, ObjectId};
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+#[cfg(feature = "std-fs")]
+use std::fs;
+#[cfg(feature = "std-fs")]
+use std::path::Path;
+
+thread_local! {
+    /// The existing objects changed so far by the revision being built on this thread.
+    static TOUCHED: RefCell<Option<BTreeSet<ObjectId>>> = const { RefCell::new(None) };
+}
+
+/// Records that the object `id` was changed, for the revision being built, if any.
+pub(crate) fn touch(id: ObjectId) {
+    TOUCHED.with(|touched| {
+        if let Some(touched) = touched.borrow_mut().as_mut() {
+            touched.insert(id);
+        }
+    });
+}
+
+/// Records with [`touch`] that the object `id` of `doc`, after following references
+/// as `Document::get_object_mut` does, was changed.
+pub(crate) fn touch_object(doc: &Document, id: ObjectId) -> lopdf::Result<()> {
+    let (target, _) = doc.dereference(doc.get_object(id)?)?;
+    touch(target.unwrap_or(id));
+    Ok(())
+}
+
+/// The object `id` for changing it, recorded with [`touch_object`].
+pub(crate) fn object_mut(doc: &mut Document, id: ObjectId) -> lopdf::Result<&mut Object> {
+    touch_object(doc, id)?;
+    doc.get_object_mut(id)
+}
+
+/// The dictionary `id` for changing it, recorded with [`touch`].
+pub(crate) fn dictionary_mut(doc: &mut Document, id: ObjectId) -> lopdf::Result<&mut Dictionary> {
+    object_mut(doc, id).and_then(Object::as_dict_mut)
+}
+
+/// The document catalog for changing it, recorded with [`touch`].
+pub(crate) fn catalog_mut(doc: &mut Document) -> lopdf::Result<&mut Dictionary> {
+    let id = doc.trailer.get(b"Root").and_then(Object::as_reference)?;
+    dictionary_mut(doc, id)
+}
+
+/// Runs `modify`, returning what it returned and the existing objects it changed.
+/// Those still count for an enclosing call.
+pub(crate) fn tracking_changes<T>(modify: impl FnOnce() -> Result<T>) -> Result<(T, BTreeSet<ObjectId>)> {
+    let outer = TOUCHED.with(|touched| touched.replace(Some(BTreeSet::new())));
+    let value = modify();
+    let touched = TOUCHED.with(|touched| {
+        let mut touched = touched.borrow_mut();
+        let inner = std::mem::replace(&mut *touched, outer).unwrap_or_default();
+        if let Some(outer) = touched.as_mut() {
+            outer.extend(inner.iter().copied());
+        }
+        inner
+    });
+    Ok((value?, touched))
+}
+
+/// Sets (adds or updates) a metadata entry by appending an incremental update to the file.
+///
+/// Every byte of the original file, including earlier revisions and signatures, is kept
+/// as-is; only a new Info dictionary and the cross-reference data needed to locate it are
+/// appended. `ModDate` is refreshed as with [`update_metadata_in_place`](crate::update_metadata_in_place).
+///
+/// # Arguments
+///
+/// * `file_path`: The path to the PDF file to update.
+/// * `metadata_key`: The key of the metadata entry to set.
+/// * `metadata_value`: The value for the metadata entry.
+///
+/// # Returns
+///
+/// * `Ok(())` if the revision was appended successfully.
+/// * `Err(Error::UnsupportedFormat)`: If the document is encrypted.
+/// * `Err(Error)`: If the file cannot be read, parsed, or written.
+///
+/// # Example
+///
+/// ```no_run
+/// use pdf_metadata::update_metadata_incremental;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     update_metadata_incremental("path/to/signed.pdf", "Subject", "Approved")?;
+///     Ok(())
+/// }
+/// ```
+#[cfg(feature = "std-fs")]
+pub fn update_metadata_incremental<P: AsRef<Path>>(file_path: P, metadata_key: &str, metadata_value: &str) -> Result<()> {
+    let file_path = file_path.as_ref();
+    update_entries_incremental(file_path, &[(metadata_key, metadata_value)], &options::current())
+}
+
+/// Appends a revision like [`update_metadata_incremental`], with `options` controlling
+/// `ModDate`, existing keys and the trailer `/ID` of the new revision.
+///
+/// # Example
+///
+/// ```no_run
+/// use pdf_metadata::{DocumentIdPolicy, SetOptions, update_metadata_incremental_with_options};
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     // Mark the revision as such for document management systems.
+///     let options = SetOptions { document_id: DocumentIdPolicy::UpdateCurrent, ..SetOptions::default() };
+///     update_metadata_incremental_with_options("path/to/signed.pdf", "Subject", "Approved", &options)?;
+///     Ok(())
+/// }
+/// ```
+#[cfg(feature = "std-fs")]
+pub fn update_metadata_incremental_with_options<P: AsRef<Path>>(
+    file_path: P,
+    metadata_key: &str,
+    metadata_value: &str,
+    options: &SetOptions,
+) -> Result<()> {
+    let file_path = file_path.as_ref();
+    update_entries_incremental(file_path, &[(metadata_key, metadata_value)], options)
+}
+
+/// Appends a single revision that sets all `entries`, replacing the file safely.
+#[cfg(feature = "std-fs")]
+pub(crate) fn update_entries_incremental<K: AsRef<str>, V: AsRef<str>>(
+    file_path: &Path,
+    entries: &[(K, V)],
+    options: &SetOptions,
+) -> Result<()> {
+    write_revision(file_path, None, options, |doc| apply_entries_with(doc, entries, options).map(drop))
+}
+
+/// Whether a write of `doc` with `options` appends a revision instead of rewriting the
+/// file: in [`SaveMode::Preserve`] unless `doc` is encrypted, and for signed documents
+/// that the [`SignedDocPolicy`](crate::SignedDocPolicy) protects.
+pub(crate) fn appends_revision(doc: &Document, options: &SetOptions) -> Result<bool> {
+    if options.save_mode == SaveMode::Preserve && doc.encryption_state.is_none() {
+        return Ok(true);
+    }
+    Ok(!signatures::rewrite_allowed(doc, options.on_signed)?)
+}
+
+/// Writes `source` followed by a revision made by `modify` to `output`, or back to
+/// `source` when `output` is `None`, honouring `overwrite`, the in-place settings and
+/// the timeout as [`save_output`](crate::save_output) and
+/// [`save_in_place_with`](crate::save_in_place_with) do.
+#[cfg(feature = "std-fs")]
+pub(crate) fn write_revision<T>(
+    source: &Path,
+    output: Option<&Path>,
+    options: &SetOptions,
+    modify: impl FnOnce(&mut Document) -> Result<T>,
+) -> Result<T> {
+    if let Some(output) = output {
+        check_overwrite(output, options.overwrite)?;
+    }
+    let (bytes, value) = append_revision_with(fs::read(source)?, None, options, modify)?;
+    let write = |path: &Path| fs::write(path, &bytes).map_err(|source| Error::Write { path: path.to_path_buf(), source });
+    match output {
+        None => write_in_place_with(source, options, write),
+        Some(output) if is_same_file(source, output) => write_in_place_with(output, options, write),
+        Some(output) => {
+            timeout::commit()?;
+            write(output)
+        }
+    }?;
+    Ok(value)
+}
+
+/// Returns `original_bytes` followed by a revision that sets `entries` in the Info dictionary.
+/// `prev_doc` is `original_bytes` as already parsed by the caller, if it was.
+pub(crate) fn append_revision<K: AsRef<str>, V: AsRef<str>>(
+    original_bytes: Vec<u8>,
+    prev_doc: Option<Document>,
+    entries: &[(K, V)],
+    options: &SetOptions,
+) -> Result<Vec<u8>> {
+    let (bytes, _) = append_revision_with(original_bytes, prev_doc, options, |doc| apply_entries_with(doc, entries, options))?;
+    Ok(bytes)
+}
+
+/// Returns `original_bytes` followed by a revision holding the objects and trailer
+/// `modify` changed, with the /ID policy of `options` applied to its trailer, along
+/// with what `modify` returned.
+fn append_revision_with<T>(
+    original_bytes: Vec<u8>,
+    prev_doc: Option<Document>,
+    options: &SetOptions,
+    modify: impl FnOnce(&mut Document) -> Result<T>,
+) -> Result<(Vec<u8>, T)> {
+    let mut doc = match prev_doc {
+        Some(doc) => doc,
+        None => load_document_mem(&original_bytes)?,
+    };
+
+    // lopdf drops the /Encrypt reference when it decrypts a document, so the appended
+    // trailer could not point at the original security handler.
+    if doc.encryption_state.is_some() {
+        return Err(Error::UnsupportedFormat(
+            "incremental updates of encrypted PDFs are not supported".to_string(),
+        ));
+    }
+
+    // lopdf only writes the new revision, so `modify` can work on the loaded document
+    // itself; the revision then takes the objects it changed or added.
+    let existing: BTreeSet<ObjectId> = doc.objects.keys().copied().collect();
+    let (value, touched) = tracking_changes(|| {
+        let value = modify_document(&mut doc, options, modify)?;
+        clock::clamp_to_source_date(&mut doc)?;
+        Ok(value)
+    })?;
+    if doc.encryption_state.is_some() {
+        return Err(Error::UnsupportedFormat("an incremental update cannot encrypt a PDF".to_string()));
+    }
+    let changed: Vec<ObjectId> =
+        doc.objects.keys().filter(|id| touched.contains(id) || !existing.contains(id)).copied().collect();
+    let changed: Vec<(ObjectId, Object)> =
+        changed.into_iter().filter_map(|id| doc.objects.remove(&id).map(|object| (id, object))).collect();
+
+    // The revision starts from the trailer as `modify` left it, pointing back at the
+    // original one through /Prev.
+    let version = doc.version.clone();
+    let mut incremental = IncrementalDocument::create_from(original_bytes, doc);
+    let revision = &mut incremental.new_document;
+    revision.version = version;
+    revision.objects.extend(changed);
+
+    // The output starts with every original byte; reserve them up front instead of
+    // growing the buffer through repeated copies.
+    let mut output = Vec::with_capacity(incremental.get_prev_documents_bytes().len() + 4096);
+    incremental.save_to(&mut output)?;
+    stats::record(Counter::DocumentWritten);
+    Ok((output, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{create_minimal_test_pdf, create_signed_test_pdf, setup_unique_test_dir};
+    use crate::{
+        BackupPolicy, MetadataEditor, SignedDocPolicy, backup, get_metadata, get_viewer_settings, set_document_title,
+        set_metadata_with_options, set_viewer_settings, update_metadata_in_place, update_metadata_in_place_with_options, with_timeout,
+        with_write_options, xmp, ViewerSettings,
+    };
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn test_incremental_update_preserves_previous_revision() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("incremental_update");
+        let file = test_dir.join("signed.pdf");
+        create_minimal_test_pdf(&file)?;
+        update_metadata_in_place(file.to_str().unwrap(), "Title", "Original Title")?;
+        let original_bytes = fs::read(&file)?;
+
+        update_metadata_incremental(file.to_str().unwrap(), "Subject", "Approved")?;
+        let first_update = fs::read(&file)?;
+        assert!(first_update.len() > original_bytes.len());
+        assert_eq!(&first_update[..original_bytes.len()], original_bytes.as_slice());
+
+        // A second revision chains onto the first one.
+        update_metadata_incremental(file.to_str().unwrap(), "Title", "Revised Title")?;
+        let second_update = fs::read(&file)?;
+        assert_eq!(&second_update[..first_update.len()], first_update.as_slice());
+
+        let metadata = get_metadata(file.to_str().unwrap())?;
+        let value = |key: &str| metadata.iter().find(|(k, _)| k == key).map(|(_, v)| v.clone());
+        assert_eq!(value("Title").as_deref(), Some("Revised Title"));
+        assert_eq!(value("Subject").as_deref(), Some("Approved"));
+        assert!(value("ModDate").is_some());
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_incremental_update_creates_missing_info_dict() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("incremental_no_info");
+        let file = test_dir.join("bare.pdf");
+        create_minimal_test_pdf(&file)?;
+        let original_bytes = fs::read(&file)?;
+
+        update_metadata_incremental(file.to_str().unwrap(), "Author", "Jane")?;
+
+        let updated = fs::read(&file)?;
+        assert_eq!(&updated[..original_bytes.len()], original_bytes.as_slice());
+        let metadata = get_metadata(file.to_str().unwrap())?;
+        assert!(metadata.iter().any(|(k, v)| k == "Author" && v == "Jane"));
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_preserve_save_mode_appends_instead_of_rewriting() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("preserve_save_mode");
+        let (file, output) = (test_dir.join("doc.pdf"), test_dir.join("out.pdf"));
+        create_minimal_test_pdf(&file)?;
+        let original = fs::read(&file)?;
+        let preserve = SetOptions { save_mode: "preserve".parse()?, ..SetOptions::default() };
+
+        set_metadata_with_options(file.to_str().unwrap(), output.to_str().unwrap(), "Title", "Relatório", &preserve)?;
+        let written = fs::read(&output)?;
+        assert_eq!(&written[..original.len()], original.as_slice());
+        assert!(written.len() - original.len() < 1024, "only the change is appended");
+        assert!(get_metadata(output.to_str().unwrap())?.contains(&("Title".to_string(), "Relatório".to_string())));
+
+        MetadataEditor::open(&output)?.remove("Title").options(preserve.clone()).save_in_place()?;
+        let edited = fs::read(&output)?;
+        assert_eq!(&edited[..written.len()], written.as_slice());
+        assert!(!get_metadata(output.to_str().unwrap())?.iter().any(|(key, _)| key == "Title"));
+
+        // Nothing is rewritten, so signed documents can be updated even under SignedDocPolicy::Error.
+        let signed = test_dir.join("signed.pdf");
+        create_signed_test_pdf(&signed)?;
+        let signed_bytes = fs::read(&signed)?;
+        let strict = SetOptions { on_signed: SignedDocPolicy::Error, ..preserve };
+        update_metadata_in_place_with_options(signed.to_str().unwrap(), "Subject", "Arquivado", &strict)?;
+        assert_eq!(&fs::read(&signed)?[..signed_bytes.len()], signed_bytes.as_slice());
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_preserve_writes_honour_in_place_options_and_timeout() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("preserve_write_options");
+        let file = test_dir.join("doc.pdf");
+        create_minimal_test_pdf(&file)?;
+        let preserve = SetOptions {
+            save_mode: SaveMode::Preserve,
+            backup: Some(BackupPolicy { keep: 0 }),
+            ..SetOptions::default()
+        };
+
+        // Naming the source as the output is an in-place update, backup included.
+        set_metadata_with_options(&file, &file, "Title", "Relatório", &preserve)?;
+        assert_eq!(backup::list(&file).len(), 1);
+
+        // A revision for a separate output is not written once the caller timed out.
+        let (source, output) = (file.clone(), test_dir.join("late.pdf"));
+        let late = output.clone();
+        let result = with_timeout(Duration::from_millis(20), move || {
+            thread::sleep(Duration::from_millis(200));
+            let preserve = SetOptions { save_mode: SaveMode::Preserve, ..SetOptions::default() };
+            set_metadata_with_options(&source, &late, "Subject", "Too late", &preserve)
+        });
+        assert!(matches!(result, Err(Error::TimedOut(_))));
+        thread::sleep(Duration::from_millis(400));
+        assert!(!output.exists());
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_revision_holds_every_changed_object() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("incremental_changed_objects");
+        let (file, output) = (test_dir.join("doc.pdf"), test_dir.join("out.pdf"));
+        create_minimal_test_pdf(&file)?;
+        let original = fs::read(&file)?;
+
+        let preserve = SetOptions { save_mode: SaveMode::Preserve, ..SetOptions::default() };
+        with_write_options(preserve, || -> crate::Result<()> {
+            // Only the catalog, which already existed, and Info change.
+            let settings = ViewerSettings { lang: Some("pt-BR".to_string()), ..ViewerSettings::default() };
+            set_viewer_settings(&file, &output, &settings)?;
+            // The title also adds an XMP packet.
+            set_document_title(&output, &output, "Atlas", true)
+        })?;
+        let written = fs::read(&output)?;
+        assert_eq!(&written[..original.len()], original.as_slice());
+        let settings = get_viewer_settings(&output)?;
+        assert_eq!((settings.lang.as_deref(), settings.display_doc_title), (Some("pt-BR"), Some(true)));
+        assert_eq!(xmp::get_xmp_title_langs(&output)?, [("x-default".to_string(), "Atlas".to_string())]);
+        assert!(get_metadata(&output)?.contains(&("Title".to_string(), "Atlas".to_string())));
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+}
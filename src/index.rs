@@ -0,0 +1,273 @@
+//! # Corpus indexer
+//!
+//! Extracts the metadata of every PDF under a directory into a JSON Lines file,
+//! one `{"path": ..., "metadata": {...}}` record per document. Files are processed
+//! in fixed-size chunks through the [batch engine](crate::batch), so memory use is
+//! bounded by the chunk size rather than the corpus size.
+//!
+//! Progress is recorded in a checkpoint file after every chunk. Running the indexer
+//! again with the same checkpoint skips the files already recorded, so an
+//! interrupted run over a large corpus resumes where it stopped. Records are
+//! written before the checkpoint, so a crash can repeat at most one chunk.
+//!
+//! ```no_run
+//! use pdf_metadata::index::{index_corpus, IndexOptions};
+//! use std::path::Path;
+//!
+//! fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let summary = index_corpus(
+//!         Path::new("/srv/archive"),
+//!         Path::new("archive.jsonl"),
+//!         Path::new("archive.checkpoint"),
+//!         &IndexOptions::default(),
+//!     )?;
+//!     println!("{} indexed, {} failed, {} skipped", summary.indexed, summary.failed, summary.skipped);
+//!     Ok(())
+//! }
+//! ```
+
+use crate::batch::{self, BatchOptions};
+use crate::export::{json_string, to_json};
+use crate::{Result, get_metadata};
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+/// Settings for [`index_corpus`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IndexOptions {
+    /// Concurrency used within each chunk.
+    pub batch: BatchOptions,
+    /// Number of files processed between checkpoints. Values below 1 are treated as 1.
+    pub chunk_size: usize,
+    /// Whether to descend into subdirectories.
+    pub recursive: bool,
+    /// Files that failed in an earlier run are retried when `true`, skipped otherwise.
+    pub retry_failed: bool,
+    /// Pause after each chunk, to limit the load on shared storage.
+    pub pause_between_chunks: Option<Duration>,
+}
+
+impl Default for IndexOptions {
+    fn default() -> Self {
+        IndexOptions {
+            batch: BatchOptions::default(),
+            chunk_size: 256,
+            recursive: true,
+            retry_failed: false,
+            pause_between_chunks: None,
+        }
+    }
+}
+
+/// Counts reported by [`index_corpus`] for the current run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IndexSummary {
+    /// Files whose metadata was written to the output.
+    pub indexed: usize,
+    /// Files that could not be read; they are recorded in the checkpoint with the error.
+    pub failed: usize,
+    /// Files skipped because the checkpoint already recorded them.
+    pub skipped: usize,
+}
+
+/// The per-file status stored in a checkpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Done,
+    Failed,
+}
+
+impl Status {
+    fn tag(self) -> &'static str {
+        match self {
+            Status::Done => "done",
+            Status::Failed => "failed",
+        }
+    }
+}
+
+/// Indexes every PDF under `root`, appending records to `output` and progress to `checkpoint`.
+///
+/// Both files are created if missing and appended to otherwise.
+///
+/// # Returns
+///
+/// * `Ok(IndexSummary)`: Counts for this run. Unreadable PDFs are counted, not fatal.
+/// * `Err(Error)`: If `root` cannot be scanned or the output/checkpoint cannot be written.
+pub fn index_corpus(root: &Path, output: &Path, checkpoint: &Path, options: &IndexOptions) -> Result<IndexSummary> {
+    let recorded = read_checkpoint(checkpoint)?;
+    let mut summary = IndexSummary::default();
+
+    let pending: Vec<PathBuf> = batch::collect_pdfs(root, options.recursive)?
+        .into_iter()
+        .filter(|path| {
+            let skip = match recorded.get(path.as_path()) {
+                Some(Status::Done) => true,
+                Some(Status::Failed) => !options.retry_failed,
+                None => false,
+            };
+            if skip {
+                summary.skipped += 1;
+            }
+            !skip
+        })
+        .collect();
+    drop(recorded);
+
+    let mut output_file = open_for_append(output)?;
+    let mut checkpoint_file = open_for_append(checkpoint)?;
+
+    for chunk in pending.chunks(options.chunk_size.max(1)) {
+        let outcomes = batch::run(chunk, &options.batch, |path| get_metadata(path));
+
+        let mut progress = String::new();
+        for outcome in outcomes {
+            let path = outcome.path.to_string_lossy();
+            match outcome.result {
+                Ok(metadata) => {
+                    writeln!(output_file, "{{\"path\":{},\"metadata\":{}}}", json_string(&path), to_json(&metadata))?;
+                    progress.push_str(&checkpoint_line(Status::Done, &path, None));
+                    summary.indexed += 1;
+                }
+                Err(e) => {
+                    progress.push_str(&checkpoint_line(Status::Failed, &path, Some(&e.to_string())));
+                    summary.failed += 1;
+                }
+            }
+        }
+
+        // Records must be durable before the checkpoint claims them.
+        output_file.flush()?;
+        output_file.get_ref().sync_data()?;
+        checkpoint_file.write_all(progress.as_bytes())?;
+        checkpoint_file.flush()?;
+        checkpoint_file.get_ref().sync_data()?;
+
+        if let Some(pause) = options.pause_between_chunks {
+            thread::sleep(pause);
+        }
+    }
+
+    Ok(summary)
+}
+
+/// Opens `path` for appending lines, terminating a torn last line left by an interrupted run.
+fn open_for_append(path: &Path) -> Result<BufWriter<File>> {
+    let mut file = OpenOptions::new().create(true).read(true).append(true).open(path)?;
+    let len = file.metadata()?.len();
+    if len > 0 {
+        let mut last = [0u8; 1];
+        file.seek(SeekFrom::Start(len - 1))?;
+        file.read_exact(&mut last)?;
+        if last[0] != b'\n' {
+            file.write_all(b"\n")?;
+        }
+    }
+    Ok(BufWriter::new(file))
+}
+
+/// Formats one checkpoint line: `status<TAB>path[<TAB>error]`.
+fn checkpoint_line(status: Status, path: &str, error: Option<&str>) -> String {
+    match error {
+        Some(message) => format!("{}\t{}\t{}\n", status.tag(), path, message.replace(['\t', '\n', '\r'], " ")),
+        None => format!("{}\t{}\n", status.tag(), path),
+    }
+}
+
+/// Loads the latest status of every path recorded in `checkpoint`.
+///
+/// A missing checkpoint means a fresh run. A torn final line from an interrupted
+/// write is ignored, so that file is simply processed again.
+fn read_checkpoint(checkpoint: &Path) -> Result<HashMap<PathBuf, Status>> {
+    let mut recorded = HashMap::new();
+    let file = match File::open(checkpoint) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(recorded),
+        Err(e) => return Err(e.into()),
+    };
+
+    for line in BufReader::new(file).lines() {
+        let line = line?;
+        let mut fields = line.split('\t');
+        let status = match fields.next() {
+            Some("done") => Status::Done,
+            Some("failed") => Status::Failed,
+            _ => continue,
+        };
+        if let Some(path) = fields.next().filter(|p| !p.is_empty()) {
+            recorded.insert(PathBuf::from(path), status);
+        }
+    }
+    Ok(recorded)
+}
+
+/// Returns the paths recorded as indexed in `checkpoint`.
+///
+/// Useful for reporting progress of a run from another process.
+pub fn indexed_paths(checkpoint: &Path) -> Result<HashSet<PathBuf>> {
+    Ok(read_checkpoint(checkpoint)?
+        .into_iter()
+        .filter(|(_, status)| *status == Status::Done)
+        .map(|(path, _)| path)
+        .collect())
+}
+
+/// Removes the checkpoint so the next run starts from scratch.
+pub fn reset_checkpoint(checkpoint: &Path) -> Result<()> {
+    match fs::remove_file(checkpoint) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{create_minimal_test_pdf, setup_unique_test_dir};
+
+    #[test]
+    fn test_index_corpus_resumes_from_checkpoint() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("index_resume");
+        let corpus = test_dir.join("corpus");
+        fs::create_dir_all(&corpus)?;
+        for i in 0..5 {
+            create_minimal_test_pdf(&corpus.join(format!("doc_{}.pdf", i)))?;
+        }
+        fs::write(corpus.join("broken.pdf"), "not a pdf")?;
+        let output = test_dir.join("index.jsonl");
+        let checkpoint = test_dir.join("index.checkpoint");
+        let options = IndexOptions { chunk_size: 2, ..IndexOptions::default() };
+
+        // Simulate an interrupted run that only recorded the first two files.
+        let first = batch::collect_pdfs(&corpus, true)?;
+        fs::write(
+            &checkpoint,
+            format!("done\t{}\ndone\t{}\ndon", first[1].display(), first[2].display()),
+        )?;
+
+        let summary = index_corpus(&corpus, &output, &checkpoint, &options)?;
+        assert_eq!(summary, IndexSummary { indexed: 3, failed: 1, skipped: 2 });
+        assert_eq!(fs::read_to_string(&output)?.lines().count(), 3);
+        assert_eq!(indexed_paths(&checkpoint)?.len(), 5);
+
+        // Nothing is left to do; the failure is only retried on request.
+        let summary = index_corpus(&corpus, &output, &checkpoint, &options)?;
+        assert_eq!(summary, IndexSummary { indexed: 0, failed: 0, skipped: 6 });
+        let retry = IndexOptions { retry_failed: true, ..options };
+        assert_eq!(index_corpus(&corpus, &output, &checkpoint, &retry)?.failed, 1);
+
+        let record = fs::read_to_string(&output)?.lines().next().unwrap_or_default().to_string();
+        assert!(record.starts_with("{\"path\":\"") && record.ends_with(",\"metadata\":{}}"), "{}", record);
+
+        reset_checkpoint(&checkpoint)?;
+        assert!(!checkpoint.exists());
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,347 @@
+//! # Declarative jobs (`jobs` feature)
+//!
+//! A job file describes a metadata run in TOML: which files to process, the
+//! operations to apply to each, and where the results go. Unlike a shell pipeline it
+//! can be reviewed and re-run as is; `pdf_metadata run job.toml` executes it on the
+//! [`batch`] engine.
+//!
+//! ```toml
+//! # Globs are relative to the job file.
+//! inputs = ["inbox/**/*.pdf"]
+//!
+//! [options]
+//! backup = false        # keep <file>.bak before changing a file in place
+//! incremental = false   # append set/copy changes as incremental updates
+//! max_concurrency = 4
+//!
+//! # Optional; without it files are changed in place.
+//! [output]
+//! pattern = "../published/{year}/{stem}.pdf"
+//!
+//! [[operations]]
+//! op = "set"
+//! key = "Author"
+//! value = "Records Office"
+//!
+//! [[operations]]
+//! op = "copy"
+//! from = "Title"
+//! to = "Subject"
+//!
+//! [[operations]]
+//! op = "strip"
+//! keep = ["Title", "Author", "Subject"]
+//!
+//! [[operations]]
+//! op = "validate"
+//! require = ["Title", "Author"]
+//! ```
+//!
+//! Operations run in order on every file. Consecutive `set` and `copy` operations
+//! are written together in one update.
+
+use crate::batch::{self, BatchOptions, MappedOutcome, OutputMapper, OutputPattern};
+use crate::incremental::update_entries_incremental;
+use crate::scrub::{StripOptions, strip_metadata_with_options};
+use crate::{Error, Result, get_metadata, options, update_entries_in_place};
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml::{Table, Value};
+
+/// One step of a [`Job`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Operation {
+    /// Sets `key` to `value`.
+    Set { key: String, value: String },
+    /// Sets `to` to the current value of `from`; fails if `from` is missing.
+    Copy { from: String, to: String },
+    /// Removes the metadata as [`strip_metadata_with_options`] does.
+    Strip(StripOptions),
+    /// Fails the file unless every key in `require` has a non-empty value.
+    Validate { require: Vec<String> },
+}
+
+/// Settings of a [`Job`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct JobOptions {
+    /// Copy each file to `<file>.bak` before changing it in place.
+    pub backup: bool,
+    /// Write `set` and `copy` changes as incremental updates.
+    pub incremental: bool,
+    /// Files processed at the same time; one per CPU when `None`.
+    pub max_concurrency: Option<usize>,
+}
+
+/// A parsed job file.
+#[derive(Debug, Clone)]
+pub struct Job {
+    /// Glob patterns selecting the input files.
+    pub inputs: Vec<String>,
+    /// The steps applied to every input, in order.
+    pub operations: Vec<Operation>,
+    pub options: JobOptions,
+    /// Where outputs go; `None` changes the inputs in place.
+    pub output: Option<OutputPattern>,
+    /// The directory relative input globs are resolved against.
+    pub base_dir: PathBuf,
+}
+
+impl Job {
+    /// Reads and parses the job file at `path`.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Job> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)?;
+        Job::parse(&text, path.parent().unwrap_or(Path::new("")))
+    }
+
+    /// Parses a job from TOML text, resolving relative globs against `base_dir`.
+    ///
+    /// Fails with `Error::JobFile` on syntax errors, unknown operations or missing
+    /// fields.
+    pub fn parse(text: &str, base_dir: &Path) -> Result<Job> {
+        let table: Table = text.parse().map_err(|e: toml::de::Error| Error::JobFile(e.message().to_string()))?;
+        let inputs = string_list(&table, "inputs")?.ok_or_else(|| Error::JobFile("missing 'inputs'".to_string()))?;
+
+        let options = match table.get("options") {
+            Some(Value::Table(options)) => JobOptions {
+                backup: flag(options, "backup")?,
+                incremental: flag(options, "incremental")?,
+                max_concurrency: match options.get("max_concurrency") {
+                    Some(Value::Integer(n)) if *n > 0 => Some(*n as usize),
+                    Some(_) => return Err(Error::JobFile("'max_concurrency' must be a positive integer".to_string())),
+                    None => None,
+                },
+            },
+            Some(_) => return Err(Error::JobFile("'options' must be a table".to_string())),
+            None => JobOptions::default(),
+        };
+
+        let output = match table.get("output") {
+            Some(Value::Table(output)) => Some(OutputPattern::new(&string(output, "pattern")?)?),
+            Some(_) => return Err(Error::JobFile("'output' must be a table".to_string())),
+            None => None,
+        };
+
+        let operations = match table.get("operations") {
+            Some(Value::Array(operations)) => operations.iter().map(parse_operation).collect::<Result<Vec<_>>>()?,
+            Some(_) => return Err(Error::JobFile("'operations' must be an array of tables".to_string())),
+            None => Vec::new(),
+        };
+
+        Ok(Job { inputs, operations, options, output, base_dir: base_dir.to_path_buf() })
+    }
+
+    /// Expands the input globs into a sorted list of files without duplicates.
+    pub fn input_files(&self) -> Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        for pattern in &self.inputs {
+            let full = self.base_dir.join(pattern);
+            let paths = glob::glob(&full.to_string_lossy())
+                .map_err(|e| Error::JobFile(format!("invalid input glob '{}': {}", pattern, e.msg)))?;
+            files.extend(paths.filter_map(|path| path.ok()).filter(|path| path.is_file()));
+        }
+        files.sort();
+        files.dedup();
+        Ok(files)
+    }
+
+    /// Runs the job on every input file.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Vec<MappedOutcome<()>>)`: One outcome per input file, with its output path.
+    /// * `Err(Error)`: If the input globs are invalid.
+    pub fn run(&self) -> Result<Vec<MappedOutcome<()>>> {
+        let files = self.input_files()?;
+        let mut batch_options = BatchOptions::default();
+        if let Some(max) = self.options.max_concurrency {
+            batch_options.max_concurrency = max;
+        }
+        let in_place = |input: &Path| input.to_path_buf();
+        let mapper: &dyn OutputMapper = match &self.output {
+            Some(pattern) => pattern,
+            None => &in_place,
+        };
+        Ok(batch::run_mapped(&files, &batch_options, mapper, |input, output| self.run_file(input, output)))
+    }
+
+    fn run_file(&self, input: &Path, output: &Path) -> Result<()> {
+        if output != input {
+            fs::copy(input, output).map_err(|source| Error::Write { path: output.to_path_buf(), source })?;
+        } else if self.options.backup && self.operations.iter().any(|op| !matches!(op, Operation::Validate { .. })) {
+            let mut backup = input.as_os_str().to_owned();
+            backup.push(".bak");
+            fs::copy(input, &backup).map_err(|source| Error::Write { path: PathBuf::from(backup), source })?;
+        }
+
+        let mut pending: Vec<(String, String)> = Vec::new();
+        for operation in &self.operations {
+            match operation {
+                Operation::Set { key, value } => pending.push((key.clone(), value.clone())),
+                Operation::Copy { from, to } => {
+                    let value = match pending.iter().rev().find(|(key, _)| key == from) {
+                        Some((_, value)) => value.clone(),
+                        None => current_value(output, from)?.ok_or_else(|| Error::Validation(format!("{} is missing", from)))?,
+                    };
+                    pending.push((to.clone(), value));
+                }
+                Operation::Strip(options) => {
+                    self.flush(output, &mut pending)?;
+                    strip_metadata_with_options(output, output, options)?;
+                }
+                Operation::Validate { require } => {
+                    self.flush(output, &mut pending)?;
+                    let metadata = get_metadata(output)?;
+                    let missing: Vec<&str> = require
+                        .iter()
+                        .filter(|key| !metadata.iter().any(|(k, v)| k == *key && !v.trim().is_empty()))
+                        .map(String::as_str)
+                        .collect();
+                    if !missing.is_empty() {
+                        return Err(Error::Validation(format!("missing {}", missing.join(", "))));
+                    }
+                }
+            }
+        }
+        self.flush(output, &mut pending)
+    }
+
+    /// Writes the accumulated `set`/`copy` entries in one update.
+    fn flush(&self, path: &Path, pending: &mut Vec<(String, String)>) -> Result<()> {
+        if pending.is_empty() {
+            return Ok(());
+        }
+        if self.options.incremental {
+            update_entries_incremental(path, pending, &options::current())?;
+        } else {
+            update_entries_in_place(path, pending)?;
+        }
+        pending.clear();
+        Ok(())
+    }
+}
+
+fn current_value(path: &Path, key: &str) -> Result<Option<String>> {
+    Ok(get_metadata(path)?.into_iter().find(|(k, _)| k == key).map(|(_, v)| v))
+}
+
+fn parse_operation(value: &Value) -> Result<Operation> {
+    let Value::Table(table) = value else {
+        return Err(Error::JobFile("each operation must be a table".to_string()));
+    };
+    match string(table, "op")?.as_str() {
+        "set" => Ok(Operation::Set { key: string(table, "key")?, value: string(table, "value")? }),
+        "copy" => Ok(Operation::Copy { from: string(table, "from")?, to: string(table, "to")? }),
+        "strip" => Ok(Operation::Strip(StripOptions {
+            keep: string_list(table, "keep")?.unwrap_or_default(),
+            remove_xmp: match table.get("remove_xmp") {
+                Some(_) => flag(table, "remove_xmp")?,
+                None => StripOptions::default().remove_xmp,
+            },
+        })),
+        "validate" => Ok(Operation::Validate { require: string_list(table, "require")?.unwrap_or_default() }),
+        other => Err(Error::JobFile(format!("unknown operation '{}'", other))),
+    }
+}
+
+fn string(table: &Table, key: &str) -> Result<String> {
+    match table.get(key) {
+        Some(Value::String(s)) => Ok(s.clone()),
+        Some(_) => Err(Error::JobFile(format!("'{}' must be a string", key))),
+        None => Err(Error::JobFile(format!("missing '{}'", key))),
+    }
+}
+
+fn string_list(table: &Table, key: &str) -> Result<Option<Vec<String>>> {
+    let not_strings = || Error::JobFile(format!("'{}' must be an array of strings", key));
+    match table.get(key) {
+        Some(Value::Array(items)) => items
+            .iter()
+            .map(|item| item.as_str().map(str::to_string).ok_or_else(not_strings))
+            .collect::<Result<Vec<_>>>()
+            .map(Some),
+        Some(_) => Err(not_strings()),
+        None => Ok(None),
+    }
+}
+
+fn flag(table: &Table, key: &str) -> Result<bool> {
+    match table.get(key) {
+        Some(Value::Boolean(b)) => Ok(*b),
+        Some(_) => Err(Error::JobFile(format!("'{}' must be true or false", key))),
+        None => Ok(false),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{create_minimal_test_pdf, setup_unique_test_dir};
+
+    #[test]
+    fn test_parse_rejects_invalid_jobs() {
+        let base = Path::new("jobs");
+        assert!(matches!(Job::parse("operations = []", base), Err(Error::JobFile(_))));
+        assert!(matches!(
+            Job::parse("inputs = ['*.pdf']\n[[operations]]\nop = 'rename'", base),
+            Err(Error::JobFile(message)) if message.contains("rename")
+        ));
+        assert!(Job::parse("inputs = ['*.pdf']\n[[operations]]\nop = 'set'\nkey = 'Title'", base).is_err());
+
+        let job = Job::parse("inputs = ['*.pdf']\n[[operations]]\nop = 'strip'\nkeep = ['Title']", base).unwrap();
+        assert_eq!(job.operations, [Operation::Strip(StripOptions { keep: vec!["Title".to_string()], remove_xmp: true })]);
+    }
+
+    #[test]
+    fn test_run_applies_operations_to_outputs() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("job_run");
+        fs::create_dir_all(test_dir.join("inbox"))?;
+        for name in ["a.pdf", "b.pdf"] {
+            create_minimal_test_pdf(&test_dir.join("inbox").join(name))?;
+        }
+        update_entries_in_place(test_dir.join("inbox/a.pdf").to_str().unwrap(), &[("Title", "Report A"), ("Producer", "Word")])?;
+        let job_file = test_dir.join("job.toml");
+        fs::write(
+            &job_file,
+            r#"
+inputs = ["inbox/*.pdf"]
+
+[output]
+pattern = "../out/{stem}.pdf"
+
+[[operations]]
+op = "set"
+key = "Author"
+value = "Records"
+
+[[operations]]
+op = "copy"
+from = "Title"
+to = "Subject"
+
+[[operations]]
+op = "strip"
+keep = ["Title", "Author", "Subject"]
+
+[[operations]]
+op = "validate"
+require = ["Title", "Author"]
+"#,
+        )?;
+
+        let outcomes = Job::load(&job_file)?.run()?;
+        assert_eq!(outcomes.len(), 2);
+        assert!(outcomes[0].result.is_ok());
+        let mut metadata = get_metadata(test_dir.join("out/a.pdf").to_str().unwrap())?;
+        metadata.sort();
+        let expected = [("Author", "Records"), ("Subject", "Report A"), ("Title", "Report A")];
+        assert_eq!(metadata, expected.map(|(k, v)| (k.to_string(), v.to_string())));
+        // b.pdf has no Title to copy.
+        assert!(matches!(&outcomes[1].result, Err(Error::Validation(message)) if message.contains("Title")));
+        assert!(get_metadata(test_dir.join("inbox/a.pdf").to_str().unwrap())?.iter().any(|(k, _)| k == "Producer"));
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,199 @@
+//! O diário de alterações do modo interativo.
+//!
+//! Cada alteração guarda os valores que as chaves gravadas tinham antes, inclusive
+//! ModDate, para que "Desfazer" devolva os arquivos ao estado anterior: valores
+//! editados voltam, chaves criadas somem e chaves apagadas reaparecem.
+
+use pdf_metadata::{MetadataEditor, MetadataValue, SetOptions, get_metadata_values, update_metadata_in_place};
+use std::error::Error;
+
+/// Uma alteração feita no modo interativo, com os valores anteriores das chaves
+/// que ela gravou, inclusive ModDate, para poder desfazê-la.
+pub struct Change {
+    previous: Vec<PreviousValue>,
+}
+
+/// O valor de `key` em `file` antes de uma alteração; `None` se a chave não existia.
+struct PreviousValue {
+    file: String,
+    key: String,
+    value: Option<MetadataValue>,
+}
+
+/// Executa `write`, que grava `keys` em `files`, e devolve a alteração que o desfaz.
+pub fn record(files: &[&str], keys: &[&str], write: impl FnOnce() -> Result<(), Box<dyn Error>>) -> Result<Change, Box<dyn Error>> {
+    let change = Change::snapshot(files, keys)?;
+    write()?;
+    Ok(change)
+}
+
+/// Um arquivo e o resultado da gravação nele.
+pub type Written<'a> = (&'a str, Result<(), Box<dyn Error>>);
+
+/// Grava `key` = `value` em cada um de `files`, seguindo adiante quando um falha.
+/// Devolve o resultado de cada arquivo e a alteração feita nos que foram gravados, se algum foi.
+pub fn apply_to_files<'a>(files: &[&'a str], key: &str, value: &str) -> (Vec<Written<'a>>, Option<Change>) {
+    let mut results = Vec::new();
+    let mut previous = Vec::new();
+    for &file in files {
+        match record(&[file], &[key], || Ok(update_metadata_in_place(file, key, value)?)) {
+            Ok(change) => {
+                previous.extend(change.previous);
+                results.push((file, Ok(())));
+            }
+            Err(e) => results.push((file, Err(e))),
+        }
+    }
+    (results, (!previous.is_empty()).then_some(Change { previous }))
+}
+
+impl Change {
+    /// Guarda os valores atuais de `keys` e de ModDate em cada um de `files`.
+    fn snapshot(files: &[&str], keys: &[&str]) -> Result<Change, Box<dyn Error>> {
+        let mut previous = Vec::new();
+        for &file in files {
+            let values = get_metadata_values(file)?;
+            for &key in keys.iter().chain(&["ModDate"]) {
+                let value = values.iter().find(|(k, _)| k == key).map(|(_, value)| value.clone());
+                previous.push(PreviousValue { file: file.to_string(), key: key.to_string(), value });
+            }
+        }
+        Ok(Change { previous })
+    }
+
+    /// As chaves e os arquivos alterados, para exibição.
+    pub fn describe(&self) -> String {
+        let mut keys: Vec<&str> = Vec::new();
+        let mut files: Vec<&str> = Vec::new();
+        for previous in &self.previous {
+            if previous.key != "ModDate" && !keys.contains(&previous.key.as_str()) {
+                keys.push(&previous.key);
+            }
+            if !files.contains(&previous.file.as_str()) {
+                files.push(&previous.file);
+            }
+        }
+        format!("{} ({})", keys.join(", "), files.join(", "))
+    }
+
+    /// Grava de volta os valores anteriores, sem atualizar ModDate, que também é restaurado.
+    pub fn undo(&self) -> Result<(), Box<dyn Error>> {
+        let mut files: Vec<&str> = self.previous.iter().map(|previous| previous.file.as_str()).collect();
+        files.dedup();
+        for file in files {
+            let mut editor = MetadataEditor::open(file)?;
+            for previous in self.previous.iter().filter(|previous| previous.file == file) {
+                editor = match &previous.value {
+                    Some(value) => editor.set_value(&previous.key, value),
+                    None => editor.remove(&previous.key),
+                };
+            }
+            editor.options(SetOptions { update_mod_date: false, ..SetOptions::default() }).save_in_place()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use lopdf::{Dictionary, Document, Object};
+    use chrono::DateTime;
+    use pdf_metadata::{FixedClock, get_metadata, remove_metadata_in_place, with_clock};
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    // Cria um diretório vazio para um teste.
+    pub(crate) fn setup_unique_test_dir(test_name: &str) -> PathBuf {
+        let micros = SystemTime::now().duration_since(UNIX_EPOCH).expect("Time went backwards").as_micros();
+        let dir = std::env::temp_dir().join("pdf_metadata_tests").join(format!("cli_{}_{}", test_name, micros));
+        fs::create_dir_all(&dir).expect("Failed to create temp test directory");
+        dir
+    }
+
+    // Grava um PDF sem páginas cujo dicionário Info tem só `Title`, sem ModDate.
+    pub(crate) fn create_test_pdf(path: &Path) -> Result<(), Box<dyn Error>> {
+        let mut doc = Document::with_version("1.7");
+        let mut pages = Dictionary::new();
+        pages.set("Type", Object::Name(b"Pages".to_vec()));
+        pages.set("Count", Object::Integer(0));
+        pages.set("Kids", Object::Array(vec![]));
+        let pages_id = doc.add_object(pages);
+        let mut catalog = Dictionary::new();
+        catalog.set("Type", Object::Name(b"Catalog".to_vec()));
+        catalog.set("Pages", Object::Reference(pages_id));
+        let catalog_id = doc.add_object(catalog);
+        let mut info = Dictionary::new();
+        info.set("Title", Object::string_literal("Report"));
+        let info_id = doc.add_object(info);
+        doc.trailer.set("Root", Object::Reference(catalog_id));
+        doc.trailer.set("Info", Object::Reference(info_id));
+        doc.save(path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_undo_restores_previous_values() -> Result<(), Box<dyn Error>> {
+        let test_dir = setup_unique_test_dir("journal_undo");
+        let file = test_dir.join("doc.pdf");
+        create_test_pdf(&file)?;
+        let file = file.to_str().unwrap();
+        let original = get_metadata(file)?;
+
+        // Uma chave nova e ModDate, que não existiam, somem ao desfazer.
+        let created = record(&[file], &["Author"], || Ok(update_metadata_in_place(file, "Author", "Ana")?))?;
+        assert_eq!(created.describe(), format!("Author ({})", file));
+        assert!(get_metadata(file)?.iter().any(|(key, _)| key == "ModDate"));
+        created.undo()?;
+        assert_eq!(get_metadata(file)?, original);
+
+        update_metadata_in_place(file, "Author", "Ana")?;
+        let mut before = get_metadata(file)?;
+        let later = DateTime::parse_from_rfc3339("2030-01-02T03:04:05+00:00")?;
+        let (edited, deleted) = with_clock(FixedClock(later), || -> Result<_, Box<dyn Error>> {
+            let edited = record(&[file], &["Title"], || Ok(update_metadata_in_place(file, "Title", "Draft")?))?;
+            let deleted = record(&[file], &["Author"], || Ok(remove_metadata_in_place(file, "Author")?))?;
+            Ok((edited, deleted))
+        })?;
+        assert!(get_metadata(file)?.contains(&("ModDate".to_string(), "D:20300102030405+00'00'".to_string())));
+        deleted.undo()?;
+        edited.undo()?;
+        // O valor editado volta, a chave apagada reaparece e ModDate é o de antes.
+        let mut after = get_metadata(file)?;
+        before.sort();
+        after.sort();
+        assert_eq!(after, before);
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_apply_to_files_skips_failures_and_undoes_the_rest() -> Result<(), Box<dyn Error>> {
+        let test_dir = setup_unique_test_dir("journal_apply");
+        let (a, b, broken) = (test_dir.join("a.pdf"), test_dir.join("b.pdf"), test_dir.join("broken.pdf"));
+        create_test_pdf(&a)?;
+        create_test_pdf(&b)?;
+        fs::write(&broken, b"not a pdf")?;
+        let files = [a.to_str().unwrap(), broken.to_str().unwrap(), b.to_str().unwrap()];
+        let original = get_metadata(files[0])?;
+
+        let (results, change) = apply_to_files(&files, "Subject", "Lote");
+        let failed: Vec<&str> = results.iter().filter(|(_, result)| result.is_err()).map(|(file, _)| *file).collect();
+        assert_eq!(failed, [files[1]]);
+        let change = change.unwrap();
+        assert_eq!(change.describe(), format!("Subject ({}, {})", files[0], files[2]));
+        for file in [files[0], files[2]] {
+            assert!(get_metadata(file)?.contains(&("Subject".to_string(), "Lote".to_string())));
+        }
+
+        change.undo()?;
+        assert_eq!(get_metadata(files[0])?, original);
+        assert_eq!(get_metadata(files[2])?, original);
+        assert!(apply_to_files(&files[1..2], "Subject", "Lote").1.is_none());
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,173 @@
+//! Matching Info keys regardless of case.
+//!
+//! Info keys are PDF names, which are case-sensitive: `author` and `Author` are two
+//! entries, and viewers only show the second. With [`KeyMatch::CaseInsensitive`],
+//! keys given in any case find the entry the document already has, and writes use
+//! the standard spelling of the well-known keys.
+
+use crate::validate::STANDARD_KEYS;
+use crate::{KeyMatch, Result, get_pdf_metadata};
+#[cfg(feature = "std-fs")]
+use crate::{get_metadata, info_dict_mut, load_document, mark_modified, options, write_loaded};
+use lopdf::Dictionary;
+#[cfg(feature = "std-fs")]
+use std::path::Path;
+
+/// Returns the entry matching `key` as `(key, value)`, with the key as spelled in the
+/// document, or `None` if there is none.
+///
+/// With [`KeyMatch::CaseInsensitive`], the entry spelled like the standard key, which
+/// is the one viewers show, is preferred, then the one spelled exactly like `key`,
+/// then the first matching entry in document order.
+///
+/// # Returns
+///
+/// * `Ok(Option<(String, String)>)`: The entry, if the document has one.
+/// * `Err(Error)`: If the file cannot be read or parsed.
+///
+/// # Example
+///
+/// ```no_run
+/// use pdf_metadata::{KeyMatch, get_metadata_matching};
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     if let Some((key, value)) = get_metadata_matching("report.pdf", "author", KeyMatch::CaseInsensitive)? {
+///         println!("{}: {}", key, value);
+///     }
+///     Ok(())
+/// }
+/// ```
+#[cfg(feature = "std-fs")]
+pub fn get_metadata_matching<P: AsRef<Path>>(file_path: P, key: &str, key_match: KeyMatch) -> Result<Option<(String, String)>> {
+    let file_path = file_path.as_ref();
+    Ok(find_matching(get_metadata(file_path)?, key, key_match))
+}
+
+/// Returns the entry matching `key` in a PDF in memory, as [`get_metadata_matching`]
+/// does for a file.
+pub fn get_pdf_metadata_matching(pdf_content: &[u8], key: &str, key_match: KeyMatch) -> Result<Option<(String, String)>> {
+    Ok(find_matching(get_pdf_metadata(pdf_content)?, key, key_match))
+}
+
+fn find_matching(mut entries: Vec<(String, String)>, key: &str, key_match: KeyMatch) -> Option<(String, String)> {
+    let found = match key_match {
+        KeyMatch::Exact => entries.iter().position(|(k, _)| k == key),
+        KeyMatch::CaseInsensitive => {
+            let preferred = standard_spelling(key).unwrap_or(key);
+            entries
+                .iter()
+                .position(|(k, _)| k == preferred)
+                .or_else(|| entries.iter().position(|(k, _)| k == key))
+                .or_else(|| entries.iter().position(|(k, _)| k.eq_ignore_ascii_case(key)))
+        }
+    };
+    found.map(|index| entries.swap_remove(index))
+}
+
+/// Removes the entries matching `key` in place and returns their keys, as spelled in
+/// the document. With [`KeyMatch::CaseInsensitive`], every entry differing from `key`
+/// only in case is removed. `ModDate` is refreshed only if something was removed.
+///
+/// # Returns
+///
+/// * `Ok(Vec<String>)`: The keys removed; empty if none matched.
+/// * `Err(Error)`: If the file cannot be read, parsed or written.
+#[cfg(feature = "std-fs")]
+pub fn remove_metadata_matching<P: AsRef<Path>>(file_path: P, key: &str, key_match: KeyMatch) -> Result<Vec<String>> {
+    let file_path = file_path.as_ref();
+    let mut doc = load_document(file_path)?;
+    let removed: Vec<Vec<u8>> = info_dict_mut(&mut doc)?
+        .iter()
+        .map(|(k, _)| k.clone())
+        .filter(|k| match key_match {
+            KeyMatch::Exact => k == key.as_bytes(),
+            KeyMatch::CaseInsensitive => k.eq_ignore_ascii_case(key.as_bytes()),
+        })
+        .collect();
+    if removed.is_empty() {
+        return Ok(Vec::new());
+    }
+    let options = options::current();
+    write_loaded(doc, file_path, None, &options, |doc| {
+        let info_dict = info_dict_mut(doc)?;
+        for k in &removed {
+            info_dict.remove(k);
+        }
+        mark_modified(doc, &options)
+    })?;
+    Ok(removed.iter().map(|k| String::from_utf8_lossy(k).into_owned()).collect())
+}
+
+/// The key a case-insensitive write of `key` sets: the standard spelling, else the
+/// spelling of an entry `info` already has, else `key` itself.
+pub(crate) fn canonical_key(info: &Dictionary, key: &str) -> String {
+    if let Some(standard) = standard_spelling(key) {
+        return standard.to_string();
+    }
+    if info.has(key.as_bytes()) {
+        return key.to_string();
+    }
+    info.iter()
+        .map(|(k, _)| k)
+        .find(|k| k.eq_ignore_ascii_case(key.as_bytes()))
+        .map_or_else(|| key.to_string(), |k| String::from_utf8_lossy(k).into_owned())
+}
+
+/// Removes the entries of `info` whose keys differ from `key` only in case.
+pub(crate) fn remove_variants(info: &mut Dictionary, key: &str) {
+    let variants: Vec<Vec<u8>> = info
+        .iter()
+        .map(|(k, _)| k)
+        .filter(|k| k.as_slice() != key.as_bytes() && k.eq_ignore_ascii_case(key.as_bytes()))
+        .cloned()
+        .collect();
+    for variant in variants {
+        info.remove(&variant);
+    }
+}
+
+fn standard_spelling(key: &str) -> Option<&'static str> {
+    STANDARD_KEYS.iter().copied().find(|standard| standard.eq_ignore_ascii_case(key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{create_minimal_test_pdf, setup_unique_test_dir};
+    use crate::{MetadataEditor, SetOptions, update_entries_in_place, update_entries_in_place_with};
+    use std::fs;
+
+    #[test]
+    fn test_case_insensitive_keys() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("key_case");
+        let file = test_dir.join("doc.pdf");
+        create_minimal_test_pdf(&file)?;
+        let file_str = file.to_str().unwrap();
+        let keys = || -> Result<Vec<String>> { Ok(get_metadata(file_str)?.into_iter().map(|(k, _)| k).collect()) };
+
+        // Exact matching, the default, adds duplicates.
+        update_entries_in_place(file_str, &[("author", "ana"), ("projectId", "1"), ("ProjectID", "2")])?;
+        assert!(keys()?.contains(&"author".to_string()));
+
+        let insensitive = SetOptions { key_match: KeyMatch::CaseInsensitive, ..SetOptions::default() };
+        update_entries_in_place_with(file_str, &[("AUTHOR", "Ana"), ("PROJECTID", "3")], &insensitive)?;
+        let found = get_metadata_matching(file_str, "author", KeyMatch::CaseInsensitive)?;
+        assert_eq!(found, Some(("Author".to_string(), "Ana".to_string())));
+        assert_eq!(get_metadata_matching(file_str, "projectid", KeyMatch::CaseInsensitive)?.unwrap().1, "3");
+        assert_eq!(get_metadata_matching(file_str, "projectid", KeyMatch::Exact)?, None);
+        assert!(keys()?.contains(&"author".to_string()), "variants stay without merging");
+
+        let merge = SetOptions { merge_case_duplicates: true, ..insensitive };
+        MetadataEditor::open(&file)?.set("projectid", "4").options(merge).save_in_place()?;
+        let after = keys()?;
+        assert_eq!(after.iter().filter(|k| k.eq_ignore_ascii_case("ProjectId")).count(), 1);
+
+        let mut removed = remove_metadata_matching(file_str, "AUTHOR", KeyMatch::CaseInsensitive)?;
+        removed.sort();
+        assert_eq!(removed, ["Author", "author"]);
+        assert!(remove_metadata_matching(file_str, "Author", KeyMatch::Exact)?.is_empty());
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,206 @@
+//! Size, object-count and time limits for parsing untrusted input.
+//!
+//! A crafted upload can declare millions of objects or keep the parser busy for
+//! minutes. [`LoadLimits`] rejects such files with `Error::LimitExceeded` before
+//! they cost more than the caller allowed: the size is checked before the file is
+//! read, the object count before and after parsing (objects packed in object streams
+//! are only visible after), and the parse runs under [`with_timeout`](crate::with_timeout).
+
+use crate::recover::object_headers;
+use crate::timeout::with_timeout;
+use crate::{Error, Result, load_document_mem, read_info_entries};
+use lopdf::Document;
+use std::collections::HashSet;
+use std::fmt;
+#[cfg(feature = "std-fs")]
+use std::fs::File;
+#[cfg(feature = "std-fs")]
+use std::io::Read;
+#[cfg(feature = "std-fs")]
+use std::path::Path;
+use std::time::Duration;
+
+/// Limits applied while loading a document. `None` leaves a dimension unlimited,
+/// so `LoadLimits::default()` behaves like the functions without limits.
+///
+/// # Example
+///
+/// ```no_run
+/// use pdf_metadata::{Error, LoadLimits, get_metadata_with_limits};
+/// use std::time::Duration;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let limits = LoadLimits {
+///         max_bytes: Some(20 * 1024 * 1024),
+///         max_objects: Some(100_000),
+///         timeout: Some(Duration::from_secs(5)),
+///     };
+///     match get_metadata_with_limits("upload.pdf", &limits) {
+///         Ok(metadata) => println!("{} entries", metadata.len()),
+///         Err(Error::LimitExceeded(limit)) => eprintln!("rejected: {}", limit),
+///         Err(e) => return Err(e.into()),
+///     }
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LoadLimits {
+    /// The largest file accepted, in bytes.
+    pub max_bytes: Option<u64>,
+    /// The most objects a document may hold, counting each object number once.
+    pub max_objects: Option<usize>,
+    /// The longest the parse may take.
+    ///
+    /// lopdf cannot interrupt a parse, so a timed-out one is not stopped: it keeps
+    /// its thread and memory until it finishes on its own. After
+    /// [`MAX_ABANDONED_WORKERS`](crate::MAX_ABANDONED_WORKERS) such parses, new loads
+    /// fail with `Limit::Time` at once until some of them end. To kill a hostile parse
+    /// instead, use the worker process of the `sandbox` feature with a timeout.
+    pub timeout: Option<Duration>,
+}
+
+/// The limit a document exceeded, with its configured maximum, as carried by
+/// `Error::LimitExceeded`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+    /// The file is larger than this many bytes.
+    Bytes(u64),
+    /// The document holds more than this many objects.
+    Objects(usize),
+    /// Parsing took longer than this.
+    Time(Duration),
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Limit::Bytes(max) => write!(f, "file is larger than {} bytes", max),
+            Limit::Objects(max) => write!(f, "document has more than {} objects", max),
+            Limit::Time(max) => write!(f, "parsing took longer than {:?}", max),
+        }
+    }
+}
+
+/// Retrieves the Info entries of a PDF file like [`get_metadata`](crate::get_metadata),
+/// within `limits`.
+///
+/// The document is always parsed in full, so the object count can be checked.
+///
+/// # Returns
+///
+/// * `Ok(Vec<(String, String)>)`: The entries, as `get_metadata` returns them.
+/// * `Err(Error::LimitExceeded)`: If the file breaks one of the limits.
+/// * `Err(Error)`: If the file cannot be read or parsed.
+#[cfg(feature = "std-fs")]
+pub fn get_metadata_with_limits<P: AsRef<Path>>(file_path: P, limits: &LoadLimits) -> Result<Vec<(String, String)>> {
+    let file_path = file_path.as_ref();
+    let bytes = read_file_limited(file_path, limits)?;
+    let doc = load_document_limited(&bytes, limits)?;
+    Ok(read_info_entries(&doc))
+}
+
+/// Retrieves the Info entries of a PDF in memory like
+/// [`get_pdf_metadata`](crate::get_pdf_metadata), within `limits`.
+///
+/// # Returns
+///
+/// * `Ok(Vec<(String, String)>)`: The entries, as `get_pdf_metadata` returns them.
+/// * `Err(Error::LimitExceeded)`: If the document breaks one of the limits.
+/// * `Err(Error)`: If the document cannot be parsed.
+pub fn get_pdf_metadata_with_limits(pdf_content: &[u8], limits: &LoadLimits) -> Result<Vec<(String, String)>> {
+    let doc = load_document_limited(pdf_content, limits)?;
+    Ok(read_info_entries(&doc))
+}
+
+/// Reads a file, failing without reading it if it is larger than `limits.max_bytes`.
+/// The read stops past the limit too, in case the file grows meanwhile.
+#[cfg(feature = "std-fs")]
+pub(crate) fn read_file_limited(file_path: &Path, limits: &LoadLimits) -> Result<Vec<u8>> {
+    let file = File::open(file_path)?;
+    let mut bytes = Vec::new();
+    match limits.max_bytes {
+        Some(max) => {
+            if file.metadata()?.len() > max {
+                return Err(Error::LimitExceeded(Limit::Bytes(max)));
+            }
+            file.take(max.saturating_add(1)).read_to_end(&mut bytes)?;
+            check_bytes(bytes.len(), limits)?;
+        }
+        None => {
+            (&file).read_to_end(&mut bytes)?;
+        }
+    }
+    Ok(bytes)
+}
+
+/// Loads a PDF from memory like `load_document_mem`, within `limits`.
+pub(crate) fn load_document_limited(pdf_content: &[u8], limits: &LoadLimits) -> Result<Document> {
+    check_bytes(pdf_content.len(), limits)?;
+    if let Some(max) = limits.max_objects {
+        let mut ids = HashSet::new();
+        for (_, id) in object_headers(pdf_content) {
+            if ids.insert(id) && ids.len() > max {
+                return Err(Error::LimitExceeded(Limit::Objects(max)));
+            }
+        }
+    }
+    let doc = match limits.timeout {
+        None => load_document_mem(pdf_content)?,
+        Some(timeout) => {
+            let owned = pdf_content.to_vec();
+            with_timeout(timeout, move || load_document_mem(&owned)).map_err(|e| match e {
+                Error::TimedOut(after) => Error::LimitExceeded(Limit::Time(after)),
+                other => other,
+            })?
+        }
+    };
+    if let Some(max) = limits.max_objects
+        && doc.objects.len() > max
+    {
+        return Err(Error::LimitExceeded(Limit::Objects(max)));
+    }
+    Ok(doc)
+}
+
+fn check_bytes(len: usize, limits: &LoadLimits) -> Result<()> {
+    match limits.max_bytes {
+        Some(max) if len as u64 > max => Err(Error::LimitExceeded(Limit::Bytes(max))),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{create_minimal_test_pdf, setup_unique_test_dir};
+    use crate::{MetadataEditor, get_metadata, update_metadata_in_place};
+    use std::fs;
+
+    #[test]
+    fn test_load_limits() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("load_limits");
+        let file = test_dir.join("doc.pdf");
+        create_minimal_test_pdf(&file)?;
+        let file_str = file.to_str().unwrap();
+        update_metadata_in_place(file_str, "Title", "Relatório")?;
+        let size = fs::metadata(&file)?.len();
+
+        let generous = LoadLimits { max_bytes: Some(size), max_objects: Some(100), timeout: Some(Duration::from_secs(30)) };
+        assert_eq!(get_metadata_with_limits(file_str, &generous)?, get_metadata(file_str)?);
+        assert_eq!(get_metadata_with_limits(file_str, &LoadLimits::default())?, get_metadata(file_str)?);
+
+        let small = LoadLimits { max_bytes: Some(size - 1), ..LoadLimits::default() };
+        let result = get_metadata_with_limits(file_str, &small);
+        assert!(matches!(result, Err(Error::LimitExceeded(Limit::Bytes(max))) if max == size - 1));
+        let few = LoadLimits { max_objects: Some(1), ..LoadLimits::default() };
+        let bytes = fs::read(&file)?;
+        assert!(matches!(get_pdf_metadata_with_limits(&bytes, &few), Err(Error::LimitExceeded(Limit::Objects(1)))));
+
+        MetadataEditor::open_with_limits(&file, &generous)?.set("Author", "Ana").save_in_place()?;
+        assert!(matches!(MetadataEditor::open_with_limits(&file, &few), Err(Error::LimitExceeded(_))));
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+}
@@ -1,44 +1,413 @@
-use pdf_metadata::{get_metadata, update_metadata_in_place};
-use dialoguer::{Select, Input, Confirm};
-use lopdf::{Document, Object};
-use std::env;
+use pdf_metadata::{add_attachment_with_options, get_signatures, get_pdf_signatures, get_pdf_metadata, get_pdf_metadata_matching, set_metadata_with_options, set_pdf_metadata_with_options, get_permissions, set_permissions, set_metadata_and_encrypt, EncryptionAlgorithm, EncryptionOptions, Permissions, detect_generator, get_document_id, regenerate_document_id, DocumentIdPolicy, extract_attachment, get_metadata, get_metadata_lenient, get_outline, get_page_metadata, list_attachments, set_page_metadata, AttachmentOptions, get_metadata_truncated, remove_metadata_in_place, update_metadata_in_place, update_metadata_in_place_with_options, preview_changes_with_options, BackupPolicy, ConflictPolicy, SaveMode, SetOptions, SignedDocPolicy, XrefStyle, ThumbnailFormat, extract_page_thumbnail, ChecksumAlgorithm, ChecksumStatus, stamp_checksum, verify_checksum, KeyNamespace, KeyMatch, get_metadata_matching, remove_metadata_matching, get_metadata_history};
+use pdf_metadata::report::{self, ReportFormat};
+use pdf_metadata::export::{KeyNaming, OutputFormat};
+use pdf_metadata::xmp::{self, InfoOrXmp};
+use pdf_metadata::batch::{self, BatchOptions};
+use pdf_metadata::progress::ProgressSink;
+use pdf_metadata::{audit, export, pdfa, query, scrub, validate, get_viewer_settings, set_document_title, set_viewer_settings, PageLayout, PageMode, ViewerSettings};
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand};
+use dialoguer::{Select, Input, Confirm, MultiSelect};
+use std::path::Path;
 use std::process;
+use std::sync::Arc;
 use std::error::Error;
-use std::fs;
-use chrono::Local;
+use std::io::{Read, Write};
+
+#[macro_use]
+mod i18n;
+mod journal;
+
+use i18n::Lang;
+use journal::Change;
+
+// Os textos de --help ficam nos catálogos de i18n, aplicados por i18n::localize.
+#[derive(Parser)]
+#[command(name = "pdf_metadata", version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    pdf_paths: Vec<String>,
+
+    #[arg(long, short, global = true)]
+    quiet: bool,
+
+    #[arg(long, global = true, conflicts_with = "quiet")]
+    errors_json: bool,
+
+    #[arg(long, global = true)]
+    lang: Option<Lang>,
+}
+
+/// Códigos de saída dos subcomandos. Erros de uso saem com 2, como os do clap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ExitStatus {
+    /// Qualquer outra falha, inclusive verificações que não passaram.
+    Failure = 1,
+    FileNotFound = 3,
+    ParseError = 4,
+    Encrypted = 5,
+    KeyNotFound = 6,
+    WriteFailed = 7,
+}
+
+impl ExitStatus {
+    /// Classifica um erro devolvido por um subcomando.
+    fn of(error: &(dyn Error + 'static)) -> ExitStatus {
+        if let Some(error) = error.downcast_ref::<pdf_metadata::Error>() {
+            return match error {
+                pdf_metadata::Error::Io(io) if io.kind() == std::io::ErrorKind::NotFound => ExitStatus::FileNotFound,
+                pdf_metadata::Error::Parse(_) => ExitStatus::ParseError,
+                pdf_metadata::Error::Encrypted | pdf_metadata::Error::IncorrectPassword => ExitStatus::Encrypted,
+                pdf_metadata::Error::Write { .. } => ExitStatus::WriteFailed,
+                _ => ExitStatus::Failure,
+            };
+        }
+        match error.downcast_ref::<std::io::Error>() {
+            Some(io) if io.kind() == std::io::ErrorKind::NotFound => ExitStatus::FileNotFound,
+            _ if error.is::<KeyNotFound>() => ExitStatus::KeyNotFound,
+            _ => ExitStatus::Failure,
+        }
+    }
+
+    /// O nome estável do erro em `--errors-json`.
+    fn name(self) -> &'static str {
+        match self {
+            ExitStatus::Failure => "failure",
+            ExitStatus::FileNotFound => "file_not_found",
+            ExitStatus::ParseError => "parse_error",
+            ExitStatus::Encrypted => "encrypted",
+            ExitStatus::KeyNotFound => "key_not_found",
+            ExitStatus::WriteFailed => "write_failed",
+        }
+    }
+}
+
+/// A chave pedida não existe no documento.
+#[derive(Debug)]
+struct KeyNotFound(String);
+
+impl std::fmt::Display for KeyNotFound {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&tr!(key_not_found, self.0))
+    }
+}
+
+impl Error for KeyNotFound {}
+
+#[derive(Subcommand)]
+enum Command {
+    Get {
+        file: String,
+        key: Option<String>,
+        #[arg(long, default_value = "plain")]
+        format: OutputFormat,
+        #[arg(long)]
+        sorted: bool,
+        #[arg(long)]
+        ignore_case: bool,
+    },
+    Set {
+        file: String,
+        key: String,
+        value: String,
+        #[arg(long, short)]
+        output: Option<String>,
+        #[arg(long)]
+        keep_mod_date: bool,
+        #[arg(long)]
+        preserve_mtime: bool,
+        #[arg(long)]
+        backup: bool,
+        #[arg(long, requires = "backup", default_value_t = 0)]
+        keep_backups: usize,
+        #[arg(long)]
+        dry_run: bool,
+        #[arg(long)]
+        strict: bool,
+        #[arg(long, default_value = "overwrite")]
+        if_exists: ConflictPolicy,
+        #[arg(long)]
+        separator: Option<String>,
+        #[arg(long, default_value = "keep")]
+        id: DocumentIdPolicy,
+        #[arg(long, default_value = "rewrite")]
+        signed: SignedDocPolicy,
+        #[arg(long)]
+        preserve: bool,
+        #[arg(long, default_value = "input")]
+        xref: XrefStyle,
+        #[arg(long)]
+        history: bool,
+        #[arg(long)]
+        prefix: Option<String>,
+        #[arg(long)]
+        ignore_case: bool,
+        #[arg(long, requires = "ignore_case")]
+        merge_duplicates: bool,
+    },
+    Validate {
+        file: String,
+    },
+    Audit {
+        file: String,
+        #[arg(long)]
+        repair: bool,
+    },
+    Delete {
+        file: String,
+        key: String,
+        #[arg(long)]
+        ignore_case: bool,
+    },
+    List {
+        file: String,
+        #[arg(long, default_value = "plain")]
+        format: OutputFormat,
+        #[arg(long, conflicts_with = "format")]
+        json: bool,
+        #[arg(long, default_value = "pdf")]
+        naming: KeyNaming,
+        #[arg(long)]
+        lenient: bool,
+        #[arg(long)]
+        sorted: bool,
+    },
+    Scrub {
+        file: String,
+    },
+    Flatten {
+        file: String,
+        output: String,
+    },
+    Report {
+        path: String,
+        #[arg(long, default_value = "markdown")]
+        format: ReportFormat,
+        #[arg(long)]
+        recursive: bool,
+    },
+    Find {
+        dir: String,
+        query: query::Query,
+    },
+    Csv {
+        dir: String,
+        output: String,
+        #[arg(long, value_delimiter = ',')]
+        fields: Vec<String>,
+    },
+    Id {
+        file: String,
+        #[arg(long)]
+        regenerate: bool,
+    },
+    Generator {
+        file: String,
+    },
+    Pdfa {
+        file: String,
+    },
+    Reconcile {
+        file: String,
+        #[arg(long, default_value = "info")]
+        from: InfoOrXmp,
+    },
+    Lang {
+        file: String,
+        lang: Option<String>,
+        #[arg(requires = "lang")]
+        value: Option<String>,
+        #[arg(long)]
+        description: bool,
+    },
+    Checksum {
+        file: String,
+        #[arg(long)]
+        stamp: bool,
+        #[arg(long, default_value = "sha256")]
+        algorithm: ChecksumAlgorithm,
+    },
+    Namespace {
+        file: String,
+        prefix: String,
+        name: Option<String>,
+        #[arg(requires = "name", conflicts_with = "remove")]
+        value: Option<String>,
+        #[arg(long, requires = "name")]
+        remove: bool,
+        #[arg(long, conflicts_with_all = ["name", "remove"])]
+        clear: bool,
+    },
+    XmpIds {
+        file: String,
+    },
+    Attachments {
+        file: String,
+    },
+    Outline {
+        file: String,
+    },
+    PageInfo {
+        file: String,
+        page: u32,
+    },
+    Thumbnail {
+        file: String,
+        output: String,
+        #[arg(long, default_value_t = 1)]
+        page: u32,
+        #[arg(long, default_value_t = 0)]
+        max_px: u32,
+    },
+    PageSet {
+        file: String,
+        page: u32,
+        application: String,
+        key: String,
+        value: String,
+    },
+    Viewer {
+        file: String,
+        #[arg(long)]
+        lang: Option<String>,
+        #[arg(long)]
+        page_mode: Option<PageMode>,
+        #[arg(long)]
+        page_layout: Option<PageLayout>,
+        #[arg(long)]
+        display_doc_title: Option<bool>,
+    },
+    Title {
+        file: String,
+        title: String,
+        #[arg(long)]
+        hide_in_window: bool,
+    },
+    Attach {
+        file: String,
+        attachment: String,
+        #[arg(long)]
+        description: Option<String>,
+        #[arg(long)]
+        relationship: Option<String>,
+    },
+    Extract {
+        file: String,
+        name: String,
+        dest: String,
+    },
+    Encrypt {
+        file: String,
+        output: String,
+        #[arg(long, default_value = "")]
+        user_password: String,
+        #[arg(long, default_value = "")]
+        owner_password: String,
+        #[arg(long, default_value = "aes256")]
+        algorithm: EncryptionAlgorithm,
+        #[arg(long)]
+        no_print: bool,
+        #[arg(long)]
+        no_copy: bool,
+        #[arg(long)]
+        no_modify: bool,
+        #[arg(long)]
+        no_annotate: bool,
+    },
+    Revisions {
+        file: String,
+    },
+    Signatures {
+        file: String,
+    },
+    Permissions {
+        file: String,
+        #[arg(long)]
+        owner_password: Option<String>,
+        #[arg(long, default_value = "")]
+        user_password: String,
+        #[arg(long)]
+        no_print: bool,
+        #[arg(long)]
+        no_copy: bool,
+        #[arg(long)]
+        no_modify: bool,
+        #[arg(long)]
+        no_annotate: bool,
+    },
+    #[cfg(feature = "jobs")]
+    Run {
+        job: String,
+    },
+    #[cfg(feature = "text-stats")]
+    TextStats {
+        file: String,
+        #[arg(long)]
+        store: bool,
+    },
+}
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
+    #[cfg(feature = "sandbox")]
+    pdf_metadata::sandbox::run_worker_if_requested();
 
-    if args.len() != 2 {
-        eprintln!("Uso: {} <caminho_para_arquivo.pdf>", args[0]);
-        eprintln!("Exemplo: {} /caminho/para/documento.pdf", args[0]);
-        process::exit(1);
+    // O --help já sai no idioma pedido, então --lang é procurado antes da análise.
+    let help_lang = Lang::from_args(std::env::args_os()).unwrap_or_else(Lang::from_env);
+    let matches = i18n::localize(Cli::command(), help_lang).get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    i18n::set_lang(cli.lang.unwrap_or_else(Lang::from_env));
+
+    if let Some(command) = cli.command {
+        if let Err(e) = run_command(command) {
+            let status = ExitStatus::of(e.as_ref());
+            if cli.errors_json {
+                eprintln!(
+                    "{{\"error\":\"{}\",\"code\":{},\"message\":{}}}",
+                    status.name(),
+                    status as i32,
+                    export::json_string(&e.to_string())
+                );
+            } else if !cli.quiet {
+                eprintln!("{}", tr!(error, e));
+            }
+            process::exit(status as i32);
+        }
+        return;
     }
 
-    let pdf_path = &args[1];
-    
-    if !std::path::Path::new(pdf_path).exists() {
-        eprintln!("Erro: Arquivo não encontrado: {}", pdf_path);
+    if cli.pdf_paths.is_empty() {
+        let _ = i18n::localize(Cli::command(), help_lang).print_help();
         process::exit(1);
     }
+    let files = match expand_paths(&cli.pdf_paths) {
+        Ok(files) => files,
+        Err(e) => {
+            eprintln!("{}", tr!(error, e));
+            process::exit(ExitStatus::FileNotFound as i32);
+        }
+    };
+    let mut session = Session { files, current: 0, journal: Vec::new() };
 
-    println!("\n📄 Editor de Metadados PDF");
-    println!("Arquivo: {}", pdf_path);
+    println!("\n{}", tr!(editor_title));
+    if session.files.len() > 1 {
+        println!("{}", tr!(editor_files, session.files.len()));
+    }
+    println!("{}", tr!(editor_file, session.current()));
     println!("{}", "═".repeat(60));
 
     loop {
-        match show_main_menu(pdf_path) {
+        match show_main_menu(&mut session) {
             Ok(should_continue) => {
                 if !should_continue {
                     break;
                 }
             }
             Err(e) => {
-                eprintln!("❌ Erro: {}", e);
+                eprintln!("❌ {}", tr!(error, e));
                 if atty::is(atty::Stream::Stdin) {
                     let retry = Confirm::new()
-                        .with_prompt("Deseja tentar novamente?")
+                        .with_prompt(tr!(retry))
                         .default(true)
                         .interact()
                         .unwrap_or(false);
@@ -46,98 +415,661 @@ fn main() {
                         break;
                     }
                 } else {
-                    eprintln!("Executando em modo não-interativo. Saindo...");
+                    eprintln!("{}", tr!(non_interactive));
                     break;
                 }
             }
         }
     }
     
-    println!("\n👋 Obrigado por usar o Editor de Metadados PDF!");
+    println!("\n{}", tr!(goodbye));
+}
+
+/// As permissões concedidas pelas opções --no-print, --no-copy, --no-modify e --no-annotate.
+fn permissions_allowing(no_print: bool, no_copy: bool, no_modify: bool, no_annotate: bool) -> Permissions {
+    Permissions {
+        print: !no_print,
+        print_high_quality: !no_print,
+        copy: !no_copy,
+        modify: !no_modify,
+        annotate: !no_annotate,
+        fill_forms: !no_annotate,
+        ..Permissions::default()
+    }
+}
+
+/// O nome de arquivo que representa a entrada ou a saída padrão.
+const STDIO: &str = "-";
+
+/// Lê da entrada padrão o PDF recebido por um pipe.
+fn read_stdin() -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut pdf = Vec::new();
+    std::io::stdin().lock().read_to_end(&mut pdf)?;
+    Ok(pdf)
+}
+
+/// Grava `pdf` em `output`, ou na saída padrão quando `output` é "-".
+fn write_output(output: &str, pdf: &[u8]) -> Result<(), Box<dyn Error>> {
+    let written = if output == STDIO {
+        let mut stdout = std::io::stdout().lock();
+        stdout.write_all(pdf).and_then(|()| stdout.flush())
+    } else {
+        std::fs::write(output, pdf)
+    };
+    written.map_err(|source| pdf_metadata::Error::Write { path: output.into(), source })?;
+    Ok(())
 }
 
-fn show_main_menu(pdf_path: &str) -> Result<bool, Box<dyn Error>> {
+/// Executa um subcomando não interativo.
+fn run_command(command: Command) -> Result<(), Box<dyn Error>> {
+    match command {
+        Command::Get { file, key: Some(key), format, ignore_case, .. } => {
+            let key_match = if ignore_case { KeyMatch::CaseInsensitive } else { KeyMatch::Exact };
+            let entry = if file == STDIO {
+                get_pdf_metadata_matching(&read_stdin()?, &key, key_match)?
+            } else {
+                get_metadata_matching(&file, &key, key_match)?
+            };
+            let entry = entry.ok_or(KeyNotFound(key))?;
+            match format {
+                OutputFormat::Plain => println!("{}", entry.1),
+                format => print!("{}", export::render(&[entry], format)),
+            }
+        }
+        Command::Get { file, key: None, format, sorted, .. } => {
+            let mut metadata = if file == STDIO { get_pdf_metadata(&read_stdin()?)? } else { get_metadata(&file)? };
+            if sorted {
+                export::sort_canonical(&mut metadata);
+            }
+            print!("{}", export::render(&metadata, format));
+        }
+        Command::List { file, format, json, naming, lenient, sorted } => {
+            let mut metadata = if lenient { get_metadata_lenient(&file)? } else { get_metadata(&file)? };
+            if sorted {
+                export::sort_canonical(&mut metadata);
+            }
+            let metadata = export::rename_keys(&metadata, naming);
+            let format = if json { OutputFormat::Json } else { format };
+            print!("{}", export::render(&metadata, format));
+        }
+        Command::Set { file, key, value, output, keep_mod_date, preserve_mtime, backup, keep_backups, dry_run, strict, if_exists, separator, id, signed, preserve, xref, history, prefix, ignore_case, merge_duplicates } => {
+            let on_conflict = match (if_exists, separator) {
+                (ConflictPolicy::Append(_), Some(separator)) => ConflictPolicy::Append(separator),
+                (policy, _) => policy,
+            };
+            let options = SetOptions { update_mod_date: !keep_mod_date, preserve_mtime, backup: backup.then_some(BackupPolicy { keep: keep_backups }), strict, on_conflict, document_id: id, on_signed: signed, save_mode: if preserve { SaveMode::Preserve } else { SaveMode::Rewrite }, xref_style: xref, xmp_history: history, key_prefix: prefix, key_match: if ignore_case { KeyMatch::CaseInsensitive } else { KeyMatch::Exact }, merge_case_duplicates: merge_duplicates, ..SetOptions::default() };
+            for warning in validate::validate_entry(&key, &value) {
+                eprintln!("{}", tr!(warning, warning));
+            }
+            if dry_run {
+                if file == STDIO {
+                    return Err(tr!(dry_run_needs_file).into());
+                }
+                for change in preview_changes_with_options(&file, &[(&key, &value)], &options)? {
+                    println!("{}", change);
+                }
+                return Ok(());
+            }
+            let pdf = if file == STDIO { Some(read_stdin()?) } else { None };
+            if signed == SignedDocPolicy::IgnoreAndRewrite && !preserve {
+                let count = match &pdf {
+                    Some(pdf) => get_pdf_signatures(pdf)?.len(),
+                    None => get_signatures(&file)?.len(),
+                };
+                if count > 0 {
+                    eprintln!("{}", tr!(warning, tr!(signatures_invalidated, count)));
+                }
+            }
+            match (pdf, output.as_deref()) {
+                (Some(pdf), output) => {
+                    let updated = set_pdf_metadata_with_options(&pdf, &key, &value, &options)?;
+                    write_output(output.unwrap_or(STDIO), &updated)?;
+                }
+                (None, Some(STDIO)) => {
+                    let updated = set_pdf_metadata_with_options(&std::fs::read(&file)?, &key, &value, &options)?;
+                    write_output(STDIO, &updated)?;
+                }
+                (None, Some(output)) => set_metadata_with_options(&file, output, &key, &value, &options)?,
+                (None, None) => update_metadata_in_place_with_options(&file, &key, &value, &options)?,
+            }
+        }
+        Command::Validate { file } => {
+            let warnings = validate::validate_file(&file)?;
+            for warning in &warnings {
+                println!("{}", warning);
+            }
+            if !warnings.is_empty() {
+                return Err(tr!(warning_count, warnings.len()).into());
+            }
+        }
+        Command::Audit { file, repair } => {
+            let issues = if repair { audit::repair_info(&file, &file)? } else { audit::audit_info(&file)? };
+            for issue in &issues {
+                println!("{}", issue);
+            }
+            if issues.is_empty() {
+                println!("{}", tr!(no_problems));
+            } else if repair {
+                println!("{}", tr!(problems_repaired, issues.len()));
+            }
+        }
+        Command::Delete { file, key, ignore_case: false } => remove_metadata_in_place(&file, &key)?,
+        Command::Delete { file, key, ignore_case: true } => {
+            for key in remove_metadata_matching(&file, &key, KeyMatch::CaseInsensitive)? {
+                println!("{}", tr!(key_removed, key));
+            }
+        }
+        Command::Scrub { file } => {
+            for (key, value) in scrub::scrub_tool_metadata(&file)? {
+                println!("{}: {}", key, value);
+            }
+        }
+        Command::Flatten { file, output } => scrub::flatten_and_strip_history(&file, &output)?,
+        Command::Report { path, format, recursive } => {
+            let path = std::path::Path::new(&path);
+            let reports = if path.is_dir() {
+                let files = batch::collect_pdfs(path, recursive)?;
+                if atty::is(atty::Stream::Stderr) {
+                    let reports = batch::run_with_progress(&files, &BatchOptions::default(), Arc::new(StderrProgress), report::report_file);
+                    eprintln!();
+                    reports
+                } else {
+                    report::report_files(&files)
+                }
+            } else {
+                report::report_files(&[path.to_path_buf()])
+            };
+            print!("{}", report::render(&reports, format));
+        }
+        Command::Find { dir, query } => {
+            for found in query::find_pdfs_with_metadata(&dir, &query)? {
+                println!("{}", found.path.display());
+                for (key, value) in found.entries {
+                    println!("  {}: {}", key, value);
+                }
+            }
+        }
+        Command::Csv { dir, output, fields } => {
+            let fields: Vec<&str> = fields.iter().map(String::as_str).collect();
+            let rows = export::export_directory_csv(&dir, &output, &fields)?;
+            println!("{}", tr!(csv_exported, rows, output));
+        }
+        Command::Id { file, regenerate } => {
+            let id = if regenerate { Some(regenerate_document_id(&file, &file)?) } else { get_document_id(&file)? };
+            match id {
+                Some(id) => println!("{}", id),
+                None => println!("{}", tr!(no_document_id)),
+            }
+        }
+        Command::Generator { file } => {
+            let info = detect_generator(&file)?;
+            match info.version {
+                Some(version) => println!("{} {}", info.generator, version),
+                None => println!("{}", info.generator),
+            }
+            for (label, text) in [("Producer", info.producer), ("Creator", info.creator), ("XMP", info.xmp_toolkit)] {
+                if let Some(text) = text {
+                    println!("  {}: {}", label, text);
+                }
+            }
+        }
+        Command::Pdfa { file } => {
+            let report = pdfa::check_pdfa_metadata(&file)?;
+            println!("{}", report.level().unwrap_or_else(|| tr!(not_pdfa).to_string()));
+            for issue in &report.issues {
+                println!("  {}", issue);
+            }
+            if !report.is_compliant() {
+                return Err(tr!(pdfa_not_compliant).into());
+            }
+        }
+        Command::Reconcile { file, from } => {
+            for key in xmp::reconcile_metadata(&file, &file, from)? {
+                println!("{}", key);
+            }
+        }
+        Command::Lang { file, lang, value, description } => match (lang, value) {
+            (Some(lang), Some(value)) if description => xmp::set_xmp_description_lang(&file, &lang, &value)?,
+            (Some(lang), Some(value)) => xmp::set_xmp_title_lang(&file, &lang, &value)?,
+            (lang, _) => {
+                let variants = if description { xmp::get_xmp_description_langs(&file)? } else { xmp::get_xmp_title_langs(&file)? };
+                for (variant, value) in variants {
+                    if lang.as_deref().is_none_or(|lang| lang.eq_ignore_ascii_case(&variant)) {
+                        println!("{}\t{}", variant, value);
+                    }
+                }
+            }
+        },
+        Command::Checksum { file, stamp, algorithm } => {
+            if stamp {
+                println!("{}", stamp_checksum(&file, &file, algorithm)?);
+            } else {
+                match verify_checksum(&file)? {
+                    ChecksumStatus::Valid => println!("{}", tr!(checksum_valid)),
+                    ChecksumStatus::Missing => return Err(tr!(checksum_missing).into()),
+                    ChecksumStatus::Mismatch => return Err(tr!(checksum_mismatch).into()),
+                }
+            }
+        }
+        Command::Namespace { file, prefix, name, value, remove, clear } => {
+            let namespace = KeyNamespace::new(&prefix)?;
+            match (name, value) {
+                _ if clear => {
+                    for name in namespace.clear(&file)? {
+                        println!("{}", tr!(key_removed, namespace.key(&name)));
+                    }
+                }
+                (Some(name), _) if remove => {
+                    if !namespace.remove(&file, &name)? {
+                        return Err(KeyNotFound(namespace.key(&name)).into());
+                    }
+                }
+                (Some(name), Some(value)) => namespace.set(&file, &name, &value)?,
+                (name, _) => {
+                    for (key, value) in namespace.list(&file)? {
+                        if name.as_deref().is_none_or(|name| name == key) {
+                            println!("{}: {}", key, value);
+                        }
+                    }
+                }
+            }
+        }
+        Command::XmpIds { file } => {
+            let ids = xmp::ensure_xmp_identifiers(&file, &file)?;
+            println!("DocumentID: {}", ids.document_id);
+            println!("InstanceID: {}", ids.instance_id);
+        }
+        Command::Attachments { file } => {
+            for attachment in list_attachments(&file)? {
+                let size = attachment.size.map(|size| format!("{} bytes", size)).unwrap_or_default();
+                let mime_type = attachment.mime_type.unwrap_or_default();
+                println!("{}\t{}\t{}\t{}", attachment.name, size, mime_type, attachment.description.unwrap_or_default());
+            }
+        }
+        Command::Outline { file } => {
+            for entry in get_outline(&file)? {
+                let page = entry.page.map(|page| format!("\tp. {}", page)).unwrap_or_default();
+                println!("{}{}{}", "  ".repeat(entry.level - 1), entry.title, page);
+            }
+        }
+        Command::Thumbnail { file, output, page, max_px } => {
+            let Some(thumbnail) = extract_page_thumbnail(&file, page, max_px)? else {
+                return Err(tr!(no_thumbnail, page).into());
+            };
+            // Amostras sem compressão são gravadas como PPM (RGB) ou PGM (cinza).
+            let header = match thumbnail.format {
+                ThumbnailFormat::Jpeg => String::new(),
+                ThumbnailFormat::Rgb => format!("P6\n{} {}\n255\n", thumbnail.width, thumbnail.height),
+                ThumbnailFormat::Gray => format!("P5\n{} {}\n255\n", thumbnail.width, thumbnail.height),
+            };
+            std::fs::write(&output, [header.into_bytes(), thumbnail.data].concat())?;
+        }
+        Command::PageInfo { file, page } => {
+            for piece in get_page_metadata(&file, page)? {
+                println!("{}\t{}", piece.application, piece.last_modified.unwrap_or_default());
+                for (key, value) in piece.private {
+                    println!("  {}: {}", key, value);
+                }
+            }
+        }
+        Command::PageSet { file, page, application, key, value } => {
+            set_page_metadata(&file, &file, page, &application, &[(key, value)])?;
+        }
+        Command::Viewer { file, lang, page_mode, page_layout, display_doc_title } => {
+            let settings = ViewerSettings { lang, page_mode, page_layout, display_doc_title };
+            if settings == ViewerSettings::default() {
+                let settings = get_viewer_settings(&file)?;
+                println!("Lang: {}", settings.lang.unwrap_or_default());
+                println!("PageMode: {}", settings.page_mode.map(PageMode::as_name).unwrap_or_default());
+                println!("PageLayout: {}", settings.page_layout.map(PageLayout::as_name).unwrap_or_default());
+                println!("DisplayDocTitle: {}", settings.display_doc_title.map(|b| b.to_string()).unwrap_or_default());
+            } else {
+                set_viewer_settings(&file, &file, &settings)?;
+            }
+        }
+        Command::Title { file, title, hide_in_window } => {
+            set_document_title(&file, &file, &title, !hide_in_window)?;
+        }
+        Command::Attach { file, attachment, description, relationship } => {
+            let options = AttachmentOptions { description, relationship, ..AttachmentOptions::default() };
+            add_attachment_with_options(&file, &file, &attachment, &options)?;
+        }
+        Command::Extract { file, name, dest } => {
+            let size = extract_attachment(&file, &name, &dest)?;
+            println!("{} bytes -> {}", size, dest);
+        }
+        Command::Encrypt { file, output, user_password, owner_password, algorithm, no_print, no_copy, no_modify, no_annotate } => {
+            let permissions = permissions_allowing(no_print, no_copy, no_modify, no_annotate);
+            let options = EncryptionOptions { user_password, owner_password, permissions, algorithm };
+            set_metadata_and_encrypt(&file, &output, &[] as &[(&str, &str)], &options)?;
+        }
+        Command::Revisions { file } => {
+            for (revision, entries) in get_metadata_history(&file)? {
+                let date = revision.mod_date.map(|date| format!(", {}", date.to_rfc3339())).unwrap_or_default();
+                println!("{}", tr!(revision, revision.number, revision.length, date));
+                for (key, value) in entries {
+                    println!("  {}: {}", key, value);
+                }
+            }
+        }
+        Command::Signatures { file } => {
+            let signatures = get_signatures(&file)?;
+            if signatures.is_empty() {
+                println!("{}", tr!(no_signatures));
+            }
+            for signature in signatures {
+                println!("{}", signature.field_name);
+                for (label, text) in [
+                    (tr!(signer), signature.signer),
+                    (tr!(signed_at), signature.signed_at.map(|date| date.to_rfc3339())),
+                    (tr!(reason), signature.reason),
+                    (tr!(location), signature.location),
+                    (tr!(signature_format), signature.sub_filter),
+                ] {
+                    if let Some(text) = text {
+                        println!("  {}: {}", label, text);
+                    }
+                }
+                if !signature.covers_whole_file {
+                    println!("  {}", tr!(signature_partial));
+                }
+            }
+        }
+        Command::Permissions { file, owner_password, user_password, no_print, no_copy, no_modify, no_annotate } => {
+            if let Some(owner_password) = owner_password {
+                let permissions = permissions_allowing(no_print, no_copy, no_modify, no_annotate);
+                set_permissions(&file, &file, &permissions, &owner_password, &user_password)?;
+            }
+            let permissions = get_permissions(&file)?;
+            for (label, allowed) in [
+                (tr!(permission_print), permissions.print),
+                (tr!(permission_print_high_quality), permissions.print_high_quality),
+                (tr!(permission_modify), permissions.modify),
+                (tr!(permission_copy), permissions.copy),
+                (tr!(permission_annotate), permissions.annotate),
+                (tr!(permission_fill_forms), permissions.fill_forms),
+                (tr!(permission_accessibility), permissions.copy_for_accessibility),
+                (tr!(permission_assemble), permissions.assemble),
+            ] {
+                println!("{}: {}", label, if allowed { tr!(yes) } else { tr!(no) });
+            }
+        }
+        #[cfg(feature = "jobs")]
+        Command::Run { job } => {
+            let outcomes = pdf_metadata::job::Job::load(&job)?.run()?;
+            let failed = outcomes.iter().filter(|outcome| outcome.result.is_err()).count();
+            for outcome in &outcomes {
+                match &outcome.result {
+                    Ok(()) => println!("{}", tr!(job_ok, outcome.path.display(), outcome.output.display())),
+                    Err(e) => println!("{}", tr!(job_failed, outcome.path.display(), e)),
+                }
+            }
+            if failed > 0 {
+                return Err(tr!(jobs_failed, failed, outcomes.len()).into());
+            }
+        }
+        #[cfg(feature = "text-stats")]
+        Command::TextStats { file, store } => {
+            let stats = if store {
+                pdf_metadata::text_stats::compute_and_store_stats(&file, &file)?
+            } else {
+                pdf_metadata::text_stats::compute_text_stats(&file)?
+            };
+            println!("{}", tr!(stats_pages, stats.pages));
+            println!("{}", tr!(stats_words, stats.words));
+            println!("{}", tr!(stats_characters, stats.characters));
+            println!("{}", tr!(stats_characters_with_spaces, stats.characters_with_spaces));
+        }
+    }
+    Ok(())
+}
+
+/// Mostra em stderr quantos arquivos já foram processados.
+struct StderrProgress;
+
+impl ProgressSink for StderrProgress {
+    fn file_finished(&self, file: &Path, finished: usize, total: usize, _succeeded: bool) {
+        let name = file.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+        eprint!("\r\x1b[K[{}/{}] {}", finished, total, name);
+    }
+}
+
+/// Os arquivos abertos no modo interativo, o que está sendo editado e as
+/// alterações feitas até agora, da mais antiga para a mais recente.
+struct Session {
+    files: Vec<String>,
+    current: usize,
+    journal: Vec<Change>,
+}
+
+impl Session {
+    fn current(&self) -> &str {
+        &self.files[self.current]
+    }
+}
+
+/// Expande os padrões glob entre `args`, mantendo a ordem e sem repetir arquivos.
+fn expand_paths(args: &[String]) -> Result<Vec<String>, Box<dyn Error>> {
+    let mut files = Vec::new();
+    for arg in args {
+        let matches = if arg.contains(['*', '?', '[']) {
+            let mut matches = Vec::new();
+            for path in glob::glob(arg)? {
+                matches.push(path?.to_string_lossy().into_owned());
+            }
+            matches
+        } else if Path::new(arg).exists() {
+            vec![arg.clone()]
+        } else {
+            Vec::new()
+        };
+        if matches.is_empty() {
+            return Err(tr!(file_not_found, arg).into());
+        }
+        for file in matches {
+            if !files.contains(&file) {
+                files.push(file);
+            }
+        }
+    }
+    Ok(files)
+}
+
+/// Ações do menu principal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MenuAction {
+    List,
+    Create,
+    Edit,
+    Rename,
+    Delete,
+    Undo,
+    SwitchFile,
+    ApplyToFiles,
+    Quit,
+}
+
+impl MenuAction {
+    fn label(self) -> &'static str {
+        match self {
+            MenuAction::List => tr!(menu_list),
+            MenuAction::Create => tr!(menu_create),
+            MenuAction::Edit => tr!(menu_edit),
+            MenuAction::Rename => tr!(menu_rename),
+            MenuAction::Delete => tr!(menu_delete),
+            MenuAction::Undo => tr!(menu_undo),
+            MenuAction::SwitchFile => tr!(menu_switch),
+            MenuAction::ApplyToFiles => tr!(menu_apply),
+            MenuAction::Quit => tr!(menu_quit),
+        }
+    }
+}
+
+fn show_main_menu(session: &mut Session) -> Result<bool, Box<dyn Error>> {
     // Verifica se está rodando em terminal interativo
     if !atty::is(atty::Stream::Stdin) {
         // Se não for interativo, apenas lista os metadados e sai
-        list_metadata(pdf_path)?;
+        for file in &session.files {
+            if session.files.len() > 1 {
+                println!("\n{}", tr!(editor_file, file));
+            }
+            list_metadata(file)?;
+        }
         return Ok(false);
     }
 
-    let options = vec![
-        "📋 Listar todos os metadados",
-        "➕ Criar novo metadado", 
-        "✏️  Editar valor de metadado",
-        "🔄 Alterar chave de metadado",
-        "🗑️  Excluir metadado",
-        "🚪 Sair"
-    ];
+    let mut actions = vec![MenuAction::List, MenuAction::Create, MenuAction::Edit, MenuAction::Rename, MenuAction::Delete];
+    if !session.journal.is_empty() {
+        actions.push(MenuAction::Undo);
+    }
+    if session.files.len() > 1 {
+        actions.extend([MenuAction::SwitchFile, MenuAction::ApplyToFiles]);
+        println!("\n{}", tr!(editor_file, session.current()));
+    }
+    actions.push(MenuAction::Quit);
+    let labels: Vec<&str> = actions.iter().map(|action| action.label()).collect();
 
     let selection = Select::new()
-        .with_prompt("\nSelecione uma opção:")
-        .items(&options)
+        .with_prompt(tr!(menu_prompt))
+        .items(&labels)
         .default(0)
         .interact()?;
 
-    match selection {
-        0 => {
+    let pdf_path = session.current();
+    let change = match actions[selection] {
+        MenuAction::List => {
             list_metadata(pdf_path)?;
             wait_for_enter();
+            None
         }
-        1 => create_metadata(pdf_path)?,
-        2 => edit_metadata_value(pdf_path)?,
-        3 => change_metadata_key(pdf_path)?,
-        4 => delete_metadata(pdf_path)?,
-        5 => return Ok(false),
-        _ => unreachable!()
-    }
+        MenuAction::Create => create_metadata(pdf_path)?,
+        MenuAction::Edit => edit_metadata_value(pdf_path)?,
+        MenuAction::Rename => change_metadata_key(pdf_path)?,
+        MenuAction::Delete => delete_metadata(pdf_path)?,
+        MenuAction::Undo => {
+            undo_last_change(session)?;
+            None
+        }
+        MenuAction::SwitchFile => {
+            switch_file(session)?;
+            None
+        }
+        MenuAction::ApplyToFiles => apply_to_files(session)?,
+        MenuAction::Quit => return Ok(false),
+    };
+    session.journal.extend(change);
     
     Ok(true)
 }
 
+fn undo_last_change(session: &mut Session) -> Result<(), Box<dyn Error>> {
+    let Some(change) = session.journal.pop() else {
+        return Ok(());
+    };
+    if let Err(e) = change.undo() {
+        // Continua no diário para que se possa tentar de novo.
+        session.journal.push(change);
+        return Err(e);
+    }
+    println!("{}", tr!(undone, change.describe()));
+    Ok(())
+}
+
+fn switch_file(session: &mut Session) -> Result<(), Box<dyn Error>> {
+    session.current = Select::new()
+        .with_prompt(tr!(switch_prompt))
+        .items(&session.files)
+        .default(session.current)
+        .interact()?;
+    Ok(())
+}
+
+fn apply_to_files(session: &Session) -> Result<Option<Change>, Box<dyn Error>> {
+    println!("\n{}", tr!(apply_heading));
+    println!("{}", "─".repeat(35));
+
+    let selected = MultiSelect::new()
+        .with_prompt(tr!(select_files))
+        .items(&session.files)
+        .defaults(&vec![true; session.files.len()])
+        .interact()?;
+    if selected.is_empty() {
+        println!("{}", tr!(no_files_selected));
+        return Ok(None);
+    }
+
+    let key: String = loop {
+        let input_key = Input::<String>::new()
+            .with_prompt(tr!(key_prompt))
+            .interact_text()?;
+        if input_key.trim().is_empty() {
+            println!("{}", tr!(empty_key));
+            continue;
+        }
+        break input_key;
+    };
+    let value = Input::<String>::new()
+        .with_prompt(tr!(value_prompt))
+        .allow_empty(true)
+        .interact_text()?;
+
+    let files: Vec<&str> = selected.iter().map(|&index| session.files[index].as_str()).collect();
+    let (results, change) = journal::apply_to_files(&files, &key, &value);
+    for (file, result) in &results {
+        match result {
+            Ok(()) => println!("✅ {}", file),
+            Err(e) => println!("❌ {}: {}", file, e),
+        }
+    }
+    let written = results.iter().filter(|(_, result)| result.is_ok()).count();
+    println!("\n{}", tr!(applied, key, written, files.len()));
+
+    Ok(change)
+}
+
 fn list_metadata(pdf_path: &str) -> Result<(), Box<dyn Error>> {
-    println!("\n📋 Metadados do PDF:");
+    println!("\n{}", tr!(list_heading));
     println!("{}", "─".repeat(50));
     
-    let metadata = get_metadata(pdf_path)?;
+    let metadata = get_metadata_truncated(pdf_path, 60)?;
     
     if metadata.is_empty() {
-        println!("ℹ️  Nenhum metadado encontrado.");
+        println!("{}", tr!(no_metadata));
         return Ok(());
     }
     
-    for (i, (key, value)) in metadata.iter().enumerate() {
-        let display_value = if value.len() > 60 {
-            format!("{}...", &value[..57])
+    for (i, entry) in metadata.iter().enumerate() {
+        let display_value = if entry.is_truncated() {
+            format!("{}...", entry.value.chars().take(57).collect::<String>())
         } else {
-            value.clone()
+            entry.value.clone()
         };
         
-        println!("{:2}. {:<20}: {}", i + 1, key, display_value);
+        println!("{:2}. {:<20}: {}", i + 1, entry.key, display_value);
     }
     
-    println!("\n📊 Total: {} metadados", metadata.len());
+    println!("\n{}", tr!(total, metadata.len()));
     Ok(())
 }
 
-fn create_metadata(pdf_path: &str) -> Result<(), Box<dyn Error>> {
-    println!("\n➕ Criar Novo Metadado");
+fn create_metadata(pdf_path: &str) -> Result<Option<Change>, Box<dyn Error>> {
+    println!("\n{}", tr!(create_heading));
     println!("{}", "─".repeat(30));
     
     let existing_metadata = get_metadata(pdf_path)?;
     
     let key: String = loop {
         let input_key = Input::<String>::new()
-            .with_prompt("Chave do metadado")
+            .with_prompt(tr!(key_prompt))
             .interact_text()?;
             
         if input_key.trim().is_empty() {
-            println!("⚠️  A chave não pode estar vazia.");
+            println!("{}", tr!(empty_key));
             continue;
         }
         
         if existing_metadata.iter().any(|(k, _)| k == &input_key) {
-            println!("⚠️  A chave '{}' já existe. Use a opção de editar.", input_key);
+            println!("{}", tr!(key_exists_use_edit, input_key));
             continue;
         }
         
@@ -145,267 +1077,175 @@ fn create_metadata(pdf_path: &str) -> Result<(), Box<dyn Error>> {
     };
     
     let value = Input::<String>::new()
-        .with_prompt("Valor do metadado")
+        .with_prompt(tr!(value_prompt))
         .allow_empty(true)
         .interact_text()?;
-        
-    let has_accents = value.chars().any(|c| !c.is_ascii());
-    let use_base64 = if has_accents {
-        Confirm::new()
-            .with_prompt("Detectados caracteres não-ASCII. Usar codificação BASE64?")
-            .default(true)
-            .interact()?
-    } else {
-        false
-    };
-    
-    let final_value = if use_base64 {
-        encode_to_base64_utf16be(&value)
-    } else {
-        value
-    };
     
-    update_metadata_in_place(pdf_path, &key, &final_value)?;
-    println!("✅ Metadado '{}' criado com sucesso!", key);
+    let change = journal::record(&[pdf_path], &[&key], || Ok(update_metadata_in_place(pdf_path, &key, &value)?))?;
+    println!("{}", tr!(created, key));
     
-    Ok(())
+    Ok(Some(change))
 }
 
-fn edit_metadata_value(pdf_path: &str) -> Result<(), Box<dyn Error>> {
-    println!("\n✏️  Editar Valor de Metadado");
+fn edit_metadata_value(pdf_path: &str) -> Result<Option<Change>, Box<dyn Error>> {
+    println!("\n{}", tr!(edit_heading));
     println!("{}", "─".repeat(35));
     
     let metadata = get_metadata(pdf_path)?;
     
     if metadata.is_empty() {
-        println!("ℹ️  Nenhum metadado encontrado para editar.");
-        return Ok(());
+        println!("{}", tr!(nothing_to_edit));
+        return Ok(None);
     }
     
     let keys: Vec<String> = metadata.iter().map(|(k, _)| k.clone()).collect();
     
     let selection = Select::new()
-        .with_prompt("Selecione o metadado para editar")
+        .with_prompt(tr!(select_to_edit))
         .items(&keys)
         .interact()?;
         
     let selected_key = &keys[selection];
     let current_value = &metadata[selection].1;
     
-    println!("\nChave: {}", selected_key);
-    println!("Valor atual: {}", current_value);
+    println!("\n{}", tr!(key_label, selected_key));
+    println!("{}", tr!(current_value, current_value));
     
     let new_value = Input::<String>::new()
-        .with_prompt("Novo valor")
+        .with_prompt(tr!(new_value_prompt))
         .with_initial_text(current_value)
         .interact_text()?;
         
-    let has_accents = new_value.chars().any(|c| !c.is_ascii());
-    let use_base64 = if has_accents {
-        Confirm::new()
-            .with_prompt("Detectados caracteres não-ASCII. Usar codificação BASE64?")
-            .default(true)
-            .interact()?
-    } else {
-        false
-    };
+    let change = journal::record(&[pdf_path], &[selected_key], || {
+        Ok(update_metadata_in_place(pdf_path, selected_key, &new_value)?)
+    })?;
+    println!("{}", tr!(value_updated, selected_key));
     
-    let final_value = if use_base64 {
-        encode_to_base64_utf16be(&new_value)
-    } else {
-        new_value
-    };
-        
-    update_metadata_in_place(pdf_path, selected_key, &final_value)?;
-    println!("✅ Valor do metadado '{}' atualizado com sucesso!", selected_key);
-    
-    Ok(())
+    Ok(Some(change))
 }
 
-fn change_metadata_key(pdf_path: &str) -> Result<(), Box<dyn Error>> {
-    println!("\n🔄 Alterar Chave de Metadado");
+fn change_metadata_key(pdf_path: &str) -> Result<Option<Change>, Box<dyn Error>> {
+    println!("\n{}", tr!(rename_heading));
     println!("{}", "─".repeat(35));
     
     let metadata = get_metadata(pdf_path)?;
     
     if metadata.is_empty() {
-        println!("ℹ️  Nenhum metadado encontrado para alterar.");
-        return Ok(());
+        println!("{}", tr!(nothing_to_rename));
+        return Ok(None);
     }
     
     let keys: Vec<String> = metadata.iter().map(|(k, _)| k.clone()).collect();
     
     let selection = Select::new()
-        .with_prompt("Selecione o metadado para alterar a chave")
+        .with_prompt(tr!(select_to_rename))
         .items(&keys)
         .interact()?;
         
     let old_key = &keys[selection];
     let value = &metadata[selection].1;
     
-    println!("\nChave atual: {}", old_key);
+    println!("\n{}", tr!(current_key, old_key));
     
     let new_key: String = loop {
         let input_key = Input::<String>::new()
-            .with_prompt("Nova chave")
+            .with_prompt(tr!(new_key_prompt))
             .with_initial_text(old_key)
             .interact_text()?;
             
         if input_key.trim().is_empty() {
-            println!("⚠️  A chave não pode estar vazia.");
+            println!("{}", tr!(empty_key));
             continue;
         }
         
         if input_key == *old_key {
-            println!("⚠️  A nova chave deve ser diferente da atual.");
+            println!("{}", tr!(same_key));
             continue;
         }
         
         if keys.contains(&input_key) {
-            println!("⚠️  A chave '{}' já existe.", input_key);
+            println!("{}", tr!(key_exists, input_key));
             continue;
         }
         
         break input_key;
     };
     
-    // Primeiro adiciona a nova chave
-    update_metadata_in_place(pdf_path, &new_key, value)?;
-    
-    // Depois remove a chave antiga
-    remove_metadata_key(pdf_path, old_key)?;
+    let change = journal::record(&[pdf_path], &[&new_key, old_key], || {
+        // Primeiro adiciona a nova chave
+        update_metadata_in_place(pdf_path, &new_key, value)?;
+
+        // Depois remove a chave antiga
+        Ok(remove_metadata_in_place(pdf_path, old_key)?)
+    })?;
     
-    println!("✅ Chave alterada de '{}' para '{}' com sucesso!", old_key, new_key);
+    println!("{}", tr!(key_renamed, old_key, new_key));
     
-    Ok(())
+    Ok(Some(change))
 }
 
-fn delete_metadata(pdf_path: &str) -> Result<(), Box<dyn Error>> {
-    println!("\n🗑️  Excluir Metadado");
+fn delete_metadata(pdf_path: &str) -> Result<Option<Change>, Box<dyn Error>> {
+    println!("\n{}", tr!(delete_heading));
     println!("{}", "─".repeat(25));
     
     let metadata = get_metadata(pdf_path)?;
     
     if metadata.is_empty() {
-        println!("ℹ️  Nenhum metadado encontrado para excluir.");
-        return Ok(());
+        println!("{}", tr!(nothing_to_delete));
+        return Ok(None);
     }
     
     let keys: Vec<String> = metadata.iter().map(|(k, _)| k.clone()).collect();
     
     let selection = Select::new()
-        .with_prompt("Selecione o metadado para excluir")
+        .with_prompt(tr!(select_to_delete))
         .items(&keys)
         .interact()?;
         
     let selected_key = &keys[selection];
     let selected_value = &metadata[selection].1;
     
-    println!("\nChave: {}", selected_key);
-    println!("Valor: {}", selected_value);
+    println!("\n{}", tr!(key_label, selected_key));
+    println!("{}", tr!(value_label, selected_value));
     
     let confirm = Confirm::new()
-        .with_prompt("Tem certeza que deseja excluir este metadado?")
+        .with_prompt(tr!(confirm_delete))
         .default(false)
         .interact()?;
         
-    if confirm {
-        remove_metadata_key(pdf_path, selected_key)?;
-        println!("✅ Metadado '{}' excluído com sucesso!", selected_key);
-    } else {
-        println!("❌ Operação cancelada.");
+    if !confirm {
+        println!("{}", tr!(cancelled));
+        return Ok(None);
     }
+    let change = journal::record(&[pdf_path], &[selected_key], || Ok(remove_metadata_in_place(pdf_path, selected_key)?))?;
+    println!("{}", tr!(deleted, selected_key));
     
-    Ok(())
-}
-
-fn remove_metadata_key(pdf_path: &str, key_to_remove: &str) -> Result<(), Box<dyn Error>> {
-    let mut doc = Document::load(pdf_path)?;
-    
-    let info_dict_id = doc
-        .trailer
-        .get(b"Info")
-        .and_then(|obj_ref| obj_ref.as_reference())
-        .map_err(|_| "PDF não possui dicionário Info")?;
-        
-    let info_dict_obj = doc.get_object_mut(info_dict_id)?;
-    let info_dict = info_dict_obj.as_dict_mut()?;
-    
-    info_dict.remove(key_to_remove.as_bytes());
-    
-    // Atualiza ModDate
-    let now = Local::now();
-    let offset = now.offset();
-    let offset_hours = offset.local_minus_utc() / 3600;
-    let offset_minutes = (offset.local_minus_utc().abs() % 3600) / 60;
-    let offset_sign = if offset.local_minus_utc() >= 0 { '+' } else { '-' };
-    let pdf_date_formatted = format!(
-        "D:{}{}{:02}'{:02}'",
-        now.format("%Y%m%d%H%M%S"),
-        offset_sign,
-        offset_hours.abs(),
-        offset_minutes
-    );
-    info_dict.set("ModDate", Object::string_literal(pdf_date_formatted));
-    
-    // Salva usando método temporário como nas outras funções
-    let original_path = std::path::Path::new(pdf_path);
-    let parent_dir = original_path.parent().ok_or("Não foi possível determinar diretório pai")?;
-    let timestamp = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)?
-        .as_micros();
-    let temp_filename = format!("temp_remove_{}_{}.pdf", 
-        original_path.file_stem().unwrap().to_string_lossy(), timestamp);
-    let temp_path = parent_dir.join(temp_filename);
-    
-    doc.save(&temp_path)?;
-    fs::rename(&temp_path, pdf_path)?;
-    
-    Ok(())
+    Ok(Some(change))
 }
 
-fn encode_to_base64_utf16be(text: &str) -> String {
-    let mut utf16_bytes = vec![0xFE, 0xFF]; // UTF-16BE BOM
-    for ch in text.encode_utf16() {
-        utf16_bytes.extend_from_slice(&ch.to_be_bytes());
-    }
-    
-    let base64_encoded = base64_encode(&utf16_bytes);
-    format!("UTF16BE:{}", base64_encoded)
+fn wait_for_enter() {
+    println!("\n{}", tr!(press_enter));
+    let _ = std::io::stdin().read_line(&mut String::new());
 }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use journal::tests::{create_test_pdf, setup_unique_test_dir};
 
-fn base64_encode(input: &[u8]) -> String {
-    let chars = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
-    let mut result = String::new();
-    
-    for chunk in input.chunks(3) {
-        let mut buf = [0u8; 3];
-        for (i, &byte) in chunk.iter().enumerate() {
-            buf[i] = byte;
-        }
-        
-        let b = ((buf[0] as u32) << 16) | ((buf[1] as u32) << 8) | (buf[2] as u32);
-        
-        result.push(chars.chars().nth(((b >> 18) & 63) as usize).unwrap());
-        result.push(chars.chars().nth(((b >> 12) & 63) as usize).unwrap());
-        
-        if chunk.len() > 1 {
-            result.push(chars.chars().nth(((b >> 6) & 63) as usize).unwrap());
-        } else {
-            result.push('=');
-        }
-        
-        if chunk.len() > 2 {
-            result.push(chars.chars().nth((b & 63) as usize).unwrap());
-        } else {
-            result.push('=');
+    #[test]
+    fn test_expand_paths_keeps_order_without_repeats() -> Result<(), Box<dyn Error>> {
+        let test_dir = setup_unique_test_dir("expand_paths");
+        for name in ["b.pdf", "a.pdf", "notes.txt"] {
+            create_test_pdf(&test_dir.join(name))?;
         }
+        let path = |name: &str| test_dir.join(name).to_string_lossy().into_owned();
+
+        let files = expand_paths(&[path("b.pdf"), path("*.pdf"), path("a.pdf")])?;
+        assert_eq!(files, [path("b.pdf"), path("a.pdf")]);
+        assert!(expand_paths(&[path("a.pdf"), path("missing.pdf")]).is_err());
+        assert!(expand_paths(&[path("*.docx")]).is_err());
+
+        std::fs::remove_dir_all(test_dir)?;
+        Ok(())
     }
-    
-    result
 }
-
-fn wait_for_enter() {
-    println!("\n⏎ Pressione Enter para continuar...");
-    let _ = std::io::stdin().read_line(&mut String::new());
-}
\ No newline at end of file
@@ -0,0 +1,179 @@
+//! Prefixed custom keys (`ACME:ProjectId`).
+//!
+//! Custom Info keys share one flat dictionary with the standard keys and with the
+//! keys of every other tool that touched the file. Prefixing an organization's keys
+//! with its own name keeps them apart: [`SetOptions::key_prefix`](crate::SetOptions::key_prefix)
+//! adds the prefix on every write, and [`KeyNamespace`] reads and edits only the keys
+//! that carry it.
+
+use crate::validate::STANDARD_KEYS;
+use crate::{Error, Result};
+#[cfg(feature = "std-fs")]
+use crate::{
+    get_metadata, info_dict_mut, load_document, mark_modified, options, update_entries_in_place_with, write_loaded,
+};
+use std::borrow::Cow;
+#[cfg(feature = "std-fs")]
+use std::path::Path;
+
+/// The custom Info keys that start with one prefix, such as `ACME:`.
+///
+/// Keys are named without the prefix in this API: `set(path, "ProjectId", ..)` on
+/// the namespace `ACME` writes `ACME:ProjectId`.
+///
+/// # Example
+///
+/// ```no_run
+/// use pdf_metadata::KeyNamespace;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let acme = KeyNamespace::new("ACME")?;
+///     acme.set("report.pdf", "ProjectId", "1234")?;
+///     for (name, value) in acme.list("report.pdf")? {
+///         println!("{}: {}", name, value);
+///     }
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyNamespace {
+    prefix: String,
+}
+
+impl KeyNamespace {
+    /// Creates the namespace of keys prefixed with `prefix` and a colon.
+    ///
+    /// Fails with `Error::Validation` if `prefix` is empty or contains anything but
+    /// ASCII letters, digits, `-`, `_` and `.`, which would be escaped in the file.
+    pub fn new(prefix: &str) -> Result<KeyNamespace> {
+        validate_prefix(prefix)?;
+        Ok(KeyNamespace { prefix: prefix.to_string() })
+    }
+
+    /// The full key of `name` in this namespace, e.g. `ACME:ProjectId`.
+    pub fn key(&self, name: &str) -> String {
+        format!("{}:{}", self.prefix, name)
+    }
+
+    /// Returns the entries of this namespace, named without the prefix, in document order.
+    #[cfg(feature = "std-fs")]
+    pub fn list<P: AsRef<Path>>(&self, file_path: P) -> Result<Vec<(String, String)>> {
+        let file_path = file_path.as_ref();
+        Ok(get_metadata(file_path)?
+            .into_iter()
+            .filter_map(|(key, value)| Some((self.name(&key)?.to_string(), value)))
+            .collect())
+    }
+
+    /// Sets `name` in this namespace in place, refreshing `ModDate`.
+    #[cfg(feature = "std-fs")]
+    pub fn set<P: AsRef<Path>>(&self, file_path: P, name: &str, value: &str) -> Result<()> {
+        let file_path = file_path.as_ref();
+        update_entries_in_place_with(file_path, &[(self.key(name), value)], &options::current())
+    }
+
+    /// Removes `name` from this namespace in place. Returns whether it was present;
+    /// `ModDate` is refreshed only then.
+    #[cfg(feature = "std-fs")]
+    pub fn remove<P: AsRef<Path>>(&self, file_path: P, name: &str) -> Result<bool> {
+        let file_path = file_path.as_ref();
+        let key = self.key(name);
+        Ok(!self.remove_matching(file_path, |k| k == key)?.is_empty())
+    }
+
+    /// Removes every key of this namespace in place, leaving standard and other
+    /// custom keys alone. Returns the names removed.
+    #[cfg(feature = "std-fs")]
+    pub fn clear<P: AsRef<Path>>(&self, file_path: P) -> Result<Vec<String>> {
+        let file_path = file_path.as_ref();
+        let removed = self.remove_matching(file_path, |key| self.name(key).is_some())?;
+        Ok(removed.iter().filter_map(|key| self.name(key)).map(str::to_string).collect())
+    }
+
+    /// `key` without the prefix, if it belongs to this namespace.
+    #[cfg(feature = "std-fs")]
+    fn name<'a>(&self, key: &'a str) -> Option<&'a str> {
+        key.strip_prefix(self.prefix.as_str())?.strip_prefix(':')
+    }
+
+    #[cfg(feature = "std-fs")]
+    fn remove_matching(&self, file_path: &Path, matches: impl Fn(&str) -> bool) -> Result<Vec<String>> {
+        let mut doc = load_document(file_path)?;
+        let removed: Vec<String> = info_dict_mut(&mut doc)?
+            .iter()
+            .map(|(key, _)| String::from_utf8_lossy(key).into_owned())
+            .filter(|key| matches(key))
+            .collect();
+        if removed.is_empty() {
+            return Ok(removed);
+        }
+        let options = options::current();
+        write_loaded(doc, file_path, None, &options, |doc| {
+            let info_dict = info_dict_mut(doc)?;
+            for key in &removed {
+                info_dict.remove(key.as_bytes());
+            }
+            mark_modified(doc, &options)
+        })?;
+        Ok(removed)
+    }
+}
+
+/// `key` as a write with `prefix` stores it: custom keys without the prefix get it,
+/// standard keys and keys that already carry it are kept.
+pub(crate) fn prefixed_key<'a>(key: &'a str, prefix: &str) -> Cow<'a, str> {
+    if STANDARD_KEYS.contains(&key) || key.strip_prefix(prefix).is_some_and(|rest| rest.starts_with(':')) {
+        Cow::Borrowed(key)
+    } else {
+        Cow::Owned(format!("{}:{}", prefix, key))
+    }
+}
+
+pub(crate) fn validate_prefix(prefix: &str) -> Result<()> {
+    if prefix.is_empty() || !prefix.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')) {
+        return Err(Error::Validation(format!("'{}' is not a valid key prefix", prefix)));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SetOptions;
+    use crate::test_support::{create_minimal_test_pdf, setup_unique_test_dir};
+    use std::fs;
+
+    #[test]
+    fn test_prefixed_keys() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("key_namespace");
+        let file = test_dir.join("doc.pdf");
+        create_minimal_test_pdf(&file)?;
+        let file_str = file.to_str().unwrap();
+
+        let options = SetOptions { key_prefix: Some("ACME".to_string()), ..SetOptions::default() };
+        let entries = [("Title", "Relatório"), ("ProjectId", "1234"), ("ACME:Owner", "Ana"), ("Other:Id", "7")];
+        update_entries_in_place_with(file_str, &entries, &options)?;
+        update_entries_in_place_with(file_str, &[("Reviewer", "Rui")], &SetOptions::default())?;
+
+        let keys: Vec<String> = get_metadata(file_str)?.into_iter().map(|(key, _)| key).collect();
+        for key in ["Title", "ACME:ProjectId", "ACME:Owner", "ACME:Other:Id", "Reviewer"] {
+            assert!(keys.contains(&key.to_string()), "{key}");
+        }
+
+        let acme = KeyNamespace::new("ACME")?;
+        let names: Vec<String> = acme.list(file_str)?.into_iter().map(|(name, _)| name).collect();
+        assert_eq!(names.len(), 3);
+        assert!(acme.remove(file_str, "Owner")?);
+        assert!(!acme.remove(file_str, "Owner")?);
+        let mut cleared = acme.clear(file_str)?;
+        cleared.sort();
+        assert_eq!(cleared, ["Other:Id", "ProjectId"]);
+        let keys: Vec<String> = get_metadata(file_str)?.into_iter().map(|(key, _)| key).collect();
+        assert!(keys.contains(&"Title".to_string()) && keys.contains(&"Reviewer".to_string()));
+        assert!(!keys.iter().any(|key| key.starts_with("ACME:")));
+
+        assert!(matches!(KeyNamespace::new("AC ME"), Err(Error::Validation(_))));
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+}
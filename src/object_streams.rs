@@ -0,0 +1,335 @@
+//! Writing documents with object streams and a cross-reference stream.
+//!
+//! PDF 1.5 lets a file pack its plain objects (dictionaries, arrays, numbers, ...)
+//! into compressed object streams and index them with a compressed cross-reference
+//! stream instead of a text table, which typically makes a file a good deal smaller.
+//! lopdf reads such files but writes every object on its own, so an input built this
+//! way would grow on every save.
+//!
+//! Saving renders the document with lopdf first, with a table so that every object's
+//! offset can be read back, and then rebuilds the file: streams and objects with a
+//! non-zero generation are copied as they were rendered, the other objects are packed
+//! into object streams of up to [`OBJECTS_PER_STREAM`] each, and a compressed
+//! cross-reference stream is written last. Encrypted documents are never packed, as
+//! the strings inside an object stream would have to be encrypted with the stream.
+
+use crate::options::XrefStyle;
+use crate::Error;
+use lopdf::xref::XrefType;
+use lopdf::{Dictionary, Document, Object, Stream};
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::io::Write;
+
+/// The most objects packed into one object stream, so a reader looking up a single
+/// object never has to inflate a huge stream.
+const OBJECTS_PER_STREAM: usize = 100;
+
+thread_local! {
+    static STYLE: Cell<XrefStyle> = const { Cell::new(XrefStyle::MatchInput) };
+}
+
+/// Runs `f` with saves on this thread writing `style`, as set by
+/// `SetOptions::xref_style`.
+pub(crate) fn with_style<T>(style: XrefStyle, f: impl FnOnce() -> T) -> T {
+    let previous = STYLE.with(|current| current.replace(style));
+    let result = f();
+    STYLE.with(|current| current.set(previous));
+    result
+}
+
+/// Writes `doc` to `target` in the cross-reference style in effect on this thread.
+pub(crate) fn write_document<W: Write>(doc: &mut Document, target: &mut W) -> std::io::Result<()> {
+    let packable = doc.trailer.get(b"Encrypt").is_err();
+    match STYLE.with(Cell::get) {
+        XrefStyle::MatchInput if packable && has_object_streams(doc) => write_packed(doc, target),
+        XrefStyle::MatchInput => doc.save_to(target),
+        XrefStyle::Table => {
+            doc.reference_table.cross_reference_type = XrefType::CrossReferenceTable;
+            doc.save_to(target)
+        }
+        XrefStyle::Stream => {
+            require_version_1_5(doc);
+            doc.reference_table.cross_reference_type = XrefType::CrossReferenceStream;
+            doc.save_to(target)
+        }
+        XrefStyle::ObjectStreams if packable => write_packed(doc, target),
+        XrefStyle::ObjectStreams => {
+            require_version_1_5(doc);
+            doc.reference_table.cross_reference_type = XrefType::CrossReferenceStream;
+            doc.save_to(target)
+        }
+    }
+}
+
+/// Whether the document was loaded from a file that used object streams. lopdf keeps
+/// the streams in `objects` next to the objects it unpacked from them.
+pub(crate) fn has_object_streams(doc: &Document) -> bool {
+    doc.objects.values().any(|object| matches!(object, Object::Stream(stream) if stream.dict.has_type(b"ObjStm")))
+}
+
+fn write_packed<W: Write>(doc: &mut Document, target: &mut W) -> std::io::Result<()> {
+    require_version_1_5(doc);
+    doc.reference_table.cross_reference_type = XrefType::CrossReferenceTable;
+    let mut rendered = Vec::new();
+    let saved = doc.save_to(&mut rendered);
+    doc.reference_table.cross_reference_type = XrefType::CrossReferenceStream;
+    saved?;
+    let offsets = rendered_offsets(&rendered).ok_or_else(|| invalid("lopdf wrote an unreadable cross-reference table"))?;
+
+    // Each object's bytes run from its offset to the next object or the table.
+    let mut spans: BTreeMap<u32, (u16, &[u8])> = BTreeMap::new();
+    let mut starts: Vec<usize> = offsets.values().map(|&(offset, _)| offset).collect();
+    starts.sort_unstable();
+    let table_start = rendered_table_start(&rendered).ok_or_else(|| invalid("missing startxref"))?;
+    for (&id, &(offset, generation)) in &offsets {
+        let end = starts.iter().copied().find(|&start| start > offset).unwrap_or(table_start);
+        spans.insert(id, (generation, rendered.get(offset..end).ok_or_else(|| invalid("object offset out of range"))?));
+    }
+    let header_end = starts.first().copied().unwrap_or(table_start);
+
+    let mut out = CountingWriter { inner: target, written: 0 };
+    out.write_all(&rendered[..header_end])?;
+    let mut entries: BTreeMap<u32, Entry> = BTreeMap::new();
+    let mut packed = Vec::new();
+    for (&id, &(generation, span)) in &spans {
+        let is_stream = matches!(doc.objects.get(&(id, generation)), Some(Object::Stream(_)));
+        if generation == 0 && !is_stream {
+            packed.push((id, object_body(span)));
+        } else {
+            entries.insert(id, Entry::Offset(out.written, generation));
+            out.write_all(span)?;
+        }
+    }
+
+    let mut next_id = spans.keys().next_back().copied().unwrap_or(0).max(doc.max_id) + 1;
+    for chunk in packed.chunks(OBJECTS_PER_STREAM) {
+        let container = next_id;
+        next_id += 1;
+        let mut index = String::new();
+        let mut bodies = Vec::new();
+        for (position, (id, body)) in chunk.iter().enumerate() {
+            index.push_str(&format!("{} {} ", id, bodies.len()));
+            bodies.extend_from_slice(body);
+            bodies.push(b'\n');
+            entries.insert(*id, Entry::Packed(container, position as u16));
+        }
+        let mut dict = Dictionary::new();
+        dict.set("N", chunk.len() as i64);
+        dict.set("First", index.len() as i64);
+        let mut content = index.into_bytes();
+        content.extend(bodies);
+        entries.insert(container, Entry::Offset(out.written, 0));
+        write_stream_object(&mut out, container, b"ObjStm", Stream::new(dict, content))?;
+    }
+
+    let xref_id = next_id;
+    let xref_offset = out.written;
+    entries.insert(xref_id, Entry::Offset(xref_offset, 0));
+    let offset_width = byte_width(entries.values().map(|entry| match *entry {
+        Entry::Offset(offset, _) => offset as u64,
+        Entry::Packed(container, _) => container as u64,
+    }));
+    let mut rows = Vec::new();
+    for id in 0..=xref_id {
+        let (kind, field2, field3) = match entries.get(&id) {
+            Some(Entry::Offset(offset, generation)) => (1, *offset as u64, *generation),
+            Some(Entry::Packed(container, index)) => (2, *container as u64, *index),
+            None if id == 0 => (0, 0, u16::MAX),
+            None => (0, 0, 0),
+        };
+        rows.push(kind);
+        rows.extend_from_slice(&field2.to_be_bytes()[8 - offset_width..]);
+        rows.extend_from_slice(&field3.to_be_bytes());
+    }
+    let mut dict = doc.trailer.clone();
+    for key in [&b"Type"[..], b"Prev", b"XRefStm", b"Index", b"Filter", b"DecodeParms", b"Length"] {
+        dict.remove(key);
+    }
+    dict.set("Size", i64::from(xref_id) + 1);
+    dict.set("W", vec![Object::Integer(1), Object::Integer(offset_width as i64), Object::Integer(2)]);
+    write_stream_object(&mut out, xref_id, b"XRef", Stream::new(dict, rows))?;
+    write!(out, "startxref\n{}\n%%EOF", xref_offset)
+}
+
+enum Entry {
+    /// Written at this offset, with this generation.
+    Offset(usize, u16),
+    /// Packed into this object stream at this index.
+    Packed(u32, u16),
+}
+
+/// Compresses `stream` and writes it as object `id` of type `type_name`, rendered by
+/// lopdf. The type is added afterwards, as lopdf leaves out objects of the types it
+/// generates itself.
+fn write_stream_object(out: &mut impl Write, id: u32, type_name: &[u8], mut stream: Stream) -> std::io::Result<()> {
+    stream.compress().map_err(into_io)?;
+    let rendered = render(Object::Stream(stream))?;
+    let dict = rendered.strip_prefix(b"<<").ok_or_else(|| invalid("rendered stream without a dictionary"))?;
+    write!(out, "{} 0 obj\n<</Type/", id)?;
+    out.write_all(type_name)?;
+    out.write_all(dict)?;
+    out.write_all(b"\nendobj\n")
+}
+
+/// `object` as lopdf writes it, without the `obj`/`endobj` wrapper.
+fn render(object: Object) -> std::io::Result<Vec<u8>> {
+    let mut scratch = Document::new();
+    scratch.objects.insert((1, 0), object);
+    scratch.max_id = 1;
+    scratch.reference_table.cross_reference_type = XrefType::CrossReferenceTable;
+    let mut rendered = Vec::new();
+    scratch.save_to(&mut rendered)?;
+    let offsets = rendered_offsets(&rendered).ok_or_else(|| invalid("lopdf wrote an unreadable cross-reference table"))?;
+    let &(start, _) = offsets.get(&1).ok_or_else(|| invalid("rendered object missing"))?;
+    let end = rendered_table_start(&rendered).ok_or_else(|| invalid("missing startxref"))?;
+    Ok(object_body(&rendered[start..end]).to_vec())
+}
+
+/// The object inside `N G obj ... endobj`, trimmed.
+fn object_body(span: &[u8]) -> &[u8] {
+    let after_header = span.iter().position(|&b| b == b'\n').map_or(span, |newline| &span[newline + 1..]);
+    let before_end = match after_header.windows(6).rposition(|w| w == b"endobj") {
+        Some(end) => &after_header[..end],
+        None => after_header,
+    };
+    before_end.trim_ascii()
+}
+
+/// Where lopdf put the cross-reference table, from the final `startxref`.
+fn rendered_table_start(rendered: &[u8]) -> Option<usize> {
+    let keyword = rendered.windows(9).rposition(|w| w == b"startxref")?;
+    std::str::from_utf8(&rendered[keyword + 9..]).ok()?.split_ascii_whitespace().next()?.parse().ok()
+}
+
+/// The offset and generation of each object lopdf wrote, from the table it wrote.
+fn rendered_offsets(rendered: &[u8]) -> Option<BTreeMap<u32, (usize, u16)>> {
+    let start = rendered_table_start(rendered)?;
+    let table = rendered.get(start..)?;
+    let end = table.windows(7).position(|w| w == b"trailer")?;
+    let text = std::str::from_utf8(&table[..end]).ok()?;
+    let mut lines = text.lines();
+    if lines.next()?.trim() != "xref" {
+        return None;
+    }
+    let mut offsets = BTreeMap::new();
+    let mut id = 0u32;
+    for line in lines {
+        let fields: Vec<&str> = line.split_ascii_whitespace().collect();
+        match fields.as_slice() {
+            [first, _count] => id = first.parse().ok()?,
+            [offset, generation, kind] => {
+                if *kind == "n" {
+                    offsets.insert(id, (offset.parse().ok()?, generation.parse().ok()?));
+                }
+                id += 1;
+            }
+            [] => {}
+            _ => return None,
+        }
+    }
+    Some(offsets)
+}
+
+/// The fewest bytes that hold every value, at least one.
+fn byte_width(values: impl Iterator<Item = u64>) -> usize {
+    let max = values.max().unwrap_or(0);
+    (8 - max.leading_zeros() as usize / 8).max(1)
+}
+
+/// Object and cross-reference streams are a PDF 1.5 feature.
+fn require_version_1_5(doc: &mut Document) {
+    if doc.version.parse::<f32>().is_ok_and(|version| version < 1.5) {
+        doc.version = "1.5".to_string();
+    }
+}
+
+fn into_io(err: lopdf::Error) -> std::io::Error {
+    match err {
+        lopdf::Error::IO(err) => err,
+        other => std::io::Error::other(Error::Parse(other)),
+    }
+}
+
+fn invalid(message: &str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, message.to_string())
+}
+
+struct CountingWriter<'a, W: Write> {
+    inner: &'a mut W,
+    written: usize,
+}
+
+impl<W: Write> Write for CountingWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.written += written;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{create_minimal_test_pdf, setup_unique_test_dir};
+    use crate::{SetOptions, get_metadata, set_metadata_with_options, update_metadata_in_place, update_metadata_in_place_with_options};
+    use std::fs;
+
+    fn contains(bytes: &[u8], needle: &[u8]) -> bool {
+        bytes.windows(needle.len()).any(|w| w == needle)
+    }
+
+    #[test]
+    fn test_object_streams_round_trip_and_are_kept() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("object_streams");
+        let (file, packed) = (test_dir.join("doc.pdf"), test_dir.join("packed.pdf"));
+        create_minimal_test_pdf(&file)?;
+        // Enough objects for several object streams, and for packing to pay off.
+        let mut doc = Document::load(&file)?;
+        let notes: Vec<Object> = (0..250)
+            .map(|n| {
+                let mut note = Dictionary::new();
+                note.set("Type", Object::Name(b"Annot".to_vec()));
+                note.set("Contents", Object::string_literal(format!("Nota {}", n)));
+                Object::Reference(doc.add_object(note))
+            })
+            .collect();
+        let catalog = doc.trailer.get(b"Root")?.as_reference()?;
+        doc.get_dictionary_mut(catalog)?.set("Notes", notes);
+        doc.save(&file)?;
+        let (file_str, packed_str) = (file.to_str().unwrap(), packed.to_str().unwrap());
+        let options = SetOptions { xref_style: "object-streams".parse()?, ..SetOptions::default() };
+
+        set_metadata_with_options(file_str, packed_str, "Title", "Relatório", &options)?;
+        let bytes = fs::read(&packed)?;
+        assert!(contains(&bytes, b"/ObjStm") && contains(&bytes, b"/XRef"));
+        assert!(!contains(&bytes, b"\nxref\n"));
+        let doc = Document::load_mem(&bytes)?;
+        assert!(doc.catalog()?.get_deref(b"Pages", &doc)?.as_dict()?.has_type(b"Pages"));
+        let notes = doc.catalog()?.get(b"Notes")?.as_array()?;
+        assert_eq!(notes.len(), 250);
+        let last = doc.get_dictionary(notes[249].as_reference()?)?;
+        assert_eq!(last.get(b"Contents")?.as_str()?, b"Nota 249");
+        assert!(get_metadata(packed_str)?.contains(&("Title".to_string(), "Relatório".to_string())));
+
+        // A default save keeps the packing the input had.
+        update_metadata_in_place(packed_str, "Author", "Ana")?;
+        let resaved = fs::read(&packed)?;
+        assert!(contains(&resaved, b"/ObjStm"));
+        assert!(get_metadata(packed_str)?.contains(&("Author".to_string(), "Ana".to_string())));
+
+        let table = SetOptions { xref_style: XrefStyle::Table, ..SetOptions::default() };
+        update_metadata_in_place_with_options(packed_str, "Author", "Ana", &table)?;
+        let unpacked = fs::read(&packed)?;
+        assert!(contains(&unpacked, b"\nxref\n") && !contains(&unpacked, b"/ObjStm"));
+        assert!(resaved.len() < unpacked.len(), "{} >= {}", resaved.len(), unpacked.len());
+        assert!(get_metadata(packed_str)?.contains(&("Title".to_string(), "Relatório".to_string())));
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,337 @@
+//! # Importing metadata from DOCX/ODT source documents
+//!
+//! Converters frequently drop document properties when producing a PDF. When the
+//! source document is still around (e.g. `report.docx` next to `report.pdf`), its
+//! core properties can be read and copied into the PDF's Info dictionary.
+//!
+//! | Source property                             | Info key       |
+//! |---------------------------------------------|----------------|
+//! | `dc:title`                                  | `Title`        |
+//! | `dc:creator` (DOCX) / `meta:initial-creator` (ODT) | `Author` |
+//! | `dc:subject`                                | `Subject`      |
+//! | `cp:keywords` (DOCX) / `meta:keyword` (ODT) | `Keywords`     |
+//! | `dcterms:created` / `meta:creation-date`    | `CreationDate` |
+//!
+//! Only available with the `office` feature (enabled by default).
+
+use crate::enrich::{Enricher, EnricherError};
+use crate::{Error, Result, format_pdf_date, get_metadata, update_entries_in_place};
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone};
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Extensions recognized as source documents, in lookup order.
+const SOURCE_EXTENSIONS: [&str; 4] = ["docx", "odt", "docm", "ott"];
+
+/// Core properties read from an office source document.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OfficeProperties {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub subject: Option<String>,
+    pub keywords: Option<String>,
+    /// Creation timestamp as stored in the document (ISO 8601).
+    pub created: Option<String>,
+}
+
+impl OfficeProperties {
+    /// Converts the properties to Info dictionary entries, skipping empty values.
+    ///
+    /// `created` is converted to a PDF date; an unparsable timestamp is skipped.
+    pub fn to_info_entries(&self) -> Vec<(String, String)> {
+        let mut entries = Vec::new();
+        let fields = [
+            ("Title", &self.title),
+            ("Author", &self.author),
+            ("Subject", &self.subject),
+            ("Keywords", &self.keywords),
+        ];
+        for (key, value) in fields {
+            if let Some(value) = value.as_deref().map(str::trim).filter(|v| !v.is_empty()) {
+                entries.push((key.to_string(), value.to_string()));
+            }
+        }
+        if let Some(date) = self.created.as_deref().and_then(iso_to_pdf_date) {
+            entries.push(("CreationDate".to_string(), date));
+        }
+        entries
+    }
+}
+
+/// Converts an ISO 8601 timestamp (with or without offset) to a PDF date string.
+fn iso_to_pdf_date(iso: &str) -> Option<String> {
+    let iso = iso.trim();
+    if let Ok(dt) = DateTime::parse_from_rfc3339(iso) {
+        return Some(format_pdf_date(&dt));
+    }
+    // ODT stores local time without an offset, optionally with fractional seconds.
+    let naive = NaiveDateTime::parse_from_str(iso, "%Y-%m-%dT%H:%M:%S%.f").ok()?;
+    Local.from_local_datetime(&naive).single().map(|dt| format_pdf_date(&dt))
+}
+
+/// Reads the core properties of a DOCX (`docProps/core.xml`) or ODT (`meta.xml`) file.
+///
+/// # Returns
+///
+/// * `Ok(OfficeProperties)`: The properties found; missing ones are `None`.
+/// * `Err(Error::UnsupportedFormat)`: If the extension is not a known source format.
+/// * `Err(Error)`: If the file cannot be opened or its archive/XML is malformed.
+pub fn read_office_properties<P: AsRef<Path>>(source_path: P) -> Result<OfficeProperties> {
+    let source_path = source_path.as_ref();
+    let extension = source_path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase())
+        .unwrap_or_default();
+    let (entry_name, is_odf) = match extension.as_str() {
+        "docx" | "docm" | "dotx" => ("docProps/core.xml", false),
+        "odt" | "ott" => ("meta.xml", true),
+        other => return Err(Error::UnsupportedFormat(format!("'.{}' is not a DOCX or ODT document", other))),
+    };
+
+    let mut archive = zip::ZipArchive::new(File::open(source_path)?).map_err(|e| Error::Archive(e.to_string()))?;
+    let mut xml = String::new();
+    archive
+        .by_name(entry_name)
+        .map_err(|e| Error::Archive(format!("{}: {}", entry_name, e)))?
+        .read_to_string(&mut xml)?;
+
+    parse_properties_xml(&xml, is_odf)
+}
+
+fn parse_properties_xml(xml: &str, is_odf: bool) -> Result<OfficeProperties> {
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().trim_text(true);
+
+    let mut props = OfficeProperties::default();
+    let mut odf_keywords: Vec<String> = Vec::new();
+    let mut odf_last_modifier: Option<String> = None;
+    let mut current: Option<Vec<u8>> = None;
+
+    loop {
+        match reader.read_event().map_err(|e| Error::Xml(e.to_string()))? {
+            Event::Start(e) => current = Some(e.name().as_ref().to_vec()),
+            Event::End(_) => current = None,
+            Event::Text(text) => {
+                let Some(name) = current.as_deref() else { continue };
+                let value = text.unescape().map_err(|e| Error::Xml(e.to_string()))?.into_owned();
+                match (name, is_odf) {
+                    (b"dc:title", _) => props.title = Some(value),
+                    (b"dc:subject", _) => props.subject = Some(value),
+                    (b"dc:creator", false) => props.author = Some(value),
+                    (b"dc:creator", true) => odf_last_modifier = Some(value),
+                    (b"meta:initial-creator", true) => props.author = Some(value),
+                    (b"cp:keywords", false) => props.keywords = Some(value),
+                    (b"meta:keyword", true) => odf_keywords.push(value),
+                    (b"dcterms:created", false) | (b"meta:creation-date", true) => props.created = Some(value),
+                    _ => {}
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    if is_odf {
+        if !odf_keywords.is_empty() {
+            props.keywords = Some(odf_keywords.join(", "));
+        }
+        // ODT only records the last editor when no initial creator was stored.
+        if props.author.is_none() {
+            props.author = odf_last_modifier;
+        }
+    }
+    Ok(props)
+}
+
+/// Looks for a source document next to `pdf_path` with the same file stem.
+///
+/// For `reports/q3.pdf` this checks `reports/q3.docx`, `reports/q3.odt`, ... and
+/// returns the first one that exists.
+pub fn find_sibling_source<P: AsRef<Path>>(pdf_path: P) -> Option<PathBuf> {
+    let pdf_path = pdf_path.as_ref();
+    SOURCE_EXTENSIONS
+        .iter()
+        .map(|ext| pdf_path.with_extension(ext))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Copies the core properties of a source document into a PDF file, in place.
+///
+/// # Arguments
+///
+/// * `pdf_path`: The PDF file to update.
+/// * `source_path`: The DOCX/ODT file to read. When `None`, a sibling with the same
+///   stem is looked up with [`find_sibling_source`].
+/// * `overwrite`: Whether to replace values the PDF already has. When `false`, only
+///   keys missing from the PDF (or empty) are filled in.
+///
+/// # Returns
+///
+/// * `Ok(Vec<(String, String)>)`: The entries that were written; empty if there was no
+///   source document or nothing to change (the PDF is not rewritten in that case).
+/// * `Err(Error)`: If reading either document or writing the PDF fails.
+///
+/// # Example
+///
+/// ```no_run
+/// use pdf_metadata::office::import_office_metadata;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let written = import_office_metadata("reports/q3.pdf", None, false)?;
+///     println!("Imported {} properties from q3.docx", written.len());
+///     Ok(())
+/// }
+/// ```
+pub fn import_office_metadata<P: AsRef<Path>>(
+    pdf_path: P,
+    source_path: Option<&Path>,
+    overwrite: bool,
+) -> Result<Vec<(String, String)>> {
+    let pdf_path = pdf_path.as_ref();
+    let entries = office_entries_for(pdf_path, &get_metadata(pdf_path)?, source_path, overwrite)?;
+    if !entries.is_empty() {
+        update_entries_in_place(pdf_path, &entries)?;
+    }
+    Ok(entries)
+}
+
+fn office_entries_for(
+    pdf_path: &Path,
+    existing: &[(String, String)],
+    source_path: Option<&Path>,
+    overwrite: bool,
+) -> Result<Vec<(String, String)>> {
+    let source = match source_path {
+        Some(path) => path.to_path_buf(),
+        None => match find_sibling_source(pdf_path) {
+            Some(path) => path,
+            None => return Ok(Vec::new()),
+        },
+    };
+
+    let entries = read_office_properties(&source)?.to_info_entries();
+    Ok(entries
+        .into_iter()
+        .filter(|(key, value)| {
+            let current = existing.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str());
+            match current {
+                Some(current) if current == value => false,
+                Some(current) if !current.trim().is_empty() => overwrite,
+                _ => true,
+            }
+        })
+        .collect())
+}
+
+/// An [`Enricher`] that fills metadata from a sibling DOCX/ODT document, for use with
+/// [`enrich_files`](crate::enrich::enrich_files). Files without a sibling are left untouched.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OfficeEnricher {
+    /// Whether to replace values the PDF already has.
+    pub overwrite: bool,
+}
+
+impl Enricher for OfficeEnricher {
+    fn name(&self) -> &str {
+        "office"
+    }
+
+    fn enrich(&self, path: &Path, metadata: &[(String, String)]) -> std::result::Result<Vec<(String, String)>, EnricherError> {
+        Ok(office_entries_for(path, metadata, None, self.overwrite)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{create_minimal_test_pdf, setup_unique_test_dir};
+    use std::fs;
+    use std::io::Write;
+
+    fn write_zip(path: &Path, entry: &str, content: &str) -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let mut writer = zip::ZipWriter::new(File::create(path)?);
+        writer.start_file(entry, zip::write::SimpleFileOptions::default())?;
+        writer.write_all(content.as_bytes())?;
+        writer.finish()?;
+        Ok(())
+    }
+
+    const DOCX_CORE: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<cp:coreProperties xmlns:cp="http://schemas.openxmlformats.org/package/2006/metadata/core-properties" xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:dcterms="http://purl.org/dc/terms/" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
+  <dc:title>Quarterly &amp; Annual Report</dc:title>
+  <dc:subject>Finance</dc:subject>
+  <dc:creator>Maria Silva</dc:creator>
+  <cp:keywords>tax; 2023</cp:keywords>
+  <cp:lastModifiedBy>João</cp:lastModifiedBy>
+  <dcterms:created xsi:type="dcterms:W3CDTF">2023-10-27T13:30:00Z</dcterms:created>
+</cp:coreProperties>"#;
+
+    const ODT_META: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<office:document-meta xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0" xmlns:meta="urn:oasis:names:tc:opendocument:xmlns:meta:1.0" xmlns:dc="http://purl.org/dc/elements/1.1/">
+  <office:meta>
+    <meta:initial-creator>Ana Souza</meta:initial-creator>
+    <dc:creator>Last Editor</dc:creator>
+    <dc:title>Relatório</dc:title>
+    <meta:keyword>alpha</meta:keyword>
+    <meta:keyword>beta</meta:keyword>
+    <meta:creation-date>2023-10-27T15:30:00.123</meta:creation-date>
+  </office:meta>
+</office:document-meta>"#;
+
+    #[test]
+    fn test_read_docx_and_odt_properties() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("office_read");
+        let docx = test_dir.join("report.docx");
+        let odt = test_dir.join("relatorio.odt");
+        write_zip(&docx, "docProps/core.xml", DOCX_CORE)?;
+        write_zip(&odt, "meta.xml", ODT_META)?;
+
+        let props = read_office_properties(&docx)?;
+        assert_eq!(props.title.as_deref(), Some("Quarterly & Annual Report"));
+        assert_eq!(props.author.as_deref(), Some("Maria Silva"));
+        assert_eq!(props.keywords.as_deref(), Some("tax; 2023"));
+        let entries = props.to_info_entries();
+        assert!(entries.contains(&("CreationDate".to_string(), "D:20231027133000+00'00'".to_string())));
+
+        let props = read_office_properties(&odt)?;
+        assert_eq!(props.author.as_deref(), Some("Ana Souza"));
+        assert_eq!(props.title.as_deref(), Some("Relatório"));
+        assert_eq!(props.keywords.as_deref(), Some("alpha, beta"));
+        assert!(props.to_info_entries().iter().any(|(k, v)| k == "CreationDate" && v.starts_with("D:20231027153000")));
+
+        assert!(matches!(read_office_properties(test_dir.join("notes.txt")), Err(Error::UnsupportedFormat(_))));
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_import_from_sibling_respects_overwrite() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("office_import");
+        let pdf = test_dir.join("report.pdf");
+        create_minimal_test_pdf(&pdf)?;
+        write_zip(&test_dir.join("report.docx"), "docProps/core.xml", DOCX_CORE)?;
+        crate::update_metadata_in_place(pdf.to_str().unwrap(), "Author", "Existing Author")?;
+
+        let written = import_office_metadata(&pdf, None, false)?;
+        assert!(written.iter().all(|(k, _)| k != "Author"));
+        let metadata = get_metadata(pdf.to_str().unwrap())?;
+        assert!(metadata.contains(&("Author".to_string(), "Existing Author".to_string())));
+        assert!(metadata.contains(&("Title".to_string(), "Quarterly & Annual Report".to_string())));
+
+        let written = import_office_metadata(&pdf, None, true)?;
+        assert_eq!(written, vec![("Author".to_string(), "Maria Silva".to_string())]);
+
+        let lonely = test_dir.join("lonely.pdf");
+        create_minimal_test_pdf(&lonely)?;
+        assert!(import_office_metadata(&lonely, None, true)?.is_empty());
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,330 @@
+//! Options controlling how writes modify the Info dictionary.
+
+use crate::{BackupPolicy, Error, Result};
+use chrono::{DateTime, FixedOffset};
+use std::cell::RefCell;
+
+/// What a write does with a key the Info dictionary already has.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ConflictPolicy {
+    /// Fail with `Error::KeyExists`; nothing is written.
+    Fail,
+    /// Keep the existing value and set only the missing keys.
+    Skip,
+    /// Replace the existing value.
+    #[default]
+    Overwrite,
+    /// Append the new value to the existing one after the separator, e.g.
+    /// `Append("; ".into())` turns `Ana` into `Ana; Bruno`. Non-string values and
+    /// empty strings are replaced.
+    Append(String),
+}
+
+impl std::str::FromStr for ConflictPolicy {
+    type Err = Error;
+
+    /// Parses `fail`, `skip`, `overwrite` or `append`, which uses `"; "` as separator.
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "fail" => Ok(ConflictPolicy::Fail),
+            "skip" => Ok(ConflictPolicy::Skip),
+            "overwrite" => Ok(ConflictPolicy::Overwrite),
+            "append" => Ok(ConflictPolicy::Append("; ".to_string())),
+            other => Err(Error::UnsupportedFormat(format!("conflict policy '{}'", other))),
+        }
+    }
+}
+
+/// What a write does with the trailer `/ID`, the pair of file identifiers that
+/// document management systems use to recognize a file and its revisions.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DocumentIdPolicy {
+    /// Leave `/ID` as it is.
+    #[default]
+    Keep,
+    /// Give the second identifier a new value, as the PDF specification recommends
+    /// for modified files, and keep the first, which identifies the document. A
+    /// document without `/ID` gets a new pair.
+    UpdateCurrent,
+    /// Give both identifiers the same new value, so the output counts as a new
+    /// document, e.g. a copy made from a template. Fails with `Error::UnsupportedFormat`
+    /// for documents that keep an RC4 or AES-128 encryption, whose key is derived from
+    /// the first identifier.
+    Regenerate,
+}
+
+impl std::str::FromStr for DocumentIdPolicy {
+    type Err = Error;
+
+    /// Parses `keep`, `update` or `regenerate`.
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "keep" => Ok(DocumentIdPolicy::Keep),
+            "update" => Ok(DocumentIdPolicy::UpdateCurrent),
+            "regenerate" => Ok(DocumentIdPolicy::Regenerate),
+            other => Err(Error::UnsupportedFormat(format!("document ID policy '{}'", other))),
+        }
+    }
+}
+
+/// How keys given to a read or write are matched against the keys of the Info
+/// dictionary, whose names are case-sensitive.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum KeyMatch {
+    /// Keys match only when spelled exactly alike, as in the PDF specification.
+    #[default]
+    Exact,
+    /// Keys match regardless of ASCII case. A key written this way takes the spelling
+    /// of the standard key (`author` becomes `Author`) or, for custom keys, of the
+    /// entry already in the document, so no duplicate differing only in case is added.
+    CaseInsensitive,
+}
+
+impl std::str::FromStr for KeyMatch {
+    type Err = Error;
+
+    /// Parses `exact` or `ignore-case`.
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "exact" => Ok(KeyMatch::Exact),
+            "ignore-case" => Ok(KeyMatch::CaseInsensitive),
+            other => Err(Error::UnsupportedFormat(format!("key match '{}'", other))),
+        }
+    }
+}
+
+/// What a write that rewrites the whole file does with a digitally signed document.
+///
+/// A full rewrite moves the signed bytes, which invalidates every signature; an
+/// incremental update appends the change instead and keeps them valid. Writers that
+/// append anyway, such as [`update_metadata_incremental`](crate::update_metadata_incremental),
+/// are not affected.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SignedDocPolicy {
+    /// Fail with `Error::Signed`; nothing is written.
+    Error,
+    /// Append an incremental update instead of rewriting the file.
+    ForceIncremental,
+    /// Rewrite the file, invalidating its signatures.
+    #[default]
+    IgnoreAndRewrite,
+}
+
+impl std::str::FromStr for SignedDocPolicy {
+    type Err = Error;
+
+    /// Parses `error`, `incremental` or `rewrite`.
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "error" => Ok(SignedDocPolicy::Error),
+            "incremental" => Ok(SignedDocPolicy::ForceIncremental),
+            "rewrite" => Ok(SignedDocPolicy::IgnoreAndRewrite),
+            other => Err(Error::UnsupportedFormat(format!("signed document policy '{}'", other))),
+        }
+    }
+}
+
+/// How a write stores the changed document.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SaveMode {
+    /// Re-serialize the whole document in the [`XrefStyle`] of
+    /// `SetOptions::xref_style`, so the output can differ in size and layout from the
+    /// input.
+    #[default]
+    Rewrite,
+    /// Append the changed objects as an incremental update, so every byte of the
+    /// original, with its object streams, compression and stream filters, is kept and
+    /// the file only grows by the size of the change. Encrypted documents, which
+    /// cannot be updated incrementally, are rewritten.
+    Preserve,
+}
+
+impl std::str::FromStr for SaveMode {
+    type Err = Error;
+
+    /// Parses `rewrite` or `preserve`.
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "rewrite" => Ok(SaveMode::Rewrite),
+            "preserve" => Ok(SaveMode::Preserve),
+            other => Err(Error::UnsupportedFormat(format!("save mode '{}'", other))),
+        }
+    }
+}
+
+/// How a rewritten document indexes its objects.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum XrefStyle {
+    /// Write what the input used: object streams if it had any, otherwise its kind of
+    /// cross-reference data.
+    #[default]
+    MatchInput,
+    /// A classic `xref` table, readable by every PDF reader.
+    Table,
+    /// A cross-reference stream (PDF 1.5), with every object written on its own.
+    Stream,
+    /// Plain objects packed into compressed object streams, indexed by a compressed
+    /// cross-reference stream (PDF 1.5). Usually the smallest output. Encrypted
+    /// documents get a cross-reference stream only.
+    ObjectStreams,
+}
+
+impl std::str::FromStr for XrefStyle {
+    type Err = Error;
+
+    /// Parses `input`, `table`, `stream` or `object-streams`.
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "input" => Ok(XrefStyle::MatchInput),
+            "table" => Ok(XrefStyle::Table),
+            "stream" => Ok(XrefStyle::Stream),
+            "object-streams" => Ok(XrefStyle::ObjectStreams),
+            other => Err(Error::UnsupportedFormat(format!("cross-reference style '{}'", other))),
+        }
+    }
+}
+
+/// Settings for the `*_with_options` write functions.
+///
+/// The default matches the plain functions: `ModDate` is set to the current time
+/// and an existing output file is replaced. Inside [`with_write_options`] the plain
+/// functions use the options given there instead.
+///
+/// ```
+/// use pdf_metadata::{ConflictPolicy, SetOptions};
+///
+/// // Archive workflow: keep the original modification date untouched.
+/// let options = SetOptions { update_mod_date: false, ..SetOptions::default() };
+/// assert!(options.mod_date.is_none());
+///
+/// // Batch import: fill in missing Authors, but never clobber existing ones.
+/// let options = SetOptions { on_conflict: ConflictPolicy::Skip, ..SetOptions::default() };
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SetOptions {
+    /// Whether `ModDate` is written at all. When `false`, any existing `ModDate`
+    /// is left exactly as it was.
+    pub update_mod_date: bool,
+    /// The timestamp to write as `ModDate` instead of the current time of the
+    /// active [`Clock`](crate::Clock).
+    /// Ignored when `update_mod_date` is `false`.
+    pub mod_date: Option<DateTime<FixedOffset>>,
+    /// Whether a write to a separate output path may replace an existing file.
+    /// When `false`, such writes fail with `Error::Write` if the output exists,
+    /// including when it is the source file itself. In-place updates ignore it.
+    pub overwrite: bool,
+    /// Whether in-place updates keep the file's modification and access times, so
+    /// tools that sort or sync by mtime do not see the file as changed. Writes to a
+    /// separate output path ignore it.
+    pub preserve_mtime: bool,
+    /// Whether in-place updates first copy the file to `<file>.bak-<timestamp>`, and
+    /// how many such copies are kept. See [`BackupPolicy`]. Writes to a separate output
+    /// path ignore it.
+    pub backup: Option<BackupPolicy>,
+    /// Whether entries are checked with [`validate`](crate::validate) before they are
+    /// set. When `true`, a write with any warning fails with `Error::Validation`
+    /// listing them, and nothing is written.
+    pub strict: bool,
+    /// What happens to keys that already exist. `ModDate` is only refreshed when an
+    /// entry was actually set, so a write that skips every key changes nothing.
+    pub on_conflict: ConflictPolicy,
+    /// What happens to the trailer `/ID` when a write changes the document. Like
+    /// `ModDate`, it is left alone by a write that changes nothing, and writers that
+    /// set `/ID` themselves ignore it.
+    pub document_id: DocumentIdPolicy,
+    /// What happens when the document is digitally signed. Signatures stay valid in
+    /// [`SaveMode::Preserve`] whatever the policy, as nothing is rewritten.
+    pub on_signed: SignedDocPolicy,
+    /// Whether the document is rewritten or the change appended to it.
+    pub save_mode: SaveMode,
+    /// How a rewritten document indexes its objects. Ignored in [`SaveMode::Preserve`],
+    /// where the appended update follows the file's own cross-reference data.
+    pub xref_style: XrefStyle,
+    /// Whether a write that sets an entry also records a `metadata-edited` event in
+    /// the XMP `xmpMM:History` and gives the packet a new `xmpMM:InstanceID`, so the
+    /// provenance kept by archival tools stays complete. A packet is created if the
+    /// document has none.
+    pub xmp_history: bool,
+    /// A prefix such as `ACME` that custom keys are written under, as `ACME:ProjectId`,
+    /// so an organization's keys cannot collide with standard or third-party ones.
+    /// Standard keys and keys that already carry the prefix are written as given.
+    /// See [`KeyNamespace`](crate::KeyNamespace) to read and edit them.
+    pub key_prefix: Option<String>,
+    /// How the keys written are matched against the keys already in the document.
+    pub key_match: KeyMatch,
+    /// Whether a write with [`KeyMatch::CaseInsensitive`] also removes the other
+    /// entries whose keys differ from the one written only in case, merging them into
+    /// one. Ignored with [`KeyMatch::Exact`].
+    pub merge_case_duplicates: bool,
+}
+
+impl Default for SetOptions {
+    fn default() -> Self {
+        SetOptions {
+            update_mod_date: true,
+            mod_date: None,
+            overwrite: true,
+            preserve_mtime: false,
+            backup: None,
+            strict: false,
+            on_conflict: ConflictPolicy::Overwrite,
+            document_id: DocumentIdPolicy::Keep,
+            on_signed: SignedDocPolicy::IgnoreAndRewrite,
+            save_mode: SaveMode::Rewrite,
+            xref_style: XrefStyle::MatchInput,
+            xmp_history: false,
+            key_prefix: None,
+            key_match: KeyMatch::Exact,
+            merge_case_duplicates: false,
+        }
+    }
+}
+
+thread_local! {
+    static THREAD_OPTIONS: RefCell<Vec<SetOptions>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Runs `f` with `options` applying to the writers on this thread that take none,
+/// such as [`strip_metadata`](crate::scrub::strip_metadata) or
+/// [`set_page_metadata`](crate::set_page_metadata), so a policy like
+/// [`SignedDocPolicy::Error`] covers every write a workflow makes.
+///
+/// Work `f` hands to other threads, such as the workers of the
+/// [batch engine](crate::batch), still uses the default options; only
+/// [`with_timeout`](crate::with_timeout) carries the scope over to its worker.
+///
+/// ```no_run
+/// use pdf_metadata::{SetOptions, SignedDocPolicy, remove_metadata_in_place, with_write_options};
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let refuse_signed = SetOptions { on_signed: SignedDocPolicy::Error, ..SetOptions::default() };
+///     with_write_options(refuse_signed, || remove_metadata_in_place("contract.pdf", "Keywords"))?;
+///     Ok(())
+/// }
+/// ```
+pub fn with_write_options<T>(options: SetOptions, f: impl FnOnce() -> T) -> T {
+    /// Removes the options again, also when `f` panics.
+    struct Restore;
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            THREAD_OPTIONS.with(|options| options.borrow_mut().pop());
+        }
+    }
+
+    THREAD_OPTIONS.with(|scoped| scoped.borrow_mut().push(options));
+    let _restore = Restore;
+    f()
+}
+
+/// The options for a writer that takes none: those of the innermost
+/// [`with_write_options`] on this thread, or the default.
+pub(crate) fn current() -> SetOptions {
+    THREAD_OPTIONS.with(|options| options.borrow().last().cloned()).unwrap_or_default()
+}
@@ -0,0 +1,228 @@
+//! The document outline (bookmarks).
+
+use crate::attachments::{name_tree, resolve_dict};
+use crate::{Result, decode_document_string, decode_string_object, load_document};
+use lopdf::{Dictionary, Document, Object, ObjectId};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+/// Upper bound on the nesting of bookmarks; deeper items are treated as damaged.
+const MAX_OUTLINE_DEPTH: usize = 32;
+
+/// A bookmark, as returned by [`get_outline`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OutlineEntry {
+    /// The text shown in the bookmarks panel.
+    pub title: String,
+    /// The nesting level: 1 for top-level bookmarks, 2 for their children, and so on.
+    pub level: usize,
+    /// The 1-based number of the page the bookmark jumps to, when it targets a page
+    /// of this document (directly, through a named destination or a `GoTo` action).
+    pub page: Option<u32>,
+}
+
+/// Reads the outline (bookmark tree) of a PDF.
+///
+/// Bookmarks are returned flattened in reading order, each with its nesting level,
+/// so children directly follow their parent. Loops in a damaged outline are cut.
+///
+/// # Returns
+///
+/// * `Ok(Vec<OutlineEntry>)`: The bookmarks; empty if the document has no outline.
+/// * `Err(Error)`: If the file cannot be read or parsed.
+///
+/// # Example
+///
+/// ```no_run
+/// use pdf_metadata::get_outline;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     for entry in get_outline("report.pdf")? {
+///         println!("{}{} (p. {:?})", "  ".repeat(entry.level - 1), entry.title, entry.page);
+///     }
+///     Ok(())
+/// }
+/// ```
+pub fn get_outline<P: AsRef<Path>>(file_path: P) -> Result<Vec<OutlineEntry>> {
+    let file_path = file_path.as_ref();
+    let doc = load_document(file_path)?;
+    Ok(outline_entries(&doc))
+}
+
+fn outline_entries(doc: &Document) -> Vec<OutlineEntry> {
+    let mut entries = Vec::new();
+    let root = doc.catalog().ok().and_then(|catalog| resolve_dict(doc, catalog.get(b"Outlines").ok()?));
+    if let Some(root) = root {
+        let pages = doc.get_pages().into_iter().map(|(number, id)| (id, number)).collect();
+        let outline = Outline { doc, pages };
+        outline.collect_children(root, 1, &mut HashSet::new(), &mut entries);
+    }
+    entries
+}
+
+struct Outline<'a> {
+    doc: &'a Document,
+    /// Page object ids to page numbers.
+    pages: HashMap<ObjectId, u32>,
+}
+
+impl Outline<'_> {
+    /// Appends the items of the `/First` .. `/Next` chain under `parent`, depth first.
+    fn collect_children(
+        &self,
+        parent: &Dictionary,
+        level: usize,
+        visited: &mut HashSet<ObjectId>,
+        entries: &mut Vec<OutlineEntry>,
+    ) {
+        if level > MAX_OUTLINE_DEPTH {
+            return;
+        }
+        let mut next = parent.get(b"First").and_then(Object::as_reference).ok();
+        while let Some(id) = next
+            && visited.insert(id)
+        {
+            let Ok(item) = self.doc.get_dictionary(id) else { break };
+            let title = item
+                .get(b"Title")
+                .ok()
+                .and_then(|title| self.doc.dereference(title).ok())
+                .and_then(|(_, title)| decode_string_object(title))
+                .unwrap_or_default();
+            entries.push(OutlineEntry { title, level, page: self.target_page(item) });
+            self.collect_children(item, level + 1, visited, entries);
+            next = item.get(b"Next").and_then(Object::as_reference).ok();
+        }
+    }
+
+    /// The page targeted by the item's `/Dest`, or by the `/D` of a `GoTo` action.
+    fn target_page(&self, item: &Dictionary) -> Option<u32> {
+        let dest = match item.get(b"Dest") {
+            Ok(dest) => dest,
+            Err(_) => {
+                let action = resolve_dict(self.doc, item.get(b"A").ok()?)?;
+                if action.get(b"S").and_then(Object::as_name).ok()? != b"GoTo" {
+                    return None;
+                }
+                action.get(b"D").ok()?
+            }
+        };
+        self.destination_page(dest, 0)
+    }
+
+    /// Resolves an explicit destination (`[page /XYZ ...]`) or a named one: a string
+    /// in the `Dests` name tree, or a name in the PDF 1.1 `/Dests` dictionary.
+    fn destination_page(&self, dest: &Object, depth: usize) -> Option<u32> {
+        let (_, dest) = self.doc.dereference(dest).ok()?;
+        match dest {
+            Object::Array(dest) => self.pages.get(&dest.first()?.as_reference().ok()?).copied(),
+            // Named destinations may be wrapped in a dictionary with the array in /D.
+            Object::Dictionary(dest) if depth < 2 => self.destination_page(dest.get(b"D").ok()?, depth + 1),
+            Object::String(name, format) if depth < 2 => {
+                let name = decode_document_string(name, *format);
+                let (_, target) = name_tree(self.doc, b"Dests").into_iter().find(|(key, _)| *key == name)?;
+                self.destination_page(target, depth + 1)
+            }
+            Object::Name(name) if depth < 2 => {
+                let dests = resolve_dict(self.doc, self.doc.catalog().ok()?.get(b"Dests").ok()?)?;
+                self.destination_page(dests.get(name).ok()?, depth + 1)
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::setup_unique_test_dir;
+    use lopdf::dictionary;
+    use std::fs;
+
+    #[test]
+    fn test_get_outline_reads_nested_bookmarks() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("outline_nested");
+        let file = test_dir.join("report.pdf");
+
+        let mut doc = Document::with_version("1.7");
+        let pages_id = doc.new_object_id();
+        let page_ids: Vec<ObjectId> =
+            (0..3).map(|_| doc.add_object(dictionary! { "Type" => "Page", "Parent" => pages_id })).collect();
+        doc.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Count" => 3,
+                "Kids" => page_ids.iter().map(|&id| Object::Reference(id)).collect::<Vec<_>>(),
+            }),
+        );
+
+        let root_id = doc.new_object_id();
+        let (intro_id, methods_id, sample_id, results_id) =
+            (doc.new_object_id(), doc.new_object_id(), doc.new_object_id(), doc.new_object_id());
+        let items = [
+            (intro_id, dictionary! {
+                "Title" => Object::string_literal("Introduction"),
+                "Parent" => root_id,
+                "Next" => methods_id,
+                "Dest" => vec![Object::Reference(page_ids[0]), "Fit".into()],
+            }),
+            (methods_id, dictionary! {
+                "Title" => Object::string_literal("Methods"),
+                "Parent" => root_id,
+                "Prev" => intro_id,
+                "Next" => results_id,
+                "First" => sample_id,
+                "Last" => sample_id,
+                "A" => dictionary! { "S" => "GoTo", "D" => Object::string_literal("methods") },
+            }),
+            (sample_id, dictionary! {
+                "Title" => crate::info_string_object("Amostragem"),
+                "Parent" => methods_id,
+                "Dest" => "sample",
+            }),
+            (results_id, dictionary! {
+                "Title" => Object::string_literal("Results"),
+                "Parent" => root_id,
+                "Prev" => methods_id,
+                // A damaged link back to the start of the chain.
+                "Next" => intro_id,
+                "A" => dictionary! { "S" => "URI", "URI" => Object::string_literal("https://example.com") },
+            }),
+        ];
+        for (id, item) in items {
+            doc.objects.insert(id, Object::Dictionary(item));
+        }
+        doc.objects.insert(
+            root_id,
+            Object::Dictionary(dictionary! { "Type" => "Outlines", "First" => intro_id, "Last" => results_id }),
+        );
+        let dests = dictionary! {
+            "Names" => vec![Object::string_literal("methods"), vec![Object::Reference(page_ids[1]), "Fit".into()].into()],
+        };
+        let catalog_id = doc.add_object(dictionary! {
+            "Type" => "Catalog",
+            "Pages" => pages_id,
+            "Outlines" => root_id,
+            "Names" => dictionary! { "Dests" => dests },
+            "Dests" => dictionary! { "sample" => dictionary! { "D" => vec![Object::Reference(page_ids[2]), "Fit".into()] } },
+        });
+        doc.trailer.set("Root", catalog_id);
+        doc.save(&file)?;
+
+        let entry = |title: &str, level, page| OutlineEntry { title: title.to_string(), level, page };
+        assert_eq!(
+            get_outline(file.to_str().unwrap())?,
+            [
+                entry("Introduction", 1, Some(1)),
+                entry("Methods", 1, Some(2)),
+                entry("Amostragem", 2, Some(3)),
+                entry("Results", 1, None),
+            ]
+        );
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+}
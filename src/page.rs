@@ -0,0 +1,197 @@
+//! Per-page application data (`/PieceInfo` page-piece dictionaries).
+//!
+//! Applications store private data on a page under their own name in the page's
+//! `/PieceInfo` dictionary, as `<< /LastModified (D:...) /Private << ... >> >>`. The
+//! page's `/LastModified` tells them whether the page changed since they wrote it.
+
+use crate::{Error, Result, incremental, info_string_object, load_document, mark_modified, options, pdf_date_now, write_file};
+use crate::attachments::resolve_dict;
+use crate::value::MetadataValue;
+use lopdf::{Dictionary, Document, Object, ObjectId};
+use std::path::Path;
+
+/// The data one application stored on a page, as returned by [`get_page_metadata`].
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PagePiece {
+    /// The application name, the key in `/PieceInfo`.
+    pub application: String,
+    /// `/LastModified` as stored, a PDF date string.
+    pub last_modified: Option<String>,
+    /// The entries of the `/Private` dictionary. Empty when `/Private` holds another
+    /// kind of object.
+    pub private: Vec<(String, MetadataValue)>,
+}
+
+/// Reads the page-piece dictionaries of page `page_number` (1-based).
+///
+/// # Returns
+///
+/// * `Ok(Vec<PagePiece>)`: One entry per application; empty if the page has no `/PieceInfo`.
+/// * `Err(Error::PageNotFound)`: If the document has no such page.
+/// * `Err(Error)`: If the file cannot be read or parsed.
+///
+/// # Example
+///
+/// ```no_run
+/// use pdf_metadata::get_page_metadata;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     for piece in get_page_metadata("scan.pdf", 1)? {
+///         println!("{}: {:?}", piece.application, piece.private);
+///     }
+///     Ok(())
+/// }
+/// ```
+pub fn get_page_metadata<P: AsRef<Path>>(file_path: P, page_number: u32) -> Result<Vec<PagePiece>> {
+    let file_path = file_path.as_ref();
+    let doc = load_document(file_path)?;
+    let page = doc.get_dictionary(page_id(&doc, page_number)?)?;
+    let Some(piece_info) = page.get(b"PieceInfo").ok().and_then(|info| resolve_dict(&doc, info)) else {
+        return Ok(Vec::new());
+    };
+    Ok(piece_info
+        .iter()
+        .filter_map(|(application, data)| {
+            let data = resolve_dict(&doc, data)?;
+            let last_modified = data
+                .get(b"LastModified")
+                .ok()
+                .and_then(|date| date.as_str().ok())
+                .map(|date| String::from_utf8_lossy(date).into_owned());
+            let private = data
+                .get(b"Private")
+                .ok()
+                .and_then(|private| resolve_dict(&doc, private))
+                .map(|private| {
+                    private
+                        .iter()
+                        .map(|(key, value)| {
+                            (String::from_utf8_lossy(key).into_owned(), MetadataValue::from_object_in(&doc, value))
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            Some(PagePiece { application: String::from_utf8_lossy(application).into_owned(), last_modified, private })
+        })
+        .collect())
+}
+
+/// Sets `entries` in the `/Private` dictionary that `application` keeps on page
+/// `page_number` (1-based), writing the result to `output_path`.
+///
+/// Existing entries with other keys are kept. The page-piece dictionary and the page
+/// get a new `/LastModified`, and the document's `ModDate` is refreshed. Use the same
+/// path for input and output to update the file in place.
+///
+/// # Returns
+///
+/// * `Ok(())` if the document was written.
+/// * `Err(Error::PageNotFound)`: If the document has no such page.
+/// * `Err(Error)`: If the file cannot be read, or the output cannot be written.
+///
+/// # Example
+///
+/// ```no_run
+/// use pdf_metadata::set_page_metadata;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     set_page_metadata("scan.pdf", "scan.pdf", 3, "AcmeOCR", &[("Engine", "v4.2"), ("Confidence", "0.97")])?;
+///     Ok(())
+/// }
+/// ```
+pub fn set_page_metadata<P: AsRef<Path>, Q: AsRef<Path>, K: AsRef<str>, V: AsRef<str>>(
+    file_path: P,
+    output_path: Q,
+    page_number: u32,
+    application: &str,
+    entries: &[(K, V)],
+) -> Result<()> {
+    let options = options::current();
+    write_file(file_path.as_ref(), Some(output_path.as_ref()), &options, |doc| {
+        let page_id = page_id(doc, page_number)?;
+        let now = Object::string_literal(pdf_date_now());
+
+        let page = doc.get_dictionary(page_id)?;
+        let mut piece_info = owned_dict(doc, page.get(b"PieceInfo").ok());
+        let mut data = owned_dict(doc, piece_info.get(application.as_bytes()).ok());
+        let mut private = owned_dict(doc, data.get(b"Private").ok());
+        for (key, value) in entries {
+            private.set(key.as_ref(), info_string_object(value.as_ref()));
+        }
+        data.set("Private", private);
+        data.set("LastModified", now.clone());
+        piece_info.set(application, data);
+
+        let page = incremental::dictionary_mut(doc, page_id)?;
+        page.set("PieceInfo", piece_info);
+        page.set("LastModified", now);
+
+        mark_modified(doc, &options)
+    })
+}
+
+pub(crate) fn page_id(doc: &Document, page_number: u32) -> Result<ObjectId> {
+    doc.get_pages().get(&page_number).copied().ok_or(Error::PageNotFound(page_number))
+}
+
+/// A copy of the dictionary `object` refers to; empty when it is missing or not a dictionary.
+fn owned_dict(doc: &Document, object: Option<&Object>) -> Dictionary {
+    object.and_then(|object| resolve_dict(doc, object)).cloned().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::setup_unique_test_dir;
+    use lopdf::dictionary;
+    use std::fs;
+
+    #[test]
+    fn test_set_and_get_page_metadata() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("page_metadata");
+        let file = test_dir.join("scan.pdf");
+        let mut doc = Document::with_version("1.7");
+        let pages_id = doc.new_object_id();
+        let page_id = doc.add_object(dictionary! {
+            "Type" => "Page",
+            "Parent" => pages_id,
+            "PieceInfo" => dictionary! {
+                "Illustrator" => dictionary! { "LastModified" => Object::string_literal("D:20200101000000Z"), "Private" => 7 },
+            },
+        });
+        doc.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! { "Type" => "Pages", "Count" => 1, "Kids" => vec![page_id.into()] }),
+        );
+        let catalog_id = doc.add_object(dictionary! { "Type" => "Catalog", "Pages" => pages_id });
+        doc.trailer.set("Root", catalog_id);
+        doc.save(&file)?;
+        let file_str = file.to_str().unwrap();
+
+        set_page_metadata(file_str, file_str, 1, "AcmeOCR", &[("Engine", "v4.2"), ("Language", "Português")])?;
+        set_page_metadata(file_str, file_str, 1, "AcmeOCR", &[("Engine", "v4.3")])?;
+
+        let pieces = get_page_metadata(file_str, 1)?;
+        assert_eq!(pieces.len(), 2);
+        let ocr = pieces.iter().find(|piece| piece.application == "AcmeOCR").unwrap();
+        assert_eq!(
+            ocr.private,
+            [
+                ("Engine".to_string(), MetadataValue::String("v4.3".to_string())),
+                ("Language".to_string(), MetadataValue::String("Português".to_string())),
+            ]
+        );
+        assert!(ocr.last_modified.as_deref().is_some_and(|date| date.starts_with("D:")));
+        let illustrator = pieces.iter().find(|piece| piece.application == "Illustrator").unwrap();
+        assert_eq!(illustrator.last_modified.as_deref(), Some("D:20200101000000Z"));
+        assert!(illustrator.private.is_empty());
+
+        let doc = Document::load(&file)?;
+        assert!(doc.get_dictionary(page_id)?.has(b"LastModified"));
+        assert!(matches!(get_page_metadata(file_str, 2), Err(Error::PageNotFound(2))));
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,218 @@
+//! Best-effort metadata reading for damaged or unusual documents.
+
+#[cfg(feature = "std-fs")]
+use crate::{Result, decode_document_string, decode_info_string, load_document};
+#[cfg(feature = "std-fs")]
+use lopdf::{Dictionary, Document, Object, StringFormat};
+use std::fmt;
+#[cfg(feature = "std-fs")]
+use std::path::Path;
+
+/// A problem found while decoding one Info entry, or the Info dictionary itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EntryError {
+    /// The affected key, or `None` if the Info dictionary as a whole could not be read.
+    pub key: Option<String>,
+    /// What went wrong.
+    pub message: String,
+}
+
+impl fmt::Display for EntryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.key {
+            Some(key) => write!(f, "{}: {}", key, self.message),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+/// The result of [`get_metadata_partial`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PartialMetadata {
+    /// Every entry that could be decoded, in document order. Entries decoded with
+    /// replacement characters are included here and also reported in `errors`.
+    pub entries: Vec<(String, String)>,
+    /// Per-key decode problems.
+    pub errors: Vec<EntryError>,
+}
+
+impl PartialMetadata {
+    /// Returns `true` if every entry was decoded without problems.
+    pub fn is_complete(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// Retrieves whatever metadata entries can be decoded, along with per-key errors.
+///
+/// Unlike `get_metadata`, problems inside the Info dictionary do not hide the rest
+/// of it: dangling references, unsupported value types and undecodable strings are
+/// reported per key in [`PartialMetadata::errors`] while the remaining entries are
+/// returned. Only failures to open the document itself produce an `Err`.
+///
+/// # Example
+///
+/// ```no_run
+/// use pdf_metadata::get_metadata_partial;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let partial = get_metadata_partial("path/to/messy.pdf")?;
+///     for (key, value) in &partial.entries {
+///         println!("{}: {}", key, value);
+///     }
+///     for error in &partial.errors {
+///         eprintln!("warning: {}", error);
+///     }
+///     Ok(())
+/// }
+/// ```
+#[cfg(feature = "std-fs")]
+pub fn get_metadata_partial<P: AsRef<Path>>(file_path: P) -> Result<PartialMetadata> {
+    let file_path = file_path.as_ref();
+    let doc = load_document(file_path)?;
+    Ok(read_partial(&doc))
+}
+
+#[cfg(feature = "std-fs")]
+pub(crate) fn read_partial(doc: &Document) -> PartialMetadata {
+    let mut partial = PartialMetadata::default();
+    let dictionary_error = |message: String| EntryError { key: None, message };
+
+    let info = match doc.trailer.get(b"Info") {
+        Err(_) => return partial,
+        Ok(Object::Dictionary(dict)) => dict,
+        Ok(Object::Reference(id)) => match doc.get_object(*id).and_then(Object::as_dict) {
+            Ok(dict) => dict,
+            Err(e) => {
+                partial.errors.push(dictionary_error(format!("Info dictionary {} {} R is unreadable: {}", id.0, id.1, e)));
+                return partial;
+            }
+        },
+        Ok(other) => {
+            partial.errors.push(dictionary_error(format!("Info entry is a {} instead of a dictionary", type_name(other))));
+            return partial;
+        }
+    };
+
+    read_entries(doc, info, &mut partial);
+    partial
+}
+
+#[cfg(feature = "std-fs")]
+fn read_entries(doc: &Document, info: &Dictionary, partial: &mut PartialMetadata) {
+    for (key_bytes, value) in info.iter() {
+        let key = String::from_utf8_lossy(key_bytes).into_owned();
+        let mut report = |message: String| partial.errors.push(EntryError { key: Some(key.clone()), message });
+
+        if std::str::from_utf8(key_bytes).is_err() {
+            report("key is not valid UTF-8".to_string());
+        }
+
+        let value = match value {
+            Object::Reference(id) => match doc.get_object(*id) {
+                Ok(resolved) => resolved,
+                Err(e) => {
+                    report(format!("reference {} {} R cannot be resolved: {}", id.0, id.1, e));
+                    continue;
+                }
+            },
+            direct => direct,
+        };
+
+        let decoded = match value {
+            Object::String(bytes, format) => {
+                if let Some(problem) = string_problem(bytes, *format) {
+                    report(problem);
+                }
+                decode_document_string(bytes, *format)
+            }
+            Object::Name(bytes) => String::from_utf8_lossy(bytes).into_owned(),
+            Object::Integer(i) => i.to_string(),
+            Object::Real(f) => f.to_string(),
+            Object::Boolean(b) => b.to_string(),
+            Object::Null => "null".to_string(),
+            other => {
+                report(format!("unsupported {} value", type_name(other)));
+                continue;
+            }
+        };
+        partial.entries.push((key, decoded));
+    }
+}
+
+/// Describes why `bytes` can only be decoded lossily, if they can't be decoded exactly.
+#[cfg(feature = "std-fs")]
+fn string_problem(bytes: &[u8], format: StringFormat) -> Option<String> {
+    if let Some(utf16) = bytes.strip_prefix(&[0xFE, 0xFF]) {
+        if !utf16.len().is_multiple_of(2) {
+            return Some("UTF-16BE string has an odd number of bytes".to_string());
+        }
+        let units: Vec<u16> = utf16.chunks_exact(2).map(|c| u16::from_be_bytes([c[0], c[1]])).collect();
+        return String::from_utf16(&units).err().map(|_| "invalid UTF-16BE string".to_string());
+    }
+    if bytes.starts_with(&[0xFF, 0xFE]) {
+        return None;
+    }
+    if std::str::from_utf8(bytes).is_err() && decode_info_string(bytes, format).contains('\u{FFFD}') {
+        return Some("string contains bytes undefined in PDFDocEncoding; they were replaced".to_string());
+    }
+    None
+}
+
+#[cfg(feature = "std-fs")]
+fn type_name(object: &Object) -> String {
+    String::from_utf8_lossy(object.type_name().unwrap_or(b"object")).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::setup_unique_test_dir;
+    use lopdf::StringFormat;
+    use std::fs;
+
+    #[test]
+    fn test_partial_reports_per_key_errors() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("partial_metadata");
+        let file = test_dir.join("messy.pdf");
+
+        let mut doc = Document::with_version("1.7");
+        let subject_id = doc.add_object(Object::string_literal("Resolved subject"));
+        let mut info_dict = Dictionary::new();
+        info_dict.set("Title", Object::string_literal("Good title"));
+        info_dict.set("Subject", Object::Reference(subject_id));
+        info_dict.set("Author", Object::Reference((999, 0)));
+        info_dict.set("Keywords", Object::String(vec![0xFE, 0xFF, 0x00], StringFormat::Hexadecimal));
+        info_dict.set("Tags", Object::Array(vec![]));
+        info_dict.set("Legacy", Object::String(vec![b'a', 0x9F, b'b'], StringFormat::Literal));
+        info_dict.set("Latin", Object::String(vec![b'a', 0xFF, b'b'], StringFormat::Literal));
+        let info_id = doc.add_object(info_dict);
+        doc.trailer.set("Info", Object::Reference(info_id));
+        doc.save(&file)?;
+
+        let partial = get_metadata_partial(file.to_str().unwrap())?;
+        let keys: Vec<&str> = partial.entries.iter().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(keys, vec!["Title", "Subject", "Keywords", "Legacy", "Latin"]);
+        assert!(partial.entries.contains(&("Latin".to_string(), "aÿb".to_string())));
+        assert!(partial.entries.contains(&("Subject".to_string(), "Resolved subject".to_string())));
+
+        let error_keys: Vec<Option<&str>> = partial.errors.iter().map(|e| e.key.as_deref()).collect();
+        assert_eq!(error_keys, vec![Some("Author"), Some("Keywords"), Some("Tags"), Some("Legacy")]);
+        assert!(!partial.is_complete());
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_partial_reports_unreadable_info_dictionary() {
+        let mut doc = Document::with_version("1.7");
+        doc.trailer.set("Info", Object::Reference((42, 0)));
+        let partial = read_partial(&doc);
+        assert!(partial.entries.is_empty());
+        assert_eq!(partial.errors.len(), 1);
+        assert_eq!(partial.errors[0].key, None);
+    }
+}
@@ -0,0 +1,204 @@
+//! # PDF/A metadata checks
+//!
+//! A PDF/A file declares its conformance level in the XMP packet
+//! (`pdfaid:part` and `pdfaid:conformance`), and every Info entry that has an XMP
+//! equivalent must carry the same value there (ISO 19005-1, 6.7.3). Validators
+//! reject files where, say, `Title` was edited without updating `dc:title`.
+//! [`check_pdfa_metadata`] reports the declared level and those mismatches; it
+//! does not check the rest of the PDF/A requirements (fonts, colour, ...). The
+//! Info/XMP pairs are listed in the [`xmp`] module, whose `reconcile_metadata` fixes
+//! the mismatches.
+
+use crate::xmp::{self, INFO_PROPERTIES, PDFAID_NS, XmpPacket, XmpValue, info_matches};
+use crate::{Result, load_document, read_info_entries};
+use std::fmt;
+use std::path::Path;
+
+/// A metadata problem found by [`check_pdfa_metadata`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PdfAIssue {
+    /// The document has no XMP metadata stream.
+    MissingXmp,
+    /// The XMP packet has no `pdfaid:part`, so the file does not claim PDF/A.
+    MissingIdentification,
+    /// `pdfaid:part` or `pdfaid:conformance` has a value PDF/A does not define.
+    InvalidIdentification { property: String, value: String },
+    /// An Info entry has no XMP equivalent or a different value there.
+    Mismatch {
+        /// The Info key, e.g. `Title`.
+        key: String,
+        /// The XMP property, e.g. `dc:title`.
+        property: String,
+        info: String,
+        /// `None` if the XMP packet lacks the property.
+        xmp: Option<String>,
+    },
+}
+
+impl fmt::Display for PdfAIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PdfAIssue::MissingXmp => write!(f, "No XMP metadata stream"),
+            PdfAIssue::MissingIdentification => write!(f, "XMP has no pdfaid:part"),
+            PdfAIssue::InvalidIdentification { property, value } => write!(f, "Invalid {}: '{}'", property, value),
+            PdfAIssue::Mismatch { key, property, info, xmp: None } => {
+                write!(f, "{} is '{}' but {} is missing", key, info, property)
+            }
+            PdfAIssue::Mismatch { key, property, info, xmp: Some(xmp) } => {
+                write!(f, "{} is '{}' but {} is '{}'", key, info, property, xmp)
+            }
+        }
+    }
+}
+
+/// The PDF/A identification and metadata consistency of a document.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PdfAReport {
+    /// `pdfaid:part` (1 to 4), if declared.
+    pub part: Option<u32>,
+    /// `pdfaid:conformance` (`A`, `B`, `U`, ...), if declared.
+    pub conformance: Option<String>,
+    /// The problems found; empty when the metadata meets PDF/A.
+    pub issues: Vec<PdfAIssue>,
+}
+
+impl PdfAReport {
+    /// The declared level as usually written, e.g. `PDF/A-2b`.
+    pub fn level(&self) -> Option<String> {
+        let part = self.part?;
+        let conformance = self.conformance.as_deref().unwrap_or_default().to_ascii_lowercase();
+        Some(format!("PDF/A-{}{}", part, conformance))
+    }
+
+    /// Whether the document declares PDF/A and no issue was found.
+    pub fn is_compliant(&self) -> bool {
+        self.part.is_some() && self.issues.is_empty()
+    }
+}
+
+/// Checks the PDF/A identification and the Info/XMP consistency of a file.
+///
+/// Info values are compared with the XMP ones after trimming; dates are compared
+/// as instants, so `D:20240102030405+01'00'` matches `2024-01-02T02:04:05Z`.
+/// `Author` must equal the only item of `dc:creator`. XMP properties without an
+/// Info counterpart are not reported: PDF/A only requires the Info entries present
+/// to be mirrored.
+///
+/// # Returns
+///
+/// * `Ok(PdfAReport)`: The declared level and any issues.
+/// * `Err(Error)`: If the file cannot be read, or its XMP packet is not well-formed XML.
+///
+/// # Example
+///
+/// ```no_run
+/// use pdf_metadata::pdfa::check_pdfa_metadata;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let report = check_pdfa_metadata("archive/contract.pdf")?;
+///     println!("{}", report.level().unwrap_or_else(|| "not PDF/A".to_string()));
+///     for issue in &report.issues {
+///         println!("  {}", issue);
+///     }
+///     Ok(())
+/// }
+/// ```
+pub fn check_pdfa_metadata<P: AsRef<Path>>(file_path: P) -> Result<PdfAReport> {
+    let file_path = file_path.as_ref();
+    let doc = load_document(file_path)?;
+    let info = read_info_entries(&doc);
+    let Some(packet) = xmp::read_packet(&doc)? else {
+        return Ok(PdfAReport { issues: vec![PdfAIssue::MissingXmp], ..PdfAReport::default() });
+    };
+    Ok(check_packet(&info, &packet))
+}
+
+fn check_packet(info: &[(String, String)], packet: &XmpPacket) -> PdfAReport {
+    let mut report = PdfAReport::default();
+    let identification = |name: &str| packet.get(PDFAID_NS, name).map(|value| value.to_text().trim().to_string());
+
+    match identification("part") {
+        None => report.issues.push(PdfAIssue::MissingIdentification),
+        Some(part) => match part.parse::<u32>() {
+            Ok(n @ 1..=4) => report.part = Some(n),
+            _ => report.issues.push(PdfAIssue::InvalidIdentification { property: "pdfaid:part".to_string(), value: part }),
+        },
+    }
+    if let Some(conformance) = identification("conformance") {
+        // PDF/A-4 dropped the letter; it only knows the E and F profiles.
+        if matches!(conformance.as_str(), "A" | "B" | "U" | "E" | "F") {
+            report.conformance = Some(conformance);
+        } else {
+            report.issues.push(PdfAIssue::InvalidIdentification {
+                property: "pdfaid:conformance".to_string(),
+                value: conformance,
+            });
+        }
+    }
+
+    for (key, namespace, prefix, name) in INFO_PROPERTIES {
+        let Some((_, info_value)) = info.iter().find(|(k, _)| k == key) else { continue };
+        let xmp_value = packet.get(namespace, name);
+        if !xmp_value.is_some_and(|xmp| info_matches(key, info_value, xmp)) {
+            report.issues.push(PdfAIssue::Mismatch {
+                key: key.to_string(),
+                property: format!("{}:{}", prefix, name),
+                info: info_value.clone(),
+                xmp: xmp_value.map(XmpValue::to_text),
+            });
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{create_minimal_test_pdf, setup_unique_test_dir};
+    use crate::update_entries_in_place;
+    use lopdf::{Dictionary, Document, Object, Stream};
+    use std::fs;
+
+    const PACKET: &str = r#"<x:xmpmeta xmlns:x="adobe:ns:meta/"><rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+<rdf:Description rdf:about="" xmlns:pdfaid="http://www.aiim.org/pdfa/ns/id/" xmlns:dc="http://purl.org/dc/elements/1.1/"
+    xmlns:xmp="http://ns.adobe.com/xap/1.0/" pdfaid:part="2" pdfaid:conformance="B"
+    xmp:CreateDate="2024-01-02T02:04:05Z">
+  <dc:title><rdf:Alt><rdf:li xml:lang="x-default">Annual Report</rdf:li></rdf:Alt></dc:title>
+  <dc:creator><rdf:Seq><rdf:li>Ana</rdf:li></rdf:Seq></dc:creator>
+</rdf:Description></rdf:RDF></x:xmpmeta>"#;
+
+    #[test]
+    fn test_check_pdfa_metadata() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("pdfa_check");
+        let file = test_dir.join("doc.pdf");
+        create_minimal_test_pdf(&file)?;
+        let file_str = file.to_str().unwrap();
+        assert_eq!(check_pdfa_metadata(file_str)?.issues, [PdfAIssue::MissingXmp]);
+
+        let mut doc = Document::load(&file)?;
+        let mut stream = Stream::new(Dictionary::new(), PACKET.as_bytes().to_vec());
+        stream.compress()?;
+        let xmp = doc.add_object(stream);
+        doc.catalog_mut()?.set("Metadata", Object::Reference(xmp));
+        doc.save(&file)?;
+        update_entries_in_place(
+            file_str,
+            &[("Title", "Annual Report"), ("Author", "Ana"), ("CreationDate", "D:20240102030405+01'00'")],
+        )?;
+
+        // Writing refreshes ModDate, which the packet does not carry.
+        let report = check_pdfa_metadata(file_str)?;
+        assert_eq!(report.level().as_deref(), Some("PDF/A-2b"));
+        assert!(matches!(&report.issues[..], [PdfAIssue::Mismatch { key, xmp: None, .. }] if key == "ModDate"));
+
+        update_entries_in_place(file_str, &[("Title", "Draft")])?;
+        let report = check_pdfa_metadata(file_str)?;
+        assert!(!report.is_compliant());
+        assert!(report.issues.iter().any(|issue| issue.to_string() == "Title is 'Draft' but dc:title is 'Annual Report'"));
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+}
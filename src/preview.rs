@@ -0,0 +1,121 @@
+//! Dry runs of metadata writes.
+
+use crate::{Result, SetOptions, apply_entries_with, load_document, options, read_info_entries};
+use std::fmt;
+use std::path::Path;
+
+/// A difference between the current Info dictionary and the one a write would produce.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Change {
+    /// `key` does not exist yet and would be added.
+    Add { key: String, value: String },
+    /// `key` exists with a different value and would be replaced.
+    Overwrite { key: String, old: String, new: String },
+}
+
+impl Change {
+    /// The key the change applies to.
+    pub fn key(&self) -> &str {
+        match self {
+            Change::Add { key, .. } | Change::Overwrite { key, .. } => key,
+        }
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::Add { key, value } => write!(f, "+ {}: {}", key, value),
+            Change::Overwrite { key, old, new } => write!(f, "~ {}: {} -> {}", key, old, new),
+        }
+    }
+}
+
+/// Reports what writing `entries` to the file would change, without writing anything.
+///
+/// The entries are applied to a copy of the document in memory, exactly as the
+/// write functions do, and the resulting Info dictionary is compared with the
+/// current one. Entries that would keep their value are not reported. With the
+/// default options, the refreshed `ModDate` is reported too.
+///
+/// # Returns
+///
+/// * `Ok(Vec<Change>)`: The additions and overwrites, in the order of the resulting
+///   Info dictionary.
+/// * `Err(Error)`: If the file cannot be read.
+///
+/// # Example
+///
+/// ```no_run
+/// use pdf_metadata::preview_changes;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     for change in preview_changes("inbox/scan.pdf", &[("Author", "Records Office")])? {
+///         println!("{}", change);
+///     }
+///     Ok(())
+/// }
+/// ```
+pub fn preview_changes<P: AsRef<Path>, K: AsRef<str>, V: AsRef<str>>(file_path: P, entries: &[(K, V)]) -> Result<Vec<Change>> {
+    let file_path = file_path.as_ref();
+    preview_changes_with_options(file_path, entries, &options::current())
+}
+
+/// Like [`preview_changes`], with `options` controlling the `ModDate` update as in
+/// the `*_with_options` writers.
+pub fn preview_changes_with_options<P: AsRef<Path>, K: AsRef<str>, V: AsRef<str>>(
+    file_path: P,
+    entries: &[(K, V)],
+    options: &SetOptions,
+) -> Result<Vec<Change>> {
+    let file_path = file_path.as_ref();
+    let mut doc = load_document(file_path)?;
+    let before = read_info_entries(&doc);
+    apply_entries_with(&mut doc, entries, options)?;
+    let after = read_info_entries(&doc);
+
+    Ok(after
+        .into_iter()
+        .filter_map(|(key, new)| match before.iter().find(|(k, _)| *k == key) {
+            None => Some(Change::Add { key, value: new }),
+            Some((_, old)) if *old != new => Some(Change::Overwrite { key, old: old.clone(), new }),
+            Some(_) => None,
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{create_minimal_test_pdf, setup_unique_test_dir};
+    use crate::update_metadata_in_place_with_options;
+    use std::fs;
+
+    #[test]
+    fn test_preview_reports_without_writing() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("preview_changes");
+        let file = test_dir.join("doc.pdf");
+        create_minimal_test_pdf(&file)?;
+        let file_str = file.to_str().unwrap();
+        let keep = SetOptions { update_mod_date: false, ..SetOptions::default() };
+        update_metadata_in_place_with_options(file_str, "Title", "Draft", &keep)?;
+        update_metadata_in_place_with_options(file_str, "Author", "Ana", &keep)?;
+        let original = fs::read(&file)?;
+
+        let changes = preview_changes_with_options(file_str, &[("Title", "Final"), ("Author", "Ana"), ("Subject", "Tax")], &keep)?;
+        assert_eq!(
+            changes,
+            [
+                Change::Overwrite { key: "Title".to_string(), old: "Draft".to_string(), new: "Final".to_string() },
+                Change::Add { key: "Subject".to_string(), value: "Tax".to_string() },
+            ]
+        );
+        let with_mod_date = preview_changes(file_str, &[("Author", "Ana")])?;
+        assert_eq!(with_mod_date.iter().map(Change::key).collect::<Vec<_>>(), ["ModDate"]);
+        assert_eq!(fs::read(&file)?, original);
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,155 @@
+//! # Progress reporting
+//!
+//! Frontends that show progress bars implement [`ProgressSink`] and install it with
+//! [`with_progress`] around a single operation, or pass it to
+//! [`batch::run_with_progress`](crate::batch::run_with_progress) for a whole batch.
+//! Every function of this crate that parses or writes a whole document then reports
+//! its [`Phase`]s to the sink; batches also report each finished file. Reads that
+//! take the fast path of `get_metadata` finish before a bar would be drawn and report
+//! nothing.
+//!
+//! ```no_run
+//! use pdf_metadata::progress::{Phase, ProgressSink, with_progress};
+//! use pdf_metadata::update_metadata_in_place;
+//! use std::path::Path;
+//! use std::sync::Arc;
+//!
+//! struct Log;
+//!
+//! impl ProgressSink for Log {
+//!     fn phase(&self, file: Option<&Path>, phase: Phase) {
+//!         eprintln!("{:?}: {:?}", file, phase);
+//!     }
+//! }
+//!
+//! fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     with_progress(Arc::new(Log), || update_metadata_in_place("big.pdf", "Title", "Atlas"))?;
+//!     Ok(())
+//! }
+//! ```
+
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// A step of an operation on one document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Phase {
+    /// The document is being read and parsed.
+    Loading,
+    /// The Info dictionary (or another structure) is being changed in memory.
+    Modifying,
+    /// The document is being serialized and written.
+    Saving,
+}
+
+/// Receives progress events. Both methods do nothing by default.
+///
+/// Batches call the sink from several worker threads at once.
+pub trait ProgressSink: Send + Sync {
+    /// Called when an operation enters `phase`. `file` is the document being
+    /// processed, or `None` for documents held in memory.
+    fn phase(&self, file: Option<&Path>, phase: Phase) {
+        let _ = (file, phase);
+    }
+
+    /// Called by batches after each file, with the number of files finished so far
+    /// (in completion order) and the size of the batch.
+    fn file_finished(&self, file: &Path, finished: usize, total: usize, succeeded: bool) {
+        let _ = (file, finished, total, succeeded);
+    }
+}
+
+struct Context {
+    sink: Arc<dyn ProgressSink>,
+    file: Option<PathBuf>,
+}
+
+thread_local! {
+    static CONTEXT: RefCell<Option<Context>> = const { RefCell::new(None) };
+}
+
+/// Runs `operation` with `sink` receiving the phases of every operation it performs
+/// on the current thread.
+pub fn with_progress<T>(sink: Arc<dyn ProgressSink>, operation: impl FnOnce() -> T) -> T {
+    scoped(Context { sink, file: None }, operation)
+}
+
+/// Like [`with_progress`], attributing phases to `file` until a load names another one.
+#[cfg(feature = "std-fs")]
+pub(crate) fn with_file<T>(sink: Arc<dyn ProgressSink>, file: &Path, operation: impl FnOnce() -> T) -> T {
+    scoped(Context { sink, file: Some(file.to_path_buf()) }, operation)
+}
+
+fn scoped<T>(context: Context, operation: impl FnOnce() -> T) -> T {
+    /// Restores the enclosing context, also when `operation` panics.
+    struct Restore(Option<Context>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            CONTEXT.with(|current| *current.borrow_mut() = self.0.take());
+        }
+    }
+
+    let _restore = Restore(CONTEXT.with(|current| current.borrow_mut().replace(context)));
+    operation()
+}
+
+/// Reports that the document at `file` is being loaded; later phases refer to it.
+pub(crate) fn loading(file: Option<&Path>) {
+    CONTEXT.with(|current| {
+        if let Some(context) = current.borrow_mut().as_mut() {
+            context.file = file.map(Path::to_path_buf);
+            context.sink.phase(file, Phase::Loading);
+        }
+    });
+}
+
+/// Reports `phase` for the document loaded last.
+pub(crate) fn phase(phase: Phase) {
+    CONTEXT.with(|current| {
+        if let Some(context) = current.borrow().as_ref() {
+            context.sink.phase(context.file.as_deref(), phase);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{create_minimal_test_pdf, setup_unique_test_dir};
+    use crate::update_metadata_in_place;
+    use std::fs;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<(Option<PathBuf>, Phase)>>);
+
+    impl ProgressSink for Recorder {
+        fn phase(&self, file: Option<&Path>, phase: Phase) {
+            self.0.lock().unwrap().push((file.map(Path::to_path_buf), phase));
+        }
+    }
+
+    #[test]
+    fn test_with_progress_reports_phases() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("progress_phases");
+        let file = test_dir.join("doc.pdf");
+        create_minimal_test_pdf(&file)?;
+
+        let recorder = Arc::new(Recorder::default());
+        with_progress(recorder.clone(), || update_metadata_in_place(file.to_str().unwrap(), "Title", "Atlas"))?;
+        let events = recorder.0.lock().unwrap().clone();
+        assert_eq!(
+            events,
+            [Phase::Loading, Phase::Modifying, Phase::Saving].map(|phase| (Some(file.clone()), phase))
+        );
+
+        // Outside `with_progress` nothing is reported.
+        update_metadata_in_place(file.to_str().unwrap(), "Title", "Atlas 2")?;
+        assert_eq!(recorder.0.lock().unwrap().len(), 3);
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,636 @@
+//! # Metadata query expressions
+//!
+//! A small filter language for selecting documents by their metadata entries.
+//! Expressions combine key comparisons with boolean operators:
+//!
+//! * `Key == 'value'` / `Key != 'value'`: exact (case-sensitive) comparison.
+//! * `Key ~= 'value'`: case-insensitive substring match.
+//! * `Key ^= 'value'` / `Key $= 'value'`: case-insensitive prefix / suffix match.
+//! * `Key =~ 'pattern'`: regular expression search, e.g. `Producer =~ '^GPL Ghostscript 9\.'`.
+//! * `has(Key)`: true if the key is present, whatever its value.
+//! * `!expr`, `expr && expr`, `expr || expr` and parentheses for grouping.
+//!
+//! Values may be single- or double-quoted, or written bare when they contain no
+//! whitespace or operator characters. Inside quotes, `\` escapes the quote
+//! character and itself; other backslashes are kept, so regular expressions can
+//! be written as they are. A comparison on a key that is absent from
+//! the metadata always evaluates to `false`; use `!has(Key)` to test for absence.
+//!
+//! ```
+//! use pdf_metadata::query;
+//!
+//! let metadata = vec![
+//!     ("Author".to_string(), "Maria Silva".to_string()),
+//!     ("Keywords".to_string(), "tax, 2023".to_string()),
+//! ];
+//! assert!(query::matches(&metadata, "Author ~= 'Silva' && has(Keywords)").unwrap());
+//! assert!(!query::matches(&metadata, "has(Title) || Author == 'silva'").unwrap());
+//! ```
+//!
+//! [`find_pdfs_with_metadata`] applies a query to every PDF under a directory.
+
+#[cfg(feature = "std-fs")]
+use crate::batch::{self, BatchOptions};
+#[cfg(feature = "std-fs")]
+use crate::{Result as CrateResult, get_metadata};
+use regex_lite::Regex;
+use std::fmt;
+use std::path::PathBuf;
+#[cfg(feature = "std-fs")]
+use std::path::Path;
+
+/// Error returned when a query expression cannot be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryError {
+    /// Byte offset in the expression where the problem was detected.
+    pub position: usize,
+    /// Human-readable description of the problem.
+    pub message: String,
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid query at position {}: {}", self.position, self.message)
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+/// Comparison operators supported between a key and a value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    /// `==`: exact match.
+    Eq,
+    /// `!=`: exact mismatch.
+    Ne,
+    /// `~=`: case-insensitive substring.
+    Contains,
+    /// `^=`: case-insensitive prefix.
+    StartsWith,
+    /// `$=`: case-insensitive suffix.
+    EndsWith,
+}
+
+/// A regular expression compiled by [`Query::parse`] for a `=~` term.
+///
+/// Two patterns are equal when they were written the same way.
+#[derive(Debug, Clone)]
+pub struct Pattern(Regex);
+
+impl Pattern {
+    /// Returns the pattern as it was written in the expression.
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+
+    /// Returns true if the pattern matches anywhere in `value`.
+    pub fn is_match(&self, value: &str) -> bool {
+        self.0.is_match(value)
+    }
+}
+
+impl PartialEq for Pattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for Pattern {}
+
+/// Parsed representation of a query expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expr {
+    /// `has(Key)`
+    Has(String),
+    /// `Key <op> 'value'`
+    Compare {
+        key: String,
+        op: CompareOp,
+        value: String,
+    },
+    /// `Key =~ 'pattern'`: regular expression search anywhere in the value; anchor
+    /// it with `^` and `$` to match the whole value.
+    Matches {
+        key: String,
+        pattern: Pattern,
+    },
+    /// `!expr`
+    Not(Box<Expr>),
+    /// `expr && expr`
+    And(Box<Expr>, Box<Expr>),
+    /// `expr || expr`
+    Or(Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    /// Evaluates the expression against a list of `(key, value)` metadata entries.
+    pub fn evaluate(&self, metadata: &[(String, String)]) -> bool {
+        let lookup = |key: &str| metadata.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str());
+        match self {
+            Expr::Has(key) => lookup(key).is_some(),
+            Expr::Compare { key, op, value } => match lookup(key) {
+                Some(actual) => compare(actual, *op, value),
+                None => false,
+            },
+            Expr::Matches { key, pattern } => lookup(key).is_some_and(|actual| pattern.is_match(actual)),
+            Expr::Not(inner) => !inner.evaluate(metadata),
+            Expr::And(lhs, rhs) => lhs.evaluate(metadata) && rhs.evaluate(metadata),
+            Expr::Or(lhs, rhs) => lhs.evaluate(metadata) || rhs.evaluate(metadata),
+        }
+    }
+}
+
+impl Expr {
+    /// Collects the keys of the `has` and comparison terms that hold, leaving out
+    /// terms under `!`, whose truth never makes a document match.
+    fn matched_keys<'a>(&'a self, metadata: &[(String, String)], keys: &mut Vec<&'a str>) {
+        match self {
+            Expr::Has(key) | Expr::Compare { key, .. } | Expr::Matches { key, .. } => {
+                if self.evaluate(metadata) {
+                    keys.push(key);
+                }
+            }
+            Expr::Not(_) => {}
+            Expr::And(lhs, rhs) | Expr::Or(lhs, rhs) => {
+                lhs.matched_keys(metadata, keys);
+                rhs.matched_keys(metadata, keys);
+            }
+        }
+    }
+}
+
+fn compare(actual: &str, op: CompareOp, expected: &str) -> bool {
+    match op {
+        CompareOp::Eq => actual == expected,
+        CompareOp::Ne => actual != expected,
+        CompareOp::Contains => actual.to_lowercase().contains(&expected.to_lowercase()),
+        CompareOp::StartsWith => actual.to_lowercase().starts_with(&expected.to_lowercase()),
+        CompareOp::EndsWith => actual.to_lowercase().ends_with(&expected.to_lowercase()),
+    }
+}
+
+/// A compiled query that can be evaluated repeatedly against different documents.
+///
+/// Parsing once and reusing the `Query` avoids re-tokenizing the expression for
+/// every file when filtering a large set of documents.
+///
+/// # Example
+///
+/// ```
+/// use pdf_metadata::query::Query;
+///
+/// let query = Query::parse("Producer ~= 'ghostscript'").unwrap();
+/// let metadata = vec![("Producer".to_string(), "GPL Ghostscript 10.0".to_string())];
+/// assert!(query.matches(&metadata));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Query {
+    expr: Expr,
+}
+
+impl Query {
+    /// Parses a query expression.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Query)` if the expression is well-formed.
+    /// * `Err(QueryError)` describing the first syntax problem found.
+    pub fn parse(expression: &str) -> Result<Query, QueryError> {
+        let tokens = tokenize(expression)?;
+        let mut parser = Parser { tokens, pos: 0, end: expression.len() };
+        let expr = parser.parse_or()?;
+        if let Some((token, position)) = parser.tokens.get(parser.pos) {
+            return Err(QueryError {
+                position: *position,
+                message: format!("unexpected {}", token.describe()),
+            });
+        }
+        Ok(Query { expr })
+    }
+
+    /// Returns the parsed expression tree.
+    pub fn expr(&self) -> &Expr {
+        &self.expr
+    }
+
+    /// Evaluates the query against a list of `(key, value)` metadata entries.
+    pub fn matches(&self, metadata: &[(String, String)]) -> bool {
+        self.expr.evaluate(metadata)
+    }
+
+    /// Returns the entries of `metadata` that satisfy a term of the query, in
+    /// metadata order: for `Producer ~= 'ghostscript'`, the `Producer` entry.
+    pub fn matched_entries(&self, metadata: &[(String, String)]) -> Vec<(String, String)> {
+        let mut keys = Vec::new();
+        self.expr.matched_keys(metadata, &mut keys);
+        metadata.iter().filter(|(key, _)| keys.contains(&key.as_str())).cloned().collect()
+    }
+}
+
+impl std::str::FromStr for Query {
+    type Err = QueryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Query::parse(s)
+    }
+}
+
+/// Parses `expression` and evaluates it against `metadata` in one step.
+///
+/// # Arguments
+///
+/// * `metadata`: The `(key, value)` entries, as returned by `get_metadata`.
+/// * `expression`: The query expression, e.g. `"Author ~= 'Silva' && has(Keywords)"`.
+///
+/// # Returns
+///
+/// * `Ok(bool)`: Whether the metadata satisfies the expression.
+/// * `Err(QueryError)`: If the expression is malformed.
+pub fn matches(metadata: &[(String, String)], expression: &str) -> Result<bool, QueryError> {
+    Ok(Query::parse(expression)?.matches(metadata))
+}
+
+/// A document found by [`find_pdfs_with_metadata`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SearchMatch {
+    pub path: PathBuf,
+    /// The entries that satisfied the query, as [`Query::matched_entries`] returns them.
+    pub entries: Vec<(String, String)>,
+}
+
+/// Scans the PDFs under `dir`, subdirectories included, and returns those whose
+/// metadata satisfies `query`, sorted by path.
+///
+/// Files are read in parallel through the [batch engine](crate::batch). Files that
+/// cannot be read (damaged, encrypted, not really PDFs) never match.
+///
+/// # Returns
+///
+/// * `Ok(Vec<SearchMatch>)`: The matching documents.
+/// * `Err(Error)`: If `dir` or one of its subdirectories cannot be listed.
+///
+/// # Example
+///
+/// ```no_run
+/// use pdf_metadata::query::{Query, find_pdfs_with_metadata};
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let query = Query::parse("Producer ~= 'Ghostscript'")?;
+///     for found in find_pdfs_with_metadata("/srv/archive", &query)? {
+///         println!("{}: {:?}", found.path.display(), found.entries);
+///     }
+///     Ok(())
+/// }
+/// ```
+#[cfg(feature = "std-fs")]
+pub fn find_pdfs_with_metadata<P: AsRef<Path>>(dir: P, query: &Query) -> CrateResult<Vec<SearchMatch>> {
+    let files = batch::collect_pdfs(dir, true)?;
+    Ok(batch::run(&files, &BatchOptions::default(), |path| get_metadata(path))
+        .into_iter()
+        .filter_map(|outcome| {
+            let metadata = outcome.result.ok()?;
+            query
+                .matches(&metadata)
+                .then(|| SearchMatch { entries: query.matched_entries(&metadata), path: outcome.path })
+        })
+        .collect())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Word(String),
+    Quoted(String),
+    Op(CompareOp),
+    Match,
+    And,
+    Or,
+    Not,
+    LParen,
+    RParen,
+}
+
+impl Token {
+    fn describe(&self) -> String {
+        match self {
+            Token::Word(w) => format!("'{}'", w),
+            Token::Quoted(q) => format!("string \"{}\"", q),
+            Token::Op(_) | Token::Match => "comparison operator".to_string(),
+            Token::And => "'&&'".to_string(),
+            Token::Or => "'||'".to_string(),
+            Token::Not => "'!'".to_string(),
+            Token::LParen => "'('".to_string(),
+            Token::RParen => "')'".to_string(),
+        }
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    !c.is_whitespace() && !"()!&|=~^$'\"".contains(c)
+}
+
+fn tokenize(input: &str) -> Result<Vec<(Token, usize)>, QueryError> {
+    let mut tokens = Vec::new();
+    let mut chars = input.char_indices().peekable();
+
+    while let Some(&(pos, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+        let two: String = input[pos..].chars().take(2).collect();
+        let token = match two.as_str() {
+            "&&" => Some(Token::And),
+            "||" => Some(Token::Or),
+            "==" => Some(Token::Op(CompareOp::Eq)),
+            "!=" => Some(Token::Op(CompareOp::Ne)),
+            "~=" => Some(Token::Op(CompareOp::Contains)),
+            "^=" => Some(Token::Op(CompareOp::StartsWith)),
+            "$=" => Some(Token::Op(CompareOp::EndsWith)),
+            "=~" => Some(Token::Match),
+            _ => None,
+        };
+        if let Some(token) = token {
+            chars.next();
+            chars.next();
+            tokens.push((token, pos));
+            continue;
+        }
+
+        match c {
+            '(' => {
+                chars.next();
+                tokens.push((Token::LParen, pos));
+            }
+            ')' => {
+                chars.next();
+                tokens.push((Token::RParen, pos));
+            }
+            '!' => {
+                chars.next();
+                tokens.push((Token::Not, pos));
+            }
+            '\'' | '"' => {
+                chars.next();
+                let mut literal = String::new();
+                let mut closed = false;
+                while let Some((_, ch)) = chars.next() {
+                    if ch == '\\' {
+                        // Only quotes and backslashes are escaped, so regex escapes like `\d` pass through.
+                        match chars.next() {
+                            Some((_, escaped)) if escaped == c || escaped == '\\' => literal.push(escaped),
+                            Some((_, other)) => {
+                                literal.push('\\');
+                                literal.push(other);
+                            }
+                            None => {}
+                        }
+                    } else if ch == c {
+                        closed = true;
+                        break;
+                    } else {
+                        literal.push(ch);
+                    }
+                }
+                if !closed {
+                    return Err(QueryError { position: pos, message: "unterminated string".to_string() });
+                }
+                tokens.push((Token::Quoted(literal), pos));
+            }
+            c if is_word_char(c) => {
+                let mut word = String::new();
+                while let Some(&(_, ch)) = chars.peek() {
+                    if !is_word_char(ch) {
+                        break;
+                    }
+                    word.push(ch);
+                    chars.next();
+                }
+                tokens.push((Token::Word(word), pos));
+            }
+            other => {
+                return Err(QueryError { position: pos, message: format!("unexpected character '{}'", other) });
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(t, _)| t)
+    }
+
+    fn position(&self) -> usize {
+        self.tokens.get(self.pos).map(|(_, p)| *p).unwrap_or(self.end)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).map(|(t, _)| t.clone());
+        self.pos += 1;
+        token
+    }
+
+    fn error(&self, message: impl Into<String>) -> QueryError {
+        QueryError { position: self.position(), message: message.into() }
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), QueryError> {
+        match self.peek() {
+            Some(t) if *t == expected => {
+                self.pos += 1;
+                Ok(())
+            }
+            Some(t) => Err(self.error(format!("expected {}, found {}", expected.describe(), t.describe()))),
+            None => Err(self.error(format!("expected {}, found end of expression", expected.describe()))),
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, QueryError> {
+        let mut lhs = self.parse_and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.pos += 1;
+            let rhs = self.parse_and()?;
+            lhs = Expr::Or(Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, QueryError> {
+        let mut lhs = self.parse_unary()?;
+        while self.peek() == Some(&Token::And) {
+            self.pos += 1;
+            let rhs = self.parse_unary()?;
+            lhs = Expr::And(Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, QueryError> {
+        if self.peek() == Some(&Token::Not) {
+            self.pos += 1;
+            return Ok(Expr::Not(Box::new(self.parse_unary()?)));
+        }
+        self.parse_primary()
+    }
+
+    fn parse_key(&mut self) -> Result<String, QueryError> {
+        match self.peek().cloned() {
+            Some(Token::Word(w)) | Some(Token::Quoted(w)) => {
+                self.pos += 1;
+                Ok(w)
+            }
+            Some(t) => Err(self.error(format!("expected a metadata key, found {}", t.describe()))),
+            None => Err(self.error("expected a metadata key, found end of expression")),
+        }
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, QueryError> {
+        if self.peek() == Some(&Token::LParen) {
+            self.pos += 1;
+            let inner = self.parse_or()?;
+            self.expect(Token::RParen)?;
+            return Ok(inner);
+        }
+
+        if let Some(Token::Word(w)) = self.peek()
+            && w == "has"
+            && self.tokens.get(self.pos + 1).map(|(t, _)| t) == Some(&Token::LParen)
+        {
+            self.pos += 2;
+            let key = self.parse_key()?;
+            self.expect(Token::RParen)?;
+            return Ok(Expr::Has(key));
+        }
+
+        let key = self.parse_key()?;
+        let op = match self.peek() {
+            Some(Token::Op(op)) => Some(*op),
+            Some(Token::Match) => None,
+            Some(t) => return Err(self.error(format!("expected a comparison operator, found {}", t.describe()))),
+            None => return Err(self.error("expected a comparison operator, found end of expression")),
+        };
+        self.pos += 1;
+        let value = match self.next() {
+            Some(Token::Word(w)) | Some(Token::Quoted(w)) => w,
+            _ => {
+                self.pos -= 1;
+                return Err(self.error("expected a value after the comparison operator"));
+            }
+        };
+        match op {
+            Some(op) => Ok(Expr::Compare { key, op, value }),
+            None => match Regex::new(&value) {
+                Ok(regex) => Ok(Expr::Matches { key, pattern: Pattern(regex) }),
+                Err(e) => {
+                    self.pos -= 1;
+                    Err(self.error(format!("invalid regular expression: {}", e)))
+                }
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{create_minimal_test_pdf, setup_unique_test_dir};
+    use crate::update_metadata_in_place;
+    use std::fs;
+
+    fn sample() -> Vec<(String, String)> {
+        vec![
+            ("Author".to_string(), "Maria Silva".to_string()),
+            ("Producer".to_string(), "GPL Ghostscript 10.02".to_string()),
+            ("Keywords".to_string(), "tax; invoices".to_string()),
+        ]
+    }
+
+    #[test]
+    fn test_comparison_operators() {
+        let metadata = sample();
+        assert!(matches(&metadata, "Author == 'Maria Silva'").unwrap());
+        assert!(!matches(&metadata, "Author == 'maria silva'").unwrap());
+        assert!(matches(&metadata, "Author != \"Someone\"").unwrap());
+        assert!(matches(&metadata, "Producer ~= ghostscript").unwrap());
+        assert!(matches(&metadata, "Producer ^= 'gpl'").unwrap());
+        assert!(matches(&metadata, "Producer $= '10.02'").unwrap());
+    }
+
+    #[test]
+    fn test_boolean_combinations_and_has() {
+        let metadata = sample();
+        assert!(matches(&metadata, "Author ~= 'Silva' && has(Keywords)").unwrap());
+        assert!(matches(&metadata, "has(Title) || Author ~= 'maria'").unwrap());
+        assert!(matches(&metadata, "!has(Title) && !(Author == 'X' || Producer == 'Y')").unwrap());
+        assert!(!matches(&metadata, "Title != 'anything'").unwrap(), "Comparisons on absent keys are false");
+    }
+
+    #[test]
+    fn test_and_binds_tighter_than_or() {
+        let metadata = sample();
+        // Parsed as has(Title) || (has(Author) && has(Keywords))
+        assert!(matches(&metadata, "has(Title) || has(Author) && has(Keywords)").unwrap());
+        // Parsed as (has(Author) && has(Title)) || has(Subject)
+        assert!(!matches(&metadata, "has(Author) && has(Title) || has(Subject)").unwrap());
+    }
+
+    #[test]
+    fn test_regex_and_matched_entries() {
+        let metadata = sample();
+        assert!(matches(&metadata, r"Producer =~ 'Ghostscript 10\.\d+$'").unwrap());
+        assert!(!matches(&metadata, "Producer =~ '^Ghostscript'").unwrap());
+        let err = Query::parse("Producer =~ '(unclosed'").unwrap_err();
+        assert_eq!(err.position, 12);
+
+        let query = Query::parse(r"Producer =~ 'Ghostscript \d+'").unwrap();
+        match query.expr() {
+            Expr::Matches { key, pattern } => assert_eq!((key.as_str(), pattern.as_str()), ("Producer", r"Ghostscript \d+")),
+            expr => panic!("unexpected {:?}", expr),
+        }
+        assert_eq!(query, Query::parse(r#"Producer =~ "Ghostscript \d+""#).unwrap());
+        assert_ne!(query, Query::parse("Producer =~ 'Ghostscript'").unwrap());
+
+        let query = Query::parse("Producer ~= 'ghostscript' && (has(Keywords) || has(Title)) && !has(Subject)").unwrap();
+        let keys: Vec<String> = query.matched_entries(&metadata).into_iter().map(|(key, _)| key).collect();
+        assert_eq!(keys, ["Producer", "Keywords"]);
+    }
+
+    #[test]
+    fn test_find_pdfs_with_metadata() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("query_find");
+        fs::create_dir(test_dir.join("scans"))?;
+        for (name, producer) in [("a.pdf", "GPL Ghostscript 9.50"), ("scans/b.pdf", "Acrobat"), ("scans/c.pdf", "ghostscript")] {
+            let file = test_dir.join(name);
+            create_minimal_test_pdf(&file)?;
+            update_metadata_in_place(file.to_str().unwrap(), "Producer", producer)?;
+        }
+        fs::write(test_dir.join("broken.pdf"), b"not a pdf")?;
+
+        let found = find_pdfs_with_metadata(&test_dir, &Query::parse("Producer ~= 'Ghostscript'")?)?;
+        let paths: Vec<PathBuf> = found.iter().map(|found| found.path.clone()).collect();
+        assert_eq!(paths, [test_dir.join("a.pdf"), test_dir.join("scans/c.pdf")]);
+        assert_eq!(found[0].entries, [("Producer".to_string(), "GPL Ghostscript 9.50".to_string())]);
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_errors_report_position() {
+        let err = Query::parse("Author == ").unwrap_err();
+        assert_eq!(err.position, 10);
+        let err = Query::parse("Author ~= 'unterminated").unwrap_err();
+        assert_eq!(err.position, 10);
+        assert!(Query::parse("has(Author").is_err());
+        assert!(Query::parse("Author 'x'").is_err());
+        assert!(Query::parse("has(Author) has(Title)").is_err());
+    }
+}
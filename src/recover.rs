@@ -0,0 +1,206 @@
+//! Salvaging metadata from damaged files.
+//!
+//! `Document::load` trusts the cross-reference table, so a truncated download or a
+//! file patched by a careless tool fails to open even when the Info dictionary itself
+//! is intact. The recovery here ignores the table: it scans the raw bytes for
+//! `N G obj ... endobj` objects and for trailer dictionaries, keeping the last copy
+//! of each object as an incremental update would.
+
+#[cfg(feature = "std-fs")]
+use crate::fast_info::{parse_dictionary, parse_object};
+#[cfg(feature = "std-fs")]
+use crate::stats::{self, Counter};
+#[cfg(feature = "std-fs")]
+use crate::{Error, Result, get_metadata, read_info_entries};
+use lopdf::ObjectId;
+#[cfg(feature = "std-fs")]
+use lopdf::{Dictionary, Document, Object};
+#[cfg(feature = "std-fs")]
+use std::collections::BTreeMap;
+#[cfg(feature = "std-fs")]
+use std::fs;
+#[cfg(feature = "std-fs")]
+use std::path::Path;
+
+/// Info keys whose presence marks an untyped dictionary as a likely Info dictionary.
+#[cfg(feature = "std-fs")]
+const INFO_KEYS: [&[u8]; 8] =
+    [b"Title", b"Author", b"Subject", b"Keywords", b"Creator", b"Producer", b"CreationDate", b"ModDate"];
+
+/// Retrieves all metadata entries, recovering them from the raw file when the
+/// cross-reference table is damaged.
+///
+/// Files that open normally return the same entries as [`get_metadata`]. When
+/// parsing fails, every indirect object and trailer still found in the bytes is
+/// salvaged; the Info dictionary is the one referenced by the newest trailer, or,
+/// when no trailer survived, the last untyped dictionary carrying standard Info keys
+/// such as `Title` or `Producer`.
+///
+/// # Returns
+///
+/// * `Ok(Vec<(String, String)>)`: The recovered entries; empty if the objects
+///   survived but no Info dictionary did.
+/// * `Err(Error::Encrypted)`: If the recovered trailer references an encryption dictionary.
+/// * `Err(Error)`: If the file cannot be read, or no object could be salvaged from it.
+///
+/// # Example
+///
+/// ```no_run
+/// use pdf_metadata::get_metadata_lenient;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     for (key, value) in get_metadata_lenient("downloads/truncated.pdf")? {
+///         println!("{}: {}", key, value);
+///     }
+///     Ok(())
+/// }
+/// ```
+#[cfg(feature = "std-fs")]
+pub fn get_metadata_lenient<P: AsRef<Path>>(file_path: P) -> Result<Vec<(String, String)>> {
+    let file_path = file_path.as_ref();
+    let error = match get_metadata(file_path) {
+        Err(error @ Error::Parse(_)) => error,
+        result => return result,
+    };
+    let bytes = fs::read(file_path)?;
+    recover_info_entries(&bytes).unwrap_or(Err(error))
+}
+
+/// Rebuilds a document from the objects and trailers found in `bytes` and reads
+/// its Info entries. Returns `None` if no object was found.
+#[cfg(feature = "std-fs")]
+fn recover_info_entries(bytes: &[u8]) -> Option<Result<Vec<(String, String)>>> {
+    let mut objects = BTreeMap::new();
+    let mut trailers = Vec::new();
+    for (offset, id) in object_headers(bytes) {
+        let Some(object) = parse_object(&bytes[offset..], id) else { continue };
+        if let Object::Stream(stream) = &object
+            && stream.dict.get(b"Type").and_then(Object::as_name).is_ok_and(|name| name == b"XRef")
+        {
+            trailers.push((offset, stream.dict.clone()));
+        }
+        // Later copies replace earlier ones, as in an incremental update.
+        objects.insert(id, (offset, object));
+    }
+    if objects.is_empty() {
+        return None;
+    }
+    trailers.extend(trailer_dictionaries(bytes));
+    trailers.sort_by_key(|(offset, _)| *offset);
+    stats::record(Counter::DocumentScanned);
+
+    if trailers.last().is_some_and(|(_, trailer)| trailer.has(b"Encrypt")) {
+        return Some(Err(Error::Encrypted));
+    }
+    let is_dictionary = |id: &ObjectId| matches!(objects.get(id), Some((_, Object::Dictionary(_))));
+    let info_id = trailers
+        .iter()
+        .rev()
+        .filter_map(|(_, trailer)| trailer.get(b"Info").and_then(Object::as_reference).ok())
+        .find(is_dictionary)
+        .or_else(|| {
+            objects
+                .iter()
+                .filter(|(_, (_, object))| object.as_dict().is_ok_and(looks_like_info))
+                .max_by_key(|(_, (offset, _))| *offset)
+                .map(|(id, _)| *id)
+        });
+
+    let mut document = Document::new();
+    if let Some(info_id) = info_id {
+        document.trailer.set("Info", Object::Reference(info_id));
+    }
+    document.objects.extend(objects.into_iter().map(|(id, (_, object))| (id, object)));
+    Some(Ok(read_info_entries(&document)))
+}
+
+/// Finds the offsets of every `N G obj` header in `bytes`.
+pub(crate) fn object_headers(bytes: &[u8]) -> Vec<(usize, ObjectId)> {
+    let mut headers = Vec::new();
+    for keyword in bytes.windows(3).enumerate().filter(|(_, w)| *w == b"obj").map(|(at, _)| at) {
+        if bytes.get(keyword + 3).is_some_and(|b| b.is_ascii_alphanumeric()) {
+            continue;
+        }
+        // Walk back over "<number> <generation> ".
+        let Some(generation_end) = skip_back(bytes, keyword, is_whitespace) else { continue };
+        let Some(generation_start) = skip_back(bytes, generation_end, |b| b.is_ascii_digit()) else { continue };
+        let Some(number_end) = skip_back(bytes, generation_start, is_whitespace) else { continue };
+        let Some(number_start) = skip_back(bytes, number_end, |b| b.is_ascii_digit()) else { continue };
+        if number_start > 0 && bytes[number_start - 1].is_ascii_alphanumeric() {
+            continue;
+        }
+        let number = std::str::from_utf8(&bytes[number_start..number_end]).ok().and_then(|n| n.parse().ok());
+        let generation = std::str::from_utf8(&bytes[generation_start..generation_end]).ok().and_then(|g| g.parse().ok());
+        if let (Some(number), Some(generation)) = (number, generation) {
+            headers.push((number_start, (number, generation)));
+        }
+    }
+    headers
+}
+
+/// Moves back from `end` over bytes matching `class`; returns the new position if
+/// at least one byte matched.
+fn skip_back(bytes: &[u8], end: usize, class: impl Fn(u8) -> bool) -> Option<usize> {
+    let start = end - bytes[..end].iter().rev().take_while(|b| class(**b)).count();
+    (start < end).then_some(start)
+}
+
+fn is_whitespace(byte: u8) -> bool {
+    matches!(byte, b' ' | b'\t' | b'\r' | b'\n' | b'\x0C' | b'\0')
+}
+
+/// Parses the dictionary after every `trailer` keyword, up to the following `startxref`.
+#[cfg(feature = "std-fs")]
+fn trailer_dictionaries(bytes: &[u8]) -> Vec<(usize, Dictionary)> {
+    let mut trailers = Vec::new();
+    for at in bytes.windows(7).enumerate().filter(|(_, w)| *w == b"trailer").map(|(at, _)| at) {
+        let rest = &bytes[at + 7..];
+        let end = rest.windows(9).position(|w| w == b"startxref").unwrap_or(rest.len());
+        if let Some(trailer) = parse_dictionary(&rest[..end]) {
+            trailers.push((at, trailer));
+        }
+    }
+    trailers
+}
+
+/// Whether `dict` has no `/Type` and at least one standard Info key.
+#[cfg(feature = "std-fs")]
+pub(crate) fn looks_like_info(dict: &Dictionary) -> bool {
+    !dict.has(b"Type") && INFO_KEYS.iter().any(|key| dict.has(key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{create_classic_xref_test_pdf, setup_unique_test_dir};
+    use crate::update_entries_in_place;
+
+    #[test]
+    fn test_recovers_info_despite_broken_xref() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("recover_lenient");
+        let file = test_dir.join("doc.pdf");
+        create_classic_xref_test_pdf(&file)?;
+        let file_str = file.to_str().unwrap();
+        update_entries_in_place(file_str, &[("Title", "Minutes"), ("Author", "Ana")])?;
+        let intact = get_metadata(file_str)?;
+        assert_eq!(get_metadata_lenient(file_str)?, intact);
+
+        // Drop the cross-reference table, leaving startxref pointing into the trailer.
+        let bytes = fs::read(&file)?;
+        let table = bytes.windows(5).position(|w| w == b"\nxref").unwrap() + 1;
+        let trailer = bytes.windows(7).position(|w| w == b"trailer").unwrap();
+        fs::write(&file, [&bytes[..table], &bytes[trailer..]].concat())?;
+        assert!(get_metadata(file_str).is_err());
+        assert_eq!(get_metadata_lenient(file_str)?, intact);
+
+        // Without any trailer, the Info dictionary is recognised by its keys.
+        fs::write(&file, &bytes[..table])?;
+        assert_eq!(get_metadata_lenient(file_str)?, intact);
+
+        fs::write(&file, b"not a pdf")?;
+        assert!(matches!(get_metadata_lenient(file_str), Err(Error::Parse(_))));
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+}
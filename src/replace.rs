@@ -0,0 +1,119 @@
+//! Replacing a file with its rewritten copy.
+//!
+//! On Unix a rename over an open file always succeeds. On Windows it fails while a
+//! viewer, an indexer or an antivirus scanner holds the destination open without
+//! `FILE_SHARE_DELETE`; those handles are usually released within moments, so the
+//! rename is retried with backoff. If it still fails, the new contents are copied
+//! over the existing file, as `ReplaceFileW` does when it cannot swap the files: the
+//! destination keeps its identity, attributes and ACLs, at the cost of atomicity.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+
+/// Delays between rename attempts; about three seconds in total.
+const RETRY_DELAYS_MS: [u64; 6] = [25, 50, 100, 250, 750, 2000];
+
+/// How a file was replaced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Replacement {
+    /// `source` was renamed over `destination`.
+    Renamed,
+    /// The rename kept failing and `source` was copied over `destination`, then removed.
+    Copied,
+}
+
+/// Moves `source` over `destination`, retrying and falling back to a copy when the
+/// destination is locked by another process.
+pub(crate) fn replace_file(source: &Path, destination: &Path) -> io::Result<Replacement> {
+    let delays = RETRY_DELAYS_MS.map(Duration::from_millis);
+    replace_with(|| fs::rename(source, destination), || copy_over(source, destination), &delays)
+}
+
+fn replace_with<R, C>(mut rename: R, copy: C, delays: &[Duration]) -> io::Result<Replacement>
+where
+    R: FnMut() -> io::Result<()>,
+    C: FnOnce() -> io::Result<()>,
+{
+    let mut delays = delays.iter();
+    loop {
+        match rename() {
+            Ok(()) => return Ok(Replacement::Renamed),
+            Err(err) if is_locked(&err) => match delays.next() {
+                Some(delay) => thread::sleep(*delay),
+                None => return copy().map(|()| Replacement::Copied).map_err(|_| err),
+            },
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+/// Overwrites `destination` with the contents of `source` and removes `source`.
+fn copy_over(source: &Path, destination: &Path) -> io::Result<()> {
+    fs::copy(source, destination)?;
+    fs::OpenOptions::new().write(true).open(destination)?.sync_all()?;
+    fs::remove_file(source)
+}
+
+/// Whether `err` means another process holds the file open: a sharing or lock
+/// violation, or the access denial Windows reports for files pending deletion.
+fn is_locked(err: &io::Error) -> bool {
+    const ERROR_ACCESS_DENIED: i32 = 5;
+    const ERROR_SHARING_VIOLATION: i32 = 32;
+    const ERROR_LOCK_VIOLATION: i32 = 33;
+    cfg!(windows)
+        && matches!(err.raw_os_error(), Some(ERROR_ACCESS_DENIED | ERROR_SHARING_VIOLATION | ERROR_LOCK_VIOLATION))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn locked() -> io::Error {
+        if cfg!(windows) { io::Error::from_raw_os_error(32) } else { io::Error::other("locked") }
+    }
+
+    #[test]
+    fn test_retries_then_copies_locked_destination() {
+        let delays = [Duration::ZERO; 3];
+        let mut attempts = 0;
+        let result = replace_with(
+            || {
+                attempts += 1;
+                Err(io::Error::from(io::ErrorKind::NotFound))
+            },
+            || panic!("copy must not run for other errors"),
+            &delays,
+        );
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::NotFound);
+        assert_eq!(attempts, 1);
+
+        if cfg!(windows) {
+            let mut attempts = 0;
+            let result = replace_with(
+                || {
+                    attempts += 1;
+                    if attempts < 3 { Err(locked()) } else { Ok(()) }
+                },
+                || panic!("rename succeeded in time"),
+                &delays,
+            );
+            assert_eq!(result.unwrap(), Replacement::Renamed);
+            assert_eq!(replace_with(|| Err(locked()), || Ok(()), &delays).unwrap(), Replacement::Copied);
+        } else {
+            // Renames do not fail on open files here, so nothing is retried.
+            assert!(replace_with(|| Err(locked()), || panic!("no fallback"), &delays).is_err());
+        }
+
+        let test_dir = crate::test_support::setup_unique_test_dir("replace_copy_over");
+        let (source, destination) = (test_dir.join("new.tmp"), test_dir.join("doc.pdf"));
+        fs::write(&source, b"new").unwrap();
+        fs::write(&destination, b"old contents").unwrap();
+        copy_over(&source, &destination).unwrap();
+        assert_eq!(fs::read(&destination).unwrap(), b"new");
+        assert!(!source.exists());
+        fs::remove_dir_all(test_dir).unwrap();
+    }
+}
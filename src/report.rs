@@ -0,0 +1,226 @@
+//! # Metadata reports
+//!
+//! Produces human-readable Markdown or HTML summaries of one or more PDFs, listing
+//! every Info entry together with basic validation findings. The output is meant to
+//! be shipped alongside delivered documents.
+//!
+//! ```no_run
+//! use pdf_metadata::report::{self, ReportFormat};
+//!
+//! fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let reports = report::report_dir("path/to/delivery", true)?;
+//!     std::fs::write("metadata.html", report::render(&reports, ReportFormat::Html))?;
+//!     Ok(())
+//! }
+//! ```
+
+use crate::batch::{self, BatchOptions, FileOutcome};
+use crate::value::DATE_KEYS;
+use crate::{Error, Result, get_metadata, parse_pdf_date};
+use std::path::{Path, PathBuf};
+
+/// Entries every delivered document is expected to carry.
+const RECOMMENDED_KEYS: [&str; 2] = ["Title", "Author"];
+
+/// Output format of [`render`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ReportFormat {
+    Markdown,
+    Html,
+}
+
+impl std::str::FromStr for ReportFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "md" | "markdown" => Ok(ReportFormat::Markdown),
+            "html" | "htm" => Ok(ReportFormat::Html),
+            other => Err(Error::UnsupportedFormat(format!("report format '{}'", other))),
+        }
+    }
+}
+
+/// The metadata and validation findings of a single file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FileReport {
+    /// The `(key, value)` entries of the Info dictionary, in document order.
+    pub entries: Vec<(String, String)>,
+    /// Human-readable validation findings; empty when the metadata looks complete.
+    pub issues: Vec<String>,
+}
+
+/// Reads the metadata of `path` and validates it.
+///
+/// # Returns
+///
+/// * `Ok(FileReport)`: The entries and any findings.
+/// * `Err(Error)`: If the file cannot be read.
+pub fn report_file(path: &Path) -> Result<FileReport> {
+    let path_str = path.to_str().ok_or_else(|| {
+        std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("Path is not valid UTF-8: {}", path.display()))
+    })?;
+    let entries = get_metadata(path_str)?;
+    let issues = validate(&entries);
+    Ok(FileReport { entries, issues })
+}
+
+/// Builds a report for every PDF in `dir`, using the batch engine.
+///
+/// # Returns
+///
+/// * `Ok(Vec<FileOutcome<FileReport>>)`: One outcome per PDF, sorted by path.
+/// * `Err(Error)`: If `dir` cannot be scanned. Unreadable PDFs are reported per file instead.
+pub fn report_dir<P: AsRef<Path>>(dir: P, recursive: bool) -> Result<Vec<FileOutcome<FileReport>>> {
+    let files = batch::collect_pdfs(dir, recursive)?;
+    Ok(report_files(&files))
+}
+
+/// Builds a report for each of `paths`, in input order.
+pub fn report_files(paths: &[PathBuf]) -> Vec<FileOutcome<FileReport>> {
+    batch::run(paths, &BatchOptions::default(), report_file)
+}
+
+/// Renders the reports as a single Markdown or HTML document.
+pub fn render(reports: &[FileOutcome<FileReport>], format: ReportFormat) -> String {
+    match format {
+        ReportFormat::Markdown => render_markdown(reports),
+        ReportFormat::Html => render_html(reports),
+    }
+}
+
+fn validate(entries: &[(String, String)]) -> Vec<String> {
+    let value = |key: &str| entries.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str());
+    let mut issues = Vec::new();
+
+    for key in RECOMMENDED_KEYS {
+        if value(key).is_none_or(|v| v.trim().is_empty()) {
+            issues.push(format!("Missing {}", key));
+        }
+    }
+    for key in DATE_KEYS {
+        if let Some(date) = value(key)
+            && parse_pdf_date(date).is_err()
+        {
+            issues.push(format!("{} is not a valid PDF date: {}", key, date));
+        }
+    }
+    issues
+}
+
+fn render_markdown(reports: &[FileOutcome<FileReport>]) -> String {
+    let cell = |text: &str| text.replace('|', "\\|").replace(['\r', '\n'], " ");
+    let mut out = String::from("# Metadata Report\n");
+
+    for outcome in reports {
+        out.push_str(&format!("\n## {}\n\n", outcome.path.display()));
+        match &outcome.result {
+            Ok(report) => {
+                if report.entries.is_empty() {
+                    out.push_str("_No metadata entries._\n");
+                } else {
+                    out.push_str("| Key | Value |\n| --- | --- |\n");
+                    for (key, value) in &report.entries {
+                        out.push_str(&format!("| {} | {} |\n", cell(key), cell(value)));
+                    }
+                }
+                if report.issues.is_empty() {
+                    out.push_str("\n**Validation:** OK\n");
+                } else {
+                    out.push_str("\n**Validation:**\n\n");
+                    for issue in &report.issues {
+                        out.push_str(&format!("- {}\n", issue));
+                    }
+                }
+            }
+            Err(e) => out.push_str(&format!("**Error:** {}\n", e)),
+        }
+    }
+    out
+}
+
+fn render_html(reports: &[FileOutcome<FileReport>]) -> String {
+    let mut out = String::from(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Metadata Report</title>\n</head>\n<body>\n<h1>Metadata Report</h1>\n",
+    );
+
+    for outcome in reports {
+        out.push_str(&format!("<h2>{}</h2>\n", escape_html(&outcome.path.display().to_string())));
+        match &outcome.result {
+            Ok(report) => {
+                if report.entries.is_empty() {
+                    out.push_str("<p><em>No metadata entries.</em></p>\n");
+                } else {
+                    out.push_str("<table>\n<tr><th>Key</th><th>Value</th></tr>\n");
+                    for (key, value) in &report.entries {
+                        out.push_str(&format!("<tr><td>{}</td><td>{}</td></tr>\n", escape_html(key), escape_html(value)));
+                    }
+                    out.push_str("</table>\n");
+                }
+                if report.issues.is_empty() {
+                    out.push_str("<p><strong>Validation:</strong> OK</p>\n");
+                } else {
+                    out.push_str("<p><strong>Validation:</strong></p>\n<ul>\n");
+                    for issue in &report.issues {
+                        out.push_str(&format!("<li>{}</li>\n", escape_html(issue)));
+                    }
+                    out.push_str("</ul>\n");
+                }
+            }
+            Err(e) => out.push_str(&format!("<p><strong>Error:</strong> {}</p>\n", escape_html(&e.to_string()))),
+        }
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{create_minimal_test_pdf, setup_unique_test_dir};
+    use crate::update_metadata_in_place;
+    use std::fs;
+
+    #[test]
+    fn test_validate_flags_missing_and_malformed_entries() {
+        let entries = vec![
+            ("Title".to_string(), "Annual Report".to_string()),
+            ("CreationDate".to_string(), "yesterday".to_string()),
+            ("ModDate".to_string(), "D:20240101120000+01'00'".to_string()),
+        ];
+        let issues = validate(&entries);
+        assert_eq!(issues, vec!["Missing Author".to_string(), "CreationDate is not a valid PDF date: yesterday".to_string()]);
+    }
+
+    #[test]
+    fn test_render_markdown_and_html() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("report_render");
+        let file = test_dir.join("delivery.pdf");
+        create_minimal_test_pdf(&file)?;
+        update_metadata_in_place(file.to_str().unwrap(), "Title", "Q1 <Draft> | v2")?;
+
+        let mut reports = report_dir(&test_dir, false)?;
+        reports.push(FileOutcome { path: test_dir.join("missing.pdf"), result: Err(Error::MissingInfoDict) });
+
+        let markdown = render(&reports, ReportFormat::Markdown);
+        assert!(markdown.contains("| Title | Q1 <Draft> \\| v2 |"));
+        assert!(markdown.contains("- Missing Author"));
+        assert!(markdown.contains("**Error:** PDF does not have an Info dictionary"));
+
+        let html = render(&reports, ReportFormat::Html);
+        assert!(html.contains("<td>Q1 &lt;Draft&gt; | v2</td>"));
+        assert!(html.contains("<li>Missing Author</li>"));
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+}
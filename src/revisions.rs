@@ -0,0 +1,155 @@
+//! Revisions left by incremental updates.
+//!
+//! An incremental update appends the changed objects, a new cross-reference section
+//! and a trailer ending in `%%EOF` to the file, leaving every earlier revision in
+//! place. Cutting the file after one of those `%%EOF` markers gives back the file as
+//! it was saved then, Info dictionary included.
+
+#[cfg(feature = "std-fs")]
+use crate::fast_info::read_info_values_from_reader;
+#[cfg(feature = "std-fs")]
+use crate::{Result, parse_pdf_date, render_values, value};
+use chrono::{DateTime, FixedOffset};
+#[cfg(feature = "std-fs")]
+use std::fs;
+#[cfg(feature = "std-fs")]
+use std::io::Cursor;
+#[cfg(feature = "std-fs")]
+use std::path::Path;
+
+/// One saved revision of a file, as listed by [`get_metadata_history`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RevisionInfo {
+    /// The position of the revision, from 1 for the original file.
+    pub number: usize,
+    /// The size of the file when the revision was saved, in bytes: the revision is
+    /// the first `length` bytes of the file.
+    pub length: u64,
+    /// The revision's `ModDate`, if it has a valid one.
+    pub mod_date: Option<DateTime<FixedOffset>>,
+}
+
+/// A revision and its Info entries, as returned by [`get_metadata_history`].
+pub type RevisionMetadata = (RevisionInfo, Vec<(String, String)>);
+
+/// Returns the Info entries of every revision of a PDF, oldest first.
+///
+/// A file without incremental updates has one revision. Sections that cannot be read
+/// as a file of their own, such as the first-page section of a linearized file, are
+/// skipped; the newest revision is the whole file and is always listed.
+///
+/// # Returns
+///
+/// * `Ok(Vec<RevisionMetadata>)`: Each revision with its entries,
+///   as [`get_metadata`](crate::get_metadata) would have returned them then.
+/// * `Err(Error)`: If the file cannot be read or its newest revision cannot be parsed.
+///
+/// # Example
+///
+/// ```no_run
+/// use pdf_metadata::get_metadata_history;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     for (revision, entries) in get_metadata_history("contract.pdf")? {
+///         let author = entries.iter().find(|(key, _)| key == "Author").map(|(_, value)| value.as_str());
+///         println!("revision {}: Author = {:?}", revision.number, author);
+///     }
+///     Ok(())
+/// }
+/// ```
+#[cfg(feature = "std-fs")]
+pub fn get_metadata_history<P: AsRef<Path>>(file_path: P) -> Result<Vec<RevisionMetadata>> {
+    let file_path = file_path.as_ref();
+    let bytes = fs::read(file_path)?;
+    let ends = revision_ends(&bytes);
+    let mut history = Vec::new();
+    for (index, &end) in ends.iter().enumerate() {
+        let newest = index + 1 == ends.len();
+        let values = match read_info_values_from_reader(&mut Cursor::new(&bytes[..end])) {
+            Ok(values) => values,
+            Err(error) if newest => return Err(error),
+            Err(_) => continue,
+        };
+        let entries = render_values(values, &value::render_unsupported);
+        let mod_date = entries.iter().find(|(key, _)| key == "ModDate").and_then(|(_, date)| parse_pdf_date(date).ok());
+        let info = RevisionInfo { number: history.len() + 1, length: end as u64, mod_date };
+        history.push((info, entries));
+    }
+    Ok(history)
+}
+
+/// The lengths of the revisions of the file `bytes`: the ends of the `%%EOF` markers
+/// that close a cross-reference section, with the last one extended to the end of
+/// the file.
+#[cfg(feature = "std-fs")]
+pub(crate) fn revision_ends(bytes: &[u8]) -> Vec<usize> {
+    let mut ends: Vec<usize> = bytes
+        .windows(5)
+        .enumerate()
+        .filter(|(at, window)| *window == b"%%EOF" && closes_xref_section(&bytes[..*at]))
+        .map(|(at, _)| {
+            let eol = bytes[at + 5..].iter().take(2).take_while(|b| matches!(b, b'\r' | b'\n')).count();
+            at + 5 + eol
+        })
+        .collect();
+    match ends.last_mut() {
+        Some(last) => *last = bytes.len(),
+        None => ends.push(bytes.len()),
+    }
+    ends
+}
+
+/// Whether `before`, the bytes preceding a `%%EOF`, ends with `startxref` and the
+/// offset of a cross-reference table or stream within it. A `%%EOF` inside a stream,
+/// such as an attached PDF, points elsewhere.
+#[cfg(feature = "std-fs")]
+fn closes_xref_section(before: &[u8]) -> bool {
+    let tail = &before[before.len().saturating_sub(64)..];
+    let Some(keyword) = tail.windows(9).rposition(|w| w == b"startxref") else { return false };
+    let offset = std::str::from_utf8(&tail[keyword + 9..]).ok().and_then(|n| n.trim().parse::<usize>().ok());
+    let Some(section) = offset.and_then(|offset| before.get(offset..)) else { return false };
+    section.starts_with(b"xref") || is_object_header(section)
+}
+
+/// Whether `bytes` starts with `N G obj`.
+#[cfg(feature = "std-fs")]
+fn is_object_header(bytes: &[u8]) -> bool {
+    let mut parts = bytes.splitn(3, |b| *b == b' ');
+    let is_number = |part: Option<&[u8]>| part.is_some_and(|part| !part.is_empty() && part.iter().all(u8::is_ascii_digit));
+    is_number(parts.next()) && is_number(parts.next()) && parts.next().is_some_and(|rest| rest.starts_with(b"obj"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{create_minimal_test_pdf, setup_unique_test_dir};
+    use crate::{update_metadata_in_place, update_metadata_incremental};
+
+    #[test]
+    fn test_get_metadata_history() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("metadata_history");
+        let file = test_dir.join("doc.pdf");
+        create_minimal_test_pdf(&file)?;
+        let file_str = file.to_str().unwrap();
+        update_metadata_in_place(file_str, "Author", "Ana Souza")?;
+        update_metadata_incremental(file_str, "Author", "Bruno Lima")?;
+        update_metadata_incremental(file_str, "Title", "Contrato")?;
+
+        let history = get_metadata_history(file_str)?;
+        assert_eq!(history.len(), 3);
+        let author = |entries: &[(String, String)]| {
+            entries.iter().find(|(key, _)| key == "Author").map(|(_, value)| value.clone())
+        };
+        assert_eq!(author(&history[0].1).as_deref(), Some("Ana Souza"));
+        assert_eq!(author(&history[1].1).as_deref(), Some("Bruno Lima"));
+        assert!(!history[1].1.iter().any(|(key, _)| key == "Title"));
+        assert!(history[2].1.contains(&("Title".to_string(), "Contrato".to_string())));
+        assert_eq!(history.iter().map(|(revision, _)| revision.number).collect::<Vec<_>>(), [1, 2, 3]);
+        assert_eq!(history[2].0.length, fs::metadata(&file)?.len());
+        assert!(history[0].0.length < history[1].0.length && history[0].0.mod_date.is_some());
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+}
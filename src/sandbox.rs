@@ -0,0 +1,265 @@
+//! # Out-of-process parsing (`sandbox` feature)
+//!
+//! Parsing untrusted uploads exercises a lot of third-party code. With a
+//! [`Sandbox`], the bytes are parsed in a child process and only the resulting
+//! entries are sent back, so a crash, an abort or a memory blow-up in the parser
+//! ends the child instead of the host service.
+//!
+//! The child is an executable that calls [`run_worker_if_requested`] at the start of
+//! `main`, usually the host program itself. The `pdf_metadata` command-line tool built
+//! with this feature is such an executable too. Process-level restrictions (seccomp,
+//! namespaces, resource limits) are left to the platform: prefix the worker with a
+//! tool such as `bwrap` or `prlimit` through [`Sandbox::new`] and [`Sandbox::arg`].
+//!
+//! ```no_run
+//! use pdf_metadata::sandbox::{self, Sandbox};
+//!
+//! fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     sandbox::run_worker_if_requested();
+//!
+//!     let sandbox = Sandbox::current_exe()?;
+//!     for (key, value) in sandbox.get_metadata("uploads/untrusted.pdf")? {
+//!         println!("{}: {}", key, value);
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use crate::{Error, Result, get_pdf_metadata};
+use std::ffi::OsString;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{self, Command, ExitStatus, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Environment variable that switches an executable into worker mode.
+pub const WORKER_ENV: &str = "PDF_METADATA_SANDBOX_WORKER";
+
+/// Launches worker processes that parse PDFs on behalf of the caller.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sandbox {
+    program: PathBuf,
+    args: Vec<OsString>,
+    timeout: Option<Duration>,
+}
+
+impl Sandbox {
+    /// Runs `program` as the worker. It must reach [`run_worker_if_requested`],
+    /// directly or through wrappers given with [`arg`](Sandbox::arg).
+    pub fn new(program: impl Into<PathBuf>) -> Self {
+        Sandbox { program: program.into(), args: Vec::new(), timeout: None }
+    }
+
+    /// Runs the current executable as the worker.
+    pub fn current_exe() -> Result<Self> {
+        Ok(Sandbox::new(std::env::current_exe()?))
+    }
+
+    /// Appends a command-line argument for the worker program.
+    pub fn arg(mut self, arg: impl Into<OsString>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    /// Kills a worker that has not answered within `timeout`. Unlike
+    /// [`with_timeout`](crate::with_timeout), the parse does not keep running after
+    /// the caller gives up.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Reads the metadata of the file at `file_path` in a worker process.
+    ///
+    /// The file is read by the caller; only its bytes reach the worker.
+    pub fn get_metadata<P: AsRef<Path>>(&self, file_path: P) -> Result<Vec<(String, String)>> {
+        let file_path = file_path.as_ref();
+        self.get_pdf_metadata(&fs::read(file_path)?)
+    }
+
+    /// Reads the metadata of an in-memory PDF in a worker process.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Vec<(String, String)>)`: The same entries as [`get_pdf_metadata`](crate::get_pdf_metadata).
+    /// * `Err(Error::Sandbox)`: If the worker rejected the document, crashed, sent
+    ///   an unreadable response or was killed after the [timeout](Sandbox::timeout).
+    /// * `Err(Error::Io)`: If the worker could not be started.
+    pub fn get_pdf_metadata(&self, pdf_content: &[u8]) -> Result<Vec<(String, String)>> {
+        let mut child = Command::new(&self.program)
+            .args(&self.args)
+            .env(WORKER_ENV, "1")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        let mut stdout = child.stdout.take().expect("stdout is piped");
+        let request = pdf_content.to_vec();
+        // Feed and drain the worker from other threads so a large document cannot
+        // deadlock against a full pipe while this one watches the clock. A worker
+        // that dies early closes its stdin, which is reported through its exit
+        // status instead.
+        let feeder = thread::spawn(move || {
+            let _ = stdin.write_all(&request);
+        });
+        let reader = thread::spawn(move || {
+            let mut response = Vec::new();
+            stdout.read_to_end(&mut response).map(|_| response)
+        });
+        let status = self.wait(&mut child)?;
+        let _ = feeder.join();
+        let response = reader.join().map_err(|_| Error::Sandbox("worker output was lost".to_string()))??;
+
+        if !status.success() {
+            return Err(Error::Sandbox(format!("worker exited with {}", status)));
+        }
+        decode_response(&response)
+    }
+
+    /// Waits for the worker to exit, killing it once the timeout has passed.
+    fn wait(&self, child: &mut process::Child) -> Result<ExitStatus> {
+        let Some(timeout) = self.timeout else {
+            return Ok(child.wait()?);
+        };
+        let started = Instant::now();
+        loop {
+            if let Some(status) = child.try_wait()? {
+                return Ok(status);
+            }
+            if started.elapsed() >= timeout {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(Error::Sandbox(format!("worker did not finish within {:?} and was killed", timeout)));
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+}
+
+/// Serves one request and exits if this process was started by a [`Sandbox`].
+///
+/// Returns immediately otherwise, so it can be called unconditionally at the start
+/// of `main`.
+pub fn run_worker_if_requested() {
+    if std::env::var_os(WORKER_ENV).is_none() {
+        return;
+    }
+    let stdout = io::stdout();
+    let code = match serve(io::stdin().lock(), stdout.lock()) {
+        Ok(()) => 0,
+        Err(_) => 1,
+    };
+    process::exit(code);
+}
+
+/// Parses the PDF read from `input` and writes the response to `output`.
+fn serve(mut input: impl Read, mut output: impl Write) -> io::Result<()> {
+    let mut pdf_content = Vec::new();
+    input.read_to_end(&mut pdf_content)?;
+    let mut response = Vec::new();
+    match get_pdf_metadata(&pdf_content) {
+        Ok(entries) => {
+            write_field(&mut response, b"ok");
+            for (key, value) in &entries {
+                write_field(&mut response, key.as_bytes());
+                write_field(&mut response, value.as_bytes());
+            }
+        }
+        Err(e) => {
+            write_field(&mut response, b"error");
+            write_field(&mut response, e.to_string().as_bytes());
+        }
+    }
+    output.write_all(&response)?;
+    output.flush()
+}
+
+/// Appends `bytes` as a netstring (`<length>:<bytes>,`).
+fn write_field(out: &mut Vec<u8>, bytes: &[u8]) {
+    out.extend_from_slice(bytes.len().to_string().as_bytes());
+    out.push(b':');
+    out.extend_from_slice(bytes);
+    out.push(b',');
+}
+
+/// Splits a response into its netstring fields.
+fn read_fields(mut input: &[u8]) -> Option<Vec<String>> {
+    let mut fields = Vec::new();
+    while !input.is_empty() {
+        let colon = input.iter().position(|&b| b == b':')?;
+        let len: usize = std::str::from_utf8(&input[..colon]).ok()?.parse().ok()?;
+        let end = colon.checked_add(1)?.checked_add(len)?;
+        if input.get(end) != Some(&b',') {
+            return None;
+        }
+        fields.push(String::from_utf8(input[colon + 1..end].to_vec()).ok()?);
+        input = &input[end + 1..];
+    }
+    Some(fields)
+}
+
+fn decode_response(response: &[u8]) -> Result<Vec<(String, String)>> {
+    let malformed = || Error::Sandbox("malformed response from worker".to_string());
+    let mut fields = read_fields(response).ok_or_else(malformed)?.into_iter();
+    match fields.next().as_deref() {
+        Some("ok") => {
+            let fields: Vec<String> = fields.collect();
+            if !fields.len().is_multiple_of(2) {
+                return Err(malformed());
+            }
+            Ok(fields.chunks(2).map(|pair| (pair[0].clone(), pair[1].clone())).collect())
+        }
+        Some("error") => Err(Error::Sandbox(fields.next().ok_or_else(malformed)?)),
+        _ => Err(malformed()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{create_minimal_test_pdf, setup_unique_test_dir};
+    use crate::update_metadata_in_place;
+
+    #[test]
+    fn test_worker_round_trip() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("sandbox_round_trip");
+        let file = test_dir.join("doc.pdf");
+        create_minimal_test_pdf(&file)?;
+        update_metadata_in_place(file.to_str().unwrap(), "Title", "Commas, colons: fine")?;
+        let pdf_content = fs::read(&file)?;
+
+        let mut response = Vec::new();
+        serve(pdf_content.as_slice(), &mut response)?;
+        assert_eq!(decode_response(&response)?, get_pdf_metadata(&pdf_content)?);
+
+        response.clear();
+        serve(&b"not a pdf"[..], &mut response)?;
+        assert!(matches!(decode_response(&response), Err(Error::Sandbox(_))));
+        assert!(matches!(decode_response(b"2:ok,5:Title,"), Err(Error::Sandbox(_))));
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_crashing_worker_is_reported() {
+        let sandbox = Sandbox::new("sh").arg("-c").arg("kill -SEGV $$");
+        let error = sandbox.get_pdf_metadata(b"%PDF-1.7").unwrap_err();
+        assert!(matches!(&error, Error::Sandbox(message) if message.contains("signal")), "{}", error);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_slow_worker_is_killed() {
+        let started = Instant::now();
+        let sandbox = Sandbox::new("sh").arg("-c").arg("exec sleep 30").timeout(Duration::from_millis(200));
+        let error = sandbox.get_pdf_metadata(b"%PDF-1.7").unwrap_err();
+        assert!(matches!(&error, Error::Sandbox(message) if message.contains("killed")), "{}", error);
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+}
@@ -0,0 +1,448 @@
+//! # Creator/Producer scrubbing and metadata stripping
+//!
+//! Authoring tools often embed the source file's full path in `Creator` or
+//! `Producer`, e.g. `C:\Users\maria\Documents\offer.docx - Microsoft Word`, which
+//! leaks local usernames and folder structures. The scrubber removes such paths
+//! (and any username found in them) while keeping the tool identification.
+//! [`strip_metadata`] goes further and removes the Info dictionary and the XMP
+//! metadata stream altogether, for publishing workflows where no authoring details
+//! may leave the building. [`flatten_and_strip_history`] keeps the current metadata
+//! but drops every trace of earlier values.
+//!
+//! ```
+//! use pdf_metadata::scrub::scrub_tool_string;
+//!
+//! assert_eq!(
+//!     scrub_tool_string(r"C:\Users\maria\Documents\offer.docx - Microsoft Word"),
+//!     "Microsoft Word"
+//! );
+//! ```
+
+#[cfg(feature = "std-fs")]
+use crate::xmp::{self, XMP_MM_NS};
+#[cfg(feature = "std-fs")]
+use crate::{Result, get_metadata, load_document, rewrite_loaded, update_entries_in_place};
+#[cfg(feature = "std-fs")]
+use lopdf::{Dictionary, Object};
+#[cfg(feature = "std-fs")]
+use std::path::Path;
+
+/// The Info entries that identify the generating tools.
+pub const TOOL_KEYS: [&str; 2] = ["Creator", "Producer"];
+
+/// Path segments after which the next segment is a username.
+const HOME_SEGMENTS: [&str; 3] = ["Users", "home", "Documents and Settings"];
+
+/// The `xmpMM` properties that record earlier versions of a document and the files
+/// it was derived from.
+#[cfg(feature = "std-fs")]
+const HISTORY_PROPERTIES: [&str; 6] = ["History", "DerivedFrom", "Ingredients", "Pantry", "Versions", "Manifest"];
+
+/// Characters that end a path embedded in free text.
+const PATH_TERMINATORS: [char; 6] = ['(', ')', ';', ',', '"', '|'];
+
+/// Removes filesystem paths and the usernames they reveal from a tool string.
+///
+/// Windows drive paths (`C:\...`), UNC paths (`\\server\...`) and absolute or
+/// home-relative Unix paths (`/home/...`, `~/...`) are dropped up to the next
+/// separator such as ` - `, a parenthesis or a comma. Leftover separators and
+/// whitespace are tidied up. Strings without paths are returned unchanged.
+pub fn scrub_tool_string(value: &str) -> String {
+    let mut kept = String::with_capacity(value.len());
+    let mut usernames: Vec<String> = Vec::new();
+    let mut rest = value;
+
+    while !rest.is_empty() {
+        match find_path_start(rest) {
+            Some(start) => {
+                kept.push_str(&rest[..start]);
+                let end = start + path_len(&rest[start..]);
+                if let Some(username) = username_in_path(&rest[start..end]) {
+                    usernames.push(username);
+                }
+                rest = &rest[end..];
+            }
+            None => {
+                kept.push_str(rest);
+                break;
+            }
+        }
+    }
+
+    if kept.len() == value.len() {
+        return value.to_string();
+    }
+    for username in &usernames {
+        kept = remove_word(&kept, username);
+    }
+    tidy(&kept)
+}
+
+/// Scrubs the `Creator` and `Producer` entries of a PDF file in place.
+///
+/// # Returns
+///
+/// * `Ok(Vec<(String, String)>)`: The entries that were rewritten, with their new values.
+///   The file is left untouched when nothing needed scrubbing.
+/// * `Err(Error)`: If the file cannot be read or written.
+#[cfg(feature = "std-fs")]
+pub fn scrub_tool_metadata<P: AsRef<Path>>(file_path: P) -> Result<Vec<(String, String)>> {
+    let file_path = file_path.as_ref();
+    let changes: Vec<(String, String)> = get_metadata(file_path)?
+        .into_iter()
+        .filter(|(key, _)| TOOL_KEYS.contains(&key.as_str()))
+        .filter_map(|(key, value)| {
+            let scrubbed = scrub_tool_string(&value);
+            (scrubbed != value).then_some((key, scrubbed))
+        })
+        .collect();
+
+    if !changes.is_empty() {
+        update_entries_in_place(file_path, &changes)?;
+    }
+    Ok(changes)
+}
+
+/// Settings for [`strip_metadata_with_options`].
+///
+/// The default removes every Info entry and the document's XMP metadata stream.
+///
+/// ```
+/// use pdf_metadata::scrub::StripOptions;
+///
+/// // Keep the title for display in viewers, drop everything else.
+/// let options = StripOptions { keep: vec!["Title".to_string()], ..StripOptions::default() };
+/// assert!(options.remove_xmp);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StripOptions {
+    /// Info entries to keep, matched exactly. When any of them is present, the
+    /// output gets a new Info dictionary holding only those entries.
+    pub keep: Vec<String>,
+    /// Whether the `/Metadata` stream of the document catalog is removed too.
+    /// XMP usually repeats the Info entries, so keeping it defeats stripping
+    /// unless it was cleaned separately.
+    pub remove_xmp: bool,
+}
+
+impl Default for StripOptions {
+    fn default() -> Self {
+        StripOptions {
+            keep: Vec::new(),
+            remove_xmp: true,
+        }
+    }
+}
+
+/// Removes all document metadata, writing the result to `output_path`.
+///
+/// The Info dictionary and the XMP metadata stream are dropped; see
+/// [`strip_metadata_with_options`] to keep selected entries. `ModDate` is not
+/// refreshed, since it would itself be metadata. The output is a full rewrite, so
+/// earlier revisions of the file are not carried over.
+///
+/// # Returns
+///
+/// * `Ok(Vec<String>)`: The keys of the Info entries that were removed.
+/// * `Err(Error)`: If the file cannot be loaded or the output cannot be written.
+///
+/// # Example
+///
+/// ```no_run
+/// use pdf_metadata::scrub::strip_metadata;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     strip_metadata("drafts/offer.pdf", "public/offer.pdf")?;
+///     Ok(())
+/// }
+/// ```
+#[cfg(feature = "std-fs")]
+pub fn strip_metadata<P: AsRef<Path>, Q: AsRef<Path>>(file_path: P, output_path: Q) -> Result<Vec<String>> {
+    let file_path = file_path.as_ref();
+    let output_path = output_path.as_ref();
+    strip_metadata_with_options(file_path, output_path, &StripOptions::default())
+}
+
+/// Like [`strip_metadata`], with `options` choosing the entries to keep and whether
+/// the XMP stream is removed.
+#[cfg(feature = "std-fs")]
+pub fn strip_metadata_with_options<P: AsRef<Path>, Q: AsRef<Path>>(file_path: P, output_path: Q, options: &StripOptions) -> Result<Vec<String>> {
+    let file_path = file_path.as_ref();
+    let output_path = output_path.as_ref();
+    let doc = load_document(file_path)?;
+    let write_options = crate::options::current();
+    rewrite_loaded(doc, file_path, Some(output_path), &write_options, "stripping metadata", |doc| {
+        let info = match doc.trailer.remove(b"Info") {
+            Some(Object::Reference(id)) => doc.objects.remove(&id),
+            other => other,
+        };
+        let mut kept = Dictionary::new();
+        let mut removed = Vec::new();
+        if let Some(Object::Dictionary(info)) = info {
+            for (key, value) in info {
+                if options.keep.iter().any(|k| k.as_bytes() == key.as_slice()) {
+                    kept.set(key, value);
+                } else {
+                    removed.push(String::from_utf8_lossy(&key).into_owned());
+                }
+            }
+        }
+        if !kept.is_empty() {
+            let id = doc.add_object(kept);
+            doc.trailer.set("Info", Object::Reference(id));
+        }
+
+        if options.remove_xmp
+            && let Ok(catalog) = doc.catalog_mut()
+            && let Some(Object::Reference(id)) = catalog.remove(b"Metadata")
+        {
+            doc.objects.remove(&id);
+        }
+        Ok(removed)
+    })
+}
+
+/// Rewrites a PDF as a single revision from which no earlier metadata can be
+/// recovered, writing the result to `output_path`.
+///
+/// Incremental updates leave every earlier Info dictionary and XMP packet in the
+/// file, reachable through the `/Prev` chain of cross-reference sections, so an old
+/// author name survives any number of later edits. The output keeps only the
+/// objects the current revision uses, which drops those old copies, unreferenced
+/// objects and object streams, and removes the XMP properties that record earlier
+/// versions (`xmpMM:History`, `DerivedFrom`, `Ingredients`, `Pantry`, `Versions` and
+/// `Manifest`). The current metadata is kept, and `ModDate` is not refreshed.
+///
+/// Digital signatures are invalidated, as by any full rewrite. `output_path` may be
+/// the input file.
+///
+/// # Returns
+///
+/// * `Ok(())` if the document was written.
+/// * `Err(Error)`: If the file cannot be loaded, its XMP packet cannot be parsed, or
+///   the output cannot be written.
+///
+/// # Example
+///
+/// ```no_run
+/// use pdf_metadata::scrub::flatten_and_strip_history;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     flatten_and_strip_history("case/exhibit.pdf", "release/exhibit.pdf")?;
+///     Ok(())
+/// }
+/// ```
+#[cfg(feature = "std-fs")]
+pub fn flatten_and_strip_history<P: AsRef<Path>, Q: AsRef<Path>>(file_path: P, output_path: Q) -> Result<()> {
+    let file_path = file_path.as_ref();
+    let output_path = output_path.as_ref();
+    let doc = load_document(file_path)?;
+    let options = crate::options::current();
+    rewrite_loaded(doc, file_path, Some(output_path), &options, "flattening the history", |doc| {
+        for key in [b"Prev".as_slice(), b"XRefStm"] {
+            doc.trailer.remove(key);
+        }
+        if let Some(packet) = xmp::read_packet_xml(doc)? {
+            let packet = xmp::remove_properties(&packet, XMP_MM_NS, &HISTORY_PROPERTIES)?;
+            xmp::write_packet(doc, packet)?;
+        }
+        doc.prune_objects();
+        Ok(())
+    })
+}
+
+/// Returns the byte offset of the first path in `text`, if any.
+fn find_path_start(text: &str) -> Option<usize> {
+    let bytes = text.as_bytes();
+    (0..bytes.len()).find(|&i| {
+        let at_boundary = i == 0 || bytes[i - 1].is_ascii_whitespace() || PATH_TERMINATORS.contains(&(bytes[i - 1] as char));
+        if !at_boundary {
+            return false;
+        }
+        let tail = &bytes[i..];
+        let drive = tail.len() >= 3 && tail[0].is_ascii_alphabetic() && tail[1] == b':' && (tail[2] == b'\\' || tail[2] == b'/');
+        let unc = tail.starts_with(b"\\\\");
+        let unix = (tail.starts_with(b"/") || tail.starts_with(b"~/"))
+            && tail[1..].iter().take_while(|&&b| !b.is_ascii_whitespace()).filter(|&&b| b == b'/').count() >= 1;
+        drive || unc || unix
+    })
+}
+
+/// Returns the length of the path at the start of `text`.
+///
+/// Windows paths may contain spaces, so a path only ends at a terminator character
+/// or a ` - ` separator; Unix paths additionally end at whitespace.
+fn path_len(text: &str) -> usize {
+    let windows = !text.starts_with('/') && !text.starts_with('~');
+    let mut end = text.len();
+    for (i, c) in text.char_indices() {
+        if PATH_TERMINATORS.contains(&c) || text[i..].starts_with(" - ") || text[i..].starts_with(" \u{2013} ") || (!windows && c.is_whitespace()) {
+            end = i;
+            break;
+        }
+    }
+    end
+}
+
+/// Extracts the username from a path such as `C:\Users\maria\...` or `/home/maria/...`.
+fn username_in_path(path: &str) -> Option<String> {
+    let segments: Vec<&str> = path.split(['\\', '/']).filter(|s| !s.is_empty()).collect();
+    segments
+        .windows(2)
+        .find(|pair| HOME_SEGMENTS.iter().any(|home| pair[0].eq_ignore_ascii_case(home)))
+        .map(|pair| pair[1].to_string())
+        .filter(|name| name.chars().count() >= 2)
+}
+
+/// Removes whole-word occurrences of `word` from `text`, ignoring ASCII case.
+fn remove_word(text: &str, word: &str) -> String {
+    // ASCII lowercasing keeps byte offsets aligned with `text`.
+    let lower_text = text.to_ascii_lowercase();
+    let lower_word = word.to_ascii_lowercase();
+    let mut out = String::with_capacity(text.len());
+    let mut last = 0;
+    for (start, _) in lower_text.match_indices(&lower_word) {
+        let end = start + word.len();
+        if start >= last && is_word_boundary(text, start, end) {
+            out.push_str(&text[last..start]);
+            last = end;
+        }
+    }
+    out.push_str(&text[last..]);
+    out
+}
+
+fn is_word_boundary(text: &str, start: usize, end: usize) -> bool {
+    let before = text[..start].chars().next_back();
+    let after = text[end..].chars().next();
+    !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+}
+
+/// Collapses whitespace, drops empty brackets and trims dangling separators.
+fn tidy(text: &str) -> String {
+    let mut result = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    for empty in ["()", "[]", "( )", "[ ]"] {
+        result = result.replace(empty, "");
+    }
+    result = result.split_whitespace().collect::<Vec<_>>().join(" ");
+    let separators: &[char] = &[' ', '-', '\u{2013}', ',', ';', ':', '|'];
+    result.trim_matches(separators).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        SetOptions, get_metadata_history, save_document, update_metadata_in_place_with_options,
+        update_metadata_incremental_with_options,
+    };
+    use crate::test_support::{create_minimal_test_pdf, setup_unique_test_dir};
+    use std::fs;
+
+    #[test]
+    fn test_scrub_tool_string() {
+        let cases = [
+            (r"C:\Users\maria\Documents\offer.docx - Microsoft Word", "Microsoft Word"),
+            (r"Microsoft Word - \\fileserver\share\Q1 plan.docx", "Microsoft Word"),
+            ("LibreOffice Writer (/home/joao/drafts/report.odt)", "LibreOffice Writer"),
+            ("PScript5.dll Version 5.2.2 (maria) C:/Users/Maria/out.ps", "PScript5.dll Version 5.2.2"),
+            ("pdfTeX-1.40.25 ~/thesis/main.tex", "pdfTeX-1.40.25"),
+            ("Skia/PDF m127 Google Docs Renderer", "Skia/PDF m127 Google Docs Renderer"),
+            ("Acrobat Distiller 23.0 (Windows)", "Acrobat Distiller 23.0 (Windows)"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(scrub_tool_string(input), expected, "input: {}", input);
+        }
+    }
+
+    #[test]
+    fn test_scrub_tool_metadata_rewrites_only_tool_keys() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("scrub_tool_metadata");
+        let file = test_dir.join("leaky.pdf");
+        create_minimal_test_pdf(&file)?;
+        let file_str = file.to_str().unwrap();
+        update_entries_in_place(
+            file_str,
+            &[
+                ("Creator", r"C:\Users\maria\Desktop\offer.docx - Microsoft Word"),
+                ("Producer", "Microsoft: Print To PDF"),
+                ("Subject", r"C:\Users\maria\notes"),
+            ],
+        )?;
+
+        let changes = scrub_tool_metadata(file_str)?;
+        assert_eq!(changes, vec![("Creator".to_string(), "Microsoft Word".to_string())]);
+
+        let metadata = get_metadata(file_str)?;
+        let value = |key: &str| metadata.iter().find(|(k, _)| k == key).map(|(_, v)| v.clone());
+        assert_eq!(value("Creator").as_deref(), Some("Microsoft Word"));
+        assert_eq!(value("Producer").as_deref(), Some("Microsoft: Print To PDF"));
+        assert_eq!(value("Subject").as_deref(), Some(r"C:\Users\maria\notes"));
+
+        assert!(scrub_tool_metadata(file_str)?.is_empty());
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_strip_metadata_keeps_only_whitelisted_entries() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("strip_metadata");
+        let file = test_dir.join("source.pdf");
+        create_minimal_test_pdf(&file)?;
+        let file_str = file.to_str().unwrap();
+        update_entries_in_place(file_str, &[("Title", "Offer"), ("Author", "Maria"), ("Producer", "Microsoft Word")])?;
+        let mut doc = load_document(file_str)?;
+        let xmp = doc.add_object(lopdf::Stream::new(Dictionary::new(), b"<x:xmpmeta/>".to_vec()));
+        doc.catalog_mut()?.set("Metadata", Object::Reference(xmp));
+        save_document(&mut doc, &file)?;
+
+        let stripped = test_dir.join("stripped.pdf");
+        strip_metadata(file_str, stripped.to_str().unwrap())?;
+        assert!(get_metadata(stripped.to_str().unwrap())?.is_empty());
+        let doc = load_document(stripped.to_str().unwrap())?;
+        assert!(doc.trailer.get(b"Info").is_err());
+        assert!(doc.catalog()?.get(b"Metadata").is_err());
+
+        let kept = test_dir.join("kept.pdf");
+        let options = StripOptions { keep: vec!["Title".to_string()], remove_xmp: false };
+        let mut removed = strip_metadata_with_options(file_str, kept.to_str().unwrap(), &options)?;
+        removed.sort();
+        assert_eq!(removed, ["Author", "ModDate", "Producer"]);
+        assert_eq!(get_metadata(kept.to_str().unwrap())?, vec![("Title".to_string(), "Offer".to_string())]);
+        assert!(load_document(kept.to_str().unwrap())?.catalog()?.get(b"Metadata").is_ok());
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_flatten_and_strip_history() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("flatten_history");
+        let file = test_dir.join("source.pdf");
+        create_minimal_test_pdf(&file)?;
+        let file_str = file.to_str().unwrap();
+        let history = SetOptions { xmp_history: true, ..SetOptions::default() };
+        update_metadata_in_place_with_options(file_str, "Author", "Ana Souza", &history)?;
+        update_metadata_incremental_with_options(file_str, "Author", "Bruno Lima", &history)?;
+        let bytes = fs::read(&file)?;
+        assert!(contains(&bytes, b"Ana Souza") && contains(&bytes, b"xmpMM:History"));
+
+        let flat = test_dir.join("flat.pdf");
+        let flat_str = flat.to_str().unwrap();
+        flatten_and_strip_history(file_str, flat_str)?;
+        let bytes = fs::read(&flat)?;
+        assert!(!contains(&bytes, b"Ana Souza"));
+        assert!(!contains(&bytes, b"History") && contains(&bytes, b"xmpMM:InstanceID"));
+        assert_eq!(get_metadata_history(flat_str)?.len(), 1);
+        assert!(get_metadata(flat_str)?.contains(&("Author".to_string(), "Bruno Lima".to_string())));
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+
+    fn contains(bytes: &[u8], needle: &[u8]) -> bool {
+        bytes.windows(needle.len()).any(|window| window == needle)
+    }
+}
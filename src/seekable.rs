@@ -0,0 +1,215 @@
+//! Metadata access on seekable streams.
+//!
+//! PDFs kept in object storage, inside archives or behind network streams do not
+//! have to be copied to a `Vec<u8>` or a temp file first: reading only touches the
+//! cross-reference chain and the Info object, and writing copies the source through
+//! unchanged before appending an incremental update.
+
+use crate::fast_info::{self, ScannedDocument};
+use crate::incremental::append_revision;
+use crate::stats::{self, Counter};
+use crate::{Error, Result, SetOptions, apply_entries_with, modify_document, options, render_values, value};
+use lopdf::Document;
+use lopdf::xref::XrefType;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+/// Retrieves all metadata entries from a PDF read through `reader`.
+///
+/// Returns the same entries as [`get_metadata`](crate::get_metadata). When the
+/// document uses classic cross-reference tables only the cross-reference chain and
+/// the Info object are read; otherwise the whole stream is read into memory and
+/// parsed.
+///
+/// # Example
+///
+/// ```no_run
+/// use pdf_metadata::get_metadata_from_reader;
+/// use std::fs::File;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let file = File::open("path/to/document.pdf")?;
+///     for (key, value) in get_metadata_from_reader(file)? {
+///         println!("{}: {}", key, value);
+///     }
+///     Ok(())
+/// }
+/// ```
+pub fn get_metadata_from_reader<R: Read + Seek>(mut reader: R) -> Result<Vec<(String, String)>> {
+    let values = fast_info::read_info_values_from_reader(&mut reader)?;
+    Ok(render_values(values, &value::render_unsupported))
+}
+
+/// Sets (adds or updates) a metadata entry, writing the updated PDF to `writer`.
+///
+/// The output is the source, byte for byte, followed by an incremental update holding
+/// the new Info dictionary, as with [`update_metadata_incremental`](crate::update_metadata_incremental).
+/// `ModDate` is refreshed. Only documents with cross-reference streams are read into
+/// memory first.
+///
+/// # Returns
+///
+/// * `Ok(())` if the updated document was written.
+/// * `Err(Error::UnsupportedFormat)`: If the document is encrypted.
+/// * `Err(Error)`: If the source cannot be read or parsed, or `writer` fails.
+///
+/// # Example
+///
+/// ```no_run
+/// use pdf_metadata::set_metadata_from_reader;
+/// use std::fs::File;
+/// use std::io::BufWriter;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let source = File::open("path/to/document.pdf")?;
+///     let target = BufWriter::new(File::create("path/to/updated.pdf")?);
+///     set_metadata_from_reader(source, target, "Title", "Quarterly Report")?;
+///     Ok(())
+/// }
+/// ```
+pub fn set_metadata_from_reader<R: Read + Seek, W: Write>(
+    reader: R,
+    writer: W,
+    metadata_key: &str,
+    metadata_value: &str,
+) -> Result<()> {
+    set_metadata_from_reader_with_options(reader, writer, metadata_key, metadata_value, &options::current())
+}
+
+/// Like [`set_metadata_from_reader`], with explicit control over `ModDate`.
+pub fn set_metadata_from_reader_with_options<R: Read + Seek, W: Write>(
+    mut reader: R,
+    mut writer: W,
+    metadata_key: &str,
+    metadata_value: &str,
+    options: &SetOptions,
+) -> Result<()> {
+    let entries = [(metadata_key, metadata_value)];
+    let scanned = fast_info::scan_document(&mut reader).and_then(|scanned| Some((max_id(&scanned)?, scanned)));
+    match scanned {
+        Some((max_id, scanned)) => append_streamed_revision(&mut reader, &mut writer, scanned, max_id, &entries, options)?,
+        None => {
+            // Cross-reference streams, encryption and damaged tables need the full parser.
+            let mut original_bytes = Vec::new();
+            reader.seek(SeekFrom::Start(0))?;
+            reader.read_to_end(&mut original_bytes)?;
+            writer.write_all(&append_revision(original_bytes, None, &entries, options)?)?;
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Copies `reader` to `writer` and appends a revision with the updated Info dictionary.
+///
+/// The revision is rendered by lopdf as a standalone document holding only the Info
+/// object; its object and trailer are then spliced after the source with offsets
+/// shifted to their final position.
+fn append_streamed_revision<R: Read + Seek, W: Write>(
+    reader: &mut R,
+    writer: &mut W,
+    scanned: ScannedDocument,
+    max_id: u32,
+    entries: &[(&str, &str)],
+    options: &SetOptions,
+) -> Result<()> {
+    let mut update = Document::new();
+    update.reference_table.cross_reference_type = XrefType::CrossReferenceTable;
+    update.binary_mark = Vec::new();
+    update.trailer = scanned.trailer;
+    update.trailer.set("Prev", scanned.xref_start as i64);
+    update.max_id = max_id;
+    update.objects.extend(scanned.info);
+    modify_document(&mut update, options, |doc| apply_entries_with(doc, entries, options))?;
+    let ((id, generation), _) = update.objects.first_key_value().expect("the Info dictionary was just set");
+    let (id, generation) = (*id, *generation);
+
+    let mut rendered = Vec::new();
+    update.save_to(&mut rendered)?;
+    let (object, trailer) = split_rendered_revision(&rendered)
+        .ok_or_else(|| Error::UnsupportedFormat("unexpected layout of the rendered revision".to_string()))?;
+
+    reader.seek(SeekFrom::Start(0))?;
+    io::copy(reader, writer)?;
+    let object_offset = scanned.len + 1;
+    let xref_offset = object_offset + object.len() as u64;
+    writer.write_all(b"\n")?;
+    writer.write_all(object)?;
+    write!(writer, "xref\n0 1\n0000000000 65535 f \n{} 1\n{:010} {:05} n \ntrailer\n", id, object_offset, generation)?;
+    writer.write_all(trailer)?;
+    write!(writer, "\nstartxref\n{}\n%%EOF", xref_offset)?;
+    stats::record(Counter::DocumentWritten);
+    Ok(())
+}
+
+/// The highest object number in use according to the trailer's `/Size`.
+fn max_id(scanned: &ScannedDocument) -> Option<u32> {
+    let size = scanned.trailer.get(b"Size").and_then(|size| size.as_i64()).ok()?;
+    u32::try_from(size.checked_sub(1)?).ok()
+}
+
+/// Splits a one-object document written by lopdf into its indirect object and its
+/// trailer dictionary.
+fn split_rendered_revision(rendered: &[u8]) -> Option<(&[u8], &[u8])> {
+    // Header line, then the (empty) binary mark line.
+    let object_start = rendered.iter().enumerate().filter(|(_, b)| **b == b'\n').nth(1)?.0 + 1;
+    let tail = rendered.windows(11).rposition(|w| w == b"\nstartxref\n")?;
+    let xref_start: usize = std::str::from_utf8(&rendered[tail + 11..]).ok()?.split_whitespace().next()?.parse().ok()?;
+    let trailer_keyword = rendered.get(xref_start..tail)?.windows(8).position(|w| w == b"trailer\n")?;
+    let trailer = &rendered[xref_start + trailer_keyword + 8..tail];
+    (trailer.starts_with(b"<<") && object_start <= xref_start).then(|| (&rendered[object_start..xref_start], trailer))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{create_classic_xref_test_pdf, create_minimal_test_pdf, setup_unique_test_dir};
+    use crate::{get_metadata, get_pdf_metadata, update_metadata_in_place};
+    use std::fs;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_reader_round_trip_appends_revisions() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("seekable_round_trip");
+        let file = test_dir.join("classic.pdf");
+        create_classic_xref_test_pdf(&file)?;
+        update_metadata_in_place(file.to_str().unwrap(), "Title", "Original")?;
+        let original = fs::read(&file)?;
+        assert_eq!(get_metadata_from_reader(Cursor::new(&original))?, get_metadata(file.to_str().unwrap())?);
+
+        let mut first = Vec::new();
+        set_metadata_from_reader(Cursor::new(&original), &mut first, "Author", "Streamed")?;
+        assert!(first.starts_with(&original));
+        let mut second = Vec::new();
+        set_metadata_from_reader(Cursor::new(&first), &mut second, "Title", "Revised")?;
+        assert!(second.starts_with(&first));
+        // The buffered fallback would have written lopdf's revision header.
+        assert!(!second[original.len()..].windows(5).any(|w| w == b"%PDF-"));
+
+        let metadata = get_pdf_metadata(&second)?;
+        assert!(metadata.iter().any(|(k, v)| k == "Title" && v == "Revised"));
+        assert!(metadata.iter().any(|(k, v)| k == "Author" && v == "Streamed"));
+        assert_eq!(get_metadata_from_reader(Cursor::new(&second))?, metadata);
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_reader_falls_back_for_xref_streams() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("seekable_fallback");
+        let file = test_dir.join("modern.pdf");
+        create_minimal_test_pdf(&file)?;
+        let original = fs::read(&file)?;
+
+        let mut output = Vec::new();
+        set_metadata_from_reader(Cursor::new(&original), &mut output, "Title", "Buffered")?;
+        assert!(output.starts_with(&original));
+        let metadata = get_metadata_from_reader(Cursor::new(&output))?;
+        assert!(metadata.iter().any(|(k, v)| k == "Title" && v == "Buffered"));
+
+        assert!(get_metadata_from_reader(Cursor::new(b"not a pdf")).is_err());
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,302 @@
+//! Digital signatures.
+//!
+//! A signature field (`/FT /Sig` in the AcroForm) holds a signature dictionary whose
+//! `/ByteRange` lists the two stretches of the file the signature covers: everything
+//! except the `/Contents` hole that holds the signature itself. A full rewrite moves
+//! those bytes and breaks the signature; appending an incremental update keeps it
+//! valid, but leaves the new revision outside the signed range.
+
+use crate::attachments::resolve_dict;
+use crate::{Error, Result, SignedDocPolicy, decode_string_object, load_document_mem, parse_pdf_date};
+use chrono::{DateTime, FixedOffset};
+use lopdf::{Dictionary, Document, Object, ObjectId};
+use std::collections::HashSet;
+#[cfg(feature = "std-fs")]
+use std::fs;
+#[cfg(feature = "std-fs")]
+use std::path::Path;
+
+/// Upper bound on the nesting of form fields; deeper fields are treated as damaged.
+const MAX_FIELD_DEPTH: usize = 32;
+
+/// A signature found in a PDF, as returned by [`get_signatures`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SignatureInfo {
+    /// The fully qualified name of the signature field, e.g. `Approval.Manager`.
+    pub field_name: String,
+    /// The signer's name (`/Name`), as the signing software recorded it. The name in
+    /// the certificate is not read.
+    pub signer: Option<String>,
+    /// The time of signing (`/M`), as claimed by the signer.
+    pub signed_at: Option<DateTime<FixedOffset>>,
+    pub reason: Option<String>,
+    pub location: Option<String>,
+    /// The signature format, e.g. `adbe.pkcs7.detached` or `ETSI.CAdES.detached`.
+    pub sub_filter: Option<String>,
+    /// The `/ByteRange` pairs of offset and length; empty when missing or malformed.
+    pub byte_range: Vec<u64>,
+    /// Whether the byte ranges span the whole file but the signature itself. When
+    /// `false`, the file was changed after signing, typically by an incremental update.
+    pub covers_whole_file: bool,
+}
+
+/// Lists the signed signature fields of a PDF, in form order.
+///
+/// Empty signature fields, waiting to be signed, are skipped. The signatures are
+/// described, not verified: no certificate or digest is checked.
+///
+/// # Returns
+///
+/// * `Ok(Vec<SignatureInfo>)`: The signatures; empty if the document is not signed.
+/// * `Err(Error)`: If the file cannot be read or parsed.
+///
+/// # Example
+///
+/// ```no_run
+/// use pdf_metadata::get_signatures;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     for signature in get_signatures("contract.pdf")? {
+///         if !signature.covers_whole_file {
+///             println!("{} was changed after signing", signature.field_name);
+///         }
+///     }
+///     Ok(())
+/// }
+/// ```
+#[cfg(feature = "std-fs")]
+pub fn get_signatures<P: AsRef<Path>>(file_path: P) -> Result<Vec<SignatureInfo>> {
+    let file_path = file_path.as_ref();
+    get_pdf_signatures(&fs::read(file_path)?)
+}
+
+/// Returns the signature fields of a PDF in memory, as [`get_signatures`] does for a file.
+pub fn get_pdf_signatures(pdf_content: &[u8]) -> Result<Vec<SignatureInfo>> {
+    let doc = load_document_mem(pdf_content)?;
+    Ok(signatures_in(&doc, pdf_content))
+}
+
+/// The signatures of `doc`, loaded from `bytes`.
+pub(crate) fn signatures_in(doc: &Document, bytes: &[u8]) -> Vec<SignatureInfo> {
+    let mut signatures = Vec::new();
+    let fields = doc
+        .catalog()
+        .ok()
+        .and_then(|catalog| resolve_dict(doc, catalog.get(b"AcroForm").ok()?))
+        .and_then(|form| form.get(b"Fields").ok())
+        .and_then(|fields| doc.dereference(fields).ok())
+        .and_then(|(_, fields)| fields.as_array().ok());
+    if let Some(fields) = fields {
+        let form = Form { doc, bytes };
+        form.collect_fields(fields, &Parent::default(), &mut HashSet::new(), &mut signatures);
+    }
+    signatures
+}
+
+/// Whether `doc` may be rewritten as a whole under `policy`. `Ok(false)` asks for an
+/// incremental update instead.
+pub(crate) fn rewrite_allowed(doc: &Document, policy: SignedDocPolicy) -> Result<bool> {
+    if policy == SignedDocPolicy::IgnoreAndRewrite {
+        return Ok(true);
+    }
+    match signatures_in(doc, &[]).len() {
+        0 => Ok(true),
+        count if policy == SignedDocPolicy::Error => Err(Error::Signed(count)),
+        _ => Ok(false),
+    }
+}
+
+struct Form<'a> {
+    doc: &'a Document,
+    /// The file the document was loaded from, to check byte ranges against.
+    bytes: &'a [u8],
+}
+
+/// What a field inherits from its ancestors.
+#[derive(Default)]
+struct Parent<'a> {
+    name: String,
+    field_type: Option<&'a [u8]>,
+    depth: usize,
+}
+
+impl<'a> Form<'a> {
+    /// Appends the signed signature fields among `fields` and their `/Kids`, depth first.
+    fn collect_fields(
+        &self,
+        fields: &'a [Object],
+        parent: &Parent<'a>,
+        visited: &mut HashSet<ObjectId>,
+        signatures: &mut Vec<SignatureInfo>,
+    ) {
+        if parent.depth > MAX_FIELD_DEPTH {
+            return;
+        }
+        for field in fields {
+            if let Object::Reference(id) = field
+                && !visited.insert(*id)
+            {
+                continue;
+            }
+            let Some(field) = resolve_dict(self.doc, field) else { continue };
+            let name = match field.get(b"T").ok().and_then(decode_string_object) {
+                Some(partial) if parent.name.is_empty() => partial,
+                Some(partial) => format!("{}.{}", parent.name, partial),
+                None => parent.name.clone(),
+            };
+            let field_type = field.get(b"FT").and_then(Object::as_name).ok().or(parent.field_type);
+            if field_type == Some(b"Sig")
+                && let Some(value) = field.get(b"V").ok().and_then(|value| resolve_dict(self.doc, value))
+            {
+                signatures.push(signature_info(name.clone(), value, self.bytes));
+            }
+            let kids = field.get(b"Kids").ok().and_then(|kids| self.doc.dereference(kids).ok());
+            if let Some((_, Object::Array(kids))) = kids {
+                let parent = Parent { name, field_type, depth: parent.depth + 1 };
+                self.collect_fields(kids, &parent, visited, signatures);
+            }
+        }
+    }
+}
+
+fn signature_info(field_name: String, signature: &Dictionary, bytes: &[u8]) -> SignatureInfo {
+    let text = |key: &[u8]| signature.get(key).ok().and_then(decode_string_object);
+    let byte_range: Vec<u64> = signature
+        .get(b"ByteRange")
+        .and_then(Object::as_array)
+        .map(|range| range.iter().map(|n| n.as_i64().ok().and_then(|n| u64::try_from(n).ok())).collect())
+        .ok()
+        .flatten()
+        .filter(|range: &Vec<u64>| range.len().is_multiple_of(2))
+        .unwrap_or_default();
+    SignatureInfo {
+        field_name,
+        signer: text(b"Name"),
+        signed_at: text(b"M").and_then(|date| parse_pdf_date(&date).ok()),
+        reason: text(b"Reason"),
+        location: text(b"Location"),
+        sub_filter: signature
+            .get(b"SubFilter")
+            .and_then(Object::as_name)
+            .ok()
+            .map(|name| String::from_utf8_lossy(name).into_owned()),
+        covers_whole_file: covers_whole_file(&byte_range, bytes),
+        byte_range,
+    }
+}
+
+/// Whether `byte_range` is `[0 a b c]` with `b + c` the file length and only the
+/// `<...>` hex string of the signature between `a` and `b`.
+fn covers_whole_file(byte_range: &[u64], bytes: &[u8]) -> bool {
+    let &[0, first_len, second_start, second_len] = byte_range else { return false };
+    let (Ok(hole_start), Ok(hole_end)) = (usize::try_from(first_len), usize::try_from(second_start)) else {
+        return false;
+    };
+    second_start.checked_add(second_len) == Some(bytes.len() as u64)
+        && hole_start < hole_end
+        && bytes.get(hole_start) == Some(&b'<')
+        && bytes.get(hole_end - 1) == Some(&b'>')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{create_signed_test_pdf, setup_unique_test_dir};
+    use crate::scrub::strip_metadata;
+    use crate::{
+        MetadataEditor, SetOptions, get_lang, get_metadata, remove_metadata_in_place, set_lang,
+        update_metadata_in_place_with_options, update_metadata_incremental, with_write_options,
+    };
+
+    #[test]
+    fn test_get_signatures_reports_signed_fields() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("signatures");
+        let file = test_dir.join("contract.pdf");
+        create_signed_test_pdf(&file)?;
+        let file_str = file.to_str().unwrap();
+
+        let signatures = get_signatures(file_str)?;
+        assert_eq!(signatures.len(), 1, "the unsigned Witness field is skipped");
+        let signature = &signatures[0];
+        assert_eq!(signature.field_name, "Approval.Manager");
+        assert_eq!(signature.signer.as_deref(), Some("Ana Souza"));
+        assert_eq!(signature.signed_at, Some(DateTime::parse_from_rfc3339("2024-03-01T10:00:00-03:00")?));
+        assert_eq!(signature.reason.as_deref(), Some("Aprovado"));
+        assert_eq!(signature.location, None);
+        assert_eq!(signature.sub_filter.as_deref(), Some("adbe.pkcs7.detached"));
+        assert_eq!(signature.byte_range.len(), 4);
+        assert!(signature.covers_whole_file);
+
+        // An incremental update keeps the signed bytes but is not covered by them.
+        update_metadata_incremental(file_str, "Subject", "Arquivado")?;
+        let signatures = get_signatures(file_str)?;
+        assert_eq!(signatures[0].signer.as_deref(), Some("Ana Souza"));
+        assert!(!signatures[0].covers_whole_file);
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_signed_doc_policy() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("signed_policy");
+        let file = test_dir.join("contract.pdf");
+        create_signed_test_pdf(&file)?;
+        let file_str = file.to_str().unwrap();
+        let original = fs::read(&file)?;
+        let policy = |on_signed| SetOptions { on_signed, ..SetOptions::default() };
+
+        let refused = update_metadata_in_place_with_options(file_str, "Subject", "x", &policy(SignedDocPolicy::Error));
+        assert!(matches!(refused, Err(Error::Signed(1))));
+        assert_eq!(fs::read(&file)?, original);
+
+        update_metadata_in_place_with_options(file_str, "Subject", "Arquivado", &policy(SignedDocPolicy::ForceIncremental))?;
+        let appended = fs::read(&file)?;
+        assert!(appended.starts_with(&original));
+        assert!(get_metadata(file_str)?.iter().any(|(k, v)| k == "Subject" && v == "Arquivado"));
+
+        let copy = test_dir.join("copy.pdf");
+        MetadataEditor::open(&file)?
+            .remove("Subject")
+            .options(policy(SignedDocPolicy::ForceIncremental))
+            .save_as(&copy)?;
+        assert!(fs::read(&copy)?.starts_with(&appended));
+        assert!(!get_metadata(copy.to_str().unwrap())?.iter().any(|(k, _)| k == "Subject"));
+
+        // The default rewrites the file, moving the signed bytes.
+        update_metadata_in_place_with_options(file_str, "Subject", "Reescrito", &SetOptions::default())?;
+        assert!(!fs::read(&file)?.starts_with(&original));
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_writers_without_options_follow_the_scoped_policy() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("signed_policy_scoped");
+        let file = test_dir.join("contract.pdf");
+        create_signed_test_pdf(&file)?;
+        let original = fs::read(&file)?;
+        let policy = |on_signed| SetOptions { on_signed, ..SetOptions::default() };
+
+        let refused = with_write_options(policy(SignedDocPolicy::Error), || remove_metadata_in_place(&file, "Title"));
+        assert!(matches!(refused, Err(Error::Signed(1))));
+        let refused = with_write_options(policy(SignedDocPolicy::Error), || set_lang(&file, "pt-BR"));
+        assert!(matches!(refused, Err(Error::Signed(1))));
+        assert_eq!(fs::read(&file)?, original);
+
+        // Changes beyond the Info dictionary are appended too.
+        with_write_options(policy(SignedDocPolicy::ForceIncremental), || set_lang(&file, "pt-BR"))?;
+        assert!(fs::read(&file)?.starts_with(&original));
+        assert_eq!(get_lang(&file)?.as_deref(), Some("pt-BR"));
+        assert!(get_signatures(&file)?.iter().all(|signature| signature.signer.as_deref() == Some("Ana Souza")));
+
+        // Stripping could not remove what the signed revision keeps, so it is refused.
+        let stripped = with_write_options(policy(SignedDocPolicy::ForceIncremental), || strip_metadata(&file, &file));
+        assert!(matches!(stripped, Err(Error::UnsupportedFormat(_))));
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,287 @@
+//! In-process usage counters.
+//!
+//! Every read and write of this crate updates a set of counters, so operators can
+//! describe a corpus (how many files needed a full parse, which string encodings
+//! occur, how many values were damaged) from the work they already do. Nothing
+//! leaves the process.
+//!
+//! Counts are kept per piece of work: [`collect_stats`] returns the counters of one
+//! operation on the current thread, `batch::run_with_stats` those of one batch and
+//! [`MetadataEditor`](crate::MetadataEditor) those of one editing session, unaffected
+//! by anything else the process does at the same time. [`stats`] returns the
+//! process-wide totals.
+
+use std::cell::RefCell;
+use std::ops::{Add, AddAssign, Sub};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// A snapshot of the usage counters, as returned by [`collect_stats`] or [`stats`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UsageStats {
+    /// Documents whose Info dictionary was read through the fast path, without a full parse.
+    pub documents_scanned: u64,
+    /// Documents parsed in full.
+    pub documents_parsed: u64,
+    /// Documents written, in place, to a new file or to memory.
+    pub documents_written: u64,
+    /// Strings read from documents that were plain ASCII.
+    pub ascii_strings: u64,
+    /// Strings read as UTF-8 without a byte order mark.
+    pub utf8_strings: u64,
+    /// Strings read as UTF-16BE with a byte order mark.
+    pub utf16be_strings: u64,
+    /// Strings read as UTF-16LE with a byte order mark.
+    pub utf16le_strings: u64,
+    /// Strings read as PDFDocEncoding.
+    pub pdf_doc_encoding_strings: u64,
+    /// Strings in which bytes without a meaning were replaced by U+FFFD.
+    pub strings_repaired: u64,
+}
+
+impl Sub for UsageStats {
+    type Output = UsageStats;
+
+    /// The counters accumulated since `earlier`, saturating at zero after a reset.
+    fn sub(self, earlier: UsageStats) -> UsageStats {
+        UsageStats {
+            documents_scanned: self.documents_scanned.saturating_sub(earlier.documents_scanned),
+            documents_parsed: self.documents_parsed.saturating_sub(earlier.documents_parsed),
+            documents_written: self.documents_written.saturating_sub(earlier.documents_written),
+            ascii_strings: self.ascii_strings.saturating_sub(earlier.ascii_strings),
+            utf8_strings: self.utf8_strings.saturating_sub(earlier.utf8_strings),
+            utf16be_strings: self.utf16be_strings.saturating_sub(earlier.utf16be_strings),
+            utf16le_strings: self.utf16le_strings.saturating_sub(earlier.utf16le_strings),
+            pdf_doc_encoding_strings: self.pdf_doc_encoding_strings.saturating_sub(earlier.pdf_doc_encoding_strings),
+            strings_repaired: self.strings_repaired.saturating_sub(earlier.strings_repaired),
+        }
+    }
+}
+
+impl Add for UsageStats {
+    type Output = UsageStats;
+
+    fn add(self, other: UsageStats) -> UsageStats {
+        UsageStats {
+            documents_scanned: self.documents_scanned + other.documents_scanned,
+            documents_parsed: self.documents_parsed + other.documents_parsed,
+            documents_written: self.documents_written + other.documents_written,
+            ascii_strings: self.ascii_strings + other.ascii_strings,
+            utf8_strings: self.utf8_strings + other.utf8_strings,
+            utf16be_strings: self.utf16be_strings + other.utf16be_strings,
+            utf16le_strings: self.utf16le_strings + other.utf16le_strings,
+            pdf_doc_encoding_strings: self.pdf_doc_encoding_strings + other.pdf_doc_encoding_strings,
+            strings_repaired: self.strings_repaired + other.strings_repaired,
+        }
+    }
+}
+
+impl AddAssign for UsageStats {
+    fn add_assign(&mut self, other: UsageStats) {
+        *self = *self + other;
+    }
+}
+
+/// The events counted in [`UsageStats`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Counter {
+    DocumentScanned,
+    DocumentParsed,
+    DocumentWritten,
+    StringRepaired,
+}
+
+/// The encoding a document string was decoded with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TextEncoding {
+    Ascii,
+    Utf8,
+    Utf16Be,
+    Utf16Le,
+    PdfDocEncoding,
+}
+
+const COUNTERS: usize = 9;
+
+/// One set of counters, shared by the threads working for the same scope.
+#[derive(Debug)]
+pub(crate) struct Counts([AtomicU64; COUNTERS]);
+
+impl Counts {
+    pub(crate) const fn new() -> Self {
+        Counts([const { AtomicU64::new(0) }; COUNTERS])
+    }
+
+    fn add(&self, slot: usize) {
+        self.0[slot].fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn snapshot(&self) -> UsageStats {
+        let count = |slot: usize| self.0[slot].load(Ordering::Relaxed);
+        UsageStats {
+            documents_scanned: count(slot(Counter::DocumentScanned)),
+            documents_parsed: count(slot(Counter::DocumentParsed)),
+            documents_written: count(slot(Counter::DocumentWritten)),
+            ascii_strings: count(encoding_slot(TextEncoding::Ascii)),
+            utf8_strings: count(encoding_slot(TextEncoding::Utf8)),
+            utf16be_strings: count(encoding_slot(TextEncoding::Utf16Be)),
+            utf16le_strings: count(encoding_slot(TextEncoding::Utf16Le)),
+            pdf_doc_encoding_strings: count(encoding_slot(TextEncoding::PdfDocEncoding)),
+            strings_repaired: count(slot(Counter::StringRepaired)),
+        }
+    }
+}
+
+static TOTALS: Counts = Counts::new();
+
+thread_local! {
+    /// The scopes the current thread is working for, innermost last. Each event is
+    /// counted in all of them, so nested scopes (an editor inside a batch) both see it.
+    static SCOPES: RefCell<Vec<Arc<Counts>>> = const { RefCell::new(Vec::new()) };
+}
+
+fn slot(counter: Counter) -> usize {
+    match counter {
+        Counter::DocumentScanned => 0,
+        Counter::DocumentParsed => 1,
+        Counter::DocumentWritten => 2,
+        Counter::StringRepaired => 3,
+    }
+}
+
+fn encoding_slot(encoding: TextEncoding) -> usize {
+    match encoding {
+        TextEncoding::Ascii => 4,
+        TextEncoding::Utf8 => 5,
+        TextEncoding::Utf16Be => 6,
+        TextEncoding::Utf16Le => 7,
+        TextEncoding::PdfDocEncoding => 8,
+    }
+}
+
+fn count(slot: usize) {
+    TOTALS.add(slot);
+    SCOPES.with(|scopes| {
+        for scope in scopes.borrow().iter() {
+            scope.add(slot);
+        }
+    });
+}
+
+pub(crate) fn record(counter: Counter) {
+    count(slot(counter));
+}
+
+/// Counts a string read from a document.
+pub(crate) fn record_string(encoding: TextEncoding, decoded: &str) {
+    count(encoding_slot(encoding));
+    if decoded.contains('\u{FFFD}') {
+        record(Counter::StringRepaired);
+    }
+}
+
+/// Runs `operation` on the current thread with its events also counted in `counts`.
+///
+/// Work `operation` hands to other threads is not counted; callers that spread work
+/// over threads enter the same `counts` on each of them.
+pub(crate) fn scoped<T>(counts: &Arc<Counts>, operation: impl FnOnce() -> T) -> T {
+    /// Leaves the scope even if `operation` panics.
+    struct Exit;
+
+    impl Drop for Exit {
+        fn drop(&mut self) {
+            SCOPES.with(|scopes| scopes.borrow_mut().pop());
+        }
+    }
+
+    SCOPES.with(|scopes| scopes.borrow_mut().push(Arc::clone(counts)));
+    let _exit = Exit;
+    operation()
+}
+
+/// Runs `operation` and returns its result with the usage counters of the work it
+/// did on the current thread.
+///
+/// Work done by other threads at the same time is not included.
+///
+/// # Example
+///
+/// ```no_run
+/// use pdf_metadata::{collect_stats, get_metadata};
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let (metadata, used) = collect_stats(|| get_metadata("path/to/document.pdf"));
+///     metadata?;
+///     println!("{} full parses, {} UTF-16BE strings", used.documents_parsed, used.utf16be_strings);
+///     Ok(())
+/// }
+/// ```
+pub fn collect_stats<T>(operation: impl FnOnce() -> T) -> (T, UsageStats) {
+    let counts = Arc::new(Counts::new());
+    let result = scoped(&counts, operation);
+    (result, counts.snapshot())
+}
+
+/// Returns the usage counters accumulated by the whole process.
+///
+/// These include the work of every thread; use [`collect_stats`] to measure one
+/// operation. Subtracting an earlier snapshot gives the work done in between.
+pub fn stats() -> UsageStats {
+    TOTALS.snapshot()
+}
+
+/// Sets every process-wide usage counter back to zero.
+pub fn reset_stats() {
+    for count in &TOTALS.0 {
+        count.store(0, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{create_classic_xref_test_pdf, setup_unique_test_dir};
+    use crate::{get_metadata, get_pdf_metadata, update_entries_in_place};
+    use std::fs;
+
+    #[test]
+    fn test_stats_count_reads_writes_and_encodings() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("stats_counts");
+        let file = test_dir.join("doc.pdf");
+        create_classic_xref_test_pdf(&file)?;
+
+        let (written, used) = collect_stats(|| update_entries_in_place(&file, &[("Title", "Café"), ("Author", "Ana")]));
+        written?;
+        assert_eq!(used.documents_parsed, 1);
+        assert_eq!(used.documents_written, 1);
+
+        let (read, used) = collect_stats(|| get_metadata(&file));
+        read?;
+        // Title is UTF-16BE; Author and the ModDate written above are ASCII.
+        assert_eq!(
+            used,
+            UsageStats { documents_scanned: 1, ascii_strings: 2, utf16be_strings: 1, ..UsageStats::default() }
+        );
+
+        let mut bytes = fs::read(&file)?;
+        let at = bytes.windows(3).position(|w| w == b"Ana").unwrap();
+        bytes[at + 1] = 0x9F; // Undefined in PDFDocEncoding, invalid as UTF-8.
+        let (read, used) = collect_stats(|| get_pdf_metadata(&bytes));
+        read?;
+        assert_eq!(used.documents_scanned, 1);
+        assert_eq!(used.pdf_doc_encoding_strings, 1);
+        assert_eq!(used.strings_repaired, 1);
+
+        // Nested scopes both count; the process totals include everything.
+        let ((_, inner), outer) = collect_stats(|| collect_stats(|| get_metadata(&file)));
+        assert_eq!(inner, outer);
+        assert_eq!(inner.documents_scanned, 1);
+        assert!(stats().documents_scanned >= 4);
+        assert_eq!(UsageStats::default() - used, UsageStats::default());
+        assert_eq!(used + used - used, used);
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,162 @@
+//! # Metadata templates
+//!
+//! Bulk tagging usually sets the same fields on every file with values derived from
+//! the file itself. A [`MetadataTemplate`] holds those fields with `{{placeholder}}`
+//! values, and [`apply_template`] expands and writes them for one file; combine it
+//! with the [`batch`](crate::batch) engine for whole directories.
+//!
+//! | Placeholder | Replaced by |
+//! |-------------|-------------|
+//! | `{{filename}}` | the file name, e.g. `scan_0042.pdf` |
+//! | `{{stem}}` | the file name without extension, e.g. `scan_0042` |
+//! | `{{ext}}` | the extension, without the dot |
+//! | `{{parent_dir}}` | the name of the file's directory |
+//! | `{{date}}`, `{{year}}`, `{{month}}`, `{{day}}` | the date of the run, as `YYYY-MM-DD`, `YYYY`, `MM`, `DD` |
+//!
+//! ```no_run
+//! use pdf_metadata::batch::{self, BatchOptions};
+//! use pdf_metadata::template::{MetadataTemplate, apply_template};
+//!
+//! fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let template = MetadataTemplate::new(&[
+//!         ("Title", "Scan {{stem}}"),
+//!         ("Subject", "Box {{parent_dir}}, digitized {{date}}"),
+//!     ])?;
+//!     let files = batch::collect_pdfs("archive/box-17", false)?;
+//!     for outcome in batch::run(&files, &BatchOptions::default(), |path| apply_template(path, &template)) {
+//!         println!("{}: {:?}", outcome.path.display(), outcome.result.is_ok());
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use crate::{Error, Result, clock, update_entries_in_place};
+use chrono::NaiveDate;
+use std::path::Path;
+
+/// Placeholders accepted by [`MetadataTemplate`].
+const PLACEHOLDERS: [&str; 8] = ["filename", "stem", "ext", "parent_dir", "date", "year", "month", "day"];
+
+/// A set of Info fields whose values may contain `{{placeholder}}`s.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MetadataTemplate {
+    fields: Vec<(String, String)>,
+    date: NaiveDate,
+}
+
+impl MetadataTemplate {
+    /// Parses the `(key, value template)` fields, dating the run today.
+    ///
+    /// Fails with `Error::UnsupportedFormat` on unknown or unclosed placeholders.
+    pub fn new<K: AsRef<str>, V: AsRef<str>>(fields: &[(K, V)]) -> Result<Self> {
+        for (_, template) in fields {
+            let template = template.as_ref();
+            let mut rest = template;
+            while let Some(open) = rest.find("{{") {
+                let close = rest[open..].find("}}").ok_or_else(|| {
+                    Error::UnsupportedFormat(format!("unclosed placeholder in template '{}'", template))
+                })?;
+                let placeholder = rest[open + 2..open + close].trim();
+                if !PLACEHOLDERS.contains(&placeholder) {
+                    return Err(Error::UnsupportedFormat(format!("template placeholder '{{{{{}}}}}'", placeholder)));
+                }
+                rest = &rest[open + close + 2..];
+            }
+        }
+        Ok(MetadataTemplate {
+            fields: fields.iter().map(|(k, v)| (k.as_ref().to_string(), v.as_ref().to_string())).collect(),
+            date: clock::now().date_naive(),
+        })
+    }
+
+    /// Uses `date` for the date placeholders instead of today.
+    pub fn with_date(mut self, date: NaiveDate) -> Self {
+        self.date = date;
+        self
+    }
+
+    /// Returns the fields with their placeholders replaced for the file at `path`.
+    pub fn expand(&self, path: &Path) -> Vec<(String, String)> {
+        let text = |s: Option<&std::ffi::OsStr>| s.map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+        let values = [
+            ("filename", text(path.file_name())),
+            ("stem", text(path.file_stem())),
+            ("ext", text(path.extension())),
+            ("parent_dir", text(path.parent().and_then(Path::file_name))),
+            ("date", self.date.format("%Y-%m-%d").to_string()),
+            ("year", self.date.format("%Y").to_string()),
+            ("month", self.date.format("%m").to_string()),
+            ("day", self.date.format("%d").to_string()),
+        ];
+        self.fields.iter().map(|(key, template)| (key.clone(), expand_one(template, &values))).collect()
+    }
+}
+
+/// Replaces every `{{placeholder}}` of `template` in one pass, so expanded values
+/// that happen to contain braces are left alone.
+fn expand_one(template: &str, values: &[(&str, String)]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find("{{") {
+        out.push_str(&rest[..open]);
+        let Some(close) = rest[open..].find("}}") else { break };
+        let placeholder = rest[open + 2..open + close].trim();
+        match values.iter().find(|(name, _)| *name == placeholder) {
+            Some((_, value)) => out.push_str(value),
+            None => out.push_str(&rest[open..open + close + 2]),
+        }
+        rest = &rest[open + close + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Expands `template` for the file at `path` and writes the fields in place.
+///
+/// `ModDate` is refreshed as by [`update_metadata_in_place`](crate::update_metadata_in_place).
+///
+/// # Returns
+///
+/// * `Ok(Vec<(String, String)>)`: The entries that were written.
+/// * `Err(Error)`: If the file cannot be read or written.
+pub fn apply_template(path: &Path, template: &MetadataTemplate) -> Result<Vec<(String, String)>> {
+    let entries = template.expand(path);
+    update_entries_in_place(path, &entries)?;
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::get_metadata;
+    use crate::test_support::{create_minimal_test_pdf, setup_unique_test_dir};
+    use std::fs;
+
+    #[test]
+    fn test_apply_template() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("template_apply");
+        let dir = test_dir.join("box-17");
+        fs::create_dir(&dir)?;
+        let file = dir.join("scan_0042.pdf");
+        create_minimal_test_pdf(&file)?;
+
+        let template = MetadataTemplate::new(&[
+            ("Title", "Scan {{ stem }} ({{filename}})"),
+            ("Subject", "{{parent_dir}}, {{date}} / {{year}}{{month}}{{day}}"),
+        ])?
+        .with_date(NaiveDate::from_ymd_opt(2024, 3, 9).unwrap());
+        let written = apply_template(&file, &template)?;
+        assert_eq!(written[0].1, "Scan scan_0042 (scan_0042.pdf)");
+        assert_eq!(written[1].1, "box-17, 2024-03-09 / 20240309");
+
+        let metadata = get_metadata(file.to_str().unwrap())?;
+        assert!(written.iter().all(|entry| metadata.contains(entry)));
+
+        assert!(matches!(MetadataTemplate::new(&[("Title", "{{author}}")]), Err(Error::UnsupportedFormat(_))));
+        assert!(matches!(MetadataTemplate::new(&[("Title", "{{stem")]), Err(Error::UnsupportedFormat(_))));
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,125 @@
+//! Helpers shared by the unit tests of every module.
+
+use lopdf::xref::XrefType;
+use lopdf::{Dictionary, Document, Object, StringFormat, dictionary};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Helper function to create a unique test directory.
+// Returns the path to the created directory.
+pub(crate) fn setup_unique_test_dir(test_name: &str) -> PathBuf {
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_micros();
+    let mut temp_dir = env::temp_dir();
+    // Create a subdirectory specific to the test run and test name
+    temp_dir.push("pdf_metadata_tests");
+    temp_dir.push(format!("{}_{}", test_name, millis));
+    fs::create_dir_all(&temp_dir).expect("Failed to create temp test directory");
+    temp_dir
+}
+
+// Serialises the tests that set `SOURCE_DATE_EPOCH`.
+static SOURCE_DATE_EPOCH_LOCK: Mutex<()> = Mutex::new(());
+
+// Runs `f` with the `SOURCE_DATE_EPOCH` environment variable set to `seconds`. Tests
+// running meanwhile do not take the lock, so pass a time in the far future: their
+// dates are then never clamped.
+pub(crate) fn with_source_date_epoch<T>(seconds: i64, f: impl FnOnce() -> T) -> T {
+    struct Unset;
+    impl Drop for Unset {
+        fn drop(&mut self) {
+            // SAFETY: the variable is only read through `std::env`, which synchronises
+            // access to the environment.
+            unsafe { env::remove_var("SOURCE_DATE_EPOCH") };
+        }
+    }
+
+    let _lock = SOURCE_DATE_EPOCH_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    // SAFETY: as above.
+    unsafe { env::set_var("SOURCE_DATE_EPOCH", seconds.to_string()) };
+    let _unset = Unset;
+    f()
+}
+
+// Helper function to create a minimal PDF for testing.
+pub(crate) fn create_minimal_test_pdf(path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    fs::write(path, minimal_test_pdf_bytes()?)?;
+    Ok(())
+}
+
+// The bytes of the PDF `create_minimal_test_pdf` writes, for the in-memory API.
+pub(crate) fn minimal_test_pdf_bytes() -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let mut doc = Document::with_version("1.7");
+    let mut catalog_dict = Dictionary::new();
+    catalog_dict.set("Type", Object::Name(b"Catalog".to_vec()));
+    let mut pages_dict = Dictionary::new();
+    pages_dict.set("Type", Object::Name(b"Pages".to_vec()));
+    pages_dict.set("Count", Object::Integer(0)); // Minimal page count
+    pages_dict.set("Kids", Object::Array(vec![])); // No actual pages
+    let pages_id = doc.add_object(pages_dict);
+    catalog_dict.set("Pages", Object::Reference(pages_id));
+    let catalog_id = doc.add_object(catalog_dict);
+    doc.trailer.set("Root", Object::Reference(catalog_id));
+    let mut bytes = Vec::new();
+    doc.save_to(&mut bytes)?;
+    Ok(bytes)
+}
+
+// Like `create_minimal_test_pdf`, but with a classic cross-reference table instead of
+// the cross-reference stream lopdf writes by default.
+pub(crate) fn create_classic_xref_test_pdf(path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    create_minimal_test_pdf(path)?;
+    let mut doc = Document::load(path)?;
+    doc.reference_table.cross_reference_type = XrefType::CrossReferenceTable;
+    doc.save(path)?;
+    Ok(())
+}
+
+// Writes a document with one signed field, `Approval.Manager`, whose byte range
+// covers the file as written.
+pub(crate) fn create_signed_test_pdf(path: &Path) -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let mut doc = Document::with_version("1.7");
+    let pages_id = doc.add_object(dictionary! { "Type" => "Pages", "Count" => 0, "Kids" => vec![] });
+    let signature_id = doc.add_object(dictionary! {
+        "Type" => "Sig",
+        "Filter" => "Adobe.PPKLite",
+        "SubFilter" => "adbe.pkcs7.detached",
+        "Name" => Object::string_literal("Ana Souza"),
+        "M" => Object::string_literal("D:20240301100000-03'00'"),
+        "Reason" => Object::string_literal("Aprovado"),
+        // Placeholders, patched below once the offsets are known.
+        "ByteRange" => vec![1_000_000_000.into(), 1_000_000_000.into(), 1_000_000_000.into(), 1_000_000_000.into()],
+        "Contents" => Object::String(vec![0; 64], StringFormat::Hexadecimal),
+    });
+    let manager_id = doc.add_object(dictionary! { "T" => Object::string_literal("Manager"), "V" => signature_id });
+    let unsigned_id = doc.add_object(dictionary! { "T" => Object::string_literal("Witness") });
+    let approval_id = doc.add_object(dictionary! {
+        "FT" => "Sig",
+        "T" => Object::string_literal("Approval"),
+        "Kids" => vec![manager_id.into(), unsigned_id.into()],
+    });
+    let catalog_id = doc.add_object(dictionary! {
+        "Type" => "Catalog",
+        "Pages" => pages_id,
+        "AcroForm" => dictionary! { "Fields" => vec![approval_id.into()], "SigFlags" => 3 },
+    });
+    doc.trailer.set("Root", catalog_id);
+    let mut bytes = Vec::new();
+    doc.save_to(&mut bytes)?;
+
+    let find = |needle: &[u8]| bytes.windows(needle.len()).position(|w| w == needle).unwrap();
+    let hole_start = find(b"/Contents<") + b"/Contents".len();
+    let hole_end = hole_start + bytes[hole_start..].iter().position(|&b| b == b'>').unwrap() + 1;
+    let range_start = find(b"/ByteRange[");
+    let range_end = range_start + bytes[range_start..].iter().position(|&b| b == b']').unwrap() + 1;
+    let range = format!("/ByteRange[0 {} {} {}", hole_start, hole_end, bytes.len() - hole_end);
+    let patched = format!("{:<width$}]", range, width = range_end - range_start - 1);
+    bytes.splice(range_start..range_end, patched.into_bytes());
+    fs::write(path, bytes)?;
+    Ok(())
+}
@@ -0,0 +1,191 @@
+//! Text statistics (`text-stats` feature).
+//!
+//! Word processors record how long a document is (Word keeps `Pages`, `Words`,
+//! `Characters` and `CharactersWithSpaces` in its document properties), and catalog
+//! and billing systems read those counts. PDFs have no such entries, so this module
+//! extracts the text shown by each page's content stream and counts it.
+//!
+//! Text is decoded with the fonts' simple encodings, as lopdf's text extraction
+//! does. Text in Type 3 or composite fonts without a usable encoding, in form
+//! XObjects or in annotations is not counted, and scanned pages have no text at all,
+//! so the counts are a lower bound.
+
+use crate::{Result, apply_entries_with, load_document, options, write_file};
+use lopdf::content::Content;
+use lopdf::{Document, Encoding, Object, ObjectId};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// The counts computed by [`compute_text_stats`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TextStats {
+    pub pages: u32,
+    /// Runs of characters separated by whitespace.
+    pub words: u64,
+    /// Characters other than whitespace.
+    pub characters: u64,
+    /// Characters including spaces, but not line breaks.
+    pub characters_with_spaces: u64,
+}
+
+/// Counts the pages, words and characters of the text shown on the document's pages.
+///
+/// # Returns
+///
+/// * `Ok(TextStats)`: The counts; zero words for a document without extractable text.
+/// * `Err(Error)`: If the file cannot be read or parsed.
+pub fn compute_text_stats<P: AsRef<Path>>(file_path: P) -> Result<TextStats> {
+    let file_path = file_path.as_ref();
+    let doc = load_document(file_path)?;
+    Ok(text_stats(&doc))
+}
+
+/// Counts the document's text, as [`compute_text_stats`] does, and stores the counts
+/// in the Info dictionary, writing the result to `output_path`.
+///
+/// The counts are stored as text under the custom keys Word uses for them: `Pages`,
+/// `Words`, `Characters` and `CharactersWithSpaces`. `ModDate` is refreshed.
+/// `output_path` may be the input file.
+///
+/// # Returns
+///
+/// * `Ok(TextStats)`: The counts stored.
+/// * `Err(Error)`: If the file cannot be read, parsed or written.
+///
+/// # Example
+///
+/// ```no_run
+/// use pdf_metadata::text_stats::compute_and_store_stats;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let stats = compute_and_store_stats("thesis.pdf", "thesis.pdf")?;
+///     println!("{} words on {} pages", stats.words, stats.pages);
+///     Ok(())
+/// }
+/// ```
+pub fn compute_and_store_stats<P: AsRef<Path>, Q: AsRef<Path>>(file_path: P, output_path: Q) -> Result<TextStats> {
+    let options = options::current();
+    write_file(file_path.as_ref(), Some(output_path.as_ref()), &options, |doc| {
+        let stats = text_stats(doc);
+        let entries = [
+            ("Pages", stats.pages.to_string()),
+            ("Words", stats.words.to_string()),
+            ("Characters", stats.characters.to_string()),
+            ("CharactersWithSpaces", stats.characters_with_spaces.to_string()),
+        ];
+        apply_entries_with(doc, &entries, &options)?;
+        Ok(stats)
+    })
+}
+
+fn text_stats(doc: &Document) -> TextStats {
+    let pages = doc.get_pages();
+    let mut stats = TextStats { pages: pages.len() as u32, ..TextStats::default() };
+    for page_id in pages.into_values() {
+        // A page whose content cannot be decoded counts as empty.
+        let text = page_text(doc, page_id).unwrap_or_default();
+        stats.words += text.split_whitespace().count() as u64;
+        stats.characters += text.chars().filter(|c| !c.is_whitespace()).count() as u64;
+        stats.characters_with_spaces += text.lines().map(|line| line.trim().chars().count() as u64).sum::<u64>();
+    }
+    stats
+}
+
+/// The text shown by the page's content stream, with a line break wherever the
+/// text moves to a new line or block and a space for wide `TJ` gaps.
+fn page_text(doc: &Document, page_id: ObjectId) -> Result<String> {
+    let encodings: BTreeMap<Vec<u8>, Encoding> = doc
+        .get_page_fonts(page_id)?
+        .into_iter()
+        .filter_map(|(name, font)| Some((name, font.get_font_encoding(doc).ok()?)))
+        .collect();
+    let content = Content::decode(&doc.get_page_content(page_id)?)?;
+
+    let mut text = String::new();
+    let mut encoding = None;
+    for operation in &content.operations {
+        match operation.operator.as_str() {
+            "Tf" => {
+                let font = operation.operands.first().and_then(|name| name.as_name().ok());
+                encoding = font.and_then(|font| encodings.get(font));
+            }
+            "Td" | "TD" | "T*" | "Tm" | "BT" | "ET" | "'" | "\"" => text.push('\n'),
+            _ => {}
+        }
+        if let ("Tj" | "TJ" | "'" | "\"", Some(encoding)) = (operation.operator.as_str(), encoding) {
+            shown_text(encoding, &operation.operands, &mut text);
+        }
+    }
+    Ok(text)
+}
+
+/// Appends the strings among `operands`, the arguments of a text-showing operator.
+fn shown_text(encoding: &Encoding, operands: &[Object], text: &mut String) {
+    for operand in operands {
+        match operand {
+            Object::String(bytes, _) => text.push_str(&Document::decode_text(encoding, bytes).unwrap_or_default()),
+            Object::Array(items) => shown_text(encoding, items, text),
+            // A gap of more than a tenth of the font size separates words.
+            Object::Integer(gap) if *gap < -100 => text.push(' '),
+            Object::Real(gap) if *gap < -100.0 => text.push(' '),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::get_metadata;
+    use crate::test_support::setup_unique_test_dir;
+    use lopdf::{Stream, dictionary};
+    use std::fs;
+
+    #[test]
+    fn test_compute_and_store_stats() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("text_stats");
+        let file = test_dir.join("doc.pdf");
+
+        let mut doc = Document::with_version("1.7");
+        let pages_id = doc.new_object_id();
+        let font_id = doc.add_object(dictionary! {
+            "Type" => "Font", "Subtype" => "Type1", "BaseFont" => "Helvetica", "Encoding" => "WinAnsiEncoding",
+        });
+        let contents: [&[u8]; 2] = [
+            // "Olá" is WinAnsi; the lines are separate words only thanks to Td.
+            b"BT /F1 12 Tf 72 720 Td (Ol\xE1 mundo) Tj 0 -14 Td (segunda) Tj T* [(li) 20 (nha) -250 (final)] TJ ET",
+            b"BT /F1 12 Tf 72 720 Td (Fim.) Tj ET",
+        ];
+        let mut kids = Vec::new();
+        for content in contents {
+            let content_id = doc.add_object(Stream::new(dictionary! {}, content.to_vec()));
+            kids.push(
+                doc.add_object(dictionary! {
+                    "Type" => "Page",
+                    "Parent" => pages_id,
+                    "Contents" => content_id,
+                    "Resources" => dictionary! { "Font" => dictionary! { "F1" => font_id } },
+                })
+                .into(),
+            );
+        }
+        doc.objects.insert(pages_id, dictionary! { "Type" => "Pages", "Count" => 2, "Kids" => kids }.into());
+        let catalog_id = doc.add_object(dictionary! { "Type" => "Catalog", "Pages" => pages_id });
+        doc.trailer.set("Root", catalog_id);
+        doc.save(&file)?;
+        let file_str = file.to_str().unwrap();
+
+        let expected = TextStats { pages: 2, words: 6, characters: 29, characters_with_spaces: 31 };
+        assert_eq!(compute_text_stats(file_str)?, expected);
+        let output = test_dir.join("out.pdf");
+        assert_eq!(compute_and_store_stats(file_str, output.to_str().unwrap())?, expected);
+        let metadata = get_metadata(output.to_str().unwrap())?;
+        for (key, value) in [("Pages", "2"), ("Words", "6"), ("Characters", "29"), ("CharactersWithSpaces", "31")] {
+            assert!(metadata.contains(&(key.to_string(), value.to_string())), "{key}");
+        }
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+}
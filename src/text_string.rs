@@ -0,0 +1,207 @@
+//! Decoding PDF string objects to text.
+//!
+//! A string object is written either literally, `(...)`, or in hex, `<...>`; lopdf
+//! undoes the notation and reports which one was used as the [`StringFormat`]. The
+//! bytes are then decoded in three steps, each of which only acts on what it can
+//! recognize for certain:
+//!
+//! Codecs registered with [`register_codec`](crate::register_codec) are tried first;
+//! the built-in decoding described here handles whatever they leave.
+//!
+//! 1. **Legacy wrapper.** Early versions of this crate wrote Unicode values as the
+//!    literal string `UTF16BE:` followed by BASE64. A literal string of that shape is
+//!    unwrapped if the BASE64 is well formed (padded, no stray characters) and decodes
+//!    to UTF-16BE with a byte order mark; otherwise it is text like any other. Hex
+//!    strings never carry the wrapper.
+//! 2. **Byte order mark.** `FE FF` starts UTF-16BE, the encoding the PDF specification
+//!    defines for Unicode text strings, and `EF BB BF` starts UTF-8 (PDF 2.0). `FF FE`
+//!    starts UTF-16LE, which some producers write. The mark is dropped, and invalid or
+//!    truncated sequences become U+FFFD.
+//! 3. **Unmarked bytes.** The specification says PDFDocEncoding, but many producers
+//!    write UTF-8 instead, so bytes that are valid UTF-8 are read as UTF-8 and anything
+//!    else as PDFDocEncoding. Genuine PDFDocEncoding text with non-ASCII characters is
+//!    practically never valid UTF-8.
+//!
+//! Nothing is guessed from how the contents look: a literal title such as `<CAFE>`
+//! reads back as written.
+
+use crate::stats::{self, TextEncoding};
+use crate::{codec, pdf_doc_encoding};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use lopdf::{Object, StringFormat};
+use std::borrow::Cow;
+
+pub(crate) const LEGACY_PREFIX: &[u8] = b"UTF16BE:";
+
+/// Decodes the bytes of a string object written in `format` with the built-in
+/// decoding, returning the text and the encoding it was read as.
+pub(crate) fn decode_text_string(bytes: &[u8], format: StringFormat) -> (String, TextEncoding) {
+    if format == StringFormat::Literal
+        && let Some(unwrapped) = unwrap_legacy(bytes)
+    {
+        return decode_bytes(&unwrapped);
+    }
+    decode_bytes(bytes)
+}
+
+/// Decodes the bytes of a string object written in `format`, with the codecs
+/// registered in [`codec`] or else the built-in decoding.
+pub(crate) fn decode_info_string(bytes: &[u8], format: StringFormat) -> String {
+    codec::decode_registered(bytes, format).unwrap_or_else(|| decode_text_string(bytes, format).0)
+}
+
+/// Like `decode_info_string`, counting the string in [`stats`] as read from a document.
+/// Strings decoded by a registered codec are not counted.
+pub(crate) fn decode_document_string(bytes: &[u8], format: StringFormat) -> String {
+    if let Some(decoded) = codec::decode_registered(bytes, format) {
+        return decoded;
+    }
+    let (decoded, encoding) = decode_text_string(bytes, format);
+    stats::record_string(encoding, &decoded);
+    decoded
+}
+
+/// Like `decode_document_string`, borrowing the text from `bytes` when they hold
+/// UTF-8 (with or without a byte order mark), which is all of plain ASCII.
+pub(crate) fn decode_document_str(bytes: &[u8], format: StringFormat) -> Cow<'_, str> {
+    if let Some(decoded) = codec::decode_registered(bytes, format) {
+        return Cow::Owned(decoded);
+    }
+    let (decoded, encoding) = match borrow_utf8(bytes, format) {
+        Some((text, encoding)) => (Cow::Borrowed(text), encoding),
+        None => {
+            let (text, encoding) = decode_text_string(bytes, format);
+            (Cow::Owned(text), encoding)
+        }
+    };
+    stats::record_string(encoding, &decoded);
+    decoded
+}
+
+/// The text of `bytes` if the built-in decoding would read them as UTF-8 as stored.
+fn borrow_utf8(bytes: &[u8], format: StringFormat) -> Option<(&str, TextEncoding)> {
+    if (format == StringFormat::Literal && bytes.starts_with(LEGACY_PREFIX))
+        || bytes.starts_with(&[0xFE, 0xFF])
+        || bytes.starts_with(&[0xFF, 0xFE])
+    {
+        return None;
+    }
+    if let Some(utf8) = bytes.strip_prefix(&[0xEF, 0xBB, 0xBF]) {
+        return std::str::from_utf8(utf8).ok().map(|text| (text, TextEncoding::Utf8));
+    }
+    let text = std::str::from_utf8(bytes).ok()?;
+    Some((text, if text.is_ascii() { TextEncoding::Ascii } else { TextEncoding::Utf8 }))
+}
+
+/// The text of `object` if it is a string, counted like `decode_document_string`.
+pub(crate) fn decode_string_object(object: &Object) -> Option<String> {
+    match object {
+        Object::String(bytes, format) => Some(decode_document_string(bytes, *format)),
+        _ => None,
+    }
+}
+
+/// Steps 2 and 3: the byte order mark, else UTF-8 or PDFDocEncoding.
+fn decode_bytes(bytes: &[u8]) -> (String, TextEncoding) {
+    if let Some(utf16) = bytes.strip_prefix(&[0xFE, 0xFF]) {
+        return (decode_utf16(utf16, u16::from_be_bytes), TextEncoding::Utf16Be);
+    }
+    if let Some(utf16) = bytes.strip_prefix(&[0xFF, 0xFE]) {
+        return (decode_utf16(utf16, u16::from_le_bytes), TextEncoding::Utf16Le);
+    }
+    if let Some(utf8) = bytes.strip_prefix(&[0xEF, 0xBB, 0xBF]) {
+        return (String::from_utf8_lossy(utf8).into_owned(), TextEncoding::Utf8);
+    }
+    match std::str::from_utf8(bytes) {
+        Ok(text) if text.is_ascii() => (text.to_string(), TextEncoding::Ascii),
+        Ok(text) => (text.to_string(), TextEncoding::Utf8),
+        Err(_) => (pdf_doc_encoding::decode(bytes), TextEncoding::PdfDocEncoding),
+    }
+}
+
+/// Decodes UTF-16 code units, replacing unpaired surrogates and a trailing odd byte
+/// with U+FFFD.
+fn decode_utf16(bytes: &[u8], unit: fn([u8; 2]) -> u16) -> String {
+    let units = bytes.chunks_exact(2).map(|pair| unit([pair[0], pair[1]]));
+    let mut text: String =
+        char::decode_utf16(units).map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER)).collect();
+    if bytes.len() % 2 == 1 {
+        text.push(char::REPLACEMENT_CHARACTER);
+    }
+    text
+}
+
+/// Step 1: the UTF-16BE bytes inside a `UTF16BE:` wrapper, if `bytes` is one.
+pub(crate) fn unwrap_legacy(bytes: &[u8]) -> Option<Vec<u8>> {
+    let decoded = BASE64.decode(bytes.strip_prefix(LEGACY_PREFIX)?).ok()?;
+    decoded.starts_with(&[0xFE, 0xFF]).then_some(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::info_string_parts;
+    use proptest::prelude::*;
+
+    fn base64(bytes: &[u8]) -> String {
+        BASE64.encode(bytes)
+    }
+
+    fn utf16be(text: &str) -> Vec<u8> {
+        let mut bytes = vec![0xFE, 0xFF];
+        bytes.extend(text.encode_utf16().flat_map(u16::to_be_bytes));
+        bytes
+    }
+
+    #[test]
+    fn test_decoding_follows_the_string_format() {
+        let literal = |text: &str| decode_info_string(text.as_bytes(), StringFormat::Literal);
+        assert_eq!(literal("<48656C6C6F>"), "<48656C6C6F>");
+        assert_eq!(literal("<CAFE>"), "<CAFE>");
+        assert_eq!(literal("Relatório"), "Relatório");
+        assert_eq!(decode_info_string(&utf16be("Relatório"), StringFormat::Hexadecimal), "Relatório");
+        assert_eq!(decode_info_string(&[0xFF, 0xFE, b'O', 0, b'k', 0], StringFormat::Literal), "Ok");
+        assert_eq!(decode_info_string(&[0xEF, 0xBB, 0xBF, b'O', b'k'], StringFormat::Literal), "Ok");
+        assert_eq!(decode_info_string(b"caf\xE9", StringFormat::Literal), "café");
+
+        let wrapped = format!("UTF16BE:{}", base64(&utf16be("Tëšt")));
+        assert_eq!(literal(&wrapped), "Tëšt");
+        assert_eq!(decode_info_string(wrapped.as_bytes(), StringFormat::Hexadecimal), wrapped);
+        for kept in ["UTF16BE:/v8ASQ", "UTF16BE:/v8A SQ==", "UTF16BE:SGVsbG8=", "UTF16BE:/v8=ASQ=", "UTF16BE:"] {
+            assert_eq!(literal(kept), kept);
+        }
+
+        assert_eq!(decode_info_string(&[0xFE, 0xFF, 0xD8, 0x00, 0x00], StringFormat::Hexadecimal), "\u{FFFD}\u{FFFD}");
+    }
+
+    proptest! {
+        #[test]
+        fn prop_decoding_never_panics(bytes in proptest::collection::vec(any::<u8>(), 0..64), hex in any::<bool>()) {
+            let format = if hex { StringFormat::Hexadecimal } else { StringFormat::Literal };
+            let _ = decode_info_string(&bytes, format);
+            let mut wrapped = LEGACY_PREFIX.to_vec();
+            wrapped.extend_from_slice(&bytes);
+            let _ = decode_info_string(&wrapped, format);
+        }
+
+        #[test]
+        fn prop_written_values_read_back(value in "\\PC*") {
+            prop_assume!(!value.starts_with("UTF16BE:"));
+            let (bytes, format) = info_string_parts(&value);
+            prop_assert_eq!(decode_info_string(&bytes, format), value);
+        }
+
+        #[test]
+        fn prop_legacy_wrapper_round_trips(value in "\\PC*") {
+            let wrapped = format!("UTF16BE:{}", base64(&utf16be(&value)));
+            prop_assert_eq!(decode_info_string(wrapped.as_bytes(), StringFormat::Literal), value);
+        }
+
+        #[test]
+        fn prop_unwrapped_text_is_kept(value in "[ -~]*") {
+            prop_assume!(!value.starts_with("UTF16BE:"));
+            prop_assert_eq!(decode_info_string(value.as_bytes(), StringFormat::Literal), value);
+        }
+    }
+}
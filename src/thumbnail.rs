@@ -0,0 +1,257 @@
+//! Page thumbnails (`/Thumb` images).
+//!
+//! A page may carry a small preview image under `/Thumb`, written by the producing
+//! application or by tools such as Acrobat's "Embed page thumbnails". Reading it is
+//! far cheaper than rendering the page, which this crate does not do.
+
+use crate::page::page_id;
+use crate::{Error, Result, load_document};
+use lopdf::{Document, Object, Stream};
+use std::path::Path;
+
+/// How the pixels of a [`Thumbnail`] are stored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ThumbnailFormat {
+    /// A JPEG file, as embedded (`/DCTDecode`).
+    Jpeg,
+    /// Three bytes per pixel, red, green and blue, row by row.
+    Rgb,
+    /// One byte per pixel, row by row.
+    Gray,
+}
+
+/// A page thumbnail, as returned by [`extract_page_thumbnail`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Thumbnail {
+    pub width: u32,
+    pub height: u32,
+    pub format: ThumbnailFormat,
+    pub data: Vec<u8>,
+}
+
+/// Returns the thumbnail embedded in page `page_number` (1-based), or `None` if the
+/// page has none.
+///
+/// Thumbnails stored as samples are decoded to RGB or grayscale pixels and scaled
+/// down, keeping the aspect ratio, so that neither side exceeds `max_px`; 0 keeps
+/// them at full size. JPEG thumbnails are returned as embedded, whatever their size,
+/// for the caller's image library to decode and scale.
+///
+/// # Returns
+///
+/// * `Ok(Option<Thumbnail>)`: The thumbnail, if the page has one.
+/// * `Err(Error::PageNotFound)`: If the document has no such page.
+/// * `Err(Error::UnsupportedFormat)`: If the thumbnail uses a color space, bit depth
+///   or filter that is not supported, or its data is truncated.
+/// * `Err(Error)`: If the file cannot be read or parsed.
+///
+/// # Example
+///
+/// ```no_run
+/// use pdf_metadata::{ThumbnailFormat, extract_page_thumbnail};
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     if let Some(thumbnail) = extract_page_thumbnail("report.pdf", 1, 128)? {
+///         if thumbnail.format == ThumbnailFormat::Jpeg {
+///             std::fs::write("preview.jpg", &thumbnail.data)?;
+///         }
+///     }
+///     Ok(())
+/// }
+/// ```
+pub fn extract_page_thumbnail<P: AsRef<Path>>(file_path: P, page_number: u32, max_px: u32) -> Result<Option<Thumbnail>> {
+    let file_path = file_path.as_ref();
+    let doc = load_document(file_path)?;
+    let page = doc.get_dictionary(page_id(&doc, page_number)?)?;
+    let stream = match page.get(b"Thumb") {
+        Ok(Object::Reference(id)) => doc.get_object(*id).and_then(Object::as_stream),
+        Ok(object) => object.as_stream(),
+        Err(_) => return Ok(None),
+    };
+    let Ok(stream) = stream else { return Ok(None) };
+    decode_thumbnail(&doc, stream, max_px).map(Some)
+}
+
+fn decode_thumbnail(doc: &Document, stream: &Stream, max_px: u32) -> Result<Thumbnail> {
+    let unsupported = |what: &str| Error::UnsupportedFormat(format!("thumbnail {}", what));
+    let dimension = |key: &[u8]| {
+        stream.dict.get(key).and_then(Object::as_i64).ok().and_then(|n| u32::try_from(n).ok()).filter(|&n| n > 0)
+    };
+    let (Some(width), Some(height)) = (dimension(b"Width"), dimension(b"Height")) else {
+        return Err(unsupported("without a valid /Width and /Height"));
+    };
+
+    let filters = stream.filters().unwrap_or_default();
+    if filters.contains(&b"DCTDecode".as_slice()) {
+        if filters.len() > 1 {
+            return Err(unsupported("with filters before /DCTDecode"));
+        }
+        return Ok(Thumbnail { width, height, format: ThumbnailFormat::Jpeg, data: stream.content.clone() });
+    }
+    if stream.dict.get(b"BitsPerComponent").and_then(Object::as_i64).unwrap_or(8) != 8 {
+        return Err(unsupported("with a bit depth other than 8"));
+    }
+    let samples = if filters.is_empty() { stream.content.clone() } else { stream.decompressed_content()? };
+
+    let color_space = stream.dict.get(b"ColorSpace").map_err(|_| unsupported("without a /ColorSpace"))?;
+    let color_space = ColorSpace::parse(doc, color_space).ok_or_else(|| unsupported("color space"))?;
+    let pixels = (width as usize).checked_mul(height as usize).ok_or_else(|| unsupported("size"))?;
+    let needed = pixels.checked_mul(color_space.components()).ok_or_else(|| unsupported("size"))?;
+    let samples = samples.get(..needed).ok_or_else(|| unsupported("data is truncated"))?;
+    let (format, data) = match color_space {
+        ColorSpace::Gray => (ThumbnailFormat::Gray, samples.to_vec()),
+        ColorSpace::Rgb => (ThumbnailFormat::Rgb, samples.to_vec()),
+        ColorSpace::Indexed(palette) => {
+            let data = samples
+                .iter()
+                .flat_map(|&index| palette.get(index as usize).copied().unwrap_or([0; 3]))
+                .collect();
+            (ThumbnailFormat::Rgb, data)
+        }
+    };
+    Ok(scale_down(Thumbnail { width, height, format, data }, max_px))
+}
+
+/// The color spaces thumbnails use: the specification allows only DeviceGray,
+/// DeviceRGB and Indexed, and writers also use ICC-based gray and RGB.
+enum ColorSpace {
+    Gray,
+    Rgb,
+    /// An RGB palette.
+    Indexed(Vec<[u8; 3]>),
+}
+
+impl ColorSpace {
+    fn parse(doc: &Document, object: &Object) -> Option<ColorSpace> {
+        let (_, object) = doc.dereference(object).ok()?;
+        match object {
+            Object::Name(name) => match name.as_slice() {
+                b"DeviceGray" | b"G" => Some(ColorSpace::Gray),
+                b"DeviceRGB" | b"RGB" => Some(ColorSpace::Rgb),
+                _ => None,
+            },
+            Object::Array(array) => match array.first().and_then(|name| name.as_name().ok())? {
+                b"ICCBased" => {
+                    let (_, profile) = doc.dereference(array.get(1)?).ok()?;
+                    match profile.as_stream().ok()?.dict.get(b"N").and_then(Object::as_i64).ok()? {
+                        1 => Some(ColorSpace::Gray),
+                        3 => Some(ColorSpace::Rgb),
+                        _ => None,
+                    }
+                }
+                b"Indexed" | b"I" => {
+                    let base = ColorSpace::parse(doc, array.get(1)?)?;
+                    let (_, lookup) = doc.dereference(array.get(3)?).ok()?;
+                    let lookup = match lookup {
+                        Object::String(bytes, _) => bytes.clone(),
+                        Object::Stream(stream) => stream.get_plain_content().ok()?,
+                        _ => return None,
+                    };
+                    let palette = match base {
+                        ColorSpace::Gray => lookup.iter().map(|&gray| [gray; 3]).collect(),
+                        ColorSpace::Rgb => lookup.chunks_exact(3).map(|rgb| [rgb[0], rgb[1], rgb[2]]).collect(),
+                        ColorSpace::Indexed(_) => return None,
+                    };
+                    Some(ColorSpace::Indexed(palette))
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// The number of samples per pixel.
+    fn components(&self) -> usize {
+        match self {
+            ColorSpace::Rgb => 3,
+            ColorSpace::Gray | ColorSpace::Indexed(_) => 1,
+        }
+    }
+}
+
+/// Scales `thumbnail` down by sampling the nearest pixel, so that neither side
+/// exceeds `max_px` (unless it is 0).
+fn scale_down(thumbnail: Thumbnail, max_px: u32) -> Thumbnail {
+    let longest = thumbnail.width.max(thumbnail.height);
+    if max_px == 0 || longest <= max_px {
+        return thumbnail;
+    }
+    let scaled = |side: u32| ((u64::from(side) * u64::from(max_px) / u64::from(longest)) as u32).max(1);
+    let (width, height) = (scaled(thumbnail.width), scaled(thumbnail.height));
+    let channels = if thumbnail.format == ThumbnailFormat::Rgb { 3 } else { 1 };
+    let mut data = Vec::with_capacity(width as usize * height as usize * channels);
+    for y in 0..height {
+        let source_y = (u64::from(y) * u64::from(thumbnail.height) / u64::from(height)) as usize;
+        for x in 0..width {
+            let source_x = (u64::from(x) * u64::from(thumbnail.width) / u64::from(width)) as usize;
+            let start = (source_y * thumbnail.width as usize + source_x) * channels;
+            data.extend_from_slice(&thumbnail.data[start..start + channels]);
+        }
+    }
+    Thumbnail { width, height, data, ..thumbnail }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::setup_unique_test_dir;
+    use lopdf::dictionary;
+    use std::fs;
+
+    #[test]
+    fn test_extract_page_thumbnail() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("thumbnail");
+        let file = test_dir.join("doc.pdf");
+
+        // Page 1 has a 4x2 Flate-compressed indexed thumbnail, page 2 a JPEG one and
+        // page 3 none.
+        let mut doc = Document::with_version("1.7");
+        let pages_id = doc.new_object_id();
+        let palette = Object::string_literal(vec![255, 0, 0, 0, 0, 255]);
+        let mut indexed = Stream::new(
+            dictionary! {
+                "Width" => 4, "Height" => 2, "BitsPerComponent" => 8,
+                "ColorSpace" => vec!["Indexed".into(), "DeviceRGB".into(), 1.into(), palette],
+            },
+            vec![0, 0, 1, 1, 1, 1, 0, 0],
+        );
+        indexed.compress()?;
+        let jpeg = Stream::new(
+            dictionary! { "Width" => 90, "Height" => 120, "ColorSpace" => "DeviceRGB", "Filter" => "DCTDecode" },
+            b"\xFF\xD8jpeg\xFF\xD9".to_vec(),
+        )
+        .with_compression(false);
+        let mut kids = Vec::new();
+        for thumb in [Some(indexed), Some(jpeg), None] {
+            let mut page = dictionary! { "Type" => "Page", "Parent" => pages_id };
+            if let Some(thumb) = thumb {
+                page.set("Thumb", doc.add_object(thumb));
+            }
+            kids.push(doc.add_object(page).into());
+        }
+        doc.objects.insert(pages_id, dictionary! { "Type" => "Pages", "Count" => 3, "Kids" => kids }.into());
+        let catalog_id = doc.add_object(dictionary! { "Type" => "Catalog", "Pages" => pages_id });
+        doc.trailer.set("Root", catalog_id);
+        doc.save(&file)?;
+        let file_str = file.to_str().unwrap();
+
+        let full = extract_page_thumbnail(file_str, 1, 0)?.unwrap();
+        assert_eq!((full.width, full.height, full.format), (4, 2, ThumbnailFormat::Rgb));
+        assert_eq!(&full.data[..9], [255, 0, 0, 255, 0, 0, 0, 0, 255]);
+        let small = extract_page_thumbnail(file_str, 1, 2)?.unwrap();
+        assert_eq!((small.width, small.height), (2, 1));
+        assert_eq!(small.data, [255, 0, 0, 0, 0, 255]);
+
+        let jpeg = extract_page_thumbnail(file_str, 2, 64)?.unwrap();
+        assert_eq!((jpeg.width, jpeg.height, jpeg.format), (90, 120, ThumbnailFormat::Jpeg));
+        assert!(jpeg.data.starts_with(b"\xFF\xD8"));
+
+        assert_eq!(extract_page_thumbnail(file_str, 3, 64)?, None);
+        assert!(matches!(extract_page_thumbnail(file_str, 4, 64), Err(Error::PageNotFound(4))));
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,265 @@
+//! Wall-clock timeouts for load and save operations.
+//!
+//! lopdf offers no way to interrupt a parse, so [`with_timeout`] runs the operation
+//! on a worker thread and stops waiting for it when the timeout expires. The
+//! abandoned worker keeps running until the parse finishes and its result is
+//! discarded, but it can no longer touch the filesystem: every write goes through
+//! [`commit`], and once the caller has been told `Error::TimedOut` the commit is
+//! refused. A timed-out operation therefore never leaves a modified file behind.
+//!
+//! Abandoned workers still hold a thread and the memory of their parse, so a
+//! stream of hostile inputs could pile them up. Once [`MAX_ABANDONED_WORKERS`] of
+//! them are still running, further calls fail with `Error::TimedOut` at once,
+//! without starting the operation, until some of them finish.
+
+use crate::{Error, Result, clock, codec, options};
+use std::cell::RefCell;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Running,
+    /// The operation started writing its output or has finished; the caller waits
+    /// for its result.
+    Committed,
+    /// The caller gave up; the operation must not write anything.
+    TimedOut,
+}
+
+#[derive(Debug)]
+struct Deadline {
+    #[cfg_attr(not(feature = "std-fs"), allow(dead_code))]
+    timeout: Duration,
+    state: Mutex<State>,
+}
+
+impl Deadline {
+    fn state(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// How many timed-out operations may still be running before [`with_timeout`]
+/// refuses to start new ones.
+pub const MAX_ABANDONED_WORKERS: usize = 8;
+
+/// The timed-out operations whose workers have not finished yet.
+static ABANDONED: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    static DEADLINE: RefCell<Option<Arc<Deadline>>> = const { RefCell::new(None) };
+}
+
+/// Marks the point after which the current operation changes files on disk.
+///
+/// Outside [`with_timeout`] this always succeeds. Inside, it fails with
+/// `Error::TimedOut` if the caller has already given up; otherwise the caller
+/// waits for the operation to finish instead of timing out.
+#[cfg(feature = "std-fs")]
+pub(crate) fn commit() -> Result<()> {
+    DEADLINE.with(|deadline| match deadline.borrow().as_deref() {
+        None => Ok(()),
+        Some(deadline) => {
+            let mut state = deadline.state();
+            match *state {
+                State::TimedOut => Err(Error::TimedOut(deadline.timeout)),
+                State::Running | State::Committed => {
+                    *state = State::Committed;
+                    Ok(())
+                }
+            }
+        }
+    })
+}
+
+/// Runs `operation`, giving up with `Error::TimedOut` if it takes longer than `timeout`.
+///
+/// Any function of this crate can be wrapped. The operation runs on another thread
+/// but sees the caller's [`with_write_options`](crate::with_write_options),
+/// [`with_clock`](crate::with_clock) and [`with_write_codec`](crate::with_write_codec)
+/// scopes as if it ran in place. Writes are all-or-nothing with respect
+/// to the timeout: either the operation completes and its result is returned, or
+/// `Error::TimedOut` is returned and no file was modified. An operation that has
+/// started replacing its output is allowed to finish, so the call can return
+/// slightly after `timeout` in that case. A panic inside `operation` is propagated.
+///
+/// A timed-out operation is not stopped: its thread runs until the operation
+/// returns. While [`MAX_ABANDONED_WORKERS`] such threads are alive, this function
+/// returns `Error::TimedOut` immediately instead of starting another one.
+///
+/// # Example
+///
+/// ```no_run
+/// use pdf_metadata::{Error, get_metadata, with_timeout};
+/// use std::time::Duration;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     match with_timeout(Duration::from_secs(5), || get_metadata("upload.pdf")) {
+///         Ok(metadata) => println!("{} entries", metadata.len()),
+///         Err(Error::TimedOut(after)) => eprintln!("gave up after {:?}", after),
+///         Err(e) => return Err(e.into()),
+///     }
+///     Ok(())
+/// }
+/// ```
+pub fn with_timeout<T, F>(timeout: Duration, operation: F) -> Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T> + Send + 'static,
+{
+    run(timeout, operation, &ABANDONED, MAX_ABANDONED_WORKERS)
+}
+
+/// [`with_timeout`], counting the workers it abandons in `abandoned` and refusing
+/// to start once `max_abandoned` of them are still running.
+fn run<T, F>(timeout: Duration, operation: F, abandoned: &'static AtomicUsize, max_abandoned: usize) -> Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T> + Send + 'static,
+{
+    if abandoned.load(Ordering::SeqCst) >= max_abandoned {
+        return Err(Error::TimedOut(timeout));
+    }
+    let deadline = Arc::new(Deadline { timeout, state: Mutex::new(State::Running) });
+    let worker_deadline = Arc::clone(&deadline);
+    let (write_options, clock, codec) = (options::current(), clock::thread_clock(), codec::thread_write_codec());
+    let (sender, receiver) = mpsc::channel();
+    thread::Builder::new().name("pdf_metadata-timeout".to_string()).spawn(move || {
+        DEADLINE.with(|deadline| *deadline.borrow_mut() = Some(Arc::clone(&worker_deadline)));
+        let scoped = || {
+            options::with_write_options(write_options, || {
+                clock::with_thread_clock(clock, || codec::with_thread_write_codec(codec, operation))
+            })
+        };
+        let outcome = panic::catch_unwind(AssertUnwindSafe(scoped));
+        // A finished operation counts as committed, so a caller that times out now
+        // still takes its result instead of counting the worker as abandoned.
+        let mut state = worker_deadline.state();
+        match *state {
+            State::TimedOut => {
+                abandoned.fetch_sub(1, Ordering::SeqCst);
+            }
+            State::Running | State::Committed => *state = State::Committed,
+        }
+        drop(state);
+        // The receiver is gone if the caller timed out; the result is discarded then.
+        let _ = sender.send(outcome);
+    })?;
+
+    let outcome = match receiver.recv_timeout(timeout) {
+        Ok(outcome) => outcome,
+        Err(RecvTimeoutError::Timeout) => {
+            let mut state = deadline.state();
+            if *state == State::Running {
+                *state = State::TimedOut;
+                abandoned.fetch_add(1, Ordering::SeqCst);
+                return Err(Error::TimedOut(timeout));
+            }
+            drop(state);
+            receiver.recv().map_err(|e| Error::Io(std::io::Error::other(e)))?
+        }
+        Err(RecvTimeoutError::Disconnected) => {
+            return Err(Error::Io(std::io::Error::other("timeout worker exited without a result")));
+        }
+    };
+    outcome.unwrap_or_else(|payload| panic::resume_unwind(payload))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{create_minimal_test_pdf, setup_unique_test_dir};
+    use crate::{
+        FixedClock, MetadataValue, PdfDocCodec, SetOptions, get_metadata, get_metadata_values, update_metadata_in_place,
+        with_clock, with_write_codec, with_write_options,
+    };
+    use chrono::DateTime;
+    use lopdf::Document;
+    use std::fs;
+
+    #[test]
+    fn test_timed_out_operations_do_not_write() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("timeout_no_write");
+        let file = test_dir.join("slow.pdf");
+        create_minimal_test_pdf(&file)?;
+        let file_str = file.to_str().unwrap().to_string();
+        let original = fs::read(&file)?;
+
+        let path = file_str.clone();
+        let metadata = with_timeout(Duration::from_secs(30), move || get_metadata(&path))?;
+        assert!(metadata.is_empty());
+
+        let path = file_str.clone();
+        let result = with_timeout(Duration::from_millis(20), move || {
+            thread::sleep(Duration::from_millis(200));
+            update_metadata_in_place(&path, "Title", "Too late")
+        });
+        assert!(matches!(result, Err(Error::TimedOut(after)) if after == Duration::from_millis(20)));
+
+        // Give the abandoned worker time to reach its write, which must be refused.
+        thread::sleep(Duration::from_millis(400));
+        assert_eq!(fs::read(&file)?, original);
+        assert_eq!(fs::read_dir(&test_dir)?.count(), 1, "temporary file was left behind");
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_worker_keeps_the_callers_scopes() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("timeout_scopes");
+        let file = test_dir.join("doc.pdf");
+        create_minimal_test_pdf(&file)?;
+        let file_str = file.to_str().unwrap().to_string();
+        let pinned = DateTime::parse_from_rfc3339("2001-02-03T04:05:06-03:00")?;
+
+        let path = file_str.clone();
+        with_clock(FixedClock(pinned), || {
+            with_write_codec(PdfDocCodec, || {
+                with_timeout(Duration::from_secs(30), move || update_metadata_in_place(&path, "Title", "Relatório"))
+            })
+        })?;
+        let values = get_metadata_values(&file_str)?;
+        assert!(values.contains(&("ModDate".to_string(), MetadataValue::Date(pinned))));
+        let doc = Document::load(&file)?;
+        let info = doc.get_dictionary(doc.trailer.get(b"Info")?.as_reference()?)?;
+        assert_eq!(info.get(b"Title")?.as_str()?, b"Relat\xF3rio");
+
+        create_minimal_test_pdf(&file)?;
+        let path = file_str.clone();
+        let keep_mod_date = SetOptions { update_mod_date: false, ..SetOptions::default() };
+        with_write_options(keep_mod_date, || {
+            with_timeout(Duration::from_secs(30), move || update_metadata_in_place(&path, "Title", "Untouched date"))
+        })?;
+        assert!(!get_metadata(&file_str)?.iter().any(|(key, _)| key == "ModDate"));
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_abandoned_workers_are_capped() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        static WORKERS: AtomicUsize = AtomicUsize::new(0);
+        let slow = || {
+            thread::sleep(Duration::from_millis(300));
+            Ok(())
+        };
+        assert!(matches!(run(Duration::from_millis(20), slow, &WORKERS, 1), Err(Error::TimedOut(_))));
+        assert_eq!(WORKERS.load(Ordering::SeqCst), 1);
+
+        let started = std::time::Instant::now();
+        let refused = run(Duration::from_secs(30), || Ok(1), &WORKERS, 1);
+        assert!(matches!(refused, Err(Error::TimedOut(after)) if after == Duration::from_secs(30)));
+        assert!(started.elapsed() < Duration::from_secs(1), "refused without waiting");
+
+        thread::sleep(Duration::from_millis(600));
+        assert_eq!(WORKERS.load(Ordering::SeqCst), 0);
+        assert_eq!(run(Duration::from_secs(30), || Ok(1), &WORKERS, 1)?, 1);
+        Ok(())
+    }
+}
@@ -0,0 +1,129 @@
+//! Reading metadata with a cap on value length.
+//!
+//! Some generators store kilobytes of XML or logs in custom Info keys. Listing such
+//! files with [`get_metadata_truncated`] keeps only a short preview of each long
+//! value together with a [`ValueHandle`] that re-reads the full value on demand.
+
+use crate::{Result, get_metadata};
+use std::path::{Path, PathBuf};
+
+/// A metadata entry whose value may have been shortened.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TruncatedEntry {
+    pub key: String,
+    /// The value, cut to at most the requested number of characters.
+    pub value: String,
+    /// Length of the complete value, in characters.
+    pub full_len: usize,
+    /// Present when `value` was truncated; fetches the complete value.
+    pub handle: Option<ValueHandle>,
+}
+
+impl TruncatedEntry {
+    /// Returns `true` if `value` is only a preview of the full value.
+    pub fn is_truncated(&self) -> bool {
+        self.handle.is_some()
+    }
+}
+
+/// A reference to the full value of a truncated entry.
+///
+/// The handle holds only the file path and key; fetching reads the file again,
+/// so it reflects the file's current contents.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ValueHandle {
+    path: PathBuf,
+    key: String,
+}
+
+impl ValueHandle {
+    /// The file the value belongs to.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Reads the complete value.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(String))`: The full value.
+    /// * `Ok(None)`: If the key is no longer present in the file.
+    /// * `Err(Error)`: If the file cannot be read.
+    pub fn fetch(&self) -> Result<Option<String>> {
+        let path_str = self.path.to_str().ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("Path is not valid UTF-8: {}", self.path.display()))
+        })?;
+        Ok(get_metadata(path_str)?
+            .into_iter()
+            .find(|(key, _)| *key == self.key)
+            .map(|(_, value)| value))
+    }
+}
+
+/// Retrieves all metadata entries, cutting values longer than `max_chars` characters.
+///
+/// Full values are dropped as soon as the previews are built, so memory use stays
+/// proportional to `max_chars` per entry.
+///
+/// # Example
+///
+/// ```no_run
+/// use pdf_metadata::get_metadata_truncated;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     for entry in get_metadata_truncated("path/to/document.pdf", 80)? {
+///         let marker = if entry.is_truncated() { "..." } else { "" };
+///         println!("{}: {}{} ({} chars)", entry.key, entry.value, marker, entry.full_len);
+///     }
+///     Ok(())
+/// }
+/// ```
+pub fn get_metadata_truncated<P: AsRef<Path>>(file_path: P, max_chars: usize) -> Result<Vec<TruncatedEntry>> {
+    let file_path = file_path.as_ref();
+    Ok(get_metadata(file_path)?
+        .into_iter()
+        .map(|(key, value)| {
+            let full_len = value.chars().count();
+            if full_len <= max_chars {
+                return TruncatedEntry { key, value, full_len, handle: None };
+            }
+            let preview: String = value.chars().take(max_chars).collect();
+            let handle = ValueHandle { path: PathBuf::from(file_path), key: key.clone() };
+            TruncatedEntry { key, value: preview, full_len, handle: Some(handle) }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{create_minimal_test_pdf, setup_unique_test_dir};
+    use crate::update_entries_in_place;
+    use std::fs;
+
+    #[test]
+    fn test_long_values_are_truncated_and_fetchable() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("truncated_values");
+        let file = test_dir.join("long.pdf");
+        create_minimal_test_pdf(&file)?;
+        let file_str = file.to_str().unwrap();
+        let long_value = "ção".repeat(1000);
+        update_entries_in_place(file_str, &[("Title", "Short"), ("Dump", long_value.as_str())])?;
+
+        let entries = get_metadata_truncated(file_str, 10)?;
+        let title = entries.iter().find(|e| e.key == "Title").unwrap();
+        assert!(!title.is_truncated());
+        assert_eq!(title.value, "Short");
+
+        let dump = entries.iter().find(|e| e.key == "Dump").unwrap();
+        assert_eq!(dump.value, "çãoçãoçãoç");
+        assert_eq!(dump.full_len, 3000);
+        let handle = dump.handle.as_ref().unwrap();
+        assert_eq!(handle.fetch()?, Some(long_value));
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+}
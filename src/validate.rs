@@ -0,0 +1,209 @@
+//! # Value validation
+//!
+//! Checks Info entries against the constraints of the PDF format and the limits
+//! readers enforce in practice, before they are written. Problems are reported as
+//! [`ValidationWarning`]s: nothing here refuses a write by itself. Set
+//! [`SetOptions::strict`](crate::SetOptions::strict) to make the `*_with_options`
+//! writers fail with `Error::Validation` instead of writing entries with warnings.
+//!
+//! ```
+//! use pdf_metadata::validate::{WarningKind, validate_entry};
+//!
+//! let warnings = validate_entry("ModDate", "yesterday");
+//! assert_eq!(warnings[0].kind, WarningKind::InvalidDate);
+//! assert!(validate_entry("Title", "Annual Report (draft)").is_empty());
+//! ```
+
+use crate::value::DATE_KEYS;
+use crate::{decode_document_string, info_string_object, parse_pdf_date};
+#[cfg(feature = "std-fs")]
+use crate::{Result, load_document};
+use lopdf::Object;
+use std::fmt;
+#[cfg(feature = "std-fs")]
+use std::path::Path;
+
+/// The longest string many readers accept, in bytes (PDF 1.7, Annex C).
+pub const MAX_STRING_BYTES: usize = 32767;
+
+/// The longest name readers must accept, in bytes (PDF 1.7, Annex C).
+pub const MAX_NAME_BYTES: usize = 127;
+
+/// The Info keys defined by the PDF specification.
+pub(crate) const STANDARD_KEYS: [&str; 9] =
+    ["Title", "Author", "Subject", "Keywords", "Creator", "Producer", "CreationDate", "ModDate", "Trapped"];
+
+/// What a [`ValidationWarning`] is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WarningKind {
+    /// The key is empty, too long, or contains characters that are written as `#`
+    /// escapes (spaces, delimiters, non-ASCII), which many tools display verbatim.
+    InvalidKey,
+    /// The key differs from a standard key only in case, e.g. `title`; readers look
+    /// up keys case-sensitively and will not find it.
+    KeyCase,
+    /// The encoded string is longer than [`MAX_STRING_BYTES`].
+    StringTooLong,
+    /// The text has unbalanced parentheses. They are written escaped, which is
+    /// valid but trips up tools that scan PDF strings naively.
+    UnbalancedParentheses,
+    /// The text contains control characters other than tab and line breaks.
+    ControlCharacter,
+    /// A date entry (`CreationDate`, `ModDate`) is not a valid PDF date.
+    InvalidDate,
+    /// A name value is empty, too long or contains a NUL byte, which names cannot hold.
+    InvalidName,
+    /// `Trapped` is not one of the names `True`, `False` or `Unknown`.
+    InvalidTrapped,
+}
+
+/// A problem found in an entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ValidationWarning {
+    /// The key of the entry.
+    pub key: String,
+    pub kind: WarningKind,
+    /// A description of the problem, in English.
+    pub message: String,
+}
+
+impl fmt::Display for ValidationWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.key, self.message)
+    }
+}
+
+/// Checks one entry as the string API would write it.
+pub fn validate_entry(key: &str, value: &str) -> Vec<ValidationWarning> {
+    validate_object(key, &info_string_object(value))
+}
+
+/// Checks each `(key, value)` entry as the string API would write it.
+pub fn validate_entries<K: AsRef<str>, V: AsRef<str>>(entries: &[(K, V)]) -> Vec<ValidationWarning> {
+    entries.iter().flat_map(|(key, value)| validate_entry(key.as_ref(), value.as_ref())).collect()
+}
+
+/// Checks the entries already stored in the Info dictionary of a PDF.
+///
+/// # Returns
+///
+/// * `Ok(Vec<ValidationWarning>)`: The problems found; empty if there are none.
+/// * `Err(Error)`: If the file cannot be read or parsed.
+#[cfg(feature = "std-fs")]
+pub fn validate_file<P: AsRef<Path>>(file_path: P) -> Result<Vec<ValidationWarning>> {
+    let file_path = file_path.as_ref();
+    let doc = load_document(file_path)?;
+    let Some(info) = doc.trailer.get(b"Info").ok().and_then(|info| doc.dereference(info).ok()) else {
+        return Ok(Vec::new());
+    };
+    let Ok(info) = info.1.as_dict() else { return Ok(Vec::new()) };
+    Ok(info
+        .iter()
+        .flat_map(|(key, value)| validate_object(&String::from_utf8_lossy(key), value))
+        .collect())
+}
+
+/// Checks an entry holding `object` as it will be written.
+pub(crate) fn validate_object(key: &str, object: &Object) -> Vec<ValidationWarning> {
+    let mut warnings = Vec::new();
+    let mut warn = |kind, message: String| warnings.push(ValidationWarning { key: key.to_string(), kind, message });
+
+    if key.is_empty() || key.len() > MAX_NAME_BYTES {
+        warn(WarningKind::InvalidKey, format!("keys must have 1 to {} bytes", MAX_NAME_BYTES));
+    } else if let Some(c) = key.chars().find(|&c| !c.is_ascii_graphic() || "()<>[]{}/%#".contains(c)) {
+        warn(WarningKind::InvalidKey, format!("{:?} is written as a # escape", c));
+    }
+    if let Some(standard) = STANDARD_KEYS.iter().find(|standard| **standard != key && standard.eq_ignore_ascii_case(key)) {
+        warn(WarningKind::KeyCase, format!("the standard key is spelled {}", standard));
+    }
+
+    match object {
+        Object::String(bytes, format) => {
+            let text = decode_document_string(bytes, *format);
+            if bytes.len() > MAX_STRING_BYTES {
+                warn(WarningKind::StringTooLong, format!("{} bytes, more than {}", bytes.len(), MAX_STRING_BYTES));
+            }
+            if !parentheses_balanced(&text) {
+                warn(WarningKind::UnbalancedParentheses, "unbalanced parentheses".to_string());
+            }
+            if let Some(c) = text.chars().find(|&c| c.is_control() && !matches!(c, '\t' | '\n' | '\r')) {
+                warn(WarningKind::ControlCharacter, format!("contains the control character {:?}", c));
+            }
+            if DATE_KEYS.contains(&key) && parse_pdf_date(&text).is_err() {
+                warn(WarningKind::InvalidDate, format!("{:?} is not a PDF date (D:YYYYMMDDHHmmSSOHH'mm')", text));
+            }
+        }
+        Object::Name(name) if name.is_empty() || name.len() > MAX_NAME_BYTES || name.contains(&0) => {
+            warn(WarningKind::InvalidName, format!("names must have 1 to {} bytes and no NUL", MAX_NAME_BYTES));
+        }
+        _ => {}
+    }
+    if key == "Trapped" && !matches!(object, Object::Name(name) if [&b"True"[..], b"False", b"Unknown"].contains(&name.as_slice())) {
+        warn(WarningKind::InvalidTrapped, "must be the name True, False or Unknown".to_string());
+    }
+    warnings
+}
+
+fn parentheses_balanced(text: &str) -> bool {
+    let mut depth = 0usize;
+    for c in text.chars() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => return false,
+            ')' => depth -= 1,
+            _ => {}
+        }
+    }
+    depth == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{create_minimal_test_pdf, setup_unique_test_dir};
+    use crate::{Error, SetOptions, get_metadata, set_metadata_with_options};
+    use std::fs;
+
+    fn kinds(warnings: Vec<ValidationWarning>) -> Vec<WarningKind> {
+        warnings.into_iter().map(|warning| warning.kind).collect()
+    }
+
+    #[test]
+    fn test_validate_entry_reports_each_problem() {
+        assert!(validate_entry("Title", "Relatório (versão 2)").is_empty());
+        assert!(validate_entry("ModDate", "D:20240102030405+01'00'").is_empty());
+        assert_eq!(kinds(validate_entry("My Key", "x")), [WarningKind::InvalidKey]);
+        assert_eq!(kinds(validate_entry("", "x")), [WarningKind::InvalidKey]);
+        assert_eq!(kinds(validate_entry("author", "x")), [WarningKind::KeyCase]);
+        assert_eq!(kinds(validate_entry("Subject", ":-)")), [WarningKind::UnbalancedParentheses]);
+        assert_eq!(kinds(validate_entry("Subject", "a\u{7}b")), [WarningKind::ControlCharacter]);
+        assert_eq!(kinds(validate_entry("CreationDate", "2024-01-02")), [WarningKind::InvalidDate]);
+        assert_eq!(kinds(validate_entry("Keywords", &"x".repeat(MAX_STRING_BYTES + 1))), [WarningKind::StringTooLong]);
+        assert_eq!(kinds(validate_entry("Trapped", "True")), [WarningKind::InvalidTrapped]);
+        assert!(validate_object("Trapped", &Object::Name(b"True".to_vec())).is_empty());
+        assert_eq!(kinds(validate_object("Custom", &Object::Name(Vec::new()))), [WarningKind::InvalidName]);
+    }
+
+    #[test]
+    fn test_strict_writes_refuse_warnings() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("validate_strict");
+        let file = test_dir.join("doc.pdf");
+        create_minimal_test_pdf(&file)?;
+        let file_str = file.to_str().unwrap();
+        let strict = SetOptions { strict: true, ..SetOptions::default() };
+
+        let result = set_metadata_with_options(file_str, file_str, "ModDate", "soon", &strict);
+        assert!(matches!(result, Err(Error::Validation(message)) if message.contains("ModDate")));
+        set_metadata_with_options(file_str, file_str, "Title", "Atlas", &strict)?;
+        set_metadata_with_options(file_str, file_str, "title", "lowercase", &SetOptions::default())?;
+
+        assert_eq!(get_metadata(file_str)?.iter().filter(|(key, _)| key.eq_ignore_ascii_case("title")).count(), 2);
+        assert_eq!(kinds(validate_file(file_str)?), [WarningKind::KeyCase]);
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,476 @@
+//! Typed Info dictionary values.
+//!
+//! [`MetadataValue`] mirrors the PDF object types that can appear in an Info
+//! dictionary. Indirect references are followed, arrays and dictionaries are
+//! converted item by item, and streams holding text (some generators store long
+//! values that way) are decoded to strings. What is left, binary streams and
+//! references that cannot be resolved, is reported as [`MetadataValue::Unsupported`]
+//! with its [`ObjectKind`], so callers can count and investigate it; the string API
+//! renders it through a replaceable renderer instead.
+
+use crate::export::json_string;
+use crate::fast_info::read_info_values_from_mem;
+#[cfg(feature = "std-fs")]
+use crate::fast_info::read_info_values_from_file;
+use crate::{Result, decode_document_string, format_pdf_date, info_string_object, parse_pdf_date};
+#[cfg(feature = "std-fs")]
+use crate::{Error, apply_objects_with, options, render_values, write_file};
+use chrono::{DateTime, FixedOffset};
+use lopdf::{Document, Object, StringFormat};
+use std::fmt;
+#[cfg(feature = "std-fs")]
+use std::path::Path;
+
+/// The kind of a PDF object that has no typed representation in [`MetadataValue`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[non_exhaustive]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ObjectKind {
+    Array,
+    Dictionary,
+    Stream,
+    Reference,
+}
+
+impl ObjectKind {
+    /// Returns the kind of `object`, or `None` if it has a typed representation.
+    pub fn of(object: &Object) -> Option<ObjectKind> {
+        match object {
+            Object::Array(_) => Some(ObjectKind::Array),
+            Object::Dictionary(_) => Some(ObjectKind::Dictionary),
+            Object::Stream(_) => Some(ObjectKind::Stream),
+            Object::Reference(_) => Some(ObjectKind::Reference),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ObjectKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ObjectKind::Array => "Array",
+            ObjectKind::Dictionary => "Dictionary",
+            ObjectKind::Stream => "Stream",
+            ObjectKind::Reference => "Reference",
+        };
+        f.write_str(name)
+    }
+}
+
+/// A decoded Info dictionary value.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MetadataValue {
+    /// A string object, decoded to Unicode.
+    String(String),
+    /// A name object such as `/Unknown`, without the leading slash.
+    Name(String),
+    Integer(i64),
+    Real(f32),
+    Boolean(bool),
+    /// A date entry (`CreationDate`, `ModDate`) parsed from its PDF date string.
+    Date(DateTime<FixedOffset>),
+    /// An array, with its items converted.
+    Array(Vec<MetadataValue>),
+    /// A dictionary, as `(key, value)` pairs in document order.
+    Dictionary(Vec<(String, MetadataValue)>),
+    Null,
+    /// An object kind this crate does not interpret.
+    Unsupported(ObjectKind),
+}
+
+impl MetadataValue {
+    /// Converts a value found in an Info dictionary.
+    ///
+    /// Without the document, references cannot be followed and are reported as
+    /// unsupported, and so are streams.
+    pub fn from_object(object: &Object) -> MetadataValue {
+        convert(None, object, 0)
+    }
+
+    /// Converts a value of `doc`, following references and decoding text streams.
+    pub(crate) fn from_object_in(doc: &Document, object: &Object) -> MetadataValue {
+        convert(Some(doc), object, 0)
+    }
+
+    /// Converts the value to the object written to an Info dictionary.
+    ///
+    /// Strings are encoded as by the string API and dates become PDF date strings.
+    /// Returns `None` for [`MetadataValue::Unsupported`], which carries no content,
+    /// and for arrays and dictionaries containing it.
+    pub fn to_object(&self) -> Option<Object> {
+        Some(match self {
+            MetadataValue::String(s) => info_string_object(s),
+            MetadataValue::Name(name) => Object::Name(name.as_bytes().to_vec()),
+            MetadataValue::Integer(i) => Object::Integer(*i),
+            MetadataValue::Real(f) => Object::Real(*f),
+            MetadataValue::Boolean(b) => Object::Boolean(*b),
+            MetadataValue::Date(dt) => Object::string_literal(format_pdf_date(dt)),
+            MetadataValue::Array(items) => Object::Array(items.iter().map(MetadataValue::to_object).collect::<Option<_>>()?),
+            MetadataValue::Dictionary(entries) => {
+                let mut dict = lopdf::Dictionary::new();
+                for (key, value) in entries {
+                    dict.set(key.as_bytes().to_vec(), value.to_object()?);
+                }
+                Object::Dictionary(dict)
+            }
+            MetadataValue::Null => Object::Null,
+            MetadataValue::Unsupported(_) => return None,
+        })
+    }
+
+    /// Renders the value as the string API does, using `render_unsupported` for
+    /// [`MetadataValue::Unsupported`].
+    ///
+    /// Arrays and dictionaries are rendered in JSON notation, e.g. `["Ana", 2]` or
+    /// `{"Name": "Ana"}`, with names, dates and unsupported items as strings.
+    pub fn to_string_with(&self, render_unsupported: &dyn Fn(ObjectKind) -> String) -> String {
+        match self {
+            MetadataValue::Array(items) => {
+                let items: Vec<String> = items.iter().map(|item| item.to_json_with(render_unsupported)).collect();
+                format!("[{}]", items.join(", "))
+            }
+            MetadataValue::Dictionary(entries) => {
+                let entries: Vec<String> = entries
+                    .iter()
+                    .map(|(key, value)| format!("{}: {}", json_string(key), value.to_json_with(render_unsupported)))
+                    .collect();
+                format!("{{{}}}", entries.join(", "))
+            }
+            MetadataValue::String(s) | MetadataValue::Name(s) => s.clone(),
+            MetadataValue::Integer(i) => i.to_string(),
+            MetadataValue::Real(f) => f.to_string(),
+            MetadataValue::Boolean(b) => b.to_string(),
+            MetadataValue::Date(dt) => format_pdf_date(dt),
+            MetadataValue::Null => "null".to_string(),
+            MetadataValue::Unsupported(kind) => render_unsupported(*kind),
+        }
+    }
+
+    /// The value as a JSON item inside an array or dictionary.
+    fn to_json_with(&self, render_unsupported: &dyn Fn(ObjectKind) -> String) -> String {
+        match self {
+            MetadataValue::Integer(_)
+            | MetadataValue::Real(_)
+            | MetadataValue::Boolean(_)
+            | MetadataValue::Null
+            | MetadataValue::Array(_)
+            | MetadataValue::Dictionary(_) => self.to_string_with(render_unsupported),
+            other => json_string(&other.to_string_with(render_unsupported)),
+        }
+    }
+}
+
+impl fmt::Display for MetadataValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_string_with(&render_unsupported))
+    }
+}
+
+/// How deeply references, arrays and dictionaries are followed; deeper values,
+/// including reference cycles, are reported as unsupported.
+pub(crate) const MAX_DEPTH: usize = 16;
+
+/// Streams longer than this are not decoded as text.
+const MAX_TEXT_STREAM_LEN: usize = 1024 * 1024;
+
+fn convert(doc: Option<&Document>, object: &Object, depth: usize) -> MetadataValue {
+    let unsupported = |object: &Object| MetadataValue::Unsupported(ObjectKind::of(object).expect("a container object"));
+    if depth > MAX_DEPTH {
+        return unsupported(object);
+    }
+    match object {
+        Object::String(bytes, format) => MetadataValue::String(decode_document_string(bytes, *format)),
+        Object::Name(bytes) => MetadataValue::Name(String::from_utf8_lossy(bytes).into_owned()),
+        Object::Integer(i) => MetadataValue::Integer(*i),
+        Object::Real(f) => MetadataValue::Real(*f),
+        Object::Boolean(b) => MetadataValue::Boolean(*b),
+        Object::Null => MetadataValue::Null,
+        Object::Array(items) => MetadataValue::Array(items.iter().map(|item| convert(doc, item, depth + 1)).collect()),
+        Object::Dictionary(dict) => MetadataValue::Dictionary(
+            dict.iter()
+                .map(|(key, value)| (String::from_utf8_lossy(key).into_owned(), convert(doc, value, depth + 1)))
+                .collect(),
+        ),
+        Object::Reference(id) => match doc.map(|doc| doc.get_object(*id)) {
+            Some(Ok(resolved)) => convert(doc, resolved, depth + 1),
+            _ => unsupported(object),
+        },
+        Object::Stream(stream) => match stream_text(stream) {
+            Some(text) if doc.is_some() => MetadataValue::String(text),
+            _ => unsupported(object),
+        },
+    }
+}
+
+/// The contents of `stream` as text, if they decode to UTF-16 with a byte order mark
+/// or to UTF-8 without control characters other than whitespace.
+pub(crate) fn stream_text(stream: &lopdf::Stream) -> Option<String> {
+    let content = stream.get_plain_content().ok()?;
+    if content.len() > MAX_TEXT_STREAM_LEN {
+        return None;
+    }
+    if content.starts_with(&[0xFE, 0xFF]) {
+        // Stream contents are raw bytes, like those of a hex string: no legacy wrapper.
+        return Some(decode_document_string(&content, StringFormat::Hexadecimal));
+    }
+    let text = String::from_utf8(content).ok()?;
+    let text = text.strip_prefix('\u{FEFF}').map(str::to_string).unwrap_or(text);
+    (!text.chars().any(|c| c.is_control() && !c.is_whitespace())).then_some(text)
+}
+
+/// Info entries that hold PDF dates.
+pub(crate) const DATE_KEYS: [&str; 2] = ["CreationDate", "ModDate"];
+
+/// Replaces parseable string values of date entries with [`MetadataValue::Date`].
+///
+/// Malformed dates are left as strings so no information is lost.
+pub(crate) fn parse_date_entries(values: &mut [(String, MetadataValue)]) {
+    for (key, value) in values.iter_mut() {
+        if DATE_KEYS.contains(&key.as_str())
+            && let MetadataValue::String(text) = value
+            && let Ok(dt) = parse_pdf_date(text)
+        {
+            *value = MetadataValue::Date(dt);
+        }
+    }
+}
+
+/// The default rendering of unsupported values in the string API, e.g. `<unsupported Array>`.
+pub fn render_unsupported(kind: ObjectKind) -> String {
+    format!("<unsupported {}>", kind)
+}
+
+/// Retrieves all metadata entries of the specified PDF file as typed values.
+///
+/// `CreationDate` and `ModDate` are returned as [`MetadataValue::Date`] when they
+/// hold a valid PDF date.
+///
+/// # Returns
+///
+/// * `Ok(Vec<(String, MetadataValue)>)`: The entries of the Info dictionary, in document order.
+/// * `Err(Error)`: If the file cannot be loaded.
+///
+/// # Example
+///
+/// ```no_run
+/// use pdf_metadata::{get_metadata_values, MetadataValue};
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     for (key, value) in get_metadata_values("path/to/document.pdf")? {
+///         if let MetadataValue::Unsupported(kind) = value {
+///             eprintln!("{} holds an unsupported {}", key, kind);
+///         }
+///     }
+///     Ok(())
+/// }
+/// ```
+#[cfg(feature = "std-fs")]
+pub fn get_metadata_values<P: AsRef<Path>>(file_path: P) -> Result<Vec<(String, MetadataValue)>> {
+    let file_path = file_path.as_ref();
+    let mut values = read_info_values_from_file(file_path)?;
+    parse_date_entries(&mut values);
+    Ok(values)
+}
+
+/// Retrieves all metadata entries of a PDF in memory as typed values.
+///
+/// The in-memory counterpart of [`get_metadata_values`].
+pub fn get_pdf_metadata_values(pdf_content: &[u8]) -> Result<Vec<(String, MetadataValue)>> {
+    let mut values = read_info_values_from_mem(pdf_content)?;
+    parse_date_entries(&mut values);
+    Ok(values)
+}
+
+/// Retrieves all metadata entries like `get_metadata`, rendering values of unsupported
+/// object kinds with `render_unsupported` instead of the default placeholder.
+///
+/// # Example
+///
+/// ```no_run
+/// use pdf_metadata::get_metadata_with_renderer;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     // Leave unsupported values empty instead of showing a placeholder.
+///     let metadata = get_metadata_with_renderer("path/to/document.pdf", &|_kind| String::new())?;
+///     println!("{} entries", metadata.len());
+///     Ok(())
+/// }
+/// ```
+#[cfg(feature = "std-fs")]
+pub fn get_metadata_with_renderer<P: AsRef<Path>>(
+    file_path: P,
+    render_unsupported: &dyn Fn(ObjectKind) -> String,
+) -> Result<Vec<(String, String)>> {
+    let file_path = file_path.as_ref();
+    Ok(render_values(read_info_values_from_file(file_path)?, render_unsupported))
+}
+
+/// Sets a metadata entry to a typed value and saves the document to `output_path`.
+///
+/// Unlike [`set_metadata`](crate::set_metadata), which always writes a string, the
+/// entry keeps the object type of `value`, so names, numbers and booleans round-trip
+/// through [`get_metadata_values`]. `ModDate` is refreshed and the other entries are
+/// left untouched.
+///
+/// # Returns
+///
+/// * `Ok(())` if the document was written.
+/// * `Err(Error::UnsupportedFormat)`: If `value` is [`MetadataValue::Unsupported`].
+/// * `Err(Error)`: If the file cannot be loaded or written.
+///
+/// # Example
+///
+/// ```no_run
+/// use pdf_metadata::{MetadataValue, set_metadata_value};
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let trapped = MetadataValue::Name("False".to_string());
+///     set_metadata_value("path/to/input.pdf", "path/to/output.pdf", "Trapped", &trapped)?;
+///     Ok(())
+/// }
+/// ```
+#[cfg(feature = "std-fs")]
+pub fn set_metadata_value<P: AsRef<Path>, Q: AsRef<Path>>(file_path: P, output_path: Q, metadata_key: &str, value: &MetadataValue) -> Result<()> {
+    let file_path = file_path.as_ref();
+    let output_path = output_path.as_ref();
+    let object = writable_object(metadata_key, value)?;
+    let options = options::current();
+    write_file(file_path, Some(output_path), &options, |doc| {
+        apply_objects_with(doc, &[(metadata_key, object)], &options).map(drop)
+    })
+}
+
+/// Sets a metadata entry to a typed value, modifying the file in place.
+///
+/// The in-place counterpart of [`set_metadata_value`], with the safety of
+/// [`update_metadata_in_place`](crate::update_metadata_in_place).
+#[cfg(feature = "std-fs")]
+pub fn update_metadata_value_in_place<P: AsRef<Path>>(file_path: P, metadata_key: &str, value: &MetadataValue) -> Result<()> {
+    let file_path = file_path.as_ref();
+    let object = writable_object(metadata_key, value)?;
+    let options = options::current();
+    write_file(file_path, None, &options, |doc| apply_objects_with(doc, &[(metadata_key, object)], &options).map(drop))
+}
+
+#[cfg(feature = "std-fs")]
+fn writable_object(metadata_key: &str, value: &MetadataValue) -> Result<Object> {
+    value.to_object().ok_or_else(|| {
+        Error::UnsupportedFormat(format!("cannot write {} as the value of {}", value, metadata_key))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::get_metadata;
+    use crate::test_support::setup_unique_test_dir;
+    use lopdf::{Dictionary, Stream, dictionary};
+    use std::fs;
+
+    #[test]
+    fn test_unsupported_values_are_typed_and_rendered() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("value_unsupported");
+        let file = test_dir.join("odd_values.pdf");
+
+        let mut doc = Document::with_version("1.7");
+        let mut info_dict = Dictionary::new();
+        info_dict.set("Title", Object::string_literal("Report"));
+        info_dict.set("Trapped", Object::Name(b"False".to_vec()));
+        info_dict.set("Revision", Object::Integer(3));
+        info_dict.set("CreationDate", Object::string_literal("D:20231027153000Z"));
+        info_dict.set("ModDate", Object::string_literal("last week"));
+        info_dict.set("Reviewers", Object::Array(vec![Object::string_literal("Ana")]));
+        let abstract_id = doc.add_object(Stream::new(Dictionary::new(), "Um resumo (longo)\n".as_bytes().to_vec()));
+        info_dict.set("Abstract", abstract_id);
+        let thumbnail_id = doc.add_object(Stream::new(Dictionary::new(), vec![0x89, b'P', b'N', b'G', 0, 1, 2]));
+        info_dict.set("Thumbnail", thumbnail_id);
+        let owner_id = doc.add_object(Object::string_literal("Ana"));
+        info_dict.set("Source", dictionary! { "Owner" => owner_id, "Pages" => vec![1.into(), 2.into()], "Kind" => "Scan" });
+        let info_id = doc.add_object(info_dict);
+        doc.trailer.set("Info", Object::Reference(info_id));
+        doc.save(&file)?;
+        let file_str = file.to_str().unwrap();
+
+        let values = get_metadata_values(file_str)?;
+        let value = |key: &str| values.iter().find(|(k, _)| k == key).map(|(_, v)| v.clone());
+        assert_eq!(value("Title"), Some(MetadataValue::String("Report".to_string())));
+        assert_eq!(value("Trapped"), Some(MetadataValue::Name("False".to_string())));
+        assert_eq!(value("Revision"), Some(MetadataValue::Integer(3)));
+        assert_eq!(value("Reviewers"), Some(MetadataValue::Array(vec![MetadataValue::String("Ana".to_string())])));
+        assert_eq!(value("Abstract"), Some(MetadataValue::String("Um resumo (longo)\n".to_string())));
+        assert_eq!(value("Thumbnail"), Some(MetadataValue::Unsupported(ObjectKind::Stream)));
+        assert_eq!(value("CreationDate"), Some(MetadataValue::Date(parse_pdf_date("D:20231027153000+00'00'")?)));
+        assert_eq!(value("ModDate"), Some(MetadataValue::String("last week".to_string())));
+        assert_eq!(get_pdf_metadata_values(&fs::read(&file)?)?, values);
+
+        let metadata = get_metadata(file_str)?;
+        let entry = |key: &str| metadata.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str());
+        assert_eq!(entry("Reviewers"), Some(r#"["Ana"]"#));
+        assert_eq!(entry("Source"), Some(r#"{"Owner": "Ana", "Pages": [1, 2], "Kind": "Scan"}"#));
+        assert_eq!(entry("Thumbnail"), Some("<unsupported Stream>"));
+
+        let metadata = get_metadata_with_renderer(file_str, &|kind| format!("[{}]", kind))?;
+        assert!(metadata.iter().any(|(k, v)| k == "Thumbnail" && v == "[Stream]"));
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_typed_values_round_trip() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("value_typed_writes");
+        let file = test_dir.join("typed.pdf");
+        crate::test_support::create_minimal_test_pdf(&file)?;
+        let file_str = file.to_str().unwrap();
+
+        let typed = [
+            ("Trapped", MetadataValue::Name("True".to_string())),
+            ("Revision", MetadataValue::Integer(12)),
+            ("Score", MetadataValue::Real(0.5)),
+            ("Approved", MetadataValue::Boolean(true)),
+            ("CreationDate", MetadataValue::Date(parse_pdf_date("D:20240102030405+01'00'")?)),
+            ("Title", MetadataValue::String("Relatório".to_string())),
+        ];
+        for (key, value) in &typed {
+            update_metadata_value_in_place(file_str, key, value)?;
+        }
+        let values = get_metadata_values(file_str)?;
+        for (key, value) in &typed {
+            assert!(values.iter().any(|(k, v)| k == key && v == value), "{} did not round-trip", key);
+        }
+
+        let output = test_dir.join("copy.pdf");
+        set_metadata_value(file_str, output.to_str().unwrap(), "Revision", &MetadataValue::Integer(13))?;
+        let copied = get_metadata_values(output.to_str().unwrap())?;
+        assert!(copied.iter().any(|(k, v)| k == "Revision" && *v == MetadataValue::Integer(13)));
+        assert!(copied.iter().any(|(k, v)| k == "Trapped" && *v == MetadataValue::Name("True".to_string())));
+
+        let unsupported = MetadataValue::Unsupported(ObjectKind::Array);
+        assert!(matches!(update_metadata_value_in_place(file_str, "X", &unsupported), Err(Error::UnsupportedFormat(_))));
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_values_serialize() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+        struct Entries {
+            values: Vec<(String, MetadataValue)>,
+        }
+        let entries = Entries {
+            values: vec![
+                ("Title".to_string(), MetadataValue::String("Relatório".to_string())),
+                ("CreationDate".to_string(), MetadataValue::Date(parse_pdf_date("D:20240102030405+01'00'")?)),
+                ("Pages".to_string(), MetadataValue::Unsupported(ObjectKind::Array)),
+            ],
+        };
+        let text = toml::to_string(&entries)?;
+        assert!(text.contains("2024-01-02T03:04:05+01:00"), "{}", text);
+        assert_eq!(toml::from_str::<Entries>(&text)?, entries);
+        Ok(())
+    }
+}
@@ -0,0 +1,175 @@
+//! Borrowed Info dictionary values, for reading many documents in a tight loop.
+//!
+//! [`get_metadata_ref`] walks the Info dictionary of an already loaded document and
+//! yields [`MetadataValueRef`]s that point into it. Keys and strings stored as
+//! UTF-8, which includes plain ASCII, are borrowed; only UTF-16 and PDFDocEncoding
+//! text, values decoded by a registered codec and text streams are copied. Values
+//! decode exactly as [`get_metadata_values`](crate::get_metadata_values) returns them.
+
+use crate::text_string::decode_document_str;
+use crate::value::{DATE_KEYS, MAX_DEPTH, stream_text};
+use crate::{MetadataValue, ObjectKind, parse_pdf_date};
+use chrono::{DateTime, FixedOffset};
+use lopdf::{Document, Object};
+use std::borrow::Cow;
+
+/// A decoded Info dictionary value borrowing its text from the document where it can.
+///
+/// The counterpart of [`MetadataValue`]; [`into_owned`](MetadataValueRef::into_owned)
+/// converts one to the other.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum MetadataValueRef<'a> {
+    /// A string object, decoded to Unicode.
+    String(Cow<'a, str>),
+    /// A name object such as `/Unknown`, without the leading slash.
+    Name(Cow<'a, str>),
+    Integer(i64),
+    Real(f32),
+    Boolean(bool),
+    /// A date entry (`CreationDate`, `ModDate`) parsed from its PDF date string.
+    Date(DateTime<FixedOffset>),
+    /// An array, with its items converted.
+    Array(Vec<MetadataValueRef<'a>>),
+    /// A dictionary, as `(key, value)` pairs in document order.
+    Dictionary(Vec<(Cow<'a, str>, MetadataValueRef<'a>)>),
+    Null,
+    /// An object kind this crate does not interpret.
+    Unsupported(ObjectKind),
+}
+
+impl<'a> MetadataValueRef<'a> {
+    /// Converts a value of `doc`, following references and decoding text streams.
+    fn from_object_in(doc: &'a Document, object: &'a Object, depth: usize) -> MetadataValueRef<'a> {
+        let unsupported =
+            |object: &Object| MetadataValueRef::Unsupported(ObjectKind::of(object).expect("a container object"));
+        if depth > MAX_DEPTH {
+            return unsupported(object);
+        }
+        match object {
+            Object::String(bytes, format) => MetadataValueRef::String(decode_document_str(bytes, *format)),
+            Object::Name(bytes) => MetadataValueRef::Name(String::from_utf8_lossy(bytes)),
+            Object::Integer(i) => MetadataValueRef::Integer(*i),
+            Object::Real(f) => MetadataValueRef::Real(*f),
+            Object::Boolean(b) => MetadataValueRef::Boolean(*b),
+            Object::Null => MetadataValueRef::Null,
+            Object::Array(items) => {
+                MetadataValueRef::Array(items.iter().map(|item| Self::from_object_in(doc, item, depth + 1)).collect())
+            }
+            Object::Dictionary(dict) => MetadataValueRef::Dictionary(
+                dict.iter()
+                    .map(|(key, value)| (String::from_utf8_lossy(key), Self::from_object_in(doc, value, depth + 1)))
+                    .collect(),
+            ),
+            Object::Reference(id) => match doc.get_object(*id) {
+                Ok(resolved) => Self::from_object_in(doc, resolved, depth + 1),
+                Err(_) => unsupported(object),
+            },
+            Object::Stream(stream) => match stream_text(stream) {
+                Some(text) => MetadataValueRef::String(Cow::Owned(text)),
+                None => unsupported(object),
+            },
+        }
+    }
+
+    /// The text of a [`String`](MetadataValueRef::String) value.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            MetadataValueRef::String(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    /// Copies the value into a [`MetadataValue`] that no longer borrows from the document.
+    pub fn into_owned(self) -> MetadataValue {
+        match self {
+            MetadataValueRef::String(text) => MetadataValue::String(text.into_owned()),
+            MetadataValueRef::Name(name) => MetadataValue::Name(name.into_owned()),
+            MetadataValueRef::Integer(i) => MetadataValue::Integer(i),
+            MetadataValueRef::Real(f) => MetadataValue::Real(f),
+            MetadataValueRef::Boolean(b) => MetadataValue::Boolean(b),
+            MetadataValueRef::Date(dt) => MetadataValue::Date(dt),
+            MetadataValueRef::Array(items) => MetadataValue::Array(items.into_iter().map(Self::into_owned).collect()),
+            MetadataValueRef::Dictionary(entries) => MetadataValue::Dictionary(
+                entries.into_iter().map(|(key, value)| (key.into_owned(), value.into_owned())).collect(),
+            ),
+            MetadataValueRef::Null => MetadataValue::Null,
+            MetadataValueRef::Unsupported(kind) => MetadataValue::Unsupported(kind),
+        }
+    }
+}
+
+impl From<MetadataValueRef<'_>> for MetadataValue {
+    fn from(value: MetadataValueRef<'_>) -> Self {
+        value.into_owned()
+    }
+}
+
+/// Iterates over the Info dictionary of a loaded document without copying its text.
+///
+/// Entries come in document order, with `CreationDate` and `ModDate` as
+/// [`MetadataValueRef::Date`] when they hold a valid PDF date. A document without an
+/// Info dictionary yields nothing. Each value is decoded as the iterator reaches it,
+/// so stopping early skips the rest.
+///
+/// # Example
+///
+/// ```no_run
+/// use lopdf::Document;
+/// use pdf_metadata::get_metadata_ref;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let doc = Document::load("path/to/document.pdf")?;
+///     let title = get_metadata_ref(&doc).find(|(key, _)| key == "Title");
+///     if let Some(title) = title.as_ref().and_then(|(_, value)| value.as_str()) {
+///         println!("{}", title);
+///     }
+///     Ok(())
+/// }
+/// ```
+pub fn get_metadata_ref(doc: &Document) -> impl Iterator<Item = (Cow<'_, str>, MetadataValueRef<'_>)> {
+    let info = doc.trailer.get(b"Info").and_then(Object::as_reference).and_then(|id| doc.get_dictionary(id)).ok();
+    info.into_iter().flat_map(|dict| dict.iter()).map(move |(key, object)| {
+        let key = String::from_utf8_lossy(key);
+        let mut value = MetadataValueRef::from_object_in(doc, object, 0);
+        if DATE_KEYS.contains(&key.as_ref())
+            && let MetadataValueRef::String(text) = &value
+            && let Ok(dt) = parse_pdf_date(text)
+        {
+            value = MetadataValueRef::Date(dt);
+        }
+        (key, value)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{create_minimal_test_pdf, setup_unique_test_dir};
+    use crate::{get_metadata_values, load_document, update_entries_in_place};
+    use std::fs;
+
+    #[test]
+    fn test_borrowed_values_match_owned_ones() -> Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("metadata_ref");
+        let file = test_dir.join("doc.pdf");
+        create_minimal_test_pdf(&file)?;
+        update_entries_in_place(&file, &[("Title", "Plain title"), ("Subject", "Relatório – 2024")])?;
+        let doc = load_document(&file)?;
+
+        let entries: Vec<_> = get_metadata_ref(&doc).collect();
+        let title = entries.iter().find(|(key, _)| key == "Title").map(|(_, value)| value);
+        assert!(matches!(title, Some(MetadataValueRef::String(Cow::Borrowed("Plain title")))));
+        let subject = entries.iter().find(|(key, _)| key == "Subject");
+        assert!(matches!(subject, Some((Cow::Borrowed(_), MetadataValueRef::String(Cow::Owned(_))))));
+        assert!(matches!(entries.iter().find(|(key, _)| key == "ModDate"), Some((_, MetadataValueRef::Date(_)))));
+
+        let owned: Vec<(String, MetadataValue)> =
+            entries.into_iter().map(|(key, value)| (key.into_owned(), value.into_owned())).collect();
+        assert_eq!(owned, get_metadata_values(&file)?);
+
+        assert_eq!(get_metadata_ref(&Document::with_version("1.7")).count(), 0);
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+}
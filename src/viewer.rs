@@ -0,0 +1,403 @@
+//! Document-level settings kept in the catalog: the natural language (`/Lang`) and
+//! how viewers open the document (`/PageMode`, `/PageLayout`, `/ViewerPreferences`).
+//!
+//! Accessibility checkers (PDF/UA, WCAG) require `/Lang` and, together with a
+//! `Title`, `/ViewerPreferences << /DisplayDocTitle true >>` so the window shows the
+//! title instead of the file name.
+
+#[cfg(feature = "std-fs")]
+use crate::attachments::resolve_dict;
+#[cfg(feature = "std-fs")]
+use crate::xmp::{XMP_NS, XmpPacket, info_properties, read_packet_xml, rewrite_packet, write_packet};
+use crate::{Error, Result};
+#[cfg(feature = "std-fs")]
+use crate::{
+    apply_entries_with, decode_string_object, incremental, info_string_object, load_document, mark_modified, options,
+    read_info_entries, write_file,
+};
+#[cfg(feature = "std-fs")]
+use lopdf::{Dictionary, Document, Object};
+#[cfg(feature = "std-fs")]
+use std::path::Path;
+
+/// What a viewer shows next to the pages when it opens the document (`/PageMode`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PageMode {
+    /// Neither outline nor thumbnails (the default).
+    UseNone,
+    /// The bookmarks panel.
+    UseOutlines,
+    /// The page thumbnails.
+    UseThumbs,
+    /// Full-screen mode, without menus or panels.
+    FullScreen,
+    /// The optional content (layers) panel.
+    UseOC,
+    /// The attachments panel.
+    UseAttachments,
+}
+
+/// How pages are arranged when the document is opened (`/PageLayout`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PageLayout {
+    /// One page at a time (the default).
+    SinglePage,
+    /// Pages in one continuous column.
+    OneColumn,
+    /// Two continuous columns, odd pages on the left.
+    TwoColumnLeft,
+    /// Two continuous columns, odd pages on the right.
+    TwoColumnRight,
+    /// Two pages at a time, odd pages on the left.
+    TwoPageLeft,
+    /// Two pages at a time, odd pages on the right.
+    TwoPageRight,
+}
+
+impl PageMode {
+    const ALL: [PageMode; 6] = [
+        PageMode::UseNone,
+        PageMode::UseOutlines,
+        PageMode::UseThumbs,
+        PageMode::FullScreen,
+        PageMode::UseOC,
+        PageMode::UseAttachments,
+    ];
+
+    /// The PDF name of the mode, without the leading slash.
+    pub fn as_name(self) -> &'static str {
+        match self {
+            PageMode::UseNone => "UseNone",
+            PageMode::UseOutlines => "UseOutlines",
+            PageMode::UseThumbs => "UseThumbs",
+            PageMode::FullScreen => "FullScreen",
+            PageMode::UseOC => "UseOC",
+            PageMode::UseAttachments => "UseAttachments",
+        }
+    }
+
+    #[cfg(feature = "std-fs")]
+    fn from_name(name: &[u8]) -> Option<PageMode> {
+        PageMode::ALL.into_iter().find(|mode| mode.as_name().as_bytes() == name)
+    }
+}
+
+impl std::str::FromStr for PageMode {
+    type Err = Error;
+
+    /// Parses the PDF name, ignoring case.
+    fn from_str(s: &str) -> Result<Self> {
+        PageMode::ALL
+            .into_iter()
+            .find(|mode| mode.as_name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| Error::UnsupportedFormat(format!("page mode '{}'", s)))
+    }
+}
+
+impl PageLayout {
+    const ALL: [PageLayout; 6] = [
+        PageLayout::SinglePage,
+        PageLayout::OneColumn,
+        PageLayout::TwoColumnLeft,
+        PageLayout::TwoColumnRight,
+        PageLayout::TwoPageLeft,
+        PageLayout::TwoPageRight,
+    ];
+
+    /// The PDF name of the layout, without the leading slash.
+    pub fn as_name(self) -> &'static str {
+        match self {
+            PageLayout::SinglePage => "SinglePage",
+            PageLayout::OneColumn => "OneColumn",
+            PageLayout::TwoColumnLeft => "TwoColumnLeft",
+            PageLayout::TwoColumnRight => "TwoColumnRight",
+            PageLayout::TwoPageLeft => "TwoPageLeft",
+            PageLayout::TwoPageRight => "TwoPageRight",
+        }
+    }
+
+    #[cfg(feature = "std-fs")]
+    fn from_name(name: &[u8]) -> Option<PageLayout> {
+        PageLayout::ALL.into_iter().find(|layout| layout.as_name().as_bytes() == name)
+    }
+}
+
+impl std::str::FromStr for PageLayout {
+    type Err = Error;
+
+    /// Parses the PDF name, ignoring case.
+    fn from_str(s: &str) -> Result<Self> {
+        PageLayout::ALL
+            .into_iter()
+            .find(|layout| layout.as_name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| Error::UnsupportedFormat(format!("page layout '{}'", s)))
+    }
+}
+
+/// Catalog-level settings, as returned by [`get_viewer_settings`].
+///
+/// When writing with [`set_viewer_settings`], `None` leaves the current value alone.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ViewerSettings {
+    /// The natural language of the document (`/Lang`), a BCP 47 tag such as `pt-BR`.
+    pub lang: Option<String>,
+    /// `/PageMode`. Names this crate does not know are read as `None`.
+    pub page_mode: Option<PageMode>,
+    /// `/PageLayout`. Names this crate does not know are read as `None`.
+    pub page_layout: Option<PageLayout>,
+    /// `/ViewerPreferences /DisplayDocTitle`: show the `Title` in the window title bar.
+    pub display_doc_title: Option<bool>,
+}
+
+/// Reads `/Lang`, `/PageMode`, `/PageLayout` and `/ViewerPreferences /DisplayDocTitle`
+/// from the catalog.
+///
+/// # Returns
+///
+/// * `Ok(ViewerSettings)`: The settings; fields are `None` for missing entries.
+/// * `Err(Error)`: If the file cannot be read or parsed.
+#[cfg(feature = "std-fs")]
+pub fn get_viewer_settings<P: AsRef<Path>>(file_path: P) -> Result<ViewerSettings> {
+    let file_path = file_path.as_ref();
+    let doc = load_document(file_path)?;
+    let catalog = doc.catalog()?;
+    let name = |key: &[u8]| catalog.get(key).and_then(Object::as_name).ok();
+    let display_doc_title = catalog
+        .get(b"ViewerPreferences")
+        .ok()
+        .and_then(|preferences| resolve_dict(&doc, preferences))
+        .and_then(|preferences| preferences.get(b"DisplayDocTitle").and_then(Object::as_bool).ok());
+    Ok(ViewerSettings {
+        lang: catalog
+            .get(b"Lang")
+            .ok()
+            .and_then(|lang| doc.dereference(lang).ok())
+            .and_then(|(_, lang)| decode_string_object(lang)),
+        page_mode: name(b"PageMode").and_then(PageMode::from_name),
+        page_layout: name(b"PageLayout").and_then(PageLayout::from_name),
+        display_doc_title,
+    })
+}
+
+/// Writes the `Some` fields of `settings` to the catalog, writing the result to
+/// `output_path`. `ModDate` is refreshed. Use the same path for input and output to
+/// update the file in place.
+///
+/// # Returns
+///
+/// * `Ok(())` if the document was written.
+/// * `Err(Error)`: If the file cannot be read, or the output cannot be written.
+///
+/// # Example
+///
+/// ```no_run
+/// use pdf_metadata::{ViewerSettings, set_viewer_settings, update_metadata_in_place};
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     // What accessibility checkers ask for.
+///     update_metadata_in_place("report.pdf", "Title", "Relatório anual")?;
+///     let settings = ViewerSettings {
+///         lang: Some("pt-BR".to_string()),
+///         display_doc_title: Some(true),
+///         ..ViewerSettings::default()
+///     };
+///     set_viewer_settings("report.pdf", "report.pdf", &settings)?;
+///     Ok(())
+/// }
+/// ```
+#[cfg(feature = "std-fs")]
+pub fn set_viewer_settings<P: AsRef<Path>, Q: AsRef<Path>>(file_path: P, output_path: Q, settings: &ViewerSettings) -> Result<()> {
+    let options = options::current();
+    write_file(file_path.as_ref(), Some(output_path.as_ref()), &options, |doc| {
+        apply_settings(doc, settings)?;
+        mark_modified(doc, &options)
+    })
+}
+
+/// Sets the document's title everywhere PDF/UA checks it: the Info `Title`, the XMP
+/// `dc:title` and `/ViewerPreferences /DisplayDocTitle`, writing the result to
+/// `output_path`.
+///
+/// The title becomes the `x-default` alternative of `dc:title`; other languages are
+/// kept. A packet is created if the document has none. `ModDate` is refreshed, and so
+/// is `xmp:ModifyDate` when the packet has one, so Info and XMP keep agreeing.
+///
+/// # Returns
+///
+/// * `Ok(())` if the document was written.
+/// * `Err(Error::Xml)`: If the existing XMP packet is not well-formed.
+/// * `Err(Error)`: If the file cannot be read, or the output cannot be written.
+///
+/// # Example
+///
+/// ```no_run
+/// use pdf_metadata::set_document_title;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     set_document_title("report.pdf", "report.pdf", "Relatório anual 2024", true)?;
+///     Ok(())
+/// }
+/// ```
+#[cfg(feature = "std-fs")]
+pub fn set_document_title<P: AsRef<Path>, Q: AsRef<Path>>(file_path: P, output_path: Q, title: &str, display_doc_title: bool) -> Result<()> {
+    let options = options::current();
+    write_file(file_path.as_ref(), Some(output_path.as_ref()), &options, |doc| {
+        apply_entries_with(doc, &[("Title", title)], &options)?;
+
+        let xml = read_packet_xml(doc)?;
+        let packet = xml.as_deref().map(XmpPacket::parse).transpose()?.unwrap_or_default();
+        let mut entries = vec![("Title".to_string(), title.to_string())];
+        if packet.get(XMP_NS, "ModifyDate").is_some() {
+            entries.extend(read_info_entries(doc).into_iter().filter(|(key, _)| key == "ModDate"));
+        }
+        let properties = info_properties(&entries, &packet);
+        write_packet(doc, rewrite_packet(xml.as_deref(), &properties)?)?;
+
+        let settings = ViewerSettings { display_doc_title: Some(display_doc_title), ..ViewerSettings::default() };
+        apply_settings(doc, &settings)
+    })
+}
+
+/// Writes the `Some` fields of `settings` to the catalog of `doc`.
+#[cfg(feature = "std-fs")]
+fn apply_settings(doc: &mut Document, settings: &ViewerSettings) -> Result<()> {
+    let preferences = match settings.display_doc_title {
+        Some(display_doc_title) => {
+            let catalog = doc.catalog()?;
+            let mut preferences: Dictionary = catalog
+                .get(b"ViewerPreferences")
+                .ok()
+                .and_then(|preferences| resolve_dict(doc, preferences))
+                .cloned()
+                .unwrap_or_default();
+            preferences.set("DisplayDocTitle", display_doc_title);
+            Some(preferences)
+        }
+        None => None,
+    };
+
+    let catalog = incremental::catalog_mut(doc)?;
+    if let Some(lang) = &settings.lang {
+        catalog.set("Lang", info_string_object(lang));
+    }
+    if let Some(page_mode) = settings.page_mode {
+        catalog.set("PageMode", Object::Name(page_mode.as_name().as_bytes().to_vec()));
+    }
+    if let Some(page_layout) = settings.page_layout {
+        catalog.set("PageLayout", Object::Name(page_layout.as_name().as_bytes().to_vec()));
+    }
+    if let Some(preferences) = preferences {
+        catalog.set("ViewerPreferences", preferences);
+    }
+    Ok(())
+}
+
+/// Returns the document's `/Lang`, or `None` if it has none.
+#[cfg(feature = "std-fs")]
+pub fn get_lang<P: AsRef<Path>>(file_path: P) -> Result<Option<String>> {
+    let file_path = file_path.as_ref();
+    Ok(get_viewer_settings(file_path)?.lang)
+}
+
+/// Sets the document's `/Lang`, in place.
+#[cfg(feature = "std-fs")]
+pub fn set_lang<P: AsRef<Path>>(file_path: P, lang: &str) -> Result<()> {
+    let file_path = file_path.as_ref();
+    let settings = ViewerSettings { lang: Some(lang.to_string()), ..ViewerSettings::default() };
+    set_viewer_settings(file_path, file_path, &settings)
+}
+
+/// Sets `/ViewerPreferences /DisplayDocTitle`, in place.
+#[cfg(feature = "std-fs")]
+pub fn set_display_doc_title<P: AsRef<Path>>(file_path: P, display_doc_title: bool) -> Result<()> {
+    let file_path = file_path.as_ref();
+    let settings = ViewerSettings { display_doc_title: Some(display_doc_title), ..ViewerSettings::default() };
+    set_viewer_settings(file_path, file_path, &settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{create_minimal_test_pdf, setup_unique_test_dir};
+    use lopdf::{Document, dictionary};
+    use std::fs;
+
+    #[test]
+    fn test_viewer_settings_round_trip() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("viewer_settings");
+        let file = test_dir.join("doc.pdf");
+        create_minimal_test_pdf(&file)?;
+        let file_str = file.to_str().unwrap();
+        assert_eq!(get_viewer_settings(file_str)?, ViewerSettings::default());
+
+        // Existing preferences, held in an indirect object, are kept.
+        let mut doc = Document::load(&file)?;
+        let preferences_id = doc.add_object(dictionary! { "HideToolbar" => true });
+        doc.catalog_mut()?.set("ViewerPreferences", preferences_id);
+        doc.save(&file)?;
+
+        set_lang(file_str, "pt-BR")?;
+        set_display_doc_title(file_str, true)?;
+        let settings = ViewerSettings {
+            page_mode: Some("useoutlines".parse()?),
+            page_layout: Some(PageLayout::TwoPageRight),
+            ..ViewerSettings::default()
+        };
+        set_viewer_settings(file_str, file_str, &settings)?;
+
+        assert_eq!(
+            get_viewer_settings(file_str)?,
+            ViewerSettings {
+                lang: Some("pt-BR".to_string()),
+                page_mode: Some(PageMode::UseOutlines),
+                page_layout: Some(PageLayout::TwoPageRight),
+                display_doc_title: Some(true),
+            }
+        );
+        let doc = Document::load(&file)?;
+        let preferences = resolve_dict(&doc, doc.catalog()?.get(b"ViewerPreferences")?).unwrap();
+        assert!(preferences.get(b"HideToolbar")?.as_bool()?);
+        assert!("Sideways".parse::<PageLayout>().is_err());
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_document_title_updates_info_xmp_and_preferences() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("viewer_document_title");
+        let file = test_dir.join("doc.pdf");
+        create_minimal_test_pdf(&file)?;
+        let file_str = file.to_str().unwrap();
+        let mut doc = Document::load(&file)?;
+        let packet = r#"<x:xmpmeta xmlns:x="adobe:ns:meta/"><rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+<rdf:Description rdf:about="" xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:xmp="http://ns.adobe.com/xap/1.0/">
+<dc:title><rdf:Alt><rdf:li xml:lang="fr">Rapport</rdf:li></rdf:Alt></dc:title>
+<xmp:ModifyDate>2020-01-01T00:00:00Z</xmp:ModifyDate>
+</rdf:Description></rdf:RDF></x:xmpmeta>"#;
+        write_packet(&mut doc, packet.to_string())?;
+        doc.save(&file)?;
+
+        set_document_title(file_str, file_str, "Relatório", true)?;
+
+        assert_eq!(crate::get_title(file_str)?.as_deref(), Some("Relatório"));
+        assert_eq!(get_viewer_settings(file_str)?.display_doc_title, Some(true));
+        let doc = Document::load(&file)?;
+        let packet = crate::xmp::read_packet(&doc)?.unwrap();
+        assert_eq!(
+            packet.get(crate::xmp::DC_NS, "title"),
+            Some(&crate::xmp::XmpValue::Alt(vec![
+                (Some("x-default".to_string()), "Relatório".to_string()),
+                (Some("fr".to_string()), "Rapport".to_string()),
+            ]))
+        );
+        let mod_date = read_info_entries(&doc).into_iter().find(|(key, _)| key == "ModDate").unwrap().1;
+        assert!(crate::xmp::info_matches("ModDate", &mod_date, packet.get(XMP_NS, "ModifyDate").unwrap()));
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+}
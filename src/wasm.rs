@@ -0,0 +1,115 @@
+//! JavaScript bindings for browsers (`wasm` feature).
+//!
+//! Browsers have no filesystem, so only the in-memory functions are exported: pass
+//! the bytes of an uploaded file (a `Uint8Array`) and get plain JavaScript values
+//! back. Errors are thrown as `Error` objects carrying the message of [`Error`](crate::Error).
+//!
+//! ```js
+//! import init, { getMetadata, setMetadata } from "./pkg/pdf_metadata.js";
+//!
+//! await init();
+//! const bytes = new Uint8Array(await file.arrayBuffer());
+//! console.log(getMetadata(bytes).Title);
+//! const updated = setMetadata(bytes, "Author", "Ana Souza");
+//! ```
+//!
+//! Build a `cdylib` without the filesystem API, selecting getrandom's JavaScript
+//! backend, then generate the `pkg/` glue:
+//!
+//! ```text
+//! RUSTFLAGS='--cfg getrandom_backend="wasm_js"' cargo rustc --release --lib \
+//!     --target wasm32-unknown-unknown --no-default-features --features wasm --crate-type cdylib
+//! wasm-bindgen --target web --out-dir pkg target/wasm32-unknown-unknown/release/pdf_metadata.wasm
+//! ```
+
+use js_sys::{Object, Reflect};
+use wasm_bindgen::prelude::*;
+
+/// Returns the Info entries of a PDF as an object mapping keys to string values.
+#[wasm_bindgen(js_name = getMetadata)]
+pub fn get_metadata(pdf: &[u8]) -> Result<Object, JsError> {
+    let entries = Object::new();
+    for (key, value) in crate::get_pdf_metadata(pdf)? {
+        // Defining a property on a plain, extensible object cannot fail.
+        let _ = Reflect::set(&entries, &key.into(), &value.into());
+    }
+    Ok(entries)
+}
+
+/// Sets one Info entry and returns the bytes of the updated PDF. `ModDate` is refreshed.
+#[wasm_bindgen(js_name = setMetadata)]
+pub fn set_metadata(pdf: &[u8], key: &str, value: &str) -> Result<Vec<u8>, JsError> {
+    Ok(crate::set_pdf_metadata(pdf, key, value)?)
+}
+
+/// The browser's clock, read through JavaScript's `Date`. The standard library has
+/// no clock on `wasm32-unknown-unknown`, so this is the default clock there.
+#[cfg(target_arch = "wasm32")]
+pub(crate) struct JsClock;
+
+#[cfg(target_arch = "wasm32")]
+impl crate::Clock for JsClock {
+    fn now(&self) -> chrono::DateTime<chrono::FixedOffset> {
+        use chrono::{DateTime, FixedOffset};
+
+        let date = js_sys::Date::new_0();
+        // getTimezoneOffset() is UTC minus local time, in minutes.
+        let offset = FixedOffset::west_opt(date.get_timezone_offset() as i32 * 60).unwrap_or(FixedOffset::east_opt(0).unwrap());
+        DateTime::from_timestamp_millis(date.get_time() as i64).unwrap_or_default().with_timezone(&offset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::minimal_test_pdf_bytes;
+    use crate::{FixedClock, with_clock};
+    use chrono::DateTime;
+
+    fn value<'a>(entries: &'a [(String, String)], key: &str) -> Option<&'a str> {
+        entries.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+    }
+
+    #[test]
+    fn test_set_metadata_returns_the_updated_bytes() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let pinned = DateTime::parse_from_rfc3339("2024-05-06T07:08:09+00:00")?;
+        let original = minimal_test_pdf_bytes()?;
+        let updated = with_clock(FixedClock(pinned), || -> std::result::Result<Vec<u8>, JsError> {
+            let titled = set_metadata(&original, "Title", "Draft")?;
+            set_metadata(&titled, "Author", "Ana Souza")
+        })
+        .map_err(|_| "setMetadata failed")?;
+
+        let entries = crate::get_pdf_metadata(&updated)?;
+        assert_eq!(value(&entries, "Title"), Some("Draft"));
+        assert_eq!(value(&entries, "Author"), Some("Ana Souza"));
+        assert_eq!(value(&entries, "ModDate"), Some("D:20240506070809+00'00'"));
+        Ok(())
+    }
+
+    // `JsValue`s, and so the object `getMetadata` returns and the errors both
+    // functions throw, only exist inside a JavaScript engine: these run under
+    // `wasm-pack test --node -- --no-default-features --features wasm`.
+    #[cfg(target_arch = "wasm32")]
+    mod js {
+        use super::*;
+        use wasm_bindgen_test::wasm_bindgen_test;
+
+        #[wasm_bindgen_test]
+        fn get_metadata_returns_an_object_of_strings() {
+            let updated = set_metadata(&minimal_test_pdf_bytes().unwrap(), "Author", "Ana Souza").unwrap();
+            let entries = get_metadata(&updated).unwrap();
+            let property = |key: &str| Reflect::get(&entries, &key.into()).unwrap().as_string();
+            assert_eq!(property("Author").as_deref(), Some("Ana Souza"));
+            // Without an installed clock the date comes from JavaScript's `Date`.
+            assert!(property("ModDate").is_some_and(|date| date.as_str() > "D:2024"));
+            assert_eq!(property("Title"), None);
+        }
+
+        #[wasm_bindgen_test]
+        fn invalid_bytes_throw() {
+            assert!(get_metadata(b"not a pdf").is_err());
+            assert!(set_metadata(b"%PDF-1.7 truncated", "Title", "Draft").is_err());
+        }
+    }
+}
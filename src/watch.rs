@@ -0,0 +1,250 @@
+//! # Directory watcher
+//!
+//! [`MetadataWatcher`] monitors a directory and reports a [`MetadataEvent`] whenever
+//! a PDF appears in it or the Info entries of one it already knows change. Document
+//! management tools use this to keep an index or a view in sync without rescanning
+//! the whole directory.
+//!
+//! File system notifications come from the platform's native API (inotify, FSEvents,
+//! kqueue or ReadDirectoryChangesW, through the `notify` crate). A file is only read
+//! when its modification time or size (on Unix, also its inode or status-change
+//! time) changed, and a change that leaves the Info
+//! dictionary as it was (a new page, a touched timestamp) produces no event. Files
+//! that cannot be parsed, such as a PDF still being copied, are retried on their next
+//! notification, so a document is reported once it is complete.
+//!
+//! ```no_run
+//! use pdf_metadata::watch::{MetadataWatcher, WatchOptions};
+//!
+//! fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let watcher = MetadataWatcher::new("/srv/inbox", &WatchOptions::default())?;
+//!     for event in watcher.iter() {
+//!         match event.old {
+//!             None => println!("added: {}", event.path.display()),
+//!             Some(_) => println!("changed: {}", event.path.display()),
+//!         }
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use crate::batch::collect_pdfs;
+use crate::cache::{CachedMetadata, FileStamp, read_metadata};
+use crate::{MetadataValue, Result};
+use notify::event::{AccessKind, AccessMode};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// Settings for [`MetadataWatcher::new`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WatchOptions {
+    /// Whether to watch subdirectories, including those created later.
+    pub recursive: bool,
+    /// Whether the PDFs already in the directory are reported as added when the
+    /// watcher starts. When `false` they only serve as the baseline for later changes.
+    pub report_existing: bool,
+}
+
+impl Default for WatchOptions {
+    fn default() -> Self {
+        WatchOptions {
+            recursive: true,
+            report_existing: false,
+        }
+    }
+}
+
+/// A PDF that was added to a watched directory or whose metadata changed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetadataEvent {
+    /// The file, under the canonical form of the watched directory.
+    pub path: PathBuf,
+    /// The Info entries before the change; `None` for a file the watcher had not seen.
+    pub old: Option<Vec<(String, MetadataValue)>>,
+    /// The Info entries now, as returned by `get_metadata_values`.
+    pub new: Vec<(String, MetadataValue)>,
+}
+
+/// Watches a directory for PDF additions and metadata changes.
+///
+/// Events are processed on a background thread and queued until read; dropping the
+/// watcher stops it. Removed files are forgotten without an event, so a file that
+/// comes back is reported as added.
+#[derive(Debug)]
+pub struct MetadataWatcher {
+    events: Receiver<MetadataEvent>,
+    // Dropping the watcher closes the notification channel, which ends the thread.
+    _watcher: RecommendedWatcher,
+}
+
+impl MetadataWatcher {
+    /// Starts watching `dir`.
+    ///
+    /// The PDFs already in the directory are read before this returns, so every
+    /// file added or changed afterwards is reported.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(MetadataWatcher)`: The running watcher.
+    /// * `Err(Error)`: If `dir` cannot be read or the platform refuses to watch it.
+    pub fn new<P: AsRef<Path>>(dir: P, options: &WatchOptions) -> Result<Self> {
+        let dir = fs::canonicalize(dir)?;
+        let (notifications, raw_events) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(notifications)?;
+        let mode = if options.recursive { RecursiveMode::Recursive } else { RecursiveMode::NonRecursive };
+        // Watch before the initial scan so nothing written in between is missed.
+        watcher.watch(&dir, mode)?;
+
+        let (sender, events) = mpsc::channel();
+        let mut state = WatchState {
+            known: HashMap::new(),
+            recursive: options.recursive,
+            sender,
+        };
+        for path in collect_pdfs(&dir, options.recursive)? {
+            state.refresh(path, options.report_existing);
+        }
+        thread::spawn(move || state.run(raw_events));
+
+        Ok(MetadataWatcher {
+            events,
+            _watcher: watcher,
+        })
+    }
+
+    /// Blocks until the next event and returns it; the iterator only ends if the
+    /// background thread stops.
+    pub fn iter(&self) -> impl Iterator<Item = MetadataEvent> + '_ {
+        self.events.iter()
+    }
+
+    /// Returns the next event, waiting at most `timeout` for one.
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<MetadataEvent> {
+        self.events.recv_timeout(timeout).ok()
+    }
+
+    /// Returns the next queued event without waiting.
+    pub fn try_recv(&self) -> Option<MetadataEvent> {
+        self.events.try_recv().ok()
+    }
+}
+
+/// The metadata the background thread last saw for each PDF.
+struct WatchState {
+    known: HashMap<PathBuf, (FileStamp, CachedMetadata)>,
+    recursive: bool,
+    sender: Sender<MetadataEvent>,
+}
+
+impl WatchState {
+    fn run(mut self, raw_events: Receiver<notify::Result<Event>>) {
+        for event in raw_events {
+            // Errors report lost notifications or a removed watch root; the files
+            // involved are picked up again on their next change.
+            let Ok(event) = event else { continue };
+            if matches!(event.kind, EventKind::Access(kind) if kind != AccessKind::Close(AccessMode::Write)) {
+                continue;
+            }
+            for path in event.paths {
+                self.handle(path);
+            }
+        }
+    }
+
+    fn handle(&mut self, path: PathBuf) {
+        if path.is_dir() {
+            // A directory moved or copied in arrives as one event for its root.
+            if self.recursive
+                && let Ok(pdfs) = collect_pdfs(&path, true)
+            {
+                for pdf in pdfs {
+                    self.refresh(pdf, true);
+                }
+            }
+        } else if !path.exists() {
+            self.known.retain(|known, _| !known.starts_with(&path));
+        } else if is_pdf(&path) {
+            self.refresh(path, true);
+        }
+    }
+
+    /// Reads `path` if it changed on disk and sends an event if its metadata differs
+    /// from what was known, or if it is new and `report_new` is set.
+    fn refresh(&mut self, path: PathBuf, report_new: bool) {
+        let Ok(stat) = fs::metadata(&path) else { return };
+        let modified = stat.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+        let stamp = FileStamp::of(&stat, modified);
+        let previous = self.known.get(&path);
+        if previous.is_some_and(|(known, _)| *known == stamp) {
+            return;
+        }
+        let Ok(current) = read_metadata(&path, modified, stat.len()) else { return };
+
+        let old = previous.map(|(_, known)| known.values.clone());
+        let report = match &old {
+            Some(old) => *old != current.values,
+            None => report_new,
+        };
+        if report {
+            let event = MetadataEvent {
+                path: path.clone(),
+                old,
+                new: current.values.clone(),
+            };
+            let _ = self.sender.send(event);
+        }
+        self.known.insert(path, (stamp, current));
+    }
+}
+
+fn is_pdf(path: &Path) -> bool {
+    path.extension().and_then(|e| e.to_str()).is_some_and(|e| e.eq_ignore_ascii_case("pdf"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{create_minimal_test_pdf, setup_unique_test_dir};
+    use crate::update_metadata_in_place;
+
+    const TIMEOUT: Duration = Duration::from_secs(10);
+
+    #[test]
+    fn test_watcher_reports_added_and_changed_pdfs() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("watch");
+        let watched = test_dir.join("inbox");
+        fs::create_dir_all(&watched)?;
+        let source = test_dir.join("source.pdf");
+        create_minimal_test_pdf(&source)?;
+        update_metadata_in_place(&source, "Title", "Draft")?;
+        let title = |values: &[(String, MetadataValue)]| {
+            values.iter().find(|(key, _)| key == "Title").map(|(_, value)| value.to_string())
+        };
+
+        let watcher = MetadataWatcher::new(&watched, &WatchOptions::default())?;
+        let target = fs::canonicalize(&watched)?.join("doc.pdf");
+        fs::write(&target, fs::read(&source)?)?;
+        fs::write(watched.join("notes.txt"), "not a pdf")?;
+        let added = watcher.recv_timeout(TIMEOUT).ok_or("no event for the new file")?;
+        assert_eq!(added.path, target);
+        assert_eq!(added.old, None);
+        assert_eq!(title(&added.new).as_deref(), Some("Draft"));
+
+        update_metadata_in_place(&target, "Title", "Final")?;
+        let changed = watcher.recv_timeout(TIMEOUT).ok_or("no event for the update")?;
+        assert_eq!(changed.path, target);
+        assert_eq!(changed.old.as_deref().and_then(title).as_deref(), Some("Draft"));
+        assert_eq!(title(&changed.new).as_deref(), Some("Final"));
+
+        assert_eq!(watcher.recv_timeout(Duration::from_millis(200)), None);
+        drop(watcher);
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,1416 @@
+//! # XMP metadata
+//!
+//! Besides the Info dictionary, a PDF can carry an XMP packet: an RDF/XML stream
+//! referenced by the catalog's `/Metadata` entry. Most viewers and archival
+//! systems read the XMP values first, so Info and XMP can show different metadata
+//! for the same file. [`reconcile_metadata`] makes the two agree, and
+//! [`set_xmp_custom`] stores properties of your own namespace in the packet,
+//! [`set_xmp_title_lang`] and [`set_xmp_description_lang`] keep one title and
+//! description per language, and [`ensure_xmp_identifiers`] gives the packet the
+//! `xmpMM` identifiers asset managers track.
+//!
+//! Only the shapes XMP uses for document properties are read: simple values,
+//! written as elements or as attributes of a top-level `rdf:Description`, and
+//! `rdf:Alt`/`rdf:Seq`/`rdf:Bag` arrays of simple values. Structures such as
+//! `xmpMM:History` are kept when a packet is rewritten, but not interpreted; with
+//! [`SetOptions::xmp_history`](crate::SetOptions::xmp_history), writes append their
+//! own events to it.
+//!
+//! | Info key       | XMP property      |
+//! |----------------|-------------------|
+//! | `Title`        | `dc:title`        |
+//! | `Author`       | `dc:creator`      |
+//! | `Subject`      | `dc:description`  |
+//! | `Keywords`     | `pdf:Keywords`    |
+//! | `Creator`      | `xmp:CreatorTool` |
+//! | `Producer`     | `pdf:Producer`    |
+//! | `CreationDate` | `xmp:CreateDate`  |
+//! | `ModDate`      | `xmp:ModifyDate`  |
+
+use crate::{Error, Result, clock, incremental, read_info_entries};
+#[cfg(feature = "std-fs")]
+use crate::{
+    SetOptions, apply_entries_with, format_pdf_date, load_document, options, parse_pdf_date, write_file,
+};
+use chrono::{DateTime, FixedOffset, SecondsFormat};
+#[cfg(feature = "std-fs")]
+use chrono::{NaiveDate, NaiveDateTime};
+use lopdf::{Dictionary, Document, Object, Stream};
+use quick_xml::escape::escape;
+#[cfg(feature = "std-fs")]
+use quick_xml::events::attributes::Attribute;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::name::{QName, ResolveResult};
+use quick_xml::{NsReader, Writer};
+use sha2::{Digest, Sha256};
+#[cfg(feature = "std-fs")]
+use std::path::Path;
+
+pub(crate) const RDF_NS: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
+#[cfg(feature = "std-fs")]
+pub(crate) const DC_NS: &str = "http://purl.org/dc/elements/1.1/";
+#[cfg(feature = "std-fs")]
+pub(crate) const XMP_NS: &str = "http://ns.adobe.com/xap/1.0/";
+#[cfg(feature = "std-fs")]
+pub(crate) const PDF_NS: &str = "http://ns.adobe.com/pdf/1.3/";
+#[cfg(feature = "std-fs")]
+pub(crate) const PDFAID_NS: &str = "http://www.aiim.org/pdfa/ns/id/";
+pub(crate) const XMP_MM_NS: &str = "http://ns.adobe.com/xap/1.0/mm/";
+const ST_EVT_NS: &str = "http://ns.adobe.com/xap/1.0/sType/ResourceEvent#";
+
+/// The `stEvt:softwareAgent` of the history events this crate records.
+const SOFTWARE_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), " ", env!("CARGO_PKG_VERSION"));
+
+/// The Info keys with an XMP equivalent, as `(key, namespace, prefix, property)`.
+#[cfg(feature = "std-fs")]
+pub(crate) const INFO_PROPERTIES: [(&str, &str, &str, &str); 8] = [
+    ("Title", DC_NS, "dc", "title"),
+    ("Author", DC_NS, "dc", "creator"),
+    ("Subject", DC_NS, "dc", "description"),
+    ("Keywords", PDF_NS, "pdf", "Keywords"),
+    ("Creator", XMP_NS, "xmp", "CreatorTool"),
+    ("Producer", PDF_NS, "pdf", "Producer"),
+    ("CreationDate", XMP_NS, "xmp", "CreateDate"),
+    ("ModDate", XMP_NS, "xmp", "ModifyDate"),
+];
+
+/// An empty packet, used when a document has none.
+const EMPTY_PACKET: &str = "<?xpacket begin=\"\u{FEFF}\" id=\"W5M0MpCehiHzreSzNTczkc9d\"?>\n\
+<x:xmpmeta xmlns:x=\"adobe:ns:meta/\">\n\
+<rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">\n\
+</rdf:RDF>\n\
+</x:xmpmeta>\n\
+<?xpacket end=\"w\"?>";
+
+/// The value of an XMP property.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum XmpValue {
+    Text(String),
+    /// Language alternatives as `(xml:lang, value)`, in document order.
+    #[cfg_attr(not(feature = "std-fs"), allow(dead_code))]
+    Alt(Vec<(Option<String>, String)>),
+    Seq(Vec<String>),
+    #[cfg_attr(not(feature = "std-fs"), allow(dead_code))]
+    Bag(Vec<String>),
+}
+
+impl XmpValue {
+    /// The value as a single string: the `x-default` (or first) alternative, the only
+    /// item of a one-item array, or the items joined with `", "`.
+    #[cfg(feature = "std-fs")]
+    pub(crate) fn to_text(&self) -> String {
+        match self {
+            XmpValue::Text(text) => text.clone(),
+            XmpValue::Alt(alternatives) => alternatives
+                .iter()
+                .find(|(lang, _)| lang.as_deref() == Some("x-default"))
+                .or_else(|| alternatives.first())
+                .map(|(_, value)| value.clone())
+                .unwrap_or_default(),
+            XmpValue::Seq(items) | XmpValue::Bag(items) => items.join(", "),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct XmpProperty {
+    pub(crate) namespace: String,
+    /// The prefix the packet uses for `namespace`, e.g. `dc`.
+    pub(crate) prefix: String,
+    pub(crate) name: String,
+    pub(crate) value: XmpValue,
+}
+
+/// The document properties of an XMP packet.
+#[cfg(feature = "std-fs")]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct XmpPacket {
+    pub(crate) properties: Vec<XmpProperty>,
+}
+
+#[cfg(feature = "std-fs")]
+impl XmpPacket {
+    /// Returns the property `name` of `namespace`.
+    #[cfg(feature = "std-fs")]
+    pub(crate) fn get(&self, namespace: &str, name: &str) -> Option<&XmpValue> {
+        self.properties.iter().find(|p| p.namespace == namespace && p.name == name).map(|p| &p.value)
+    }
+
+    /// Parses an XMP packet, failing with `Error::Xml` if it is not well-formed.
+    #[cfg(feature = "std-fs")]
+    pub(crate) fn parse(xml: &str) -> Result<XmpPacket> {
+        let xml_error = |e: quick_xml::Error| Error::Xml(e.to_string());
+        let mut reader = NsReader::from_str(xml.trim_start_matches('\u{FEFF}'));
+        let mut packet = XmpPacket::default();
+        let mut stack: Vec<Frame> = Vec::new();
+
+        loop {
+            let (namespace, event) = reader.read_resolved_event().map_err(xml_error)?;
+            let namespace = match namespace {
+                ResolveResult::Bound(ns) => String::from_utf8_lossy(ns.as_ref()).into_owned(),
+                _ => String::new(),
+            };
+            match event {
+                Event::Start(start) => {
+                    let frame = open_frame(&reader, &stack, namespace, &start, &mut packet)?;
+                    stack.push(frame);
+                }
+                Event::Empty(start) => {
+                    let frame = open_frame(&reader, &stack, namespace, &start, &mut packet)?;
+                    close_frame(frame, &mut stack, &mut packet);
+                }
+                Event::End(_) => {
+                    if let Some(frame) = stack.pop() {
+                        close_frame(frame, &mut stack, &mut packet);
+                    }
+                }
+                Event::Text(text) => {
+                    let text = text.unescape().map_err(xml_error)?;
+                    match stack.last_mut() {
+                        Some(Frame::Property { text: buffer, .. }) | Some(Frame::Item { text: buffer, .. }) => {
+                            buffer.push_str(&text)
+                        }
+                        _ => {}
+                    }
+                }
+                Event::CData(data) => {
+                    if let Some(Frame::Property { text, .. } | Frame::Item { text, .. }) = stack.last_mut() {
+                        text.push_str(&String::from_utf8_lossy(&data));
+                    }
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+        Ok(packet)
+    }
+}
+
+/// An open element while parsing.
+#[cfg(feature = "std-fs")]
+enum Frame {
+    Rdf,
+    Description,
+    Property {
+        namespace: String,
+        prefix: String,
+        name: String,
+        text: String,
+        array: Option<ArrayKind>,
+        items: Vec<(Option<String>, String)>,
+        /// Set when the value contains elements other than an array of simple items.
+        structured: bool,
+    },
+    Array(ArrayKind),
+    Item { lang: Option<String>, text: String },
+    /// Anything else, including the inside of structures.
+    Other,
+}
+
+#[cfg(feature = "std-fs")]
+#[derive(Debug, Clone, Copy)]
+enum ArrayKind {
+    Alt,
+    Seq,
+    Bag,
+}
+
+fn qname_prefix(name: QName) -> String {
+    name.prefix().map(|prefix| String::from_utf8_lossy(prefix.as_ref()).into_owned()).unwrap_or_default()
+}
+
+#[cfg(feature = "std-fs")]
+fn is_rdf_attribute(reader: &NsReader<&[u8]>, attr: &Attribute, names: &[&[u8]]) -> bool {
+    let (namespace, name) = reader.resolve_attribute(attr.key);
+    matches!(namespace, ResolveResult::Bound(ns) if ns.as_ref() == RDF_NS.as_bytes()) && names.contains(&name.as_ref())
+}
+
+#[cfg(feature = "std-fs")]
+fn open_frame(
+    reader: &NsReader<&[u8]>,
+    stack: &[Frame],
+    namespace: String,
+    start: &BytesStart,
+    packet: &mut XmpPacket,
+) -> Result<Frame> {
+    let local = String::from_utf8_lossy(start.local_name().as_ref()).into_owned();
+    let is_rdf = |name: &str| namespace == RDF_NS && local == name;
+
+    Ok(match stack.last() {
+        _ if is_rdf("RDF") => Frame::Rdf,
+        Some(Frame::Rdf) if is_rdf("Description") => {
+            for attr in start.attributes() {
+                let attr = attr.map_err(|e| Error::Xml(e.to_string()))?;
+                let (ns, name) = reader.resolve_attribute(attr.key);
+                let ResolveResult::Bound(ns) = ns else { continue };
+                if ns.as_ref() == RDF_NS.as_bytes() || attr.key.as_namespace_binding().is_some() {
+                    continue;
+                }
+                packet.properties.push(XmpProperty {
+                    namespace: String::from_utf8_lossy(ns.as_ref()).into_owned(),
+                    prefix: qname_prefix(attr.key),
+                    name: String::from_utf8_lossy(name.as_ref()).into_owned(),
+                    value: XmpValue::Text(attr.unescape_value().map_err(|e| Error::Xml(e.to_string()))?.into_owned()),
+                });
+            }
+            Frame::Description
+        }
+        Some(Frame::Description) => {
+            let is_struct =
+                start.attributes().flatten().any(|attr| is_rdf_attribute(reader, &attr, &[b"parseType", b"resource"]));
+            if is_struct || namespace.is_empty() {
+                Frame::Other
+            } else {
+                Frame::Property {
+                    namespace,
+                    prefix: qname_prefix(start.name()),
+                    name: local,
+                    text: String::new(),
+                    array: None,
+                    items: Vec::new(),
+                    structured: false,
+                }
+            }
+        }
+        Some(Frame::Property { .. }) => match local.as_str() {
+            "Alt" if namespace == RDF_NS => Frame::Array(ArrayKind::Alt),
+            "Seq" if namespace == RDF_NS => Frame::Array(ArrayKind::Seq),
+            "Bag" if namespace == RDF_NS => Frame::Array(ArrayKind::Bag),
+            _ => Frame::Other,
+        },
+        Some(Frame::Array(_)) if is_rdf("li") => {
+            let lang = start
+                .attributes()
+                .flatten()
+                .find(|attr| attr.key.as_ref() == b"xml:lang")
+                .map(|attr| attr.unescape_value().map(|v| v.into_owned()))
+                .transpose()
+                .map_err(|e| Error::Xml(e.to_string()))?;
+            Frame::Item { lang, text: String::new() }
+        }
+        _ => Frame::Other,
+    })
+}
+
+#[cfg(feature = "std-fs")]
+fn close_frame(frame: Frame, stack: &mut [Frame], packet: &mut XmpPacket) {
+    match frame {
+        Frame::Property { structured: true, .. } => {}
+        Frame::Property { namespace, prefix, name, text, array, items, .. } => {
+            let value = match array {
+                None => XmpValue::Text(text.trim().to_string()),
+                Some(ArrayKind::Alt) => XmpValue::Alt(items),
+                Some(ArrayKind::Seq) => XmpValue::Seq(items.into_iter().map(|(_, item)| item).collect()),
+                Some(ArrayKind::Bag) => XmpValue::Bag(items.into_iter().map(|(_, item)| item).collect()),
+            };
+            packet.properties.push(XmpProperty { namespace, prefix, name, value });
+        }
+        Frame::Array(kind) => {
+            if let Some(Frame::Property { array, .. }) = stack.last_mut() {
+                *array = Some(kind);
+            }
+        }
+        Frame::Item { lang, text } => {
+            if let [.., Frame::Property { items, .. }, Frame::Array(_)] = stack {
+                items.push((lang, text.trim().to_string()));
+            }
+        }
+        Frame::Other => {
+            if let Some(Frame::Property { structured, .. }) =
+                stack.iter_mut().rev().find(|frame| matches!(frame, Frame::Property { .. }))
+            {
+                *structured = true;
+            }
+        }
+        Frame::Rdf | Frame::Description => {}
+    }
+}
+
+/// Returns the text of the document's XMP packet, or `None` if it has none.
+pub(crate) fn read_packet_xml(doc: &Document) -> Result<Option<String>> {
+    let Ok(catalog) = doc.catalog() else { return Ok(None) };
+    let stream = match catalog.get(b"Metadata") {
+        Ok(Object::Reference(id)) => doc.get_object(*id).and_then(Object::as_stream),
+        Ok(object) => object.as_stream(),
+        Err(_) => return Ok(None),
+    };
+    let Ok(stream) = stream else { return Ok(None) };
+    let content = stream.get_plain_content()?;
+    Ok(Some(String::from_utf8_lossy(&content).into_owned()))
+}
+
+/// The `x:xmptk` attribute of the packet's `x:xmpmeta` element, naming the toolkit
+/// that wrote it, e.g. `Adobe XMP Core 5.6-c015`.
+#[cfg(feature = "std-fs")]
+pub(crate) fn xmp_toolkit(xml: &str) -> Option<String> {
+    let mut reader = NsReader::from_str(xml.trim_start_matches('\u{FEFF}'));
+    loop {
+        match reader.read_resolved_event().ok()?.1 {
+            // Packets written before XMP 1.0 use `x:xapmeta`.
+            Event::Start(start) | Event::Empty(start) if matches!(start.local_name().as_ref(), b"xmpmeta" | b"xapmeta") => {
+                return start.attributes().flatten().find_map(|attr| {
+                    let (namespace, name) = reader.resolve_attribute(attr.key);
+                    let in_meta_ns = matches!(namespace, ResolveResult::Bound(ns) if ns.as_ref() == b"adobe:ns:meta/");
+                    (in_meta_ns && name.as_ref() == b"xmptk").then(|| attr.unescape_value().ok().map(|v| v.into_owned()))?
+                });
+            }
+            // The toolkit is only recorded on the root element.
+            Event::Start(_) | Event::Empty(_) | Event::Eof => return None,
+            _ => {}
+        }
+    }
+}
+
+/// Reads and parses the document's XMP packet, or returns `None` if it has none.
+#[cfg(feature = "std-fs")]
+pub(crate) fn read_packet(doc: &Document) -> Result<Option<XmpPacket>> {
+    read_packet_xml(doc)?.as_deref().map(XmpPacket::parse).transpose()
+}
+
+/// Stores `xml` as the document's XMP packet, uncompressed as PDF/A expects.
+pub(crate) fn write_packet(doc: &mut Document, xml: String) -> Result<()> {
+    let mut dict = Dictionary::new();
+    dict.set("Type", Object::Name(b"Metadata".to_vec()));
+    dict.set("Subtype", Object::Name(b"XML".to_vec()));
+    let stream = Object::Stream(Stream::new(dict, xml.into_bytes()));
+    match doc.catalog()?.get(b"Metadata") {
+        Ok(Object::Reference(id)) => {
+            let id = *id;
+            doc.objects.insert(id, stream);
+            incremental::touch(id);
+        }
+        _ => {
+            let id = doc.add_object(stream);
+            incremental::catalog_mut(doc)?.set("Metadata", Object::Reference(id));
+        }
+    }
+    Ok(())
+}
+
+/// Replaces properties in the packet `xml`, keeping everything else as written.
+///
+/// Existing values of the given properties, as elements or as attributes of a
+/// top-level `rdf:Description`, are removed, and the new values are written to an
+/// `rdf:Description` added at the end of `rdf:RDF`. A packet of `None` starts from
+/// an empty one.
+pub(crate) fn rewrite_packet(xml: Option<&str>, properties: &[XmpProperty]) -> Result<String> {
+    edit_packet(xml, &[], properties)
+}
+
+/// Removes the properties `names` of `namespace` from the packet `xml`, keeping
+/// everything else as written.
+#[cfg(feature = "std-fs")]
+pub(crate) fn remove_properties(xml: &str, namespace: &str, names: &[&str]) -> Result<String> {
+    let removed: Vec<(&str, &str)> = names.iter().map(|name| (namespace, *name)).collect();
+    edit_packet(Some(xml), &removed, &[])
+}
+
+/// Removes the `(namespace, name)` properties in `removed` and replaces `properties`,
+/// as [`rewrite_packet`] describes.
+fn edit_packet(xml: Option<&str>, removed: &[(&str, &str)], properties: &[XmpProperty]) -> Result<String> {
+    let xml_error = |e: quick_xml::Error| Error::Xml(e.to_string());
+    let write_error = |e: std::io::Error| Error::Xml(e.to_string());
+    let replaced = |namespace: &[u8], name: &[u8]| {
+        removed.iter().any(|(ns, n)| ns.as_bytes() == namespace && n.as_bytes() == name)
+            || properties.iter().any(|p| p.namespace.as_bytes() == namespace && p.name.as_bytes() == name)
+    };
+    let mut reader = NsReader::from_str(xml.unwrap_or(EMPTY_PACKET));
+    let mut writer = Writer::new(Vec::new());
+    // A top-level rdf:Description is buffered, and dropped if nothing is left in it.
+    let mut description: Option<(Writer<Vec<u8>>, bool)> = None;
+    // Whitespace before a property element, dropped with the element.
+    let mut indent: Option<Event> = None;
+    let mut depth = 0usize;
+    let mut rdf_depth = None;
+    let mut description_depth = None;
+    let mut skip_from = None;
+    let mut rdf_prefix = String::from("rdf");
+    let mut found_rdf = false;
+
+    loop {
+        let (namespace, event) = reader.read_resolved_event().map_err(xml_error)?;
+        let is_rdf = matches!(namespace, ResolveResult::Bound(ns) if ns.as_ref() == RDF_NS.as_bytes());
+        let element_namespace = match namespace {
+            ResolveResult::Bound(ns) => ns.as_ref().to_vec(),
+            _ => Vec::new(),
+        };
+        if skip_from.is_some() {
+            match event {
+                Event::Start(_) => depth += 1,
+                Event::End(_) => {
+                    if skip_from == Some(depth) {
+                        skip_from = None;
+                    }
+                    depth -= 1;
+                }
+                _ => {}
+            }
+            continue;
+        }
+        let in_description = description_depth == Some(depth);
+        if in_description
+            && let Event::Text(text) = &event
+            && text.iter().all(u8::is_ascii_whitespace)
+        {
+            indent = Some(event.into_owned());
+            continue;
+        }
+        let out = match description.as_mut() {
+            Some((buffer, _)) => buffer,
+            None => &mut writer,
+        };
+        match event {
+            Event::Start(ref start) | Event::Empty(ref start) => {
+                let local = start.local_name();
+                let opens = matches!(event, Event::Start(_));
+                if is_rdf && local.as_ref() == b"RDF" && opens {
+                    rdf_prefix = qname_prefix(start.name());
+                    rdf_depth = Some(depth + 1);
+                    found_rdf = true;
+                }
+                if is_rdf && local.as_ref() == b"Description" && rdf_depth == Some(depth) {
+                    let mut kept = BytesStart::new(String::from_utf8_lossy(start.name().as_ref()).into_owned());
+                    let mut has_properties = false;
+                    for attr in start.attributes() {
+                        let attr = attr.map_err(|e| Error::Xml(e.to_string()))?;
+                        let (ns, name) = reader.resolve_attribute(attr.key);
+                        match ns {
+                            ResolveResult::Bound(ns) if replaced(ns.as_ref(), name.as_ref()) => continue,
+                            ResolveResult::Bound(ns) if ns.as_ref() != RDF_NS.as_bytes() => has_properties = true,
+                            _ => {}
+                        }
+                        kept.push_attribute(attr);
+                    }
+                    let mut buffer = Writer::new(Vec::new());
+                    if let Some(indent) = indent.take() {
+                        buffer.write_event(indent).map_err(write_error)?;
+                    }
+                    if opens {
+                        buffer.write_event(Event::Start(kept)).map_err(write_error)?;
+                        description = Some((buffer, has_properties));
+                        description_depth = Some(depth + 1);
+                    } else if has_properties {
+                        buffer.write_event(Event::Empty(kept)).map_err(write_error)?;
+                        out.get_mut().extend_from_slice(&buffer.into_inner());
+                    }
+                } else if in_description && replaced(&element_namespace, local.as_ref()) {
+                    indent = None;
+                    if opens {
+                        skip_from = Some(depth + 1);
+                    }
+                } else {
+                    if let Some(indent) = indent.take() {
+                        out.write_event(indent).map_err(write_error)?;
+                    }
+                    out.write_event(event.borrow()).map_err(write_error)?;
+                    if in_description && let Some((_, has_properties)) = description.as_mut() {
+                        *has_properties = true;
+                    }
+                }
+                if opens {
+                    depth += 1;
+                }
+            }
+            Event::End(ref end) => {
+                if let Some(indent) = indent.take() {
+                    out.write_event(indent).map_err(write_error)?;
+                }
+                if rdf_depth == Some(depth) {
+                    write_description(out, &rdf_prefix, properties)?;
+                    rdf_depth = None;
+                }
+                out.write_event(Event::End(end.borrow())).map_err(write_error)?;
+                if description_depth == Some(depth) {
+                    description_depth = None;
+                    if let Some((buffer, true)) = description.take() {
+                        writer.get_mut().extend_from_slice(&buffer.into_inner());
+                    }
+                }
+                depth -= 1;
+            }
+            Event::Eof => break,
+            other => {
+                if let Some(indent) = indent.take() {
+                    out.write_event(indent).map_err(write_error)?;
+                }
+                out.write_event(other).map_err(write_error)?;
+            }
+        }
+    }
+    if !found_rdf {
+        return Err(Error::Xml("XMP packet has no rdf:RDF element".to_string()));
+    }
+    String::from_utf8(writer.into_inner()).map_err(|e| Error::Xml(e.to_string()))
+}
+
+/// Writes `properties` as a new `rdf:Description`, declaring their namespaces on it.
+fn write_description(writer: &mut Writer<Vec<u8>>, rdf: &str, properties: &[XmpProperty]) -> Result<()> {
+    if properties.is_empty() {
+        return Ok(());
+    }
+    let mut description = format!("<{rdf}:Description {rdf}:about=\"\"");
+    let mut declared: Vec<&str> = Vec::new();
+    for property in properties {
+        if !declared.contains(&property.prefix.as_str()) {
+            description.push_str(&format!(" xmlns:{}=\"{}\"", property.prefix, escape(&property.namespace)));
+            declared.push(&property.prefix);
+        }
+    }
+    description.push('>');
+    for property in properties {
+        let name = format!("{}:{}", property.prefix, property.name);
+        description.push_str(&format!("\n  <{}>", name));
+        let items = |kind: &str, items: &[(Option<String>, String)], description: &mut String| {
+            description.push_str(&format!("<{rdf}:{kind}>"));
+            for (lang, item) in items {
+                match lang {
+                    Some(lang) => description.push_str(&format!("<{rdf}:li xml:lang=\"{}\">", escape(lang))),
+                    None => description.push_str(&format!("<{rdf}:li>")),
+                }
+                description.push_str(&format!("{}</{rdf}:li>", escape(item)));
+            }
+            description.push_str(&format!("</{rdf}:{kind}>"));
+        };
+        match &property.value {
+            XmpValue::Text(text) => description.push_str(&escape(text)),
+            XmpValue::Alt(alternatives) => items("Alt", alternatives, &mut description),
+            XmpValue::Seq(values) | XmpValue::Bag(values) => {
+                let kind = if matches!(property.value, XmpValue::Seq(_)) { "Seq" } else { "Bag" };
+                let values: Vec<_> = values.iter().map(|v| (None, v.clone())).collect();
+                items(kind, &values, &mut description);
+            }
+        }
+        description.push_str(&format!("</{}>", name));
+    }
+    description.push_str(&format!("\n</{rdf}:Description>\n"));
+    writer.get_mut().extend_from_slice(description.as_bytes());
+    Ok(())
+}
+
+/// Appends a `metadata-edited` event at `when` to the document's `xmpMM:History`,
+/// creating the history (and the packet) if needed, and sets `xmpMM:InstanceID` to the
+/// new instance the event names.
+pub(crate) fn append_history_event(doc: &mut Document, when: &DateTime<FixedOffset>) -> Result<()> {
+    let instance_id = format!("xmp.iid:{}", new_uuid(doc, "xmp.iid")?);
+    let xml = rewrite_packet(read_packet_xml(doc)?.as_deref(), &[mm_property("InstanceID", &instance_id)])?;
+    let event = |rdf: &str| {
+        let field = |name: &str, value: &str| format!("<stEvt:{name}>{}</stEvt:{name}>", escape(value));
+        format!(
+            "<{rdf}:li {rdf}:parseType=\"Resource\" xmlns:stEvt=\"{ST_EVT_NS}\">{}{}{}{}</{rdf}:li>",
+            field("action", "metadata-edited"),
+            field("instanceID", &instance_id),
+            field("when", &when.to_rfc3339_opts(SecondsFormat::Secs, true)),
+            field("softwareAgent", SOFTWARE_AGENT),
+        )
+    };
+    let xml = match insert_history_item(&xml, &event)? {
+        Some(xml) => xml,
+        None => {
+            let history = XmpProperty { value: XmpValue::Seq(Vec::new()), ..mm_property("History", "") };
+            let xml = rewrite_packet(Some(&xml), &[history])?;
+            insert_history_item(&xml, &event)?.ok_or_else(|| Error::Xml("xmpMM:History was not written".to_string()))?
+        }
+    };
+    write_packet(doc, xml)
+}
+
+/// Inserts the item made by `item`, given the packet's `rdf` prefix, at the end of
+/// the `rdf:Seq` of `xmpMM:History`. Returns `None` if the packet has no such history.
+fn insert_history_item(xml: &str, item: &dyn Fn(&str) -> String) -> Result<Option<String>> {
+    let write_error = |e: std::io::Error| Error::Xml(e.to_string());
+    let mut reader = NsReader::from_str(xml);
+    let mut writer = Writer::new(Vec::new());
+    // The namespace and local name of each open element.
+    let mut open: Vec<(Vec<u8>, Vec<u8>)> = Vec::new();
+    let mut inserted = false;
+    let in_history = |open: &[(Vec<u8>, Vec<u8>)]| {
+        matches!(open, [.., (ns, name)] if ns == XMP_MM_NS.as_bytes() && name == b"History")
+    };
+
+    loop {
+        let (namespace, event) = reader.read_resolved_event().map_err(|e| Error::Xml(e.to_string()))?;
+        let namespace = match namespace {
+            ResolveResult::Bound(ns) => ns.as_ref().to_vec(),
+            _ => Vec::new(),
+        };
+        match event {
+            Event::Start(ref start) => open.push((namespace, start.local_name().as_ref().to_vec())),
+            Event::Empty(ref start)
+                if !inserted
+                    && namespace == RDF_NS.as_bytes()
+                    && start.local_name().as_ref() == b"Seq"
+                    && in_history(&open) =>
+            {
+                let rdf = qname_prefix(start.name());
+                writer.write_event(Event::Start(start.borrow())).map_err(write_error)?;
+                writer.get_mut().extend_from_slice(item(&rdf).as_bytes());
+                writer.write_event(Event::End(start.to_end())).map_err(write_error)?;
+                inserted = true;
+                continue;
+            }
+            Event::End(ref end) => {
+                let closed = open.pop();
+                if !inserted
+                    && matches!(&closed, Some((ns, name)) if ns == RDF_NS.as_bytes() && name == b"Seq")
+                    && in_history(&open)
+                {
+                    writer.get_mut().extend_from_slice(item(&qname_prefix(end.name())).as_bytes());
+                    inserted = true;
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+        writer.write_event(event).map_err(write_error)?;
+    }
+    if !inserted {
+        return Ok(None);
+    }
+    String::from_utf8(writer.into_inner()).map(Some).map_err(|e| Error::Xml(e.to_string()))
+}
+
+/// The `xmpMM` property `name` with the text `value`.
+fn mm_property(name: &str, value: &str) -> XmpProperty {
+    XmpProperty {
+        namespace: XMP_MM_NS.to_string(),
+        prefix: "xmpMM".to_string(),
+        name: name.to_string(),
+        value: XmpValue::Text(value.to_string()),
+    }
+}
+
+/// A new RFC 4122 UUID for `doc`, in lowercase hyphenated form, for the identifier
+/// named by `prefix`.
+///
+/// It is random (version 4), except when `SOURCE_DATE_EPOCH` is set: then it is a
+/// digest of the epoch, `prefix` and the document's Info entries and XMP packet
+/// (version 8), so a reproducible build writes the same identifiers every time.
+fn new_uuid(doc: &Document, prefix: &str) -> Result<String> {
+    let mut bytes = [0u8; 16];
+    match clock::source_date_epoch() {
+        Some(epoch) => {
+            let mut hasher = Sha256::new();
+            hasher.update(epoch.timestamp().to_le_bytes());
+            hasher.update(prefix);
+            for (key, value) in read_info_entries(doc) {
+                hasher.update([0]);
+                hasher.update(key);
+                hasher.update([0]);
+                hasher.update(value);
+            }
+            hasher.update([1]);
+            hasher.update(read_packet_xml(doc)?.unwrap_or_default());
+            bytes.copy_from_slice(&hasher.finalize()[..16]);
+            bytes[6] = (bytes[6] & 0x0F) | 0x80;
+        }
+        None => {
+            getrandom::fill(&mut bytes)
+                .map_err(|error| Error::Io(std::io::Error::other(format!("no random source: {}", error))))?;
+            bytes[6] = (bytes[6] & 0x0F) | 0x40;
+        }
+    }
+    bytes[8] = (bytes[8] & 0x3F) | 0x80;
+    let hex: String = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+    Ok(format!("{}-{}-{}-{}-{}", &hex[..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..]))
+}
+
+/// Whether an Info value and its XMP equivalent agree, as PDF/A requires.
+///
+/// Values are compared after trimming; dates as instants. `Author` must equal the
+/// only item of `dc:creator`.
+#[cfg(feature = "std-fs")]
+pub(crate) fn info_matches(key: &str, info: &str, xmp: &XmpValue) -> bool {
+    match xmp {
+        XmpValue::Seq(items) if key == "Author" => items.len() == 1 && items[0].trim() == info.trim(),
+        value if key.ends_with("Date") => {
+            matches!((parse_pdf_date(info), parse_xmp_date(&value.to_text())), (Ok(a), Some(b)) if a == b)
+        }
+        value => value.to_text().trim() == info.trim(),
+    }
+}
+
+/// Parses an XMP date (ISO 8601: `YYYY`, `YYYY-MM-DD`, `YYYY-MM-DDThh:mm[:ss[.s]]TZD`).
+/// A missing offset is treated as UTC, as [`parse_pdf_date`] does.
+#[cfg(feature = "std-fs")]
+pub(crate) fn parse_xmp_date(value: &str) -> Option<DateTime<FixedOffset>> {
+    let value = value.trim();
+    let with_offset = match value.strip_suffix('Z') {
+        Some(rest) => format!("{}+00:00", rest),
+        None => value.to_string(),
+    };
+    for format in ["%Y-%m-%dT%H:%M%:z", "%Y-%m-%dT%H:%M:%S%.f%:z"] {
+        if let Ok(date) = DateTime::parse_from_str(&with_offset, format) {
+            return Some(date);
+        }
+    }
+    let utc = |naive: NaiveDateTime| Some(naive.and_utc().fixed_offset());
+    for format in ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%dT%H:%M"] {
+        if let Ok(naive) = NaiveDateTime::parse_from_str(value, format) {
+            return utc(naive);
+        }
+    }
+    let date = match value.len() {
+        4 => NaiveDate::from_ymd_opt(value.parse().ok()?, 1, 1)?,
+        7 => NaiveDate::parse_from_str(&format!("{}-01", value), "%Y-%m-%d").ok()?,
+        _ => NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()?,
+    };
+    utc(date.and_hms_opt(0, 0, 0)?)
+}
+
+/// The side whose values win in [`reconcile_metadata`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum InfoOrXmp {
+    Info,
+    Xmp,
+}
+
+impl std::str::FromStr for InfoOrXmp {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "info" => Ok(InfoOrXmp::Info),
+            "xmp" => Ok(InfoOrXmp::Xmp),
+            other => Err(Error::UnsupportedFormat(format!("metadata source '{}'", other))),
+        }
+    }
+}
+
+/// Makes the Info dictionary and the XMP packet agree, writing the result to `output_path`.
+///
+/// For each Info key with an XMP equivalent (see the table above), a value present
+/// on one side only is copied to the other, and when both sides have different
+/// values the one from `source` wins. Other XMP properties and Info entries are left
+/// alone, and `ModDate` is not refreshed. A packet is created if the document has
+/// none and there is something to put in it. `output_path` may be the input file.
+///
+/// # Returns
+///
+/// * `Ok(Vec<String>)`: The Info keys whose values were copied, in either direction.
+/// * `Err(Error)`: If the file cannot be read or written, or its XMP packet is not
+///   well-formed XML.
+///
+/// # Example
+///
+/// ```no_run
+/// use pdf_metadata::xmp::{InfoOrXmp, reconcile_metadata};
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let fixed = reconcile_metadata("archive/contract.pdf", "archive/contract.pdf", InfoOrXmp::Info)?;
+///     println!("Copied {:?} to XMP", fixed);
+///     Ok(())
+/// }
+/// ```
+#[cfg(feature = "std-fs")]
+pub fn reconcile_metadata<P: AsRef<Path>, Q: AsRef<Path>>(file_path: P, output_path: Q, source: InfoOrXmp) -> Result<Vec<String>> {
+    let options = options::current();
+    write_file(file_path.as_ref(), Some(output_path.as_ref()), &options, |doc| {
+        let info = read_info_entries(doc);
+        let xml = read_packet_xml(doc)?;
+        let packet = xml.as_deref().map(XmpPacket::parse).transpose()?.unwrap_or_default();
+
+        let mut changed = Vec::new();
+        let mut info_updates = Vec::new();
+        let mut xmp_updates = Vec::new();
+        for (key, namespace, prefix, name) in INFO_PROPERTIES {
+            let info_value = info.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str());
+            let xmp_value = packet.get(namespace, name);
+            let to_xmp = match (info_value, xmp_value) {
+                (Some(info), Some(xmp)) if info_matches(key, info, xmp) => continue,
+                (Some(_), Some(_)) => source == InfoOrXmp::Info,
+                (Some(_), None) => true,
+                (None, Some(_)) => false,
+                (None, None) => continue,
+            };
+            if to_xmp {
+                let Some(value) = info_value.and_then(|info| info_to_xmp(key, info, xmp_value)) else { continue };
+                xmp_updates.push(XmpProperty {
+                    namespace: namespace.to_string(),
+                    prefix: prefix.to_string(),
+                    name: name.to_string(),
+                    value,
+                });
+            } else {
+                let Some(value) = xmp_value.and_then(|xmp| xmp_to_info(key, xmp)) else { continue };
+                info_updates.push((key, value));
+            }
+            changed.push(key.to_string());
+        }
+
+        if !info_updates.is_empty() {
+            apply_entries_with(doc, &info_updates, &SetOptions { update_mod_date: false, ..options.clone() })?;
+        }
+        if !xmp_updates.is_empty() {
+            let xml = rewrite_packet(xml.as_deref(), &xmp_updates)?;
+            write_packet(doc, xml)?;
+        }
+        Ok(changed)
+    })
+}
+
+/// The XMP properties for the Info `entries` that have an XMP equivalent, keeping the
+/// other languages of existing alternatives in `packet`. Unparsable dates are skipped.
+#[cfg(feature = "std-fs")]
+pub(crate) fn info_properties(entries: &[(String, String)], packet: &XmpPacket) -> Vec<XmpProperty> {
+    entries
+        .iter()
+        .filter_map(|(key, info)| {
+            let (_, namespace, prefix, name) = INFO_PROPERTIES.into_iter().find(|(k, ..)| k == key)?;
+            Some(XmpProperty {
+                namespace: namespace.to_string(),
+                prefix: prefix.to_string(),
+                name: name.to_string(),
+                value: info_to_xmp(key, info, packet.get(namespace, name))?,
+            })
+        })
+        .collect()
+}
+
+/// Converts an Info value to its XMP form, keeping the other languages of an
+/// existing `rdf:Alt`. Returns `None` for a date that cannot be parsed.
+#[cfg(feature = "std-fs")]
+fn info_to_xmp(key: &str, info: &str, existing: Option<&XmpValue>) -> Option<XmpValue> {
+    Some(match key {
+        "Title" | "Subject" => {
+            let mut alternatives = match existing {
+                Some(XmpValue::Alt(alternatives)) => alternatives.clone(),
+                _ => Vec::new(),
+            };
+            alternatives.retain(|(lang, _)| lang.as_deref() != Some("x-default"));
+            alternatives.insert(0, (Some("x-default".to_string()), info.to_string()));
+            XmpValue::Alt(alternatives)
+        }
+        "Author" => XmpValue::Seq(vec![info.to_string()]),
+        "CreationDate" | "ModDate" => {
+            XmpValue::Text(parse_pdf_date(info).ok()?.to_rfc3339_opts(SecondsFormat::Secs, true))
+        }
+        _ => XmpValue::Text(info.to_string()),
+    })
+}
+
+/// Converts an XMP value to its Info form. Returns `None` for a date that cannot be parsed.
+#[cfg(feature = "std-fs")]
+fn xmp_to_info(key: &str, xmp: &XmpValue) -> Option<String> {
+    match key {
+        "CreationDate" | "ModDate" => parse_xmp_date(&xmp.to_text()).map(|date| format_pdf_date(&date)),
+        _ => Some(xmp.to_text()),
+    }
+}
+
+/// Sets a custom XMP property in place, in the namespace `namespace` written with
+/// the prefix `prefix`.
+///
+/// Archival systems that validate XMP against a schema ignore custom Info keys, so
+/// project-specific values have to live in a namespace of their own. The property is
+/// written as a simple text value, replacing any earlier value of the same property
+/// whatever prefix it used; the rest of the packet is kept, and a packet is created
+/// if the document has none. The Info dictionary, including `ModDate`, is not changed.
+///
+/// # Returns
+///
+/// * `Ok(())`: If the packet was written.
+/// * `Err(Error::Xml)`: If `prefix` or `name` is not a valid XML name, `prefix` is
+///   reserved (`xml...`, `rdf`, `x`), or the existing packet is not well-formed.
+/// * `Err(Error)`: If the file cannot be read or written.
+///
+/// # Example
+///
+/// ```no_run
+/// use pdf_metadata::xmp::{get_xmp_custom, set_xmp_custom};
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let path = "archive/contract.pdf";
+///     set_xmp_custom(path, "http://example.com/ns/", "ex", "projectId", "1234")?;
+///     assert_eq!(get_xmp_custom(path, "http://example.com/ns/", "projectId")?.as_deref(), Some("1234"));
+///     Ok(())
+/// }
+/// ```
+#[cfg(feature = "std-fs")]
+pub fn set_xmp_custom<P: AsRef<Path>>(file_path: P, namespace: &str, prefix: &str, name: &str, value: &str) -> Result<()> {
+    let file_path = file_path.as_ref();
+    for part in [prefix, name] {
+        if !is_xml_name(part) {
+            return Err(Error::Xml(format!("'{}' is not a valid XML name", part)));
+        }
+    }
+    let lower = prefix.to_ascii_lowercase();
+    if lower.starts_with("xml") || prefix == "rdf" || prefix == "x" {
+        return Err(Error::Xml(format!("the prefix '{}' is reserved", prefix)));
+    }
+
+    let options = options::current();
+    write_file(file_path, None, &options, |doc| {
+        let property = XmpProperty {
+            namespace: namespace.to_string(),
+            prefix: prefix.to_string(),
+            name: name.to_string(),
+            value: XmpValue::Text(value.to_string()),
+        };
+        let xml = rewrite_packet(read_packet_xml(doc)?.as_deref(), &[property])?;
+        write_packet(doc, xml)
+    })
+}
+
+/// Returns the value of the XMP property `name` in `namespace`, or `None` if the
+/// document has no such property.
+///
+/// Array values are returned as by the rest of this module: the `x-default`
+/// alternative, or the items joined with `", "`.
+#[cfg(feature = "std-fs")]
+pub fn get_xmp_custom<P: AsRef<Path>>(file_path: P, namespace: &str, name: &str) -> Result<Option<String>> {
+    let file_path = file_path.as_ref();
+    let doc = load_document(file_path)?;
+    Ok(read_packet(&doc)?.and_then(|packet| packet.get(namespace, name).map(XmpValue::to_text)))
+}
+
+/// Returns every language variant of the document title (`dc:title`), as
+/// `(language, title)` pairs in packet order, e.g. `("x-default", "Report")` and
+/// `("pt-BR", "Relatório")`. A title written without language alternatives is
+/// returned as `x-default`; a document without one gives an empty list.
+#[cfg(feature = "std-fs")]
+pub fn get_xmp_title_langs<P: AsRef<Path>>(file_path: P) -> Result<Vec<(String, String)>> {
+    let file_path = file_path.as_ref();
+    get_lang_alternatives(file_path, "Title")
+}
+
+/// Returns every language variant of the document description (`dc:description`),
+/// as [`get_xmp_title_langs`] does for the title.
+#[cfg(feature = "std-fs")]
+pub fn get_xmp_description_langs<P: AsRef<Path>>(file_path: P) -> Result<Vec<(String, String)>> {
+    let file_path = file_path.as_ref();
+    get_lang_alternatives(file_path, "Subject")
+}
+
+/// Sets the document title in language `lang` (an RFC 3066 tag such as `pt-BR`, or
+/// `x-default`) in place, keeping the other languages.
+///
+/// Languages are matched case-insensitively. The `x-default` variant is the one
+/// viewers show when none matches the reader's language and the one the Info
+/// `Title` mirrors: setting it updates `Title` too, and the first language set on a
+/// document becomes its `x-default` as well. `ModDate` is not changed.
+///
+/// # Returns
+///
+/// * `Ok(())`: If the document was written.
+/// * `Err(Error::Xml)`: If `lang` is not a language tag or the existing packet is not
+///   well-formed.
+/// * `Err(Error)`: If the file cannot be read or written.
+///
+/// # Example
+///
+/// ```no_run
+/// use pdf_metadata::xmp::{get_xmp_title_langs, set_xmp_title_lang};
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     set_xmp_title_lang("report.pdf", "x-default", "Annual report")?;
+///     set_xmp_title_lang("report.pdf", "pt-BR", "Relatório anual")?;
+///     for (lang, title) in get_xmp_title_langs("report.pdf")? {
+///         println!("{}: {}", lang, title);
+///     }
+///     Ok(())
+/// }
+/// ```
+#[cfg(feature = "std-fs")]
+pub fn set_xmp_title_lang<P: AsRef<Path>>(file_path: P, lang: &str, title: &str) -> Result<()> {
+    let file_path = file_path.as_ref();
+    set_lang_alternative(file_path, "Title", lang, title)
+}
+
+/// Sets the document description (`dc:description`) in language `lang` in place, as
+/// [`set_xmp_title_lang`] does for the title; the `x-default` variant is mirrored in
+/// the Info `Subject`.
+#[cfg(feature = "std-fs")]
+pub fn set_xmp_description_lang<P: AsRef<Path>>(file_path: P, lang: &str, description: &str) -> Result<()> {
+    let file_path = file_path.as_ref();
+    set_lang_alternative(file_path, "Subject", lang, description)
+}
+
+#[cfg(feature = "std-fs")]
+fn get_lang_alternatives(file_path: &Path, key: &str) -> Result<Vec<(String, String)>> {
+    let (_, namespace, _, name) = info_property(key);
+    let doc = load_document(file_path)?;
+    let alternatives = match read_packet(&doc)?.as_ref().and_then(|packet| packet.get(namespace, name)) {
+        Some(XmpValue::Alt(alternatives)) => alternatives.clone(),
+        Some(other) => vec![(None, other.to_text())],
+        None => Vec::new(),
+    };
+    Ok(alternatives.into_iter().map(|(lang, value)| (lang.unwrap_or_else(|| "x-default".to_string()), value)).collect())
+}
+
+#[cfg(feature = "std-fs")]
+fn set_lang_alternative(file_path: &Path, key: &str, lang: &str, value: &str) -> Result<()> {
+    if !is_language_tag(lang) {
+        return Err(Error::Xml(format!("'{}' is not a language tag", lang)));
+    }
+    let (_, namespace, prefix, name) = info_property(key);
+    let options = options::current();
+    write_file(file_path, None, &options, |doc| {
+        let xml = read_packet_xml(doc)?;
+        let packet = xml.as_deref().map(XmpPacket::parse).transpose()?.unwrap_or_default();
+        let mut alternatives     = match packet.get(namespace, name) {
+            Some(XmpValue::Alt(alternatives)) => alternatives.clone(),
+            Some(other) => vec![(Some("x-default".to_string()), other.to_text())],
+            None => Vec::new(),
+        };
+
+        let is_lang = |candidate: &Option<String>, lang: &str| candidate.as_deref().is_some_and(|c| c.eq_ignore_ascii_case(lang));
+        match alternatives.iter_mut().find(|(candidate, _)| is_lang(candidate, lang)) {
+            Some((_, existing)) => *existing = value.to_string(),
+            None => alternatives.push((Some(lang.to_string()), value.to_string())),
+        }
+        let mut sets_default = is_lang(&Some(lang.to_string()), "x-default");
+        if !alternatives.iter().any(|(candidate, _)| is_lang(candidate, "x-default")) {
+            alternatives.insert(0, (Some("x-default".to_string()), value.to_string()));
+            sets_default = true;
+        }
+
+        let property = XmpProperty {
+            namespace: namespace.to_string(),
+            prefix: prefix.to_string(),
+            name: name.to_string(),
+            value: XmpValue::Alt(alternatives),
+        };
+        write_packet(doc, rewrite_packet(xml.as_deref(), &[property])?)?;
+        if sets_default {
+            apply_entries_with(doc, &[(key, value)], &SetOptions { update_mod_date: false, ..options.clone() })?;
+        }
+        Ok(())
+    })
+}
+
+/// The entry of [`INFO_PROPERTIES`] for the Info `key`, which must be one of them.
+#[cfg(feature = "std-fs")]
+fn info_property(key: &str) -> (&'static str, &'static str, &'static str, &'static str) {
+    INFO_PROPERTIES.into_iter().find(|(k, ..)| *k == key).expect("key has an XMP equivalent")
+}
+
+/// Whether `tag` has the shape of an RFC 3066 language tag: letter subtags of up to
+/// eight characters, then alphanumeric ones, separated by hyphens.
+#[cfg(feature = "std-fs")]
+fn is_language_tag(tag: &str) -> bool {
+    let mut subtags = tag.split('-');
+    let valid = |subtag: &str, letters_only: bool| {
+        (1..=8).contains(&subtag.len())
+            && subtag.chars().all(|c| if letters_only { c.is_ascii_alphabetic() } else { c.is_ascii_alphanumeric() })
+    };
+    subtags.next().is_some_and(|primary| valid(primary, true)) && subtags.all(|subtag| valid(subtag, false))
+}
+
+/// The identifiers of a document in its XMP packet, as returned by [`ensure_xmp_identifiers`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct XmpIdentifiers {
+    /// `xmpMM:DocumentID`, shared by every version of the document.
+    pub document_id: String,
+    /// `xmpMM:InstanceID`, naming this version of the file.
+    pub instance_id: String,
+}
+
+/// Gives the XMP packet an `xmpMM:DocumentID` if it has none and a new
+/// `xmpMM:InstanceID`, writing the result to `output_path`.
+///
+/// Asset managers track a document across copies and edits by these identifiers.
+/// As Adobe applications do, new identifiers are RFC 4122 UUIDs (`xmp.did:...` and
+/// `xmp.iid:...`), an existing `DocumentID` is kept whatever its form, and the
+/// `InstanceID` changes on every call, since each call writes a new version. A
+/// packet is created if the document has none; the Info dictionary is not changed.
+/// `output_path` may be the input file.
+///
+/// # Returns
+///
+/// * `Ok(XmpIdentifiers)`: The identifiers written.
+/// * `Err(Error)`: If the file cannot be read or written, or its XMP packet is not
+///   well-formed XML.
+///
+/// # Example
+///
+/// ```no_run
+/// use pdf_metadata::xmp::ensure_xmp_identifiers;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let ids = ensure_xmp_identifiers("report.pdf", "report.pdf")?;
+///     println!("{} ({})", ids.document_id, ids.instance_id);
+///     Ok(())
+/// }
+/// ```
+#[cfg(feature = "std-fs")]
+pub fn ensure_xmp_identifiers<P: AsRef<Path>, Q: AsRef<Path>>(file_path: P, output_path: Q) -> Result<XmpIdentifiers> {
+    let options = options::current();
+    write_file(file_path.as_ref(), Some(output_path.as_ref()), &options, |doc| {
+        let xml = read_packet_xml(doc)?;
+        let packet = xml.as_deref().map(XmpPacket::parse).transpose()?.unwrap_or_default();
+        let existing = packet
+            .get(XMP_MM_NS, "DocumentID")
+            .map(XmpValue::to_text)
+            .filter(|id| !id.trim().is_empty());
+
+        let ids = XmpIdentifiers {
+            document_id: match existing.clone() {
+                Some(id) => id,
+                None => format!("xmp.did:{}", new_uuid(doc, "xmp.did")?),
+            },
+            instance_id: format!("xmp.iid:{}", new_uuid(doc, "xmp.iid")?),
+        };
+        let mut properties = vec![mm_property("InstanceID", &ids.instance_id)];
+        if existing.is_none() {
+            properties.push(mm_property("DocumentID", &ids.document_id));
+        }
+        write_packet(doc, rewrite_packet(xml.as_deref(), &properties)?)?;
+        Ok(ids)
+    })
+}
+
+/// Whether `text` is an XML name without a colon (an NCName), restricted to the
+/// characters XMP tools accept in practice.
+#[cfg(feature = "std-fs")]
+fn is_xml_name(text: &str) -> bool {
+    let mut chars = text.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{create_minimal_test_pdf, setup_unique_test_dir};
+    use crate::update_entries_in_place_with;
+    use std::fs;
+
+    #[test]
+    fn test_parse_packet_shapes() -> Result<()> {
+        let packet = XmpPacket::parse(
+            r#"<?xpacket begin="" id="W5M0MpCehiHzreSzNTczkc9d"?>
+<x:xmpmeta xmlns:x="adobe:ns:meta/"><rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+  <rdf:Description rdf:about="" xmlns:pdfaid="http://www.aiim.org/pdfa/ns/id/" pdfaid:part="2" pdfaid:conformance="B"/>
+  <rdf:Description rdf:about="" xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:xmp="http://ns.adobe.com/xap/1.0/"
+      xmlns:xmpMM="http://ns.adobe.com/xap/1.0/mm/" xmlns:stEvt="http://ns.adobe.com/xap/1.0/sType/ResourceEvent#">
+    <dc:title><rdf:Alt><rdf:li xml:lang="pt-BR">Relatório</rdf:li><rdf:li xml:lang="x-default">Report &amp; notes</rdf:li></rdf:Alt></dc:title>
+    <dc:creator><rdf:Seq><rdf:li>Ana</rdf:li><rdf:li>Rui</rdf:li></rdf:Seq></dc:creator>
+    <xmp:CreateDate>2024-01-02T03:04:05+01:00</xmp:CreateDate>
+    <xmpMM:History><rdf:Seq><rdf:li rdf:parseType="Resource"><stEvt:action>saved</stEvt:action></rdf:li></rdf:Seq></xmpMM:History>
+  </rdf:Description>
+</rdf:RDF></x:xmpmeta>
+<?xpacket end="w"?>"#,
+        )?;
+
+        assert_eq!(packet.get(PDFAID_NS, "part"), Some(&XmpValue::Text("2".to_string())));
+        assert_eq!(packet.get(PDFAID_NS, "conformance").map(XmpValue::to_text).as_deref(), Some("B"));
+        assert_eq!(packet.get(DC_NS, "title").map(XmpValue::to_text).as_deref(), Some("Report & notes"));
+        assert_eq!(packet.get(DC_NS, "creator"), Some(&XmpValue::Seq(vec!["Ana".to_string(), "Rui".to_string()])));
+        assert_eq!(packet.get(XMP_NS, "CreateDate").map(XmpValue::to_text).as_deref(), Some("2024-01-02T03:04:05+01:00"));
+        assert!(packet.properties.iter().all(|p| p.name != "action" && p.name != "History"));
+        assert!(matches!(XmpPacket::parse("<rdf:RDF><a></b></rdf:RDF>"), Err(Error::Xml(_))));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_xmp_date() {
+        let expected = parse_pdf_date("D:20240102030400Z").ok();
+        assert_eq!(parse_xmp_date("2024-01-02T03:04Z"), expected);
+        assert_eq!(parse_xmp_date("2024-01-02T04:04:00.000+01:00"), expected);
+        assert_eq!(parse_xmp_date("2024-01"), parse_pdf_date("D:202401").ok());
+        assert_eq!(parse_xmp_date("yesterday"), None);
+    }
+
+    #[test]
+    fn test_reconcile_metadata() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("xmp_reconcile");
+        let file = test_dir.join("doc.pdf");
+        create_minimal_test_pdf(&file)?;
+        let file_str = file.to_str().unwrap();
+        let options = SetOptions { update_mod_date: false, ..SetOptions::default() };
+        update_entries_in_place_with(file_str, &[("Title", "Relatório & notas"), ("Author", "Ana")], &options)?;
+
+        // Without a packet, Info is copied into a new one.
+        assert_eq!(reconcile_metadata(file_str, file_str, InfoOrXmp::Xmp)?, ["Title", "Author"]);
+        let doc = Document::load(&file)?;
+        let packet = read_packet(&doc)?.unwrap();
+        assert_eq!(packet.get(DC_NS, "title").map(XmpValue::to_text).as_deref(), Some("Relatório & notas"));
+        assert_eq!(packet.get(DC_NS, "creator"), Some(&XmpValue::Seq(vec!["Ana".to_string()])));
+
+        // Conflicts go to `source`; values on one side only are copied either way.
+        let xml = rewrite_packet(
+            read_packet_xml(&doc)?.as_deref(),
+            &[
+                XmpProperty {
+                    namespace: DC_NS.to_string(),
+                    prefix: "dc".to_string(),
+                    name: "title".to_string(),
+                    value: XmpValue::Alt(vec![
+                        (Some("x-default".to_string()), "Report".to_string()),
+                        (Some("pt-BR".to_string()), "Relatório".to_string()),
+                    ]),
+                },
+                XmpProperty {
+                    namespace: XMP_NS.to_string(),
+                    prefix: "xmp".to_string(),
+                    name: "CreateDate".to_string(),
+                    value: XmpValue::Text("2024-01-02T03:04:05+01:00".to_string()),
+                },
+            ],
+        )?;
+        let mut doc = Document::load(&file)?;
+        write_packet(&mut doc, xml)?;
+        doc.save(&file)?;
+        let output = test_dir.join("out.pdf");
+        let changed = reconcile_metadata(file_str, output.to_str().unwrap(), InfoOrXmp::Info)?;
+        assert_eq!(changed, ["Title", "CreationDate"]);
+
+        let doc = Document::load(&output)?;
+        let packet = read_packet(&doc)?.unwrap();
+        assert_eq!(
+            packet.get(DC_NS, "title"),
+            Some(&XmpValue::Alt(vec![
+                (Some("x-default".to_string()), "Relatório & notas".to_string()),
+                (Some("pt-BR".to_string()), "Relatório".to_string()),
+            ]))
+        );
+        assert_eq!(packet.properties.iter().filter(|p| p.name == "title").count(), 1);
+        assert_eq!(read_packet_xml(&doc)?.unwrap().matches("</rdf:Description>").count(), 3, "emptied descriptions are dropped");
+        let info = read_info_entries(&doc);
+        assert!(info.contains(&("CreationDate".to_string(), "D:20240102030405+01'00'".to_string())));
+        assert!(reconcile_metadata(output.to_str().unwrap(), output.to_str().unwrap(), InfoOrXmp::Xmp)?.is_empty());
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_custom_properties() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("xmp_custom");
+        let file = test_dir.join("doc.pdf");
+        create_minimal_test_pdf(&file)?;
+        let file_str = file.to_str().unwrap();
+        let ns = "http://example.com/ns/";
+
+        assert_eq!(get_xmp_custom(file_str, ns, "projectId")?, None);
+        set_xmp_custom(file_str, ns, "ex", "projectId", "1234")?;
+        set_xmp_custom(file_str, ns, "ex", "owner", "Ana & Rui")?;
+        set_xmp_custom(file_str, ns, "ex2", "projectId", "5678")?;
+
+        assert_eq!(get_xmp_custom(file_str, ns, "projectId")?.as_deref(), Some("5678"));
+        assert_eq!(get_xmp_custom(file_str, ns, "owner")?.as_deref(), Some("Ana & Rui"));
+        let xml = read_packet_xml(&Document::load(&file)?)?.unwrap();
+        assert_eq!(xml.matches("projectId>").count(), 2, "{}", xml);
+        assert!(crate::get_metadata(file_str)?.is_empty());
+
+        assert!(matches!(set_xmp_custom(file_str, ns, "ex", "1st", "x"), Err(Error::Xml(_))));
+        assert!(matches!(set_xmp_custom(file_str, ns, "rdf", "id", "x"), Err(Error::Xml(_))));
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_history_events() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("xmp_history");
+        let file = test_dir.join("doc.pdf");
+        create_minimal_test_pdf(&file)?;
+        let file_str = file.to_str().unwrap();
+        let when = DateTime::parse_from_rfc3339("2024-05-06T07:08:09-03:00")?;
+        let options = SetOptions { xmp_history: true, mod_date: Some(when), ..SetOptions::default() };
+
+        update_entries_in_place_with(file_str, &[("Title", "Relatório")], &options)?;
+        // Skipped writes change nothing and record nothing.
+        let skip = SetOptions { on_conflict: crate::ConflictPolicy::Skip, ..options.clone() };
+        update_entries_in_place_with(file_str, &[("Title", "Outro")], &skip)?;
+        crate::update_metadata_incremental_with_options(file_str, "Author", "Ana", &options)?;
+
+        let xml = read_packet_xml(&Document::load(&file)?)?.unwrap();
+        assert_eq!(xml.matches("<stEvt:action>metadata-edited</stEvt:action>").count(), 2);
+        assert_eq!(xml.matches("<stEvt:when>2024-05-06T07:08:09-03:00</stEvt:when>").count(), 2);
+        assert!(xml.contains(&format!("<stEvt:softwareAgent>{}</stEvt:softwareAgent>", SOFTWARE_AGENT)));
+        // The instance ID names the last event's instance.
+        let packet = XmpPacket::parse(&xml)?;
+        let instance_id = packet.get(XMP_MM_NS, "InstanceID").map(XmpValue::to_text).unwrap();
+        assert!(instance_id.starts_with("xmp.iid:") && instance_id.len() == "xmp.iid:".len() + 36);
+        let last_event = xml.rfind("<stEvt:instanceID>").unwrap();
+        assert!(xml[last_event..].starts_with(&format!("<stEvt:instanceID>{}<", instance_id)));
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_ensure_xmp_identifiers() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("xmp_identifiers");
+        let file = test_dir.join("doc.pdf");
+        create_minimal_test_pdf(&file)?;
+        let file_str = file.to_str().unwrap();
+
+        let first = ensure_xmp_identifiers(file_str, file_str)?;
+        let uuid = first.document_id.strip_prefix("xmp.did:").unwrap();
+        assert_eq!(uuid.len(), 36);
+        assert_eq!(uuid.as_bytes()[14], b'4', "version 4 UUID");
+        assert!(first.instance_id.starts_with("xmp.iid:"));
+
+        // The document ID is kept, the instance ID rotates.
+        let second = ensure_xmp_identifiers(file_str, file_str)?;
+        assert_eq!(second.document_id, first.document_id);
+        assert_ne!(second.instance_id, first.instance_id);
+        let packet = read_packet(&Document::load(&file)?)?.unwrap();
+        assert_eq!(packet.get(XMP_MM_NS, "DocumentID"), Some(&XmpValue::Text(first.document_id)));
+        assert_eq!(packet.get(XMP_MM_NS, "InstanceID"), Some(&XmpValue::Text(second.instance_id)));
+        assert_eq!(packet.properties.iter().filter(|p| p.namespace == XMP_MM_NS).count(), 2);
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_identifiers_are_reproducible_with_source_date_epoch() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("xmp_reproducible_ids");
+        let (a, b) = (test_dir.join("a.pdf"), test_dir.join("b.pdf"));
+        create_minimal_test_pdf(&a)?;
+        fs::copy(&a, &b)?;
+        let history = SetOptions { xmp_history: true, ..SetOptions::default() };
+
+        let (first, second) = crate::test_support::with_source_date_epoch(4_102_444_800, || -> Result<_> {
+            let mut ids = Vec::new();
+            for file in [&a, &b] {
+                ids.push(ensure_xmp_identifiers(file, file)?);
+                update_entries_in_place_with(file, &[("Title", "Report")], &history)?;
+            }
+            Ok((ids.remove(0), ids.remove(0)))
+        })?;
+        assert_eq!(first, second);
+        assert_eq!(first.document_id.as_bytes()["xmp.did:".len() + 14], b'8', "version 8 UUID");
+        assert_ne!(first.document_id["xmp.did:".len()..], first.instance_id["xmp.iid:".len()..]);
+        assert_eq!(fs::read(&a)?, fs::read(&b)?);
+        let instance_id = read_packet(&Document::load(&a)?)?.unwrap().get(XMP_MM_NS, "InstanceID").cloned();
+        assert_ne!(instance_id, Some(XmpValue::Text(first.instance_id)), "the history event names a new instance");
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_language_alternatives() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_dir = setup_unique_test_dir("xmp_lang");
+        let file = test_dir.join("doc.pdf");
+        create_minimal_test_pdf(&file)?;
+        let file_str = file.to_str().unwrap();
+
+        // The first language also becomes the default, mirrored in Info.
+        set_xmp_title_lang(file_str, "pt-BR", "Relatório")?;
+        set_xmp_title_lang(file_str, "en", "Report")?;
+        set_xmp_title_lang(file_str, "PT-br", "Relatório anual")?;
+        let pair = |lang: &str, value: &str| (lang.to_string(), value.to_string());
+        assert_eq!(
+            get_xmp_title_langs(file_str)?,
+            [pair("x-default", "Relatório"), pair("pt-BR", "Relatório anual"), pair("en", "Report")]
+        );
+        set_xmp_title_lang(file_str, "x-default", "Annual report")?;
+        let info = read_info_entries(&Document::load(&file)?);
+        assert!(info.contains(&pair("Title", "Annual report")));
+        assert!(!info.iter().any(|(key, _)| key == "ModDate"));
+
+        set_xmp_description_lang(file_str, "de", "Jahresbericht")?;
+        assert_eq!(get_xmp_description_langs(file_str)?, [pair("x-default", "Jahresbericht"), pair("de", "Jahresbericht")]);
+        assert!(matches!(set_xmp_title_lang(file_str, "pt_BR", "x"), Err(Error::Xml(_))));
+
+        fs::remove_dir_all(test_dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,154 @@
+//! Runs the `pdf_metadata` executable the way scripts do: arguments, pipes and exit codes.
+
+#![cfg(feature = "cli")]
+
+use lopdf::{Dictionary, Document, Object};
+use std::error::Error;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Creates an empty directory for one test.
+fn setup_unique_test_dir(test_name: &str) -> PathBuf {
+    let micros = SystemTime::now().duration_since(UNIX_EPOCH).expect("Time went backwards").as_micros();
+    let dir = std::env::temp_dir().join("pdf_metadata_tests").join(format!("cli_{}_{}", test_name, micros));
+    fs::create_dir_all(&dir).expect("Failed to create temp test directory");
+    dir
+}
+
+// Writes a PDF with an empty page tree and an Info dictionary holding `Title`.
+fn create_test_pdf(path: &Path) -> Result<(), Box<dyn Error>> {
+    let mut doc = Document::with_version("1.7");
+    let mut pages = Dictionary::new();
+    pages.set("Type", Object::Name(b"Pages".to_vec()));
+    pages.set("Count", Object::Integer(0));
+    pages.set("Kids", Object::Array(vec![]));
+    let pages_id = doc.add_object(pages);
+    let mut catalog = Dictionary::new();
+    catalog.set("Type", Object::Name(b"Catalog".to_vec()));
+    catalog.set("Pages", Object::Reference(pages_id));
+    let catalog_id = doc.add_object(catalog);
+    let mut info = Dictionary::new();
+    info.set("Title", Object::string_literal("Report"));
+    let info_id = doc.add_object(info);
+    doc.trailer.set("Root", Object::Reference(catalog_id));
+    doc.trailer.set("Info", Object::Reference(info_id));
+    doc.save(path)?;
+    Ok(())
+}
+
+// Runs the tool with `args`, feeding `stdin` to it, with English messages.
+fn run(args: &[&str], stdin: &[u8]) -> Result<Output, Box<dyn Error>> {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_pdf_metadata"))
+        .args(args)
+        .env("PDF_METADATA_LANG", "en")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let mut input = child.stdin.take().expect("stdin is piped");
+    let stdin = stdin.to_vec();
+    // Commands that do not read stdin close it early; that is not an error here.
+    let feeder = thread::spawn(move || {
+        let _ = input.write_all(&stdin);
+    });
+    let output = child.wait_with_output()?;
+    let _ = feeder.join();
+    Ok(output)
+}
+
+// The `--errors-json` line a failed command wrote, without the message.
+fn json_error(output: &Output) -> String {
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let line = stderr.lines().next().unwrap_or_default();
+    assert!(line.starts_with('{') && line.ends_with('}') && line.contains(",\"message\":\""), "{}", line);
+    line[..line.find(",\"message\"").unwrap()].to_string() + "}"
+}
+
+#[test]
+fn test_exit_codes_and_errors_json() -> Result<(), Box<dyn Error>> {
+    let test_dir = setup_unique_test_dir("exit_codes");
+    let file = test_dir.join("doc.pdf");
+    create_test_pdf(&file)?;
+    let file = file.to_str().unwrap();
+    let missing = test_dir.join("missing.pdf");
+    let missing = missing.to_str().unwrap();
+    let garbage = test_dir.join("garbage.pdf");
+    fs::write(&garbage, b"not a pdf")?;
+    let garbage = garbage.to_str().unwrap();
+    let encrypted = test_dir.join("encrypted.pdf");
+    let encrypted = encrypted.to_str().unwrap();
+    assert!(run(&["encrypt", file, encrypted, "--user-password", "secret"], b"")?.status.success());
+    let unwritable = test_dir.join("no-such-dir").join("out.pdf");
+    let unwritable = unwritable.to_str().unwrap();
+
+    let output = run(&["get", file, "Title"], b"")?;
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(output.stdout, b"Report\n");
+
+    let cases: [(&[&str], i32, &str); 6] = [
+        (&["get", missing], 3, "file_not_found"),
+        (&["get", garbage], 4, "parse_error"),
+        (&["get", encrypted, "Title"], 5, "encrypted"),
+        (&["get", file, "Subject"], 6, "key_not_found"),
+        (&["set", "-", "Title", "New", "--output", unwritable], 7, "write_failed"),
+        (&["pdfa", file], 1, "failure"),
+    ];
+    let pdf = fs::read(file)?;
+    for (args, code, name) in cases {
+        let output = run(args, &pdf)?;
+        assert_eq!(output.status.code(), Some(code), "{:?}", args);
+        assert!(String::from_utf8_lossy(&output.stderr).starts_with("Error: "), "{:?}", args);
+
+        // The global flags work before and after the subcommand.
+        for flags in [[&["--errors-json"], args].concat(), [args, &["--errors-json"]].concat()] {
+            let output = run(&flags, &pdf)?;
+            assert_eq!(output.status.code(), Some(code), "{:?}", flags);
+            assert_eq!(json_error(&output), format!("{{\"error\":\"{}\",\"code\":{}}}", name, code));
+        }
+        let output = run(&[&["--quiet"], args].concat(), &pdf)?;
+        assert_eq!(output.status.code(), Some(code), "{:?}", args);
+        assert!(output.stderr.is_empty(), "{:?}", args);
+    }
+    // Usage errors keep clap's code.
+    assert_eq!(run(&["--quiet", "--errors-json", "get", missing], b"")?.status.code(), Some(2));
+
+    fs::remove_dir_all(test_dir)?;
+    Ok(())
+}
+
+#[test]
+fn test_stdin_and_stdout_pipes() -> Result<(), Box<dyn Error>> {
+    let test_dir = setup_unique_test_dir("pipes");
+    let file = test_dir.join("doc.pdf");
+    create_test_pdf(&file)?;
+    let original = fs::read(&file)?;
+    let file = file.to_str().unwrap();
+
+    let output = run(&["get", "-", "Title"], &original)?;
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"Report\n");
+
+    // `--output -` writes the PDF to stdout and leaves the file alone.
+    let output = run(&["set", file, "Title", "Piped", "--output", "-"], b"")?;
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(output.stdout.starts_with(b"%PDF-"));
+    assert_eq!(fs::read(file)?, original);
+    let piped = output.stdout;
+    assert_eq!(run(&["get", "-", "Title"], &piped)?.stdout, b"Piped\n");
+
+    // Reading from stdin, the output goes to stdout unless --output names a file.
+    let output = run(&["set", "-", "Author", "Ana"], &piped)?;
+    assert!(output.status.success());
+    let listed = String::from_utf8(run(&["get", "-"], &output.stdout)?.stdout)?;
+    assert!(listed.contains("Title: Piped") && listed.contains("Author: Ana"), "{}", listed);
+    let copy = test_dir.join("copy.pdf");
+    assert!(run(&["set", "-", "Author", "Ana", "--output", copy.to_str().unwrap()], &piped)?.stdout.is_empty());
+    assert_eq!(run(&["get", copy.to_str().unwrap(), "Author"], b"")?.stdout, b"Ana\n");
+
+    fs::remove_dir_all(test_dir)?;
+    Ok(())
+}